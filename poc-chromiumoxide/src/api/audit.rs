@@ -0,0 +1,328 @@
+// Audit trail
+// Every API request is recorded with the principal that made it, and LLM usage is
+// totalled per principal and per tenant so multi-user deployments can see who did
+// what and who spent what.
+
+use axum::{
+    extract::{Json, Query, State},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::RwLock;
+use utoipa::IntoParams;
+
+use super::auth::{current_principal, Principal};
+use super::tenants::current_tenant;
+use super::{ApiResponse, AppState};
+
+/// Requests kept in the audit trail before the oldest are dropped
+const MAX_ENTRIES: usize = 1000;
+
+/// Usage key of requests made without authentication
+const ANONYMOUS: &str = "anonymous";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Subject of the authenticated principal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub duration_ms: u64,
+    /// Correlation id the request ran under
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+/// Totals of one principal or tenant
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PrincipalUsage {
+    pub requests: u64,
+    pub llm_requests: u64,
+    pub llm_tokens: u64,
+    pub llm_cost_usd: f64,
+}
+
+#[derive(Default)]
+pub struct AuditLog {
+    entries: RwLock<VecDeque<AuditEntry>>,
+    usage: RwLock<HashMap<String, PrincipalUsage>>,
+    tenant_usage: RwLock<HashMap<String, PrincipalUsage>>,
+    /// Tenant each principal last acted for
+    principal_tenants: RwLock<HashMap<String, String>>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn record_request(&self, entry: AuditEntry) {
+        let key = entry.principal.clone().unwrap_or_else(|| ANONYMOUS.into());
+        self.usage
+            .write()
+            .await
+            .entry(key.clone())
+            .or_default()
+            .requests += 1;
+        if let Some(tenant) = &entry.tenant {
+            self.tenant_usage
+                .write()
+                .await
+                .entry(tenant.clone())
+                .or_default()
+                .requests += 1;
+            self.principal_tenants
+                .write()
+                .await
+                .insert(key, tenant.clone());
+        }
+
+        let mut entries = self.entries.write().await;
+        entries.push_back(entry);
+        while entries.len() > MAX_ENTRIES {
+            entries.pop_front();
+        }
+    }
+
+    /// Add an LLM call's tokens and cost to the current principal's and tenant's totals
+    pub async fn record_llm_usage(&self, tokens: u64, cost_usd: f64) {
+        let key = current_principal()
+            .map(|principal| principal.subject)
+            .unwrap_or_else(|| ANONYMOUS.into());
+        let add = |totals: &mut PrincipalUsage| {
+            totals.llm_requests += 1;
+            totals.llm_tokens += tokens;
+            totals.llm_cost_usd += cost_usd;
+        };
+        add(self.usage.write().await.entry(key).or_default());
+        if let Some(tenant) = current_tenant() {
+            add(self.tenant_usage.write().await.entry(tenant).or_default());
+        }
+    }
+
+    /// Most recent entries first, optionally only those of one tenant and principal
+    pub async fn entries(
+        &self,
+        tenant: Option<&str>,
+        principal: Option<&str>,
+        limit: usize,
+    ) -> Vec<AuditEntry> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|entry| tenant.is_none() || entry.tenant.as_deref() == tenant)
+            .filter(|entry| principal.is_none() || entry.principal.as_deref() == principal)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Requests made under a correlation id, oldest first
+    pub async fn for_correlation(&self, correlation_id: &str) -> Vec<AuditEntry> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .filter(|entry| entry.correlation_id.as_deref() == Some(correlation_id))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn usage(&self) -> HashMap<String, PrincipalUsage> {
+        self.usage.read().await.clone()
+    }
+
+    /// Totals keyed by tenant
+    pub async fn tenant_usage(&self) -> HashMap<String, PrincipalUsage> {
+        self.tenant_usage.read().await.clone()
+    }
+
+    /// Principals that acted for `tenant`
+    async fn principals_of(&self, tenant: &str) -> Vec<String> {
+        self.principal_tenants
+            .read()
+            .await
+            .iter()
+            .filter(|(_, t)| t.as_str() == tenant)
+            .map(|(principal, _)| principal.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub principal: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_limit() -> usize {
+    100
+}
+
+/// Principal whose records a caller may read: anyone's for admins and
+/// unauthenticated deployments, otherwise only their own
+fn visible_principal(requested: Option<String>, caller: Option<&Principal>) -> Option<String> {
+    match caller {
+        Some(caller) if !caller.is_admin() => Some(caller.subject.clone()),
+        _ => requested,
+    }
+}
+
+/// Recent requests with the principal that made them; non-admins see only their own
+/// and admins of a tenant only their tenant's
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    tag = "audit",
+    params(AuditQuery),
+    responses((status = 200, description = "Audit entries, newest first", body = ApiEnvelope))
+)]
+pub async fn list_audit_entries(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    let caller = current_principal();
+    let principal = visible_principal(query.principal, caller.as_ref());
+    let tenant = caller.and_then(|caller| caller.tenant);
+    let entries = state
+        .audit
+        .entries(
+            tenant.as_deref(),
+            principal.as_deref(),
+            query.limit.min(MAX_ENTRIES),
+        )
+        .await;
+    Json(ApiResponse::success(entries)).into_response()
+}
+
+/// Request and LLM totals per principal; non-admins see only their own and admins
+/// of a tenant only their tenant's principals
+#[utoipa::path(
+    get,
+    path = "/api/audit/usage",
+    tag = "audit",
+    responses((status = 200, description = "Usage keyed by principal", body = ApiEnvelope))
+)]
+pub async fn usage_by_principal(State(state): State<AppState>) -> Response {
+    let caller = current_principal();
+    let mut usage = state.audit.usage().await;
+    if let Some(subject) = visible_principal(None, caller.as_ref()) {
+        usage.retain(|key, _| *key == subject);
+    } else if let Some(tenant) = caller.and_then(|caller| caller.tenant) {
+        let principals = state.audit.principals_of(&tenant).await;
+        usage.retain(|key, _| principals.contains(key));
+    }
+    Json(ApiResponse::success(usage)).into_response()
+}
+
+/// Request and LLM totals per tenant; callers acting for a tenant see only theirs
+#[utoipa::path(
+    get,
+    path = "/api/audit/tenants",
+    tag = "audit",
+    responses((status = 200, description = "Usage keyed by tenant", body = ApiEnvelope))
+)]
+pub async fn usage_by_tenant(State(state): State<AppState>) -> Response {
+    let mut usage = state.audit.tenant_usage().await;
+    if let Some(tenant) = current_tenant() {
+        usage.retain(|key, _| *key == tenant);
+    }
+    Json(ApiResponse::success(usage)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(principal: Option<&str>, path: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            principal: principal.map(str::to_string),
+            tenant: None,
+            method: "POST".to_string(),
+            path: path.to_string(),
+            status: 200,
+            duration_ms: 3,
+            correlation_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn filters_entries_and_totals_usage() {
+        let log = AuditLog::new();
+        log.record_request(entry(Some("alice"), "/api/navigate"))
+            .await;
+        log.record_request(entry(None, "/api/tools")).await;
+        log.record_request(entry(Some("alice"), "/api/llm/query"))
+            .await;
+        log.record_llm_usage(120, 0.01).await;
+
+        let alice = log.entries(None, Some("alice"), 10).await;
+        assert_eq!(alice.len(), 2);
+        assert_eq!(alice[0].path, "/api/llm/query");
+        assert_eq!(log.entries(None, None, 1).await.len(), 1);
+
+        let usage = log.usage().await;
+        assert_eq!(usage["alice"].requests, 2);
+        assert_eq!(usage[ANONYMOUS].requests, 1);
+        assert_eq!(usage[ANONYMOUS].llm_tokens, 120);
+    }
+
+    #[tokio::test]
+    async fn partitions_entries_and_usage_by_tenant() {
+        let log = AuditLog::new();
+        let mut acme = entry(Some("acme"), "/api/tools/execute");
+        acme.tenant = Some("acme".to_string());
+        log.record_request(acme).await;
+        log.record_request(entry(Some("alice"), "/api/navigate"))
+            .await;
+        let acme_principal = Principal {
+            subject: "acme".to_string(),
+            tenant: Some("acme".to_string()),
+            ..Principal::default()
+        };
+        super::super::auth::with_principal(Some(acme_principal), log.record_llm_usage(80, 0.02))
+            .await;
+
+        let entries = log.entries(Some("acme"), None, 10).await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "/api/tools/execute");
+        assert_eq!(log.entries(None, None, 10).await.len(), 2);
+
+        let usage = log.tenant_usage().await;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage["acme"].requests, 1);
+        assert_eq!(usage["acme"].llm_tokens, 80);
+        assert_eq!(log.principals_of("acme").await, vec!["acme".to_string()]);
+    }
+
+    #[test]
+    fn non_admins_only_see_themselves() {
+        let mut bob = Principal {
+            subject: "bob".to_string(),
+            ..Principal::default()
+        };
+        assert_eq!(
+            visible_principal(Some("alice".into()), Some(&bob)),
+            Some("bob".to_string())
+        );
+        bob.scopes.push(super::super::auth::ADMIN_SCOPE.to_string());
+        assert_eq!(
+            visible_principal(Some("alice".into()), Some(&bob)),
+            Some("alice".to_string())
+        );
+        assert_eq!(visible_principal(None, None), None);
+    }
+}
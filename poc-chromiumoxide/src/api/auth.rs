@@ -0,0 +1,813 @@
+// Authentication for multi-user deployments
+//
+// Requests carry a JWT as `Authorization: Bearer <token>`, in the `rainbow_token`
+// cookie set by the dashboard's OAuth2 login, or as `?access_token=` for clients
+// that cannot set headers (EventSource, WebSocket). Tokens are checked against the
+// configured issuer's JWKS or a shared HS256 secret. Tenants' services may instead
+// send an `X-API-Key` from `RAINBOW_API_KEYS`. Authentication is off unless one of
+// them is configured.
+
+use axum::{
+    extract::{Json, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+use utoipa::IntoParams;
+
+use super::audit::AuditEntry;
+use super::tenants::{valid_tenant_id, API_KEY_HEADER};
+use super::{ApiResponse, AppState, ErrorCode};
+use crate::coordination::correlation::current_correlation_id;
+
+/// Cookie holding the dashboard's token after an OAuth2 login
+pub const TOKEN_COOKIE: &str = "rainbow_token";
+
+/// Scope letting a principal read everyone's audit trail and usage
+pub const ADMIN_SCOPE: &str = "rainbow:admin";
+
+/// How long fetched signing keys are trusted before being fetched again
+const JWKS_TTL: Duration = Duration::from_secs(3600);
+/// Minimum time between fetches triggered by a token signed with an unknown key
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// How long a started OAuth2 login may take to come back
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+tokio::task_local! {
+    /// Principal of the request the current task serves
+    static PRINCIPAL: Option<Principal>;
+}
+
+/// Authenticated caller, taken from a validated token's claims
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Principal {
+    pub subject: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    pub scopes: Vec<String>,
+    /// Tenant whose sessions and learning the principal works with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl Principal {
+    pub fn is_admin(&self) -> bool {
+        self.scopes.iter().any(|scope| scope == ADMIN_SCOPE)
+    }
+}
+
+/// Principal of the request being served, if it was authenticated
+pub fn current_principal() -> Option<Principal> {
+    PRINCIPAL
+        .try_with(|principal| principal.clone())
+        .ok()
+        .flatten()
+}
+
+/// Run `future` on behalf of `principal`, e.g. work spawned off a request, within
+/// its tenant's sessions
+pub async fn with_principal<F: Future>(principal: Option<Principal>, future: F) -> F::Output {
+    let tenant = principal
+        .as_ref()
+        .and_then(|principal| principal.tenant.clone());
+    PRINCIPAL
+        .scope(
+            principal,
+            crate::browser::session::with_tenant(tenant, future),
+        )
+        .await
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    iss: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    preferred_username: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    /// Space-separated scopes (RFC 8693)
+    #[serde(default)]
+    scope: Option<String>,
+    /// Scopes as a list or a space-separated string, as some providers send them
+    #[serde(default)]
+    scp: Option<serde_json::Value>,
+    #[serde(default)]
+    tenant: Option<String>,
+}
+
+impl Claims {
+    fn into_principal(self) -> Principal {
+        let mut scopes: Vec<String> = self
+            .scope
+            .iter()
+            .flat_map(|scope| scope.split_whitespace())
+            .map(str::to_string)
+            .collect();
+        match self.scp {
+            Some(serde_json::Value::String(scp)) => {
+                scopes.extend(scp.split_whitespace().map(str::to_string))
+            }
+            Some(serde_json::Value::Array(scp)) => scopes.extend(
+                scp.iter()
+                    .filter_map(|scope| scope.as_str())
+                    .map(str::to_string),
+            ),
+            _ => {}
+        }
+        Principal {
+            subject: self.sub,
+            issuer: self.iss,
+            name: self.name.or(self.preferred_username),
+            email: self.email,
+            scopes,
+            tenant: self.tenant,
+        }
+    }
+}
+
+/// Token validation settings
+#[derive(Debug, Clone, Default)]
+pub struct JwtConfig {
+    /// Expected `iss`; its `/.well-known/jwks.json` is the default JWKS location
+    pub issuer: Option<String>,
+    /// Expected `aud`, unchecked when unset
+    pub audience: Option<String>,
+    pub jwks_url: Option<String>,
+    /// Shared secret for HS256 tokens
+    pub secret: Option<String>,
+}
+
+impl JwtConfig {
+    /// `RAINBOW_JWT_ISSUER`, `RAINBOW_JWT_AUDIENCE`, `RAINBOW_JWT_JWKS_URL` and
+    /// `RAINBOW_JWT_SECRET`; `None` when neither keys nor a secret are configured
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let issuer = var("RAINBOW_JWT_ISSUER");
+        let jwks_url = var("RAINBOW_JWT_JWKS_URL").or_else(|| {
+            issuer
+                .as_ref()
+                .map(|iss| format!("{}/.well-known/jwks.json", iss.trim_end_matches('/')))
+        });
+        let config = Self {
+            issuer,
+            audience: var("RAINBOW_JWT_AUDIENCE"),
+            jwks_url,
+            secret: var("RAINBOW_JWT_SECRET"),
+        };
+        if config.jwks_url.is_none() && config.secret.is_none() {
+            return None;
+        }
+        Some(config)
+    }
+}
+
+/// Authorization-code login for the dashboard
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    pub authorize_url: String,
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    /// This server's `/auth/callback`, as registered with the provider
+    pub redirect_url: String,
+    pub scopes: String,
+}
+
+impl OAuthConfig {
+    /// `RAINBOW_OAUTH_AUTHORIZE_URL`, `RAINBOW_OAUTH_TOKEN_URL`, `RAINBOW_OAUTH_CLIENT_ID`,
+    /// `RAINBOW_OAUTH_REDIRECT_URL`, optional `RAINBOW_OAUTH_CLIENT_SECRET` and
+    /// `RAINBOW_OAUTH_SCOPES` (default `openid profile email`)
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Some(Self {
+            authorize_url: var("RAINBOW_OAUTH_AUTHORIZE_URL")?,
+            token_url: var("RAINBOW_OAUTH_TOKEN_URL")?,
+            client_id: var("RAINBOW_OAUTH_CLIENT_ID")?,
+            client_secret: var("RAINBOW_OAUTH_CLIENT_SECRET"),
+            redirect_url: var("RAINBOW_OAUTH_REDIRECT_URL")?,
+            scopes: var("RAINBOW_OAUTH_SCOPES")
+                .unwrap_or_else(|| "openid profile email".to_string()),
+        })
+    }
+}
+
+struct CachedJwks {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Validates tokens and runs the OAuth2 login
+pub struct Authenticator {
+    config: JwtConfig,
+    oauth: Option<OAuthConfig>,
+    client: reqwest::Client,
+    jwks: RwLock<Option<CachedJwks>>,
+    /// `state` values of logins in progress, to reject forged callbacks
+    pending_logins: Mutex<HashMap<String, Instant>>,
+}
+
+impl Authenticator {
+    pub fn new(config: JwtConfig, oauth: Option<OAuthConfig>) -> Self {
+        Self {
+            config,
+            oauth,
+            client: reqwest::Client::new(),
+            jwks: RwLock::new(None),
+            pending_logins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let config = JwtConfig::from_env()?;
+        let oauth = OAuthConfig::from_env();
+        info!(
+            "JWT authentication enabled (issuer: {:?}, JWKS: {:?}, OAuth2 login: {})",
+            config.issuer,
+            config.jwks_url,
+            oauth.is_some()
+        );
+        Some(Self::new(config, oauth))
+    }
+
+    /// Check a token's signature, expiry, issuer and audience
+    pub async fn validate(&self, token: &str) -> anyhow::Result<Principal> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let key = match (&self.config.secret, header.alg) {
+            (Some(secret), Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) => {
+                DecodingKey::from_secret(secret.as_bytes())
+            }
+            _ => self.jwks_key(header.kid.as_deref()).await?,
+        };
+
+        let mut validation = Validation::new(header.alg);
+        if let Some(issuer) = &self.config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = jsonwebtoken::decode::<Claims>(token, &key, &validation)?.claims;
+        if let Some(tenant) = claims.tenant.as_deref().filter(|t| !valid_tenant_id(t)) {
+            anyhow::bail!("Invalid tenant claim {:?}", tenant);
+        }
+        Ok(claims.into_principal())
+    }
+
+    /// Key the token names, fetching the JWKS when it is stale or lacks the key
+    async fn jwks_key(&self, kid: Option<&str>) -> anyhow::Result<DecodingKey> {
+        let Some(url) = &self.config.jwks_url else {
+            anyhow::bail!("Token is not signed with the shared secret");
+        };
+
+        let refresh = match self.jwks.read().await.as_ref() {
+            None => true,
+            Some(cached) => {
+                let age = cached.fetched_at.elapsed();
+                age > JWKS_TTL
+                    || (find_key(&cached.keys, kid).is_none() && age > JWKS_REFRESH_INTERVAL)
+            }
+        };
+        if refresh {
+            let keys: JwkSet = self
+                .client
+                .get(url)
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            *self.jwks.write().await = Some(CachedJwks {
+                keys,
+                fetched_at: Instant::now(),
+            });
+        }
+
+        let cached = self.jwks.read().await;
+        let jwk = cached
+            .as_ref()
+            .and_then(|cached| find_key(&cached.keys, kid))
+            .ok_or_else(|| anyhow::anyhow!("No signing key matches kid {:?}", kid))?;
+        Ok(DecodingKey::from_jwk(jwk)?)
+    }
+}
+
+fn find_key<'a>(keys: &'a JwkSet, kid: Option<&str>) -> Option<&'a jsonwebtoken::jwk::Jwk> {
+    match kid {
+        Some(kid) => keys.find(kid),
+        // Without a kid only an unambiguous key set can be used
+        None if keys.keys.len() == 1 => keys.keys.first(),
+        None => None,
+    }
+}
+
+/// Paths served without a token: the dashboard shell, health checks, API docs and login
+fn is_public(path: &str) -> bool {
+    matches!(
+        path,
+        "/" | "/health" | "/api/health" | "/api/auth/me" | "/api/openapi.json" | "/api/docs"
+    ) || path.starts_with("/static/")
+        || path.starts_with("/auth/")
+}
+
+fn bearer_token(headers: &HeaderMap, query: Option<&str>) -> Option<String> {
+    if let Some(token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        return Some(token.trim().to_string());
+    }
+    if let Some(token) = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            cookie
+                .trim()
+                .strip_prefix(TOKEN_COOKIE)
+                .and_then(|value| value.strip_prefix('='))
+        })
+    {
+        return Some(token.to_string());
+    }
+    query?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("access_token="))
+        .map(str::to_string)
+}
+
+/// API calls, as opposed to the dashboard, its assets and health checks; these are
+/// audited and rate limited
+pub(super) fn is_api_request(path: &str) -> bool {
+    (path.starts_with("/api/") && path != "/api/health") || path == "/ws"
+}
+
+/// Principal of a request: the tenant of its API key or the subject of its token.
+/// `Ok(None)` when authentication is off.
+async fn resolve_principal(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: Option<&str>,
+) -> Result<Option<Principal>, String> {
+    if state.api_keys.is_enabled() {
+        if let Some(key) = headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            return match state.api_keys.principal(key.trim()) {
+                Some(principal) => Ok(Some(principal)),
+                None => Err("Unknown API key".to_string()),
+            };
+        }
+    }
+    match (&state.auth, bearer_token(headers, query)) {
+        (Some(auth), Some(token)) => auth
+            .validate(&token)
+            .await
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        (Some(_), None) => Err("Missing bearer token".to_string()),
+        (None, _) if state.api_keys.is_enabled() => Err("Missing API key".to_string()),
+        (None, _) => Ok(None),
+    }
+}
+
+/// Authenticate requests when JWT validation or API keys are configured, run them on
+/// behalf of their principal and record them in the audit trail
+pub(super) async fn authenticate(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+
+    let principal = match resolve_principal(&state, request.headers(), request.uri().query()).await
+    {
+        Ok(principal) => principal,
+        Err(_) if is_public(&path) => None,
+        Err(e) => {
+            warn!("Rejected {} {}: {}", method, path, e);
+            let mut response = (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::Unauthorized,
+                    format!("Unauthorized: {}", e),
+                )),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            if is_api_request(&path) {
+                state
+                    .audit
+                    .record_request(AuditEntry {
+                        timestamp: chrono::Utc::now(),
+                        principal: None,
+                        tenant: None,
+                        method,
+                        path,
+                        status: StatusCode::UNAUTHORIZED.as_u16(),
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        correlation_id: current_correlation_id(),
+                    })
+                    .await;
+            }
+            return response;
+        }
+    };
+
+    let subject = principal.as_ref().map(|p| p.subject.clone());
+    let tenant = principal.as_ref().and_then(|p| p.tenant.clone());
+    let response = with_principal(principal, next.run(request)).await;
+    if is_api_request(&path) {
+        state
+            .audit
+            .record_request(AuditEntry {
+                timestamp: chrono::Utc::now(),
+                principal: subject,
+                tenant,
+                method,
+                path,
+                status: response.status().as_u16(),
+                duration_ms: started.elapsed().as_millis() as u64,
+                correlation_id: current_correlation_id(),
+            })
+            .await;
+    }
+    response
+}
+
+/// Who the caller is, and where to sign in when they are not authenticated
+#[utoipa::path(
+    get,
+    path = "/api/auth/me",
+    tag = "auth",
+    responses((status = 200, description = "The caller", body = ApiEnvelope))
+)]
+pub async fn me(State(state): State<AppState>) -> Response {
+    let principal = current_principal();
+    let login_url = state
+        .auth
+        .as_ref()
+        .filter(|auth| auth.oauth.is_some() && principal.is_none())
+        .map(|_| "/auth/login");
+    Json(ApiResponse::success(serde_json::json!({
+        "auth_required": state.auth.is_some() || state.api_keys.is_enabled(),
+        "authenticated": principal.is_some(),
+        "principal": principal,
+        "login_url": login_url,
+    })))
+    .into_response()
+}
+
+fn login_unavailable() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ApiResponse::<()>::error(
+            ErrorCode::NotFound,
+            "OAuth2 login is not configured".to_string(),
+        )),
+    )
+        .into_response()
+}
+
+/// Redirect the browser to the provider's authorization page
+#[utoipa::path(
+    get,
+    path = "/auth/login",
+    tag = "auth",
+    responses((status = 303, description = "Redirect to the identity provider"))
+)]
+pub async fn login(State(state): State<AppState>) -> Response {
+    let Some((auth, oauth)) = state
+        .auth
+        .as_ref()
+        .and_then(|auth| auth.oauth.as_ref().map(|oauth| (auth, oauth)))
+    else {
+        return login_unavailable();
+    };
+
+    let login_state = uuid::Uuid::new_v4().to_string();
+    {
+        let mut pending = auth.pending_logins.lock().await;
+        pending.retain(|_, started| started.elapsed() < LOGIN_TIMEOUT);
+        pending.insert(login_state.clone(), Instant::now());
+    }
+    match reqwest::Url::parse_with_params(
+        &oauth.authorize_url,
+        &[
+            ("response_type", "code"),
+            ("client_id", oauth.client_id.as_str()),
+            ("redirect_uri", oauth.redirect_url.as_str()),
+            ("scope", oauth.scopes.as_str()),
+            ("state", login_state.as_str()),
+        ],
+    ) {
+        Ok(url) => Redirect::to(url.as_str()).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::Internal,
+                format!("Invalid authorize URL: {}", e),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CallbackQuery {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    id_token: Option<String>,
+}
+
+/// Exchange the authorization code for tokens and keep the validated one in a cookie
+#[utoipa::path(
+    get,
+    path = "/auth/callback",
+    tag = "auth",
+    params(CallbackQuery),
+    responses(
+        (status = 303, description = "Redirect to the dashboard with the token cookie set"),
+    )
+)]
+pub async fn callback(
+    State(state): State<AppState>,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    let Some((auth, oauth)) = state
+        .auth
+        .as_ref()
+        .and_then(|auth| auth.oauth.as_ref().map(|oauth| (auth, oauth)))
+    else {
+        return login_unavailable();
+    };
+    let unauthorized = |message: String| {
+        warn!("OAuth2 login failed: {}", message);
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::<()>::error(ErrorCode::Unauthorized, message)),
+        )
+            .into_response()
+    };
+
+    if let Some(error) = query.error {
+        return unauthorized(format!("Provider refused the login: {}", error));
+    }
+    let started = match &query.state {
+        Some(login_state) => auth.pending_logins.lock().await.remove(login_state),
+        None => None,
+    };
+    if !matches!(started, Some(started) if started.elapsed() < LOGIN_TIMEOUT) {
+        return unauthorized("Unknown or expired login state".to_string());
+    }
+    let Some(code) = query.code else {
+        return unauthorized("Missing authorization code".to_string());
+    };
+
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", oauth.redirect_url.as_str()),
+        ("client_id", oauth.client_id.as_str()),
+    ];
+    if let Some(secret) = &oauth.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let tokens: TokenResponse = match auth
+        .client
+        .post(&oauth.token_url)
+        .form(&form)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(response) => match response.json().await {
+            Ok(tokens) => tokens,
+            Err(e) => return unauthorized(format!("Invalid token response: {}", e)),
+        },
+        Err(e) => return unauthorized(format!("Token exchange failed: {}", e)),
+    };
+
+    // The access token is what API requests will carry; fall back to the ID token
+    // for providers whose access tokens are opaque
+    let mut validated = None;
+    for token in std::iter::once(tokens.access_token).chain(tokens.id_token) {
+        match auth.validate(&token).await {
+            Ok(principal) => {
+                validated = Some((token, principal));
+                break;
+            }
+            Err(e) => warn!("Token from OAuth2 login did not validate: {}", e),
+        }
+    }
+    let Some((token, principal)) = validated else {
+        return unauthorized("No token from the provider could be validated".to_string());
+    };
+    info!("Dashboard login for {}", principal.subject);
+
+    let secure = if oauth.redirect_url.starts_with("https://") {
+        "; Secure"
+    } else {
+        ""
+    };
+    let cookie = format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Lax{}",
+        TOKEN_COOKIE, token, secure
+    );
+    let mut response = Redirect::to("/").into_response();
+    if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+        response.headers_mut().insert(header::SET_COOKIE, cookie);
+    }
+    response
+}
+
+/// Forget the dashboard's token
+#[utoipa::path(
+    get,
+    path = "/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 303, description = "Redirect to the dashboard with the token cookie removed"),
+    )
+)]
+pub async fn logout() -> Response {
+    let mut response = Redirect::to("/").into_response();
+    let cookie = format!(
+        "{}=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0",
+        TOKEN_COOKIE
+    );
+    if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+        response.headers_mut().insert(header::SET_COOKIE, cookie);
+    }
+    response
+}
+
+/// Start authentication from the environment, if configured
+pub(super) fn start_auth() -> Option<Arc<Authenticator>> {
+    Authenticator::from_env().map(Arc::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &str = "test-secret";
+
+    fn token(claims: serde_json::Value, secret: &str, kid: Option<&str>) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = kid.map(str::to_string);
+        encode(
+            &header,
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn expiry() -> i64 {
+        chrono::Utc::now().timestamp() + 600
+    }
+
+    fn authenticator(config: JwtConfig) -> Authenticator {
+        Authenticator::new(config, None)
+    }
+
+    #[tokio::test]
+    async fn validates_shared_secret_tokens() {
+        let auth = authenticator(JwtConfig {
+            issuer: Some("https://id.example.com".into()),
+            audience: Some("rainbow".into()),
+            secret: Some(SECRET.into()),
+            ..JwtConfig::default()
+        });
+        let claims = json!({
+            "sub": "alice",
+            "iss": "https://id.example.com",
+            "aud": "rainbow",
+            "exp": expiry(),
+            "preferred_username": "Alice",
+            "scope": "read rainbow:admin",
+        });
+
+        let principal = auth
+            .validate(&token(claims.clone(), SECRET, None))
+            .await
+            .unwrap();
+        assert_eq!(principal.subject, "alice");
+        assert_eq!(principal.name.as_deref(), Some("Alice"));
+        assert!(principal.is_admin());
+
+        assert!(auth
+            .validate(&token(claims.clone(), "other", None))
+            .await
+            .is_err());
+        let mut wrong_issuer = claims.clone();
+        wrong_issuer["iss"] = json!("https://evil.example.com");
+        assert!(auth
+            .validate(&token(wrong_issuer, SECRET, None))
+            .await
+            .is_err());
+        let mut expired = claims;
+        expired["exp"] = json!(chrono::Utc::now().timestamp() - 3600);
+        assert!(auth.validate(&token(expired, SECRET, None)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn validates_tokens_against_the_jwks() {
+        use base64::Engine;
+        let k = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(SECRET);
+        let jwks = json!({"keys": [{"kty": "oct", "kid": "k1", "alg": "HS256", "k": k}]});
+        let app = axum::Router::new().route(
+            "/jwks.json",
+            axum::routing::get(move || {
+                let jwks = jwks.clone();
+                async move { Json(jwks) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let auth = authenticator(JwtConfig {
+            jwks_url: Some(format!("http://{}/jwks.json", addr)),
+            ..JwtConfig::default()
+        });
+        let claims = json!({"sub": "bob", "exp": expiry(), "scp": ["read"]});
+        let principal = auth
+            .validate(&token(claims.clone(), SECRET, Some("k1")))
+            .await
+            .unwrap();
+        assert_eq!(principal.subject, "bob");
+        assert_eq!(principal.scopes, vec!["read".to_string()]);
+        assert!(auth
+            .validate(&token(claims, SECRET, Some("k2")))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn reads_tokens_from_header_cookie_and_query() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            bearer_token(&headers, Some("access_token=q")),
+            Some("q".into())
+        );
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; rainbow_token=c"),
+        );
+        assert_eq!(bearer_token(&headers, None), Some("c".into()));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer h"));
+        assert_eq!(bearer_token(&headers, None), Some("h".into()));
+        assert_eq!(bearer_token(&HeaderMap::new(), None), None);
+    }
+
+    #[tokio::test]
+    async fn principal_is_scoped_to_the_request() {
+        assert_eq!(current_principal(), None);
+        let principal = Principal {
+            subject: "carol".to_string(),
+            ..Principal::default()
+        };
+        let seen = with_principal(Some(principal.clone()), async { current_principal() }).await;
+        assert_eq!(seen, Some(principal));
+    }
+}
@@ -0,0 +1,259 @@
+// Batch calls
+// Chatty clients send an ordered list of navigate, tool and perceive calls to
+// `/api/batch` and get every result back in one round-trip. Calls run one after
+// another against the same session, through the endpoints they mirror.
+
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{debug, info};
+use utoipa::ToSchema;
+
+use super::operations::response_json;
+use super::perception_handlers::{self, PerceptionModeRequest};
+use super::{ApiResponse, AppState, ErrorCode, ExecuteToolRequest, NavigateRequest};
+
+/// Most calls one batch may carry
+const MAX_BATCH_CALLS: usize = 100;
+
+/// One call of a batch, tagged by `type`
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchCall {
+    /// As `/api/navigate`
+    Navigate {
+        url: String,
+        #[serde(default)]
+        device: Option<String>,
+    },
+    /// As `/api/tools/execute`
+    Tool {
+        tool_name: String,
+        #[serde(default)]
+        #[schema(value_type = Object)]
+        parameters: serde_json::Value,
+    },
+    /// As `/api/perceive-mode`
+    Perceive {
+        #[serde(default = "default_perception_mode")]
+        mode: String,
+        #[serde(default)]
+        url: Option<String>,
+    },
+}
+
+fn default_perception_mode() -> String {
+    "lightning".to_string()
+}
+
+impl BatchCall {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Navigate { .. } => "navigate",
+            Self::Tool { .. } => "tool",
+            Self::Perceive { .. } => "perceive",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchRequest {
+    /// Session every call runs against; the caller's own browser when left out
+    #[serde(default)]
+    pub session_id: Option<String>,
+    pub calls: Vec<BatchCall>,
+    /// Skip the remaining calls once one fails
+    #[serde(default)]
+    pub stop_on_error: bool,
+}
+
+/// Outcome of one call, with the HTTP status and body of the matching endpoint
+#[derive(Debug, Serialize)]
+struct BatchResult {
+    index: usize,
+    #[serde(rename = "type")]
+    call: &'static str,
+    status: u16,
+    success: bool,
+    body: serde_json::Value,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    succeeded: usize,
+    failed: usize,
+    /// Calls left unrun after a failure with `stop_on_error`
+    skipped: usize,
+    results: Vec<BatchResult>,
+    duration_ms: u64,
+}
+
+/// Run a call through the endpoint it mirrors
+async fn run_call(
+    state: &AppState,
+    headers: &HeaderMap,
+    session_id: Option<String>,
+    call: BatchCall,
+) -> Response {
+    match call {
+        BatchCall::Navigate { url, device } => {
+            let req = NavigateRequest {
+                url,
+                session_id,
+                device,
+            };
+            super::navigate(State(state.clone()), headers.clone(), Json(req)).await
+        }
+        BatchCall::Tool {
+            tool_name,
+            parameters,
+        } => {
+            let req = ExecuteToolRequest {
+                tool_name,
+                parameters,
+                session_id,
+            };
+            super::execute_tool(State(state.clone()), Json(req)).await
+        }
+        BatchCall::Perceive { mode, url } => {
+            let req = PerceptionModeRequest {
+                mode,
+                session_id,
+                url,
+                load_lazy_content: false,
+            };
+            perception_handlers::perceive_with_mode(
+                State(state.clone()),
+                headers.clone(),
+                Json(req),
+            )
+            .await
+            .into_response()
+        }
+    }
+}
+
+/// Run an ordered list of navigate, tool and perceive calls against one session
+#[utoipa::path(
+    post,
+    path = "/api/batch",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = BatchRequest,
+    responses(
+        (status = 200, description = "Per-call results, in order", body = ApiEnvelope),
+        (status = 400, description = "Empty or oversized batch", body = ApiEnvelope),
+    )
+)]
+pub async fn execute_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<BatchRequest>,
+) -> Response {
+    if req.calls.is_empty() || req.calls.len() > MAX_BATCH_CALLS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                format!(
+                    "A batch needs between 1 and {} calls, got {}",
+                    MAX_BATCH_CALLS,
+                    req.calls.len()
+                ),
+            )),
+        )
+            .into_response();
+    }
+
+    let started = Instant::now();
+    let total = req.calls.len();
+    info!(
+        "Running batch of {} calls (session: {:?})",
+        total, req.session_id
+    );
+    let mut results = Vec::with_capacity(total);
+    for (index, call) in req.calls.into_iter().enumerate() {
+        let name = call.name();
+        let call_started = Instant::now();
+        let response = run_call(&state, &headers, req.session_id.clone(), call).await;
+        let status = response.status();
+        let body = response_json(response).await.unwrap_or_else(|e| {
+            serde_json::json!(ApiResponse::<()>::error(ErrorCode::Internal, e))
+        });
+        debug!("Batch call {} ({}) finished with {}", index, name, status);
+        results.push(BatchResult {
+            index,
+            call: name,
+            status: status.as_u16(),
+            success: status.is_success() && body_succeeded(&body),
+            body,
+            duration_ms: call_started.elapsed().as_millis() as u64,
+        });
+        if req.stop_on_error && results.last().is_some_and(|result| !result.success) {
+            break;
+        }
+    }
+
+    let succeeded = results.iter().filter(|result| result.success).count();
+    let response = BatchResponse {
+        session_id: req.session_id,
+        succeeded,
+        failed: results.len() - succeeded,
+        skipped: total - results.len(),
+        results,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    Json(ApiResponse::success(response)).into_response()
+}
+
+/// Endpoints report some failures with a 200 and `"success": false`
+fn body_succeeded(body: &serde_json::Value) -> bool {
+    body.get("success")
+        .and_then(|success| success.as_bool())
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_calls_by_type() {
+        let req: BatchRequest = serde_json::from_value(json!({
+            "session_id": "s1",
+            "stop_on_error": true,
+            "calls": [
+                {"type": "navigate", "url": "https://example.com"},
+                {"type": "tool", "tool_name": "click", "parameters": {"selector": "#buy"}},
+                {"type": "perceive"}
+            ]
+        }))
+        .unwrap();
+        let names: Vec<_> = req.calls.iter().map(BatchCall::name).collect();
+        assert_eq!(names, vec!["navigate", "tool", "perceive"]);
+        assert!(matches!(
+            &req.calls[2],
+            BatchCall::Perceive { mode, url: None } if mode == "lightning"
+        ));
+        assert!(serde_json::from_value::<BatchCall>(json!({"type": "tool"})).is_err());
+    }
+
+    #[test]
+    fn failures_reported_in_the_body_count() {
+        assert!(body_succeeded(&json!({"success": true, "data": {}})));
+        assert!(!body_succeeded(
+            &json!({"success": false, "error": "no element"})
+        ));
+        assert!(body_succeeded(&json!("plain text")));
+    }
+}
@@ -0,0 +1,2034 @@
+// Browser-level API handlers (tabs and other per-browser state)
+
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use super::pagination::PageQuery;
+use super::{client_token, ApiError, ApiResponse, AppState, ErrorCode};
+use crate::browser::{
+    ActionScript, ActivityKind, Browser, KeyChord, RecordedAction, RecordedWorkflow, ReplayStep,
+    SessionActivity, SessionOptions,
+};
+use crate::tools::registry::ToolRegistry;
+
+/// Resolve the browser for a request.
+///
+/// Uses the session's browser when a session_id is given, otherwise the browser
+/// bound to the caller's `X-Client-Token` (the same one `/api/navigate` and
+/// `/api/click` use), acquiring one from the pool on first use.
+pub(super) async fn resolve_browser(
+    state: &AppState,
+    headers: &HeaderMap,
+    session_id: Option<&str>,
+) -> Result<Arc<Browser>, Response> {
+    if let Some(session_id) = session_id {
+        return session_browser(state, session_id).await;
+    }
+
+    match state.affinity.browser(&client_token(headers)).await {
+        Ok(browser) => Ok(browser),
+        Err(e) => {
+            error!("Failed to acquire browser: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Browser of an existing session; 404 when there is no such session
+pub(super) async fn session_browser(
+    state: &AppState,
+    session_id: &str,
+) -> Result<Arc<Browser>, Response> {
+    match state.session_manager.get_session(session_id).await {
+        Some(session) => Ok(session.read().await.browser.clone()),
+        None => {
+            let error = ApiError::new(
+                ErrorCode::SessionNotFound,
+                format!("Session not found: {}", session_id),
+            )
+            .with_details(serde_json::json!({ "session_id": session_id }));
+            Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::failure(error)),
+            )
+                .into_response())
+        }
+    }
+}
+
+fn internal_error(context: &str, e: anyhow::Error) -> Response {
+    error!("{}: {}", context, e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiResponse::<()>::error(
+            ErrorCode::classify(&e, ErrorCode::BrowserError),
+            e.to_string(),
+        )),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SessionQuery {
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct OpenTabRequest {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default = "default_activate")]
+    pub activate: bool,
+}
+
+fn default_activate() -> bool {
+    true
+}
+
+/// Open tabs of a session
+#[utoipa::path(
+    get,
+    path = "/api/tabs",
+    tag = "tabs",
+    params(SessionQuery),
+    responses((status = 200, description = "Tabs", body = ApiEnvelope))
+)]
+pub async fn list_tabs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.list_tabs().await {
+        Ok(tabs) => Json(ApiResponse::success(tabs)).into_response(),
+        Err(e) => internal_error("List tabs failed", e),
+    }
+}
+
+/// Open a tab, optionally at a URL
+#[utoipa::path(
+    post,
+    path = "/api/tabs",
+    tag = "tabs",
+    request_body = OpenTabRequest,
+    responses((status = 200, description = "The new tab", body = ApiEnvelope))
+)]
+pub async fn open_tab(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<OpenTabRequest>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.open_tab(req.url.as_deref(), req.activate).await {
+        Ok(tab) => Json(ApiResponse::success(tab)).into_response(),
+        Err(e) => internal_error("Open tab failed", e),
+    }
+}
+
+/// Make a tab the one later actions run in
+#[utoipa::path(
+    post,
+    path = "/api/tabs/{id}/activate",
+    tag = "tabs",
+    params(
+        ("id" = String, Path, description = "Tab id"),
+        SessionQuery,
+    ),
+    responses((status = 200, description = "Tab is active", body = ApiEnvelope))
+)]
+pub async fn switch_tab(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tab_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.switch_tab(&tab_id).await {
+        Ok(tab) => Json(ApiResponse::success(tab)).into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::NotFound),
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Close a tab
+#[utoipa::path(
+    delete,
+    path = "/api/tabs/{id}",
+    tag = "tabs",
+    params(
+        ("id" = String, Path, description = "Tab id"),
+        SessionQuery,
+    ),
+    responses((status = 200, description = "Tab was closed", body = ApiEnvelope))
+)]
+pub async fn close_tab(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tab_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.close_tab(&tab_id).await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "closed": true,
+            "tab_id": tab_id
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct WaitDownloadRequest {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default = "default_download_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_download_timeout_ms() -> u64 {
+    30_000
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DownloadDirRequest {
+    pub directory: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Downloads a session has started
+#[utoipa::path(
+    get,
+    path = "/api/downloads",
+    tag = "downloads",
+    params(SessionQuery),
+    responses((status = 200, description = "Downloads", body = ApiEnvelope))
+)]
+pub async fn list_downloads(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let downloads = browser.downloads();
+    Json(ApiResponse::success(serde_json::json!({
+        "directory": downloads.dir().await,
+        "downloads": downloads.list().await,
+    })))
+    .into_response()
+}
+
+/// Progress and state of one download
+#[utoipa::path(
+    get,
+    path = "/api/downloads/{id}",
+    tag = "downloads",
+    params(
+        ("id" = String, Path, description = "Download id"),
+        SessionQuery,
+    ),
+    responses((status = 200, description = "The download", body = ApiEnvelope))
+)]
+pub async fn get_download(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.downloads().get(&id).await {
+        Some(download) => Json(ApiResponse::success(serde_json::json!({
+            "download": download,
+            "progress": download.progress(),
+        })))
+        .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("Download not found: {}", id),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Contents of a completed download
+#[utoipa::path(
+    get,
+    path = "/api/downloads/{id}/file",
+    tag = "downloads",
+    params(
+        ("id" = String, Path, description = "Download id"),
+        SessionQuery,
+    ),
+    responses(
+        (status = 200, description = "The downloaded file", content_type = "application/octet-stream", body = String),
+    )
+)]
+pub async fn get_download_file(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let path = match browser.downloads().get(&id).await {
+        Some(download) if download.state == crate::browser::DownloadState::Completed => {
+            download.path
+        }
+        Some(_) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::Conflict,
+                    format!("Download not completed: {}", id),
+                )),
+            )
+                .into_response()
+        }
+        None => None,
+    };
+    let Some(path) = path else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("Download not found: {}", id),
+            )),
+        )
+            .into_response();
+    };
+
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => {
+            let filename = path
+                .file_name()
+                .map(|n| n.to_string_lossy().replace('"', ""))
+                .unwrap_or_else(|| "download".to_string());
+            (
+                [
+                    (
+                        axum::http::header::CONTENT_TYPE,
+                        "application/octet-stream".to_string(),
+                    ),
+                    (
+                        axum::http::header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}\"", filename),
+                    ),
+                ],
+                bytes,
+            )
+                .into_response()
+        }
+        Err(e) => internal_error("Read download failed", e.into()),
+    }
+}
+
+/// Wait until a session's next or given download completes
+#[utoipa::path(
+    post,
+    path = "/api/downloads/wait",
+    tag = "downloads",
+    request_body = WaitDownloadRequest,
+    responses((status = 200, description = "The completed download", body = ApiEnvelope))
+)]
+pub async fn wait_for_download(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<WaitDownloadRequest>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let timeout = std::time::Duration::from_millis(req.timeout_ms);
+    match browser
+        .downloads()
+        .wait_for(req.id.as_deref(), timeout)
+        .await
+    {
+        Ok(download) => Json(ApiResponse::success(download)).into_response(),
+        Err(e) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(ApiResponse::<()>::error(ErrorCode::Timeout, e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+/// Change the directory a session downloads into, relative to the download root
+#[utoipa::path(
+    post,
+    path = "/api/downloads/config",
+    tag = "downloads",
+    request_body = DownloadDirRequest,
+    responses(
+        (status = 200, description = "Download directory was set", body = ApiEnvelope),
+        (status = 400, description = "Directory is outside the download root", body = ApiEnvelope)
+    )
+)]
+pub async fn set_download_dir(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<DownloadDirRequest>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let requested = std::path::Path::new(&req.directory);
+    if let Err(e) = browser.downloads().resolve_dir(requested) {
+        let error = ApiError::new(ErrorCode::InvalidRequest, e.to_string())
+            .with_details(serde_json::json!({ "root": browser.downloads().root() }));
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::failure(error)),
+        )
+            .into_response();
+    }
+    match browser.set_download_dir(requested).await {
+        Ok(directory) => Json(ApiResponse::success(serde_json::json!({
+            "directory": directory
+        })))
+        .into_response(),
+        Err(e) => internal_error("Set download directory failed", e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetCookiesRequest {
+    #[schema(value_type = Vec<Object>)]
+    pub cookies: Vec<chromiumoxide::cdp::browser_protocol::network::CookieParam>,
+    #[serde(default)]
+    pub replace: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RestoreSessionRequest {
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub snapshot: Option<crate::browser::SessionSnapshot>,
+    #[serde(default)]
+    pub from_session_id: Option<String>,
+}
+
+/// Cookies of a session
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/cookies",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Cookies", body = ApiEnvelope))
+)]
+pub async fn get_session_cookies(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.get_all_cookies().await {
+        Ok(cookies) => Json(ApiResponse::success(cookies)).into_response(),
+        Err(e) => internal_error("Get cookies failed", e),
+    }
+}
+
+/// Set cookies in a session
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/cookies",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    request_body = SetCookiesRequest,
+    responses((status = 200, description = "Number of cookies set", body = ApiEnvelope))
+)]
+pub async fn set_session_cookies(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetCookiesRequest>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    if req.replace {
+        if let Err(e) = browser.clear_cookies().await {
+            return internal_error("Clear cookies failed", e);
+        }
+    }
+    let count = req.cookies.len();
+    match browser.set_cookies(req.cookies).await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": id,
+            "cookies_set": count,
+            "replaced": req.replace
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Clear all cookies of a session
+#[utoipa::path(
+    delete,
+    path = "/api/session/{id}/cookies",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Cookies were cleared", body = ApiEnvelope))
+)]
+pub async fn clear_session_cookies(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.clear_cookies().await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": id,
+            "cleared": true
+        })))
+        .into_response(),
+        Err(e) => internal_error("Clear cookies failed", e),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StorageQuery {
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub area: Option<crate::browser::StorageArea>,
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetStorageRequest {
+    #[schema(value_type = String)]
+    pub area: crate::browser::StorageArea,
+    pub items: std::collections::BTreeMap<String, String>,
+    /// Clear the area before setting the items
+    #[serde(default)]
+    pub replace: bool,
+}
+
+/// localStorage and sessionStorage of a session's current origin
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/storage",
+    tag = "sessions",
+    params(
+        ("id" = String, Path, description = "Session id"),
+        StorageQuery,
+    ),
+    responses((status = 200, description = "Storage items", body = ApiEnvelope))
+)]
+pub async fn get_session_storage(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<StorageQuery>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let result = match query.area {
+        Some(area) => browser
+            .get_storage(area)
+            .await
+            .map(|items| serde_json::json!({ "area": area, "items": items })),
+        None => browser
+            .export_storage()
+            .await
+            .and_then(|snapshot| Ok(serde_json::to_value(snapshot)?)),
+    };
+    match result {
+        Ok(data) => Json(ApiResponse::success(data)).into_response(),
+        Err(e) => internal_error("Get storage failed", e),
+    }
+}
+
+/// Set items in a session's localStorage or sessionStorage
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/storage",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    request_body = SetStorageRequest,
+    responses((status = 200, description = "Items were set", body = ApiEnvelope))
+)]
+pub async fn set_session_storage(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetStorageRequest>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser
+        .set_storage_items(req.area, &req.items, req.replace)
+        .await
+    {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": id,
+            "area": req.area,
+            "items_set": req.items.len(),
+            "replaced": req.replace
+        })))
+        .into_response(),
+        Err(e) => internal_error("Set storage failed", e),
+    }
+}
+
+/// Remove one key, or clear a storage area of a session
+#[utoipa::path(
+    delete,
+    path = "/api/session/{id}/storage",
+    tag = "sessions",
+    params(
+        ("id" = String, Path, description = "Session id"),
+        StorageQuery,
+    ),
+    responses((status = 200, description = "Storage was cleared", body = ApiEnvelope))
+)]
+pub async fn clear_session_storage(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<StorageQuery>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let areas = match query.area {
+        Some(area) => vec![area],
+        None => vec![
+            crate::browser::StorageArea::Local,
+            crate::browser::StorageArea::Session,
+        ],
+    };
+    for area in &areas {
+        if let Err(e) = browser.remove_storage(*area, query.key.as_deref()).await {
+            return internal_error("Clear storage failed", e);
+        }
+    }
+    Json(ApiResponse::success(serde_json::json!({
+        "session_id": id,
+        "areas": areas,
+        "key": query.key
+    })))
+    .into_response()
+}
+
+/// Restore cookies and storage exported from another session
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/storage/import",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    request_body(content = Object, description = "Cookies and storage exported from a session"),
+    responses((status = 200, description = "Snapshot was imported", body = ApiEnvelope))
+)]
+pub async fn import_session_storage(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(snapshot): Json<crate::browser::StorageSnapshot>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.import_storage(&snapshot).await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": id,
+            "origin": snapshot.origin,
+            "local_items": snapshot.local.len(),
+            "session_items": snapshot.session.len()
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Network throttling given as a preset name or custom conditions
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum NetworkThrottling {
+    Preset(String),
+    Custom(crate::browser::NetworkConditions),
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ThrottlingRequest {
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub network: Option<NetworkThrottling>,
+    #[serde(default)]
+    pub cpu_slowdown: Option<f64>,
+}
+
+/// Network and CPU throttling applied to a session
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/throttling",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Current throttling", body = ApiEnvelope))
+)]
+pub async fn get_session_throttling(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    Json(ApiResponse::success(browser.throttling().await)).into_response()
+}
+
+/// Throttle a session's network and CPU
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/throttling",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    request_body = ThrottlingRequest,
+    responses((status = 200, description = "Throttling was applied", body = ApiEnvelope))
+)]
+pub async fn set_session_throttling(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<ThrottlingRequest>,
+) -> Response {
+    let network = match req.network {
+        Some(NetworkThrottling::Preset(name)) => {
+            match crate::browser::NetworkConditions::preset(&name) {
+                Some(conditions) => Some(conditions),
+                None => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::InvalidRequest,
+                            format!(
+                            "Unknown network preset: {} (available: slow-3g, fast-3g, 4g, offline)",
+                            name
+                        ),
+                        )),
+                    )
+                        .into_response()
+                }
+            }
+        }
+        Some(NetworkThrottling::Custom(conditions)) => Some(conditions),
+        None => None,
+    };
+
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    if let Some(conditions) = network {
+        if let Err(e) = browser.set_network_throttling(Some(conditions)).await {
+            return internal_error("Set network throttling failed", e);
+        }
+    }
+    if let Some(rate) = req.cpu_slowdown {
+        if let Err(e) = browser.set_cpu_throttling(rate).await {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+    Json(ApiResponse::success(browser.throttling().await)).into_response()
+}
+
+/// Remove a session's throttling
+#[utoipa::path(
+    delete,
+    path = "/api/session/{id}/throttling",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Throttling was removed", body = ApiEnvelope))
+)]
+pub async fn clear_session_throttling(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.clear_throttling().await {
+        Ok(_) => Json(ApiResponse::success(browser.throttling().await)).into_response(),
+        Err(e) => internal_error("Clear throttling failed", e),
+    }
+}
+
+/// Browser performance metrics of a session
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/metrics",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Session metrics", body = ApiEnvelope))
+)]
+pub async fn get_session_metrics(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.get_metrics().await {
+        Ok(metrics) => Json(ApiResponse::success(metrics)).into_response(),
+        Err(e) => internal_error("Get metrics failed", e),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConsoleQuery {
+    /// Only entries of this level ("log", "info", "debug", "warning", "error")
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Return only the newest N entries
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Console messages and uncaught errors of a session's pages
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/console",
+    tag = "sessions",
+    params(
+        ("id" = String, Path, description = "Session id"),
+        ConsoleQuery,
+    ),
+    responses((status = 200, description = "Console entries", body = ApiEnvelope))
+)]
+pub async fn get_session_console(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ConsoleQuery>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let entries = browser
+        .console()
+        .entries(query.level.as_deref(), query.limit)
+        .await;
+    Json(ApiResponse::success(entries)).into_response()
+}
+
+/// Forget a session's console messages
+#[utoipa::path(
+    delete,
+    path = "/api/session/{id}/console",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Console was cleared", body = ApiEnvelope))
+)]
+pub async fn clear_session_console(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    browser.console().clear().await;
+    Json(ApiResponse::success(serde_json::json!({
+        "session_id": id,
+        "cleared": true
+    })))
+    .into_response()
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DomChangesQuery {
+    /// Return only the newest N change batches
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Recent DOM changes of a session's pages, with the counters perception caches key on
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/dom-changes",
+    tag = "sessions",
+    params(
+        ("id" = String, Path, description = "Session id"),
+        DomChangesQuery,
+    ),
+    responses((status = 200, description = "DOM change batches", body = ApiEnvelope))
+)]
+pub async fn get_session_dom_changes(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<DomChangesQuery>,
+) -> Response {
+    let browser = match session_browser(&state, &id).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let mutations = browser.dom_mutations();
+    Json(ApiResponse::success(serde_json::json!({
+        "session_id": id,
+        "observing": mutations.is_active(),
+        "version": mutations.version(),
+        "structure_version": mutations.structure_version(),
+        "quiet_ms": mutations.quiet_for().await.as_millis() as u64,
+        "changes": mutations.recent(query.limit).await
+    })))
+    .into_response()
+}
+
+/// Health of the pooled browsers
+#[utoipa::path(
+    get,
+    path = "/api/pool/health",
+    tag = "browser",
+    responses((status = 200, description = "Pool health", body = ApiEnvelope))
+)]
+pub async fn get_pool_health(State(state): State<AppState>) -> Response {
+    Json(ApiResponse::success(serde_json::json!({
+        "idle_browsers": state.browser_pool.size().await,
+        "bound_clients": state.affinity.client_count().await,
+        "last_check": state.browser_pool.last_health().await
+    })))
+    .into_response()
+}
+
+/// Probe idle browsers now instead of waiting for the next scheduled check
+#[utoipa::path(
+    post,
+    path = "/api/pool/health",
+    tag = "browser",
+    responses((status = 200, description = "Pool health after the check", body = ApiEnvelope))
+)]
+pub async fn check_pool_health(State(state): State<AppState>) -> Response {
+    Json(ApiResponse::success(
+        state.browser_pool.check_health().await,
+    ))
+    .into_response()
+}
+
+/// Web Vitals of the current page, plus the recorded navigations when a session is given
+#[utoipa::path(
+    get,
+    path = "/api/performance",
+    tag = "browser",
+    params(SessionQuery),
+    responses((status = 200, description = "Performance report", body = ApiEnvelope))
+)]
+pub async fn get_performance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let current = match browser.collect_web_vitals().await {
+        Ok(vitals) => vitals,
+        Err(e) => return internal_error("Collect Web Vitals failed", e),
+    };
+    let rating = current.overall_rating();
+
+    let history = match &query.session_id {
+        Some(id) => match state.session_manager.get_session(id).await {
+            Some(session) => session.read().await.performance.clone(),
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+
+    Json(ApiResponse::success(serde_json::json!({
+        "current": current,
+        "rating": rating,
+        "history": history
+    })))
+    .into_response()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetTagsRequest {
+    pub tags: HashMap<String, String>,
+}
+
+/// Add or overwrite tags of a session
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/tags",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    request_body = SetTagsRequest,
+    responses((status = 200, description = "The session's tags", body = ApiEnvelope))
+)]
+pub async fn set_session_tags(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetTagsRequest>,
+) -> Response {
+    match state.session_manager.set_tags(&id, req.tags).await {
+        Ok(tags) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": id,
+            "tags": tags
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::NotFound),
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Remove one tag of a session
+#[utoipa::path(
+    delete,
+    path = "/api/session/{id}/tags/{key}",
+    tag = "sessions",
+    params(
+        ("id" = String, Path, description = "Session id"),
+        ("key" = String, Path, description = "Tag key"),
+    ),
+    responses(
+        (status = 200, description = "The session's remaining tags", body = ApiEnvelope),
+    )
+)]
+pub async fn remove_session_tag(
+    State(state): State<AppState>,
+    Path((id, key)): Path<(String, String)>,
+) -> Response {
+    match state.session_manager.remove_tag(&id, &key).await {
+        Ok(tags) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": id,
+            "tags": tags
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::NotFound),
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Stream a session's navigations, clicks, tool executions and perceptions as
+/// JSON text messages until the client disconnects or the session ends
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/events",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 101, description = "Switching to the WebSocket protocol"))
+)]
+pub async fn session_events(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if state.session_manager.get_session(&id).await.is_none() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::SessionNotFound,
+                format!("Session not found: {}", id),
+            )),
+        )
+            .into_response();
+    }
+    let activity = state.session_manager.subscribe_activity();
+    ws.on_upgrade(move |socket| stream_session_events(socket, state, id, activity))
+}
+
+async fn stream_session_events(
+    mut socket: WebSocket,
+    state: AppState,
+    session_id: String,
+    mut activity: broadcast::Receiver<SessionActivity>,
+) {
+    let mut liveness = tokio::time::interval(std::time::Duration::from_secs(5));
+    loop {
+        tokio::select! {
+            event = activity.recv() => match event {
+                Ok(event) if event.session_id == session_id => {
+                    let Ok(text) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Event stream of session {} skipped {} events",
+                        session_id, skipped
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            _ = liveness.tick() => {
+                if state.session_manager.get_session(&session_id).await.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// Replayable script of the actions a session performed
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/recording",
+    tag = "recording",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Recorded actions", body = ApiEnvelope))
+)]
+pub async fn get_session_recording(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    match state.session_manager.recording(&id).await {
+        Some(script) => Json(ApiResponse::success(script)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::SessionNotFound,
+                format!("Session not found: {}", id),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Start a fresh recording of the session's actions, dropping earlier ones
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/recording/start",
+    tag = "recording",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Recording started", body = ApiEnvelope))
+)]
+pub async fn start_session_recording(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    set_session_recording(&state, &id, true).await
+}
+
+/// Stop recording the session's actions, keeping what was recorded
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/recording/stop",
+    tag = "recording",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Recording stopped", body = ApiEnvelope))
+)]
+pub async fn stop_session_recording(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    set_session_recording(&state, &id, false).await
+}
+
+async fn set_session_recording(state: &AppState, id: &str, recording: bool) -> Response {
+    match state.session_manager.set_recording(id, recording).await {
+        Ok(()) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": id,
+            "recording": recording
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::NotFound),
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecordingWorkflowQuery {
+    pub name: Option<String>,
+}
+
+/// The session's recording as an editable workflow YAML document
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/recording/workflow",
+    tag = "recording",
+    params(
+        ("id" = String, Path, description = "Session id"),
+        RecordingWorkflowQuery,
+    ),
+    responses(
+        (status = 200, description = "Workflow YAML", content_type = "application/yaml", body = String),
+    )
+)]
+pub async fn get_recording_workflow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<RecordingWorkflowQuery>,
+) -> Response {
+    let Some(script) = state.session_manager.recording(&id).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::SessionNotFound,
+                format!("Session not found: {}", id),
+            )),
+        )
+            .into_response();
+    };
+    let name = query
+        .name
+        .unwrap_or_else(|| format!("Recorded session {}", id));
+    match script.to_workflow(&name).to_yaml() {
+        Ok(yaml) => (
+            [(axum::http::header::CONTENT_TYPE, "application/yaml")],
+            yaml,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::BrowserError),
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, Default, ToSchema)]
+pub struct ReplayRequest {
+    /// Script to run instead of the session's own recording, e.g. one exported earlier
+    #[serde(default)]
+    #[schema(value_type = Option<Vec<Object>>)]
+    pub actions: Option<Vec<RecordedAction>>,
+    /// Workflow YAML, as generated from a recording and possibly edited, to run instead
+    #[serde(default)]
+    pub workflow: Option<String>,
+    /// Values for the workflow's inputs, overriding the recorded ones
+    #[serde(default)]
+    pub inputs: HashMap<String, String>,
+    /// Keep going after a failed action (default: stop at the first failure)
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+/// Re-execute a session's recorded actions, in order, in a new session
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/replay",
+    tag = "recording",
+    params(("id" = String, Path, description = "Session id")),
+    request_body = Option<ReplayRequest>,
+    responses(
+        (status = 200, description = "Per-action results of the replay", body = ApiEnvelope),
+    )
+)]
+pub async fn replay_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<ReplayRequest>>,
+) -> Response {
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let workflow_actions = match req.workflow.as_deref().map(RecordedWorkflow::from_yaml) {
+        Some(Ok(workflow)) => Some(workflow.actions(&req.inputs)),
+        Some(Err(e)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    format!("{:#}", e),
+                )),
+            )
+                .into_response()
+        }
+        None => None,
+    };
+    let script = match workflow_actions.or(req.actions) {
+        Some(actions) => ActionScript {
+            session_id: id.clone(),
+            actions,
+        },
+        None => match state.session_manager.recording(&id).await {
+            Some(script) => script,
+            None => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::SessionNotFound,
+                        format!("Session not found: {}", id),
+                    )),
+                )
+                    .into_response()
+            }
+        },
+    };
+
+    if let Err(response) = super::quotas::check_session_quota(&state).await {
+        return response;
+    }
+    let options = SessionOptions {
+        tags: HashMap::from([("replay_of".to_string(), id.clone())]),
+        owner: super::auth::current_principal().map(|principal| principal.subject),
+        ..SessionOptions::default()
+    };
+    let replay_id = match state.session_manager.create_session_with(options).await {
+        Ok(replay_id) => {
+            state
+                .quotas
+                .track_session(&super::quotas::current_client(), &replay_id)
+                .await;
+            replay_id
+        }
+        Err(e) => {
+            error!("Failed to create replay session: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+    let Some(session) = state.session_manager.get_session(&replay_id).await else {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::BrowserError,
+                format!("Replay session disappeared: {}", replay_id),
+            )),
+        )
+            .into_response();
+    };
+    let registry = ToolRegistry::new(session.read().await.browser.clone());
+
+    let mut steps = Vec::with_capacity(script.actions.len());
+    for (index, action) in script.actions.iter().enumerate() {
+        let started = std::time::Instant::now();
+        let outcome = registry
+            .execute_tool(&action.tool, action.parameters.clone())
+            .await;
+        let step = ReplayStep {
+            index,
+            tool: action.tool.clone(),
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        state.session_manager.record_activity(
+            &replay_id,
+            ActivityKind::for_tool(&action.tool),
+            serde_json::json!({
+                "tool": action.tool,
+                "parameters": action.parameters,
+                "success": step.success,
+                "error": step.error,
+                "duration_ms": step.duration_ms,
+                "replay_of": id
+            }),
+        );
+        let failed = !step.success;
+        if failed {
+            warn!(
+                "Replay of session {} failed at action {} ({}): {}",
+                id,
+                index,
+                action.tool,
+                step.error.as_deref().unwrap_or_default()
+            );
+        } else {
+            let mut session = session.write().await;
+            session.record_action(&action.tool, action.parameters.clone());
+            session.touch();
+        }
+        steps.push(step);
+        if failed && !req.continue_on_error {
+            break;
+        }
+    }
+    let browser = session.read().await.browser.clone();
+    if let Ok(url) = browser.current_url().await {
+        session.write().await.current_url = Some(url);
+    }
+
+    let passed = steps.iter().filter(|step| step.success).count();
+    Json(ApiResponse::success(serde_json::json!({
+        "session_id": replay_id,
+        "replay_of": id,
+        "total": script.actions.len(),
+        "passed": passed,
+        "completed": passed == script.actions.len(),
+        "steps": steps
+    })))
+    .into_response()
+}
+
+/// Persist a snapshot of a session so it can be restored later
+#[utoipa::path(
+    post,
+    path = "/api/session/{id}/save",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "The saved snapshot", body = ApiEnvelope))
+)]
+pub async fn save_session(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.session_manager.save_session(&id).await {
+        Ok(snapshot) => Json(ApiResponse::success(snapshot)).into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::NotFound),
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// The last saved snapshot of a session
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}/snapshot",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "The snapshot", body = ApiEnvelope))
+)]
+pub async fn get_session_snapshot(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    match state.session_manager.get_snapshot(&id).await {
+        Some(snapshot) => Json(ApiResponse::success(snapshot)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("No saved snapshot for session: {}", id),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a session from a saved or inline snapshot
+#[utoipa::path(
+    post,
+    path = "/api/session/restore",
+    tag = "sessions",
+    request_body = RestoreSessionRequest,
+    responses((status = 200, description = "The restored session", body = ApiEnvelope))
+)]
+pub async fn restore_session(
+    State(state): State<AppState>,
+    Json(req): Json<RestoreSessionRequest>,
+) -> Response {
+    let snapshot = match (req.snapshot, req.from_session_id) {
+        (Some(snapshot), _) => snapshot,
+        (None, Some(id)) => match state.session_manager.get_snapshot(&id).await {
+            Some(snapshot) => snapshot,
+            None => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::NotFound,
+                        format!("No saved snapshot for session: {}", id),
+                    )),
+                )
+                    .into_response()
+            }
+        },
+        (None, None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    "Either snapshot or from_session_id is required".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    };
+
+    match state.session_manager.restore_session(&snapshot).await {
+        Ok(session_id) => Json(ApiResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "restored_from": snapshot.session_id,
+            "cookies_restored": snapshot.cookies.len()
+        })))
+        .into_response(),
+        Err(e) => internal_error("Restore session failed", e),
+    }
+}
+
+/// Device presets accepted by `device` fields
+#[utoipa::path(
+    get,
+    path = "/api/emulation/devices",
+    tag = "emulation",
+    responses((status = 200, description = "Device presets", body = ApiEnvelope))
+)]
+pub async fn list_devices() -> Response {
+    Json(ApiResponse::success(
+        crate::browser::emulation::DEVICE_PRESETS.to_vec(),
+    ))
+    .into_response()
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GeolocationRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default)]
+    pub accuracy: Option<f64>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TimezoneRequest {
+    /// IANA timezone id, e.g. "Europe/Paris". Empty restores the system timezone.
+    pub timezone: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Override a session's geolocation
+#[utoipa::path(
+    post,
+    path = "/api/emulation/geolocation",
+    tag = "emulation",
+    request_body = GeolocationRequest,
+    responses((status = 200, description = "Geolocation was set", body = ApiEnvelope))
+)]
+pub async fn set_geolocation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<GeolocationRequest>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let location = match crate::browser::Geolocation::new(req.latitude, req.longitude, req.accuracy)
+    {
+        Ok(location) => location,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
+            )
+                .into_response()
+        }
+    };
+    match browser
+        .set_geolocation(
+            location.latitude,
+            location.longitude,
+            Some(location.accuracy),
+        )
+        .await
+    {
+        Ok(location) => Json(ApiResponse::success(location)).into_response(),
+        Err(e) => internal_error("Set geolocation failed", e),
+    }
+}
+
+/// Remove a session's geolocation override
+#[utoipa::path(
+    delete,
+    path = "/api/emulation/geolocation",
+    tag = "emulation",
+    params(SessionQuery),
+    responses((status = 200, description = "Override was removed", body = ApiEnvelope))
+)]
+pub async fn clear_geolocation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.clear_geolocation().await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "action": "geolocation_cleared"
+        })))
+        .into_response(),
+        Err(e) => internal_error("Clear geolocation failed", e),
+    }
+}
+
+/// Override a session's timezone
+#[utoipa::path(
+    post,
+    path = "/api/emulation/timezone",
+    tag = "emulation",
+    request_body = TimezoneRequest,
+    responses((status = 200, description = "Timezone was set", body = ApiEnvelope))
+)]
+pub async fn set_timezone(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<TimezoneRequest>,
+) -> Response {
+    if !req.timezone.is_empty() && !crate::browser::location::is_valid_timezone_id(&req.timezone) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                format!("Invalid timezone id: {}", req.timezone),
+            )),
+        )
+            .into_response();
+    }
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.set_timezone(&req.timezone).await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "timezone": req.timezone
+        })))
+        .into_response(),
+        Err(e) => internal_error("Set timezone failed", e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SendKeysRequest {
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub selector: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Send key presses and shortcuts to the focused element
+#[utoipa::path(
+    post,
+    path = "/api/keys",
+    tag = "browser",
+    request_body = SendKeysRequest,
+    responses(
+        (status = 200, description = "Keys were sent", body = ApiEnvelope),
+        (status = 400, description = "Unknown key or modifier", body = ApiEnvelope)
+    )
+)]
+pub async fn send_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<SendKeysRequest>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    if let Err(e) = req
+        .keys
+        .iter()
+        .try_for_each(|k| KeyChord::parse(k).map(drop))
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response();
+    }
+    match browser.send_keys(req.selector.as_deref(), &req.keys).await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "keys": req.keys,
+            "action": "keys_sent"
+        })))
+        .into_response(),
+        Err(e) => internal_error("Send keys failed", e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DialogPolicyRequest {
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub policy: Option<crate::browser::DialogPolicy>,
+    /// Restrict the policy to one dialog type (alert, confirm, prompt, beforeunload)
+    #[serde(default)]
+    pub dialog_type: Option<String>,
+    /// Drop all overrides and go back to the default policies
+    #[serde(default)]
+    pub reset: bool,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// How a session answers JavaScript dialogs
+#[utoipa::path(
+    get,
+    path = "/api/dialogs/policy",
+    tag = "browser",
+    params(SessionQuery),
+    responses((status = 200, description = "Dialog policy", body = ApiEnvelope))
+)]
+pub async fn get_dialog_policy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    Json(ApiResponse::success(browser.dialogs().policies().await)).into_response()
+}
+
+/// Change how a session answers JavaScript dialogs
+#[utoipa::path(
+    post,
+    path = "/api/dialogs/policy",
+    tag = "browser",
+    request_body = DialogPolicyRequest,
+    responses((status = 200, description = "The new policy", body = ApiEnvelope))
+)]
+pub async fn set_dialog_policy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<DialogPolicyRequest>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let dialogs = browser.dialogs();
+
+    if req.reset {
+        dialogs.reset().await;
+    }
+    if let Some(policy) = req.policy {
+        if let Err(e) = dialogs.set_policy(req.dialog_type.as_deref(), policy).await {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
+            )
+                .into_response();
+        }
+    } else if !req.reset {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                "Either policy or reset is required".to_string(),
+            )),
+        )
+            .into_response();
+    }
+
+    Json(ApiResponse::success(dialogs.policies().await)).into_response()
+}
+
+/// JavaScript dialogs a session has seen and how they were answered
+#[utoipa::path(
+    get,
+    path = "/api/dialogs",
+    tag = "browser",
+    params(SessionQuery, PageQuery),
+    responses((status = 200, description = "Dialogs", body = ApiEnvelope))
+)]
+pub async fn list_dialogs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+    page: PageQuery,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    page.respond(browser.dialogs().history().await)
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PermissionsRequest {
+    /// Permission name to state, e.g. {"notifications": "granted", "camera": "denied"}
+    #[schema(value_type = BTreeMap<String, String>)]
+    pub permissions: std::collections::BTreeMap<String, crate::browser::PermissionState>,
+    /// Origin to apply to; all origins when omitted
+    #[serde(default)]
+    pub origin: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Grant or deny browser permissions to a session
+#[utoipa::path(
+    post,
+    path = "/api/permissions",
+    tag = "browser",
+    request_body = PermissionsRequest,
+    responses((status = 200, description = "Permissions were set", body = ApiEnvelope))
+)]
+pub async fn set_permissions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<PermissionsRequest>,
+) -> Response {
+    if let Some(name) = req
+        .permissions
+        .keys()
+        .find(|name| crate::browser::permissions::resolve_permission(name).is_err())
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                format!("Unknown permission: {}", name),
+            )),
+        )
+            .into_response();
+    }
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser
+        .set_permissions(&req.permissions, req.origin.as_deref())
+        .await
+    {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "origin": req.origin,
+            "permissions": req.permissions
+        })))
+        .into_response(),
+        Err(e) => internal_error("Set permissions failed", e),
+    }
+}
+
+/// Reset a session's permissions to the browser defaults
+#[utoipa::path(
+    delete,
+    path = "/api/permissions",
+    tag = "browser",
+    params(SessionQuery),
+    responses((status = 200, description = "Permissions were reset", body = ApiEnvelope))
+)]
+pub async fn reset_permissions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let browser = match resolve_browser(&state, &headers, query.session_id.as_deref()).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    match browser.reset_permissions().await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "action": "permissions_reset"
+        })))
+        .into_response(),
+        Err(e) => internal_error("Reset permissions failed", e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateProfileRequest {
+    pub name: String,
+}
+
+/// Persistent browser profiles
+#[utoipa::path(
+    get,
+    path = "/api/profiles",
+    tag = "profiles",
+    params(PageQuery),
+    responses((status = 200, description = "Profiles", body = ApiEnvelope))
+)]
+pub async fn list_profiles(State(state): State<AppState>, page: PageQuery) -> Response {
+    let profiles = match state.browser_pool.profiles().list() {
+        Ok(profiles) => profiles,
+        Err(e) => return internal_error("List profiles failed", e),
+    };
+
+    let mut entries = Vec::with_capacity(profiles.len());
+    for profile in profiles {
+        let in_use = state.browser_pool.profile_in_use(&profile.name).await;
+        entries.push(serde_json::json!({
+            "name": profile.name,
+            "path": profile.path,
+            "created_at": profile.created_at,
+            "in_use": in_use,
+        }));
+    }
+    page.respond(entries)
+}
+
+/// Create a persistent browser profile
+#[utoipa::path(
+    post,
+    path = "/api/profiles",
+    tag = "profiles",
+    request_body = CreateProfileRequest,
+    responses((status = 200, description = "The created profile", body = ApiEnvelope))
+)]
+pub async fn create_profile(
+    State(state): State<AppState>,
+    Json(req): Json<CreateProfileRequest>,
+) -> Response {
+    if let Err(e) = crate::browser::profiles::validate_profile_name(&req.name) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response();
+    }
+    match state.browser_pool.profiles().get_or_create(&req.name) {
+        Ok(profile) => Json(ApiResponse::success(profile)).into_response(),
+        Err(e) => internal_error("Create profile failed", e),
+    }
+}
+
+/// Delete a persistent browser profile and its data
+#[utoipa::path(
+    delete,
+    path = "/api/profiles/{name}",
+    tag = "profiles",
+    params(("name" = String, Path, description = "Profile name")),
+    responses((status = 200, description = "Profile was deleted", body = ApiEnvelope))
+)]
+pub async fn delete_profile(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    match state.browser_pool.delete_profile(&name).await {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "deleted": true,
+            "name": name
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
@@ -9,8 +9,9 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::error;
+use utoipa::ToSchema;
 
-use super::ApiResponse;
+use super::{ApiError, ApiResponse, ErrorCode};
 use crate::coordination::RainbowCoordinator;
 
 /// State for coordinated API handlers
@@ -21,23 +22,30 @@ pub struct CoordinatedApiState {
 
 // Request/Response types
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+#[aliases(
+    CoordinatedNavigate = CoordinatedRequest<CoordinatedNavigateRequest>,
+    CoordinatedIntelligentAction = CoordinatedRequest<IntelligentActionRequest>,
+    CoordinatedPerceptionAnalysis = CoordinatedRequest<PerceptionAnalysisRequest>,
+    CoordinatedToolExecution = CoordinatedRequest<ToolExecutionRequest>
+)]
 pub struct CoordinatedRequest<T> {
     pub session_id: Option<String>,
     #[serde(flatten)]
     pub data: T,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(CoordinatedEnvelope = CoordinatedResponse<serde_json::Value>)]
 pub struct CoordinatedResponse<T> {
     pub success: bool,
     pub session_id: String,
     pub data: Option<T>,
-    pub error: Option<String>,
+    pub error: Option<ApiError>,
     pub metrics: Option<OperationMetrics>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OperationMetrics {
     pub duration_ms: u64,
     pub cache_hits: u32,
@@ -55,12 +63,12 @@ impl<T> CoordinatedResponse<T> {
         }
     }
 
-    pub fn error(error: String) -> Self {
+    pub fn error(code: ErrorCode, message: impl Into<String>) -> Self {
         Self {
             success: false,
             session_id: String::new(),
             data: None,
-            error: Some(error),
+            error: Some(ApiError::new(code, message)),
             metrics: None,
         }
     }
@@ -69,6 +77,12 @@ impl<T> CoordinatedResponse<T> {
 // Session management endpoints
 
 /// Create a new coordinated session
+#[utoipa::path(
+    post,
+    path = "/api/v2/session/create",
+    tag = "coordinated",
+    responses((status = 200, description = "The created session", body = ApiEnvelope))
+)]
 pub async fn create_coordinated_session(State(state): State<CoordinatedApiState>) -> Response {
     match state.coordinator.create_session().await {
         Ok(bundle) => {
@@ -87,7 +101,10 @@ pub async fn create_coordinated_session(State(state): State<CoordinatedApiState>
             error!("Failed to create coordinated session: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
@@ -95,6 +112,13 @@ pub async fn create_coordinated_session(State(state): State<CoordinatedApiState>
 }
 
 /// Get session information
+#[utoipa::path(
+    get,
+    path = "/api/v2/session/{id}",
+    tag = "coordinated",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "The session", body = ApiEnvelope))
+)]
 pub async fn get_coordinated_session(
     State(state): State<CoordinatedApiState>,
     Path(session_id): Path<String>,
@@ -116,16 +140,22 @@ pub async fn get_coordinated_session(
         }
         None => (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error(format!(
-                "Session not found: {}",
-                session_id
-            ))),
+            Json(ApiResponse::<()>::error(
+                ErrorCode::SessionNotFound,
+                format!("Session not found: {}", session_id),
+            )),
         )
             .into_response(),
     }
 }
 
 /// List all active sessions
+#[utoipa::path(
+    get,
+    path = "/api/v2/sessions",
+    tag = "coordinated",
+    responses((status = 200, description = "Sessions", body = ApiEnvelope))
+)]
 pub async fn list_coordinated_sessions(State(state): State<CoordinatedApiState>) -> Response {
     let sessions = state.coordinator.list_sessions().await;
     let response = serde_json::json!({
@@ -145,6 +175,13 @@ pub async fn list_coordinated_sessions(State(state): State<CoordinatedApiState>)
 }
 
 /// Delete a session
+#[utoipa::path(
+    delete,
+    path = "/api/v2/session/{id}",
+    tag = "coordinated",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "Session was deleted", body = ApiEnvelope))
+)]
 pub async fn delete_coordinated_session(
     State(state): State<CoordinatedApiState>,
     Path(session_id): Path<String>,
@@ -159,7 +196,10 @@ pub async fn delete_coordinated_session(
             error!("Failed to delete session: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
@@ -168,17 +208,26 @@ pub async fn delete_coordinated_session(
 
 // Coordinated operation endpoints
 
-#[derive(Debug, Deserialize)]
-pub struct NavigateRequest {
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CoordinatedNavigateRequest {
     pub url: String,
     pub _wait_for_load: Option<bool>,
     pub analyze_page: Option<bool>,
 }
 
 /// Navigate with coordinated perception
+#[utoipa::path(
+    post,
+    path = "/api/v2/navigate",
+    tag = "coordinated",
+    request_body = CoordinatedNavigate,
+    responses(
+        (status = 200, description = "Navigation result with metrics", body = CoordinatedEnvelope),
+    )
+)]
 pub async fn coordinated_navigate(
     State(state): State<CoordinatedApiState>,
-    Json(req): Json<CoordinatedRequest<NavigateRequest>>,
+    Json(req): Json<CoordinatedRequest<CoordinatedNavigateRequest>>,
 ) -> Response {
     let start_time = std::time::Instant::now();
 
@@ -192,7 +241,10 @@ pub async fn coordinated_navigate(
         Err(e) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(CoordinatedResponse::<()>::error(e.to_string())),
+                Json(CoordinatedResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
             )
                 .into_response();
         }
@@ -232,14 +284,17 @@ pub async fn coordinated_navigate(
             error!("Navigation failed: {}", e);
             (
                 StatusCode::BAD_REQUEST,
-                Json(CoordinatedResponse::<()>::error(e.to_string())),
+                Json(CoordinatedResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct IntelligentActionRequest {
     pub action_type: String,
     pub target: String,
@@ -247,6 +302,15 @@ pub struct IntelligentActionRequest {
 }
 
 /// Execute an intelligent action with full coordination
+#[utoipa::path(
+    post,
+    path = "/api/v2/intelligent-action",
+    tag = "coordinated",
+    request_body = CoordinatedIntelligentAction,
+    responses(
+        (status = 200, description = "Action result with metrics", body = CoordinatedEnvelope),
+    )
+)]
 pub async fn coordinated_intelligent_action(
     State(state): State<CoordinatedApiState>,
     Json(req): Json<CoordinatedRequest<IntelligentActionRequest>>,
@@ -263,7 +327,10 @@ pub async fn coordinated_intelligent_action(
         Err(e) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(CoordinatedResponse::<()>::error(e.to_string())),
+                Json(CoordinatedResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
             )
                 .into_response();
         }
@@ -316,7 +383,10 @@ pub async fn coordinated_intelligent_action(
             error!("Intelligent action failed: {}", e);
             (
                 StatusCode::BAD_REQUEST,
-                Json(CoordinatedResponse::<()>::error(e.to_string())),
+                Json(CoordinatedResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
@@ -324,6 +394,12 @@ pub async fn coordinated_intelligent_action(
 }
 
 /// Get system health status
+#[utoipa::path(
+    get,
+    path = "/api/v2/health",
+    tag = "coordinated",
+    responses((status = 200, description = "System health", body = ApiEnvelope))
+)]
 pub async fn get_system_health(State(state): State<CoordinatedApiState>) -> Response {
     let health = state.coordinator.get_system_health().await;
 
@@ -364,13 +440,22 @@ pub async fn get_system_health(State(state): State<CoordinatedApiState>) -> Resp
     Json(ApiResponse::success(response)).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ToolExecutionRequest {
     pub tool_name: String,
     pub _parameters: serde_json::Value,
 }
 
 /// Execute a tool in a coordinated session
+#[utoipa::path(
+    post,
+    path = "/api/v2/tool/execute",
+    tag = "coordinated",
+    request_body = CoordinatedToolExecution,
+    responses(
+        (status = 200, description = "Tool result with metrics", body = CoordinatedEnvelope),
+    )
+)]
 pub async fn coordinated_tool_execution(
     State(state): State<CoordinatedApiState>,
     Json(req): Json<CoordinatedRequest<ToolExecutionRequest>>,
@@ -387,7 +472,10 @@ pub async fn coordinated_tool_execution(
         Err(e) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(CoordinatedResponse::<()>::error(e.to_string())),
+                Json(CoordinatedResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
             )
                 .into_response();
         }
@@ -412,13 +500,22 @@ pub async fn coordinated_tool_execution(
     Json(response).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct PerceptionAnalysisRequest {
     pub analysis_type: Option<String>, // "quick", "standard", "deep"
     pub _target: Option<String>,
 }
 
 /// Analyze page with coordinated perception
+#[utoipa::path(
+    post,
+    path = "/api/v2/perception/analyze",
+    tag = "coordinated",
+    request_body = CoordinatedPerceptionAnalysis,
+    responses(
+        (status = 200, description = "Analysis with metrics", body = CoordinatedEnvelope),
+    )
+)]
 pub async fn coordinated_perception_analysis(
     State(state): State<CoordinatedApiState>,
     Json(req): Json<CoordinatedRequest<PerceptionAnalysisRequest>>,
@@ -435,7 +532,10 @@ pub async fn coordinated_perception_analysis(
         Err(e) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(CoordinatedResponse::<()>::error(e.to_string())),
+                Json(CoordinatedResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
             )
                 .into_response();
         }
@@ -465,7 +565,10 @@ pub async fn coordinated_perception_analysis(
             error!("Perception analysis failed: {}", e);
             (
                 StatusCode::BAD_REQUEST,
-                Json(CoordinatedResponse::<()>::error(e.to_string())),
+                Json(CoordinatedResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
@@ -0,0 +1,155 @@
+// Error codes
+// Every failed response carries `{code, message, details, retryable}` in its
+// `error` field, so clients branch on a stable code such as `SESSION_NOT_FOUND`
+// or `BROWSER_UNAVAILABLE` instead of matching message text.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use utoipa::ToSchema;
+
+/// What went wrong, independent of the message wording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The request body, query or parameters are invalid
+    InvalidRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    SessionNotFound,
+    ToolNotFound,
+    /// The resource is in a state that does not allow the request
+    Conflict,
+    RateLimited,
+    /// No browser could be acquired or the tool registry has none
+    BrowserUnavailable,
+    /// The browser failed to perform an action
+    BrowserError,
+    ElementNotFound,
+    /// A selector matched several elements where one was expected
+    SelectorAmbiguous,
+    Timeout,
+    ToolFailed,
+    PerceptionFailed,
+    /// No LLM provider is configured or reachable
+    LlmUnavailable,
+    LlmError,
+    /// A federated worker could not be reached
+    WorkerUnavailable,
+    NotImplemented,
+    Internal,
+}
+
+impl ErrorCode {
+    /// Whether the same request may succeed when retried later
+    pub fn retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::RateLimited
+                | ErrorCode::BrowserUnavailable
+                | ErrorCode::Timeout
+                | ErrorCode::LlmUnavailable
+                | ErrorCode::WorkerUnavailable
+        )
+    }
+
+    /// Code of a browser, tool or perception error, from what its message reports;
+    /// `fallback` when it names nothing more specific
+    pub fn classify(error: impl Display, fallback: ErrorCode) -> ErrorCode {
+        let message = error.to_string().to_lowercase();
+        if message.contains("session not found") {
+            ErrorCode::SessionNotFound
+        } else if message.starts_with("tool '") && message.ends_with("' not found") {
+            ErrorCode::ToolNotFound
+        } else if message.contains("ambiguous") || message.contains("matched multiple elements") {
+            ErrorCode::SelectorAmbiguous
+        } else if message.contains("element not found")
+            || message.contains("no element")
+            || message.contains("no elements found")
+        {
+            ErrorCode::ElementNotFound
+        } else if message.contains("timed out") || message.contains("timeout") {
+            ErrorCode::Timeout
+        } else {
+            fallback
+        }
+    }
+}
+
+/// Body of the `error` field of a failed response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Code-specific context, e.g. the selector or the allowed values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<serde_json::Value>,
+    pub retryable: bool,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+            retryable: code.retryable(),
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_code_message_and_retryable() {
+        let error = ApiError::new(ErrorCode::SessionNotFound, "Session not found: s1")
+            .with_details(json!({"session_id": "s1"}));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "SESSION_NOT_FOUND",
+                "message": "Session not found: s1",
+                "details": {"session_id": "s1"},
+                "retryable": false
+            })
+        );
+        let unavailable = ApiError::new(ErrorCode::BrowserUnavailable, "pool exhausted");
+        assert!(unavailable.retryable);
+        assert!(serde_json::to_value(&unavailable)
+            .unwrap()
+            .get("details")
+            .is_none());
+    }
+
+    #[test]
+    fn classifies_browser_and_tool_errors() {
+        let fallback = ErrorCode::ToolFailed;
+        let cases = [
+            ("Session not found: abc", ErrorCode::SessionNotFound),
+            ("Tool 'clik' not found", ErrorCode::ToolNotFound),
+            (
+                "Tool 'click' execution failed: Element not found: #buy",
+                ErrorCode::ElementNotFound,
+            ),
+            (
+                "Selector is ambiguous: 3 matches",
+                ErrorCode::SelectorAmbiguous,
+            ),
+            ("Tool 'wait_for_element' timed out", ErrorCode::Timeout),
+            ("Timeout waiting for selector: #x", ErrorCode::Timeout),
+            ("Protocol error", fallback),
+        ];
+        for (message, code) in cases {
+            assert_eq!(ErrorCode::classify(message, fallback), code, "{}", message);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+// Coordination event subscriptions
+// Clients pick the events they want by type, session and source engine, and the
+// server filters the event bus for them: `/api/events` lists the matching history,
+// `/api/events/stream` streams matching events as SSE, and `/ws` takes the same
+// filter in a `subscribe_events` message. Callers only see their tenant's events.
+
+use axum::{
+    extract::{Json, Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tokio::sync::broadcast;
+use tracing::warn;
+use utoipa::IntoParams;
+
+use super::pagination::PageQuery;
+use super::tenants::current_tenant;
+use super::{ApiResponse, AppState, ErrorCode};
+use crate::coordination::{EventFilter, TimestampedEvent};
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventQuery {
+    /// Comma-separated event types, e.g. `navigation_completed,tool_execution_failed`
+    #[serde(default)]
+    pub types: Option<String>,
+    /// Only events of this session
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Comma-separated source engines: browser, perception, intelligence, tools,
+    /// workflow, session, cache, llm or system
+    #[serde(default)]
+    pub sources: Option<String>,
+    /// Only events at or after this time (RFC 3339); the stream replays them first
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl EventQuery {
+    /// The filter the query describes, or why a type or source is unknown
+    pub fn filter(&self) -> Result<EventFilter, String> {
+        Ok(EventFilter {
+            event_types: parse_names(self.types.as_deref(), "event type")?,
+            session_id: self.session_id.clone(),
+            sources: parse_names(self.sources.as_deref(), "event source")?,
+        })
+    }
+}
+
+fn parse_names<T: serde::de::DeserializeOwned>(
+    names: Option<&str>,
+    what: &str,
+) -> Result<Vec<T>, String> {
+    names
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            serde_json::from_value(serde_json::json!(name))
+                .map_err(|_| format!("Unknown {}: {}", what, name))
+        })
+        .collect()
+}
+
+/// Whether the caller acting for `tenant` wants `logged`
+pub fn wanted(logged: &TimestampedEvent, filter: &EventFilter, tenant: &Option<String>) -> bool {
+    &logged.tenant == tenant && filter.matches(&logged.event)
+}
+
+/// An event as clients receive it
+pub fn event_json(logged: &TimestampedEvent) -> serde_json::Value {
+    serde_json::json!({
+        "sequence_id": logged.sequence_id,
+        "occurred_at": logged.occurred_at,
+        "source": logged.event.source(),
+        "correlation_id": logged.correlation_id,
+        "event": logged.event,
+    })
+}
+
+fn invalid_query(message: String) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, message)),
+    )
+        .into_response()
+}
+
+/// Past coordination events matching the filter, newest first
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "events",
+    params(EventQuery, PageQuery),
+    responses(
+        (status = 200, description = "Events", body = ApiEnvelope),
+        (status = 400, description = "Unknown event type or source", body = ApiEnvelope),
+    )
+)]
+pub async fn list_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventQuery>,
+    page: PageQuery,
+) -> Response {
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(message) => return invalid_query(message),
+    };
+    let logged = match query.since {
+        Some(since) => match state.events.replay_since(since).await {
+            Ok(logged) => logged,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::Internal,
+                        format!("Failed to read event log: {}", e),
+                    )),
+                )
+                    .into_response()
+            }
+        },
+        None => state.events.get_history(None).await,
+    };
+    let tenant = current_tenant();
+    let events: Vec<_> = logged
+        .iter()
+        .rev()
+        .filter(|logged| wanted(logged, &filter, &tenant))
+        .map(event_json)
+        .collect();
+    page.respond(events)
+}
+
+/// Stream coordination events matching the filter as SSE, starting with those
+/// since `since` when given
+#[utoipa::path(
+    get,
+    path = "/api/events/stream",
+    tag = "events",
+    params(EventQuery),
+    responses(
+        (status = 200, description = "Server-sent event stream", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Unknown event type or source", body = ApiEnvelope),
+    )
+)]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventQuery>,
+) -> Response {
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(message) => return invalid_query(message),
+    };
+    // Subscribe before replaying so no event falls between the two
+    let mut live = state.events.subscribe_live();
+    let replayed = match query.since {
+        Some(since) => state.events.replay_since(since).await.unwrap_or_else(|e| {
+            warn!("Event replay unavailable: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let tenant = current_tenant();
+
+    let stream = async_stream::stream! {
+        let mut next_sequence = 0;
+        for logged in replayed {
+            next_sequence = logged.sequence_id + 1;
+            if wanted(&logged, &filter, &tenant) {
+                yield Ok::<_, std::convert::Infallible>(sse_event(&logged));
+            }
+        }
+        loop {
+            match live.recv().await {
+                Ok(logged) if logged.sequence_id >= next_sequence => {
+                    if wanted(&logged, &filter, &tenant) {
+                        yield Ok(sse_event(&logged));
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Event stream skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    };
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn sse_event(logged: &TimestampedEvent) -> SseEvent {
+    let event_type = serde_json::to_value(logged.event.event_type())
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string))
+        .unwrap_or_default();
+    SseEvent::default()
+        .event(event_type)
+        .id(logged.sequence_id.to_string())
+        .json_data(event_json(logged))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordination::{Event, EventSource, EventType};
+    use std::time::Instant;
+
+    #[test]
+    fn test_query_filters_events() {
+        let query = EventQuery {
+            types: Some("navigation_completed, tool_execution_failed".to_string()),
+            sources: Some("browser".to_string()),
+            session_id: Some("s1".to_string()),
+            ..EventQuery::default()
+        };
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.sources, [EventSource::Browser]);
+        assert_eq!(filter.event_types.len(), 2);
+
+        let logged = |session_id: &str, tenant: Option<&str>| TimestampedEvent {
+            event: Event::NavigationCompleted {
+                session_id: session_id.to_string(),
+                url: "https://example.com".to_string(),
+                load_time_ms: 120,
+                timestamp: Instant::now(),
+            },
+            timestamp: Instant::now(),
+            occurred_at: Utc::now(),
+            sequence_id: 1,
+            tenant: tenant.map(str::to_string),
+            correlation_id: None,
+        };
+        assert!(wanted(&logged("s1", None), &filter, &None));
+        assert!(!wanted(&logged("s2", None), &filter, &None));
+        assert!(!wanted(&logged("s1", Some("acme")), &filter, &None));
+        assert!(wanted(
+            &logged("s1", Some("acme")),
+            &EventFilter::default(),
+            &Some("acme".to_string())
+        ));
+
+        let json = event_json(&logged("s1", None));
+        assert_eq!(json["source"], "browser");
+        assert_eq!(json["event"]["type"], "navigation_completed");
+        assert!(!EventFilter {
+            event_types: vec![EventType::SessionCreated],
+            ..EventFilter::default()
+        }
+        .matches(&logged("s1", None).event));
+
+        let unknown = EventQuery {
+            sources: Some("kernel".to_string()),
+            ..EventQuery::default()
+        };
+        assert_eq!(
+            unknown.filter().unwrap_err(),
+            "Unknown event source: kernel"
+        );
+    }
+}
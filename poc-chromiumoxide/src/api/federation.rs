@@ -0,0 +1,400 @@
+// Pool federation: schedule sessions onto remote worker agents
+//
+// Each worker is a regular API server running its own BrowserPool. The coordinating
+// server creates sessions on the least loaded healthy worker and forwards every
+// request that names one of those sessions to the worker hosting it.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Json, Request, State},
+    http::{header, request::Parts, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use super::{ApiResponse, AppState, ErrorCode};
+use crate::coordination::correlation::{current_correlation_id, CORRELATION_ID_HEADER};
+
+/// Largest request body forwarded to a worker
+const MAX_FORWARD_BODY: usize = 32 * 1024 * 1024;
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Worker {
+    url: String,
+    healthy: AtomicBool,
+}
+
+/// Worker as reported by `/api/federation/workers`
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerStatus {
+    pub url: String,
+    pub healthy: bool,
+    pub sessions: Vec<String>,
+}
+
+/// Remote workers and the sessions placed on them
+pub struct Federation {
+    client: reqwest::Client,
+    workers: Vec<Worker>,
+    /// Session id -> index of the worker hosting it
+    placements: RwLock<HashMap<String, usize>>,
+}
+
+impl Federation {
+    pub fn new(urls: Vec<String>) -> Self {
+        let workers = urls
+            .into_iter()
+            .map(|url| Worker {
+                url: url.trim_end_matches('/').to_string(),
+                healthy: AtomicBool::new(true),
+            })
+            .collect();
+        Self {
+            client: reqwest::Client::new(),
+            workers,
+            placements: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Federation over the comma-separated worker URLs in `RAINBOW_WORKERS`, if set
+    pub fn from_env() -> Option<Self> {
+        let urls: Vec<String> = std::env::var("RAINBOW_WORKERS")
+            .ok()?
+            .split(',')
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .collect();
+        if urls.is_empty() {
+            return None;
+        }
+        info!("Pool federation across {} workers: {:?}", urls.len(), urls);
+        Some(Self::new(urls))
+    }
+
+    /// Probe every worker's health endpoint and record which ones answer
+    pub async fn check_workers(&self) {
+        for worker in &self.workers {
+            let healthy = self
+                .client
+                .get(format!("{}/api/health", worker.url))
+                .timeout(HEALTH_TIMEOUT)
+                .send()
+                .await
+                .map(|r| r.status().is_success())
+                .unwrap_or(false);
+            if worker.healthy.swap(healthy, Ordering::Relaxed) != healthy {
+                if healthy {
+                    info!("Worker {} is back", worker.url);
+                } else {
+                    warn!("Worker {} stopped responding", worker.url);
+                }
+            }
+        }
+    }
+
+    /// Run `check_workers` every `interval` until the returned task is aborted
+    pub fn start_health_checks(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.check_workers().await;
+            }
+        })
+    }
+
+    pub async fn status(&self) -> Vec<WorkerStatus> {
+        let placements = self.placements.read().await;
+        self.workers
+            .iter()
+            .enumerate()
+            .map(|(index, worker)| WorkerStatus {
+                url: worker.url.clone(),
+                healthy: worker.healthy.load(Ordering::Relaxed),
+                sessions: placements
+                    .iter()
+                    .filter(|(_, w)| **w == index)
+                    .map(|(id, _)| id.clone())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    async fn worker_for(&self, session_id: &str) -> Option<usize> {
+        self.placements.read().await.get(session_id).copied()
+    }
+
+    /// Create a session on the least loaded healthy worker and remember its placement
+    async fn create_session(&self, parts: &Parts, body: Bytes) -> Response {
+        let loads: Vec<(bool, usize)> = {
+            let placements = self.placements.read().await;
+            self.workers
+                .iter()
+                .enumerate()
+                .map(|(index, worker)| {
+                    let sessions = placements.values().filter(|w| **w == index).count();
+                    (worker.healthy.load(Ordering::Relaxed), sessions)
+                })
+                .collect()
+        };
+        let Some(worker) = least_loaded(&loads) else {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::WorkerUnavailable,
+                    "No healthy worker available".to_string(),
+                )),
+            )
+                .into_response();
+        };
+
+        let (status, _, body) = match self.forward(worker, parts, body).await {
+            Ok(forwarded) => forwarded,
+            Err(response) => return response,
+        };
+        let mut value: serde_json::Value = match serde_json::from_slice(&body) {
+            Ok(value) => value,
+            Err(_) => return raw_response(status, None, body),
+        };
+        if let Some(session_id) = value["data"]["session_id"].as_str() {
+            self.placements
+                .write()
+                .await
+                .insert(session_id.to_string(), worker);
+            info!(
+                "Placed session {} on worker {}",
+                session_id, self.workers[worker].url
+            );
+            value["data"]["worker"] = serde_json::json!(self.workers[worker].url);
+        }
+        (status, Json(value)).into_response()
+    }
+
+    /// Send a request to a worker unchanged. Unreachable workers are marked unhealthy
+    /// and reported as 502.
+    async fn forward(
+        &self,
+        worker: usize,
+        parts: &Parts,
+        body: Bytes,
+    ) -> Result<(StatusCode, Option<String>, Bytes), Response> {
+        let target = &self.workers[worker];
+        let path = parts
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or("/");
+        let result = async {
+            let method = reqwest::Method::from_bytes(parts.method.as_str().as_bytes())?;
+            let mut request = self
+                .client
+                .request(method, format!("{}{}", target.url, path))
+                .body(body.to_vec());
+            for (name, value) in &parts.headers {
+                if name == header::HOST
+                    || name == header::CONTENT_LENGTH
+                    || name == CORRELATION_ID_HEADER
+                {
+                    continue;
+                }
+                if let Ok(value) = value.to_str() {
+                    request = request.header(name.as_str(), value);
+                }
+            }
+            // The worker's part of the request is traced under the same id
+            if let Some(correlation_id) = current_correlation_id() {
+                request = request.header(CORRELATION_ID_HEADER, correlation_id);
+            }
+            let response = request.send().await?;
+            let status = response.status().as_u16();
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            Ok::<_, anyhow::Error>((status, content_type, response.bytes().await?))
+        }
+        .await;
+
+        match result {
+            Ok((status, content_type, body)) => Ok((
+                StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_GATEWAY),
+                content_type,
+                body,
+            )),
+            Err(e) => {
+                warn!("Worker {} unreachable: {}", target.url, e);
+                target.healthy.store(false, Ordering::Relaxed);
+                Err((
+                    StatusCode::BAD_GATEWAY,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::WorkerUnavailable,
+                        format!("Worker {} unreachable: {}", target.url, e),
+                    )),
+                )
+                    .into_response())
+            }
+        }
+    }
+}
+
+/// Index of the healthy worker with the fewest sessions, given `(healthy, sessions)`
+fn least_loaded(loads: &[(bool, usize)]) -> Option<usize> {
+    loads
+        .iter()
+        .enumerate()
+        .filter(|(_, (healthy, _))| *healthy)
+        .min_by_key(|(_, (_, sessions))| *sessions)
+        .map(|(index, _)| index)
+}
+
+/// Session a request refers to, from `/api/session/:id/...`, `?session_id=` or a
+/// `session_id` field in a JSON body
+fn session_id_of(path: &str, query: Option<&str>, body: &[u8]) -> Option<String> {
+    if let Some(rest) = path.strip_prefix("/api/session/") {
+        let id = rest.split('/').next().unwrap_or_default();
+        if !id.is_empty() && id != "create" && id != "restore" {
+            return Some(id.to_string());
+        }
+    }
+    let from_query = query.and_then(|q| {
+        q.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "session_id")
+            .map(|(_, value)| value.to_string())
+    });
+    if from_query.is_some() {
+        return from_query;
+    }
+    serde_json::from_slice::<serde_json::Value>(body)
+        .ok()?
+        .get("session_id")?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn raw_response(status: StatusCode, content_type: Option<String>, body: Bytes) -> Response {
+    let mut response = (status, body).into_response();
+    if let Some(value) = content_type.and_then(|ct| HeaderValue::from_str(&ct).ok()) {
+        response.headers_mut().insert(header::CONTENT_TYPE, value);
+    }
+    response
+}
+
+/// Middleware routing session creation and session-bound requests to workers.
+/// Everything else, and all requests when federation is off, is served locally.
+pub(super) async fn route_to_worker(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(federation) = state.federation.clone() else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_FORWARD_BODY).await {
+        Ok(body) => body,
+        Err(e) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
+            )
+                .into_response()
+        }
+    };
+
+    if parts.method == Method::POST && parts.uri.path() == "/api/session/create" {
+        return federation.create_session(&parts, body).await;
+    }
+
+    let worker = match session_id_of(parts.uri.path(), parts.uri.query(), &body) {
+        Some(session_id) => federation
+            .worker_for(&session_id)
+            .await
+            .map(|worker| (session_id, worker)),
+        None => None,
+    };
+    let Some((session_id, worker)) = worker else {
+        return next.run(Request::from_parts(parts, Body::from(body))).await;
+    };
+
+    let (status, content_type, response_body) = match federation.forward(worker, &parts, body).await
+    {
+        Ok(forwarded) => forwarded,
+        Err(response) => return response,
+    };
+    let closed = parts.method == Method::DELETE
+        && parts.uri.path() == format!("/api/session/{}", session_id);
+    if closed && status.is_success() {
+        federation.placements.write().await.remove(&session_id);
+    }
+    raw_response(status, content_type, response_body)
+}
+
+/// Workers of the federation with their health and placed sessions
+#[utoipa::path(
+    get,
+    path = "/api/federation/workers",
+    tag = "federation",
+    responses((status = 200, description = "Workers", body = ApiEnvelope))
+)]
+pub async fn list_workers(State(state): State<AppState>) -> Response {
+    match &state.federation {
+        Some(federation) => Json(ApiResponse::success(serde_json::json!({
+            "enabled": true,
+            "workers": federation.status().await
+        })))
+        .into_response(),
+        None => Json(ApiResponse::success(serde_json::json!({
+            "enabled": false,
+            "workers": []
+        })))
+        .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_least_loaded_healthy_worker() {
+        assert_eq!(least_loaded(&[(true, 3), (true, 1), (false, 0)]), Some(1));
+        assert_eq!(least_loaded(&[(false, 0), (false, 2)]), None);
+    }
+
+    #[test]
+    fn finds_session_in_path_query_or_body() {
+        assert_eq!(
+            session_id_of("/api/session/abc/cookies", None, b""),
+            Some("abc".to_string())
+        );
+        assert_eq!(session_id_of("/api/session/create", None, b""), None);
+        assert_eq!(
+            session_id_of("/api/tabs", Some("x=1&session_id=def"), b""),
+            Some("def".to_string())
+        );
+        assert_eq!(
+            session_id_of(
+                "/api/navigate",
+                None,
+                br#"{"url": "u", "session_id": "ghi"}"#
+            ),
+            Some("ghi".to_string())
+        );
+        assert_eq!(session_id_of("/api/navigate", None, b"not json"), None);
+    }
+}
@@ -0,0 +1,689 @@
+// GraphQL
+// `/api/graphql` answers read-only GraphQL queries over the automation state:
+// sessions with the runs (jobs) made in them, runs with the data they extracted,
+// the caller's metrics and the learned success patterns. Queries pick the fields
+// they need and nest relations, so a dashboard gets everything in one request.
+//
+// This is not a spec-compliant GraphQL server. It understands only the subset
+// the README documents: one named or anonymous query with `$variables` (their
+// declared types are not checked), aliases, string/integer/boolean/list
+// arguments and nested selections. There is no schema or introspection, and
+// fragments, directives, comments, default values, mutations and subscriptions
+// are rejected.
+
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use super::jobs::{Job, JobStatus};
+use super::quotas::current_client;
+use super::{session_details, tag_filter, AppState};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphqlRequest {
+    /// Query document, e.g. `{ sessions { id runs { id status } } }`
+    pub query: String,
+    /// Values of the query's `$variables`
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub variables: Option<Map<String, Value>>,
+    /// Name of the operation to run; documents hold a single one
+    #[serde(default)]
+    pub operation_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct GraphqlError {
+    message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    path: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct GraphqlResponse {
+    data: Option<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<GraphqlError>,
+}
+
+/// A selected field: `alias: name(args) { selection }`
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    alias: Option<String>,
+    name: String,
+    args: Map<String, Value>,
+    selection: Vec<Field>,
+}
+
+impl Field {
+    /// Key of the field in the response
+    fn key(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    fn selects(&self, name: &str) -> bool {
+        self.selection.iter().any(|field| field.name == name)
+    }
+
+    fn str_arg(&self, name: &str) -> Result<Option<&str>, String> {
+        match self.args.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value)),
+            Some(_) => Err(format!(
+                "Argument {} of {} must be a string",
+                name, self.name
+            )),
+        }
+    }
+
+    fn count_arg(&self, name: &str) -> Result<Option<usize>, String> {
+        match self.args.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .map(|count| Some(count as usize))
+                .ok_or_else(|| {
+                    format!(
+                        "Argument {} of {} must be a non-negative integer",
+                        name, self.name
+                    )
+                }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(char),
+    Name(String),
+    Int(i64),
+    Str(String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() || c == ',' => {
+                chars.next();
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' | ':' | '$' | '!' => {
+                tokens.push(Token::Punct(c));
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        None | Some('\n') => return Err("Unterminated string".to_string()),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => value.push(c),
+                            _ => return Err("Only \\\" and \\\\ escapes are supported".to_string()),
+                        },
+                        Some(c) => value.push(c),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '-') {
+                    number.push(c);
+                }
+                let value = number
+                    .parse()
+                    .map_err(|_| format!("Invalid integer {}", number))?;
+                tokens.push(Token::Int(value));
+            }
+            c if c == '_' || c.is_ascii_alphabetic() => {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| *c == '_' || c.is_ascii_alphanumeric()) {
+                    name.push(c);
+                }
+                tokens.push(Token::Name(name));
+            }
+            '.' | '@' | '#' | '=' => {
+                return Err(format!(
+                    "'{}' is not supported: fragments, directives, comments and default \
+                     values are outside the supported query subset",
+                    c
+                ))
+            }
+            c => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    variables: &'a Map<String, Value>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        if self.peek() == Some(&Token::Punct(punct)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}', found {}", punct, self.describe()))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Name(_)) => match self.next() {
+                Some(Token::Name(name)) => Ok(name),
+                _ => unreachable!(),
+            },
+            _ => Err(format!("Expected a name, found {}", self.describe())),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.peek() {
+            None => "end of query".to_string(),
+            Some(Token::Punct(c)) => format!("'{}'", c),
+            Some(Token::Name(name)) => format!("'{}'", name),
+            Some(Token::Int(value)) => value.to_string(),
+            Some(Token::Str(value)) => format!("\"{}\"", value),
+        }
+    }
+
+    /// The operation's selection; `operation_name` must name it if given
+    fn document(&mut self, operation_name: Option<&str>) -> Result<Vec<Field>, String> {
+        let mut name = None;
+        if let Some(Token::Name(keyword)) = self.peek() {
+            if keyword != "query" {
+                return Err(format!(
+                    "Only query operations are supported, found '{}'",
+                    keyword
+                ));
+            }
+            self.pos += 1;
+            if let Some(Token::Name(_)) = self.peek() {
+                name = Some(self.name()?);
+            }
+            if self.eat('(') {
+                while !self.eat(')') {
+                    self.variable_definition()?;
+                }
+            }
+        }
+        if let Some(wanted) = operation_name {
+            if name.as_deref() != Some(wanted) {
+                return Err(format!("Unknown operation named {}", wanted));
+            }
+        }
+        let selection = self.selection_set()?;
+        if self.peek().is_some() {
+            return Err("Only one operation per document is supported".to_string());
+        }
+        Ok(selection)
+    }
+
+    /// `$name: Type`; the type is skipped, not checked against the variable's value
+    fn variable_definition(&mut self) -> Result<(), String> {
+        self.expect('$')?;
+        self.name()?;
+        self.expect(':')?;
+        if self.eat('[') {
+            self.name()?;
+            self.eat('!');
+            self.expect(']')?;
+        } else {
+            self.name()?;
+        }
+        self.eat('!');
+        Ok(())
+    }
+
+    fn selection_set(&mut self) -> Result<Vec<Field>, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        while !self.eat('}') {
+            fields.push(self.field()?);
+        }
+        if fields.is_empty() {
+            return Err("Selection sets must select at least one field".to_string());
+        }
+        Ok(fields)
+    }
+
+    fn field(&mut self) -> Result<Field, String> {
+        let mut alias = None;
+        let mut name = self.name()?;
+        if self.eat(':') {
+            alias = Some(name);
+            name = self.name()?;
+        }
+        let mut args = Map::new();
+        if self.eat('(') {
+            while !self.eat(')') {
+                let arg = self.name()?;
+                self.expect(':')?;
+                let value = self.value()?;
+                args.insert(arg, value);
+            }
+        }
+        let selection = if self.peek() == Some(&Token::Punct('{')) {
+            self.selection_set()?
+        } else {
+            Vec::new()
+        };
+        Ok(Field {
+            alias,
+            name,
+            args,
+            selection,
+        })
+    }
+
+    /// A string, integer, boolean, null, list or `$variable`
+    fn value(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Punct('$')) => {
+                let name = self.name()?;
+                self.variables
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| format!("Variable ${} is not provided", name))
+            }
+            Some(Token::Int(value)) => Ok(Value::from(value)),
+            Some(Token::Str(value)) => Ok(Value::String(value)),
+            Some(Token::Name(name)) => match name.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "null" => Ok(Value::Null),
+                _ => Err(format!("Expected a value, found '{}'", name)),
+            },
+            Some(Token::Punct('[')) => {
+                let mut items = Vec::new();
+                while !self.eat(']') {
+                    items.push(self.value()?);
+                }
+                Ok(Value::Array(items))
+            }
+            _ => {
+                self.pos -= 1;
+                Err(format!("Expected a value, found {}", self.describe()))
+            }
+        }
+    }
+}
+
+/// The top-level selection of `query` with `variables` substituted
+fn parse(
+    query: &str,
+    operation_name: Option<&str>,
+    variables: &Map<String, Value>,
+) -> Result<Vec<Field>, String> {
+    Parser {
+        tokens: tokenize(query)?,
+        pos: 0,
+        variables,
+    }
+    .document(operation_name)
+}
+
+/// Apply `field`'s `offset`/`limit` and selection to a resolved value
+fn project(value: Value, field: &Field) -> Result<Value, String> {
+    match value {
+        Value::Array(items) => {
+            let offset = field.count_arg("offset")?.unwrap_or(0);
+            let limit = field.count_arg("limit")?.unwrap_or(usize::MAX);
+            items
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(|item| select(item, field))
+                .collect::<Result<_, _>>()
+                .map(Value::Array)
+        }
+        value => select(value, field),
+    }
+}
+
+fn select(value: Value, field: &Field) -> Result<Value, String> {
+    if field.selection.is_empty() {
+        // Without a selection an object field answers with all of its fields
+        return Ok(value);
+    }
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::Array(_) => project(value, field),
+        Value::Object(mut object) => {
+            let mut selected = Map::new();
+            for child in &field.selection {
+                if let Some(arg) = child
+                    .args
+                    .keys()
+                    .find(|arg| *arg != "limit" && *arg != "offset")
+                {
+                    return Err(format!("Unknown argument {} of {}", arg, child.name));
+                }
+                let value = object.remove(&child.name).unwrap_or(Value::Null);
+                selected.insert(child.key().to_string(), project(value, child)?);
+            }
+            Ok(Value::Object(selected))
+        }
+        _ => Err(format!("{} has no fields to select", field.name)),
+    }
+}
+
+/// A job as a run, with the data and search results it extracted lifted to the top
+fn run_view(job: Job) -> Value {
+    let mut run = serde_json::to_value(&job).unwrap_or_default();
+    for key in ["extracted_data", "search_results"] {
+        let found = job
+            .result
+            .as_ref()
+            .and_then(|result| find_key(result, key))
+            .cloned();
+        run[key] = found.unwrap_or(Value::Null);
+    }
+    run
+}
+
+fn find_key<'v>(value: &'v Value, key: &str) -> Option<&'v Value> {
+    match value {
+        Value::Object(object) => object
+            .get(key)
+            .filter(|found| !found.is_null())
+            .or_else(|| object.values().find_map(|value| find_key(value, key))),
+        Value::Array(items) => items.iter().find_map(|value| find_key(value, key)),
+        _ => None,
+    }
+}
+
+fn runs_of(jobs: &[Job], session_id: &str) -> Value {
+    Value::Array(
+        jobs.iter()
+            .filter(|job| job.request.get("session_id").and_then(Value::as_str) == Some(session_id))
+            .cloned()
+            .map(run_view)
+            .collect(),
+    )
+}
+
+/// Resolve a root field to the JSON its selection is applied to
+async fn resolve(state: &AppState, field: &Field) -> Result<Value, String> {
+    let allowed: &[&str] = match field.name.as_str() {
+        "sessions" => &["tag"],
+        "session" | "run" => &["id"],
+        "runs" => &["status", "kind", "session_id"],
+        "patterns" => &["domain"],
+        _ => &[],
+    };
+    if let Some(arg) = field
+        .args
+        .keys()
+        .find(|arg| !allowed.contains(&arg.as_str()) && *arg != "limit" && *arg != "offset")
+    {
+        return Err(format!("Unknown argument {} of {}", arg, field.name));
+    }
+
+    match field.name.as_str() {
+        "sessions" | "session" => {
+            let ids = if field.name == "session" {
+                vec![field
+                    .str_arg("id")?
+                    .ok_or("Argument id of session is required")?
+                    .to_string()]
+            } else {
+                let tags = match field.args.get("tag") {
+                    None | Some(Value::Null) => Vec::new(),
+                    Some(Value::String(tag)) => vec![tag.clone()],
+                    Some(Value::Array(tags)) => tags
+                        .iter()
+                        .map(|tag| tag.as_str().map(str::to_string))
+                        .collect::<Option<_>>()
+                        .ok_or("Argument tag of sessions must be a list of strings")?,
+                    Some(_) => {
+                        return Err("Argument tag of sessions must be a list of strings".to_string())
+                    }
+                };
+                state
+                    .session_manager
+                    .find_sessions(&tag_filter(tags))
+                    .await
+                    .into_iter()
+                    .map(|info| info.id)
+                    .collect()
+            };
+            let jobs = if field.selects("runs") {
+                state.jobs.list(None).await
+            } else {
+                Vec::new()
+            };
+            let mut sessions = Vec::new();
+            for id in ids {
+                let Some(session) = state.session_manager.get_session(&id).await else {
+                    continue;
+                };
+                let mut details = session_details(&*session.read().await);
+                if field.selects("runs") {
+                    details["runs"] = runs_of(&jobs, &id);
+                }
+                sessions.push(details);
+            }
+            if field.name == "session" {
+                Ok(sessions.pop().unwrap_or(Value::Null))
+            } else {
+                Ok(Value::Array(sessions))
+            }
+        }
+        "runs" => {
+            let status: Option<JobStatus> = match field.str_arg("status")? {
+                None => None,
+                Some(value) => Some(
+                    serde_json::from_value(Value::String(value.to_lowercase()))
+                        .map_err(|_| format!("Unknown job status: {}", value))?,
+                ),
+            };
+            let kind = field.str_arg("kind")?.map(str::to_lowercase);
+            let session_id = field.str_arg("session_id")?;
+            let runs = state
+                .jobs
+                .list(status)
+                .await
+                .into_iter()
+                .map(run_view)
+                .filter(|run| kind.as_deref().is_none_or(|kind| run["kind"] == kind))
+                .filter(|run| session_id.is_none_or(|id| run["request"]["session_id"] == id))
+                .collect();
+            Ok(Value::Array(runs))
+        }
+        "run" => {
+            let id = field
+                .str_arg("id")?
+                .ok_or("Argument id of run is required")?;
+            Ok(state
+                .jobs
+                .get(id)
+                .await
+                .map(run_view)
+                .unwrap_or(Value::Null))
+        }
+        "metrics" => {
+            let client = current_client();
+            let jobs = state.jobs.list(None).await;
+            let mut by_status: HashMap<String, usize> = HashMap::new();
+            for job in &jobs {
+                let status = serde_json::to_value(job.status).unwrap_or_default();
+                *by_status
+                    .entry(status.as_str().unwrap_or_default().to_string())
+                    .or_default() += 1;
+            }
+            // Tool statistics only once a browser exists; a query shouldn't launch one
+            let tools = if state.tool_registry.initialized().await {
+                match state.tool_registry.get().await {
+                    Ok(registry) => {
+                        serde_json::to_value(registry.get_all_performance_stats().await)
+                            .unwrap_or_default()
+                    }
+                    Err(_) => Value::Null,
+                }
+            } else {
+                Value::Null
+            };
+            Ok(serde_json::json!({
+                "sessions": state.session_manager.find_sessions(&[]).await.len(),
+                "runs": jobs.len(),
+                "runs_by_status": by_status,
+                "browser_pool_size": state.browser_pool.size().await,
+                "quota": state.quotas.usage(&client).await,
+                "usage": state.audit.usage().await.remove(&client),
+                "tools": tools,
+            }))
+        }
+        "patterns" => {
+            let learning = state.learning.current().await;
+            let bundle = learning
+                .pattern_recognizer
+                .read()
+                .await
+                .export_bundle(field.str_arg("domain")?);
+            Ok(serde_json::to_value(bundle.patterns).unwrap_or_default())
+        }
+        name => Err(format!("Unknown field {} on Query", name)),
+    }
+}
+
+/// Run a read-only GraphQL query over sessions, runs, metrics and learned patterns
+#[utoipa::path(
+    post,
+    path = "/api/graphql",
+    tag = "graphql",
+    request_body = GraphqlRequest,
+    responses(
+        (status = 200, description = "`data` with the selected fields and any field `errors`", body = Object),
+        (status = 400, description = "The query could not be parsed", body = Object),
+    )
+)]
+pub async fn graphql(State(state): State<AppState>, Json(req): Json<GraphqlRequest>) -> Response {
+    let variables = req.variables.unwrap_or_default();
+    let fields = match parse(&req.query, req.operation_name.as_deref(), &variables) {
+        Ok(fields) => fields,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(GraphqlResponse {
+                    data: None,
+                    errors: vec![GraphqlError {
+                        message,
+                        path: Vec::new(),
+                    }],
+                }),
+            )
+                .into_response()
+        }
+    };
+
+    let mut data = Map::new();
+    let mut errors = Vec::new();
+    for field in &fields {
+        let value = match resolve(&state, field).await {
+            Ok(value) => project(value, field),
+            Err(message) => Err(message),
+        };
+        match value {
+            Ok(value) => {
+                data.insert(field.key().to_string(), value);
+            }
+            Err(message) => {
+                errors.push(GraphqlError {
+                    message,
+                    path: vec![field.key().to_string()],
+                });
+                data.insert(field.key().to_string(), Value::Null);
+            }
+        }
+    }
+    Json(GraphqlResponse {
+        data: Some(Value::Object(data)),
+        errors,
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_project() {
+        let variables = serde_json::json!({"n": 1, "tags": ["owner:ci"]})
+            .as_object()
+            .unwrap()
+            .clone();
+        let fields = parse(
+            r#"query Dashboard($n: Int!, $tags: [String!]) {
+                recent: runs(limit: $n, status: "completed") { id result { url } }
+                sessions(tag: $tags) { id }
+            }"#,
+            Some("Dashboard"),
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(fields.len(), 2);
+        let runs = &fields[0];
+        assert_eq!(runs.key(), "recent");
+        assert_eq!(runs.args["limit"], 1);
+        assert_eq!(runs.args["status"], "completed");
+        assert_eq!(fields[1].args["tag"], serde_json::json!(["owner:ci"]));
+
+        let value = serde_json::json!([
+            {"id": "a", "kind": "tool", "result": {"url": "https://a", "data": 1}},
+            {"id": "b", "kind": "tool", "result": null},
+        ]);
+        assert_eq!(
+            project(value, runs).unwrap(),
+            serde_json::json!([{"id": "a", "result": {"url": "https://a"}}])
+        );
+    }
+
+    #[test]
+    fn test_unsupported_queries_rejected() {
+        let none = Map::new();
+        assert!(parse("mutation { x }", None, &none).is_err());
+        assert!(parse("{ runs { ...Fields } }", None, &none).is_err());
+        assert!(parse("{ runs @skip(if: true) { id } }", None, &none).is_err());
+        assert!(parse("query A($n: Int = 5) { runs { id } }", None, &none).is_err());
+        assert!(parse("{ runs(id: $missing) { id } }", None, &none).is_err());
+        assert!(parse("{ runs { id }", None, &none).is_err());
+        assert!(parse("query A { runs { id } }", Some("B"), &none).is_err());
+
+        let field = &parse("{ run { id { deeper } } }", None, &none).unwrap()[0];
+        assert!(project(serde_json::json!({"id": "a"}), field).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+// Idempotency keys
+// POSTs to `/api/tools/execute` and `/api/workflow` may carry an `Idempotency-Key`
+// header. The first request with a key runs and its response is kept; a retry with
+// the same key and body gets that response replayed rather than clicking or
+// submitting again. A retry arriving while the first request still runs is
+// answered 409, and reusing a key for a different body 422.
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Json, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use super::quotas::current_client;
+use super::tenants;
+use super::{ApiResponse, AppState, ErrorCode};
+
+/// Header carrying the client's idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header marking a response as a replay of an earlier one
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Endpoints whose POSTs honour idempotency keys
+const IDEMPOTENT_PATHS: &[&str] = &["/api/tools/execute", "/api/workflow"];
+
+/// Longest key accepted
+const MAX_KEY_LEN: usize = 255;
+
+/// Largest request or response body buffered for a keyed request
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Keys remembered at once; the oldest responses are dropped first
+const MAX_ENTRIES: usize = 10_000;
+
+/// Response kept for replay
+#[derive(Debug, Clone)]
+struct StoredResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl StoredResponse {
+    fn replay(&self) -> Response {
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response
+            .headers_mut()
+            .insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+#[derive(Debug)]
+enum Entry {
+    Running {
+        fingerprint: u64,
+    },
+    Done {
+        fingerprint: u64,
+        stored_at: Instant,
+        response: StoredResponse,
+    },
+}
+
+impl Entry {
+    fn fingerprint(&self) -> u64 {
+        match self {
+            Entry::Running { fingerprint } | Entry::Done { fingerprint, .. } => *fingerprint,
+        }
+    }
+}
+
+/// What to do with a keyed request
+#[derive(Debug)]
+enum Lookup {
+    /// First time the key is seen: run the request
+    Run,
+    Replay(StoredResponse),
+    /// The first request with the key has not finished yet
+    InProgress,
+    /// The key was used for a different request
+    Mismatch,
+}
+
+/// Responses of keyed requests, kept for `ttl`
+pub struct ReplayCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl ReplayCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keys are kept for `RAINBOW_IDEMPOTENCY_TTL_SECS`, by default a day
+    pub fn from_env() -> Self {
+        let ttl = std::env::var("RAINBOW_IDEMPOTENCY_TTL_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(24 * 60 * 60);
+        Self::new(Duration::from_secs(ttl))
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Claim `key` for a request with `fingerprint`, or say why it cannot run
+    fn begin(&self, key: &str, fingerprint: u64) -> Lookup {
+        let mut entries = self.entries();
+        let ttl = self.ttl;
+        entries.retain(|_, entry| match entry {
+            Entry::Running { .. } => true,
+            Entry::Done { stored_at, .. } => stored_at.elapsed() < ttl,
+        });
+        match entries.get(key) {
+            Some(entry) if entry.fingerprint() != fingerprint => Lookup::Mismatch,
+            Some(Entry::Running { .. }) => Lookup::InProgress,
+            Some(Entry::Done { response, .. }) => Lookup::Replay(response.clone()),
+            None => {
+                if entries.len() >= MAX_ENTRIES {
+                    let oldest = entries
+                        .iter()
+                        .filter_map(|(key, entry)| match entry {
+                            Entry::Done { stored_at, .. } => Some((key.clone(), *stored_at)),
+                            Entry::Running { .. } => None,
+                        })
+                        .min_by_key(|(_, stored_at)| *stored_at);
+                    if let Some((oldest, _)) = oldest {
+                        entries.remove(&oldest);
+                    }
+                }
+                entries.insert(key.to_string(), Entry::Running { fingerprint });
+                Lookup::Run
+            }
+        }
+    }
+
+    fn complete(&self, key: &str, fingerprint: u64, response: StoredResponse) {
+        self.entries().insert(
+            key.to_string(),
+            Entry::Done {
+                fingerprint,
+                stored_at: Instant::now(),
+                response,
+            },
+        );
+    }
+
+    /// Forget a key whose request never produced a response
+    fn abandon(&self, key: &str) {
+        let mut entries = self.entries();
+        if matches!(entries.get(key), Some(Entry::Running { .. })) {
+            entries.remove(key);
+        }
+    }
+}
+
+/// Releases a claimed key if the request is dropped before it answers, e.g. when
+/// the client disconnects, so a retry can run it
+struct Claim {
+    cache: Arc<ReplayCache>,
+    key: String,
+    completed: bool,
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.cache.abandon(&self.key);
+        }
+    }
+}
+
+fn fingerprint(path: &str, body: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    body.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn error(status: StatusCode, code: ErrorCode, message: String) -> Response {
+    (status, Json(ApiResponse::<()>::error(code, message))).into_response()
+}
+
+/// Replay the response of an earlier request with the same `Idempotency-Key`
+pub(super) async fn replay(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    if request.method() != axum::http::Method::POST || !IDEMPOTENT_PATHS.contains(&path.as_str()) {
+        return next.run(request).await;
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LEN => key.to_string(),
+        _ => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidRequest,
+                format!(
+                    "Idempotency-Key must be 1 to {} visible ASCII characters",
+                    MAX_KEY_LEN
+                ),
+            )
+        }
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            return error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )
+        }
+    };
+    // Keys are the caller's own: another client reusing one runs independently
+    let scoped = format!("{}|{}", tenants::client_key(&current_client()), key);
+    let fingerprint = fingerprint(&path, &body);
+
+    match state.idempotency.begin(&scoped, fingerprint) {
+        Lookup::Run => {}
+        Lookup::Replay(response) => {
+            info!("Replaying response for idempotency key {} on {}", key, path);
+            return response.replay();
+        }
+        Lookup::InProgress => {
+            return error(
+                StatusCode::CONFLICT,
+                ErrorCode::Conflict,
+                format!("A request with idempotency key {} is still running", key),
+            )
+        }
+        Lookup::Mismatch => {
+            return error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorCode::InvalidRequest,
+                format!(
+                    "Idempotency key {} was already used for a different request",
+                    key
+                ),
+            )
+        }
+    }
+
+    let mut claim = Claim {
+        cache: state.idempotency.clone(),
+        key: scoped,
+        completed: false,
+    };
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            // The request ran but its answer cannot be kept; a retry runs it again
+            warn!("Response for idempotency key {} not kept: {}", key, e);
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::Internal,
+                e.to_string(),
+            );
+        }
+    };
+    debug!("Keeping response for idempotency key {} on {}", key, path);
+    claim.cache.complete(
+        &claim.key,
+        fingerprint,
+        StoredResponse {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+        },
+    );
+    claim.completed = true;
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(body: &'static str) -> StoredResponse {
+        StoredResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(body.as_bytes()),
+        }
+    }
+
+    #[test]
+    fn replays_completed_requests_with_the_same_body() {
+        let cache = ReplayCache::new(Duration::from_secs(60));
+        let click = fingerprint("/api/tools/execute", br#"{"tool_name":"click"}"#);
+        let other = fingerprint("/api/tools/execute", br#"{"tool_name":"type_text"}"#);
+
+        assert!(matches!(cache.begin("k1", click), Lookup::Run));
+        assert!(matches!(cache.begin("k1", click), Lookup::InProgress));
+        cache.complete("k1", click, stored("clicked"));
+
+        match cache.begin("k1", click) {
+            Lookup::Replay(response) => {
+                let replayed = response.replay();
+                assert_eq!(replayed.headers()[REPLAYED_HEADER], "true");
+                assert_eq!(response.body, Bytes::from_static(b"clicked"));
+            }
+            other => panic!("expected a replay, got {:?}", other),
+        }
+        assert!(matches!(cache.begin("k1", other), Lookup::Mismatch));
+        assert!(matches!(cache.begin("k2", other), Lookup::Run));
+    }
+
+    #[test]
+    fn abandoned_and_expired_keys_run_again() {
+        let cache = ReplayCache::new(Duration::ZERO);
+        let fingerprint = fingerprint("/api/workflow", b"{}");
+
+        assert!(matches!(cache.begin("k1", fingerprint), Lookup::Run));
+        cache.abandon("k1");
+        assert!(matches!(cache.begin("k1", fingerprint), Lookup::Run));
+
+        cache.complete("k1", fingerprint, stored("done"));
+        cache.abandon("k1");
+        assert!(matches!(cache.begin("k1", fingerprint), Lookup::Run));
+    }
+}
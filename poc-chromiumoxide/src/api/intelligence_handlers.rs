@@ -2,19 +2,21 @@
 // Provides advanced AI-driven automation with learning and adaptation capabilities
 
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Json, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Instant;
 use tracing::{error, info};
+use utoipa::{IntoParams, ToSchema};
 
-use super::AppState;
+use super::browser_handlers::resolve_browser;
+use super::{ApiError, AppState, ErrorCode};
 use crate::intelligence::{
-    ActionRecommendation, IntelligenceAnalysis, IntelligenceConfig, IntelligenceService,
-    PageContext, ViewportInfo,
+    ActionRecommendation, DomainProfile, ElementEmbeddingIndex, IntelligenceAnalysis,
+    IntelligenceConfig, IntelligenceService, PageContext, PatternBundle, ViewportInfo,
 };
 
 /// Enhanced error type for intelligence operations
@@ -47,15 +49,16 @@ pub enum IntelligenceApiError {
 }
 
 /// Enhanced response wrapper for intelligence operations
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(IntelligenceEnvelope = IntelligenceResponse<serde_json::Value>)]
 pub struct IntelligenceResponse<T> {
     pub success: bool,
     pub data: Option<T>,
-    pub error: Option<String>,
+    pub error: Option<ApiError>,
     pub metadata: IntelligenceResponseMetadata,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct IntelligenceResponseMetadata {
     pub processing_time_ms: u64,
     pub analysis_depth: String,
@@ -75,17 +78,28 @@ impl<T> IntelligenceResponse<T> {
         }
     }
 
-    pub fn error(error: String, metadata: IntelligenceResponseMetadata) -> Self {
+    pub fn error(
+        code: ErrorCode,
+        message: impl Into<String>,
+        metadata: IntelligenceResponseMetadata,
+    ) -> Self {
         Self {
             success: false,
             data: None,
-            error: Some(error),
+            error: Some(ApiError::new(code, message)),
             metadata,
         }
     }
 }
 
 /// Comprehensive intelligence analysis endpoint
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/analyze",
+    tag = "intelligence",
+    request_body = AnalyzeSituationRequest,
+    responses((status = 200, description = "Situation analysis", body = IntelligenceEnvelope))
+)]
 pub async fn analyze_situation(
     State(state): State<AppState>,
     Json(req): Json<AnalyzeSituationRequest>,
@@ -109,6 +123,7 @@ pub async fn analyze_situation(
         return (
             StatusCode::BAD_REQUEST,
             Json(IntelligenceResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -132,6 +147,7 @@ pub async fn analyze_situation(
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(IntelligenceResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     format!("Browser acquisition failed: {}", e),
                     metadata,
                 )),
@@ -144,7 +160,8 @@ pub async fn analyze_situation(
 
     // Create intelligence service with specified configuration
     let config = req.config.unwrap_or_default();
-    let intelligence_service = IntelligenceService::new(config.clone());
+    let intelligence_service =
+        IntelligenceService::with_learning(config.clone(), state.learning.current().await);
 
     // Create page context from request
     let domain = req
@@ -219,6 +236,7 @@ pub async fn analyze_situation(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(IntelligenceResponse::<()>::error(
+                    ErrorCode::Internal,
                     format!("Analysis failed: {}", e),
                     metadata,
                 )),
@@ -229,8 +247,15 @@ pub async fn analyze_situation(
 }
 
 /// Get intelligent action recommendation endpoint
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/recommend",
+    tag = "intelligence",
+    request_body = RecommendActionRequest,
+    responses((status = 200, description = "Recommended action", body = IntelligenceEnvelope))
+)]
 pub async fn recommend_action(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(req): Json<RecommendActionRequest>,
 ) -> impl IntoResponse {
     let start_time = Instant::now();
@@ -249,6 +274,7 @@ pub async fn recommend_action(
         return (
             StatusCode::BAD_REQUEST,
             Json(IntelligenceResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -260,7 +286,8 @@ pub async fn recommend_action(
 
     // Create intelligence service
     let config = req.config.unwrap_or_default();
-    let intelligence_service = IntelligenceService::new(config);
+    let intelligence_service =
+        IntelligenceService::with_learning(config, state.learning.current().await);
 
     // Generate action recommendation from analysis
     match intelligence_service.recommend_action(&req.analysis).await {
@@ -298,6 +325,7 @@ pub async fn recommend_action(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(IntelligenceResponse::<()>::error(
+                    ErrorCode::Internal,
                     format!("Recommendation failed: {}", e),
                     metadata,
                 )),
@@ -308,8 +336,17 @@ pub async fn recommend_action(
 }
 
 /// Learning feedback endpoint - allows the system to learn from results
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/learn",
+    tag = "intelligence",
+    request_body = LearningFeedbackRequest,
+    responses(
+        (status = 200, description = "Feedback was recorded", body = IntelligenceEnvelope),
+    )
+)]
 pub async fn submit_learning_feedback(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(req): Json<LearningFeedbackRequest>,
 ) -> impl IntoResponse {
     let start_time = Instant::now();
@@ -328,6 +365,7 @@ pub async fn submit_learning_feedback(
         return (
             StatusCode::BAD_REQUEST,
             Json(IntelligenceResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -339,7 +377,8 @@ pub async fn submit_learning_feedback(
 
     // Create intelligence service
     let config = req.config.unwrap_or_default();
-    let intelligence_service = IntelligenceService::new(config);
+    let intelligence_service =
+        IntelligenceService::with_learning(config, state.learning.current().await);
 
     // Submit learning feedback
     match intelligence_service
@@ -391,6 +430,7 @@ pub async fn submit_learning_feedback(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(IntelligenceResponse::<()>::error(
+                    ErrorCode::Internal,
                     format!("Learning failed: {}", e),
                     metadata,
                 )),
@@ -401,8 +441,15 @@ pub async fn submit_learning_feedback(
 }
 
 /// Get intelligence service statistics endpoint
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/statistics",
+    tag = "intelligence",
+    request_body = StatisticsRequest,
+    responses((status = 200, description = "Statistics", body = IntelligenceEnvelope))
+)]
 pub async fn get_intelligence_statistics(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(req): Json<StatisticsRequest>,
 ) -> impl IntoResponse {
     let start_time = Instant::now();
@@ -412,7 +459,8 @@ pub async fn get_intelligence_statistics(
 
     // Create intelligence service
     let config = req.config.unwrap_or_default();
-    let intelligence_service = IntelligenceService::new(config);
+    let intelligence_service =
+        IntelligenceService::with_learning(config, state.learning.current().await);
 
     // Get statistics
     match intelligence_service.get_statistics().await {
@@ -446,6 +494,7 @@ pub async fn get_intelligence_statistics(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(IntelligenceResponse::<()>::error(
+                    ErrorCode::Internal,
                     format!("Statistics retrieval failed: {}", e),
                     metadata,
                 )),
@@ -456,6 +505,15 @@ pub async fn get_intelligence_statistics(
 }
 
 /// Update intelligence configuration endpoint
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/config",
+    tag = "intelligence",
+    request_body = UpdateConfigRequest,
+    responses(
+        (status = 200, description = "The new configuration", body = IntelligenceEnvelope),
+    )
+)]
 pub async fn update_intelligence_config(
     State(_state): State<AppState>,
     Json(req): Json<UpdateConfigRequest>,
@@ -476,6 +534,7 @@ pub async fn update_intelligence_config(
         return (
             StatusCode::BAD_REQUEST,
             Json(IntelligenceResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -513,9 +572,266 @@ pub async fn update_intelligence_config(
     Json(IntelligenceResponse::success(response_data, metadata)).into_response()
 }
 
+/// Export learned success patterns as a portable bundle for another instance
+#[utoipa::path(
+    get,
+    path = "/api/intelligence/patterns/export",
+    tag = "intelligence",
+    params(ExportPatternsQuery),
+    responses((status = 200, description = "Pattern bundle", body = Object))
+)]
+pub async fn export_patterns(
+    State(state): State<AppState>,
+    Query(query): Query<ExportPatternsQuery>,
+) -> impl IntoResponse {
+    let learning = state.learning.current().await;
+    let mut bundle = learning
+        .pattern_recognizer
+        .read()
+        .await
+        .export_bundle(query.domain.as_deref());
+    bundle.description = query.description;
+    info!("Exported {} success patterns", bundle.patterns.len());
+    Json(bundle)
+}
+
+/// Import a pattern bundle exported by another instance
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/patterns/import",
+    tag = "intelligence",
+    request_body(content = Object, description = "Bundle exported by `/api/intelligence/patterns/export`"),
+    responses((status = 200, description = "Import summary", body = IntelligenceEnvelope))
+)]
+pub async fn import_patterns(
+    State(state): State<AppState>,
+    Json(bundle): Json<PatternBundle>,
+) -> impl IntoResponse {
+    let start_time = Instant::now();
+    let learning = state.learning.current().await;
+    let result = learning
+        .pattern_recognizer
+        .write()
+        .await
+        .import_bundle(bundle);
+
+    let metadata = management_metadata("pattern_import", "pattern_recognition", start_time);
+    match result {
+        Ok(summary) => {
+            if summary.added + summary.merged > 0 {
+                learning.mark_dirty();
+            }
+            Json(IntelligenceResponse::success(summary, metadata)).into_response()
+        }
+        Err(e) => {
+            error!("Pattern import rejected: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(IntelligenceResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    IntelligenceApiError::PatternRecognitionError(e.to_string()).to_string(),
+                    metadata,
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Elements of the current page ranked by embedding similarity to a description
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/elements/match",
+    tag = "intelligence",
+    request_body = MatchElementsRequest,
+    responses((status = 200, description = "Ranked elements", body = IntelligenceEnvelope))
+)]
+pub async fn match_elements(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<MatchElementsRequest>,
+) -> Response {
+    let start_time = Instant::now();
+    let browser = match resolve_browser(&state, &headers, req.session_id.as_deref()).await {
+        Ok(browser) => browser,
+        Err(response) => return response,
+    };
+
+    let result = async {
+        let index = ElementEmbeddingIndex::from_page(&browser, state.embeddings.clone()).await?;
+        index.search(&req.query, req.limit.unwrap_or(5)).await
+    }
+    .await;
+
+    let metadata = management_metadata(
+        state.embeddings.provider_name(),
+        "element_index",
+        start_time,
+    );
+    match result {
+        Ok(matches) => {
+            info!(
+                "Matched {} elements for '{}' in {}ms",
+                matches.len(),
+                req.query,
+                start_time.elapsed().as_millis()
+            );
+            Json(IntelligenceResponse::success(matches, metadata)).into_response()
+        }
+        Err(e) => {
+            error!("Element matching failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(IntelligenceResponse::<()>::error(
+                    ErrorCode::Internal,
+                    IntelligenceApiError::AnalysisError(e.to_string()).to_string(),
+                    metadata,
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Recent anomalies raised by action outcomes, newest first
+#[utoipa::path(
+    get,
+    path = "/api/intelligence/anomalies",
+    tag = "intelligence",
+    params(AnomaliesQuery),
+    responses((status = 200, description = "Anomalies", body = IntelligenceEnvelope))
+)]
+pub async fn list_anomalies(
+    State(state): State<AppState>,
+    Query(query): Query<AnomaliesQuery>,
+) -> impl IntoResponse {
+    let start_time = Instant::now();
+    let events = state
+        .anomalies
+        .read()
+        .await
+        .events(query.origin.as_deref(), query.limit.unwrap_or(50));
+    let metadata = management_metadata("anomalies", "anomaly_detection", start_time);
+    Json(IntelligenceResponse::success(events, metadata))
+}
+
+/// Per-origin adaptation profiles, applied after navigating to their origin
+#[utoipa::path(
+    get,
+    path = "/api/intelligence/domains",
+    tag = "intelligence",
+    responses((status = 200, description = "Profiles by origin", body = IntelligenceEnvelope))
+)]
+pub async fn list_domain_profiles(State(state): State<AppState>) -> impl IntoResponse {
+    let start_time = Instant::now();
+    let profiles = state
+        .learning
+        .current()
+        .await
+        .adaptation_manager
+        .read()
+        .await
+        .profiles();
+    let metadata = management_metadata("domain_profiles", "adaptation_manager", start_time);
+    Json(IntelligenceResponse::success(profiles, metadata))
+}
+
+/// Add or replace the adaptation profile of an origin
+#[utoipa::path(
+    post,
+    path = "/api/intelligence/domains",
+    tag = "intelligence",
+    request_body(content = Object, description = "Adaptation profile of an origin"),
+    responses((status = 200, description = "Profile was stored", body = IntelligenceEnvelope))
+)]
+pub async fn save_domain_profile(
+    State(state): State<AppState>,
+    Json(profile): Json<DomainProfile>,
+) -> impl IntoResponse {
+    let start_time = Instant::now();
+    let learning = state.learning.current().await;
+    let result = learning
+        .adaptation_manager
+        .write()
+        .await
+        .set_profile(profile);
+
+    let metadata = management_metadata("domain_profiles", "adaptation_manager", start_time);
+    match result {
+        Ok(profile) => {
+            learning.mark_dirty();
+            Json(IntelligenceResponse::success(profile, metadata)).into_response()
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(IntelligenceResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                IntelligenceApiError::AdaptationError(e.to_string()).to_string(),
+                metadata,
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Remove the adaptation profile of an origin
+#[utoipa::path(
+    delete,
+    path = "/api/intelligence/domains",
+    tag = "intelligence",
+    params(DomainProfileQuery),
+    responses(
+        (status = 200, description = "Profile was removed", body = IntelligenceEnvelope),
+    )
+)]
+pub async fn delete_domain_profile(
+    State(state): State<AppState>,
+    Query(query): Query<DomainProfileQuery>,
+) -> impl IntoResponse {
+    let start_time = Instant::now();
+    let learning = state.learning.current().await;
+    let removed = learning
+        .adaptation_manager
+        .write()
+        .await
+        .remove_profile(&query.origin);
+
+    let metadata = management_metadata("domain_profiles", "adaptation_manager", start_time);
+    match removed {
+        Some(profile) => {
+            learning.mark_dirty();
+            Json(IntelligenceResponse::success(profile, metadata)).into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(IntelligenceResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("No domain profile for {}", query.origin),
+                metadata,
+            )),
+        )
+            .into_response(),
+    }
+}
+
+fn management_metadata(
+    operation: &str,
+    component: &str,
+    start_time: Instant,
+) -> IntelligenceResponseMetadata {
+    IntelligenceResponseMetadata {
+        processing_time_ms: start_time.elapsed().as_millis() as u64,
+        analysis_depth: operation.to_string(),
+        confidence: None,
+        components_used: vec![component.to_string()],
+        total_time_ms: start_time.elapsed().as_millis() as u64,
+        intelligence_version: "1.0.0".to_string(),
+    }
+}
+
 // Request/Response types for Intelligence API
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[allow(dead_code)]
 pub struct AnalyzeSituationRequest {
     pub user_intent: String,
@@ -527,36 +843,72 @@ pub struct AnalyzeSituationRequest {
     pub dynamic_content: Option<bool>,
     pub form_elements: Option<u32>,
     pub interactive_elements: Option<u32>,
+    #[schema(value_type = Option<Object>)]
     pub config: Option<IntelligenceConfig>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct RecommendActionRequest {
+    #[schema(value_type = Object)]
     pub analysis: IntelligenceAnalysis,
+    #[schema(value_type = Option<Object>)]
     pub config: Option<IntelligenceConfig>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[allow(dead_code)]
 pub struct LearningFeedbackRequest {
+    #[schema(value_type = Object)]
     pub action_recommendation: ActionRecommendation,
     pub actual_result: String,
     pub success: bool,
     pub execution_time_ms: u64,
     pub additional_context: Option<HashMap<String, serde_json::Value>>,
+    #[schema(value_type = Option<Object>)]
     pub config: Option<IntelligenceConfig>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[allow(dead_code)]
 pub struct StatisticsRequest {
+    #[schema(value_type = Option<Object>)]
     pub config: Option<IntelligenceConfig>,
     pub include_detailed_metrics: Option<bool>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportPatternsQuery {
+    /// Only export patterns learned on this domain (subdomains included)
+    pub domain: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct MatchElementsRequest {
+    /// How a user would refer to the element, e.g. "the sign in button"
+    pub query: String,
+    pub limit: Option<usize>,
+    pub session_id: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnomaliesQuery {
+    pub origin: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DomainProfileQuery {
+    pub origin: String,
+}
+
+#[derive(Deserialize, ToSchema)]
 #[allow(dead_code)]
 pub struct UpdateConfigRequest {
+    #[schema(value_type = Object)]
     pub config: IntelligenceConfig,
     pub apply_immediately: Option<bool>,
 }
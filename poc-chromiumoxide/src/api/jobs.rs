@@ -0,0 +1,753 @@
+// Job queue
+// Commands, workflows and tool calls submitted to `/api/jobs` get a job id
+// right away and wait in a priority queue until one of the workers picks them
+// up. Each job runs as an operation, so its progress doubles as the job's log
+// and can be followed from `/api/operations/:id/events` while it runs.
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Mutex;
+use tokio::sync::{Notify, RwLock};
+use tracing::{info, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use super::auth::{current_principal, with_principal, Principal};
+use super::llm_handlers::{self, ExecuteCommandRequest};
+use super::operations::{self, OperationEvent};
+use super::pagination::PageQuery;
+use super::quotas::{self, current_client};
+use super::tenants::current_tenant;
+use super::workflow_handlers::{self, IntelligentWorkflowRequest, SimpleWorkflowRequest};
+use super::{ApiResponse, AppState, ErrorCode, ExecuteToolRequest};
+use crate::coordination::correlation::{current_correlation_id, with_correlation_id};
+
+/// Finished jobs kept for late readers before the oldest are dropped
+const MAX_FINISHED_JOBS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Natural-language command, as `/api/llm/execute`
+    Command,
+    /// Declarative steps, as `/api/workflow/simple`
+    Workflow,
+    /// Perceive, plan and execute a goal, as `/api/workflow/intelligent`
+    IntelligentWorkflow,
+    /// One tool call, as `/api/tools/execute`
+    Tool,
+}
+
+impl JobKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Workflow => "workflow",
+            Self::IntelligentWorkflow => "intelligent_workflow",
+            Self::Tool => "tool",
+        }
+    }
+
+    /// Whether jobs of this kind spend the caller's LLM budget
+    fn uses_llm(self) -> bool {
+        matches!(self, Self::Command | Self::IntelligentWorkflow)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Parsed request of a job, ready to hand to its endpoint
+enum JobSpec {
+    Command(ExecuteCommandRequest),
+    Workflow(SimpleWorkflowRequest),
+    IntelligentWorkflow(IntelligentWorkflowRequest),
+    Tool(ExecuteToolRequest),
+}
+
+impl JobSpec {
+    fn kind(&self) -> JobKind {
+        match self {
+            Self::Command(_) => JobKind::Command,
+            Self::Workflow(_) => JobKind::Workflow,
+            Self::IntelligentWorkflow(_) => JobKind::IntelligentWorkflow,
+            Self::Tool(_) => JobKind::Tool,
+        }
+    }
+
+    fn parse(kind: JobKind, request: serde_json::Value) -> Result<Self, serde_json::Error> {
+        Ok(match kind {
+            JobKind::Command => Self::Command(serde_json::from_value(request)?),
+            JobKind::Workflow => Self::Workflow(serde_json::from_value(request)?),
+            JobKind::IntelligentWorkflow => {
+                Self::IntelligentWorkflow(serde_json::from_value(request)?)
+            }
+            JobKind::Tool => Self::Tool(serde_json::from_value(request)?),
+        })
+    }
+
+    /// The endpoint's handler, to be awaited by a worker
+    fn run(self, state: AppState) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        match self {
+            Self::Command(req) => Box::pin(async move {
+                llm_handlers::execute_command(State(state), Json(req))
+                    .await
+                    .into_response()
+            }),
+            Self::Workflow(req) => Box::pin(async move {
+                workflow_handlers::execute_simple_workflow(State(state), Json(req))
+                    .await
+                    .into_response()
+            }),
+            Self::IntelligentWorkflow(req) => Box::pin(async move {
+                workflow_handlers::execute_intelligent_workflow(State(state), Json(req))
+                    .await
+                    .into_response()
+            }),
+            Self::Tool(req) => Box::pin(async move {
+                super::execute_tool(State(state), Json(req))
+                    .await
+                    .into_response()
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: String,
+    pub kind: JobKind,
+    /// Higher runs first; equal priorities run in submission order
+    pub priority: i32,
+    pub status: JobStatus,
+    /// Body the job was submitted with
+    pub request: serde_json::Value,
+    /// Subject of the principal that submitted the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submitted_by: Option<String>,
+    /// Tenant of the submitter; only it can see or cancel the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Correlation id of the submitting request, which the job runs under
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Operation the job runs as, once a worker picked it up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    /// HTTP status the endpoint answered with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// Body the endpoint answered with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// Events of the job's operation: started, progress, then completed or failed
+    pub logs: Vec<OperationEvent>,
+}
+
+/// A queued job; the heap's greatest entry is the next to run
+struct Pending {
+    priority: i32,
+    sequence: u64,
+    id: String,
+    spec: JobSpec,
+    principal: Option<Principal>,
+    correlation_id: Option<String>,
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+/// Why a job could not be cancelled
+#[derive(Debug)]
+pub enum CancelError {
+    NotFound,
+    AlreadyStarted(JobStatus),
+}
+
+/// Submitted jobs and the priority queue the workers take them from
+pub struct JobQueue {
+    jobs: RwLock<HashMap<String, Job>>,
+    pending: Mutex<BinaryHeap<Pending>>,
+    finished: RwLock<VecDeque<String>>,
+    sequence: AtomicU64,
+    ready: Notify,
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobQueue {
+    pub fn new() -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            pending: Mutex::new(BinaryHeap::new()),
+            finished: RwLock::new(VecDeque::new()),
+            sequence: AtomicU64::new(0),
+            ready: Notify::new(),
+        }
+    }
+
+    /// Queue a job and return it as submitted
+    async fn submit(
+        &self,
+        kind: JobKind,
+        priority: i32,
+        request: serde_json::Value,
+        spec: JobSpec,
+        principal: Option<Principal>,
+    ) -> Job {
+        let id = uuid::Uuid::new_v4().to_string();
+        let job = Job {
+            id: id.clone(),
+            kind,
+            priority,
+            status: JobStatus::Queued,
+            request,
+            submitted_by: principal.as_ref().map(|p| p.subject.clone()),
+            tenant: principal.as_ref().and_then(|p| p.tenant.clone()),
+            correlation_id: current_correlation_id(),
+            submitted_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            operation_id: None,
+            status_code: None,
+            result: None,
+            logs: Vec::new(),
+        };
+        self.jobs.write().await.insert(id.clone(), job.clone());
+        let sequence = self.sequence.fetch_add(1, AtomicOrdering::Relaxed);
+        self.pending_queue().push(Pending {
+            priority,
+            sequence,
+            id,
+            spec,
+            principal,
+            correlation_id: job.correlation_id.clone(),
+        });
+        self.ready.notify_one();
+        job
+    }
+
+    /// Wait for the highest-priority queued job and mark it running; returns its
+    /// id, spec, submitter and correlation id
+    async fn next(&self) -> (String, JobSpec, Option<Principal>, Option<String>) {
+        loop {
+            while let Some(pending) = self.pop() {
+                let mut jobs = self.jobs.write().await;
+                // Cancelled jobs stay in the heap until they come up
+                let Some(job) = jobs
+                    .get_mut(&pending.id)
+                    .filter(|job| job.status == JobStatus::Queued)
+                else {
+                    continue;
+                };
+                job.status = JobStatus::Running;
+                job.started_at = Some(Utc::now());
+                drop(jobs);
+                // Wake another worker for whatever is still queued
+                if !self.pending_queue().is_empty() {
+                    self.ready.notify_one();
+                }
+                return (
+                    pending.id,
+                    pending.spec,
+                    pending.principal,
+                    pending.correlation_id,
+                );
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    fn pop(&self) -> Option<Pending> {
+        self.pending_queue().pop()
+    }
+
+    fn pending_queue(&self) -> std::sync::MutexGuard<'_, BinaryHeap<Pending>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn attach_operation(&self, id: &str, operation_id: &str) {
+        if let Some(job) = self.jobs.write().await.get_mut(id) {
+            job.operation_id = Some(operation_id.to_string());
+        }
+    }
+
+    /// Store the endpoint's response and the operation's events
+    async fn finish(
+        &self,
+        id: &str,
+        status_code: StatusCode,
+        result: serde_json::Value,
+        logs: Vec<OperationEvent>,
+    ) {
+        if let Some(job) = self.jobs.write().await.get_mut(id) {
+            job.status = if status_code.is_success() {
+                JobStatus::Completed
+            } else {
+                JobStatus::Failed
+            };
+            job.finished_at = Some(Utc::now());
+            job.status_code = Some(status_code.as_u16());
+            job.result = Some(result);
+            job.logs = logs;
+        }
+        self.prune(id).await;
+    }
+
+    /// Cancel a job that no worker has picked up yet
+    pub async fn cancel(&self, id: &str) -> Result<Job, CancelError> {
+        let job = {
+            let mut jobs = self.jobs.write().await;
+            let job = jobs
+                .get_mut(id)
+                .filter(|job| job.tenant == current_tenant())
+                .ok_or(CancelError::NotFound)?;
+            if job.status != JobStatus::Queued {
+                return Err(CancelError::AlreadyStarted(job.status));
+            }
+            job.status = JobStatus::Cancelled;
+            job.finished_at = Some(Utc::now());
+            job.clone()
+        };
+        self.prune(id).await;
+        Ok(job)
+    }
+
+    /// The job, if the current tenant submitted it
+    pub async fn get(&self, id: &str) -> Option<Job> {
+        self.jobs
+            .read()
+            .await
+            .get(id)
+            .filter(|job| job.tenant == current_tenant())
+            .cloned()
+    }
+
+    /// The current tenant's jobs with `status`, or all of them, newest first
+    pub async fn list(&self, status: Option<JobStatus>) -> Vec<Job> {
+        let tenant = current_tenant();
+        let mut jobs: Vec<Job> = self
+            .jobs
+            .read()
+            .await
+            .values()
+            .filter(|job| job.tenant == tenant)
+            .filter(|job| status.is_none_or(|status| job.status == status))
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.submitted_at));
+        jobs
+    }
+
+    async fn prune(&self, id: &str) {
+        let mut finished = self.finished.write().await;
+        finished.push_back(id.to_string());
+        while finished.len() > MAX_FINISHED_JOBS {
+            if let Some(oldest) = finished.pop_front() {
+                self.jobs.write().await.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Start `workers` tasks that run queued jobs one at a time each
+pub(super) fn start_workers(state: &AppState, workers: usize) {
+    for worker in 0..workers {
+        let state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                let (id, spec, principal, correlation_id) = state.jobs.next().await;
+                // Run as the submitter so the job stays within its tenant, and as
+                // part of the request that submitted it
+                with_correlation_id(
+                    correlation_id,
+                    with_principal(
+                        principal.clone(),
+                        run_job(&state, worker, &id, spec, principal),
+                    ),
+                )
+                .await;
+            }
+        });
+    }
+    info!("Job queue started with {} workers", workers);
+}
+
+async fn run_job(
+    state: &AppState,
+    worker: usize,
+    id: &str,
+    spec: JobSpec,
+    principal: Option<Principal>,
+) {
+    let kind = spec.kind();
+    let operation_id = state.operations.start(kind.as_str()).await;
+    state.jobs.attach_operation(id, &operation_id).await;
+    info!("Worker {} running {} job {}", worker, kind.as_str(), id);
+
+    let handler = spec.run(state.clone());
+    let (status, result) = operations::run_operation(
+        state.operations.clone(),
+        operation_id.clone(),
+        principal,
+        handler,
+    )
+    .await;
+    let logs = match state.operations.get(&operation_id).await {
+        Some(operation) => operation.events,
+        None => {
+            warn!("Operation {} of job {} already dropped", operation_id, id);
+            Vec::new()
+        }
+    };
+    state.jobs.finish(id, status, result, logs).await;
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubmitJobRequest {
+    #[serde(rename = "type")]
+    pub kind: JobKind,
+    /// Body of the endpoint the job runs, e.g. `{"command": "..."}` for `command`
+    #[schema(value_type = Object)]
+    pub request: serde_json::Value,
+    /// Higher runs first (default 0)
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct JobSubmitted {
+    job_id: String,
+    status: JobStatus,
+    status_url: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct JobListQuery {
+    /// Only jobs in this status: queued, running, completed, failed or cancelled
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+fn job_not_found(id: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ApiResponse::<()>::error(
+            ErrorCode::NotFound,
+            format!("Job not found: {}", id),
+        )),
+    )
+        .into_response()
+}
+
+/// Queue a command, workflow or tool call and answer with its job id right away
+#[utoipa::path(
+    post,
+    path = "/api/jobs",
+    tag = "jobs",
+    request_body = SubmitJobRequest,
+    responses(
+        (status = 202, description = "Job queued", body = ApiEnvelope),
+        (status = 400, description = "Request does not match the job type", body = ApiEnvelope),
+        (status = 429, description = "Daily LLM budget exceeded", body = ApiEnvelope),
+    )
+)]
+pub async fn submit_job(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitJobRequest>,
+) -> Response {
+    let spec = match JobSpec::parse(req.kind, req.request.clone()) {
+        Ok(spec) => spec,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    format!("Invalid {} job request: {}", req.kind.as_str(), e),
+                )),
+            )
+                .into_response()
+        }
+    };
+    // The budget is checked at submission since workers bypass the quota middleware
+    if req.kind.uses_llm() {
+        if let Err(retry_after) = state.quotas.check_llm_budget(&current_client()).await {
+            return quotas::too_many_requests("Daily LLM budget exceeded".to_string(), retry_after);
+        }
+    }
+
+    let job = state
+        .jobs
+        .submit(
+            req.kind,
+            req.priority,
+            req.request,
+            spec,
+            current_principal(),
+        )
+        .await;
+    info!(
+        "Queued {} job {} with priority {}",
+        job.kind.as_str(),
+        job.id,
+        job.priority
+    );
+    (
+        StatusCode::ACCEPTED,
+        Json(ApiResponse::success(JobSubmitted {
+            status_url: format!("/api/jobs/{}", job.id),
+            job_id: job.id,
+            status: job.status,
+        })),
+    )
+        .into_response()
+}
+
+/// Submitted jobs, newest first; finished ones are kept until 200 more finish
+#[utoipa::path(
+    get,
+    path = "/api/jobs",
+    tag = "jobs",
+    params(JobListQuery, PageQuery),
+    responses((status = 200, description = "Jobs", body = ApiEnvelope))
+)]
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    Query(query): Query<JobListQuery>,
+    page: PageQuery,
+) -> Response {
+    let status = match query.status.as_deref() {
+        None => None,
+        Some(value) => match serde_json::from_value(serde_json::json!(value)) {
+            Ok(status) => Some(status),
+            Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::InvalidRequest,
+                        format!("Unknown job status: {}", value),
+                    )),
+                )
+                    .into_response()
+            }
+        },
+    };
+    page.respond(state.jobs.list(status).await)
+}
+
+/// Status, logs and, once finished, result of a job
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "The job", body = ApiEnvelope),
+        (status = 404, description = "Unknown job", body = ApiEnvelope),
+    )
+)]
+pub async fn get_job(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let Some(mut job) = state.jobs.get(&id).await else {
+        return job_not_found(&id);
+    };
+    // A running job's log is whatever its operation has reported so far
+    if job.status == JobStatus::Running {
+        if let Some(operation_id) = &job.operation_id {
+            if let Some(operation) = state.operations.get(operation_id).await {
+                job.logs = operation.events;
+            }
+        }
+    }
+    Json(ApiResponse::success(job)).into_response()
+}
+
+/// Cancel a job that is still queued
+#[utoipa::path(
+    delete,
+    path = "/api/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "The cancelled job", body = ApiEnvelope),
+        (status = 404, description = "Unknown job", body = ApiEnvelope),
+        (status = 409, description = "Job already started", body = ApiEnvelope),
+    )
+)]
+pub async fn cancel_job(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.jobs.cancel(&id).await {
+        Ok(job) => {
+            info!("Cancelled job {}", id);
+            Json(ApiResponse::success(job)).into_response()
+        }
+        Err(CancelError::NotFound) => job_not_found(&id),
+        Err(CancelError::AlreadyStarted(status)) => (
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::Conflict,
+                format!("Job {} is already {}", id, status.as_str()),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_request() -> serde_json::Value {
+        json!({"tool_name": "get_page_info", "parameters": {}})
+    }
+
+    async fn submit(queue: &JobQueue, priority: i32) -> String {
+        let spec = JobSpec::parse(JobKind::Tool, tool_request()).unwrap();
+        queue
+            .submit(JobKind::Tool, priority, tool_request(), spec, None)
+            .await
+            .id
+    }
+
+    #[tokio::test]
+    async fn runs_higher_priorities_first_then_in_submission_order() {
+        let queue = JobQueue::new();
+        let low = submit(&queue, 0).await;
+        let high = submit(&queue, 5).await;
+        let low_later = submit(&queue, 0).await;
+
+        let mut order = Vec::new();
+        for _ in 0..3 {
+            order.push(queue.next().await.0);
+        }
+        assert_eq!(order, vec![high, low, low_later]);
+        assert_eq!(
+            queue.get(&order[0]).await.unwrap().status,
+            JobStatus::Running
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_jobs_are_skipped() {
+        let queue = JobQueue::new();
+        let cancelled = submit(&queue, 10).await;
+        let kept = submit(&queue, 0).await;
+        queue.cancel(&cancelled).await.unwrap();
+
+        assert_eq!(queue.next().await.0, kept);
+        assert!(matches!(
+            queue.cancel(&kept).await,
+            Err(CancelError::AlreadyStarted(JobStatus::Running))
+        ));
+        assert_eq!(
+            queue.list(Some(JobStatus::Cancelled)).await[0].id,
+            cancelled
+        );
+    }
+
+    #[tokio::test]
+    async fn finished_jobs_keep_result_and_logs() {
+        let queue = JobQueue::new();
+        let id = submit(&queue, 0).await;
+        queue.next().await;
+        queue
+            .finish(
+                &id,
+                StatusCode::BAD_REQUEST,
+                json!({"success": false}),
+                Vec::new(),
+            )
+            .await;
+
+        let job = queue.get(&id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.status_code, Some(400));
+        assert!(job.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn jobs_are_only_visible_to_their_tenant() {
+        let queue = JobQueue::new();
+        let acme = Principal {
+            subject: "acme".to_string(),
+            tenant: Some("acme".to_string()),
+            ..Principal::default()
+        };
+        let spec = JobSpec::parse(JobKind::Tool, tool_request()).unwrap();
+        let id = queue
+            .submit(JobKind::Tool, 0, tool_request(), spec, Some(acme.clone()))
+            .await
+            .id;
+
+        assert!(queue.get(&id).await.is_none());
+        assert!(queue.list(None).await.is_empty());
+        assert!(matches!(
+            queue.cancel(&id).await,
+            Err(CancelError::NotFound)
+        ));
+        let listed = with_principal(Some(acme), queue.list(None)).await;
+        assert_eq!(listed[0].tenant.as_deref(), Some("acme"));
+    }
+
+    #[test]
+    fn rejects_requests_that_do_not_match_the_kind() {
+        assert!(JobSpec::parse(JobKind::Command, json!({"steps": []})).is_err());
+        assert!(JobSpec::parse(JobKind::Workflow, json!({"steps": []})).is_ok());
+    }
+}
@@ -4,16 +4,23 @@
 use axum::{
     extract::{Json, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Instant;
-use tracing::{error, info};
-
-use super::task_executor::TaskPlanExecutor;
-use super::AppState;
-use crate::llm::{LLMConfig, LLMResponse as RealLLMResponse, LLMService, TokenUsage};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use super::task_executor::{ActionResult, TaskPlanExecutor};
+use super::{ApiError, AppState, ErrorCode};
+use crate::browser::session::BrowserSession;
+use crate::browser::{ActivityKind, RecordedAction};
+use crate::coordination::Event;
+use crate::llm::{
+    AzureOpenAIConfig, LLMConfig, LLMError, LLMResponse as RealLLMResponse, LLMService,
+    LocalModelConfig, ModelSelectionStrategy, TokenBudget, TokenUsage,
+};
 
 // Re-export TaskPlan from the real LLM module or define here if needed
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,7 +80,7 @@ fn build_planning_prompt(
         {\n\
           \"steps\": [\n\
             {\n\
-              \"action_type\": \"navigate|click|type|wait|extract\",\n\
+              \"action_type\": \"navigate|click|type|set_date|wait|extract|extract_search_results|open_result\",\n\
               \"target\": \"CSS selector or URL\",\n\
               \"value\": \"text to type (if applicable)\",\n\
               \"options\": {\n\
@@ -87,7 +94,12 @@ fn build_planning_prompt(
           \"estimated_time_seconds\": integer,\n\
           \"complexity\": \"simple|medium|complex\"\n\
         }\n\n\
-        Only use common, reliable CSS selectors. Be specific and accurate.",
+        Only use common, reliable CSS selectors. Be specific and accurate.\n\
+        On search result pages, use extract_search_results to list the results, then \
+        open_result with a target describing the wanted result (or a 1-based position \
+        as value) instead of guessing result selectors.\n\
+        For date fields and calendar widgets, use set_date with the field as target and \
+        the date as value (e.g. 2025-03-14) instead of clicking through the calendar.",
     );
 
     prompt
@@ -232,15 +244,16 @@ pub enum LLMApiError {
 }
 
 /// Enhanced response wrapper for LLM operations
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(LlmEnvelope = LLMResponse<serde_json::Value>)]
 pub struct LLMResponse<T> {
     pub success: bool,
     pub data: Option<T>,
-    pub error: Option<String>,
+    pub error: Option<ApiError>,
     pub metadata: LLMResponseMetadata,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LLMResponseMetadata {
     pub processing_time_ms: u64,
     pub provider_used: String,
@@ -260,19 +273,30 @@ impl<T> LLMResponse<T> {
         }
     }
 
-    pub fn error(error: String, metadata: LLMResponseMetadata) -> Self {
+    pub fn error(
+        code: ErrorCode,
+        message: impl Into<String>,
+        metadata: LLMResponseMetadata,
+    ) -> Self {
         Self {
             success: false,
             data: None,
-            error: Some(error),
+            error: Some(ApiError::new(code, message)),
             metadata,
         }
     }
 }
 
 /// Direct LLM query endpoint
+#[utoipa::path(
+    post,
+    path = "/api/llm/query",
+    tag = "llm",
+    request_body = LLMQueryRequest,
+    responses((status = 200, description = "LLM response", body = LlmEnvelope))
+)]
 pub async fn llm_query(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(req): Json<LLMQueryRequest>,
 ) -> impl IntoResponse {
     let start_time = Instant::now();
@@ -294,6 +318,7 @@ pub async fn llm_query(
         return (
             StatusCode::BAD_REQUEST,
             Json(LLMResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -301,10 +326,16 @@ pub async fn llm_query(
             .into_response();
     }
 
+    let system_prompt =
+        match session_system_prompt(&state, req.session_id.as_deref(), start_time).await {
+            Ok(system_prompt) => system_prompt,
+            Err(response) => return response,
+        };
+
     let processing_start = Instant::now();
 
     // Create LLM configuration and service
-    let llm_config = create_llm_config(&req);
+    let llm_config = create_llm_config(&req, system_prompt);
     let mut llm_service = match LLMService::new(llm_config.clone()) {
         Ok(service) => service,
         Err(e) => {
@@ -320,6 +351,7 @@ pub async fn llm_query(
                 confidence: None,
                 total_time_ms: start_time.elapsed().as_millis() as u64,
             };
+            record_llm_usage(&state, &metadata).await;
             return Json(LLMResponse::success(mock_response, metadata)).into_response();
         }
     };
@@ -336,13 +368,12 @@ pub async fn llm_query(
                     .clone()
                     .unwrap_or_else(|| llm_config.default_provider.clone()),
                 tokens_used: real_response.usage.total_tokens,
-                estimated_cost_usd: calculate_cost(
-                    &real_response.usage,
-                    &llm_config.default_provider,
-                ),
+                // Priced by the model that actually answered
+                estimated_cost_usd: llm_service.get_cost_metrics().total_cost_usd,
                 confidence: Some(0.9),
                 total_time_ms: start_time.elapsed().as_millis() as u64,
             };
+            record_llm_usage(&state, &metadata).await;
 
             info!(
                 "Real LLM query completed in {}ms, tokens: {}",
@@ -351,6 +382,27 @@ pub async fn llm_query(
             Json(LLMResponse::success(real_response, metadata)).into_response()
         }
         Err(e) => {
+            // Over-budget requests are refused rather than answered by the mock
+            if let Some(LLMError::BudgetExceeded(reason)) = e.downcast_ref::<LLMError>() {
+                let metadata = LLMResponseMetadata {
+                    processing_time_ms: processing_start.elapsed().as_millis() as u64,
+                    provider_used: llm_config.default_provider.clone(),
+                    tokens_used: 0,
+                    estimated_cost_usd: 0.0,
+                    confidence: None,
+                    total_time_ms: start_time.elapsed().as_millis() as u64,
+                };
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(LLMResponse::<()>::error(
+                        ErrorCode::InvalidRequest,
+                        format!("Token budget exceeded: {}", reason),
+                        metadata,
+                    )),
+                )
+                    .into_response();
+            }
+
             error!("LLM query failed: {}, falling back to mock", e);
 
             // Fallback to mock response
@@ -364,6 +416,7 @@ pub async fn llm_query(
                 confidence: Some(0.5),
                 total_time_ms: start_time.elapsed().as_millis() as u64,
             };
+            record_llm_usage(&state, &metadata).await;
 
             info!(
                 "Mock LLM query completed in {}ms, tokens: {}",
@@ -376,7 +429,7 @@ pub async fn llm_query(
 
 /// Task planning endpoint - converts natural language to browser automation plan
 pub async fn task_planning(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(req): Json<TaskPlanningRequest>,
 ) -> impl IntoResponse {
     let start_time = Instant::now();
@@ -395,6 +448,7 @@ pub async fn task_planning(
         return (
             StatusCode::BAD_REQUEST,
             Json(LLMResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -402,8 +456,14 @@ pub async fn task_planning(
             .into_response();
     }
 
+    let system_prompt =
+        match session_system_prompt(&state, req.session_id.as_deref(), start_time).await {
+            Ok(system_prompt) => system_prompt,
+            Err(response) => return response,
+        };
+
     // Create LLM configuration
-    let llm_config = create_llm_config_for_planning(&req);
+    let llm_config = create_llm_config_for_planning(&req, system_prompt);
     let provider_name = llm_config.default_provider.clone();
 
     match LLMService::new(llm_config) {
@@ -430,6 +490,11 @@ pub async fn task_planning(
 
             // Build planning prompt
             let planning_prompt = build_planning_prompt(&req.instruction, &context);
+            super::operations::report_progress(
+                "planning",
+                serde_json::json!({ "instruction": req.instruction, "provider": provider_name }),
+            )
+            .await;
 
             match llm_service.query(&planning_prompt).await {
                 Ok(llm_response) => {
@@ -450,6 +515,7 @@ pub async fn task_planning(
                                 confidence: Some(task_plan.confidence),
                                 total_time_ms: start_time.elapsed().as_millis() as u64,
                             };
+                            record_llm_usage(&state, &metadata).await;
 
                             info!(
                                 "Task planning completed in {}ms with {} steps",
@@ -484,6 +550,7 @@ pub async fn task_planning(
                                 confidence: Some(task_plan.confidence),
                                 total_time_ms: start_time.elapsed().as_millis() as u64,
                             };
+                            record_llm_usage(&state, &metadata).await;
 
                             info!(
                                 "Fallback task planning completed in {}ms with {} steps",
@@ -540,6 +607,7 @@ pub async fn task_planning(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(LLMResponse::<()>::error(
+                    ErrorCode::LlmUnavailable,
                     format!("LLM service initialization failed: {}", e),
                     metadata,
                 )),
@@ -570,6 +638,7 @@ pub async fn execute_command(
         return (
             StatusCode::BAD_REQUEST,
             Json(LLMResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -577,6 +646,12 @@ pub async fn execute_command(
             .into_response();
     }
 
+    let system_prompt =
+        match session_system_prompt(&state, req.session_id.as_deref(), start_time).await {
+            Ok(system_prompt) => system_prompt,
+            Err(response) => return response,
+        };
+
     // First, plan the task
     let task_planning_req = TaskPlanningRequest {
         instruction: req.command.clone(),
@@ -588,32 +663,44 @@ pub async fn execute_command(
         session_id: req.session_id.clone(),
     };
 
-    // Get or create browser session
-    let _browser = match state.browser_pool.acquire().await {
-        Ok(browser) => browser,
-        Err(e) => {
-            error!("Failed to acquire browser: {}", e);
-            let metadata = LLMResponseMetadata {
-                processing_time_ms: 0,
-                provider_used: "none".to_string(),
-                tokens_used: 0,
-                estimated_cost_usd: 0.0,
-                confidence: None,
-                total_time_ms: start_time.elapsed().as_millis() as u64,
-            };
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(LLMResponse::<()>::error(
-                    format!("Failed to acquire browser: {}", e),
-                    metadata,
-                )),
-            )
-                .into_response();
-        }
+    // Run in the session's browser when one is given, so the session's recording
+    // captures the actions the AI performs
+    let session = match req.session_id.as_deref() {
+        Some(session_id) => match state.session_manager.get_session(session_id).await {
+            Some(session) => Some(session),
+            None => return session_not_found(session_id, start_time),
+        },
+        None => None,
+    };
+    let (browser, _pooled) = match &session {
+        Some(session) => (session.read().await.browser.clone(), None),
+        None => match state.browser_pool.acquire().await {
+            Ok(pooled) => (pooled.browser_arc(), Some(pooled)),
+            Err(e) => {
+                error!("Failed to acquire browser: {}", e);
+                let metadata = LLMResponseMetadata {
+                    processing_time_ms: 0,
+                    provider_used: "none".to_string(),
+                    tokens_used: 0,
+                    estimated_cost_usd: 0.0,
+                    confidence: None,
+                    total_time_ms: start_time.elapsed().as_millis() as u64,
+                };
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(LLMResponse::<()>::error(
+                        ErrorCode::BrowserUnavailable,
+                        format!("Failed to acquire browser: {}", e),
+                        metadata,
+                    )),
+                )
+                    .into_response();
+            }
+        },
     };
 
     // Create LLM configuration
-    let llm_config = create_llm_config_for_planning(&task_planning_req);
+    let llm_config = create_llm_config_for_planning(&task_planning_req, system_prompt);
     let provider_name = llm_config.default_provider.clone();
 
     match LLMService::new(llm_config) {
@@ -633,6 +720,13 @@ pub async fn execute_command(
 
             // Build planning prompt and query LLM
             let planning_prompt = build_planning_prompt(&req.command, &context);
+            record_thinking(
+                &state,
+                req.session_id.as_deref(),
+                "planning",
+                serde_json::json!({ "command": req.command, "provider": provider_name }),
+            )
+            .await;
 
             match llm_service.query(&planning_prompt).await {
                 Ok(llm_response) => {
@@ -641,6 +735,13 @@ pub async fn execute_command(
                         Ok(task_plan) => {
                             let planning_time = processing_start.elapsed().as_millis() as u64;
 
+                            record_thinking(
+                                &state,
+                                req.session_id.as_deref(),
+                                "plan",
+                                serde_json::json!({ "source": "llm", "steps": task_plan.steps, "confidence": task_plan.confidence }),
+                            )
+                            .await;
                             // Execute the plan if auto_execute is true
                             let execution_result = if req.auto_execute.unwrap_or(true) {
                                 info!(
@@ -649,9 +750,17 @@ pub async fn execute_command(
                                 );
 
                                 // Use the real task plan executor
-                                let executor = TaskPlanExecutor::new(_browser.browser_arc());
+                                let executor = TaskPlanExecutor::new(browser.clone());
                                 match executor.execute_plan(task_plan.clone()).await {
                                     Ok(exec_result) => {
+                                        if let Some(session) = &session {
+                                            record_plan_actions(
+                                                session,
+                                                &task_plan.steps,
+                                                &exec_result.action_results,
+                                            )
+                                            .await;
+                                        }
                                         info!("Task plan execution completed: {} steps completed, {} failed", 
                                               exec_result.steps_completed, exec_result.steps_failed);
 
@@ -684,6 +793,13 @@ pub async fn execute_command(
                                 None
                             };
 
+                            record_thinking(
+                                &state,
+                                req.session_id.as_deref(),
+                                "result",
+                                serde_json::json!({ "execution_result": execution_result }),
+                            )
+                            .await;
                             let total_processing_time =
                                 processing_start.elapsed().as_millis() as u64;
                             let metadata = LLMResponseMetadata {
@@ -699,6 +815,7 @@ pub async fn execute_command(
                                 confidence: Some(task_plan.confidence),
                                 total_time_ms: start_time.elapsed().as_millis() as u64,
                             };
+                            record_llm_usage(&state, &metadata).await;
 
                             let response_data = serde_json::json!({
                                 "command": req.command,
@@ -726,9 +843,16 @@ pub async fn execute_command(
                             };
 
                             let planning_time = processing_start.elapsed().as_millis() as u64;
+                            record_thinking(
+                                &state,
+                                req.session_id.as_deref(),
+                                "plan",
+                                serde_json::json!({ "source": "fallback", "steps": task_plan.steps, "confidence": task_plan.confidence }),
+                            )
+                            .await;
                             let execution_result = if req.auto_execute.unwrap_or(true) {
                                 // Execute fallback plan with task executor
-                                let executor = TaskPlanExecutor::new(_browser.browser_arc());
+                                let executor = TaskPlanExecutor::new(browser.clone());
                                 match executor.execute_plan(task_plan.clone()).await {
                                     Ok(exec_result) => Some(serde_json::json!({
                                         "executed": true,
@@ -754,6 +878,13 @@ pub async fn execute_command(
                                 None
                             };
 
+                            record_thinking(
+                                &state,
+                                req.session_id.as_deref(),
+                                "result",
+                                serde_json::json!({ "execution_result": execution_result }),
+                            )
+                            .await;
                             let total_processing_time =
                                 processing_start.elapsed().as_millis() as u64;
                             let metadata = LLMResponseMetadata {
@@ -767,6 +898,7 @@ pub async fn execute_command(
                                 confidence: Some(task_plan.confidence),
                                 total_time_ms: start_time.elapsed().as_millis() as u64,
                             };
+                            record_llm_usage(&state, &metadata).await;
 
                             let response_data = serde_json::json!({
                                 "command": req.command,
@@ -792,9 +924,16 @@ pub async fn execute_command(
                     };
 
                     let planning_time = processing_start.elapsed().as_millis() as u64;
+                    record_thinking(
+                        &state,
+                        req.session_id.as_deref(),
+                        "plan",
+                        serde_json::json!({ "source": "mock", "steps": task_plan.steps, "confidence": task_plan.confidence }),
+                    )
+                    .await;
                     let execution_result = if req.auto_execute.unwrap_or(true) {
                         // Execute mock plan with task executor
-                        let executor = TaskPlanExecutor::new(_browser.browser_arc());
+                        let executor = TaskPlanExecutor::new(browser.clone());
                         match executor.execute_plan(task_plan.clone()).await {
                             Ok(exec_result) => Some(serde_json::json!({
                                 "executed": true,
@@ -820,6 +959,13 @@ pub async fn execute_command(
                         None
                     };
 
+                    record_thinking(
+                        &state,
+                        req.session_id.as_deref(),
+                        "result",
+                        serde_json::json!({ "execution_result": execution_result }),
+                    )
+                    .await;
                     let total_processing_time = processing_start.elapsed().as_millis() as u64;
                     let metadata = LLMResponseMetadata {
                         processing_time_ms: total_processing_time,
@@ -854,6 +1000,7 @@ pub async fn execute_command(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(LLMResponse::<()>::error(
+                    ErrorCode::LlmUnavailable,
                     format!("LLM service initialization failed: {}", e),
                     metadata,
                 )),
@@ -864,6 +1011,13 @@ pub async fn execute_command(
 }
 
 /// Cost tracking and usage monitoring endpoint
+#[utoipa::path(
+    post,
+    path = "/api/llm/usage",
+    tag = "llm",
+    request_body = UsageMetricsRequest,
+    responses((status = 200, description = "Usage metrics", body = LlmEnvelope))
+)]
 pub async fn get_usage_metrics(
     State(_state): State<AppState>,
     Json(req): Json<UsageMetricsRequest>,
@@ -926,21 +1080,25 @@ pub async fn get_usage_metrics(
 
 // Request/Response types for LLM API
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[allow(dead_code)]
 pub struct LLMQueryRequest {
     pub prompt: String,
-    pub provider: Option<String>, // "openai", "claude", "mock"
+    pub provider: Option<String>, // "openai", "azure", "claude"/"anthropic", "gemini", "local"/"ollama", "mock"
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
     pub model: Option<String>,
+    /// Session whose persona becomes the system prompt
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[allow(dead_code)]
 pub struct TaskPlanningRequest {
     pub instruction: String,
     pub url: Option<String>,
+    #[schema(value_type = Option<Object>)]
     pub page_context: Option<serde_json::Map<String, serde_json::Value>>,
     pub complexity: Option<String>, // "simple", "medium", "complex"
     pub provider: Option<String>,
@@ -948,7 +1106,7 @@ pub struct TaskPlanningRequest {
     pub session_id: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct ExecuteCommandRequest {
     pub command: String,
     pub url: Option<String>,
@@ -959,7 +1117,7 @@ pub struct ExecuteCommandRequest {
     pub session_id: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[allow(dead_code)]
 pub struct UsageMetricsRequest {
     pub timeframe: Option<String>, // "hour", "day", "week", "month"
@@ -984,7 +1142,16 @@ fn validate_llm_query_request(req: &LLMQueryRequest) -> Result<(), LLMApiError>
     }
 
     if let Some(ref provider) = req.provider {
-        let valid_providers = ["openai", "claude", "mock"];
+        let valid_providers = [
+            "openai",
+            "azure",
+            "claude",
+            "anthropic",
+            "gemini",
+            "local",
+            "ollama",
+            "mock",
+        ];
         if !valid_providers.contains(&provider.as_str()) {
             return Err(LLMApiError::ValidationError(format!(
                 "Invalid provider '{}'. Valid providers: {}",
@@ -1066,42 +1233,211 @@ fn validate_execute_command_request(req: &ExecuteCommandRequest) -> Result<(), L
 
 // Utility functions
 
-fn create_llm_config(req: &LLMQueryRequest) -> LLMConfig {
+/// Add the successfully executed steps of a plan to the session's recording
+async fn record_plan_actions(
+    session: &tokio::sync::RwLock<BrowserSession>,
+    steps: &[BrowserAction],
+    results: &[ActionResult],
+) {
+    let mut session = session.write().await;
+    for result in results.iter().filter(|result| result.success) {
+        let Some(step) = steps.get(result.step_index) else {
+            continue;
+        };
+        if let Some(action) = RecordedAction::from_plan_step(
+            &step.action_type,
+            step.target.as_deref(),
+            step.value.as_deref(),
+        ) {
+            session.record(action);
+        }
+    }
+    session.touch();
+}
+
+/// Count an LLM call's tokens and cost towards the caller's audit totals and
+/// daily budget, announcing on the event bus when the budget is used up
+async fn record_llm_usage(state: &AppState, metadata: &LLMResponseMetadata) {
+    state
+        .audit
+        .record_llm_usage(metadata.tokens_used as u64, metadata.estimated_cost_usd)
+        .await;
+    state
+        .traces
+        .record(
+            "llm",
+            "llm_call",
+            None,
+            serde_json::json!({
+                "provider": metadata.provider_used,
+                "tokens_used": metadata.tokens_used,
+                "estimated_cost_usd": metadata.estimated_cost_usd,
+                "processing_time_ms": metadata.processing_time_ms,
+            }),
+        )
+        .await;
+    let client = super::quotas::current_client();
+    if state
+        .quotas
+        .record_llm_spend(&client, metadata.estimated_cost_usd)
+        .await
+    {
+        let usage = state.quotas.usage(&client).await;
+        warn!("{} spent its daily LLM budget", client);
+        let _ = state
+            .events
+            .emit(Event::BudgetExceeded {
+                budget_usd: usage.limits.daily_llm_budget_usd.unwrap_or_default(),
+                spent_usd: usage.llm_spend_today_usd,
+                client,
+                timestamp: Instant::now(),
+            })
+            .await;
+    }
+}
+
+/// Report a reasoning step of a command on its session's activity stream and,
+/// for asynchronous requests, as operation progress
+async fn record_thinking(
+    state: &AppState,
+    session_id: Option<&str>,
+    stage: &str,
+    mut detail: serde_json::Value,
+) {
+    super::operations::report_progress(stage, detail.clone()).await;
+    let Some(session_id) = session_id else {
+        return;
+    };
+    if let Some(fields) = detail.as_object_mut() {
+        fields.insert("stage".to_string(), stage.into());
+    }
+    state
+        .session_manager
+        .record_activity(session_id, ActivityKind::Thinking, detail);
+}
+
+/// Persona the request's session was created with, or a 404 response when the
+/// session does not exist
+async fn session_system_prompt(
+    state: &AppState,
+    session_id: Option<&str>,
+    start_time: Instant,
+) -> Result<Option<String>, Response> {
+    let Some(session_id) = session_id else {
+        return Ok(None);
+    };
+    match state.session_manager.get_session(session_id).await {
+        Some(session) => Ok(session.read().await.system_prompt.clone()),
+        None => Err(session_not_found(session_id, start_time)),
+    }
+}
+
+fn session_not_found(session_id: &str, start_time: Instant) -> Response {
+    let metadata = LLMResponseMetadata {
+        processing_time_ms: 0,
+        provider_used: "none".to_string(),
+        tokens_used: 0,
+        estimated_cost_usd: 0.0,
+        confidence: None,
+        total_time_ms: start_time.elapsed().as_millis() as u64,
+    };
+    (
+        StatusCode::NOT_FOUND,
+        Json(LLMResponse::<()>::error(
+            ErrorCode::SessionNotFound,
+            format!("Session not found: {}", session_id),
+            metadata,
+        )),
+    )
+        .into_response()
+}
+
+fn create_llm_config(req: &LLMQueryRequest, system_prompt: Option<String>) -> LLMConfig {
     LLMConfig {
-        default_provider: req.provider.clone().unwrap_or_else(|| "openai".to_string()),
+        default_provider: req.provider.clone().unwrap_or_else(default_provider),
         openai_api_key: std::env::var("OPENAI_API_KEY").ok(),
-        claude_api_key: std::env::var("CLAUDE_API_KEY").ok(),
+        claude_api_key: claude_api_key(),
         max_tokens: req.max_tokens.unwrap_or(4000),
         temperature: req.temperature.unwrap_or(0.7),
         cost_limit_usd: 10.0, // Default cost limit
+        model: req.model.clone(),
+        model_selection: model_selection_from_env(),
+        system_prompt,
+        local: LocalModelConfig::from_env(),
+        azure: AzureOpenAIConfig::from_env(),
+        gemini_api_key: gemini_api_key(),
+        budget: TokenBudget::from_env(),
     }
 }
 
-fn create_llm_config_for_planning(req: &TaskPlanningRequest) -> LLMConfig {
+fn create_llm_config_for_planning(
+    req: &TaskPlanningRequest,
+    system_prompt: Option<String>,
+) -> LLMConfig {
     LLMConfig {
-        default_provider: req.provider.clone().unwrap_or_else(|| "openai".to_string()),
+        default_provider: req.provider.clone().unwrap_or_else(default_provider),
         openai_api_key: std::env::var("OPENAI_API_KEY").ok(),
-        claude_api_key: std::env::var("CLAUDE_API_KEY").ok(),
+        claude_api_key: claude_api_key(),
         max_tokens: 2000, // Optimized for task planning
         temperature: 0.3, // Lower temperature for more focused planning
         cost_limit_usd: 5.0,
+        model: None,
+        model_selection: model_selection_from_env(),
+        system_prompt,
+        local: LocalModelConfig::from_env(),
+        azure: AzureOpenAIConfig::from_env(),
+        gemini_api_key: gemini_api_key(),
+        budget: TokenBudget::from_env(),
     }
 }
 
+/// Provider from `AI_PROVIDER`, falling back to OpenAI
+fn default_provider() -> String {
+    std::env::var("AI_PROVIDER").unwrap_or_else(|_| "openai".to_string())
+}
+
+/// Anthropic key from `CLAUDE_API_KEY` or `ANTHROPIC_API_KEY`
+fn claude_api_key() -> Option<String> {
+    std::env::var("CLAUDE_API_KEY")
+        .or_else(|_| std::env::var("ANTHROPIC_API_KEY"))
+        .ok()
+}
+
+/// Gemini key from `GEMINI_API_KEY` or `GOOGLE_API_KEY`
+fn gemini_api_key() -> Option<String> {
+    std::env::var("GEMINI_API_KEY")
+        .or_else(|_| std::env::var("GOOGLE_API_KEY"))
+        .ok()
+}
+
+/// Strategy from `RAINBOW_MODEL_SELECTION` (balanced, cost_optimized, quality_optimized, adaptive)
+fn model_selection_from_env() -> ModelSelectionStrategy {
+    std::env::var("RAINBOW_MODEL_SELECTION")
+        .ok()
+        .and_then(|s| serde_json::from_value(serde_json::Value::String(s)).ok())
+        .unwrap_or_default()
+}
+
 fn calculate_cost(usage: &TokenUsage, provider: &str) -> f64 {
     match provider {
-        "openai" => {
+        "openai" | "azure" => {
             // GPT-4 pricing (approximate)
             let input_cost = usage.prompt_tokens as f64 * 0.00003;
             let output_cost = usage.completion_tokens as f64 * 0.00006;
             input_cost + output_cost
         }
-        "claude" => {
+        "claude" | "anthropic" => {
             // Claude pricing (approximate)
             let input_cost = usage.prompt_tokens as f64 * 0.000008;
             let output_cost = usage.completion_tokens as f64 * 0.000024;
             input_cost + output_cost
         }
+        "gemini" => {
+            // Gemini 1.5 Pro pricing (approximate)
+            let input_cost = usage.prompt_tokens as f64 * 0.00000125;
+            let output_cost = usage.completion_tokens as f64 * 0.000005;
+            input_cost + output_cost
+        }
         _ => 0.01, // Default/mock cost
     }
 }
@@ -1134,6 +1470,7 @@ async fn create_mock_llm_response(req: &LLMQueryRequest) -> RealLLMResponse {
         },
         finish_reason: "stop".to_string(),
         timestamp: chrono::Utc::now(),
+        tool_calls: Vec::new(),
     }
 }
 
@@ -1149,6 +1486,7 @@ mod tests {
             max_tokens: Some(1000),
             temperature: Some(0.7),
             model: None,
+            session_id: None,
         };
         assert!(validate_llm_query_request(&valid_req).is_ok());
 
@@ -1158,6 +1496,7 @@ mod tests {
             max_tokens: None,
             temperature: None,
             model: None,
+            session_id: None,
         };
         assert!(validate_llm_query_request(&invalid_req).is_err());
     }
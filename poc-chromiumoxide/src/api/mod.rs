@@ -1,27 +1,54 @@
 use anyhow::Result;
 use axum::{
     extract::{Json, Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
+mod audit;
+mod auth;
+mod batch;
+mod browser_handlers;
 mod coordinated_handlers;
+mod errors;
+mod events;
+mod federation;
+mod graphql;
+mod idempotency;
 mod intelligence_handlers;
+mod jobs;
 mod llm_handlers;
+mod openapi;
+mod operations;
+mod pagination;
 mod perception_handlers;
+mod quotas;
+mod security;
 mod task_executor;
+mod tenants;
+mod trace;
+mod webhooks;
 mod workflow_handlers; // New coordinated handlers
-use crate::browser::{pool::BrowserPool, BrowserOps, SessionManager};
+mod ws;
+use crate::browser::affinity::{CLIENT_TOKEN_HEADER, DEFAULT_CLIENT};
+use crate::browser::{
+    pool::BrowserPool, ActivityKind, BrowserAffinity, BrowserOps, SessionManager,
+};
+use crate::coordination::{Event, EventBus, EventBusConfig};
+use crate::intelligence::{AnomalyDetector, AppliedProfile, LearningStore, SharedLearning};
+use crate::llm::EmbeddingProvider;
 use crate::tools::registry::ToolRegistry;
+use errors::{ApiError, ErrorCode};
+use pagination::PageQuery;
 use std::io::ErrorKind;
 use tokio::sync::RwLock;
 use tokio::time::{timeout, Duration};
-use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
 use tracing::{debug, error, info, warn};
@@ -31,43 +58,72 @@ struct AppState {
     browser_pool: Arc<BrowserPool>,
     session_manager: Arc<SessionManager>,
     tool_registry: Arc<LazyToolRegistry>,
-    recent_nav: Arc<RwLock<HashMap<String, String>>>,
+    affinity: Arc<BrowserAffinity>,
+    /// Remote workers sessions are scheduled onto, when federation is enabled
+    federation: Option<Arc<federation::Federation>>,
+    /// Learning samples and success patterns of each tenant, shared by its
+    /// intelligence requests
+    learning: Arc<tenants::TenantLearning>,
+    /// Embeds element descriptions and queries for similarity matching
+    embeddings: Arc<dyn EmbeddingProvider>,
+    /// Per-origin action outcomes and the anomalies they raised
+    anomalies: Arc<RwLock<AnomalyDetector>>,
+    /// Requests running in the background, with their progress and results
+    operations: Arc<operations::OperationStore>,
+    /// Commands, workflows and tool calls queued for the job workers
+    jobs: Arc<jobs::JobQueue>,
+    /// Token validation and dashboard login, when authentication is configured
+    auth: Option<Arc<auth::Authenticator>>,
+    /// API keys of tenants, when multi-tenant access is configured
+    api_keys: Arc<tenants::ApiKeys>,
+    /// Requests and LLM usage per principal
+    audit: Arc<audit::AuditLog>,
+    /// Per-client request rate, session and LLM spend limits
+    quotas: Arc<quotas::Quotas>,
+    /// Responses kept for replay to retries with the same `Idempotency-Key`
+    idempotency: Arc<idempotency::ReplayCache>,
+    /// Coordination event bus, the coordinator's when there is one
+    events: Arc<EventBus>,
+    /// Callbacks registered for event bus events
+    webhooks: Arc<webhooks::Webhooks>,
+    /// Session activity and LLM calls by the correlation id they ran under
+    traces: Arc<trace::TraceStore>,
 }
 
 #[derive(Clone)]
 struct LazyToolRegistry {
-    inner: Arc<RwLock<Option<Arc<ToolRegistry>>>>,
-    // Track the active browser instance used by the registry so other subsystems
-    // (e.g., perception) can share the same browser when no session_id is provided.
-    active_browser: Arc<RwLock<Option<Arc<crate::browser::Browser>>>>,
-    browser_pool: Arc<BrowserPool>,
+    // One registry, and so one tool cache, per tenant
+    inner: Arc<RwLock<HashMap<String, Arc<ToolRegistry>>>>,
+    // The registry's browser is the tenant's default client affinity binding, so
+    // other subsystems (e.g., perception) share it when no session_id is provided.
+    affinity: Arc<BrowserAffinity>,
     session_manager: Arc<SessionManager>,
 }
 
 impl LazyToolRegistry {
-    fn new(browser_pool: Arc<BrowserPool>, session_manager: Arc<SessionManager>) -> Self {
+    fn new(affinity: Arc<BrowserAffinity>, session_manager: Arc<SessionManager>) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(None)),
-            active_browser: Arc::new(RwLock::new(None)),
-            browser_pool,
+            inner: Arc::new(RwLock::new(HashMap::new())),
+            affinity,
             session_manager,
         }
     }
 
     async fn get(&self) -> anyhow::Result<Arc<ToolRegistry>> {
+        let client = tenants::client_key(DEFAULT_CLIENT);
         // Fast path: already initialized
-        if let Some(existing) = self.inner.read().await.as_ref() {
+        if let Some(existing) = self.inner.read().await.get(&client) {
             return Ok(existing.clone());
         }
         // Initialize lazily
         let mut write_guard = self.inner.write().await;
-        if let Some(existing) = write_guard.as_ref() {
+        if let Some(existing) = write_guard.get(&client) {
             return Ok(existing.clone());
         }
         // Try to acquire a browser from the pool with a short timeout to avoid deadlocks
         let browser_arc =
-            match timeout(Duration::from_millis(2000), self.browser_pool.acquire()).await {
-                Ok(Ok(guard)) => guard.browser_arc(),
+            match timeout(Duration::from_millis(2000), self.affinity.browser(&client)).await {
+                Ok(Ok(browser)) => browser,
                 Ok(Err(e)) => {
                     warn!(
                         "Tool registry: pool acquire failed ({}). Attempting session fallback...",
@@ -83,11 +139,8 @@ impl LazyToolRegistry {
 
         let registry = Arc::new(ToolRegistry::new(browser_arc.clone()));
 
-        // Record the active browser used by the registry for cross-module sharing
-        {
-            let mut ab = self.active_browser.write().await;
-            *ab = Some(browser_arc);
-        }
+        // Record the browser used by the registry for cross-module sharing
+        self.affinity.bind(&client, browser_arc).await;
 
         // Start background cache cleanup task once
         {
@@ -99,23 +152,13 @@ impl LazyToolRegistry {
             ));
         }
 
-        *write_guard = Some(registry.clone());
+        write_guard.insert(client, registry.clone());
         Ok(registry)
     }
 
     async fn initialized(&self) -> bool {
-        self.inner.read().await.is_some()
-    }
-
-    // Expose the active browser if the registry has been initialized
-    async fn active_browser(&self) -> Option<Arc<crate::browser::Browser>> {
-        self.active_browser.read().await.clone()
-    }
-
-    // Allow other subsystems to promote a browser as the active one
-    async fn set_active_browser(&self, browser: Arc<crate::browser::Browser>) {
-        let mut ab = self.active_browser.write().await;
-        *ab = Some(browser);
+        let client = tenants::client_key(DEFAULT_CLIENT);
+        self.inner.read().await.contains_key(&client)
     }
 
     async fn try_session_browser_fallback(&self) -> anyhow::Result<Arc<crate::browser::Browser>> {
@@ -167,12 +210,30 @@ async fn bind_with_retry(base_port: u16, attempts: u16) -> Result<(tokio::net::T
 pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
     let browser_pool_arc = Arc::new(browser_pool);
 
+    // Probe idle browsers periodically so crashed ones are never handed out
+    let health_secs = std::env::var("RAINBOW_POOL_HEALTH_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+    if health_secs > 0 {
+        browser_pool_arc
+            .clone()
+            .start_health_checks(Duration::from_secs(health_secs));
+    }
+
     // Create session manager using the browser pool
     let session_manager = SessionManager::new(
         browser_pool_arc.clone(),
         10,   // max_sessions
         1800, // session_timeout (30 minutes)
     );
+    // Sessions persisted by the last shutdown stay restorable
+    if let Err(e) = session_manager
+        .load_snapshots(&crate::browser::session::snapshot_file())
+        .await
+    {
+        warn!("Failed to load persisted sessions: {}", e);
+    }
 
     // Create the RainbowCoordinator for coordinated operations
     let coordinator =
@@ -186,15 +247,36 @@ pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
         };
 
     let session_manager_arc = Arc::new(session_manager);
+    // End sessions past their TTL, idle timeout or limits and announce it on the event bus
+    coordinator.forward_session_expiry(session_manager_arc.subscribe_expiry());
+    session_manager_arc
+        .clone()
+        .start_cleanup(Duration::from_secs(30));
+    let affinity = start_affinity(browser_pool_arc.clone());
     let state = AppState {
         browser_pool: browser_pool_arc.clone(),
         session_manager: session_manager_arc.clone(),
         tool_registry: Arc::new(LazyToolRegistry::new(
-            browser_pool_arc.clone(),
+            affinity.clone(),
             session_manager_arc.clone(),
         )),
-        recent_nav: Arc::new(RwLock::new(HashMap::new())),
+        affinity,
+        federation: start_federation(),
+        learning: start_learning().await,
+        embeddings: crate::llm::embeddings::embedding_provider_from_env(),
+        anomalies: Arc::new(RwLock::new(AnomalyDetector::new())),
+        operations: Arc::new(operations::OperationStore::new()),
+        jobs: Arc::new(jobs::JobQueue::new()),
+        auth: auth::start_auth(),
+        api_keys: Arc::new(tenants::ApiKeys::from_env()),
+        audit: Arc::new(audit::AuditLog::new()),
+        quotas: start_quotas(),
+        idempotency: Arc::new(idempotency::ReplayCache::from_env()),
+        events: coordinator.event_bus(),
+        webhooks: Arc::new(webhooks::Webhooks::from_env(coordinator.event_bus())),
+        traces: start_traces(&session_manager_arc),
     };
+    start_job_workers(&state);
 
     let coordinated_state = coordinated_handlers::CoordinatedApiState {
         coordinator: coordinator.clone(),
@@ -234,41 +316,161 @@ pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
         .route("/health", get(coordinated_handlers::get_system_health))
         .with_state(coordinated_state);
 
-    // Static list of important routes for diagnostics
-    fn route_list() -> Vec<&'static str> {
-        vec![
-            "/api/health",
-            "/api/diagnostics",
-            "/api/session/create",
-            "/api/sessions",
-            "/api/navigate",
-            "/api/perception/analyze",
-            "/api/perceive-mode",
-            "/api/navigate-perceive",
-            "/api/tools/execute",
-        ]
-    }
-
+    let http_security = Arc::new(security::HttpSecurity::from_env());
     let app = Router::new()
         // Health check
         .route("/health", get(health_check))
         .route("/api/health", get(health_check))
         .route("/api/diagnostics", get(diagnostics))
-        .route(
-            "/api/routes",
-            get(|| async move { Json(ApiResponse::success(route_list())) }),
-        )
+        .route("/api/routes", get(list_routes))
+        .route("/api/openapi.json", get(openapi::openapi_json))
+        .route("/api/docs", get(openapi::swagger_ui))
         // Nest v2 coordinated endpoints under /api/v2
         .nest("/api/v2", v2_router)
         // Session management
         .route("/api/session/create", post(create_session))
         .route("/api/session/:id", get(get_session).delete(delete_session))
         .route("/api/sessions", get(list_sessions))
+        .route(
+            "/api/session/:id/cookies",
+            get(browser_handlers::get_session_cookies)
+                .post(browser_handlers::set_session_cookies)
+                .delete(browser_handlers::clear_session_cookies),
+        )
+        .route(
+            "/api/session/:id/storage",
+            get(browser_handlers::get_session_storage)
+                .post(browser_handlers::set_session_storage)
+                .delete(browser_handlers::clear_session_storage),
+        )
+        .route(
+            "/api/session/:id/storage/import",
+            post(browser_handlers::import_session_storage),
+        )
+        .route(
+            "/api/session/:id/throttling",
+            get(browser_handlers::get_session_throttling)
+                .post(browser_handlers::set_session_throttling)
+                .delete(browser_handlers::clear_session_throttling),
+        )
+        .route(
+            "/api/session/:id/metrics",
+            get(browser_handlers::get_session_metrics),
+        )
+        .route(
+            "/api/session/:id/console",
+            get(browser_handlers::get_session_console)
+                .delete(browser_handlers::clear_session_console),
+        )
+        .route(
+            "/api/session/:id/dom-changes",
+            get(browser_handlers::get_session_dom_changes),
+        )
+        .route(
+            "/api/session/:id/tags",
+            post(browser_handlers::set_session_tags),
+        )
+        .route(
+            "/api/session/:id/tags/:key",
+            delete(browser_handlers::remove_session_tag),
+        )
+        .route(
+            "/api/session/:id/events",
+            get(browser_handlers::session_events),
+        )
+        .route("/ws", get(ws::interactive_channel))
+        // Long-running operations started with ?async=true
+        .route("/api/operations/:id", get(operations::get_operation))
+        .route(
+            "/api/operations/:id/events",
+            get(operations::operation_events),
+        )
+        // Queued commands, workflows and tool calls
+        .route("/api/jobs", get(jobs::list_jobs).post(jobs::submit_job))
+        .route("/api/jobs/:id", get(jobs::get_job).delete(jobs::cancel_job))
+        // Callbacks for event bus events
+        .route(
+            "/api/webhooks",
+            get(webhooks::list_webhooks).post(webhooks::register_webhook),
+        )
+        .route(
+            "/api/webhooks/:id",
+            get(webhooks::get_webhook).delete(webhooks::delete_webhook),
+        )
+        .route(
+            "/api/webhooks/:id/deliveries",
+            get(webhooks::list_deliveries),
+        )
+        // One-request queries over sessions, runs, metrics and patterns
+        .route("/api/graphql", post(graphql::graphql))
+        // Coordination events, filtered by type, session and source
+        .route("/api/events", get(events::list_events))
+        .route("/api/events/stream", get(events::stream_events))
+        // What a request caused across engines, by correlation id
+        .route("/api/trace/:id", get(trace::get_trace))
+        // Authentication and audit trail
+        .route("/api/auth/me", get(auth::me))
+        .route("/auth/login", get(auth::login))
+        .route("/auth/callback", get(auth::callback))
+        .route("/auth/logout", get(auth::logout))
+        .route("/api/audit", get(audit::list_audit_entries))
+        .route("/api/audit/usage", get(audit::usage_by_principal))
+        .route("/api/audit/tenants", get(audit::usage_by_tenant))
+        // Rate limits and quotas
+        .route("/api/quotas", get(quotas::get_quota))
+        .route(
+            "/api/quotas/:client",
+            axum::routing::put(quotas::set_quota).delete(quotas::remove_quota),
+        )
+        .route(
+            "/api/session/:id/recording",
+            get(browser_handlers::get_session_recording),
+        )
+        .route(
+            "/api/session/:id/recording/start",
+            post(browser_handlers::start_session_recording),
+        )
+        .route(
+            "/api/session/:id/recording/stop",
+            post(browser_handlers::stop_session_recording),
+        )
+        .route(
+            "/api/session/:id/recording/workflow",
+            get(browser_handlers::get_recording_workflow),
+        )
+        .route(
+            "/api/session/:id/replay",
+            post(browser_handlers::replay_session),
+        )
+        .route(
+            "/api/session/:id/save",
+            post(browser_handlers::save_session),
+        )
+        .route(
+            "/api/session/:id/snapshot",
+            get(browser_handlers::get_session_snapshot),
+        )
+        .route(
+            "/api/session/restore",
+            post(browser_handlers::restore_session),
+        )
         // Browser actions
         .route("/api/navigate", post(navigate))
         .route("/api/screenshot", post(screenshot))
         .route("/api/click", post(click))
         .route("/api/type", post(type_text))
+        .route("/api/keys", post(browser_handlers::send_keys))
+        .route("/api/dialogs", get(browser_handlers::list_dialogs))
+        .route("/api/performance", get(browser_handlers::get_performance))
+        .route(
+            "/api/pool/health",
+            get(browser_handlers::get_pool_health).post(browser_handlers::check_pool_health),
+        )
+        .route("/api/federation/workers", get(federation::list_workers))
+        .route(
+            "/api/dialogs/policy",
+            get(browser_handlers::get_dialog_policy).post(browser_handlers::set_dialog_policy),
+        )
         .route("/api/execute", post(execute_script))
         .route("/api/find", post(find_elements))
         .route("/api/get_text", post(get_text))
@@ -276,11 +478,61 @@ pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
         .route("/api/zoom", post(set_zoom_level))
         .route("/api/fix_scaling", post(fix_content_scaling))
         .route("/api/fix_window", post(fix_window_completely))
+        // Tab management
+        .route(
+            "/api/tabs",
+            get(browser_handlers::list_tabs).post(browser_handlers::open_tab),
+        )
+        .route("/api/tabs/:id", delete(browser_handlers::close_tab))
+        .route("/api/tabs/:id/activate", post(browser_handlers::switch_tab))
+        // Device emulation
+        .route(
+            "/api/emulation/devices",
+            get(browser_handlers::list_devices),
+        )
+        .route(
+            "/api/emulation/geolocation",
+            post(browser_handlers::set_geolocation).delete(browser_handlers::clear_geolocation),
+        )
+        .route(
+            "/api/emulation/timezone",
+            post(browser_handlers::set_timezone),
+        )
+        // Persistent profiles
+        .route(
+            "/api/profiles",
+            get(browser_handlers::list_profiles).post(browser_handlers::create_profile),
+        )
+        .route(
+            "/api/profiles/:name",
+            delete(browser_handlers::delete_profile),
+        )
+        // Permissions
+        .route(
+            "/api/permissions",
+            post(browser_handlers::set_permissions).delete(browser_handlers::reset_permissions),
+        )
+        // Downloads
+        .route("/api/downloads", get(browser_handlers::list_downloads))
+        .route(
+            "/api/downloads/wait",
+            post(browser_handlers::wait_for_download),
+        )
+        .route(
+            "/api/downloads/config",
+            post(browser_handlers::set_download_dir),
+        )
+        .route("/api/downloads/:id", get(browser_handlers::get_download))
+        .route(
+            "/api/downloads/:id/file",
+            get(browser_handlers::get_download_file),
+        )
         // Workflow
         .route("/api/workflow", post(execute_workflow))
         // Tools API endpoints
         .route("/api/tools", get(list_tools))
         .route("/api/tools/execute", post(execute_tool))
+        .route("/api/batch", post(batch::execute_batch))
         .route("/api/tools/metadata", get(get_tools_metadata))
         .route("/api/tools/validate", post(validate_registry))
         // Performance Monitoring API endpoints
@@ -352,15 +604,33 @@ pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
             "/api/perception/forms/fill",
             post(perception_handlers::auto_fill_form),
         )
-        // NEW: Layered perception endpoints
         .route(
-            "/api/perceive-mode",
-            post(perception_handlers::perceive_with_mode),
+            "/api/perception/forms/profiles",
+            get(perception_handlers::list_fill_profiles)
+                .post(perception_handlers::save_fill_profile),
+        )
+        .route(
+            "/api/perception/forms/profiles/:name",
+            delete(perception_handlers::delete_fill_profile),
+        )
+        .route(
+            "/api/perception/schemas",
+            get(perception_handlers::list_perception_schemas),
         )
+        .route(
+            "/api/perception/schemas/:kind",
+            get(perception_handlers::get_perception_schema),
+        )
+        // NEW: Layered perception endpoints
+        .route("/api/perceive-mode", post(operations::perceive_with_mode))
         .route(
             "/api/navigate-perceive",
             post(perception_handlers::navigate_and_perceive),
         )
+        .route(
+            "/api/perception/batch",
+            post(perception_handlers::batch_perceive),
+        )
         .route("/api/quick-scan", post(perception_handlers::quick_scan))
         .route(
             "/api/smart-element-search",
@@ -376,8 +646,8 @@ pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
         )
         // LLM API endpoints
         .route("/api/llm/query", post(llm_handlers::llm_query))
-        .route("/api/llm/plan", post(llm_handlers::task_planning))
-        .route("/api/llm/execute", post(llm_handlers::execute_command))
+        .route("/api/llm/plan", post(operations::task_planning))
+        .route("/api/llm/execute", post(operations::execute_command))
         .route("/api/llm/usage", post(llm_handlers::get_usage_metrics))
         // Intelligence API endpoints
         .route(
@@ -400,10 +670,32 @@ pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
             "/api/intelligence/config",
             post(intelligence_handlers::update_intelligence_config),
         )
+        .route(
+            "/api/intelligence/patterns/export",
+            get(intelligence_handlers::export_patterns),
+        )
+        .route(
+            "/api/intelligence/patterns/import",
+            post(intelligence_handlers::import_patterns),
+        )
+        .route(
+            "/api/intelligence/elements/match",
+            post(intelligence_handlers::match_elements),
+        )
+        .route(
+            "/api/intelligence/anomalies",
+            get(intelligence_handlers::list_anomalies),
+        )
+        .route(
+            "/api/intelligence/domains",
+            get(intelligence_handlers::list_domain_profiles)
+                .post(intelligence_handlers::save_domain_profile)
+                .delete(intelligence_handlers::delete_domain_profile),
+        )
         // Workflow API endpoints
         .route(
             "/api/workflow/intelligent",
-            post(workflow_handlers::execute_intelligent_workflow),
+            post(operations::execute_intelligent_workflow),
         )
         .route(
             "/api/workflow/simple",
@@ -416,19 +708,227 @@ pub async fn serve(port: u16, browser_pool: BrowserPool) -> Result<()> {
         // Static files (serve our migrated interface)
         .nest_service("/static", ServeDir::new("static"))
         .route("/", get(dashboard))
-        .layer(CorsLayer::permissive())
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            federation::route_to_worker,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            idempotency::replay,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            quotas::enforce,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::authenticate,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            http_security.clone(),
+            security::dashboard_headers,
+        ))
+        .layer(http_security.cors())
         .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .layer(axum::middleware::from_fn(trace::correlate))
+        .with_state(state.clone());
 
     // Bind to loopback and gracefully retry on EADDRINUSE (Windows 10048)
     let (listener, actual_port) = bind_with_retry(port, 3).await?;
     let addr = format_addr(actual_port);
     info!("API server listening on {}", addr);
-    axum::serve(listener, app).await?;
+    serve_until_shutdown(listener, app, state).await
+}
+
+/// Resolves on Ctrl+C, or SIGTERM on Unix
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Serve until a shutdown signal, then drain and release browsers.
+///
+/// New connections are refused once the signal arrives. In-flight requests, including
+/// tool and workflow executions, get `RAINBOW_SHUTDOWN_TIMEOUT_SECS` (default 30) to
+/// finish before sessions are persisted and Chromium is closed.
+async fn serve_until_shutdown(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    state: AppState,
+) -> Result<()> {
+    let drain_secs = std::env::var("RAINBOW_SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+    let (signalled_tx, mut signalled_rx) = tokio::sync::watch::channel(false);
+
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        info!("Shutdown requested, draining in-flight requests");
+        let _ = signalled_tx.send(true);
+    });
+    let drain_deadline = async move {
+        if signalled_rx.wait_for(|signalled| *signalled).await.is_ok() {
+            tokio::time::sleep(Duration::from_secs(drain_secs)).await;
+        } else {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    tokio::select! {
+        result = server => result?,
+        _ = drain_deadline => warn!(
+            "In-flight requests still running after {}s, shutting down anyway",
+            drain_secs
+        ),
+    }
 
+    shutdown(&state).await;
     Ok(())
 }
 
+/// Persist sessions and close every browser process
+async fn shutdown(state: &AppState) {
+    let snapshot_file = crate::browser::session::snapshot_file();
+    if let Err(e) = state
+        .session_manager
+        .persist_snapshots(&snapshot_file)
+        .await
+    {
+        error!("Failed to persist sessions: {}", e);
+    }
+    if let Err(e) = state.learning.flush().await {
+        error!("Failed to persist learning data: {}", e);
+    }
+    state.browser_pool.shutdown().await;
+    info!("API server stopped");
+}
+
+/// Sticky browsers for stateless calls, released after 10 minutes without use
+fn start_affinity(browser_pool: Arc<BrowserPool>) -> Arc<BrowserAffinity> {
+    let affinity = Arc::new(BrowserAffinity::new(browser_pool, Duration::from_secs(600)));
+    affinity.clone().start_expiry(Duration::from_secs(60));
+    affinity
+}
+
+/// Quotas from the environment, with request counters of idle clients dropped every minute
+fn start_quotas() -> Arc<quotas::Quotas> {
+    let quotas = Arc::new(quotas::Quotas::from_env());
+    let cleanup = quotas.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            cleanup.cleanup().await;
+        }
+    });
+    quotas
+}
+
+/// Job workers, `RAINBOW_JOB_WORKERS` (default 2) of them
+fn start_job_workers(state: &AppState) {
+    let workers = std::env::var("RAINBOW_JOB_WORKERS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(2)
+        .max(1);
+    jobs::start_workers(state, workers);
+}
+
+/// Federation over `RAINBOW_WORKERS`, with worker health probed every 15 seconds
+fn start_federation() -> Option<Arc<federation::Federation>> {
+    let federation = Arc::new(federation::Federation::from_env()?);
+    federation
+        .clone()
+        .start_health_checks(Duration::from_secs(15));
+    Some(federation)
+}
+
+/// Trace store recording the session activity each request causes
+fn start_traces(session_manager: &SessionManager) -> Arc<trace::TraceStore> {
+    let traces = Arc::new(trace::TraceStore::new());
+    traces.follow_activity(session_manager.subscribe_activity());
+    traces
+}
+
+/// Learning state loaded from disk and flushed every minute while it changes;
+/// tenants' learning is kept under `tenants/` next to the default partition's
+async fn start_learning() -> Arc<tenants::TenantLearning> {
+    let store = LearningStore::new();
+    let path = store.path().display().to_string();
+    let tenant_dir = store.path().parent().map(|dir| dir.join("tenants"));
+    let learning = match SharedLearning::load(store).await {
+        Ok(learning) => Arc::new(learning),
+        Err(e) => {
+            // Leave the unreadable file alone rather than overwrite it
+            warn!(
+                "Learning data at {} not loaded, learning in memory only: {}",
+                path, e
+            );
+            let learning = Arc::new(SharedLearning::in_memory());
+            return Arc::new(tenants::TenantLearning::new(learning, None));
+        }
+    };
+    learning.clone().start_flush(Duration::from_secs(60));
+    Arc::new(tenants::TenantLearning::new(learning, tenant_dir))
+}
+
+/// Apply the adaptation profile of `url`'s origin after navigating there
+async fn apply_domain_profile(
+    state: &AppState,
+    browser: &crate::browser::Browser,
+    url: &str,
+) -> Option<AppliedProfile> {
+    let profile = state
+        .learning
+        .current()
+        .await
+        .adaptation_manager
+        .read()
+        .await
+        .profile_for(url)
+        .cloned()?;
+    match profile.apply(browser).await {
+        Ok(applied) => Some(applied),
+        Err(e) => {
+            warn!("Failed to apply domain profile {}: {}", profile.origin, e);
+            None
+        }
+    }
+}
+
+/// Client token of a stateless request, from the `X-Client-Token` header, within
+/// the caller's tenant
+fn client_token(headers: &HeaderMap) -> String {
+    let token = headers
+        .get(CLIENT_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .unwrap_or(DEFAULT_CLIENT);
+    tenants::client_key(token)
+}
+
 // Legacy serve function for fallback when coordinator fails
 async fn serve_legacy(
     port: u16,
@@ -438,55 +938,198 @@ async fn serve_legacy(
     info!("Starting API server in LEGACY mode (coordinator unavailable)");
 
     let session_manager_arc = Arc::new(session_manager);
+    session_manager_arc
+        .clone()
+        .start_cleanup(Duration::from_secs(30));
+    let affinity = start_affinity(browser_pool_arc.clone());
+    let traces = start_traces(&session_manager_arc);
+    // Without a coordinator the API's own events are all the bus carries
+    let events = Arc::new(EventBus::with_config(EventBusConfig::from_env()));
     let state = AppState {
-        browser_pool: browser_pool_arc.clone(),
+        browser_pool: browser_pool_arc,
         session_manager: session_manager_arc.clone(),
-        tool_registry: Arc::new(LazyToolRegistry::new(browser_pool_arc, session_manager_arc)),
-        recent_nav: Arc::new(RwLock::new(HashMap::new())),
+        tool_registry: Arc::new(LazyToolRegistry::new(affinity.clone(), session_manager_arc)),
+        affinity,
+        federation: start_federation(),
+        learning: start_learning().await,
+        embeddings: crate::llm::embeddings::embedding_provider_from_env(),
+        anomalies: Arc::new(RwLock::new(AnomalyDetector::new())),
+        operations: Arc::new(operations::OperationStore::new()),
+        jobs: Arc::new(jobs::JobQueue::new()),
+        auth: auth::start_auth(),
+        api_keys: Arc::new(tenants::ApiKeys::from_env()),
+        audit: Arc::new(audit::AuditLog::new()),
+        quotas: start_quotas(),
+        idempotency: Arc::new(idempotency::ReplayCache::from_env()),
+        events: events.clone(),
+        webhooks: Arc::new(webhooks::Webhooks::from_env(events)),
+        traces,
     };
+    start_job_workers(&state);
 
     // Build app without coordinated endpoints
-    let app = build_legacy_app(state);
+    let app = build_legacy_app(state.clone());
 
     let (listener, actual_port) = bind_with_retry(port, 3).await?;
     let addr = format_addr(actual_port);
     info!("API server (legacy mode) listening on {}", addr);
-    axum::serve(listener, app).await?;
-
-    Ok(())
+    serve_until_shutdown(listener, app, state).await
 }
 
 // Build legacy app without coordination
 fn build_legacy_app(state: AppState) -> Router {
+    let http_security = Arc::new(security::HttpSecurity::from_env());
     Router::new()
         // Health check
         .route("/health", get(health_check))
         .route("/api/health", get(health_check))
         .route("/api/diagnostics", get(diagnostics))
-        .route(
-            "/api/routes",
-            get(|| async move {
-                Json(ApiResponse::success(vec![
-                    "/api/health",
-                    "/api/diagnostics",
-                    "/api/session/create",
-                    "/api/sessions",
-                    "/api/navigate",
-                    "/api/perception/analyze",
-                    "/api/perceive-mode",
-                    "/api/navigate-perceive",
-                    "/api/tools/execute",
-                ]))
-            }),
-        )
+        .route("/api/routes", get(list_routes))
+        .route("/api/openapi.json", get(openapi::openapi_json))
+        .route("/api/docs", get(openapi::swagger_ui))
         // All the existing non-coordinated endpoints
         .route("/api/session/create", post(create_session))
         .route("/api/session/:id", get(get_session).delete(delete_session))
         .route("/api/sessions", get(list_sessions))
+        .route(
+            "/api/session/:id/cookies",
+            get(browser_handlers::get_session_cookies)
+                .post(browser_handlers::set_session_cookies)
+                .delete(browser_handlers::clear_session_cookies),
+        )
+        .route(
+            "/api/session/:id/storage",
+            get(browser_handlers::get_session_storage)
+                .post(browser_handlers::set_session_storage)
+                .delete(browser_handlers::clear_session_storage),
+        )
+        .route(
+            "/api/session/:id/storage/import",
+            post(browser_handlers::import_session_storage),
+        )
+        .route(
+            "/api/session/:id/throttling",
+            get(browser_handlers::get_session_throttling)
+                .post(browser_handlers::set_session_throttling)
+                .delete(browser_handlers::clear_session_throttling),
+        )
+        .route(
+            "/api/session/:id/metrics",
+            get(browser_handlers::get_session_metrics),
+        )
+        .route(
+            "/api/session/:id/console",
+            get(browser_handlers::get_session_console)
+                .delete(browser_handlers::clear_session_console),
+        )
+        .route(
+            "/api/session/:id/dom-changes",
+            get(browser_handlers::get_session_dom_changes),
+        )
+        .route(
+            "/api/session/:id/tags",
+            post(browser_handlers::set_session_tags),
+        )
+        .route(
+            "/api/session/:id/tags/:key",
+            delete(browser_handlers::remove_session_tag),
+        )
+        .route(
+            "/api/session/:id/events",
+            get(browser_handlers::session_events),
+        )
+        .route("/ws", get(ws::interactive_channel))
+        // Long-running operations started with ?async=true
+        .route("/api/operations/:id", get(operations::get_operation))
+        .route(
+            "/api/operations/:id/events",
+            get(operations::operation_events),
+        )
+        // Queued commands, workflows and tool calls
+        .route("/api/jobs", get(jobs::list_jobs).post(jobs::submit_job))
+        .route("/api/jobs/:id", get(jobs::get_job).delete(jobs::cancel_job))
+        // Callbacks for event bus events
+        .route(
+            "/api/webhooks",
+            get(webhooks::list_webhooks).post(webhooks::register_webhook),
+        )
+        .route(
+            "/api/webhooks/:id",
+            get(webhooks::get_webhook).delete(webhooks::delete_webhook),
+        )
+        .route(
+            "/api/webhooks/:id/deliveries",
+            get(webhooks::list_deliveries),
+        )
+        // One-request queries over sessions, runs, metrics and patterns
+        .route("/api/graphql", post(graphql::graphql))
+        // Coordination events, filtered by type, session and source
+        .route("/api/events", get(events::list_events))
+        .route("/api/events/stream", get(events::stream_events))
+        // What a request caused across engines, by correlation id
+        .route("/api/trace/:id", get(trace::get_trace))
+        // Authentication and audit trail
+        .route("/api/auth/me", get(auth::me))
+        .route("/auth/login", get(auth::login))
+        .route("/auth/callback", get(auth::callback))
+        .route("/auth/logout", get(auth::logout))
+        .route("/api/audit", get(audit::list_audit_entries))
+        .route("/api/audit/usage", get(audit::usage_by_principal))
+        .route("/api/audit/tenants", get(audit::usage_by_tenant))
+        // Rate limits and quotas
+        .route("/api/quotas", get(quotas::get_quota))
+        .route(
+            "/api/quotas/:client",
+            axum::routing::put(quotas::set_quota).delete(quotas::remove_quota),
+        )
+        .route(
+            "/api/session/:id/recording",
+            get(browser_handlers::get_session_recording),
+        )
+        .route(
+            "/api/session/:id/recording/start",
+            post(browser_handlers::start_session_recording),
+        )
+        .route(
+            "/api/session/:id/recording/stop",
+            post(browser_handlers::stop_session_recording),
+        )
+        .route(
+            "/api/session/:id/recording/workflow",
+            get(browser_handlers::get_recording_workflow),
+        )
+        .route(
+            "/api/session/:id/replay",
+            post(browser_handlers::replay_session),
+        )
+        .route(
+            "/api/session/:id/save",
+            post(browser_handlers::save_session),
+        )
+        .route(
+            "/api/session/:id/snapshot",
+            get(browser_handlers::get_session_snapshot),
+        )
+        .route(
+            "/api/session/restore",
+            post(browser_handlers::restore_session),
+        )
         .route("/api/navigate", post(navigate))
         .route("/api/screenshot", post(screenshot))
         .route("/api/click", post(click))
         .route("/api/type", post(type_text))
+        .route("/api/keys", post(browser_handlers::send_keys))
+        .route("/api/dialogs", get(browser_handlers::list_dialogs))
+        .route("/api/performance", get(browser_handlers::get_performance))
+        .route(
+            "/api/pool/health",
+            get(browser_handlers::get_pool_health).post(browser_handlers::check_pool_health),
+        )
+        .route("/api/federation/workers", get(federation::list_workers))
+        .route(
+            "/api/dialogs/policy",
+            get(browser_handlers::get_dialog_policy).post(browser_handlers::set_dialog_policy),
+        )
         .route("/api/execute", post(execute_script))
         .route("/api/find", post(find_elements))
         .route("/api/get_text", post(get_text))
@@ -494,9 +1137,58 @@ fn build_legacy_app(state: AppState) -> Router {
         .route("/api/zoom", post(set_zoom_level))
         .route("/api/fix_scaling", post(fix_content_scaling))
         .route("/api/fix_window", post(fix_window_completely))
+        .route(
+            "/api/tabs",
+            get(browser_handlers::list_tabs).post(browser_handlers::open_tab),
+        )
+        .route("/api/tabs/:id", delete(browser_handlers::close_tab))
+        .route("/api/tabs/:id/activate", post(browser_handlers::switch_tab))
+        // Device emulation
+        .route(
+            "/api/emulation/devices",
+            get(browser_handlers::list_devices),
+        )
+        .route(
+            "/api/emulation/geolocation",
+            post(browser_handlers::set_geolocation).delete(browser_handlers::clear_geolocation),
+        )
+        .route(
+            "/api/emulation/timezone",
+            post(browser_handlers::set_timezone),
+        )
+        // Persistent profiles
+        .route(
+            "/api/profiles",
+            get(browser_handlers::list_profiles).post(browser_handlers::create_profile),
+        )
+        .route(
+            "/api/profiles/:name",
+            delete(browser_handlers::delete_profile),
+        )
+        // Permissions
+        .route(
+            "/api/permissions",
+            post(browser_handlers::set_permissions).delete(browser_handlers::reset_permissions),
+        )
+        // Downloads
+        .route("/api/downloads", get(browser_handlers::list_downloads))
+        .route(
+            "/api/downloads/wait",
+            post(browser_handlers::wait_for_download),
+        )
+        .route(
+            "/api/downloads/config",
+            post(browser_handlers::set_download_dir),
+        )
+        .route("/api/downloads/:id", get(browser_handlers::get_download))
+        .route(
+            "/api/downloads/:id/file",
+            get(browser_handlers::get_download_file),
+        )
         .route("/api/workflow", post(execute_workflow))
         .route("/api/tools", get(list_tools))
         .route("/api/tools/execute", post(execute_tool))
+        .route("/api/batch", post(batch::execute_batch))
         .route("/api/tools/metadata", get(get_tools_metadata))
         .route("/api/tools/validate", post(validate_registry))
         .route(
@@ -564,15 +1256,33 @@ fn build_legacy_app(state: AppState) -> Router {
             "/api/perception/forms/fill",
             post(perception_handlers::auto_fill_form),
         )
+        .route(
+            "/api/perception/forms/profiles",
+            get(perception_handlers::list_fill_profiles)
+                .post(perception_handlers::save_fill_profile),
+        )
+        .route(
+            "/api/perception/forms/profiles/:name",
+            delete(perception_handlers::delete_fill_profile),
+        )
+        .route(
+            "/api/perception/schemas",
+            get(perception_handlers::list_perception_schemas),
+        )
+        .route(
+            "/api/perception/schemas/:kind",
+            get(perception_handlers::get_perception_schema),
+        )
         // Combined navigate + perceive (available in legacy mode too)
         .route(
             "/api/navigate-perceive",
             post(perception_handlers::navigate_and_perceive),
         )
         .route(
-            "/api/perceive-mode",
-            post(perception_handlers::perceive_with_mode),
+            "/api/perception/batch",
+            post(perception_handlers::batch_perceive),
         )
+        .route("/api/perceive-mode", post(operations::perceive_with_mode))
         .route("/api/quick-scan", post(perception_handlers::quick_scan))
         .route(
             "/api/smart-element-search",
@@ -587,8 +1297,8 @@ fn build_legacy_app(state: AppState) -> Router {
             post(perception_handlers::intelligent_find_element),
         )
         .route("/api/llm/query", post(llm_handlers::llm_query))
-        .route("/api/llm/plan", post(llm_handlers::task_planning))
-        .route("/api/llm/execute", post(llm_handlers::execute_command))
+        .route("/api/llm/plan", post(operations::task_planning))
+        .route("/api/llm/execute", post(operations::execute_command))
         .route("/api/llm/usage", post(llm_handlers::get_usage_metrics))
         .route(
             "/api/intelligence/analyze",
@@ -610,9 +1320,31 @@ fn build_legacy_app(state: AppState) -> Router {
             "/api/intelligence/config",
             post(intelligence_handlers::update_intelligence_config),
         )
+        .route(
+            "/api/intelligence/patterns/export",
+            get(intelligence_handlers::export_patterns),
+        )
+        .route(
+            "/api/intelligence/patterns/import",
+            post(intelligence_handlers::import_patterns),
+        )
+        .route(
+            "/api/intelligence/elements/match",
+            post(intelligence_handlers::match_elements),
+        )
+        .route(
+            "/api/intelligence/anomalies",
+            get(intelligence_handlers::list_anomalies),
+        )
+        .route(
+            "/api/intelligence/domains",
+            get(intelligence_handlers::list_domain_profiles)
+                .post(intelligence_handlers::save_domain_profile)
+                .delete(intelligence_handlers::delete_domain_profile),
+        )
         .route(
             "/api/workflow/intelligent",
-            post(workflow_handlers::execute_intelligent_workflow),
+            post(operations::execute_intelligent_workflow),
         )
         .route(
             "/api/workflow/simple",
@@ -624,12 +1356,40 @@ fn build_legacy_app(state: AppState) -> Router {
         )
         .nest_service("/static", ServeDir::new("static"))
         .route("/", get(dashboard))
-        .layer(CorsLayer::permissive())
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            federation::route_to_worker,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            idempotency::replay,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            quotas::enforce,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::authenticate,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            http_security.clone(),
+            security::dashboard_headers,
+        ))
+        .layer(http_security.cors())
         .layer(TraceLayer::new_for_http())
+        .layer(axum::middleware::from_fn(trace::correlate))
         .with_state(state)
 }
 
 // Health check endpoint
+/// Liveness probe with build information
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    tag = "health",
+    responses((status = 200, description = "Service status and build", body = Object))
+)]
 async fn health_check() -> impl IntoResponse {
     Json(serde_json::json!({
         "status": "healthy",
@@ -644,7 +1404,53 @@ async fn health_check() -> impl IntoResponse {
     }))
 }
 
+// Static list of important routes for diagnostics
+fn route_list() -> Vec<&'static str> {
+    vec![
+        "/api/health",
+        "/api/diagnostics",
+        "/api/session/create",
+        "/api/sessions",
+        "/api/navigate",
+        "/api/perception/analyze",
+        "/api/perceive-mode",
+        "/api/navigate-perceive",
+        "/api/tools/execute",
+        "/api/batch",
+        "/api/tabs",
+        "/api/downloads",
+        "/ws",
+        "/api/operations/:id/events",
+        "/api/jobs",
+        "/api/auth/me",
+        "/api/audit",
+        "/api/quotas",
+        "/api/openapi.json",
+        "/api/docs",
+    ]
+}
+
+/// The most important routes, for quick discovery
+#[utoipa::path(
+    get,
+    path = "/api/routes",
+    tag = "health",
+    responses((status = 200, description = "Route paths", body = ApiEnvelope))
+)]
+async fn list_routes() -> Response {
+    Json(ApiResponse::success(route_list())).into_response()
+}
+
 // Dashboard HTML - serve our migrated interface
+/// The web dashboard
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "dashboard",
+    responses(
+        (status = 200, description = "Dashboard HTML", content_type = "text/html", body = String),
+    )
+)]
 async fn dashboard() -> impl IntoResponse {
     match tokio::fs::read_to_string("static/index.html").await {
         Ok(html) => axum::response::Html(html).into_response(),
@@ -673,6 +1479,16 @@ async fn dashboard() -> impl IntoResponse {
 }
 
 // Diagnostics endpoint
+/// Registry state and, with `?probe=true`, whether a browser can be launched
+#[utoipa::path(
+    get,
+    path = "/api/diagnostics",
+    tag = "health",
+    params(
+        ("probe" = Option<bool>, Query, description = "Also try to launch a browser"),
+    ),
+    responses((status = 200, description = "Diagnostics report", body = ApiEnvelope))
+)]
 async fn diagnostics(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
@@ -715,62 +1531,99 @@ async fn diagnostics(
 }
 
 // Request/Response types
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct NavigateRequest {
     url: String,
     #[serde(default)]
     session_id: Option<String>,
+    /// Optional device preset to emulate before navigating (e.g. "iphone", "pixel")
+    #[serde(default)]
+    device: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ScreenshotRequest {
     url: Option<String>,
     full_page: Option<bool>,
     format: Option<String>,
+    #[serde(default)]
+    device: Option<String>,
+    /// Capture only the element matching this selector
+    #[serde(default)]
+    selector: Option<String>,
+    /// Capture only this region of the document
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    clip: Option<crate::browser::core::ElementRect>,
+}
+
+fn unknown_device_response(device: &str) -> Response {
+    let available: Vec<_> = crate::browser::emulation::DEVICE_PRESETS
+        .iter()
+        .map(|p| p.name)
+        .collect();
+    let error = ApiError::new(
+        ErrorCode::InvalidRequest,
+        format!(
+            "Unknown device: {} (available: {})",
+            device,
+            available.join(", ")
+        ),
+    )
+    .with_details(serde_json::json!({ "device": device, "available": available }));
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse::<()>::failure(error)),
+    )
+        .into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ClickRequest {
     selector: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct TypeRequest {
     selector: String,
     text: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ExecuteRequest {
     script: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct FindRequest {
     selector: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct GetTextRequest {
     selector: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ScrollRequest {
     x: i32,
     y: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ZoomRequest {
     zoom_factor: f64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[aliases(ApiEnvelope = ApiResponse<serde_json::Value>)]
 struct ApiResponse<T> {
     success: bool,
     data: Option<T>,
-    error: Option<String>,
+    error: Option<ApiError>,
+    /// Where a paged list's `data` sits in the whole list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<pagination::PageInfo>,
 }
 
 impl<T> ApiResponse<T> {
@@ -779,44 +1632,96 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            page: None,
         }
     }
 
-    fn error(msg: String) -> Self {
+    fn with_page(mut self, page: pagination::PageInfo) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    fn error(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self::failure(ApiError::new(code, message))
+    }
+
+    fn failure(error: ApiError) -> Self {
         Self {
             success: false,
             data: None,
-            error: Some(msg),
+            error: Some(error),
+            page: None,
         }
     }
 }
 
 // API Handlers
-async fn navigate(State(state): State<AppState>, Json(req): Json<NavigateRequest>) -> Response {
+/// Navigate a session, or the shared browser, to a URL
+#[utoipa::path(
+    post,
+    path = "/api/navigate",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = NavigateRequest,
+    responses((status = 200, description = "Navigation result", body = ApiEnvelope))
+)]
+async fn navigate(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<NavigateRequest>,
+) -> Response {
+    if let Some(device) = &req.device {
+        if crate::browser::DevicePreset::find(device).is_none() {
+            return unknown_device_response(device);
+        }
+    }
+
     // If a session_id is provided, navigate using that session's browser and update its state
     if let Some(session_id) = &req.session_id {
         if let Some(session_arc) = state.session_manager.get_session(session_id).await {
             let mut session = session_arc.write().await;
+            if let Some(device) = &req.device {
+                if let Err(e) = session.browser.emulate_device_preset(device).await {
+                    error!("Device emulation failed: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
+                            e.to_string(),
+                        )),
+                    )
+                        .into_response();
+                }
+            }
+            let started = std::time::Instant::now();
             match session.navigate(&req.url).await {
                 Ok(_) => {
-                    // Promote this browser as the active tool-registry browser for non-session flows
-                    let browser_arc = session.browser.clone();
-                    tokio::spawn({
-                        let tr = state.tool_registry.clone();
-                        async move {
-                            tr.set_active_browser(browser_arc).await;
-                        }
-                    });
-                    // Record recent navigation for this session
-                    {
-                        let mut map = state.recent_nav.write().await;
-                        map.insert(session_id.clone(), req.url.clone());
-                    }
+                    let load_time_ms = started.elapsed().as_millis() as u64;
+                    let domain_profile =
+                        apply_domain_profile(&state, &session.browser, &req.url).await;
+                    state.session_manager.record_activity(
+                        session_id,
+                        ActivityKind::Navigation,
+                        serde_json::json!({ "url": req.url, "device": req.device }),
+                    );
+                    let _ = state
+                        .events
+                        .emit(Event::NavigationCompleted {
+                            session_id: session_id.clone(),
+                            url: req.url.clone(),
+                            load_time_ms,
+                            timestamp: std::time::Instant::now(),
+                        })
+                        .await;
                     return Json(ApiResponse::success(serde_json::json!({
                         "url": req.url,
                         "status": "navigated",
                         "session_id": session_id,
-                        "session": true
+                        "session": true,
+                        "device": req.device,
+                        "domain_profile": domain_profile
                     })))
                     .into_response();
                 }
@@ -824,7 +1729,10 @@ async fn navigate(State(state): State<AppState>, Json(req): Json<NavigateRequest
                     error!("Session navigation failed: {}", e);
                     return (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(e.to_string())),
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
+                            e.to_string(),
+                        )),
                     )
                         .into_response();
                 }
@@ -832,68 +1740,117 @@ async fn navigate(State(state): State<AppState>, Json(req): Json<NavigateRequest
         } else {
             return (
                 StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error(format!(
-                    "Session not found: {}",
-                    session_id
-                ))),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::SessionNotFound,
+                    format!("Session not found: {}", session_id),
+                )),
             )
                 .into_response();
         }
     }
 
-    // Fallback: use a pooled browser (legacy behavior)
-    match state.browser_pool.acquire().await {
-        Ok(browser) => match browser.navigate_to(&req.url).await {
-            Ok(_) => {
-                // Promote this pool browser as the active tool-registry browser for non-session flows
-                let browser_arc = browser.browser_arc();
-                {
-                    let tr = state.tool_registry.clone();
-                    tokio::spawn(async move {
-                        tr.set_active_browser(browser_arc).await;
-                    });
-                }
-                // Record global recent navigation hint
-                {
-                    let mut map = state.recent_nav.write().await;
-                    map.insert("__global__".to_string(), req.url.clone());
+    // Fallback: the browser bound to this client
+    match state.affinity.browser(&client_token(&headers)).await {
+        Ok(browser) => {
+            if let Some(device) = &req.device {
+                if let Err(e) = browser.emulate_device_preset(device).await {
+                    error!("Device emulation failed: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
+                            e.to_string(),
+                        )),
+                    )
+                        .into_response();
                 }
-                Json(ApiResponse::success(serde_json::json!({
-                    "url": req.url,
-                    "status": "navigated",
-                    "session": false
-                })))
-                .into_response()
             }
-            Err(e) => {
-                error!("Navigation failed: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
-                )
+            match browser.navigate_to(&req.url).await {
+                Ok(_) => {
+                    let domain_profile = apply_domain_profile(&state, &browser, &req.url).await;
+                    Json(ApiResponse::success(serde_json::json!({
+                        "url": req.url,
+                        "status": "navigated",
+                        "session": false,
+                        "device": req.device,
+                        "domain_profile": domain_profile
+                    })))
                     .into_response()
+                }
+                Err(e) => {
+                    error!("Navigation failed: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
+                            e.to_string(),
+                        )),
+                    )
+                        .into_response()
+                }
             }
-        },
+        }
         Err(e) => {
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn screenshot(State(state): State<AppState>, Json(req): Json<ScreenshotRequest>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Capture the page, one element or a region as a base64 image
+#[utoipa::path(
+    post,
+    path = "/api/screenshot",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = ScreenshotRequest,
+    responses((status = 200, description = "Screenshot", body = ApiEnvelope))
+)]
+async fn screenshot(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ScreenshotRequest>,
+) -> Response {
+    if let Some(device) = &req.device {
+        if crate::browser::DevicePreset::find(device).is_none() {
+            return unknown_device_response(device);
+        }
+    }
+
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => {
+            // Emulate the device before loading so the page renders its mobile layout
+            if let Some(device) = &req.device {
+                if let Err(e) = browser.emulate_device_preset(device).await {
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
+                            e.to_string(),
+                        )),
+                    )
+                        .into_response();
+                }
+            }
+
             // Navigate if URL provided
             if let Some(url) = req.url {
                 if let Err(e) = browser.navigate_to(&url).await {
                     return (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(e.to_string())),
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
+                            e.to_string(),
+                        )),
                     )
                         .into_response();
                 }
@@ -906,6 +1863,8 @@ async fn screenshot(State(state): State<AppState>, Json(req): Json<ScreenshotReq
             if let Some(format) = req.format {
                 options.format = format;
             }
+            options.selector = req.selector.clone();
+            options.clip = req.clip;
 
             match browser.screenshot(options).await {
                 Ok(data) => {
@@ -913,7 +1872,9 @@ async fn screenshot(State(state): State<AppState>, Json(req): Json<ScreenshotReq
                     let base64 = base64::engine::general_purpose::STANDARD.encode(&data);
                     Json(ApiResponse::success(serde_json::json!({
                         "screenshot": base64,
-                        "size": data.len()
+                        "size": data.len(),
+                        "device": req.device,
+                        "selector": req.selector
                     })))
                     .into_response()
                 }
@@ -921,7 +1882,10 @@ async fn screenshot(State(state): State<AppState>, Json(req): Json<ScreenshotReq
                     error!("Screenshot failed: {}", e);
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(e.to_string())),
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
+                            e.to_string(),
+                        )),
                     )
                         .into_response()
                 }
@@ -931,15 +1895,33 @@ async fn screenshot(State(state): State<AppState>, Json(req): Json<ScreenshotReq
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn click(State(state): State<AppState>, Json(req): Json<ClickRequest>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Click the element matching a selector
+#[utoipa::path(
+    post,
+    path = "/api/click",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = ClickRequest,
+    responses((status = 200, description = "Click result", body = ApiEnvelope))
+)]
+async fn click(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ClickRequest>,
+) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.click(&req.selector).await {
             Ok(_) => Json(ApiResponse::success(serde_json::json!({
                 "selector": req.selector,
@@ -950,7 +1932,10 @@ async fn click(State(state): State<AppState>, Json(req): Json<ClickRequest>) ->
                 error!("Click failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -959,15 +1944,33 @@ async fn click(State(state): State<AppState>, Json(req): Json<ClickRequest>) ->
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn type_text(State(state): State<AppState>, Json(req): Json<TypeRequest>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Type text into the element matching a selector
+#[utoipa::path(
+    post,
+    path = "/api/type",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = TypeRequest,
+    responses((status = 200, description = "Typing result", body = ApiEnvelope))
+)]
+async fn type_text(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<TypeRequest>,
+) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.type_text(&req.selector, &req.text).await {
             Ok(_) => Json(ApiResponse::success(serde_json::json!({
                 "selector": req.selector,
@@ -979,7 +1982,10 @@ async fn type_text(State(state): State<AppState>, Json(req): Json<TypeRequest>)
                 error!("Type failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -988,25 +1994,43 @@ async fn type_text(State(state): State<AppState>, Json(req): Json<TypeRequest>)
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// Evaluate JavaScript in the page
+#[utoipa::path(
+    post,
+    path = "/api/execute",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = ExecuteRequest,
+    responses((status = 200, description = "Script result", body = ApiEnvelope))
+)]
 async fn execute_script(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<ExecuteRequest>,
 ) -> Response {
-    match state.browser_pool.acquire().await {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.execute_script(&req.script).await {
             Ok(result) => Json(ApiResponse::success(result)).into_response(),
             Err(e) => {
                 error!("Script execution failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -1015,22 +2039,43 @@ async fn execute_script(
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn find_elements(State(state): State<AppState>, Json(req): Json<FindRequest>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Elements matching a selector
+#[utoipa::path(
+    post,
+    path = "/api/find",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = FindRequest,
+    responses((status = 200, description = "Matching elements", body = ApiEnvelope))
+)]
+async fn find_elements(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FindRequest>,
+) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.find_elements(&req.selector).await {
             Ok(elements) => Json(ApiResponse::success(elements)).into_response(),
             Err(e) => {
                 error!("Find elements failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -1039,15 +2084,33 @@ async fn find_elements(State(state): State<AppState>, Json(req): Json<FindReques
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn get_text(State(state): State<AppState>, Json(req): Json<GetTextRequest>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Text content of the element matching a selector
+#[utoipa::path(
+    post,
+    path = "/api/get_text",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = GetTextRequest,
+    responses((status = 200, description = "Element text", body = ApiEnvelope))
+)]
+async fn get_text(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<GetTextRequest>,
+) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.get_text(&req.selector).await {
             Ok(text) => Json(ApiResponse::success(serde_json::json!({
                 "selector": req.selector,
@@ -1058,7 +2121,10 @@ async fn get_text(State(state): State<AppState>, Json(req): Json<GetTextRequest>
                 error!("Get text failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -1067,15 +2133,33 @@ async fn get_text(State(state): State<AppState>, Json(req): Json<GetTextRequest>
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn scroll(State(state): State<AppState>, Json(req): Json<ScrollRequest>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Scroll the page to a position
+#[utoipa::path(
+    post,
+    path = "/api/scroll",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = ScrollRequest,
+    responses((status = 200, description = "Scroll result", body = ApiEnvelope))
+)]
+async fn scroll(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ScrollRequest>,
+) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.scroll_to(req.x, req.y).await {
             Ok(_) => Json(ApiResponse::success(serde_json::json!({
                 "x": req.x,
@@ -1087,7 +2171,10 @@ async fn scroll(State(state): State<AppState>, Json(req): Json<ScrollRequest>) -
                 error!("Scroll failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -1096,15 +2183,33 @@ async fn scroll(State(state): State<AppState>, Json(req): Json<ScrollRequest>) -
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn set_zoom_level(State(state): State<AppState>, Json(req): Json<ZoomRequest>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Set the page zoom factor
+#[utoipa::path(
+    post,
+    path = "/api/zoom",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    request_body = ZoomRequest,
+    responses((status = 200, description = "Zoom result", body = ApiEnvelope))
+)]
+async fn set_zoom_level(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ZoomRequest>,
+) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.set_zoom_level(req.zoom_factor).await {
             Ok(_) => Json(ApiResponse::success(serde_json::json!({
                 "zoom_factor": req.zoom_factor,
@@ -1115,7 +2220,10 @@ async fn set_zoom_level(State(state): State<AppState>, Json(req): Json<ZoomReque
                 error!("Set zoom level failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -1124,15 +2232,28 @@ async fn set_zoom_level(State(state): State<AppState>, Json(req): Json<ZoomReque
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn fix_content_scaling(State(state): State<AppState>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Reset zoom and device scale when content renders at the wrong size
+#[utoipa::path(
+    post,
+    path = "/api/fix_scaling",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    responses((status = 200, description = "Scaling was fixed", body = ApiEnvelope))
+)]
+async fn fix_content_scaling(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.fix_content_scaling().await {
             Ok(_) => Json(ApiResponse::success(serde_json::json!({
                 "action": "content_scaling_fixed",
@@ -1143,7 +2264,10 @@ async fn fix_content_scaling(State(state): State<AppState>) -> Response {
                 error!("Fix content scaling failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -1152,15 +2276,28 @@ async fn fix_content_scaling(State(state): State<AppState>) -> Response {
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn fix_window_completely(State(state): State<AppState>) -> Response {
-    match state.browser_pool.acquire().await {
+/// Trigger a repaint and fix content scaling when the window renders incorrectly
+#[utoipa::path(
+    post,
+    path = "/api/fix_window",
+    tag = "browser",
+    params(
+        ("X-Client-Token" = Option<String>, Header, description = "Pins the caller to its own browser"),
+    ),
+    responses((status = 200, description = "Window was fixed", body = ApiEnvelope))
+)]
+async fn fix_window_completely(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    match state.affinity.browser(&client_token(&headers)).await {
         Ok(browser) => match browser.fix_window_completely().await {
             Ok(_) => Json(ApiResponse::success(serde_json::json!({
                 "action": "window_completely_fixed",
@@ -1171,7 +2308,10 @@ async fn fix_window_completely(State(state): State<AppState>) -> Response {
                 error!("Complete window fix failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
+                        e.to_string(),
+                    )),
                 )
                     .into_response()
             }
@@ -1180,13 +2320,31 @@ async fn fix_window_completely(State(state): State<AppState>) -> Response {
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// Placeholder for declarative workflows; see `/api/workflow/simple`
+#[utoipa::path(
+    post,
+    path = "/api/workflow",
+    tag = "workflow",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key get the first response replayed"),
+    ),
+    request_body(content = Object, description = "Workflow definition"),
+    responses(
+        (status = 200, description = "Not implemented", body = ApiEnvelope),
+        (status = 409, description = "A request with the same idempotency key is still running", body = ApiEnvelope),
+        (status = 422, description = "Idempotency key reused for a different request", body = ApiEnvelope),
+    )
+)]
 async fn execute_workflow(
     State(state): State<AppState>,
     Json(_workflow): Json<serde_json::Value>,
@@ -1194,58 +2352,176 @@ async fn execute_workflow(
     // TODO: Implement workflow execution
     let _ = state; // Suppress unused warning
     Json(ApiResponse::<()>::error(
+        ErrorCode::NotImplemented,
         "Workflow execution not yet implemented".to_string(),
     ))
     .into_response()
 }
 
 // Session management handlers
-async fn create_session(State(state): State<AppState>) -> Response {
-    match state.session_manager.create_session().await {
-        Ok(session_id) => Json(ApiResponse::success(serde_json::json!({
-            "session_id": session_id,
-            "created": true
-        })))
-        .into_response(),
+#[derive(Deserialize, Default, ToSchema)]
+struct CreateSessionRequest {
+    /// Persistent browser profile to bind the session to
+    #[serde(default)]
+    profile: Option<String>,
+    /// Set to false to give the session a whole browser instead of an incognito context
+    #[serde(default)]
+    isolated: Option<bool>,
+    /// Headless mode, viewport and proxy for the session's browser
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    launch: crate::browser::pool::LaunchOptions,
+    /// Labels to group and find the session by, e.g. owner or workflow id
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    /// TTL, idle timeout, navigation and memory caps for this session
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    limits: crate::browser::SessionLimits,
+    /// Persona used as the system prompt of every LLM call made for this session
+    #[serde(default, alias = "persona")]
+    system_prompt: Option<String>,
+}
+
+/// Create a browser session, optionally bound to a profile, with its own launch options, tags and limits
+#[utoipa::path(
+    post,
+    path = "/api/session/create",
+    tag = "sessions",
+    request_body = Option<CreateSessionRequest>,
+    responses((status = 200, description = "The created session", body = ApiEnvelope))
+)]
+async fn create_session(
+    State(state): State<AppState>,
+    body: Option<Json<CreateSessionRequest>>,
+) -> Response {
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    if let Some(profile) = req.profile.as_deref() {
+        if let Err(e) = crate::browser::profiles::validate_profile_name(profile) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    let options = crate::browser::SessionOptions {
+        isolated: req.isolated.unwrap_or(true),
+        profile: req.profile,
+        launch: req.launch,
+        tags: req.tags,
+        limits: req.limits,
+        system_prompt: req.system_prompt.filter(|prompt| !prompt.trim().is_empty()),
+        owner: auth::current_principal().map(|principal| principal.subject),
+    };
+    if let Err(response) = quotas::check_session_quota(&state).await {
+        return response;
+    }
+    let isolated = options.uses_context();
+    let profile = options.profile.clone();
+    let launch = options.launch.clone();
+    let tags = options.tags.clone();
+    let limits = options.limits.clone();
+    let system_prompt = options.system_prompt.clone();
+    let owner = options.owner.clone();
+
+    match state.session_manager.create_session_with(options).await {
+        Ok(session_id) => {
+            state
+                .quotas
+                .track_session(&quotas::current_client(), &session_id)
+                .await;
+            let _ = state
+                .events
+                .emit(Event::SessionCreated {
+                    session_id: session_id.clone(),
+                    timestamp: std::time::Instant::now(),
+                })
+                .await;
+            Json(ApiResponse::success(serde_json::json!({
+                "session_id": session_id,
+                "profile": profile,
+                "isolated": isolated,
+                "launch": launch,
+                "tags": tags,
+                "limits": limits,
+                "system_prompt": system_prompt,
+                "owner": owner,
+                "created": true
+            })))
+            .into_response()
+        }
         Err(e) => {
             error!("Failed to create session: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// A session's state, tags and limits, as `/api/session/:id` reports them
+fn session_details(session: &crate::browser::session::BrowserSession) -> serde_json::Value {
+    serde_json::json!({
+        "id": session.id,
+        "created_at": session.created_at,
+        "last_used": session.last_used,
+        "current_url": session.current_url,
+        "history": session.history,
+        "age_seconds": session.age_seconds(),
+        "idle_seconds": session.idle_seconds(),
+        "tags": session.tags,
+        "limits": session.limits,
+        "navigation_count": session.navigation_count,
+        "system_prompt": session.system_prompt,
+        "owner": session.owner,
+    })
+}
+
+/// One session with its state, tags and limits
+#[utoipa::path(
+    get,
+    path = "/api/session/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "The session", body = ApiEnvelope))
+)]
 async fn get_session(
     State(state): State<AppState>,
     axum::extract::Path(id): axum::extract::Path<String>,
 ) -> Response {
     if let Some(session) = state.session_manager.get_session(&id).await {
-        let session_guard = session.read().await;
-        Json(ApiResponse::success(serde_json::json!({
-            "id": session_guard.id,
-            "created_at": session_guard.created_at,
-            "last_used": session_guard.last_used,
-            "current_url": session_guard.current_url,
-            "history": session_guard.history,
-            "age_seconds": session_guard.age_seconds(),
-            "idle_seconds": session_guard.idle_seconds(),
-        })))
-        .into_response()
+        let details = session_details(&*session.read().await);
+        Json(ApiResponse::success(details)).into_response()
     } else {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error(format!(
-                "Session not found: {}",
-                id
-            ))),
+            Json(ApiResponse::<()>::error(
+                ErrorCode::SessionNotFound,
+                format!("Session not found: {}", id),
+            )),
         )
             .into_response()
     }
 }
 
+/// Close a session and release its browser
+#[utoipa::path(
+    delete,
+    path = "/api/session/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id")),
+    responses((status = 200, description = "The session was closed", body = ApiEnvelope))
+)]
 async fn delete_session(
     State(state): State<AppState>,
     axum::extract::Path(id): axum::extract::Path<String>,
@@ -1260,19 +2536,60 @@ async fn delete_session(
             error!("Failed to delete session: {}", e);
             (
                 StatusCode::NOT_FOUND,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::NotFound),
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-async fn list_sessions(State(state): State<AppState>) -> Response {
-    let sessions = state.session_manager.list_sessions().await;
-    Json(ApiResponse::success(sessions)).into_response()
+/// Sessions, optionally filtered by `?tag=key` or `?tag=key:value` (repeatable, all must match)
+#[utoipa::path(
+    get,
+    path = "/api/sessions",
+    tag = "sessions",
+    params(
+        ("tag" = Option<Vec<String>>, Query, description = "`key` or `key:value`; repeat to require several tags"),
+        PageQuery,
+    ),
+    responses((status = 200, description = "Matching sessions", body = ApiEnvelope))
+)]
+async fn list_sessions(
+    State(state): State<AppState>,
+    Query(params): Query<Vec<(String, String)>>,
+    page: PageQuery,
+) -> Response {
+    let filter = tag_filter(
+        params
+            .into_iter()
+            .filter(|(name, _)| name == "tag")
+            .map(|(_, tag)| tag),
+    );
+    let sessions = state.session_manager.find_sessions(&filter).await;
+    page.respond(sessions)
+}
+
+/// Session tag filter from `key` and `key:value` tags
+fn tag_filter(tags: impl IntoIterator<Item = String>) -> Vec<(String, Option<String>)> {
+    tags.into_iter()
+        .map(|tag| match tag.split_once(':') {
+            Some((key, value)) => (key.to_string(), Some(value.to_string())),
+            None => (tag, None),
+        })
+        .collect()
 }
 
 // Tools API handlers
+/// Registered tools
+#[utoipa::path(
+    get,
+    path = "/api/tools",
+    tag = "tools",
+    responses((status = 200, description = "Tool names by category", body = ApiEnvelope))
+)]
 async fn list_tools(State(state): State<AppState>) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
@@ -1281,6 +2598,7 @@ async fn list_tools(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1317,13 +2635,28 @@ async fn list_tools(State(state): State<AppState>) -> Response {
     Json(ApiResponse::success(response)).into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ExecuteToolRequest {
     tool_name: String,
     parameters: serde_json::Value,
     session_id: Option<String>, // Add session_id field
 }
 
+/// Execute a tool by name with JSON parameters
+#[utoipa::path(
+    post,
+    path = "/api/tools/execute",
+    tag = "tools",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key get the first response replayed"),
+    ),
+    request_body = ExecuteToolRequest,
+    responses(
+        (status = 200, description = "Tool output", body = ApiEnvelope),
+        (status = 409, description = "A request with the same idempotency key is still running", body = ApiEnvelope),
+        (status = 422, description = "Idempotency key reused for a different request", body = ApiEnvelope),
+    )
+)]
 async fn execute_tool(
     State(state): State<AppState>,
     Json(req): Json<ExecuteToolRequest>,
@@ -1333,6 +2666,9 @@ async fn execute_tool(
         req.tool_name, req.parameters, req.session_id
     );
 
+    // Session whose activity stream reports this execution
+    let mut activity_session = None;
+
     // If session_id is provided, use the session's browser
     let registry = if let Some(session_id) = &req.session_id {
         // Get the session's browser
@@ -1341,18 +2677,10 @@ async fn execute_tool(
                 "Using browser from session {} for tool execution",
                 session_id
             );
+            activity_session = Some(session_id.clone());
             let session_guard = session.read().await;
             // Create a tool registry with the session's browser
-            let browser_arc = session_guard.browser.clone();
-            // Promote as active browser for cross-module sharing
-            let tr = state.tool_registry.clone();
-            let browser_arc_for_promotion = browser_arc.clone();
-            tokio::spawn(async move {
-                tr.set_active_browser(browser_arc_for_promotion).await;
-            });
-            // Use a separate clone for registry construction
-            let browser_arc_for_registry = browser_arc.clone();
-            Arc::new(ToolRegistry::new(browser_arc_for_registry))
+            Arc::new(ToolRegistry::new(session_guard.browser.clone()))
         } else {
             warn!(
                 "Session {} not found, falling back to pool browser",
@@ -1366,6 +2694,7 @@ async fn execute_tool(
                     return (
                         StatusCode::SERVICE_UNAVAILABLE,
                         Json(ApiResponse::<()>::error(
+                            ErrorCode::BrowserUnavailable,
                             "Tool registry not initialized (browser unavailable)".to_string(),
                         )),
                     )
@@ -1382,6 +2711,7 @@ async fn execute_tool(
                 return (
                     StatusCode::SERVICE_UNAVAILABLE,
                     Json(ApiResponse::<()>::error(
+                        ErrorCode::BrowserUnavailable,
                         "Tool registry not initialized (browser unavailable)".to_string(),
                     )),
                 )
@@ -1390,17 +2720,35 @@ async fn execute_tool(
         }
     };
 
-    match registry
+    let started = std::time::Instant::now();
+    let outcome = registry
         .execute_tool(&req.tool_name, req.parameters.clone())
-        .await
-    {
+        .await;
+    if let Some(session_id) = &activity_session {
+        state.session_manager.record_activity(
+            session_id,
+            ActivityKind::for_tool(&req.tool_name),
+            serde_json::json!({
+                "tool": req.tool_name,
+                "parameters": req.parameters,
+                "success": outcome.is_ok(),
+                "error": outcome.as_ref().err().map(|e| e.to_string()),
+                "duration_ms": started.elapsed().as_millis() as u64
+            }),
+        );
+    }
+
+    match outcome {
         Ok(result) => {
             debug!("Tool '{}' executed successfully", req.tool_name);
 
             // If this was a session-bound tool execution, update session state to reflect the real page
             if let Some(session_id) = req.session_id.clone() {
                 if let Some(session_arc) = state.session_manager.get_session(&session_id).await {
-                    let tr = state.tool_registry.clone();
+                    session_arc
+                        .write()
+                        .await
+                        .record_action(&req.tool_name, req.parameters.clone());
                     tokio::spawn(async move {
                         // Update last_used and current_url from the browser
                         let current_url = {
@@ -1417,49 +2765,22 @@ async fn execute_tool(
                             }
                             s.current_url = Some(current_url);
                             s.touch();
-                            // Promote this browser as active for cross-module sharing
-                            tr.set_active_browser(s.browser.clone()).await;
                         }
                     });
                 }
-                // Also record recent navigation if this was navigate_to_url
-                if req.tool_name == "navigate_to_url" {
-                    // Extract URL from input parameters
-                    let url = req
-                        .parameters
-                        .get("url")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("");
-                    if !url.is_empty() {
-                        let mut map = state.recent_nav.write().await;
-                        map.insert(session_id.clone(), url.to_string());
-                    }
-                }
-            }
-            // For non-session flows, if it was navigate_to_url, set global recent nav intent
-            if req.session_id.is_none() && req.tool_name == "navigate_to_url" {
-                let url = req
-                    .parameters
-                    .get("url")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                if !url.is_empty() {
-                    // Promote current active browser if registry exists
-                    if let Ok(r) = state.tool_registry.get().await {
-                        // registry initialization ensures an active browser is set in LazyToolRegistry
-                        let _ = r; // no-op, just ensures get() side effects
-                    }
-                    let mut map = state.recent_nav.write().await;
-                    map.insert("__global__".to_string(), url.to_string());
-                }
             }
             Json(ApiResponse::success(result)).into_response()
         }
         Err(e) => {
             error!("Tool '{}' execution failed: {}", req.tool_name, e);
+            let error = ApiError::new(
+                ErrorCode::classify(&e, ErrorCode::ToolFailed),
+                e.to_string(),
+            )
+            .with_details(serde_json::json!({ "tool_name": req.tool_name }));
             (
                 StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::failure(error)),
             )
                 .into_response()
         }
@@ -1467,7 +2788,15 @@ async fn execute_tool(
 }
 
 // Tool metadata and validation endpoints
-async fn get_tools_metadata(State(state): State<AppState>) -> Response {
+/// Parameter schemas and descriptions of all tools
+#[utoipa::path(
+    get,
+    path = "/api/tools/metadata",
+    tag = "tools",
+    params(PageQuery),
+    responses((status = 200, description = "Tool metadata by name", body = ApiEnvelope))
+)]
+async fn get_tools_metadata(State(state): State<AppState>, page: PageQuery) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
         Err(e) => {
@@ -1475,17 +2804,23 @@ async fn get_tools_metadata(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
                 .into_response();
         }
     };
-    let metadata = registry.get_all_metadata();
-
-    Json(ApiResponse::success(metadata)).into_response()
+    page.respond(registry.get_all_metadata())
 }
 
+/// Check the tool registry for inconsistent registrations
+#[utoipa::path(
+    post,
+    path = "/api/tools/validate",
+    tag = "tools",
+    responses((status = 200, description = "Validation report", body = ApiEnvelope))
+)]
 async fn validate_registry(State(state): State<AppState>) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
@@ -1494,6 +2829,7 @@ async fn validate_registry(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1506,7 +2842,7 @@ async fn validate_registry(State(state): State<AppState>) -> Response {
             error!("Registry validation failed: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(ErrorCode::Internal, e.to_string())),
             )
                 .into_response()
         }
@@ -1515,7 +2851,15 @@ async fn validate_registry(State(state): State<AppState>) -> Response {
 
 // Performance Monitoring API handlers
 
-async fn get_all_performance_stats(State(state): State<AppState>) -> Response {
+/// Aggregated execution statistics of every tool
+#[utoipa::path(
+    get,
+    path = "/api/tools/performance/stats",
+    tag = "tools",
+    params(PageQuery),
+    responses((status = 200, description = "Statistics by tool", body = ApiEnvelope))
+)]
+async fn get_all_performance_stats(State(state): State<AppState>, page: PageQuery) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
         Err(e) => {
@@ -1523,16 +2867,24 @@ async fn get_all_performance_stats(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
                 .into_response();
         }
     };
-    let stats = registry.get_all_performance_stats().await;
-    Json(ApiResponse::success(stats)).into_response()
+    page.respond(registry.get_all_performance_stats().await)
 }
 
+/// Aggregated execution statistics of one tool
+#[utoipa::path(
+    get,
+    path = "/api/tools/performance/stats/{tool_name}",
+    tag = "tools",
+    params(("tool_name" = String, Path, description = "Registered tool name")),
+    responses((status = 200, description = "Tool statistics", body = ApiEnvelope))
+)]
 async fn get_tool_performance_stats(
     State(state): State<AppState>,
     Path(tool_name): Path<String>,
@@ -1544,6 +2896,7 @@ async fn get_tool_performance_stats(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1554,15 +2907,25 @@ async fn get_tool_performance_stats(
         Some(stats) => Json(ApiResponse::success(stats)).into_response(),
         None => (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error(format!(
-                "No performance data found for tool '{}'",
-                tool_name
-            ))),
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("No performance data found for tool '{}'", tool_name),
+            )),
         )
             .into_response(),
     }
 }
 
+/// Most recent tool executions
+#[utoipa::path(
+    get,
+    path = "/api/tools/performance/metrics",
+    tag = "tools",
+    params(
+        ("limit" = Option<usize>, Query, description = "Return only the newest N executions"),
+    ),
+    responses((status = 200, description = "Execution metrics", body = ApiEnvelope))
+)]
 async fn get_recent_performance_metrics(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
@@ -1574,6 +2937,7 @@ async fn get_recent_performance_metrics(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1589,6 +2953,17 @@ async fn get_recent_performance_metrics(
     Json(ApiResponse::success(metrics)).into_response()
 }
 
+/// Most recent executions of one tool
+#[utoipa::path(
+    get,
+    path = "/api/tools/performance/metrics/{tool_name}",
+    tag = "tools",
+    params(
+        ("tool_name" = String, Path, description = "Registered tool name"),
+        ("limit" = Option<usize>, Query, description = "Return only the newest N executions"),
+    ),
+    responses((status = 200, description = "Execution metrics", body = ApiEnvelope))
+)]
 async fn get_tool_performance_metrics(
     State(state): State<AppState>,
     Path(tool_name): Path<String>,
@@ -1601,6 +2976,7 @@ async fn get_tool_performance_metrics(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1613,6 +2989,13 @@ async fn get_tool_performance_metrics(
     Json(ApiResponse::success(metrics)).into_response()
 }
 
+/// Forget all recorded tool executions
+#[utoipa::path(
+    post,
+    path = "/api/tools/performance/clear",
+    tag = "tools",
+    responses((status = 200, description = "Metrics were cleared", body = ApiEnvelope))
+)]
 async fn clear_performance_metrics(State(state): State<AppState>) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
@@ -1621,6 +3004,7 @@ async fn clear_performance_metrics(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1636,6 +3020,13 @@ async fn clear_performance_metrics(State(state): State<AppState>) -> Response {
 
 // Cache Management API handlers
 
+/// Hit rates and sizes of the tool result caches
+#[utoipa::path(
+    get,
+    path = "/api/tools/cache/stats",
+    tag = "tools",
+    responses((status = 200, description = "Cache statistics", body = ApiEnvelope))
+)]
 async fn get_cache_stats(State(state): State<AppState>) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
@@ -1644,6 +3035,7 @@ async fn get_cache_stats(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1654,6 +3046,13 @@ async fn get_cache_stats(State(state): State<AppState>) -> Response {
     Json(ApiResponse::success(stats)).into_response()
 }
 
+/// Clear every tool result cache
+#[utoipa::path(
+    post,
+    path = "/api/tools/cache/clear",
+    tag = "tools",
+    responses((status = 200, description = "Caches were cleared", body = ApiEnvelope))
+)]
 async fn clear_all_cache(State(state): State<AppState>) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
@@ -1662,6 +3061,7 @@ async fn clear_all_cache(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1677,6 +3077,14 @@ async fn clear_all_cache(State(state): State<AppState>) -> Response {
     .into_response()
 }
 
+/// Clear the result cache of one tool
+#[utoipa::path(
+    post,
+    path = "/api/tools/cache/clear/{tool_name}",
+    tag = "tools",
+    params(("tool_name" = String, Path, description = "Registered tool name")),
+    responses((status = 200, description = "Cache was cleared", body = ApiEnvelope))
+)]
 async fn clear_tool_cache(
     State(state): State<AppState>,
     Path(tool_name): Path<String>,
@@ -1688,6 +3096,7 @@ async fn clear_tool_cache(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1703,7 +3112,7 @@ async fn clear_tool_cache(
     .into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct CacheConfigRequest {
     ttl_seconds: Option<u64>,
     max_entries: Option<usize>,
@@ -1711,6 +3120,17 @@ struct CacheConfigRequest {
     invalidate_on_navigation: Option<bool>,
 }
 
+/// Change how one tool's results are cached
+#[utoipa::path(
+    post,
+    path = "/api/tools/cache/config/{tool_name}",
+    tag = "tools",
+    params(("tool_name" = String, Path, description = "Registered tool name")),
+    request_body = CacheConfigRequest,
+    responses(
+        (status = 200, description = "Cache configuration was updated", body = ApiEnvelope),
+    )
+)]
 async fn set_tool_cache_config(
     State(state): State<AppState>,
     Path(tool_name): Path<String>,
@@ -1726,6 +3146,7 @@ async fn set_tool_cache_config(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1756,11 +3177,19 @@ async fn set_tool_cache_config(
 
 // Dependency Management API handlers
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ExecutionPlanRequest {
     tool_names: Vec<String>,
 }
 
+/// Order tools so each runs after the tools it depends on
+#[utoipa::path(
+    post,
+    path = "/api/tools/dependencies/plan",
+    tag = "tools",
+    request_body = ExecutionPlanRequest,
+    responses((status = 200, description = "Execution plan", body = ApiEnvelope))
+)]
 async fn create_execution_plan(
     State(state): State<AppState>,
     Json(req): Json<ExecutionPlanRequest>,
@@ -1772,6 +3201,7 @@ async fn create_execution_plan(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1784,13 +3214,24 @@ async fn create_execution_plan(
             error!("Failed to create execution plan: {}", e);
             (
                 StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// Plan and execute tools in dependency order
+#[utoipa::path(
+    post,
+    path = "/api/tools/dependencies/execute",
+    tag = "tools",
+    request_body = ExecutionPlanRequest,
+    responses((status = 200, description = "Execution results", body = ApiEnvelope))
+)]
 async fn execute_with_dependencies(
     State(state): State<AppState>,
     Json(req): Json<ExecutionPlanRequest>,
@@ -1802,6 +3243,7 @@ async fn execute_with_dependencies(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1817,19 +3259,33 @@ async fn execute_with_dependencies(
             error!("Failed to execute tools with dependencies: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::ToolFailed),
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct RegisterDependenciesRequest {
     tool_name: String,
+    #[schema(value_type = Vec<Object>)]
     dependencies: Vec<crate::tools::dependencies::ToolDependency>,
 }
 
+/// Declare the dependencies of a tool
+#[utoipa::path(
+    post,
+    path = "/api/tools/dependencies/register",
+    tag = "tools",
+    request_body = RegisterDependenciesRequest,
+    responses(
+        (status = 200, description = "Dependencies were registered", body = ApiEnvelope),
+    )
+)]
 async fn register_tool_dependencies(
     State(state): State<AppState>,
     Json(req): Json<RegisterDependenciesRequest>,
@@ -1841,6 +3297,7 @@ async fn register_tool_dependencies(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1857,6 +3314,14 @@ async fn register_tool_dependencies(
     }))).into_response()
 }
 
+/// Tools a tool depends on
+#[utoipa::path(
+    get,
+    path = "/api/tools/dependencies/{tool_name}",
+    tag = "tools",
+    params(("tool_name" = String, Path, description = "Registered tool name")),
+    responses((status = 200, description = "Dependencies", body = ApiEnvelope))
+)]
 async fn get_tool_dependencies(
     State(state): State<AppState>,
     Path(tool_name): Path<String>,
@@ -1868,6 +3333,7 @@ async fn get_tool_dependencies(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1878,6 +3344,14 @@ async fn get_tool_dependencies(
     Json(ApiResponse::success(dependencies)).into_response()
 }
 
+/// Tools that depend on a tool
+#[utoipa::path(
+    get,
+    path = "/api/tools/dependencies/dependents/{tool_name}",
+    tag = "tools",
+    params(("tool_name" = String, Path, description = "Registered tool name")),
+    responses((status = 200, description = "Dependent tools", body = ApiEnvelope))
+)]
 async fn get_dependent_tools(
     State(state): State<AppState>,
     Path(tool_name): Path<String>,
@@ -1889,6 +3363,7 @@ async fn get_dependent_tools(
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
@@ -1899,6 +3374,13 @@ async fn get_dependent_tools(
     Json(ApiResponse::success(dependents)).into_response()
 }
 
+/// Size and shape of the dependency graph
+#[utoipa::path(
+    get,
+    path = "/api/tools/dependencies/stats",
+    tag = "tools",
+    responses((status = 200, description = "Dependency statistics", body = ApiEnvelope))
+)]
 async fn get_dependency_stats(State(state): State<AppState>) -> Response {
     let registry = match state.tool_registry.get().await {
         Ok(r) => r,
@@ -1907,6 +3389,7 @@ async fn get_dependency_stats(State(state): State<AppState>) -> Response {
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     "Tool registry not initialized (browser unavailable)".to_string(),
                 )),
             )
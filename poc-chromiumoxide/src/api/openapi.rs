@@ -0,0 +1,509 @@
+// OpenAPI specification
+// Handlers carry `#[utoipa::path]` annotations; this module gathers them into the
+// document served at /api/openapi.json and browsed with Swagger UI at /api/docs.
+// A test compares the documented paths with the routers so the two cannot drift.
+
+use axum::{
+    extract::Json,
+    response::{Html, IntoResponse, Response},
+};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use super::{
+    audit, auth, batch, browser_handlers, coordinated_handlers, events, federation, graphql,
+    intelligence_handlers, jobs, llm_handlers, operations, perception_handlers, quotas, trace,
+    webhooks, workflow_handlers, ws,
+};
+
+/// Routes served by the same handler as a documented route
+const ALIASES: &[(&str, &str)] = &[
+    ("/health", "/api/health"),
+    ("/api/perception/smart_search", "/api/smart-element-search"),
+    ("/api/perception/find_element", "/api/perception/find"),
+];
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "RainbowBrowserAI API",
+        description = "Browser automation, perception and LLM-driven workflows over a pool of Chromium browsers."
+    ),
+    paths(
+        super::health_check,
+        super::diagnostics,
+        super::list_routes,
+        super::create_session,
+        super::get_session,
+        super::delete_session,
+        super::list_sessions,
+        super::navigate,
+        super::screenshot,
+        super::click,
+        super::type_text,
+        super::execute_script,
+        super::find_elements,
+        super::get_text,
+        super::scroll,
+        super::set_zoom_level,
+        super::fix_content_scaling,
+        super::fix_window_completely,
+        super::execute_workflow,
+        super::list_tools,
+        super::execute_tool,
+        super::get_tools_metadata,
+        super::validate_registry,
+        super::get_all_performance_stats,
+        super::get_tool_performance_stats,
+        super::get_recent_performance_metrics,
+        super::get_tool_performance_metrics,
+        super::clear_performance_metrics,
+        super::get_cache_stats,
+        super::clear_all_cache,
+        super::clear_tool_cache,
+        super::set_tool_cache_config,
+        super::create_execution_plan,
+        super::execute_with_dependencies,
+        super::register_tool_dependencies,
+        super::get_tool_dependencies,
+        super::get_dependent_tools,
+        super::get_dependency_stats,
+        super::dashboard,
+        browser_handlers::get_session_cookies,
+        browser_handlers::set_session_cookies,
+        browser_handlers::clear_session_cookies,
+        browser_handlers::get_session_storage,
+        browser_handlers::set_session_storage,
+        browser_handlers::clear_session_storage,
+        browser_handlers::import_session_storage,
+        browser_handlers::get_session_throttling,
+        browser_handlers::set_session_throttling,
+        browser_handlers::clear_session_throttling,
+        browser_handlers::get_session_metrics,
+        browser_handlers::get_session_console,
+        browser_handlers::clear_session_console,
+        browser_handlers::get_session_dom_changes,
+        browser_handlers::set_session_tags,
+        browser_handlers::remove_session_tag,
+        browser_handlers::session_events,
+        browser_handlers::get_session_recording,
+        browser_handlers::start_session_recording,
+        browser_handlers::stop_session_recording,
+        browser_handlers::get_recording_workflow,
+        browser_handlers::replay_session,
+        browser_handlers::save_session,
+        browser_handlers::get_session_snapshot,
+        browser_handlers::restore_session,
+        browser_handlers::send_keys,
+        browser_handlers::list_dialogs,
+        browser_handlers::get_performance,
+        browser_handlers::get_pool_health,
+        browser_handlers::check_pool_health,
+        browser_handlers::get_dialog_policy,
+        browser_handlers::set_dialog_policy,
+        browser_handlers::list_tabs,
+        browser_handlers::open_tab,
+        browser_handlers::close_tab,
+        browser_handlers::switch_tab,
+        browser_handlers::list_devices,
+        browser_handlers::set_geolocation,
+        browser_handlers::clear_geolocation,
+        browser_handlers::set_timezone,
+        browser_handlers::list_profiles,
+        browser_handlers::create_profile,
+        browser_handlers::delete_profile,
+        browser_handlers::set_permissions,
+        browser_handlers::reset_permissions,
+        browser_handlers::list_downloads,
+        browser_handlers::wait_for_download,
+        browser_handlers::set_download_dir,
+        browser_handlers::get_download,
+        browser_handlers::get_download_file,
+        ws::interactive_channel,
+        operations::get_operation,
+        operations::operation_events,
+        operations::perceive_with_mode,
+        operations::task_planning,
+        operations::execute_command,
+        operations::execute_intelligent_workflow,
+        batch::execute_batch,
+        jobs::submit_job,
+        jobs::list_jobs,
+        jobs::get_job,
+        jobs::cancel_job,
+        webhooks::register_webhook,
+        webhooks::list_webhooks,
+        webhooks::get_webhook,
+        webhooks::delete_webhook,
+        webhooks::list_deliveries,
+        graphql::graphql,
+        events::list_events,
+        events::stream_events,
+        trace::get_trace,
+        auth::me,
+        auth::login,
+        auth::callback,
+        auth::logout,
+        audit::list_audit_entries,
+        audit::usage_by_principal,
+        audit::usage_by_tenant,
+        quotas::get_quota,
+        quotas::set_quota,
+        quotas::remove_quota,
+        federation::list_workers,
+        perception_handlers::analyze_page,
+        perception_handlers::intelligent_find_element,
+        perception_handlers::execute_intelligent_command,
+        perception_handlers::analyze_form,
+        perception_handlers::auto_fill_form,
+        perception_handlers::list_fill_profiles,
+        perception_handlers::save_fill_profile,
+        perception_handlers::delete_fill_profile,
+        perception_handlers::list_perception_schemas,
+        perception_handlers::get_perception_schema,
+        perception_handlers::navigate_and_perceive,
+        perception_handlers::batch_perceive,
+        perception_handlers::quick_scan,
+        perception_handlers::smart_element_search,
+        llm_handlers::llm_query,
+        llm_handlers::get_usage_metrics,
+        intelligence_handlers::analyze_situation,
+        intelligence_handlers::recommend_action,
+        intelligence_handlers::submit_learning_feedback,
+        intelligence_handlers::get_intelligence_statistics,
+        intelligence_handlers::update_intelligence_config,
+        intelligence_handlers::export_patterns,
+        intelligence_handlers::import_patterns,
+        intelligence_handlers::match_elements,
+        intelligence_handlers::list_anomalies,
+        intelligence_handlers::list_domain_profiles,
+        intelligence_handlers::save_domain_profile,
+        intelligence_handlers::delete_domain_profile,
+        workflow_handlers::execute_simple_workflow,
+        workflow_handlers::get_workflow_status,
+        coordinated_handlers::create_coordinated_session,
+        coordinated_handlers::get_coordinated_session,
+        coordinated_handlers::delete_coordinated_session,
+        coordinated_handlers::list_coordinated_sessions,
+        coordinated_handlers::coordinated_navigate,
+        coordinated_handlers::coordinated_intelligent_action,
+        coordinated_handlers::coordinated_perception_analysis,
+        coordinated_handlers::coordinated_tool_execution,
+        coordinated_handlers::get_system_health,
+        openapi_json,
+        swagger_ui,
+    ),
+    components(schemas(
+        super::ApiEnvelope,
+        super::ApiError,
+        super::ErrorCode,
+        perception_handlers::PerceptionEnvelope,
+        perception_handlers::PerformanceMetrics,
+        intelligence_handlers::IntelligenceEnvelope,
+        intelligence_handlers::IntelligenceResponseMetadata,
+        llm_handlers::LlmEnvelope,
+        llm_handlers::LLMResponseMetadata,
+        workflow_handlers::WorkflowEnvelope,
+        workflow_handlers::WorkflowResponseMetadata,
+        coordinated_handlers::CoordinatedEnvelope,
+        coordinated_handlers::OperationMetrics,
+        super::NavigateRequest,
+        super::ScreenshotRequest,
+        super::ClickRequest,
+        super::TypeRequest,
+        super::ExecuteRequest,
+        super::FindRequest,
+        super::GetTextRequest,
+        super::ScrollRequest,
+        super::ZoomRequest,
+        super::CreateSessionRequest,
+        super::ExecuteToolRequest,
+        super::CacheConfigRequest,
+        super::ExecutionPlanRequest,
+        super::RegisterDependenciesRequest,
+        browser_handlers::OpenTabRequest,
+        browser_handlers::WaitDownloadRequest,
+        browser_handlers::DownloadDirRequest,
+        browser_handlers::SetCookiesRequest,
+        browser_handlers::RestoreSessionRequest,
+        browser_handlers::SetStorageRequest,
+        browser_handlers::ThrottlingRequest,
+        browser_handlers::SetTagsRequest,
+        browser_handlers::ReplayRequest,
+        browser_handlers::GeolocationRequest,
+        browser_handlers::TimezoneRequest,
+        browser_handlers::SendKeysRequest,
+        browser_handlers::DialogPolicyRequest,
+        browser_handlers::PermissionsRequest,
+        browser_handlers::CreateProfileRequest,
+        perception_handlers::AnalyzePageRequest,
+        perception_handlers::PerceptionModeRequest,
+        perception_handlers::SmartElementSearchRequest,
+        perception_handlers::FindElementRequest,
+        perception_handlers::IntelligentCommandRequest,
+        perception_handlers::AnalyzeFormRequest,
+        perception_handlers::AutoFillFormRequest,
+        perception_handlers::NavigatePerceiveRequest,
+        perception_handlers::BatchPerceiveRequest,
+        perception_handlers::QuickScanRequest,
+        intelligence_handlers::AnalyzeSituationRequest,
+        intelligence_handlers::RecommendActionRequest,
+        intelligence_handlers::LearningFeedbackRequest,
+        intelligence_handlers::StatisticsRequest,
+        intelligence_handlers::MatchElementsRequest,
+        intelligence_handlers::UpdateConfigRequest,
+        llm_handlers::LLMQueryRequest,
+        llm_handlers::TaskPlanningRequest,
+        llm_handlers::ExecuteCommandRequest,
+        llm_handlers::UsageMetricsRequest,
+        workflow_handlers::IntelligentWorkflowRequest,
+        workflow_handlers::SimpleWorkflowRequest,
+        workflow_handlers::WorkflowStep,
+        workflow_handlers::WorkflowStatusRequest,
+        quotas::QuotaLimits,
+        batch::BatchRequest,
+        batch::BatchCall,
+        jobs::SubmitJobRequest,
+        jobs::JobKind,
+        webhooks::RegisterWebhookRequest,
+        graphql::GraphqlRequest,
+        coordinated_handlers::CoordinatedNavigateRequest,
+        coordinated_handlers::IntelligentActionRequest,
+        coordinated_handlers::ToolExecutionRequest,
+        coordinated_handlers::PerceptionAnalysisRequest,
+        coordinated_handlers::CoordinatedNavigate,
+        coordinated_handlers::CoordinatedIntelligentAction,
+        coordinated_handlers::CoordinatedPerceptionAnalysis,
+        coordinated_handlers::CoordinatedToolExecution,
+    )),
+    modifiers(&BearerAuth, &RouteAliases),
+    security((), ("bearer" = [])),
+    tags(
+        (name = "health", description = "Liveness and diagnostics"),
+        (name = "docs", description = "This specification"),
+        (name = "sessions", description = "Browser sessions and their cookies, storage and settings"),
+        (name = "recording", description = "Recording and replaying session actions"),
+        (name = "browser", description = "Direct browser actions"),
+        (name = "tabs", description = "Tab management"),
+        (name = "emulation", description = "Device, geolocation and timezone emulation"),
+        (name = "profiles", description = "Persistent browser profiles"),
+        (name = "downloads", description = "File downloads"),
+        (name = "tools", description = "Tool registry, performance, caching and dependencies"),
+        (name = "perception", description = "Page perception and smart forms"),
+        (name = "llm", description = "LLM queries, planning and execution"),
+        (name = "intelligence", description = "Situation analysis, learning and adaptation"),
+        (name = "workflow", description = "Multi-step workflows"),
+        (name = "operations", description = "Long-running operations started with `?async=true`"),
+        (name = "jobs", description = "Commands, workflows and tool calls queued for the worker pool"),
+        (name = "webhooks", description = "Callbacks for coordination events"),
+        (name = "graphql", description = "Nested queries over sessions, runs, metrics and patterns"),
+        (name = "events", description = "Coordination events filtered by type, session and source"),
+        (name = "trace", description = "What a request caused across engines, by correlation id"),
+        (name = "realtime", description = "WebSocket channels"),
+        (name = "auth", description = "Sign-in with the configured identity provider"),
+        (name = "audit", description = "Request audit trail and usage"),
+        (name = "quotas", description = "Rate limits and budgets"),
+        (name = "federation", description = "Worker federation"),
+        (name = "coordinated", description = "Coordinated sessions (v2)"),
+        (name = "dashboard", description = "Web dashboard"),
+    )
+)]
+pub struct ApiDoc;
+
+/// JWT bearer tokens, required when `RAINBOW_JWT_ISSUER` or `RAINBOW_JWT_SECRET` is set
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
+/// Document each alias as a copy of the route it stands for
+struct RouteAliases;
+
+impl Modify for RouteAliases {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for (alias, canonical) in ALIASES {
+            let Some(mut item) = openapi.paths.paths.get(*canonical).cloned() else {
+                continue;
+            };
+            for operation in item.operations.values_mut() {
+                // Operation ids must stay unique across the document
+                operation.operation_id = operation
+                    .operation_id
+                    .take()
+                    .map(|id| format!("{}_alias{}", id, alias.replace(['/', '-'], "_")));
+            }
+            openapi.paths.paths.insert(alias.to_string(), item);
+        }
+    }
+}
+
+/// This specification as JSON
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "docs",
+    responses((status = 200, description = "OpenAPI 3 document", body = Object))
+)]
+pub async fn openapi_json() -> Response {
+    Json(ApiDoc::openapi()).into_response()
+}
+
+/// Swagger UI for browsing and trying the API
+#[utoipa::path(
+    get,
+    path = "/api/docs",
+    tag = "docs",
+    responses((status = 200, description = "Swagger UI", content_type = "text/html", body = String))
+)]
+pub async fn swagger_ui() -> Response {
+    Html(SWAGGER_UI).into_response()
+}
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>RainbowBrowserAI API</title>
+    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+    <div id="swagger-ui"></div>
+    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+    <script>
+        window.ui = SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+    </script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    const ROUTER_SOURCE: &str = include_str!("mod.rs");
+    const METHODS: &[&str] = &["get", "post", "put", "delete", "patch"];
+
+    /// Text of `source` from `start` up to the following `end`
+    fn section<'a>(source: &'a str, start: &str, end: &str) -> &'a str {
+        let from = source.find(start).expect("section start");
+        let len = source[from..].find(end).expect("section end");
+        &source[from..from + len]
+    }
+
+    /// (method, path) of every `.route(...)` in `source`, with `:param` written `{param}`
+    fn routes_in(source: &str, prefix: &str) -> BTreeSet<(String, String)> {
+        let mut routes = BTreeSet::new();
+        for (start, _) in source.match_indices(".route(") {
+            let call = &source[start + ".route".len()..];
+            let mut depth = 0;
+            let len = call
+                .char_indices()
+                .find_map(|(i, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    (depth == 0).then_some(i)
+                })
+                .expect("balanced route call");
+            let call = &call[..len];
+            let path = call.split('"').nth(1).expect("route path");
+            let path: Vec<String> = path
+                .split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                })
+                .collect();
+            let path = format!("{}{}", prefix, path.join("/"));
+            for method in METHODS {
+                let called = call.match_indices(&format!("{}(", method)).any(|(i, _)| {
+                    !call[..i]
+                        .chars()
+                        .next_back()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+                });
+                if called {
+                    routes.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        routes
+    }
+
+    fn documented_routes() -> BTreeSet<(String, String)> {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut routes = BTreeSet::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for method in item.as_object().unwrap().keys() {
+                if METHODS.contains(&method.as_str()) {
+                    routes.insert((method.clone(), path.clone()));
+                }
+            }
+        }
+        routes
+    }
+
+    #[test]
+    fn spec_documents_exactly_the_served_routes() {
+        let mut served = routes_in(
+            section(
+                ROUTER_SOURCE,
+                "let app = Router::new()",
+                "// Bind to loopback",
+            ),
+            "",
+        );
+        served.extend(routes_in(
+            section(
+                ROUTER_SOURCE,
+                "let v2_router = Router::new()",
+                ".with_state(coordinated_state)",
+            ),
+            "/api/v2",
+        ));
+        assert!(served.len() > 100, "router source not parsed");
+
+        let documented = documented_routes();
+        let undocumented: Vec<_> = served.difference(&documented).collect();
+        let unserved: Vec<_> = documented.difference(&served).collect();
+        assert!(
+            undocumented.is_empty(),
+            "routes missing from the spec: {:?}",
+            undocumented
+        );
+        assert!(
+            unserved.is_empty(),
+            "documented routes not served: {:?}",
+            unserved
+        );
+    }
+
+    #[test]
+    fn legacy_router_serves_documented_routes() {
+        let legacy = routes_in(section(ROUTER_SOURCE, "fn build_legacy_app", "\n}\n"), "");
+        let documented = documented_routes();
+        let unknown: Vec<_> = legacy.difference(&documented).collect();
+        assert!(
+            unknown.is_empty(),
+            "legacy routes missing from the spec: {:?}",
+            unknown
+        );
+        assert!(legacy.contains(&("get".to_string(), "/api/openapi.json".to_string())));
+    }
+}
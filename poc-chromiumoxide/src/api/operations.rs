@@ -0,0 +1,581 @@
+// Long-running operations
+// Deep perception, intelligent workflows and LLM commands can run for tens of
+// seconds. With `?async=true` their endpoints answer 202 with an operation id
+// right away; progress is streamed from `/api/operations/:id/events` (SSE) and
+// the final response is kept on the operation.
+
+use axum::{
+    body::to_bytes,
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, warn};
+use utoipa::IntoParams;
+
+use super::auth::{current_principal, with_principal, Principal};
+use super::llm_handlers::{self, ExecuteCommandRequest, TaskPlanningRequest};
+use super::perception_handlers::{self, PerceptionModeRequest};
+use super::tenants::current_tenant;
+use super::workflow_handlers::{self, IntelligentWorkflowRequest};
+use super::{ApiResponse, AppState, ErrorCode};
+use crate::coordination::correlation::{current_correlation_id, with_correlation_id};
+
+/// Largest response body kept as an operation's result
+const MAX_RESULT_BYTES: usize = 16 * 1024 * 1024;
+/// Finished operations kept for late readers before the oldest are dropped
+const MAX_FINISHED_OPERATIONS: usize = 200;
+
+tokio::task_local! {
+    /// Operation the current task is running, so handlers can report progress
+    static CURRENT_OPERATION: (Arc<OperationStore>, String);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationEventKind {
+    Started,
+    Progress,
+    Completed,
+    Failed,
+}
+
+impl OperationEventKind {
+    fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
+/// One step of an operation, as sent over SSE
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationEvent {
+    pub operation_id: String,
+    /// Position within the operation, starting at 0
+    pub sequence: usize,
+    pub kind: OperationEventKind,
+    /// Handler-defined step name, e.g. `perception` or `planning`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+    pub detail: serde_json::Value,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    pub id: String,
+    /// Endpoint the operation runs, e.g. `perceive`
+    pub kind: String,
+    pub status: OperationStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// HTTP status the endpoint answered with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// Body the endpoint answered with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    pub events: Vec<OperationEvent>,
+    /// Tenant that started the operation; only it can see the operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+/// Operations started by this server and the broadcast of their events
+pub struct OperationStore {
+    operations: RwLock<HashMap<String, Operation>>,
+    finished: RwLock<VecDeque<String>>,
+    events: broadcast::Sender<OperationEvent>,
+}
+
+impl Default for OperationStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OperationStore {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(1024);
+        Self {
+            operations: RwLock::new(HashMap::new()),
+            finished: RwLock::new(VecDeque::new()),
+            events,
+        }
+    }
+
+    /// Register a running operation and return its id
+    pub async fn start(&self, kind: &str) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        let operation = Operation {
+            id: id.clone(),
+            kind: kind.to_string(),
+            status: OperationStatus::Running,
+            created_at: Utc::now(),
+            finished_at: None,
+            status_code: None,
+            result: None,
+            events: Vec::new(),
+            tenant: current_tenant(),
+        };
+        self.operations.write().await.insert(id.clone(), operation);
+        self.push_event(
+            &id,
+            OperationEventKind::Started,
+            None,
+            serde_json::json!({ "kind": kind }),
+        )
+        .await;
+        id
+    }
+
+    pub async fn progress(&self, id: &str, stage: &str, detail: serde_json::Value) {
+        self.push_event(id, OperationEventKind::Progress, Some(stage), detail)
+            .await;
+    }
+
+    /// Store the endpoint's response and emit the terminal event
+    pub async fn finish(&self, id: &str, status_code: StatusCode, result: serde_json::Value) {
+        let kind = if status_code.is_success() {
+            OperationEventKind::Completed
+        } else {
+            OperationEventKind::Failed
+        };
+        if let Some(operation) = self.operations.write().await.get_mut(id) {
+            operation.status = if kind == OperationEventKind::Completed {
+                OperationStatus::Completed
+            } else {
+                OperationStatus::Failed
+            };
+            operation.finished_at = Some(Utc::now());
+            operation.status_code = Some(status_code.as_u16());
+            operation.result = Some(result);
+        }
+        self.push_event(
+            id,
+            kind,
+            None,
+            serde_json::json!({ "status_code": status_code.as_u16() }),
+        )
+        .await;
+        self.prune(id).await;
+    }
+
+    /// The operation, if the current tenant started it
+    pub async fn get(&self, id: &str) -> Option<Operation> {
+        self.operations
+            .read()
+            .await
+            .get(id)
+            .filter(|operation| operation.tenant == current_tenant())
+            .cloned()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OperationEvent> {
+        self.events.subscribe()
+    }
+
+    async fn push_event(
+        &self,
+        id: &str,
+        kind: OperationEventKind,
+        stage: Option<&str>,
+        detail: serde_json::Value,
+    ) {
+        let event = {
+            let mut operations = self.operations.write().await;
+            let Some(operation) = operations.get_mut(id) else {
+                return;
+            };
+            let event = OperationEvent {
+                operation_id: id.to_string(),
+                sequence: operation.events.len(),
+                kind,
+                stage: stage.map(str::to_string),
+                detail,
+                timestamp: Utc::now(),
+            };
+            operation.events.push(event.clone());
+            event
+        };
+        // No receivers just means nobody is watching
+        let _ = self.events.send(event);
+    }
+
+    async fn prune(&self, id: &str) {
+        let mut finished = self.finished.write().await;
+        finished.push_back(id.to_string());
+        while finished.len() > MAX_FINISHED_OPERATIONS {
+            if let Some(oldest) = finished.pop_front() {
+                self.operations.write().await.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Report progress of the operation the current task runs; a no-op for
+/// synchronous requests
+pub async fn report_progress(stage: &str, detail: serde_json::Value) {
+    let Ok((store, id)) = CURRENT_OPERATION.try_with(|(store, id)| (store.clone(), id.clone()))
+    else {
+        return;
+    };
+    store.progress(&id, stage, detail).await;
+}
+
+/// Body of a response as JSON, or as a JSON string when it is not JSON
+pub(super) async fn response_json(response: Response) -> Result<serde_json::Value, String> {
+    let bytes = to_bytes(response.into_body(), MAX_RESULT_BYTES)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(serde_json::from_slice(&bytes).unwrap_or_else(|_| {
+        serde_json::Value::String(String::from_utf8_lossy(&bytes).into_owned())
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AsyncQuery {
+    /// Answer 202 with an operation id instead of waiting for the result
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct OperationStarted {
+    operation_id: String,
+    status_url: String,
+    events_url: String,
+}
+
+/// Run `handler` as the started operation `id` on behalf of `principal`, then
+/// store and return its response
+pub(super) async fn run_operation<F>(
+    store: Arc<OperationStore>,
+    id: String,
+    principal: Option<Principal>,
+    handler: F,
+) -> (StatusCode, serde_json::Value)
+where
+    F: Future<Output = Response>,
+{
+    let handler = CURRENT_OPERATION.scope((store.clone(), id.clone()), handler);
+    let response = with_principal(principal, handler).await;
+    let status = response.status();
+    let result = match response_json(response).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to read result of operation {}: {}", id, e);
+            serde_json::json!(ApiResponse::<()>::error(ErrorCode::Internal, e))
+        }
+    };
+    debug!("Operation {} finished with {}", id, status);
+    store.finish(&id, status, result.clone()).await;
+    (status, result)
+}
+
+/// Await `handler` inline, or run it as an operation when the caller asked for `async`
+async fn maybe_async<F>(state: &AppState, query: AsyncQuery, kind: &str, handler: F) -> Response
+where
+    F: Future<Output = Response> + Send + 'static,
+{
+    if !query.run_async {
+        return handler.await;
+    }
+
+    let store = state.operations.clone();
+    let id = store.start(kind).await;
+    // The operation stays part of the request that started it
+    let handler = with_correlation_id(current_correlation_id(), handler);
+    tokio::spawn(run_operation(
+        store,
+        id.clone(),
+        current_principal(),
+        handler,
+    ));
+
+    (
+        StatusCode::ACCEPTED,
+        Json(ApiResponse::success(OperationStarted {
+            status_url: format!("/api/operations/{}", id),
+            events_url: format!("/api/operations/{}/events", id),
+            operation_id: id,
+        })),
+    )
+        .into_response()
+}
+
+/// Perceive the current page at the lightning, quick, standard or deep layer
+#[utoipa::path(
+    post,
+    path = "/api/perceive-mode",
+    tag = "perception",
+    params(AsyncQuery),
+    request_body = PerceptionModeRequest,
+    responses(
+        (status = 200, description = "Perception result", body = PerceptionEnvelope),
+        (status = 202, description = "Started as an operation with `?async=true`", body = ApiEnvelope),
+    )
+)]
+pub async fn perceive_with_mode(
+    State(state): State<AppState>,
+    Query(query): Query<AsyncQuery>,
+    headers: HeaderMap,
+    Json(req): Json<PerceptionModeRequest>,
+) -> Response {
+    let handler = perception_handlers::perceive_with_mode(State(state.clone()), headers, Json(req));
+    maybe_async(&state, query, "perceive", async move {
+        handler.await.into_response()
+    })
+    .await
+}
+
+/// Perceive, analyze, plan and execute a goal in one call
+#[utoipa::path(
+    post,
+    path = "/api/workflow/intelligent",
+    tag = "workflow",
+    params(AsyncQuery),
+    request_body = IntelligentWorkflowRequest,
+    responses(
+        (status = 200, description = "Workflow result", body = WorkflowEnvelope),
+        (status = 202, description = "Started as an operation with `?async=true`", body = ApiEnvelope),
+    )
+)]
+pub async fn execute_intelligent_workflow(
+    State(state): State<AppState>,
+    Query(query): Query<AsyncQuery>,
+    Json(req): Json<IntelligentWorkflowRequest>,
+) -> Response {
+    let handler = workflow_handlers::execute_intelligent_workflow(State(state.clone()), Json(req));
+    maybe_async(&state, query, "intelligent_workflow", async move {
+        handler.await.into_response()
+    })
+    .await
+}
+
+/// Plan the steps of a natural-language task
+#[utoipa::path(
+    post,
+    path = "/api/llm/plan",
+    tag = "llm",
+    params(AsyncQuery),
+    request_body = TaskPlanningRequest,
+    responses(
+        (status = 200, description = "Task plan", body = LlmEnvelope),
+        (status = 202, description = "Started as an operation with `?async=true`", body = ApiEnvelope),
+    )
+)]
+pub async fn task_planning(
+    State(state): State<AppState>,
+    Query(query): Query<AsyncQuery>,
+    Json(req): Json<TaskPlanningRequest>,
+) -> Response {
+    let handler = llm_handlers::task_planning(State(state.clone()), Json(req));
+    maybe_async(&state, query, "llm_plan", async move {
+        handler.await.into_response()
+    })
+    .await
+}
+
+/// Plan and execute a natural-language command
+#[utoipa::path(
+    post,
+    path = "/api/llm/execute",
+    tag = "llm",
+    params(AsyncQuery),
+    request_body = ExecuteCommandRequest,
+    responses(
+        (status = 200, description = "Execution result", body = LlmEnvelope),
+        (status = 202, description = "Started as an operation with `?async=true`", body = ApiEnvelope),
+    )
+)]
+pub async fn execute_command(
+    State(state): State<AppState>,
+    Query(query): Query<AsyncQuery>,
+    Json(req): Json<ExecuteCommandRequest>,
+) -> Response {
+    let handler = llm_handlers::execute_command(State(state.clone()), Json(req));
+    maybe_async(&state, query, "llm_execute", async move {
+        handler.await.into_response()
+    })
+    .await
+}
+
+/// Status and, once finished, result of an operation started with `?async=true`
+#[utoipa::path(
+    get,
+    path = "/api/operations/{id}",
+    tag = "operations",
+    params(("id" = String, Path, description = "Operation id")),
+    responses((status = 200, description = "The operation", body = ApiEnvelope))
+)]
+pub async fn get_operation(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.operations.get(&id).await {
+        Some(operation) => Json(ApiResponse::success(operation)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("Operation not found: {}", id),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Stream an operation's events as SSE: those already emitted, then live ones
+/// until it completes or fails
+#[utoipa::path(
+    get,
+    path = "/api/operations/{id}/events",
+    tag = "operations",
+    params(("id" = String, Path, description = "Operation id")),
+    responses(
+        (status = 200, description = "Server-sent event stream", content_type = "text/event-stream", body = String),
+    )
+)]
+pub async fn operation_events(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    // Subscribe before reading the history so no event falls between the two
+    let mut live = state.operations.subscribe();
+    let Some(operation) = state.operations.get(&id).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("Operation not found: {}", id),
+            )),
+        )
+            .into_response();
+    };
+
+    let stream = async_stream::stream! {
+        let mut next_sequence = 0;
+        for event in operation.events {
+            next_sequence = event.sequence + 1;
+            let terminal = event.kind.is_terminal();
+            yield Ok::<_, std::convert::Infallible>(sse_event(&event));
+            if terminal {
+                return;
+            }
+        }
+        loop {
+            match live.recv().await {
+                Ok(event) if event.operation_id == id && event.sequence >= next_sequence => {
+                    next_sequence = event.sequence + 1;
+                    let terminal = event.kind.is_terminal();
+                    yield Ok(sse_event(&event));
+                    if terminal {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Operation {} event stream skipped {} events", id, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    };
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn sse_event(event: &OperationEvent) -> Event {
+    let kind = serde_json::to_value(event.kind)
+        .ok()
+        .and_then(|kind| kind.as_str().map(str::to_string))
+        .unwrap_or_default();
+    Event::default()
+        .event(kind)
+        .id(event.sequence.to_string())
+        .json_data(event)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn records_events_and_result() {
+        let store = OperationStore::new();
+        let mut live = store.subscribe();
+        let id = store.start("perceive").await;
+        store
+            .progress(&id, "perception", json!({"mode": "deep"}))
+            .await;
+        store
+            .finish(&id, StatusCode::OK, json!({"success": true}))
+            .await;
+
+        let operation = store.get(&id).await.unwrap();
+        assert_eq!(operation.status, OperationStatus::Completed);
+        assert_eq!(operation.status_code, Some(200));
+        assert_eq!(operation.result, Some(json!({"success": true})));
+        let kinds: Vec<_> = operation.events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                OperationEventKind::Started,
+                OperationEventKind::Progress,
+                OperationEventKind::Completed
+            ]
+        );
+        assert_eq!(operation.events[1].stage.as_deref(), Some("perception"));
+
+        let first = live.recv().await.unwrap();
+        assert_eq!(first.operation_id, id);
+        assert_eq!(first.sequence, 0);
+    }
+
+    #[tokio::test]
+    async fn failed_responses_fail_the_operation() {
+        let store = OperationStore::new();
+        let id = store.start("llm_execute").await;
+        store
+            .finish(&id, StatusCode::BAD_REQUEST, json!({"success": false}))
+            .await;
+        let operation = store.get(&id).await.unwrap();
+        assert_eq!(operation.status, OperationStatus::Failed);
+        assert_eq!(
+            operation.events.last().map(|e| e.kind),
+            Some(OperationEventKind::Failed)
+        );
+    }
+
+    #[tokio::test]
+    async fn progress_outside_an_operation_is_ignored() {
+        report_progress("planning", json!({})).await;
+        let store = Arc::new(OperationStore::new());
+        let id = store.start("llm_plan").await;
+        CURRENT_OPERATION
+            .scope((store.clone(), id.clone()), async {
+                report_progress("planning", json!({"steps": 2})).await;
+            })
+            .await;
+        let operation = store.get(&id).await.unwrap();
+        assert_eq!(operation.events.len(), 2);
+        assert_eq!(operation.events[1].detail, json!({"steps": 2}));
+    }
+}
@@ -0,0 +1,269 @@
+// Pagination
+// List endpoints take `limit` with `offset` or the `cursor` of the previous page,
+// keep the items matching every `filter=field:value`, and trim items to the
+// `fields` asked for. The envelope's `page` reports the total after filtering
+// and the cursor of the next page.
+
+use axum::{
+    extract::{FromRequestParts, Json, Query},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use utoipa::{IntoParams, ToSchema};
+
+use super::{ApiResponse, ErrorCode};
+
+/// Items per page when `limit` is left out
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Largest `limit` honoured
+pub const MAX_LIMIT: usize = 1000;
+
+const CURSOR_PREFIX: &str = "offset:";
+
+/// Page, filter and field selection of a list request
+#[derive(Debug, Clone, Default, PartialEq, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Items per page, at most 1000 (default 100)
+    pub limit: Option<usize>,
+    /// Items to skip
+    pub offset: Option<usize>,
+    /// `next_cursor` of the previous page, in place of `offset`
+    pub cursor: Option<String>,
+    /// Comma-separated fields to keep in each item, e.g. `id,current_url`
+    pub fields: Option<String>,
+    /// `field:value`, with dots for nested fields; repeat to require several
+    pub filter: Vec<String>,
+}
+
+/// Position of a page in the whole list
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct PageInfo {
+    /// Items matching the filters, across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Pass as `cursor` for the next page; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+fn encode_cursor(offset: usize) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("{CURSOR_PREFIX}{offset}"))
+}
+
+fn decode_cursor(cursor: &str) -> Option<usize> {
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()?;
+    String::from_utf8(bytes)
+        .ok()?
+        .strip_prefix(CURSOR_PREFIX)?
+        .parse()
+        .ok()
+}
+
+impl PageQuery {
+    fn from_pairs(pairs: Vec<(String, String)>) -> Result<Self, String> {
+        let count = |name: &str, value: &str| {
+            value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("{} must be a non-negative integer", name))
+        };
+        let mut query = Self::default();
+        for (name, value) in pairs {
+            match name.as_str() {
+                "limit" => query.limit = Some(count("limit", &value)?),
+                "offset" => query.offset = Some(count("offset", &value)?),
+                "cursor" => query.cursor = Some(value),
+                "fields" => query.fields = Some(value),
+                "filter" => {
+                    if !value.contains(':') {
+                        return Err(format!("filter must be field:value, got {}", value));
+                    }
+                    query.filter.push(value);
+                }
+                _ => {}
+            }
+        }
+        if let Some(cursor) = &query.cursor {
+            query.offset = Some(decode_cursor(cursor).ok_or("Invalid cursor")?);
+        }
+        Ok(query)
+    }
+
+    fn matches(&self, item: &Value) -> bool {
+        self.filter.iter().all(|filter| {
+            let (path, expected) = filter.split_once(':').unwrap_or((filter, ""));
+            path.split('.')
+                .try_fold(item, |value, key| value.get(key))
+                .is_some_and(|value| equals(value, expected))
+        })
+    }
+
+    fn select(&self, item: Value) -> Value {
+        match (&self.fields, item) {
+            (Some(fields), Value::Object(mut object)) => Value::Object(
+                fields
+                    .split(',')
+                    .map(str::trim)
+                    .filter_map(|field| object.remove_entry(field))
+                    .collect::<Map<String, Value>>(),
+            ),
+            (_, item) => item,
+        }
+    }
+
+    /// The requested page of `items`, a list or a map paged in key order
+    pub fn apply(&self, items: Value) -> (Value, PageInfo) {
+        let offset = self.offset.unwrap_or(0);
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let (page, total) = match items {
+            Value::Array(items) => {
+                let items: Vec<Value> = items.into_iter().filter(|i| self.matches(i)).collect();
+                let total = items.len();
+                let page = items
+                    .into_iter()
+                    .skip(offset)
+                    .take(limit)
+                    .map(|item| self.select(item))
+                    .collect();
+                (Value::Array(page), total)
+            }
+            Value::Object(entries) => {
+                let mut entries: Vec<(String, Value)> = entries
+                    .into_iter()
+                    .filter(|(_, item)| self.matches(item))
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                let total = entries.len();
+                let page = entries
+                    .into_iter()
+                    .skip(offset)
+                    .take(limit)
+                    .map(|(key, item)| (key, self.select(item)))
+                    .collect();
+                (Value::Object(page), total)
+            }
+            other => return (other, self.info(1, offset, limit)),
+        };
+        (page, self.info(total, offset, limit))
+    }
+
+    fn info(&self, total: usize, offset: usize, limit: usize) -> PageInfo {
+        PageInfo {
+            total,
+            offset,
+            limit,
+            next_cursor: (offset.saturating_add(limit) < total)
+                .then(|| encode_cursor(offset + limit)),
+        }
+    }
+
+    /// `items` paged into the success envelope
+    pub fn respond<T: Serialize>(&self, items: T) -> Response {
+        let (data, page) = self.apply(serde_json::to_value(items).unwrap_or_default());
+        Json(ApiResponse::success(data).with_page(page)).into_response()
+    }
+}
+
+/// Whether a field holds `expected`: the string itself, a number or boolean
+/// written the same way, or a list containing it
+fn equals(value: &Value, expected: &str) -> bool {
+    match value {
+        Value::String(value) => value == expected,
+        Value::Array(items) => items.iter().any(|item| equals(item, expected)),
+        other => serde_json::from_str::<Value>(expected).is_ok_and(|expected| expected == *other),
+    }
+}
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for PageQuery {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Response> {
+        let Query(pairs) = Query::<Vec<(String, String)>>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        Self::from_pairs(pairs).map_err(|message| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, message)),
+            )
+                .into_response()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn query(pairs: &[(&str, &str)]) -> Result<PageQuery, String> {
+        PageQuery::from_pairs(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_pages_filters_and_fields() {
+        let items = json!([
+            {"id": "a", "status": "failed", "tags": {"owner": "ci"}},
+            {"id": "b", "status": "completed", "tags": {"owner": "ci"}},
+            {"id": "c", "status": "failed", "tags": {"owner": "ops"}},
+            {"id": "d", "status": "failed", "tags": {"owner": "ci"}},
+        ]);
+        let first = query(&[
+            ("filter", "status:failed"),
+            ("limit", "1"),
+            ("fields", "id"),
+        ])
+        .unwrap();
+        let (page, info) = first.apply(items.clone());
+        assert_eq!(page, json!([{"id": "a"}]));
+        assert_eq!(info.total, 3);
+
+        let cursor = info.next_cursor.unwrap();
+        let next = query(&[
+            ("filter", "status:failed"),
+            ("filter", "tags.owner:ci"),
+            ("limit", "1"),
+            ("cursor", &cursor),
+        ])
+        .unwrap();
+        let (page, info) = next.apply(items);
+        assert_eq!(page[0]["id"], "d");
+        assert_eq!((info.total, info.offset), (2, 1));
+        assert_eq!(info.next_cursor, None);
+
+        let (page, info) = query(&[("limit", "1")])
+            .unwrap()
+            .apply(json!({"b": {"calls": 2}, "a": {"calls": 1}}));
+        assert_eq!(page, json!({"a": {"calls": 1}}));
+        assert_eq!(info.total, 2);
+    }
+
+    #[test]
+    fn test_invalid_queries_rejected() {
+        assert!(query(&[("limit", "-1")]).is_err());
+        assert!(query(&[("cursor", "not-a-cursor")]).is_err());
+        assert!(query(&[("filter", "status")]).is_err());
+        assert_eq!(
+            query(&[("limit", "5000")])
+                .unwrap()
+                .apply(json!([]))
+                .1
+                .limit,
+            MAX_LIMIT
+        );
+    }
+}
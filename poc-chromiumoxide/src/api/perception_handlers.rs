@@ -1,15 +1,19 @@
 // Perception API handlers
 
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use tracing::{debug, error, info, warn};
+use utoipa::{IntoParams, ToSchema};
 
-use super::{ApiResponse, AppState};
+use super::{client_token, ApiError, ApiResponse, AppState, ErrorCode};
+use crate::browser::{ActivityKind, RecordedAction};
+use crate::perception::fill_profiles::FillProfileStore;
+use crate::perception::schema::{self, SchemaKind};
 use crate::perception::PerceptionMode;
 
 /// Enhanced error type for perception operations
@@ -39,15 +43,16 @@ pub enum PerceptionError {
 }
 
 /// Enhanced response wrapper with performance metrics
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(PerceptionEnvelope = PerceptionResponse<serde_json::Value>)]
 pub struct PerceptionResponse<T> {
     pub success: bool,
     pub data: Option<T>,
-    pub error: Option<String>,
+    pub error: Option<ApiError>,
     pub metrics: PerformanceMetrics,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PerformanceMetrics {
     pub processing_time_ms: u64,
     pub browser_acquisition_time_ms: u64,
@@ -65,16 +70,24 @@ impl<T> PerceptionResponse<T> {
         }
     }
 
-    pub fn error(error: String, metrics: PerformanceMetrics) -> Self {
+    pub fn error(code: ErrorCode, message: impl Into<String>, metrics: PerformanceMetrics) -> Self {
         Self {
             success: false,
             data: None,
-            error: Some(error),
+            error: Some(ApiError::new(code, message)),
             metrics,
         }
     }
 }
 
+/// Classify the current page and list its key elements
+#[utoipa::path(
+    post,
+    path = "/api/perception/analyze",
+    tag = "perception",
+    request_body = AnalyzePageRequest,
+    responses((status = 200, description = "Page analysis", body = PerceptionEnvelope))
+)]
 pub async fn analyze_page(
     State(state): State<AppState>,
     Json(req): Json<AnalyzePageRequest>,
@@ -94,6 +107,7 @@ pub async fn analyze_page(
             total_time_ms: start_time.elapsed().as_millis() as u64,
         };
         return Json(PerceptionResponse::<()>::error(
+            ErrorCode::InvalidRequest,
             validation_error.to_string(),
             metrics,
         ))
@@ -117,6 +131,7 @@ pub async fn analyze_page(
             return (
                 StatusCode::BAD_REQUEST,
                 Json(PerceptionResponse::<()>::error(
+                    ErrorCode::SessionNotFound,
                     format!("Session not found: {}", session_id),
                     metrics,
                 )),
@@ -144,6 +159,7 @@ pub async fn analyze_page(
                     return (
                         StatusCode::INTERNAL_SERVER_ERROR,
                         Json(PerceptionResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::BrowserError),
                             format!("Navigation failed: {}", e),
                             metrics,
                         )),
@@ -169,7 +185,24 @@ pub async fn analyze_page(
                                 "Enhanced page analysis completed successfully in {}ms",
                                 perception_time
                             );
-                            Json(PerceptionResponse::success(analysis, metrics)).into_response()
+                            match schema::render(
+                                SchemaKind::EnhancedPageAnalysis,
+                                &analysis,
+                                req.schema_version,
+                            ) {
+                                Ok(data) => {
+                                    Json(PerceptionResponse::success(data, metrics)).into_response()
+                                }
+                                Err(e) => (
+                                    StatusCode::INTERNAL_SERVER_ERROR,
+                                    Json(PerceptionResponse::<()>::error(
+                                        ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                                        e.to_string(),
+                                        metrics,
+                                    )),
+                                )
+                                    .into_response(),
+                            }
                         }
                         Err(e) => {
                             error!("Enhanced page analysis failed: {}", e);
@@ -203,6 +236,7 @@ pub async fn analyze_page(
                                     (
                                         StatusCode::INTERNAL_SERVER_ERROR,
                                         Json(PerceptionResponse::<()>::error(
+                                            ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
                                             format!(
                                                 "Both enhanced and legacy analysis failed: {}",
                                                 e
@@ -227,6 +261,7 @@ pub async fn analyze_page(
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
                         Json(PerceptionResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
                             format!("Failed to create perception engine: {}", e),
                             metrics,
                         )),
@@ -246,6 +281,7 @@ pub async fn analyze_page(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(PerceptionResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     format!("Failed to acquire browser: {}", e),
                     metrics,
                 )),
@@ -280,6 +316,11 @@ fn validate_analyze_page_request(req: &AnalyzePageRequest) -> Result<(), Percept
     // Validate session_id format if provided
     validate_session_id(&req.session_id)?;
 
+    if let Some(version) = req.schema_version {
+        schema::json_schema(SchemaKind::EnhancedPageAnalysis, version)
+            .map_err(|e| PerceptionError::ValidationError(e.to_string()))?;
+    }
+
     Ok(())
 }
 
@@ -316,7 +357,7 @@ fn validate_session_id(session_id: &Option<String>) -> Result<(), PerceptionErro
 /// Validate perception mode request
 fn validate_perception_mode_request(req: &PerceptionModeRequest) -> Result<(), PerceptionError> {
     // Validate mode
-    let valid_modes = ["lightning", "quick", "standard", "deep", "adaptive"];
+    let valid_modes = ["lightning", "quick", "standard", "deep", "adaptive", "accessibility"];
     if !valid_modes.contains(&req.mode.to_lowercase().as_str()) {
         return Err(PerceptionError::InvalidMode(format!(
             "Invalid mode '{}'. Valid modes: {}",
@@ -333,6 +374,14 @@ fn validate_perception_mode_request(req: &PerceptionModeRequest) -> Result<(), P
 
 // Unused validation functions removed for code consolidation
 
+/// Find an element from a natural-language description
+#[utoipa::path(
+    post,
+    path = "/api/perception/find",
+    tag = "perception",
+    request_body = FindElementRequest,
+    responses((status = 200, description = "Best matching element", body = ApiEnvelope))
+)]
 pub async fn intelligent_find_element(
     State(state): State<AppState>,
     Json(req): Json<FindElementRequest>,
@@ -341,12 +390,29 @@ pub async fn intelligent_find_element(
         Ok(browser) => {
             match crate::perception::PerceptionEngine::new(browser.browser_arc()).await {
                 Ok(mut perception) => match perception.find_element(&req.description).await {
-                    Ok(element) => Json(ApiResponse::success(element)).into_response(),
+                    Ok(element) => match schema::render(
+                        SchemaKind::PerceivedElement,
+                        &element,
+                        req.schema_version,
+                    ) {
+                        Ok(data) => Json(ApiResponse::success(data)).into_response(),
+                        Err(e) => (
+                            StatusCode::BAD_REQUEST,
+                            Json(ApiResponse::<()>::error(
+                                ErrorCode::InvalidRequest,
+                                e.to_string(),
+                            )),
+                        )
+                            .into_response(),
+                    },
                     Err(e) => {
                         error!("Element finding failed: {}", e);
                         (
                             StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(ApiResponse::<()>::error(e.to_string())),
+                            Json(ApiResponse::<()>::error(
+                                ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                                e.to_string(),
+                            )),
                         )
                             .into_response()
                     }
@@ -355,7 +421,10 @@ pub async fn intelligent_find_element(
                     error!("Failed to create perception engine: {}", e);
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(e.to_string())),
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                            e.to_string(),
+                        )),
                     )
                         .into_response()
                 }
@@ -365,13 +434,24 @@ pub async fn intelligent_find_element(
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// Execute a structured command against perceived elements
+#[utoipa::path(
+    post,
+    path = "/api/perception/command",
+    tag = "perception",
+    request_body = IntelligentCommandRequest,
+    responses((status = 200, description = "Command result", body = ApiEnvelope))
+)]
 pub async fn execute_intelligent_command(
     State(state): State<AppState>,
     Json(req): Json<IntelligentCommandRequest>,
@@ -391,7 +471,10 @@ pub async fn execute_intelligent_command(
                             error!("Intelligent command execution failed: {}", e);
                             (
                                 StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(ApiResponse::<()>::error(e.to_string())),
+                                Json(ApiResponse::<()>::error(
+                                    ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                                    e.to_string(),
+                                )),
                             )
                                 .into_response()
                         }
@@ -401,7 +484,10 @@ pub async fn execute_intelligent_command(
                     error!("Failed to create perception browser: {}", e);
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(e.to_string())),
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                            e.to_string(),
+                        )),
                     )
                         .into_response()
                 }
@@ -411,13 +497,24 @@ pub async fn execute_intelligent_command(
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// Fields, types and validation of a form on the page
+#[utoipa::path(
+    post,
+    path = "/api/perception/forms/analyze",
+    tag = "perception",
+    request_body = AnalyzeFormRequest,
+    responses((status = 200, description = "Form analysis", body = ApiEnvelope))
+)]
 pub async fn analyze_form(
     State(state): State<AppState>,
     Json(req): Json<AnalyzeFormRequest>,
@@ -447,10 +544,10 @@ pub async fn analyze_form(
         } else {
             return (
                 StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error(format!(
-                    "Session not found: {}",
-                    session_id
-                ))),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::SessionNotFound,
+                    format!("Session not found: {}", session_id),
+                )),
             )
                 .into_response();
         }
@@ -468,7 +565,10 @@ pub async fn analyze_form(
                     error!("Form analysis failed: {}", e);
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(e.to_string())),
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                            e.to_string(),
+                        )),
                     )
                         .into_response()
                 }
@@ -478,25 +578,76 @@ pub async fn analyze_form(
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// Fill a form from a stored or inline profile
+#[utoipa::path(
+    post,
+    path = "/api/perception/forms/fill",
+    tag = "perception",
+    request_body = AutoFillFormRequest,
+    responses((status = 200, description = "Filled fields", body = ApiEnvelope))
+)]
 pub async fn auto_fill_form(
     State(state): State<AppState>,
     Json(req): Json<AutoFillFormRequest>,
 ) -> impl IntoResponse {
+    // Inline profiles win; otherwise the profile is looked up by name in the store
+    let store = FillProfileStore::new();
+    let profile = match req.user_profile {
+        Some(profile) => {
+            if req.save_profile {
+                if let Err(e) = store.save(&profile) {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::InvalidRequest,
+                            e.to_string(),
+                        )),
+                    )
+                        .into_response();
+                }
+            }
+            profile
+        }
+        None => match store.get(&req.profile_name) {
+            Ok(Some(profile)) => profile,
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::NotFound,
+                        format!("Fill profile not found: {}", req.profile_name),
+                    )),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::InvalidRequest,
+                        e.to_string(),
+                    )),
+                )
+                    .into_response();
+            }
+        },
+    };
+    let profile_name = profile.name.clone();
+
     match state.browser_pool.acquire().await {
         Ok(browser) => {
             let mut form_handler = crate::perception::smart_forms::SmartFormHandler::new();
-
-            // Add user profile if provided
-            if let Some(profile) = req.user_profile {
-                form_handler.add_user_profile(profile);
-            }
+            form_handler.add_user_profile(profile);
 
             // First analyze the form
             match form_handler
@@ -505,7 +656,7 @@ pub async fn auto_fill_form(
             {
                 Ok(form_analysis) => {
                     match form_handler
-                        .auto_fill_form(browser.browser(), &form_analysis, &req.profile_name)
+                        .auto_fill_form(browser.browser(), &form_analysis, &profile_name)
                         .await
                     {
                         Ok(fill_result) => Json(ApiResponse::success(fill_result)).into_response(),
@@ -513,7 +664,10 @@ pub async fn auto_fill_form(
                             error!("Form auto-fill failed: {}", e);
                             (
                                 StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(ApiResponse::<()>::error(e.to_string())),
+                                Json(ApiResponse::<()>::error(
+                                    ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                                    e.to_string(),
+                                )),
                             )
                                 .into_response()
                         }
@@ -523,7 +677,10 @@ pub async fn auto_fill_form(
                     error!("Form analysis failed: {}", e);
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(e.to_string())),
+                        Json(ApiResponse::<()>::error(
+                            ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                            e.to_string(),
+                        )),
                     )
                         .into_response()
                 }
@@ -533,29 +690,178 @@ pub async fn auto_fill_form(
             error!("Failed to acquire browser: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
     }
 }
 
+/// Stored fill profiles, without their personal data
+#[utoipa::path(
+    get,
+    path = "/api/perception/forms/profiles",
+    tag = "perception",
+    responses((status = 200, description = "Profile names and fields", body = ApiEnvelope))
+)]
+pub async fn list_fill_profiles() -> impl IntoResponse {
+    match FillProfileStore::new().list() {
+        Ok(profiles) => Json(ApiResponse::success(profiles)).into_response(),
+        Err(e) => {
+            error!("Listing fill profiles failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                    e.to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Store a fill profile under its name for later `/api/perception/forms/fill` calls
+#[utoipa::path(
+    post,
+    path = "/api/perception/forms/profiles",
+    tag = "perception",
+    request_body(content = Object, description = "Named fill profile"),
+    responses((status = 200, description = "Profile was stored", body = ApiEnvelope))
+)]
+pub async fn save_fill_profile(
+    Json(profile): Json<crate::perception::smart_forms::UserProfile>,
+) -> impl IntoResponse {
+    match FillProfileStore::new().save(&profile) {
+        Ok(summary) => Json(ApiResponse::success(summary)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Delete a stored fill profile
+#[utoipa::path(
+    delete,
+    path = "/api/perception/forms/profiles/{name}",
+    tag = "perception",
+    params(("name" = String, Path, description = "Fill profile name")),
+    responses((status = 200, description = "Profile was deleted", body = ApiEnvelope))
+)]
+pub async fn delete_fill_profile(Path(name): Path<String>) -> impl IntoResponse {
+    match FillProfileStore::new().delete(&name) {
+        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+            "deleted": true,
+            "name": name
+        })))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Published perception schemas and the versions the API can answer in
+#[utoipa::path(
+    get,
+    path = "/api/perception/schemas",
+    tag = "perception",
+    responses((status = 200, description = "Schemas and versions", body = ApiEnvelope))
+)]
+pub async fn list_perception_schemas() -> impl IntoResponse {
+    let kinds: Vec<_> = SchemaKind::ALL
+        .iter()
+        .map(|kind| {
+            serde_json::json!({
+                "kind": kind.name(),
+                "current_version": schema::SCHEMA_VERSION,
+                "supported_versions": (schema::OLDEST_SCHEMA_VERSION..=schema::SCHEMA_VERSION)
+                    .collect::<Vec<_>>(),
+                "schema_url": format!("/api/perception/schemas/{}", kind.name()),
+            })
+        })
+        .collect();
+    Json(ApiResponse::success(kinds))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SchemaQuery {
+    pub version: Option<u32>,
+}
+
+/// JSON Schema of one perception payload, current version unless `?version=` asks otherwise
+#[utoipa::path(
+    get,
+    path = "/api/perception/schemas/{kind}",
+    tag = "perception",
+    params(
+        ("kind" = String, Path, description = "Perception payload kind, e.g. `quick`"),
+        SchemaQuery,
+    ),
+    responses((status = 200, description = "JSON Schema", body = Object))
+)]
+pub async fn get_perception_schema(
+    Path(kind): Path<String>,
+    Query(query): Query<SchemaQuery>,
+) -> impl IntoResponse {
+    let Some(kind) = SchemaKind::from_name(&kind) else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("Unknown perception schema: {}", kind),
+            )),
+        )
+            .into_response();
+    };
+    match schema::json_schema(kind, query.version.unwrap_or(schema::SCHEMA_VERSION)) {
+        Ok(schema) => Json(schema).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+    }
+}
+
 // Request/Response types for perception API
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AnalyzePageRequest {
     pub url: Option<String>,
     pub session_id: Option<String>, // NEW: Use specific session
+    /// Perception schema version to answer in; the current one by default
+    #[serde(default)]
+    pub schema_version: Option<u32>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct PerceptionModeRequest {
-    pub mode: String, // "lightning", "quick", "standard", "deep", "adaptive"
+    pub mode: String, // "lightning", "quick", "standard", "deep", "adaptive", "accessibility"
     pub session_id: Option<String>, // NEW: Use specific session
     #[serde(default)]
     pub url: Option<String>, // Optional: navigate before perception
+    #[serde(default)]
+    pub load_lazy_content: bool, // Scroll until stable before deep perception
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct SmartElementSearchRequest {
     pub query: String,
     pub max_results: Option<usize>,
@@ -563,36 +869,45 @@ pub struct SmartElementSearchRequest {
     pub session_id: Option<String>, // NEW: Use specific session
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct FindElementRequest {
     pub description: String,
     #[allow(dead_code)] // Reserved for session-aware element finding
     pub session_id: Option<String>, // NEW: Use specific session
+    /// Perception schema version to answer in; the current one by default
+    #[serde(default)]
+    pub schema_version: Option<u32>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct IntelligentCommandRequest {
+    #[schema(value_type = Object)]
     pub command: crate::perception::integration::IntelligentCommand,
     #[allow(dead_code)] // Reserved for session-aware commands
     pub session_id: Option<String>, // NEW: Use specific session
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AnalyzeFormRequest {
     pub form_selector: Option<String>,
     pub session_id: Option<String>, // NEW: Use specific session
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AutoFillFormRequest {
     pub form_selector: Option<String>,
+    /// Stored fill profile to use when `user_profile` is not given
     pub profile_name: String,
+    #[schema(value_type = Option<Object>)]
     pub user_profile: Option<crate::perception::smart_forms::UserProfile>,
+    /// Store `user_profile` so later requests can refer to it by name
+    #[serde(default)]
+    pub save_profile: bool,
     #[allow(dead_code)] // Reserved for session-aware form filling
     pub session_id: Option<String>, // NEW: Use specific session
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct NavigatePerceiveRequest {
     pub url: String,
     pub session_id: Option<String>,
@@ -601,8 +916,18 @@ pub struct NavigatePerceiveRequest {
 }
 
 /// Combined operation: navigate to URL then perceive using the SAME browser/context
+#[utoipa::path(
+    post,
+    path = "/api/navigate-perceive",
+    tag = "perception",
+    request_body = NavigatePerceiveRequest,
+    responses(
+        (status = 200, description = "Navigation and perception result", body = ApiEnvelope),
+    )
+)]
 pub async fn navigate_and_perceive(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<NavigatePerceiveRequest>,
 ) -> impl IntoResponse {
     // Validate URL
@@ -613,7 +938,10 @@ pub async fn navigate_and_perceive(
     {
         return (
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error("Invalid URL".to_string())),
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                "Invalid URL".to_string(),
+            )),
         )
             .into_response();
     }
@@ -631,10 +959,11 @@ pub async fn navigate_and_perceive(
         "standard" => PerceptionMode::Standard,
         "deep" => PerceptionMode::Deep,
         "adaptive" => PerceptionMode::Adaptive,
+        "accessibility" => PerceptionMode::Accessibility,
         _ => PerceptionMode::Lightning,
     };
 
-    // Acquire browser with priority: session -> active registry -> pool
+    // The session's browser, or the one bound to the caller's client token
     let (browser_arc, source) = if let Some(ref sid) = req.session_id {
         if let Some(sess) = state.session_manager.get_session(sid).await {
             let lock = sess.read().await;
@@ -643,25 +972,23 @@ pub async fn navigate_and_perceive(
             // Session not found; return explicit error so caller can recover
             return (
                 StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error(format!(
-                    "Session not found: {}",
-                    sid
-                ))),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::SessionNotFound,
+                    format!("Session not found: {}", sid),
+                )),
             )
                 .into_response();
         }
-    } else if let Some(active) = state.tool_registry.active_browser().await {
-        (active, "tool_registry_active")
     } else {
-        match state.browser_pool.acquire().await {
-            Ok(guard) => (guard.browser_arc(), "pool"),
+        match state.affinity.browser(&client_token(&headers)).await {
+            Ok(browser) => (browser, "client"),
             Err(e) => {
                 return (
                     StatusCode::SERVICE_UNAVAILABLE,
-                    Json(ApiResponse::<()>::error(format!(
-                        "Browser unavailable: {}",
-                        e
-                    ))),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::BrowserUnavailable,
+                        format!("Browser unavailable: {}", e),
+                    )),
                 )
                     .into_response();
             }
@@ -672,18 +999,22 @@ pub async fn navigate_and_perceive(
     if let Err(e) = browser_arc.navigate_to(&req.url).await {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!(
-                "Navigation failed: {}",
-                e
-            ))),
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::BrowserError),
+                format!("Navigation failed: {}", e),
+            )),
         )
             .into_response();
     }
 
-    // Update session state and promote active browser if session-bound
+    // Update session state if session-bound
     if let Some(ref sid) = req.session_id {
+        state.session_manager.record_activity(
+            sid,
+            ActivityKind::Navigation,
+            serde_json::json!({ "url": req.url }),
+        );
         if let Some(sess) = state.session_manager.get_session(sid).await {
-            let tr = state.tool_registry.clone();
             let url_now = req.url.clone();
             tokio::spawn(async move {
                 {
@@ -691,9 +1022,9 @@ pub async fn navigate_and_perceive(
                     if let Some(prev) = s.current_url.clone() {
                         s.history.push(prev);
                     }
+                    s.record(RecordedAction::navigate(&url_now));
                     s.current_url = Some(url_now);
                     s.touch();
-                    tr.set_active_browser(s.browser.clone()).await;
                 }
             });
         }
@@ -709,11 +1040,23 @@ pub async fn navigate_and_perceive(
                 crate::perception::PerceptionResult::Quick(q) => serde_json::to_value(q).unwrap_or_default(),
                 crate::perception::PerceptionResult::Standard(s) => serde_json::to_value(s).unwrap_or_default(),
                 crate::perception::PerceptionResult::Deep(d) => serde_json::to_value(d).unwrap_or_default(),
+                crate::perception::PerceptionResult::Accessibility(a) => serde_json::to_value(a).unwrap_or_default(),
             };
+            if let Some(ref sid) = req.session_id {
+                state.session_manager.record_activity(
+                    sid,
+                    ActivityKind::Perception,
+                    serde_json::json!({
+                        "mode": req.mode.as_deref().unwrap_or("lightning"),
+                        "url": req.url
+                    }),
+                );
+            }
             let mut resp = serde_json::json!({
                 "navigate": {"status": "navigated", "url": req.url},
                 "perception": perception_json,
                 "source": source,
+                "schema_version": schema::SCHEMA_VERSION,
             });
             if let Some(sid) = req.session_id {
                 if let Some(obj) = resp.as_object_mut() {
@@ -724,18 +1067,89 @@ pub async fn navigate_and_perceive(
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!(
-                "Perception failed: {}",
-                e
-            ))),
+            Json(ApiResponse::<()>::error(
+                ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                format!("Perception failed: {}", e),
+            )),
         )
             .into_response(),
     }
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct BatchPerceiveRequest {
+    pub urls: Vec<String>,
+    /// "quick" (default) or "standard"
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub mode: crate::perception::batch::BatchMode,
+    /// Pages perceived at once, 1-10 (default 3)
+    pub concurrency: Option<usize>,
+    /// Budget per page in milliseconds (default 30000)
+    pub page_timeout_ms: Option<u64>,
+    /// Include each page's full perception result
+    #[serde(default)]
+    pub include_perception: bool,
+}
+
+/// Perceive a list of URLs in parallel, each in its own pooled browser, and return
+/// one consolidated report
+#[utoipa::path(
+    post,
+    path = "/api/perception/batch",
+    tag = "perception",
+    request_body = BatchPerceiveRequest,
+    responses((status = 200, description = "Consolidated report", body = ApiEnvelope))
+)]
+pub async fn batch_perceive(
+    State(state): State<AppState>,
+    Json(req): Json<BatchPerceiveRequest>,
+) -> impl IntoResponse {
+    use crate::perception::batch::{self, BatchOptions, MAX_BATCH_URLS};
+
+    let invalid = if req.urls.is_empty() {
+        Some("At least one URL is required".to_string())
+    } else if req.urls.len() > MAX_BATCH_URLS {
+        Some(format!("At most {} URLs can be perceived in one batch", MAX_BATCH_URLS))
+    } else {
+        req.urls
+            .iter()
+            .find(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+            .map(|url| format!("Invalid URL: {}", url))
+    };
+    if let Some(message) = invalid {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                PerceptionError::ValidationError(message).to_string(),
+            )),
+        )
+            .into_response();
+    }
+
+    let defaults = BatchOptions::default();
+    let options = BatchOptions {
+        mode: req.mode,
+        concurrency: req.concurrency.unwrap_or(defaults.concurrency).clamp(1, 10),
+        page_timeout: req
+            .page_timeout_ms
+            .map(std::time::Duration::from_millis)
+            .unwrap_or(defaults.page_timeout),
+        include_perception: req.include_perception,
+    };
+    let report = batch::perceive_urls(&state.browser_pool, &req.urls, &options).await;
+    info!(
+        "Batch perception finished: {}/{} pages in {}ms",
+        report.succeeded, report.total, report.duration_ms
+    );
+    Json(ApiResponse::success(report)).into_response()
+}
+
 /// Layered perception with specific mode
 pub async fn perceive_with_mode(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<PerceptionModeRequest>,
 ) -> impl IntoResponse {
     let start_time = Instant::now();
@@ -755,6 +1169,7 @@ pub async fn perceive_with_mode(
         return (
             StatusCode::BAD_REQUEST,
             Json(PerceptionResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metrics,
             )),
@@ -768,6 +1183,7 @@ pub async fn perceive_with_mode(
         "standard" => PerceptionMode::Standard,
         "deep" => PerceptionMode::Deep,
         "adaptive" => PerceptionMode::Adaptive,
+        "accessibility" => PerceptionMode::Accessibility,
         _ => unreachable!(), // Should be caught by validation
     };
 
@@ -791,6 +1207,7 @@ pub async fn perceive_with_mode(
             return (
                 StatusCode::BAD_REQUEST,
                 Json(PerceptionResponse::<()>::error(
+                    ErrorCode::SessionNotFound,
                     format!("Session not found: {}", session_id),
                     metrics,
                 )),
@@ -798,14 +1215,13 @@ pub async fn perceive_with_mode(
                 .into_response();
         }
     } else {
-        // No session_id provided
-        // Strategy:
-        // 1) If req.url provided -> acquire a fresh browser from pool and navigate to url (consistent starting point)
-        // 2) Else if active tool-registry browser exists -> use it
-        // 3) Else return 400 asking caller to provide url or navigate first
-        if let Some(_target) = &req.url {
-            match state.browser_pool.acquire().await {
-                Ok(guard) => (guard.browser_arc(), "pool_nav"),
+        // No session_id provided: use the browser bound to the caller's client token.
+        // With a url one is bound on first use; without one the caller must have
+        // navigated first, or there is nothing to perceive.
+        let client = client_token(&headers);
+        if req.url.is_some() {
+            match state.affinity.browser(&client).await {
+                Ok(browser) => (browser, "client"),
                 Err(e) => {
                     error!("Failed to acquire browser: {}", e);
                     let metrics = PerformanceMetrics {
@@ -818,6 +1234,7 @@ pub async fn perceive_with_mode(
                     return (
                         StatusCode::SERVICE_UNAVAILABLE,
                         Json(PerceptionResponse::<()>::error(
+                            ErrorCode::BrowserUnavailable,
                             format!("Browser unavailable: {}", e),
                             metrics,
                         )),
@@ -825,27 +1242,9 @@ pub async fn perceive_with_mode(
                         .into_response();
                 }
             }
-        } else if state.tool_registry.initialized().await {
-            if let Some(active) = state.tool_registry.active_browser().await {
-                info!("Using active tool-registry browser for perception (no session, no url)");
-                (active, "tool_registry_active")
-            } else {
-                let metrics = PerformanceMetrics {
-                    processing_time_ms: 0,
-                    browser_acquisition_time_ms: browser_acquisition_start.elapsed().as_millis()
-                        as u64,
-                    perception_time_ms: 0,
-                    total_time_ms: start_time.elapsed().as_millis() as u64,
-                };
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(PerceptionResponse::<()>::error(
-                        "No active browser. Provide 'url' or navigate first.".to_string(),
-                        metrics,
-                    )),
-                )
-                    .into_response();
-            }
+        } else if let Some(browser) = state.affinity.get(&client).await {
+            info!("Using the client's browser for perception (no session, no url)");
+            (browser, "client")
         } else {
             let metrics = PerformanceMetrics {
                 processing_time_ms: 0,
@@ -856,6 +1255,7 @@ pub async fn perceive_with_mode(
             return (
                 StatusCode::BAD_REQUEST,
                 Json(PerceptionResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
                     "No browser context. Provide 'url' for perception or navigate first."
                         .to_string(),
                     metrics,
@@ -885,6 +1285,7 @@ pub async fn perceive_with_mode(
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(PerceptionResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::BrowserError),
                         format!("Navigation failed: {}", e),
                         metrics,
                     )),
@@ -892,29 +1293,27 @@ pub async fn perceive_with_mode(
                     .into_response();
             }
         }
-    } else {
-        // No explicit URL: try recent navigation hints (session-first, then global)
-        let hint = if let Some(ref sid) = req.session_id {
-            state.recent_nav.read().await.get(sid).cloned()
-        } else {
-            state.recent_nav.read().await.get("__global__").cloned()
-        };
-        if let Some(target_url) = hint {
-            // Only navigate if current page is blank
-            if let Ok(cur) = browser_arc.current_url().await {
-                if cur == "about:blank" && !target_url.is_empty() && target_url != "__pending__" {
-                    if let Err(e) = browser_arc.navigate_to(&target_url).await {
-                        warn!("Perception nav-by-hint failed: {}", e);
-                    }
-                }
-            }
-        }
     }
 
+    super::operations::report_progress(
+        "perception",
+        serde_json::json!({
+            "mode": req.mode,
+            "browser_source": browser_source,
+            "browser_acquisition_time_ms": browser_acquisition_time,
+        }),
+    )
+    .await;
     let perception_start = Instant::now();
 
     // Create layered perception engine with the browser (either from session or pool)
-    let mut layered_perception = crate::perception::LayeredPerception::new(browser_arc);
+    let mut layered_perception = crate::perception::LayeredPerception::with_config(
+        browser_arc,
+        crate::perception::PerceptionConfig {
+            load_lazy_content: req.load_lazy_content,
+            ..Default::default()
+        },
+    );
 
     match layered_perception.perceive(mode).await {
         Ok(result) => {
@@ -948,8 +1347,21 @@ pub async fn perceive_with_mode(
                     m.insert("deep".to_string(), serde_json::to_value(d).unwrap_or_default());
                     serde_json::Value::Object(m)
                 }
+                crate::perception::PerceptionResult::Accessibility(a) => {
+                    let mut m = serde_json::Map::new();
+                    m.insert("accessibility".to_string(), serde_json::to_value(a).unwrap_or_default());
+                    serde_json::Value::Object(m)
+                }
             };
+            if let Some(obj) = response_data.as_object_mut() {
+                obj.insert("schema_version".to_string(), schema::SCHEMA_VERSION.into());
+            }
             if let Some(session_id) = req.session_id {
+                state.session_manager.record_activity(
+                    &session_id,
+                    ActivityKind::Perception,
+                    serde_json::json!({ "mode": req.mode, "duration_ms": perception_time }),
+                );
                 if let Some(obj) = response_data.as_object_mut() {
                     obj.insert(
                         "session_id".to_string(),
@@ -979,6 +1391,7 @@ pub async fn perceive_with_mode(
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(PerceptionResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
                     format!("Perception failed: {}", e),
                     metrics,
                 )),
@@ -989,11 +1402,19 @@ pub async fn perceive_with_mode(
 }
 
 /// Lightning fast perception for quick decisions
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct QuickScanRequest {
     pub session_id: Option<String>,
 }
 
+/// Fast overview of the current page's interactive elements
+#[utoipa::path(
+    post,
+    path = "/api/quick-scan",
+    tag = "perception",
+    request_body = Option<QuickScanRequest>,
+    responses((status = 200, description = "Scan result", body = ApiEnvelope))
+)]
 pub async fn quick_scan(
     State(state): State<AppState>,
     maybe_req: Option<Json<QuickScanRequest>>,
@@ -1019,7 +1440,10 @@ pub async fn quick_scan(
                         error!("Failed to acquire browser for quick scan: {}", e);
                         return (
                             StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(ApiResponse::<()>::error(e.to_string())),
+                            Json(ApiResponse::<()>::error(
+                                ErrorCode::BrowserUnavailable,
+                                e.to_string(),
+                            )),
                         )
                             .into_response();
                     }
@@ -1033,7 +1457,10 @@ pub async fn quick_scan(
                 error!("Failed to acquire browser for quick scan: {}", e);
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::BrowserUnavailable,
+                        e.to_string(),
+                    )),
                 )
                     .into_response();
             }
@@ -1048,10 +1475,10 @@ pub async fn quick_scan(
                 error!("Quick scan failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(format!(
-                        "Quick scan failed: {}",
-                        e
-                    ))),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                        format!("Quick scan failed: {}", e),
+                    )),
                 )
                     .into_response()
             }
@@ -1060,7 +1487,10 @@ pub async fn quick_scan(
             error!("Failed to create perception engine for quick scan: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
@@ -1068,6 +1498,13 @@ pub async fn quick_scan(
 }
 
 /// Smart element search using multiple strategies
+#[utoipa::path(
+    post,
+    path = "/api/smart-element-search",
+    tag = "perception",
+    request_body = SmartElementSearchRequest,
+    responses((status = 200, description = "Matching elements", body = ApiEnvelope))
+)]
 pub async fn smart_element_search(
     State(state): State<AppState>,
     Json(req): Json<SmartElementSearchRequest>,
@@ -1090,7 +1527,10 @@ pub async fn smart_element_search(
                         error!("Failed to acquire browser for element search: {}", e);
                         return (
                             StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(ApiResponse::<()>::error(e.to_string())),
+                            Json(ApiResponse::<()>::error(
+                                ErrorCode::BrowserUnavailable,
+                                e.to_string(),
+                            )),
                         )
                             .into_response();
                     }
@@ -1104,7 +1544,10 @@ pub async fn smart_element_search(
                 error!("Failed to acquire browser for element search: {}", e);
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(e.to_string())),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::BrowserUnavailable,
+                        e.to_string(),
+                    )),
                 )
                     .into_response();
             }
@@ -1132,10 +1575,10 @@ pub async fn smart_element_search(
                 error!("Smart element search failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(format!(
-                        "Element search failed: {}",
-                        e
-                    ))),
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                        format!("Element search failed: {}", e),
+                    )),
                 )
                     .into_response()
             }
@@ -1147,7 +1590,10 @@ pub async fn smart_element_search(
             );
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(e.to_string())),
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
+                    e.to_string(),
+                )),
             )
                 .into_response()
         }
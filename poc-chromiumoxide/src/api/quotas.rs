@@ -0,0 +1,505 @@
+// Per-client rate limits and quotas
+// Clients are keyed by their authenticated principal; unauthenticated requests
+// share one bucket. Each client gets a sliding-window request rate, a cap on
+// concurrent sessions and a daily LLM spend budget. Requests over a limit are
+// answered 429 with a Retry-After header.
+
+use axum::{
+    extract::{Json, Path, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use super::auth::{current_principal, is_api_request};
+use super::{ApiResponse, AppState, ErrorCode};
+
+/// Client key of unauthenticated requests
+const ANONYMOUS: &str = "anonymous";
+
+/// Retry-After sent when the session quota is full, since sessions end at no set time
+const SESSION_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Endpoints that call an LLM and are refused once the daily budget is spent
+const LLM_PATHS: &[&str] = &[
+    "/api/llm/query",
+    "/api/llm/plan",
+    "/api/llm/execute",
+    "/api/workflow/intelligent",
+];
+
+/// Limits of one client; unset limits are unlimited
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct QuotaLimits {
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub max_sessions: Option<usize>,
+    #[serde(default)]
+    pub daily_llm_budget_usd: Option<f64>,
+}
+
+impl QuotaLimits {
+    /// `RAINBOW_RATE_LIMIT_PER_MINUTE`, `RAINBOW_MAX_SESSIONS_PER_CLIENT` and
+    /// `RAINBOW_DAILY_LLM_BUDGET_USD`
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.trim().parse().ok()
+        }
+        Self {
+            requests_per_minute: var("RAINBOW_RATE_LIMIT_PER_MINUTE"),
+            max_sessions: var("RAINBOW_MAX_SESSIONS_PER_CLIENT"),
+            daily_llm_budget_usd: var("RAINBOW_DAILY_LLM_BUDGET_USD"),
+        }
+    }
+}
+
+/// Sliding-window request counter per client
+pub struct RateLimiter {
+    requests: RwLock<HashMap<String, VecDeque<Instant>>>,
+    window: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            requests: RwLock::new(HashMap::new()),
+            window,
+        }
+    }
+
+    /// Count a request, or return how long until the client is under `limit` again
+    pub async fn check(&self, client: &str, limit: u32) -> Result<(), Duration> {
+        let mut requests = self.requests.write().await;
+        let now = Instant::now();
+        let history = requests.entry(client.to_string()).or_default();
+        while history
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= self.window)
+        {
+            history.pop_front();
+        }
+
+        if history.len() >= limit as usize {
+            let oldest = history.front().copied().unwrap_or(now);
+            return Err(self.window.saturating_sub(now.duration_since(oldest)));
+        }
+        history.push_back(now);
+        Ok(())
+    }
+
+    /// Forget clients without requests in the current window
+    pub async fn cleanup(&self) {
+        let now = Instant::now();
+        self.requests.write().await.retain(|_, history| {
+            history
+                .back()
+                .is_some_and(|&at| now.duration_since(at) < self.window)
+        });
+    }
+
+    async fn count(&self, client: &str) -> usize {
+        let now = Instant::now();
+        self.requests.read().await.get(client).map_or(0, |history| {
+            history
+                .iter()
+                .filter(|&&at| now.duration_since(at) < self.window)
+                .count()
+        })
+    }
+}
+
+/// A client's limits and how much of them is used
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaUsage {
+    pub client: String,
+    pub limits: QuotaLimits,
+    pub requests_last_minute: usize,
+    pub sessions: usize,
+    pub llm_spend_today_usd: f64,
+}
+
+pub struct Quotas {
+    defaults: QuotaLimits,
+    overrides: RwLock<HashMap<String, QuotaLimits>>,
+    limiter: RateLimiter,
+    /// Sessions created by each client; ended ones are dropped when counted
+    sessions: RwLock<HashMap<String, HashSet<String>>>,
+    /// LLM spend of each client on its last active UTC day
+    spend: RwLock<HashMap<String, (NaiveDate, f64)>>,
+}
+
+impl Quotas {
+    pub fn new(defaults: QuotaLimits) -> Self {
+        Self {
+            defaults,
+            overrides: RwLock::new(HashMap::new()),
+            limiter: RateLimiter::new(Duration::from_secs(60)),
+            sessions: RwLock::new(HashMap::new()),
+            spend: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let defaults = QuotaLimits::from_env();
+        if defaults != QuotaLimits::default() {
+            info!("Per-client quotas: {:?}", defaults);
+        }
+        Self::new(defaults)
+    }
+
+    /// Limits of `client`, its override replacing the defaults
+    pub async fn limits(&self, client: &str) -> QuotaLimits {
+        self.overrides
+            .read()
+            .await
+            .get(client)
+            .cloned()
+            .unwrap_or_else(|| self.defaults.clone())
+    }
+
+    pub async fn set_override(&self, client: &str, limits: QuotaLimits) {
+        self.overrides
+            .write()
+            .await
+            .insert(client.to_string(), limits);
+    }
+
+    pub async fn remove_override(&self, client: &str) -> bool {
+        self.overrides.write().await.remove(client).is_some()
+    }
+
+    pub async fn check_request(&self, client: &str) -> Result<(), Duration> {
+        match self.limits(client).await.requests_per_minute {
+            Some(limit) => self.limiter.check(client, limit).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Refuse a new session while the client holds as many as it may, counting
+    /// only sessions for which `is_live` holds
+    pub async fn check_sessions(
+        &self,
+        client: &str,
+        is_live: impl Fn(&str) -> bool,
+    ) -> Result<(), Duration> {
+        let Some(max) = self.limits(client).await.max_sessions else {
+            return Ok(());
+        };
+        let mut sessions = self.sessions.write().await;
+        let held = sessions.entry(client.to_string()).or_default();
+        held.retain(|id| is_live(id));
+        if held.len() >= max {
+            return Err(SESSION_RETRY_AFTER);
+        }
+        Ok(())
+    }
+
+    pub async fn track_session(&self, client: &str, session_id: &str) {
+        self.sessions
+            .write()
+            .await
+            .entry(client.to_string())
+            .or_default()
+            .insert(session_id.to_string());
+    }
+
+    /// Refuse LLM calls once today's spend reached the budget, until UTC midnight
+    pub async fn check_llm_budget(&self, client: &str) -> Result<(), Duration> {
+        let Some(budget) = self.limits(client).await.daily_llm_budget_usd else {
+            return Ok(());
+        };
+        if self.spend_today(client).await < budget {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let midnight = (now.date_naive() + chrono::Days::new(1))
+            .and_hms_opt(0, 0, 0)
+            .map(|midnight| midnight.and_utc())
+            .unwrap_or(now);
+        Err((midnight - now).to_std().unwrap_or_default())
+    }
+
+    /// Add to today's spend; true when this call used up the budget
+    pub async fn record_llm_spend(&self, client: &str, cost_usd: f64) -> bool {
+        let budget = self.limits(client).await.daily_llm_budget_usd;
+        let today = Utc::now().date_naive();
+        let mut spend = self.spend.write().await;
+        let entry = spend.entry(client.to_string()).or_insert((today, 0.0));
+        if entry.0 != today {
+            *entry = (today, 0.0);
+        }
+        let before = entry.1;
+        entry.1 += cost_usd;
+        budget.is_some_and(|budget| before < budget && entry.1 >= budget)
+    }
+
+    async fn spend_today(&self, client: &str) -> f64 {
+        let today = Utc::now().date_naive();
+        match self.spend.read().await.get(client) {
+            Some((day, spent)) if *day == today => *spent,
+            _ => 0.0,
+        }
+    }
+
+    /// Drop request counters of clients idle for a whole window
+    pub async fn cleanup(&self) {
+        self.limiter.cleanup().await;
+    }
+
+    pub async fn usage(&self, client: &str) -> QuotaUsage {
+        QuotaUsage {
+            client: client.to_string(),
+            limits: self.limits(client).await,
+            requests_last_minute: self.limiter.count(client).await,
+            sessions: self
+                .sessions
+                .read()
+                .await
+                .get(client)
+                .map_or(0, HashSet::len),
+            llm_spend_today_usd: self.spend_today(client).await,
+        }
+    }
+}
+
+/// Key quotas are tracked under for the request being served
+pub fn current_client() -> String {
+    current_principal()
+        .map(|principal| principal.subject)
+        .unwrap_or_else(|| ANONYMOUS.to_string())
+}
+
+pub(super) fn too_many_requests(message: String, retry_after: Duration) -> Response {
+    // Round up so clients never retry before the limit frees up
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(ApiResponse::<()>::error(ErrorCode::RateLimited, message)),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+    response
+}
+
+/// Apply the caller's request rate, and LLM budget on LLM endpoints
+pub(super) async fn enforce(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if !is_api_request(path) {
+        return next.run(request).await;
+    }
+    let client = current_client();
+
+    if let Err(retry_after) = state.quotas.check_request(&client).await {
+        warn!("Rate limit exceeded for {}", client);
+        return too_many_requests("Rate limit exceeded".to_string(), retry_after);
+    }
+    if LLM_PATHS.contains(&path) {
+        if let Err(retry_after) = state.quotas.check_llm_budget(&client).await {
+            warn!("Daily LLM budget spent for {}", client);
+            return too_many_requests("Daily LLM budget exceeded".to_string(), retry_after);
+        }
+    }
+    next.run(request).await
+}
+
+/// Refuse to create a session past the caller's concurrent-session quota
+pub(super) async fn check_session_quota(state: &AppState) -> Result<(), Response> {
+    let client = current_client();
+    let live: HashSet<String> = state
+        .session_manager
+        .list_sessions()
+        .await
+        .into_iter()
+        .map(|session| session.id)
+        .collect();
+    state
+        .quotas
+        .check_sessions(&client, |id| live.contains(id))
+        .await
+        .map_err(|retry_after| {
+            warn!("Session quota reached for {}", client);
+            too_many_requests("Concurrent session quota reached".to_string(), retry_after)
+        })
+}
+
+/// May the caller change quotas: admins, or anyone when neither tokens nor API
+/// keys are required
+fn may_manage(state: &AppState) -> bool {
+    admin_or_open(state.auth.is_some() || state.api_keys.is_enabled())
+}
+
+fn admin_or_open(auth_required: bool) -> bool {
+    !auth_required || current_principal().is_some_and(|p| p.is_admin())
+}
+
+fn forbidden() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ApiResponse::<()>::error(
+            ErrorCode::Forbidden,
+            "Managing quotas requires the admin scope".to_string(),
+        )),
+    )
+        .into_response()
+}
+
+/// The caller's limits and usage
+#[utoipa::path(
+    get,
+    path = "/api/quotas",
+    tag = "quotas",
+    responses((status = 200, description = "Limits and usage", body = ApiEnvelope))
+)]
+pub async fn get_quota(State(state): State<AppState>) -> Response {
+    let usage = state.quotas.usage(&current_client()).await;
+    Json(ApiResponse::success(usage)).into_response()
+}
+
+/// Override the limits of one client
+#[utoipa::path(
+    put,
+    path = "/api/quotas/{client}",
+    tag = "quotas",
+    params(
+        ("client" = String, Path, description = "Principal subject, or `anonymous`"),
+    ),
+    request_body = QuotaLimits,
+    responses((status = 200, description = "The client's new limits", body = ApiEnvelope))
+)]
+pub async fn set_quota(
+    State(state): State<AppState>,
+    Path(client): Path<String>,
+    Json(limits): Json<QuotaLimits>,
+) -> Response {
+    if !may_manage(&state) {
+        return forbidden();
+    }
+    state.quotas.set_override(&client, limits).await;
+    Json(ApiResponse::success(state.quotas.usage(&client).await)).into_response()
+}
+
+/// Return a client to the default limits
+#[utoipa::path(
+    delete,
+    path = "/api/quotas/{client}",
+    tag = "quotas",
+    params(
+        ("client" = String, Path, description = "Principal subject, or `anonymous`"),
+    ),
+    responses(
+        (status = 200, description = "The client's override was removed", body = ApiEnvelope),
+    )
+)]
+pub async fn remove_quota(State(state): State<AppState>, Path(client): Path<String>) -> Response {
+    if !may_manage(&state) {
+        return forbidden();
+    }
+    let removed = state.quotas.remove_override(&client).await;
+    Json(ApiResponse::success(serde_json::json!({
+        "client": client,
+        "removed": removed,
+    })))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::auth::{with_principal, Principal, ADMIN_SCOPE};
+    use super::super::tenants::ApiKeys;
+    use super::*;
+
+    #[tokio::test]
+    async fn limits_requests_within_the_window() {
+        let limiter = RateLimiter::new(Duration::from_secs(60));
+        assert!(limiter.check("a", 2).await.is_ok());
+        assert!(limiter.check("a", 2).await.is_ok());
+        let retry_after = limiter.check("a", 2).await.unwrap_err();
+        assert!(retry_after > Duration::from_secs(55) && retry_after <= Duration::from_secs(60));
+        assert!(limiter.check("b", 2).await.is_ok());
+        assert_eq!(limiter.count("a").await, 2);
+    }
+
+    #[tokio::test]
+    async fn overrides_replace_the_defaults() {
+        let quotas = Quotas::new(QuotaLimits {
+            requests_per_minute: Some(1),
+            ..QuotaLimits::default()
+        });
+        assert!(quotas.check_request("ci").await.is_ok());
+        assert!(quotas.check_request("ci").await.is_err());
+
+        quotas.set_override("ci", QuotaLimits::default()).await;
+        assert!(quotas.check_request("ci").await.is_ok());
+        assert!(quotas.remove_override("ci").await);
+        assert_eq!(quotas.limits("ci").await.requests_per_minute, Some(1));
+    }
+
+    #[tokio::test]
+    async fn counts_only_live_sessions() {
+        let quotas = Quotas::new(QuotaLimits {
+            max_sessions: Some(1),
+            ..QuotaLimits::default()
+        });
+        assert!(quotas.check_sessions("ci", |_| true).await.is_ok());
+        quotas.track_session("ci", "s1").await;
+        assert_eq!(
+            quotas.check_sessions("ci", |_| true).await,
+            Err(SESSION_RETRY_AFTER)
+        );
+        // s1 ended
+        assert!(quotas.check_sessions("ci", |_| false).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn refuses_llm_calls_past_the_daily_budget() {
+        let quotas = Quotas::new(QuotaLimits {
+            daily_llm_budget_usd: Some(1.0),
+            ..QuotaLimits::default()
+        });
+        assert!(!quotas.record_llm_spend("ci", 0.6).await);
+        assert!(quotas.check_llm_budget("ci").await.is_ok());
+        // Only the call that crosses the budget reports it
+        assert!(quotas.record_llm_spend("ci", 0.6).await);
+        assert!(!quotas.record_llm_spend("ci", 0.1).await);
+        let retry_after = quotas.check_llm_budget("ci").await.unwrap_err();
+        assert!(retry_after <= Duration::from_secs(24 * 3600));
+        assert!(quotas.check_llm_budget("other").await.is_ok());
+        assert!((quotas.usage("ci").await.llm_spend_today_usd - 1.3).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn only_admins_manage_quotas_when_keys_are_required() {
+        let keys = ApiKeys::parse("acme:k1");
+        assert!(!with_principal(keys.principal("k1"), async { admin_or_open(true) }).await);
+        assert_eq!(forbidden().status(), StatusCode::FORBIDDEN);
+
+        let admin = Principal {
+            subject: "ops".to_string(),
+            scopes: vec![ADMIN_SCOPE.to_string()],
+            ..Principal::default()
+        };
+        assert!(with_principal(Some(admin), async { admin_or_open(true) }).await);
+        assert!(admin_or_open(false));
+    }
+
+    #[test]
+    fn rejections_carry_retry_after() {
+        let response = too_many_requests("slow down".to_string(), Duration::from_millis(1500));
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
+}
@@ -0,0 +1,234 @@
+// HTTP security headers
+// Which origins may call the API from a browser, and the content security policy
+// of the dashboard, so the server can be reached from other machines without
+// letting any page a user visits drive its browsers.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::warn;
+
+use super::idempotency::{IDEMPOTENCY_KEY_HEADER, REPLAYED_HEADER};
+use super::tenants::API_KEY_HEADER;
+use crate::browser::affinity::CLIENT_TOKEN_HEADER;
+use crate::coordination::correlation::CORRELATION_ID_HEADER;
+
+/// Policy of the dashboard pages: its script and styles, Font Awesome from cdnjs,
+/// `data:` screenshots and the `/ws` live channel. Inline scripts are allowed for
+/// the buttons' `onclick` handlers.
+pub const DEFAULT_CSP: &str = "default-src 'self'; \
+    script-src 'self' 'unsafe-inline'; \
+    style-src 'self' 'unsafe-inline' https://cdnjs.cloudflare.com; \
+    font-src 'self' https://cdnjs.cloudflare.com; \
+    img-src 'self' data:; \
+    connect-src 'self' ws: wss:; \
+    frame-ancestors 'none'; \
+    base-uri 'self'; \
+    form-action 'self'";
+
+/// Cached preflights are good for this long
+const PREFLIGHT_MAX_AGE: Duration = Duration::from_secs(600);
+
+/// Origins whose pages may call the API
+#[derive(Debug, Clone, PartialEq)]
+pub enum AllowedOrigins {
+    /// `*`: every origin
+    Any,
+    /// Pages served from this machine, on any port; the default
+    Loopback,
+    /// Exactly these origins
+    List(Vec<HeaderValue>),
+}
+
+/// CORS and CSP settings
+#[derive(Debug, Clone)]
+pub struct HttpSecurity {
+    pub origins: AllowedOrigins,
+    pub methods: Vec<Method>,
+    pub headers: Vec<HeaderName>,
+    /// `Content-Security-Policy` of the dashboard
+    pub csp: HeaderValue,
+}
+
+impl Default for HttpSecurity {
+    fn default() -> Self {
+        Self {
+            origins: AllowedOrigins::Loopback,
+            methods: vec![
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ],
+            headers: vec![
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                HeaderName::from_static(API_KEY_HEADER),
+                HeaderName::from_static(CLIENT_TOKEN_HEADER),
+                HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+                HeaderName::from_static(CORRELATION_ID_HEADER),
+            ],
+            csp: HeaderValue::from_static(DEFAULT_CSP),
+        }
+    }
+}
+
+impl HttpSecurity {
+    /// `RAINBOW_CORS_ORIGINS` (comma-separated origins, `*` for any; pages on
+    /// localhost by default), `RAINBOW_CORS_METHODS`, `RAINBOW_CORS_HEADERS` and
+    /// `RAINBOW_CSP`. Entries that don't parse are skipped with a warning.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let mut config = Self::default();
+        if let Some(origins) = var("RAINBOW_CORS_ORIGINS") {
+            config.origins = parse_origins(&origins);
+        }
+        if let Some(methods) = var("RAINBOW_CORS_METHODS") {
+            config.methods = parse_list("RAINBOW_CORS_METHODS", &methods, |method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes()).ok()
+            });
+        }
+        if let Some(headers) = var("RAINBOW_CORS_HEADERS") {
+            config.headers = parse_list("RAINBOW_CORS_HEADERS", &headers, |name| {
+                HeaderName::from_bytes(name.as_bytes()).ok()
+            });
+        }
+        if let Some(csp) = var("RAINBOW_CSP") {
+            match HeaderValue::from_str(csp.trim()) {
+                Ok(csp) => config.csp = csp,
+                Err(_) => warn!("Ignoring RAINBOW_CSP: not a valid header value"),
+            }
+        }
+        config
+    }
+
+    /// CORS for the API, answering preflights of the allowed origins
+    pub fn cors(&self) -> CorsLayer {
+        let origins = match &self.origins {
+            AllowedOrigins::Any => AllowOrigin::any(),
+            AllowedOrigins::Loopback => {
+                AllowOrigin::predicate(|origin: &HeaderValue, _| is_loopback(origin))
+            }
+            AllowedOrigins::List(origins) => AllowOrigin::list(origins.clone()),
+        };
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(self.methods.clone())
+            .allow_headers(self.headers.clone())
+            .expose_headers([
+                header::RETRY_AFTER,
+                HeaderName::from_static(REPLAYED_HEADER),
+                HeaderName::from_static(CORRELATION_ID_HEADER),
+            ])
+            .max_age(PREFLIGHT_MAX_AGE)
+    }
+}
+
+fn parse_origins(value: &str) -> AllowedOrigins {
+    if value.split(',').any(|origin| origin.trim() == "*") {
+        return AllowedOrigins::Any;
+    }
+    AllowedOrigins::List(parse_list("RAINBOW_CORS_ORIGINS", value, |origin| {
+        HeaderValue::from_str(origin.trim_end_matches('/')).ok()
+    }))
+}
+
+fn parse_list<T>(var: &str, value: &str, parse: impl Fn(&str) -> Option<T>) -> Vec<T> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| {
+            let parsed = parse(item);
+            if parsed.is_none() {
+                warn!("Ignoring {} entry {:?}", var, item);
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Whether `origin` is `http(s)://localhost`, `127.0.0.1` or `[::1]`, on any port
+fn is_loopback(origin: &HeaderValue) -> bool {
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    let Some(host) = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    else {
+        return false;
+    };
+    let host = match host.strip_prefix("[::1]") {
+        Some(port) => return port.is_empty() || port.starts_with(':'),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host == "localhost" || host == "127.0.0.1"
+}
+
+/// Whether `path` is the dashboard or one of its static files
+fn is_dashboard(path: &str) -> bool {
+    path == "/" || path.starts_with("/static/")
+}
+
+/// Add the content security policy and framing and sniffing protections to the
+/// dashboard's responses
+pub async fn dashboard_headers(
+    State(security): State<Arc<HttpSecurity>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let dashboard = is_dashboard(request.uri().path());
+    let mut response = next.run(request).await;
+    if dashboard {
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_SECURITY_POLICY, security.csp.clone());
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+        headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+        headers.insert(
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("same-origin"),
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origins() {
+        let loopback = |origin: &'static str| is_loopback(&HeaderValue::from_static(origin));
+        assert!(loopback("http://localhost:3000"));
+        assert!(loopback("https://127.0.0.1"));
+        assert!(loopback("http://[::1]:8080"));
+        assert!(!loopback("http://localhost.evil.com"));
+        assert!(!loopback("http://192.168.1.20:3000"));
+        assert!(!loopback("null"));
+
+        assert_eq!(
+            parse_origins("http://192.168.1.20:3002/, ,bad\norigin"),
+            AllowedOrigins::List(vec![HeaderValue::from_static("http://192.168.1.20:3002")])
+        );
+        assert_eq!(parse_origins("https://a.example, *"), AllowedOrigins::Any);
+    }
+
+    #[test]
+    fn test_dashboard_paths() {
+        assert!(is_dashboard("/"));
+        assert!(is_dashboard("/static/app.js"));
+        assert!(!is_dashboard("/api/health"));
+        assert!(!is_dashboard("/staticfoo"));
+    }
+}
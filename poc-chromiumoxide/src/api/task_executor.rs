@@ -2,22 +2,33 @@
 // Bridges LLM-generated task plans to actual browser operations
 
 use crate::api::llm_handlers::{BrowserAction, TaskPlan};
-use crate::browser::Browser;
+use crate::browser::{Browser, ScreenshotOptions};
+use crate::perception::search_results;
+use crate::perception::smart_forms::SmartFormHandler;
+use crate::perception::visual_diff::{self, VisualDiff};
 use anyhow::Result;
 use serde::Serialize;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
+/// Time without DOM changes after which a page counts as loaded
+const DOM_QUIET: Duration = Duration::from_millis(500);
+
 /// Execution result for a single action
 #[derive(Debug, Clone, Serialize)]
 pub struct ActionResult {
+    /// Position of the step in the plan
+    pub step_index: usize,
     pub action_type: String,
     pub target: Option<String>,
     pub success: bool,
     pub execution_time_ms: u64,
     pub result_data: Option<serde_json::Value>,
     pub error: Option<String>,
+    /// Screenshot comparison before and after actions expected to change the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_change: Option<VisualDiff>,
 }
 
 /// Overall execution result for a task plan
@@ -71,9 +82,26 @@ impl TaskPlanExecutor {
             );
 
             let action_start = Instant::now();
+            let before = if changes_page(&action.action_type) {
+                self.viewport_screenshot().await
+            } else {
+                None
+            };
             match self.execute_action(action).await {
                 Ok(mut result) => {
+                    result.step_index = index;
                     result.execution_time_ms = action_start.elapsed().as_millis() as u64;
+                    if let Some(before) = before {
+                        result.page_change = self.page_change_since(&before).await;
+                        if let Some(diff) = result.page_change.as_ref().filter(|d| !d.changed) {
+                            warn!(
+                                "Step {} ({}) left the page visually unchanged: {}",
+                                index + 1,
+                                action.action_type,
+                                diff.describe()
+                            );
+                        }
+                    }
                     steps_completed += 1;
 
                     // Update final result based on action type
@@ -89,12 +117,14 @@ impl TaskPlanExecutor {
                 Err(e) => {
                     steps_failed += 1;
                     let error_result = ActionResult {
+                        step_index: index,
                         action_type: action.action_type.clone(),
                         target: action.target.clone(),
                         success: false,
                         execution_time_ms: action_start.elapsed().as_millis() as u64,
                         result_data: None,
                         error: Some(e.to_string()),
+                        page_change: None,
                     };
                     action_results.push(error_result);
 
@@ -163,10 +193,11 @@ impl TaskPlanExecutor {
 
                     // Wait for page load if requested
                     if action.options.wait_for_element.unwrap_or(true) {
-                        tokio::time::sleep(Duration::from_millis(1000)).await;
+                        self.browser.wait_for_dom_settled(DOM_QUIET, timeout).await;
                     }
 
                     Ok(ActionResult {
+                        step_index: 0, // Will be set by caller
                         action_type: action.action_type.clone(),
                         target: action.target.clone(),
                         success: true,
@@ -176,6 +207,7 @@ impl TaskPlanExecutor {
                             "action": "navigated"
                         })),
                         error: None,
+                        page_change: None,
                     })
                 } else {
                     Err(anyhow::anyhow!("Navigate action requires target URL"))
@@ -192,6 +224,7 @@ impl TaskPlanExecutor {
                     self.browser.click(selector).await?;
 
                     Ok(ActionResult {
+                        step_index: 0, // Will be set by caller
                         action_type: action.action_type.clone(),
                         target: action.target.clone(),
                         success: true,
@@ -201,6 +234,7 @@ impl TaskPlanExecutor {
                             "action": "clicked"
                         })),
                         error: None,
+                        page_change: None,
                     })
                 } else {
                     Err(anyhow::anyhow!("Click action requires target selector"))
@@ -217,6 +251,7 @@ impl TaskPlanExecutor {
                     self.browser.type_text(selector, text).await?;
 
                     Ok(ActionResult {
+                        step_index: 0, // Will be set by caller
                         action_type: action.action_type.clone(),
                         target: action.target.clone(),
                         success: true,
@@ -227,6 +262,7 @@ impl TaskPlanExecutor {
                             "action": "typed"
                         })),
                         error: None,
+                        page_change: None,
                     })
                 } else {
                     Err(anyhow::anyhow!(
@@ -235,6 +271,39 @@ impl TaskPlanExecutor {
                 }
             }
 
+            "set_date" => {
+                if let (Some(ref selector), Some(ref date)) = (&action.target, &action.value) {
+                    if action.options.wait_for_element.unwrap_or(true) {
+                        self.browser.wait_for_selector(selector, timeout).await?;
+                    }
+
+                    let fill = SmartFormHandler::new()
+                        .fill_date(&self.browser, selector, date)
+                        .await?;
+
+                    Ok(ActionResult {
+                        step_index: 0, // Will be set by caller
+                        action_type: action.action_type.clone(),
+                        target: action.target.clone(),
+                        success: true,
+                        execution_time_ms: 0,
+                        result_data: Some(serde_json::json!({
+                            "selector": selector,
+                            "date": fill.date,
+                            "method": fill.method,
+                            "value": fill.value,
+                            "action": "date_set"
+                        })),
+                        error: None,
+                        page_change: None,
+                    })
+                } else {
+                    Err(anyhow::anyhow!(
+                        "Set date action requires both target selector and date value"
+                    ))
+                }
+            }
+
             "wait" => {
                 let wait_time = if let Some(ref time_str) = action.value {
                     time_str.parse::<u64>().unwrap_or(1000)
@@ -245,6 +314,7 @@ impl TaskPlanExecutor {
                 tokio::time::sleep(Duration::from_millis(wait_time)).await;
 
                 Ok(ActionResult {
+                    step_index: 0, // Will be set by caller
                     action_type: action.action_type.clone(),
                     target: action.target.clone(),
                     success: true,
@@ -254,6 +324,7 @@ impl TaskPlanExecutor {
                         "action": "waited"
                     })),
                     error: None,
+                    page_change: None,
                 })
             }
 
@@ -267,6 +338,7 @@ impl TaskPlanExecutor {
                     let text = self.browser.get_text(selector).await?;
 
                     Ok(ActionResult {
+                        step_index: 0, // Will be set by caller
                         action_type: action.action_type.clone(),
                         target: action.target.clone(),
                         success: true,
@@ -277,17 +349,69 @@ impl TaskPlanExecutor {
                             "action": "extracted"
                         })),
                         error: None,
+                        page_change: None,
                     })
                 } else {
                     Err(anyhow::anyhow!("Extract action requires target selector"))
                 }
             }
 
+            "extract_search_results" => {
+                let results = search_results::extract(&self.browser).await?;
+
+                Ok(ActionResult {
+                    step_index: 0, // Will be set by caller
+                    action_type: action.action_type.clone(),
+                    target: action.target.clone(),
+                    success: true,
+                    execution_time_ms: 0,
+                    result_data: Some(serde_json::json!({
+                        "engine": results.engine,
+                        "query": results.query,
+                        "results": results.results,
+                        "action": "extracted_search_results"
+                    })),
+                    error: None,
+                    page_change: None,
+                })
+            }
+
+            "open_result" => {
+                // Value picks a result by position, target describes the wanted result
+                let results = search_results::extract(&self.browser).await?;
+                let position = action.value.as_deref().and_then(|v| v.trim().parse().ok());
+                let chosen = match (position, action.target.as_deref()) {
+                    (Some(position), _) => results.at(position),
+                    (None, Some(intent)) => results.ranked(intent).into_iter().next(),
+                    (None, None) => results.at(1),
+                }
+                .ok_or_else(|| anyhow::anyhow!("No matching search result on the page"))?;
+
+                self.browser.navigate_to(&chosen.url).await?;
+
+                Ok(ActionResult {
+                    step_index: 0, // Will be set by caller
+                    action_type: action.action_type.clone(),
+                    target: action.target.clone(),
+                    success: true,
+                    execution_time_ms: 0,
+                    result_data: Some(serde_json::json!({
+                        "url": chosen.url,
+                        "title": chosen.title,
+                        "position": chosen.position,
+                        "action": "opened_result"
+                    })),
+                    error: None,
+                    page_change: None,
+                })
+            }
+
             "wait_for_element" => {
                 if let Some(ref selector) = action.target {
                     self.browser.wait_for_selector(selector, timeout).await?;
 
                     Ok(ActionResult {
+                        step_index: 0, // Will be set by caller
                         action_type: action.action_type.clone(),
                         target: action.target.clone(),
                         success: true,
@@ -297,6 +421,7 @@ impl TaskPlanExecutor {
                             "action": "element_found"
                         })),
                         error: None,
+                        page_change: None,
                     })
                 } else {
                     Err(anyhow::anyhow!(
@@ -306,18 +431,20 @@ impl TaskPlanExecutor {
             }
 
             "wait_for_load" => {
-                // Simple page load wait
-                tokio::time::sleep(Duration::from_millis(2000)).await;
+                let settled = self.browser.wait_for_dom_settled(DOM_QUIET, timeout).await;
 
                 Ok(ActionResult {
+                    step_index: 0, // Will be set by caller
                     action_type: action.action_type.clone(),
                     target: action.target.clone(),
                     success: true,
                     execution_time_ms: 0,
                     result_data: Some(serde_json::json!({
-                        "action": "page_loaded"
+                        "action": "page_loaded",
+                        "settled": settled
                     })),
                     error: None,
+                    page_change: None,
                 })
             }
 
@@ -328,6 +455,7 @@ impl TaskPlanExecutor {
                     .await?;
 
                 Ok(ActionResult {
+                    step_index: 0, // Will be set by caller
                     action_type: action.action_type.clone(),
                     target: action.target.clone(),
                     success: true,
@@ -337,6 +465,7 @@ impl TaskPlanExecutor {
                         "action": "screenshot_taken"
                     })),
                     error: None,
+                    page_change: None,
                 })
             }
 
@@ -359,7 +488,7 @@ impl TaskPlanExecutor {
     ) {
         if let Some(result_data) = &result.result_data {
             match action.action_type.as_str() {
-                "navigate" => {
+                "navigate" | "open_result" => {
                     if let Some(url) = result_data.get("url") {
                         final_result["current_url"] = url.clone();
                     }
@@ -377,6 +506,11 @@ impl TaskPlanExecutor {
                         );
                     }
                 }
+                "extract_search_results" => {
+                    if let Some(results) = result_data.get("results") {
+                        final_result["search_results"] = results.clone();
+                    }
+                }
                 "screenshot" => {
                     final_result["screenshot_taken"] = serde_json::Value::Bool(true);
                 }
@@ -385,12 +519,44 @@ impl TaskPlanExecutor {
         }
     }
 
+    /// Screenshot of the visible viewport, if one can be taken
+    async fn viewport_screenshot(&self) -> Option<Vec<u8>> {
+        let options = ScreenshotOptions {
+            full_page: false,
+            wait_after_load: Duration::ZERO,
+            ..ScreenshotOptions::default()
+        };
+        match self.browser.screenshot(options).await {
+            Ok(screenshot) => Some(screenshot),
+            Err(e) => {
+                warn!("Could not capture screenshot for change detection: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Compare the current viewport with a screenshot taken before an action
+    async fn page_change_since(&self, before: &[u8]) -> Option<VisualDiff> {
+        let after = self.viewport_screenshot().await?;
+        visual_diff::compare(before, &after)
+            .map_err(|e| warn!("Screenshot comparison failed: {}", e))
+            .ok()
+    }
+
     /// Determine if execution should stop on failure for this action type
     fn should_stop_on_failure(&self, action_type: &str) -> bool {
         matches!(action_type, "navigate" | "wait_for_element")
     }
 }
 
+/// Actions whose success should be visible on the page
+fn changes_page(action_type: &str) -> bool {
+    matches!(
+        action_type,
+        "navigate" | "click" | "type" | "set_date" | "open_result"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
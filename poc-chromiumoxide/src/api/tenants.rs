@@ -0,0 +1,234 @@
+// Tenants
+// API keys from `RAINBOW_API_KEYS` map callers to tenants; JWTs name theirs in a
+// `tenant` claim. Each tenant only sees its own sessions, jobs and operations, has
+// its own stateless browsers and tool caches, and learns into its own patterns, so
+// one customer's automation state cannot leak into another's. Callers without a
+// tenant share the default partition.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::auth::{current_principal, Principal};
+use crate::intelligence::{LearningStore, SharedLearning};
+
+/// Header carrying an API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// How often a tenant's learning is written to disk while it changes
+const LEARNING_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tenant of the request being served; `None` for the default partition
+pub fn current_tenant() -> Option<String> {
+    current_principal().and_then(|principal| principal.tenant)
+}
+
+/// Tenant ids name directories, so they are limited to letters, digits, `-` and `_`
+pub fn valid_tenant_id(tenant: &str) -> bool {
+    !tenant.is_empty()
+        && tenant.len() <= 64
+        && tenant
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Key of a stateless caller's browser binding, so tenants never share one
+pub fn client_key(token: &str) -> String {
+    match current_tenant() {
+        Some(tenant) => format!("{}/{}", tenant, token),
+        None => token.to_string(),
+    }
+}
+
+/// API keys and the tenants they belong to
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    tenants: HashMap<String, String>,
+}
+
+impl ApiKeys {
+    /// Parse `tenant:key` pairs separated by commas; a tenant may have several keys
+    pub fn parse(value: &str) -> Self {
+        let mut tenants = HashMap::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once(':') {
+                Some((tenant, key)) if valid_tenant_id(tenant.trim()) && !key.trim().is_empty() => {
+                    tenants.insert(key.trim().to_string(), tenant.trim().to_string());
+                }
+                _ => warn!("Ignoring malformed API key entry for RAINBOW_API_KEYS"),
+            }
+        }
+        Self { tenants }
+    }
+
+    /// Keys from `RAINBOW_API_KEYS`; none when unset
+    pub fn from_env() -> Self {
+        let keys = Self::parse(&std::env::var("RAINBOW_API_KEYS").unwrap_or_default());
+        if keys.is_enabled() {
+            info!(
+                "API key authentication enabled ({} keys)",
+                keys.tenants.len()
+            );
+        }
+        keys
+    }
+
+    /// Whether any key is configured, making a key or token mandatory
+    pub fn is_enabled(&self) -> bool {
+        !self.tenants.is_empty()
+    }
+
+    /// Principal of the tenant owning `key`
+    pub fn principal(&self, key: &str) -> Option<Principal> {
+        let tenant = self.tenants.get(key)?;
+        Some(Principal {
+            subject: tenant.clone(),
+            tenant: Some(tenant.clone()),
+            ..Principal::default()
+        })
+    }
+}
+
+/// Learning state of each tenant, loaded on first use
+pub struct TenantLearning {
+    default: Arc<SharedLearning>,
+    /// Directory tenants' learning is kept under; in memory only when unset
+    dir: Option<PathBuf>,
+    tenants: RwLock<HashMap<String, Arc<SharedLearning>>>,
+}
+
+impl TenantLearning {
+    /// `default` is the default partition's learning; tenants get
+    /// `<dir>/<id>/learning.json`
+    pub fn new(default: Arc<SharedLearning>, dir: Option<PathBuf>) -> Self {
+        Self {
+            default,
+            dir,
+            tenants: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Learning of the current request's tenant
+    pub async fn current(&self) -> Arc<SharedLearning> {
+        match current_tenant() {
+            Some(tenant) => self.tenant(&tenant).await,
+            None => self.default.clone(),
+        }
+    }
+
+    async fn tenant(&self, tenant: &str) -> Arc<SharedLearning> {
+        if let Some(learning) = self.tenants.read().await.get(tenant) {
+            return learning.clone();
+        }
+        let mut tenants = self.tenants.write().await;
+        if let Some(learning) = tenants.get(tenant) {
+            return learning.clone();
+        }
+        let learning = match &self.dir {
+            Some(dir) => {
+                let store = LearningStore::with_path(dir.join(tenant).join("learning.json"));
+                match SharedLearning::load(store).await {
+                    Ok(learning) => {
+                        let learning = Arc::new(learning);
+                        learning.clone().start_flush(LEARNING_FLUSH_INTERVAL);
+                        learning
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Learning data of tenant {} not loaded, learning in memory only: {}",
+                            tenant, e
+                        );
+                        Arc::new(SharedLearning::in_memory())
+                    }
+                }
+            }
+            None => Arc::new(SharedLearning::in_memory()),
+        };
+        tenants.insert(tenant.to_string(), learning.clone());
+        learning
+    }
+
+    /// Write every partition's pending changes
+    pub async fn flush(&self) -> anyhow::Result<()> {
+        self.default.flush().await?;
+        for (tenant, learning) in self.tenants.read().await.iter() {
+            if let Err(e) = learning.flush().await {
+                warn!(
+                    "Failed to persist learning data of tenant {}: {}",
+                    tenant, e
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::auth::with_principal;
+    use super::*;
+    use crate::browser::affinity::is_default_client;
+
+    fn tenant_principal(tenant: &str) -> Option<Principal> {
+        ApiKeys::parse(&format!("{}:key", tenant)).principal("key")
+    }
+
+    #[test]
+    fn maps_keys_to_tenants() {
+        let keys = ApiKeys::parse("acme:k1, acme:k2,globex:k3,bad tenant:k4,nokey:");
+        assert!(keys.is_enabled());
+        assert_eq!(
+            keys.principal("k2").unwrap().tenant.as_deref(),
+            Some("acme")
+        );
+        assert_eq!(keys.principal("k3").unwrap().subject, "globex");
+        assert!(keys.principal("k4").is_none());
+        assert!(keys.principal("unknown").is_none());
+        assert!(!ApiKeys::parse("").is_enabled());
+    }
+
+    #[tokio::test]
+    async fn partitions_learning_and_browsers_by_tenant() {
+        let default = Arc::new(SharedLearning::in_memory());
+        let learning = TenantLearning::new(default.clone(), None);
+
+        assert!(Arc::ptr_eq(&learning.current().await, &default));
+        assert_eq!(client_key("default"), "default");
+
+        let (acme, acme_again, acme_client) = with_principal(tenant_principal("acme"), async {
+            (
+                learning.current().await,
+                learning.current().await,
+                client_key("default"),
+            )
+        })
+        .await;
+        let globex = with_principal(tenant_principal("globex"), learning.current()).await;
+        assert!(Arc::ptr_eq(&acme, &acme_again));
+        assert!(!Arc::ptr_eq(&acme, &default));
+        assert!(!Arc::ptr_eq(&acme, &globex));
+        assert_eq!(acme_client, "acme/default");
+    }
+
+    #[tokio::test]
+    async fn keeps_default_client_of_every_tenant() {
+        let keys = |tenant: &str| {
+            with_principal(tenant_principal(tenant), async {
+                (client_key("default"), client_key("crawler"))
+            })
+        };
+        let (acme_default, acme_crawler) = keys("acme").await;
+        let (globex_default, globex_crawler) = keys("globex").await;
+
+        // Each tenant's registry browser survives expiry and eviction, other clients do not
+        assert!(is_default_client(&client_key("default")));
+        assert!(is_default_client(&acme_default));
+        assert!(is_default_client(&globex_default));
+        assert!(!is_default_client(&acme_crawler));
+        assert!(!is_default_client(&globex_crawler));
+        assert!(!is_default_client(&client_key("crawler")));
+    }
+}
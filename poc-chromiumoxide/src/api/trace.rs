@@ -0,0 +1,307 @@
+// Request traces
+// Each request runs under a correlation id (see `coordination::correlation`), and
+// `/api/trace/:id` puts back together what it caused across the engines: the API
+// requests made under the id, session activity such as navigations, tool runs and
+// perceptions, LLM calls and coordination events, in the order they happened.
+
+use axum::{
+    extract::{Json, Path, Request, State},
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::warn;
+
+use super::tenants::current_tenant;
+use super::{ApiResponse, AppState, ErrorCode};
+use crate::browser::{ActivityKind, SessionActivity};
+use crate::coordination::correlation::{
+    current_correlation_id, new_correlation_id, valid_correlation_id, with_correlation_id,
+    CORRELATION_ID_HEADER,
+};
+
+/// Correlation ids whose steps are kept before the oldest are dropped
+const MAX_TRACES: usize = 500;
+/// Steps kept per correlation id
+const MAX_STEPS: usize = 1000;
+
+/// Something a request caused
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStep {
+    pub at: DateTime<Utc>,
+    /// `api`, `browser`, `tools`, `perception`, `llm`, or the source of an event
+    pub engine: String,
+    /// `request`, the kind of session activity, `llm_call`, or the event type
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub detail: serde_json::Value,
+    #[serde(skip)]
+    tenant: Option<String>,
+}
+
+/// The causal chain of one correlation id
+#[derive(Debug, Serialize)]
+pub struct Trace {
+    pub correlation_id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    /// Engines in the order the request first reached them
+    pub engines: Vec<String>,
+    pub steps: Vec<TraceStep>,
+}
+
+impl Trace {
+    /// The chain of `steps`, oldest first; `None` when there are none
+    fn build(correlation_id: &str, mut steps: Vec<TraceStep>) -> Option<Self> {
+        steps.sort_by_key(|step| step.at);
+        let started_at = steps.first()?.at;
+        let finished_at = steps.last()?.at;
+        let mut engines: Vec<String> = Vec::new();
+        for step in &steps {
+            if !engines.contains(&step.engine) {
+                engines.push(step.engine.clone());
+            }
+        }
+        Some(Self {
+            correlation_id: correlation_id.to_string(),
+            started_at,
+            finished_at,
+            duration_ms: (finished_at - started_at).num_milliseconds(),
+            engines,
+            steps,
+        })
+    }
+}
+
+/// Steps that are neither requests nor events, by correlation id
+#[derive(Default)]
+pub struct TraceStore {
+    steps: RwLock<HashMap<String, Vec<TraceStep>>>,
+    /// Correlation ids, oldest first
+    order: RwLock<VecDeque<String>>,
+}
+
+impl TraceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record session activity performed under a correlation id as it happens
+    pub fn follow_activity(self: &Arc<Self>, mut activity: broadcast::Receiver<SessionActivity>) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match activity.recv().await {
+                    Ok(activity) => {
+                        let Some(correlation_id) = activity.correlation_id.clone() else {
+                            continue;
+                        };
+                        let step = TraceStep {
+                            at: activity.timestamp,
+                            engine: activity_engine(activity.kind).to_string(),
+                            kind: serde_json::to_value(activity.kind)
+                                .ok()
+                                .and_then(|kind| kind.as_str().map(str::to_string))
+                                .unwrap_or_default(),
+                            session_id: Some(activity.session_id),
+                            detail: activity.detail,
+                            tenant: activity.tenant,
+                        };
+                        store.push(correlation_id, step).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Traces missed {} activity events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    /// Record a step of the current request; does nothing outside a request
+    pub async fn record(
+        &self,
+        engine: &str,
+        kind: &str,
+        session_id: Option<String>,
+        detail: serde_json::Value,
+    ) {
+        let Some(correlation_id) = current_correlation_id() else {
+            return;
+        };
+        let step = TraceStep {
+            at: Utc::now(),
+            engine: engine.to_string(),
+            kind: kind.to_string(),
+            session_id,
+            detail,
+            tenant: crate::browser::session::current_tenant(),
+        };
+        self.push(correlation_id, step).await;
+    }
+
+    async fn push(&self, correlation_id: String, step: TraceStep) {
+        let mut traces = self.steps.write().await;
+        if !traces.contains_key(&correlation_id) {
+            let mut order = self.order.write().await;
+            order.push_back(correlation_id.clone());
+            while order.len() > MAX_TRACES {
+                if let Some(oldest) = order.pop_front() {
+                    traces.remove(&oldest);
+                }
+            }
+        }
+        let steps = traces.entry(correlation_id).or_default();
+        if steps.len() < MAX_STEPS {
+            steps.push(step);
+        }
+    }
+
+    async fn steps(&self, correlation_id: &str) -> Vec<TraceStep> {
+        self.steps
+            .read()
+            .await
+            .get(correlation_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+fn activity_engine(kind: ActivityKind) -> &'static str {
+    match kind {
+        ActivityKind::Navigation | ActivityKind::Click | ActivityKind::Type => "browser",
+        ActivityKind::ToolExecution => "tools",
+        ActivityKind::Perception => "perception",
+        ActivityKind::Thinking => "llm",
+    }
+}
+
+/// Run each request under the correlation id its client sent, or a new one, and
+/// answer with the id
+pub(super) async fn correlate(request: Request, next: Next) -> Response {
+    let correlation_id = request
+        .headers()
+        .get(CORRELATION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| valid_correlation_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(new_correlation_id);
+    let mut response = with_correlation_id(Some(correlation_id.clone()), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&correlation_id) {
+        response.headers_mut().insert(CORRELATION_ID_HEADER, value);
+    }
+    response
+}
+
+/// Everything a request caused, across engines, in the order it happened
+#[utoipa::path(
+    get,
+    path = "/api/trace/{id}",
+    tag = "trace",
+    params(("id" = String, Path, description = "Correlation id, from the X-Correlation-Id response header")),
+    responses(
+        (status = 200, description = "The causal chain", body = ApiEnvelope),
+        (status = 404, description = "Nothing recorded under the id", body = ApiEnvelope),
+    )
+)]
+pub async fn get_trace(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let mut steps = state.traces.steps(&id).await;
+    for entry in state.audit.for_correlation(&id).await {
+        steps.push(TraceStep {
+            at: entry.timestamp - chrono::Duration::milliseconds(entry.duration_ms as i64),
+            engine: "api".to_string(),
+            kind: "request".to_string(),
+            session_id: None,
+            detail: serde_json::json!({
+                "method": entry.method,
+                "path": entry.path,
+                "status": entry.status,
+                "duration_ms": entry.duration_ms,
+                "principal": entry.principal,
+            }),
+            tenant: entry.tenant,
+        });
+    }
+    for logged in state.events.get_history(None).await {
+        if logged.correlation_id.as_deref() != Some(id.as_str()) {
+            continue;
+        }
+        steps.push(TraceStep {
+            at: logged.occurred_at,
+            engine: serde_json::to_value(logged.event.source())
+                .ok()
+                .and_then(|source| source.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            kind: serde_json::to_value(logged.event.event_type())
+                .ok()
+                .and_then(|kind| kind.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            session_id: logged.event.session_id().map(str::to_string),
+            detail: serde_json::json!({
+                "sequence_id": logged.sequence_id,
+                "event": logged.event,
+            }),
+            tenant: logged.tenant,
+        });
+    }
+
+    // Other tenants' traces look the same as unknown ones
+    let tenant = current_tenant();
+    steps.retain(|step| step.tenant == tenant);
+    match Trace::build(&id, steps) {
+        Some(trace) => Json(ApiResponse::success(trace)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::NotFound,
+                format!("Trace not found: {}", id),
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_steps_follow_correlation_id() {
+        let store = Arc::new(TraceStore::new());
+        let (activity, receiver) = broadcast::channel(8);
+        store.follow_activity(receiver);
+
+        with_correlation_id(Some("req-1".to_string()), async {
+            store
+                .record("llm", "llm_call", None, json!({"tokens_used": 42}))
+                .await;
+            let mut clicked =
+                SessionActivity::new("s1", ActivityKind::Click, json!({"selector": "#buy"}));
+            clicked.correlation_id = current_correlation_id();
+            activity.send(clicked).unwrap();
+        })
+        .await;
+        // Outside a request nothing is recorded
+        store.record("llm", "llm_call", None, json!({})).await;
+        activity
+            .send(SessionActivity::new("s1", ActivityKind::Click, json!({})))
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let trace = Trace::build("req-1", store.steps("req-1").await).unwrap();
+        assert_eq!(trace.engines, ["llm", "browser"]);
+        assert_eq!(trace.steps[1].kind, "click");
+        assert_eq!(trace.steps[1].session_id.as_deref(), Some("s1"));
+        assert_eq!(store.order.read().await.len(), 1);
+        assert!(Trace::build("req-2", store.steps("req-2").await).is_none());
+    }
+}
@@ -0,0 +1,696 @@
+// Webhooks
+// Callbacks registered at `/api/webhooks` are POSTed the coordination events of
+// the types they subscribe to: sessions created, navigations, finished workflows,
+// spent LLM budgets and the rest of the event bus. Each delivery is signed with
+// the webhook's secret, retried with backoff while the receiver fails, and kept
+// in the webhook's delivery log. Receivers on loopback, link-local or private
+// addresses, such as cloud metadata endpoints, are refused unless the operator
+// sets `RAINBOW_WEBHOOK_ALLOW_PRIVATE`.
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use super::auth::current_principal;
+use super::pagination::PageQuery;
+use super::tenants::current_tenant;
+use super::{ApiResponse, AppState, ErrorCode};
+use crate::coordination::{Event, EventBus, EventHandler, EventType};
+
+/// Header carrying `sha256=` and the hex HMAC-SHA256 of the body under the secret
+pub const SIGNATURE_HEADER: &str = "x-rainbow-signature";
+
+/// Header naming the event type of a delivery
+const EVENT_HEADER: &str = "x-rainbow-event";
+
+/// Header carrying the delivery id, the same across retries
+const DELIVERY_HEADER: &str = "x-rainbow-delivery";
+
+/// Attempts per delivery before it is given up
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry; doubled for each further one
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Time the receiver has to answer one attempt
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Deliveries kept in each webhook's log, newest first
+const MAX_LOGGED_DELIVERIES: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<EventType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Signs deliveries; only returned when the webhook is registered
+    #[serde(skip)]
+    secret: String,
+    /// Subject of the principal that registered the webhook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Tenant of the registrant; only its events are delivered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of delivering one event to a webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub id: String,
+    pub event: EventType,
+    pub attempts: u32,
+    pub success: bool,
+    /// Status the receiver answered the last attempt with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Registered webhooks and their delivery logs
+pub struct Webhooks {
+    events: Arc<EventBus>,
+    client: reqwest::Client,
+    /// Whether receivers may be on loopback, link-local or private addresses
+    allow_private: bool,
+    hooks: RwLock<HashMap<String, Webhook>>,
+    deliveries: RwLock<HashMap<String, VecDeque<Delivery>>>,
+    /// Event types the dispatcher is subscribed to on the bus
+    subscribed: Mutex<HashSet<EventType>>,
+}
+
+impl Webhooks {
+    /// Webhooks allowing private receivers when `RAINBOW_WEBHOOK_ALLOW_PRIVATE` is set
+    pub fn from_env(events: Arc<EventBus>) -> Self {
+        let allow_private = std::env::var("RAINBOW_WEBHOOK_ALLOW_PRIVATE")
+            .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        if allow_private {
+            warn!("Webhooks may call loopback, link-local and private addresses");
+        }
+        Self::new(events, allow_private)
+    }
+
+    /// Webhooks delivering to loopback, link-local and private addresses only when
+    /// `allow_private` is set
+    pub fn new(events: Arc<EventBus>, allow_private: bool) -> Self {
+        // Redirects are not followed, so a receiver cannot point deliveries elsewhere
+        let mut client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+        if !allow_private {
+            client = client.dns_resolver(Arc::new(PublicResolver));
+        }
+        Self {
+            events,
+            client: client
+                .build()
+                .expect("Failed to create webhook HTTP client"),
+            allow_private,
+            hooks: RwLock::new(HashMap::new()),
+            deliveries: RwLock::new(HashMap::new()),
+            subscribed: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Register a webhook for the current tenant, subscribing to any event type
+    /// no webhook asked for before
+    async fn register(self: &Arc<Self>, req: RegisterWebhookRequest) -> Webhook {
+        let principal = current_principal();
+        let webhook = Webhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: req.url,
+            events: req.events,
+            description: req.description,
+            secret: req.secret.unwrap_or_else(generate_secret),
+            created_by: principal.as_ref().map(|p| p.subject.clone()),
+            tenant: current_tenant(),
+            created_at: Utc::now(),
+        };
+
+        let mut subscribed = self.subscribed.lock().await;
+        for event_type in &webhook.events {
+            if subscribed.insert(*event_type) {
+                self.events
+                    .subscribe(*event_type, Dispatcher(Arc::downgrade(self)))
+                    .await;
+            }
+        }
+        drop(subscribed);
+
+        self.hooks
+            .write()
+            .await
+            .insert(webhook.id.clone(), webhook.clone());
+        webhook
+    }
+
+    /// The webhook, if the current tenant registered it
+    pub async fn get(&self, id: &str) -> Option<Webhook> {
+        self.hooks
+            .read()
+            .await
+            .get(id)
+            .filter(|hook| hook.tenant == current_tenant())
+            .cloned()
+    }
+
+    /// The current tenant's webhooks, oldest first
+    pub async fn list(&self) -> Vec<Webhook> {
+        let tenant = current_tenant();
+        let mut hooks: Vec<Webhook> = self
+            .hooks
+            .read()
+            .await
+            .values()
+            .filter(|hook| hook.tenant == tenant)
+            .cloned()
+            .collect();
+        hooks.sort_by_key(|hook| hook.created_at);
+        hooks
+    }
+
+    pub async fn remove(&self, id: &str) -> Option<Webhook> {
+        let tenant = current_tenant();
+        let mut hooks = self.hooks.write().await;
+        if hooks.get(id).is_none_or(|hook| hook.tenant != tenant) {
+            return None;
+        }
+        self.deliveries.write().await.remove(id);
+        hooks.remove(id)
+    }
+
+    /// Deliveries to a webhook, newest first
+    pub async fn deliveries(&self, id: &str) -> Option<Vec<Delivery>> {
+        self.get(id).await?;
+        Some(
+            self.deliveries
+                .read()
+                .await
+                .get(id)
+                .map(|log| log.iter().cloned().collect())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Webhooks of `tenant` subscribed to `event_type`
+    async fn matching(&self, event_type: EventType, tenant: &Option<String>) -> Vec<Webhook> {
+        self.hooks
+            .read()
+            .await
+            .values()
+            .filter(|hook| &hook.tenant == tenant && hook.events.contains(&event_type))
+            .cloned()
+            .collect()
+    }
+
+    /// Deliver an event to the webhooks of the tenant it happened for, in the
+    /// background so the event bus is not held up by slow receivers
+    async fn dispatch(self: &Arc<Self>, event: &Event) {
+        let event_type = event.event_type();
+        for webhook in self.matching(event_type, &current_tenant()).await {
+            let delivery_id = uuid::Uuid::new_v4().to_string();
+            let body = payload(&delivery_id, event);
+            let webhooks = self.clone();
+            tokio::spawn(async move {
+                let delivery = webhooks
+                    .deliver(&webhook, event_type, delivery_id, body)
+                    .await;
+                webhooks.log(&webhook.id, delivery).await;
+            });
+        }
+    }
+
+    /// POST `body` to the webhook until it answers 2xx, a client error other than
+    /// 429, or the attempts run out
+    async fn deliver(
+        &self,
+        webhook: &Webhook,
+        event_type: EventType,
+        delivery_id: String,
+        body: Vec<u8>,
+    ) -> Delivery {
+        let signature = sign(&webhook.secret, &body);
+        let started_at = Utc::now();
+        let mut delay = FIRST_RETRY_DELAY;
+        let mut attempts = 0;
+        let (status_code, error) = loop {
+            attempts += 1;
+            let outcome = self
+                .client
+                .post(&webhook.url)
+                .timeout(ATTEMPT_TIMEOUT)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, event_name(event_type))
+                .header(DELIVERY_HEADER, &delivery_id)
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.clone())
+                .send()
+                .await;
+            let (status_code, error, retry) = match outcome {
+                Ok(response) if response.status().is_success() => {
+                    break (Some(response.status().as_u16()), None)
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    (
+                        Some(status.as_u16()),
+                        Some(format!("Receiver answered {}", status)),
+                        status.is_server_error()
+                            || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+                    )
+                }
+                Err(e) => (None, Some(e.to_string()), true),
+            };
+            if !retry || attempts >= MAX_ATTEMPTS {
+                break (status_code, error);
+            }
+            debug!(
+                "Webhook {} delivery {} failed, retrying in {:?}",
+                webhook.id, delivery_id, delay
+            );
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        };
+
+        match &error {
+            None => debug!("Delivered {} to webhook {}", delivery_id, webhook.id),
+            Some(error) => warn!(
+                "Gave up delivering {} to webhook {} after {} attempts: {}",
+                delivery_id, webhook.id, attempts, error
+            ),
+        }
+        Delivery {
+            id: delivery_id,
+            event: event_type,
+            attempts,
+            success: error.is_none(),
+            status_code,
+            error,
+            started_at,
+            finished_at: Utc::now(),
+        }
+    }
+
+    async fn log(&self, id: &str, delivery: Delivery) {
+        // The webhook may have been removed while the delivery ran
+        if !self.hooks.read().await.contains_key(id) {
+            return;
+        }
+        let mut deliveries = self.deliveries.write().await;
+        let log = deliveries.entry(id.to_string()).or_default();
+        log.push_front(delivery);
+        log.truncate(MAX_LOGGED_DELIVERIES);
+    }
+}
+
+/// Forwards bus events to the webhooks; holds them weakly as they own the bus
+struct Dispatcher(Weak<Webhooks>);
+
+#[async_trait::async_trait]
+impl EventHandler for Dispatcher {
+    async fn handle(&self, event: &Event) -> anyhow::Result<()> {
+        if let Some(webhooks) = self.0.upgrade() {
+            webhooks.dispatch(event).await;
+        }
+        Ok(())
+    }
+}
+
+fn generate_secret() -> String {
+    format!("whsec_{}", uuid::Uuid::new_v4().simple())
+}
+
+fn event_name(event_type: EventType) -> String {
+    serde_json::to_value(event_type)
+        .ok()
+        .and_then(|name| name.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Body POSTed for an event
+fn payload(delivery_id: &str, event: &Event) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "id": delivery_id,
+        "event": event.event_type(),
+        "occurred_at": Utc::now(),
+        "data": event,
+    }))
+    .unwrap_or_default()
+}
+
+/// `sha256=` and the hex HMAC-SHA256 of `body` under `secret`, as sent in the
+/// signature header
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, body);
+    let hex: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterWebhookRequest {
+    /// http(s) URL the events are POSTed to
+    pub url: String,
+    /// Event types to deliver, e.g. `session_created`, `navigation_completed`,
+    /// `workflow_completed` or `budget_exceeded`
+    #[schema(value_type = Vec<String>)]
+    pub events: Vec<EventType>,
+    /// Signing secret; one is generated when left out
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl RegisterWebhookRequest {
+    fn validate(&self, allow_private: bool) -> Result<(), String> {
+        let url = match url::Url::parse(&self.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+            _ => return Err(format!("Webhook URL must be an http(s) URL: {}", self.url)),
+        };
+        if !allow_private && is_private_host(&url) {
+            return Err(format!(
+                "Webhook URL must not point at a loopback, link-local or private address: {}",
+                self.url
+            ));
+        }
+        if self.events.is_empty() {
+            return Err("Webhook must subscribe to at least one event type".to_string());
+        }
+        if self.secret.as_deref().is_some_and(str::is_empty) {
+            return Err("Webhook secret must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Whether `url` names localhost or a literal loopback, link-local or private address.
+/// Host names resolving to such addresses are refused when delivering.
+fn is_private_host(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Some(url::Host::Ipv4(ip)) => is_private_ip(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => is_private_ip(IpAddr::V6(ip)),
+        None => true,
+    }
+}
+
+/// Loopback, link-local (including the 169.254.169.254 metadata service), private,
+/// shared (100.64.0.0/10) and unspecified addresses
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_private()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_private_ip(IpAddr::V4(mapped)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local fc00::/7 and link-local fe80::/10
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// Resolves receivers' host names, leaving out private addresses so a name cannot
+/// be pointed at an internal service after the webhook was registered
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let public: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| !is_private_ip(addr.ip()))
+                .collect();
+            if public.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(public.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// A webhook as registered, with the secret it signs deliveries with
+#[derive(Debug, Serialize)]
+struct WebhookRegistered {
+    #[serde(flatten)]
+    webhook: Webhook,
+    secret: String,
+}
+
+fn webhook_not_found(id: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ApiResponse::<()>::error(
+            ErrorCode::NotFound,
+            format!("Webhook not found: {}", id),
+        )),
+    )
+        .into_response()
+}
+
+/// Register a callback for coordination events of the given types
+#[utoipa::path(
+    post,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    request_body = RegisterWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook registered, with its signing secret", body = ApiEnvelope),
+        (status = 400, description = "Invalid URL or event types", body = ApiEnvelope),
+    )
+)]
+pub async fn register_webhook(
+    State(state): State<AppState>,
+    Json(req): Json<RegisterWebhookRequest>,
+) -> Response {
+    if let Err(message) = req.validate(state.webhooks.allow_private) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, message)),
+        )
+            .into_response();
+    }
+    let webhook = state.webhooks.register(req).await;
+    info!(
+        "Registered webhook {} for {:?} at {}",
+        webhook.id, webhook.events, webhook.url
+    );
+    (
+        StatusCode::CREATED,
+        Json(ApiResponse::success(WebhookRegistered {
+            secret: webhook.secret.clone(),
+            webhook,
+        })),
+    )
+        .into_response()
+}
+
+/// Webhooks registered by the caller's tenant
+#[utoipa::path(
+    get,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    params(PageQuery),
+    responses((status = 200, description = "Webhooks", body = ApiEnvelope))
+)]
+pub async fn list_webhooks(State(state): State<AppState>, page: PageQuery) -> Response {
+    page.respond(state.webhooks.list().await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "The webhook", body = ApiEnvelope),
+        (status = 404, description = "Unknown webhook", body = ApiEnvelope),
+    )
+)]
+pub async fn get_webhook(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.webhooks.get(&id).await {
+        Some(webhook) => Json(ApiResponse::success(webhook)).into_response(),
+        None => webhook_not_found(&id),
+    }
+}
+
+/// Stop delivering events to a webhook and drop its delivery log
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "The removed webhook", body = ApiEnvelope),
+        (status = 404, description = "Unknown webhook", body = ApiEnvelope),
+    )
+)]
+pub async fn delete_webhook(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.webhooks.remove(&id).await {
+        Some(webhook) => {
+            info!("Removed webhook {}", id);
+            Json(ApiResponse::success(webhook)).into_response()
+        }
+        None => webhook_not_found(&id),
+    }
+}
+
+/// The last 100 deliveries to a webhook, newest first, with their attempts and
+/// the receiver's answers
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id"), PageQuery),
+    responses(
+        (status = 200, description = "Deliveries", body = ApiEnvelope),
+        (status = 404, description = "Unknown webhook", body = ApiEnvelope),
+    )
+)]
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    page: PageQuery,
+) -> Response {
+    match state.webhooks.deliveries(&id).await {
+        Some(deliveries) => page.respond(deliveries),
+        None => webhook_not_found(&id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::auth::{with_principal, Principal};
+    use serde_json::json;
+
+    fn request(events: Vec<EventType>) -> RegisterWebhookRequest {
+        RegisterWebhookRequest {
+            url: "https://example.com/hooks".to_string(),
+            events,
+            secret: Some("s3cret".to_string()),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn signs_bodies_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let event = Event::BudgetExceeded {
+            client: "ci".to_string(),
+            budget_usd: 1.0,
+            spent_usd: 1.2,
+            timestamp: std::time::Instant::now(),
+        };
+        let body: serde_json::Value = serde_json::from_slice(&payload("d1", &event)).unwrap();
+        assert_eq!(body["event"], json!("budget_exceeded"));
+        assert_eq!(body["data"]["type"], json!("budget_exceeded"));
+        assert_eq!(body["data"]["spent_usd"], json!(1.2));
+
+        let mut invalid = request(vec![EventType::SessionCreated]);
+        invalid.url = "ftp://example.com".to_string();
+        assert!(invalid.validate(false).is_err());
+        assert!(request(vec![]).validate(false).is_err());
+    }
+
+    #[test]
+    fn refuses_private_receivers_unless_allowed() {
+        assert!(request(vec![EventType::SessionCreated])
+            .validate(false)
+            .is_ok());
+        for url in [
+            "http://localhost:8080/hooks",
+            "http://127.0.0.1/hooks",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.1.2.3/hooks",
+            "http://172.16.0.1/hooks",
+            "http://192.168.1.10/hooks",
+            "http://100.64.0.1/hooks",
+            "http://0.0.0.0/hooks",
+            "http://[::1]/hooks",
+            "http://[fd00::1]/hooks",
+            "http://[fe80::1]/hooks",
+            "http://[::ffff:169.254.169.254]/hooks",
+        ] {
+            let mut private = request(vec![EventType::SessionCreated]);
+            private.url = url.to_string();
+            assert!(private.validate(false).is_err(), "{}", url);
+            assert!(private.validate(true).is_ok(), "{}", url);
+        }
+        assert!(!is_private_ip("93.184.216.34".parse().unwrap()));
+        assert!(!is_private_ip("2606:2800:220:1::1".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn matches_webhooks_by_event_type_and_tenant() {
+        let webhooks = Arc::new(Webhooks::new(Arc::new(EventBus::new()), false));
+        let sessions = webhooks
+            .register(request(vec![
+                EventType::SessionCreated,
+                EventType::NavigationCompleted,
+            ]))
+            .await;
+        let principal = Principal {
+            subject: "acme".to_string(),
+            tenant: Some("acme".to_string()),
+            ..Principal::default()
+        };
+        let acme = with_principal(
+            Some(principal),
+            webhooks.register(request(vec![EventType::SessionCreated])),
+        )
+        .await;
+
+        let matched = webhooks.matching(EventType::SessionCreated, &None).await;
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].id, sessions.id);
+        let matched = webhooks
+            .matching(EventType::SessionCreated, &Some("acme".to_string()))
+            .await;
+        assert_eq!(matched[0].id, acme.id);
+        assert!(webhooks
+            .matching(EventType::WorkflowCompleted, &None)
+            .await
+            .is_empty());
+
+        // Other tenants can neither see nor remove the webhook
+        assert!(webhooks.remove(&acme.id).await.is_none());
+        assert_eq!(webhooks.list().await.len(), 1);
+        assert!(webhooks.deliveries(&sessions.id).await.unwrap().is_empty());
+    }
+}
@@ -8,12 +8,16 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+use utoipa::ToSchema;
 
-use super::AppState;
+use super::operations::report_progress;
+use super::{ApiError, AppState, ErrorCode};
+use crate::coordination::Event;
+use crate::intelligence::anomaly_detection::recent_layout_shift;
 use crate::intelligence::{
-    ActionRecommendation, IntelligenceAnalysis, IntelligenceConfig, IntelligenceService,
-    PageContext, ViewportInfo,
+    ActionObservation, ActionRecommendation, AnomalyDetector, AnomalyEvent, IntelligenceAnalysis,
+    IntelligenceConfig, IntelligenceService, PageContext, ViewportInfo,
 };
 use crate::perception::{LayeredPerception, PerceptionMode};
 
@@ -51,15 +55,16 @@ pub enum WorkflowApiError {
 }
 
 /// Enhanced response wrapper for workflow operations
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(WorkflowEnvelope = WorkflowResponse<serde_json::Value>)]
 pub struct WorkflowResponse<T> {
     pub success: bool,
     pub data: Option<T>,
-    pub error: Option<String>,
+    pub error: Option<ApiError>,
     pub metadata: WorkflowResponseMetadata,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WorkflowResponseMetadata {
     pub total_processing_time_ms: u64,
     pub modules_used: Vec<String>,
@@ -81,11 +86,15 @@ impl<T> WorkflowResponse<T> {
         }
     }
 
-    pub fn error(error: String, metadata: WorkflowResponseMetadata) -> Self {
+    pub fn error(
+        code: ErrorCode,
+        message: impl Into<String>,
+        metadata: WorkflowResponseMetadata,
+    ) -> Self {
         Self {
             success: false,
             data: None,
-            error: Some(error),
+            error: Some(ApiError::new(code, message)),
             metadata,
         }
     }
@@ -117,6 +126,7 @@ pub async fn execute_intelligent_workflow(
         return (
             StatusCode::BAD_REQUEST,
             Json(WorkflowResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 validation_error.to_string(),
                 metadata,
             )),
@@ -142,6 +152,7 @@ pub async fn execute_intelligent_workflow(
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(WorkflowResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     format!("Browser acquisition failed: {}", e),
                     metadata,
                 )),
@@ -173,6 +184,7 @@ pub async fn execute_intelligent_workflow(
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(WorkflowResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::BrowserError),
                     format!("Navigation failed: {}", e),
                     metadata,
                 )),
@@ -183,6 +195,7 @@ pub async fn execute_intelligent_workflow(
 
     // PHASE 1: Perception Analysis
     info!("Phase 1: Perception Analysis");
+    report_progress("perception", serde_json::json!({ "phase": 1 })).await;
     let perception_start = Instant::now();
 
     let mut layered_perception = LayeredPerception::new(browser.browser_arc());
@@ -196,6 +209,7 @@ pub async fn execute_intelligent_workflow(
         "standard" => PerceptionMode::Standard,
         "deep" => PerceptionMode::Deep,
         "adaptive" => PerceptionMode::Adaptive,
+        "accessibility" => PerceptionMode::Accessibility,
         _ => PerceptionMode::Standard,
     };
 
@@ -224,6 +238,7 @@ pub async fn execute_intelligent_workflow(
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(WorkflowResponse::<()>::error(
+                    ErrorCode::classify(&e, ErrorCode::PerceptionFailed),
                     format!("Perception failed: {}", e),
                     metadata,
                 )),
@@ -234,13 +249,19 @@ pub async fn execute_intelligent_workflow(
 
     // PHASE 2: Intelligence Analysis
     info!("Phase 2: Intelligence Analysis");
+    report_progress(
+        "intelligence",
+        serde_json::json!({ "phase": 2, "perception_time_ms": perception_time }),
+    )
+    .await;
     let intelligence_start = Instant::now();
 
     let intelligence_config = req
         .intelligence_config
         .clone()
         .unwrap_or_default();
-    let intelligence_service = IntelligenceService::new(intelligence_config);
+    let intelligence_service =
+        IntelligenceService::with_learning(intelligence_config, state.learning.current().await);
 
     // Create page context from current page
     let current_url = req.url.clone().unwrap_or_else(|| "about:blank".to_string());
@@ -293,6 +314,7 @@ pub async fn execute_intelligent_workflow(
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(WorkflowResponse::<()>::error(
+                    ErrorCode::Internal,
                     format!("Intelligence analysis failed: {}", e),
                     metadata,
                 )),
@@ -303,6 +325,11 @@ pub async fn execute_intelligent_workflow(
 
     // PHASE 3: LLM Task Planning
     info!("Phase 3: LLM Task Planning");
+    report_progress(
+        "planning",
+        serde_json::json!({ "phase": 3, "intelligence_time_ms": intelligence_time }),
+    )
+    .await;
     let llm_start = Instant::now();
 
     // Create enhanced prompt combining perception and intelligence insights
@@ -326,6 +353,11 @@ pub async fn execute_intelligent_workflow(
 
     // PHASE 4: Intelligent Action Recommendation
     info!("Phase 4: Action Recommendation");
+    report_progress(
+        "recommendation",
+        serde_json::json!({ "phase": 4, "steps": task_plan.steps.len() }),
+    )
+    .await;
     let action_recommendation = match intelligence_service
         .recommend_action(&intelligence_analysis)
         .await
@@ -352,6 +384,7 @@ pub async fn execute_intelligent_workflow(
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(WorkflowResponse::<()>::error(
+                    ErrorCode::Internal,
                     format!("Action recommendation failed: {}", e),
                     metadata,
                 )),
@@ -363,9 +396,20 @@ pub async fn execute_intelligent_workflow(
     // PHASE 5: Execution (Optional)
     let execution_result = if req.auto_execute.unwrap_or(true) {
         info!("Phase 5: Task Execution");
+        report_progress(
+            "execution",
+            serde_json::json!({ "phase": 5, "confidence": action_recommendation.confidence }),
+        )
+        .await;
         let execution_start = Instant::now();
 
-        let result = execute_task_plan(&browser, &task_plan, &action_recommendation).await;
+        let result = execute_task_plan(
+            &browser.browser_arc(),
+            &task_plan,
+            &action_recommendation,
+            &state.anomalies,
+        )
+        .await;
         execution_time = Some(execution_start.elapsed().as_millis() as u64);
         debug!("Task execution completed in {}ms", execution_time.unwrap());
 
@@ -404,6 +448,8 @@ pub async fn execute_intelligent_workflow(
                 total_actions: task_plan.steps.len(),
                 execution_time_ms: execution_time.unwrap_or(0),
                 errors: vec![e.to_string()],
+                anomalies: vec![],
+                halted_for_review: false,
             },
         }),
         modules_coordination: ModulesCoordination {
@@ -438,6 +484,22 @@ pub async fn execute_intelligent_workflow(
         success_rate,
     };
 
+    let (success, steps_completed) = match &workflow_result.execution_result {
+        Some(result) => (result.success, result.actions_completed),
+        None => (true, 0),
+    };
+    let _ = state
+        .events
+        .emit(Event::WorkflowCompleted {
+            workflow: "intelligent".to_string(),
+            success,
+            steps_completed,
+            total_steps: workflow_result.task_plan.steps.len(),
+            duration_ms: total_time,
+            timestamp: Instant::now(),
+        })
+        .await;
+
     info!(
         "Intelligent workflow completed in {}ms with success rate: {:.2}",
         total_time, success_rate
@@ -446,6 +508,13 @@ pub async fn execute_intelligent_workflow(
 }
 
 /// Simple workflow execution with basic module coordination
+#[utoipa::path(
+    post,
+    path = "/api/workflow/simple",
+    tag = "workflow",
+    request_body = SimpleWorkflowRequest,
+    responses((status = 200, description = "Per-step results", body = WorkflowEnvelope))
+)]
 pub async fn execute_simple_workflow(
     State(state): State<AppState>,
     Json(req): Json<SimpleWorkflowRequest>,
@@ -471,6 +540,7 @@ pub async fn execute_simple_workflow(
         return (
             StatusCode::BAD_REQUEST,
             Json(WorkflowResponse::<()>::error(
+                ErrorCode::InvalidRequest,
                 "Workflow steps cannot be empty".to_string(),
                 metadata,
             )),
@@ -496,6 +566,7 @@ pub async fn execute_simple_workflow(
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(WorkflowResponse::<()>::error(
+                    ErrorCode::BrowserUnavailable,
                     format!("Browser acquisition failed: {}", e),
                     metadata,
                 )),
@@ -556,6 +627,18 @@ pub async fn execute_simple_workflow(
         success_rate,
     };
 
+    let _ = state
+        .events
+        .emit(Event::WorkflowCompleted {
+            workflow: "simple".to_string(),
+            success,
+            steps_completed: completed_steps,
+            total_steps: req.steps.len(),
+            duration_ms: start_time.elapsed().as_millis() as u64,
+            timestamp: Instant::now(),
+        })
+        .await;
+
     info!(
         "Simple workflow completed: {} of {} steps succeeded",
         completed_steps,
@@ -565,6 +648,13 @@ pub async fn execute_simple_workflow(
 }
 
 /// Get workflow execution status and metrics
+#[utoipa::path(
+    post,
+    path = "/api/workflow/status",
+    tag = "workflow",
+    request_body = WorkflowStatusRequest,
+    responses((status = 200, description = "Workflow status", body = WorkflowEnvelope))
+)]
 pub async fn get_workflow_status(
     State(_state): State<AppState>,
     Json(req): Json<WorkflowStatusRequest>,
@@ -655,18 +745,40 @@ async fn create_enhanced_task_plan(
 }
 
 async fn execute_task_plan(
-    browser: &crate::browser::Browser,
+    browser: &std::sync::Arc<crate::browser::Browser>,
     task_plan: &TaskPlan,
     action_recommendation: &ActionRecommendation,
+    anomalies: &tokio::sync::RwLock<AnomalyDetector>,
 ) -> Result<ExecutionResult, anyhow::Error> {
     let mut completed_actions = 0;
     let mut errors = Vec::new();
+    let mut raised_anomalies = Vec::new();
+    let mut halted_for_review = false;
     let start_time = Instant::now();
 
     for (index, action) in task_plan.steps.iter().enumerate() {
         debug!("Executing action {}: {}", index + 1, action.action_type);
 
-        match execute_browser_action(browser, action).await {
+        let page_url = browser.current_url().await.unwrap_or_default();
+        let action_start = Instant::now();
+        let outcome = execute_browser_action(browser, action).await;
+        let observation = ActionObservation {
+            url: page_url,
+            action_type: action.action_type.clone(),
+            selector: match action.action_type.as_str() {
+                "click" | "type" | "wait_for_element" => action.target.clone(),
+                _ => None,
+            },
+            success: outcome.is_ok(),
+            expected_url: match action.action_type.as_str() {
+                "navigate" => action.target.clone(),
+                _ => None,
+            },
+            final_url: browser.current_url().await.ok(),
+            layout_shift: recent_layout_shift(browser, action_start.elapsed()).await,
+        };
+
+        match outcome {
             Ok(_) => {
                 completed_actions += 1;
                 debug!("Action {} completed successfully", index + 1);
@@ -683,14 +795,41 @@ async fn execute_task_plan(
                 }
             }
         }
+
+        // Anomalies mean the plan's view of the page can no longer be trusted
+        let raised = anomalies.write().await.observe(&observation);
+        if raised.iter().any(AnomalyEvent::requires_review) {
+            warn!(
+                "Stopping workflow after action {} for human review",
+                index + 1
+            );
+            halted_for_review = true;
+        } else if !raised.is_empty() {
+            let mut perception = LayeredPerception::new(browser.clone());
+            if let Err(e) = perception.perceive(PerceptionMode::Quick).await {
+                warn!("Re-perception after anomaly failed: {}", e);
+            } else {
+                debug!("Re-perceived page after action {}", index + 1);
+            }
+        }
+        raised_anomalies.extend(raised);
+        if halted_for_review {
+            break;
+        }
     }
 
     let execution_time = start_time.elapsed().as_millis() as u64;
-    let success = errors.is_empty();
+    let success = errors.is_empty() && !halted_for_review;
 
     Ok(ExecutionResult {
         success,
-        summary: if success {
+        summary: if halted_for_review {
+            format!(
+                "Halted for review after {} of {} actions",
+                completed_actions,
+                task_plan.steps.len()
+            )
+        } else if success {
             format!(
                 "All {} actions completed successfully",
                 task_plan.steps.len()
@@ -706,6 +845,8 @@ async fn execute_task_plan(
         total_actions: task_plan.steps.len(),
         execution_time_ms: execution_time,
         errors,
+        anomalies: raised_anomalies,
+        halted_for_review,
     })
 }
 
@@ -744,6 +885,33 @@ async fn execute_browser_action(
                     .await?;
             }
         }
+        "set_geolocation" => {
+            // value: "latitude,longitude[,accuracy]"
+            if let Some(ref value) = action.value {
+                let parts = value
+                    .split(',')
+                    .map(|p| p.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| anyhow::anyhow!("Invalid geolocation '{}': {}", value, e))?;
+                match parts.as_slice() {
+                    [lat, lon] => browser.set_geolocation(*lat, *lon, None).await?,
+                    [lat, lon, accuracy] => {
+                        browser.set_geolocation(*lat, *lon, Some(*accuracy)).await?
+                    }
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "Geolocation must be 'latitude,longitude[,accuracy]': {}",
+                            value
+                        ))
+                    }
+                };
+            }
+        }
+        "set_timezone" => {
+            if let Some(ref value) = action.value {
+                browser.set_timezone(value.trim()).await?;
+            }
+        }
         _ => {
             debug!("Unknown action type: {}", action.action_type);
         }
@@ -784,24 +952,25 @@ fn calculate_workflow_success_rate(
 
 // Request/Response types
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct IntelligentWorkflowRequest {
     pub user_command: String,
     pub url: Option<String>,
     pub perception_mode: Option<String>, // "lightning", "quick", "standard", "deep"
+    #[schema(value_type = Option<Object>)]
     pub intelligence_config: Option<IntelligenceConfig>,
     pub auto_execute: Option<bool>,
     #[allow(dead_code)]
     pub learning_enabled: Option<bool>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct SimpleWorkflowRequest {
     pub steps: Vec<WorkflowStep>,
     pub stop_on_error: Option<bool>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct WorkflowStep {
     pub action_type: String,
     pub target: Option<String>,
@@ -810,7 +979,7 @@ pub struct WorkflowStep {
     pub timeout_ms: Option<u64>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct WorkflowStatusRequest {
     pub workflow_id: String,
 }
@@ -847,6 +1016,10 @@ pub struct ExecutionResult {
     pub total_actions: usize,
     pub execution_time_ms: u64,
     pub errors: Vec<String>,
+    /// Anomalies raised while the plan ran
+    pub anomalies: Vec<AnomalyEvent>,
+    /// Whether execution stopped early so a person can check the page
+    pub halted_for_review: bool,
 }
 
 #[derive(Clone, Serialize)]
@@ -977,6 +1150,8 @@ mod tests {
             total_actions: 5,
             execution_time_ms: 1000,
             errors: vec![],
+            anomalies: vec![],
+            halted_for_review: false,
         };
 
         let success_rate = calculate_workflow_success_rate(&Some(Ok(success_result)));
@@ -989,6 +1164,8 @@ mod tests {
             total_actions: 5,
             execution_time_ms: 1000,
             errors: vec!["Some error".to_string()],
+            anomalies: vec![],
+            halted_for_review: false,
         };
 
         let partial_rate = calculate_workflow_success_rate(&Some(Ok(partial_result)));
@@ -0,0 +1,461 @@
+// Interactive WebSocket channel
+// Clients send JSON commands over `/ws` and receive their results, along with the
+// live activity of a subscribed session: browser events, perceptions and the
+// thinking steps of LLM commands. Coordination events can be streamed as well,
+// filtered by type, session and source engine.
+
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{Json, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+
+use super::auth::{current_principal, with_principal};
+use super::events::{event_json, wanted};
+use super::llm_handlers::{self, ExecuteCommandRequest};
+use super::operations::response_json;
+use super::perception_handlers::{self, PerceptionModeRequest};
+use super::tenants::current_tenant;
+use super::{AppState, ExecuteToolRequest, NavigateRequest};
+use crate::browser::{session, SessionActivity};
+use crate::coordination::correlation::{
+    current_correlation_id, new_correlation_id, with_correlation_id,
+};
+use crate::coordination::EventFilter;
+
+/// Messages clients send, tagged by `type`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    /// Stream a session's activity; all sessions when `session_id` is left out
+    Subscribe {
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+    Unsubscribe,
+    /// Stream the coordination events matching the filter, replacing any earlier one
+    SubscribeEvents {
+        #[serde(flatten)]
+        filter: EventFilter,
+    },
+    UnsubscribeEvents,
+    Ping {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    Navigate {
+        #[serde(default)]
+        id: Option<String>,
+        url: String,
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+    Tool {
+        #[serde(default)]
+        id: Option<String>,
+        tool_name: String,
+        #[serde(default)]
+        parameters: serde_json::Value,
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+    Perceive {
+        #[serde(default)]
+        id: Option<String>,
+        #[serde(default = "default_perception_mode")]
+        mode: String,
+        #[serde(default)]
+        url: Option<String>,
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+    /// Natural-language command, planned by the LLM and executed
+    Command {
+        #[serde(default)]
+        id: Option<String>,
+        command: String,
+        #[serde(default)]
+        url: Option<String>,
+        #[serde(default)]
+        auto_execute: Option<bool>,
+        #[serde(default)]
+        provider: Option<String>,
+        #[serde(default)]
+        max_steps: Option<usize>,
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+}
+
+fn default_perception_mode() -> String {
+    "lightning".to_string()
+}
+
+/// Messages sent to clients, tagged by `type`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Subscribed {
+        session_id: Option<String>,
+    },
+    Unsubscribed,
+    EventsSubscribed {
+        #[serde(flatten)]
+        filter: EventFilter,
+    },
+    EventsUnsubscribed,
+    Pong {
+        id: Option<String>,
+    },
+    /// Outcome of a command, with the HTTP status and body of the matching endpoint
+    /// and the correlation id it ran under, for `/api/trace/:id`
+    Result {
+        id: Option<String>,
+        command: &'static str,
+        status: u16,
+        success: bool,
+        body: serde_json::Value,
+        correlation_id: Option<String>,
+    },
+    Event(SessionActivity),
+    /// A coordination event matching the events subscription
+    CoordinationEvent(serde_json::Value),
+    Error {
+        id: Option<String>,
+        message: String,
+    },
+}
+
+/// Which activity a connection streams
+#[derive(Debug, Clone, PartialEq)]
+enum Subscription {
+    None,
+    All,
+    Session(String),
+}
+
+impl Subscription {
+    fn wants(&self, activity: &SessionActivity) -> bool {
+        match self {
+            Subscription::None => false,
+            Subscription::All => true,
+            Subscription::Session(id) => &activity.session_id == id,
+        }
+    }
+
+    /// Session commands run in when they do not name one
+    fn session(&self) -> Option<&str> {
+        match self {
+            Subscription::Session(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Accept commands and stream activity over a WebSocket until the client disconnects
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "realtime",
+    responses((status = 101, description = "Switching to the WebSocket protocol"))
+)]
+pub async fn interactive_channel(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    // Commands run on behalf of whoever opened the channel
+    let principal = current_principal();
+    ws.on_upgrade(move |socket| with_principal(principal, run_channel(socket, state)))
+}
+
+async fn run_channel(mut socket: WebSocket, state: AppState) {
+    let mut activity = state.session_manager.subscribe_activity();
+    let mut subscription = Subscription::None;
+    let mut coordination = state.events.subscribe_live();
+    let mut event_filter: Option<EventFilter> = None;
+    let tenant = current_tenant();
+    // Commands run concurrently so events keep streaming while they execute
+    let (results_tx, mut results) = mpsc::channel::<ServerMessage>(32);
+
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    handle_message(&text, &state, &mut subscription, &mut event_filter, &results_tx)
+                        .await
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => None,
+            },
+            event = activity.recv() => match event {
+                // Other tenants' activity is never streamed
+                Ok(event) if subscription.wants(&event) && session::visible(&event.tenant) => {
+                    Some(ServerMessage::Event(event))
+                }
+                Ok(_) => None,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("WebSocket channel skipped {} activity events", skipped);
+                    None
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            logged = coordination.recv() => match (logged, &event_filter) {
+                (Ok(logged), Some(filter)) if wanted(&logged, filter, &tenant) => {
+                    Some(ServerMessage::CoordinationEvent(event_json(&logged)))
+                }
+                (Err(broadcast::error::RecvError::Lagged(skipped)), Some(_)) => {
+                    warn!("WebSocket channel skipped {} coordination events", skipped);
+                    None
+                }
+                _ => None,
+            },
+            Some(result) = results.recv() => Some(result),
+        };
+
+        if let Some(reply) = reply {
+            let Ok(text) = serde_json::to_string(&reply) else {
+                continue;
+            };
+            if socket.send(Message::Text(text)).await.is_err() {
+                return;
+            }
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// Apply a subscription change or start a command; returns an immediate reply
+async fn handle_message(
+    text: &str,
+    state: &AppState,
+    subscription: &mut Subscription,
+    event_filter: &mut Option<EventFilter>,
+    results: &mpsc::Sender<ServerMessage>,
+) -> Option<ServerMessage> {
+    let message: ClientMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            return Some(ServerMessage::Error {
+                id: None,
+                message: format!("Invalid message: {}", e),
+            })
+        }
+    };
+
+    match message {
+        ClientMessage::Subscribe { session_id } => {
+            if let Some(id) = &session_id {
+                if state.session_manager.get_session(id).await.is_none() {
+                    return Some(ServerMessage::Error {
+                        id: None,
+                        message: format!("Session not found: {}", id),
+                    });
+                }
+            }
+            *subscription = match &session_id {
+                Some(id) => Subscription::Session(id.clone()),
+                None => Subscription::All,
+            };
+            Some(ServerMessage::Subscribed { session_id })
+        }
+        ClientMessage::Unsubscribe => {
+            *subscription = Subscription::None;
+            Some(ServerMessage::Unsubscribed)
+        }
+        ClientMessage::SubscribeEvents { filter } => {
+            *event_filter = Some(filter.clone());
+            Some(ServerMessage::EventsSubscribed { filter })
+        }
+        ClientMessage::UnsubscribeEvents => {
+            *event_filter = None;
+            Some(ServerMessage::EventsUnsubscribed)
+        }
+        ClientMessage::Ping { id } => Some(ServerMessage::Pong { id }),
+        command => {
+            let state = state.clone();
+            let results = results.clone();
+            let default_session = subscription.session().map(str::to_string);
+            let principal = current_principal();
+            // Each command is traced on its own
+            let correlation_id = Some(new_correlation_id());
+            tokio::spawn(async move {
+                let result = with_correlation_id(
+                    correlation_id,
+                    with_principal(principal, run_command(command, state, default_session)),
+                )
+                .await;
+                // The client may have gone away meanwhile
+                let _ = results.send(result).await;
+            });
+            None
+        }
+    }
+}
+
+/// Run a command through the endpoint it mirrors
+async fn run_command(
+    command: ClientMessage,
+    state: AppState,
+    default_session: Option<String>,
+) -> ServerMessage {
+    let (id, name, response) = match command {
+        ClientMessage::Navigate {
+            id,
+            url,
+            session_id,
+        } => {
+            let req = NavigateRequest {
+                url,
+                session_id: session_id.or(default_session),
+                device: None,
+            };
+            let response = super::navigate(State(state), HeaderMap::new(), Json(req)).await;
+            (id, "navigate", response)
+        }
+        ClientMessage::Tool {
+            id,
+            tool_name,
+            parameters,
+            session_id,
+        } => {
+            let req = ExecuteToolRequest {
+                tool_name,
+                parameters,
+                session_id: session_id.or(default_session),
+            };
+            (
+                id,
+                "tool",
+                super::execute_tool(State(state), Json(req)).await,
+            )
+        }
+        ClientMessage::Perceive {
+            id,
+            mode,
+            url,
+            session_id,
+        } => {
+            let req = PerceptionModeRequest {
+                mode,
+                session_id: session_id.or(default_session),
+                url,
+                load_lazy_content: false,
+            };
+            let response =
+                perception_handlers::perceive_with_mode(State(state), HeaderMap::new(), Json(req))
+                    .await
+                    .into_response();
+            (id, "perceive", response)
+        }
+        ClientMessage::Command {
+            id,
+            command,
+            url,
+            auto_execute,
+            provider,
+            max_steps,
+            session_id,
+        } => {
+            let req = ExecuteCommandRequest {
+                command,
+                url,
+                auto_execute,
+                complexity: None,
+                provider,
+                max_steps,
+                session_id: session_id.or(default_session),
+            };
+            let response = llm_handlers::execute_command(State(state), Json(req))
+                .await
+                .into_response();
+            (id, "command", response)
+        }
+        ClientMessage::Subscribe { .. }
+        | ClientMessage::Unsubscribe
+        | ClientMessage::SubscribeEvents { .. }
+        | ClientMessage::UnsubscribeEvents
+        | ClientMessage::Ping { .. } => {
+            unreachable!("handled without running a command")
+        }
+    };
+
+    let status = response.status();
+    let body = match response_json(response).await {
+        Ok(body) => body,
+        Err(e) => {
+            return ServerMessage::Error {
+                id,
+                message: format!("Failed to read {} result: {}", name, e),
+            }
+        }
+    };
+    debug!("WebSocket {} command finished with {}", name, status);
+    ServerMessage::Result {
+        id,
+        command: name,
+        status: status.as_u16(),
+        success: status.is_success(),
+        body,
+        correlation_id: current_correlation_id(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser::ActivityKind;
+    use crate::coordination::EventSource;
+    use serde_json::json;
+
+    #[test]
+    fn parses_client_messages() {
+        let message: ClientMessage = serde_json::from_value(json!({
+            "type": "tool",
+            "id": "7",
+            "tool_name": "click",
+            "parameters": {"selector": "#buy"}
+        }))
+        .unwrap();
+        assert!(matches!(
+            message,
+            ClientMessage::Tool { id: Some(ref id), ref tool_name, session_id: None, .. }
+                if id == "7" && tool_name == "click"
+        ));
+
+        let message: ClientMessage = serde_json::from_value(json!({"type": "perceive"})).unwrap();
+        assert!(matches!(message, ClientMessage::Perceive { ref mode, .. } if mode == "lightning"));
+        assert!(serde_json::from_value::<ClientMessage>(json!({"type": "navigate"})).is_err());
+
+        let message: ClientMessage = serde_json::from_value(json!({
+            "type": "subscribe_events",
+            "event_types": ["tool_execution_failed"],
+            "sources": ["tools"]
+        }))
+        .unwrap();
+        assert!(matches!(
+            message,
+            ClientMessage::SubscribeEvents { ref filter }
+                if filter.sources == [EventSource::Tools] && filter.session_id.is_none()
+        ));
+        assert!(serde_json::from_value::<ClientMessage>(
+            json!({"type": "subscribe_events", "sources": ["kernel"]})
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn subscriptions_filter_activity() {
+        let activity = SessionActivity::new("s1", ActivityKind::Thinking, json!({"stage": "plan"}));
+        assert!(!Subscription::None.wants(&activity));
+        assert!(Subscription::All.wants(&activity));
+        assert!(Subscription::Session("s1".to_string()).wants(&activity));
+        assert!(!Subscription::Session("s2".to_string()).wants(&activity));
+        assert_eq!(Subscription::All.session(), None);
+
+        let event = serde_json::to_value(ServerMessage::Event(activity)).unwrap();
+        assert_eq!(event["type"], json!("event"));
+        assert_eq!(event["kind"], json!("thinking"));
+        assert_eq!(event["session_id"], json!("s1"));
+    }
+}
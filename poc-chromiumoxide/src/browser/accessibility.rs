@@ -0,0 +1,440 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::accessibility::GetFullAxTreeParams;
+use chromiumoxide::cdp::browser_protocol::dom::{BackendNodeId, ResolveNodeParams};
+use chromiumoxide::cdp::js_protocol::runtime::{
+    CallArgument, CallFunctionOnParams, ReleaseObjectGroupParams,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+/// Most accessibility nodes kept from one tree
+const MAX_AX_ELEMENTS: usize = 1000;
+
+/// Most interactive nodes given a CSS selector, one DOM.resolveNode call each
+const MAX_RESOLVED_SELECTORS: usize = 150;
+
+const AX_OBJECT_GROUP: &str = "rainbow-accessibility";
+
+/// Roles a user can act on
+const INTERACTIVE_ROLES: &[&str] = &[
+    "button",
+    "link",
+    "textbox",
+    "searchbox",
+    "combobox",
+    "listbox",
+    "checkbox",
+    "radio",
+    "switch",
+    "slider",
+    "spinbutton",
+    "tab",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "option",
+    "treeitem",
+];
+
+/// Roles marking the regions of a page
+const LANDMARK_ROLES: &[&str] = &[
+    "banner",
+    "navigation",
+    "main",
+    "contentinfo",
+    "complementary",
+    "search",
+    "form",
+    "region",
+    "dialog",
+    "alertdialog",
+];
+
+/// Layout and text roles that carry nothing beyond their parent's name
+const STRUCTURAL_ROLES: &[&str] = &[
+    "generic",
+    "none",
+    "presentation",
+    "StaticText",
+    "InlineTextBox",
+    "LineBreak",
+    "RootWebArea",
+    "WebArea",
+];
+
+/// Properties reported as element states when set
+const STATE_PROPERTIES: &[&str] = &[
+    "focused",
+    "disabled",
+    "checked",
+    "pressed",
+    "selected",
+    "expanded",
+    "required",
+    "readonly",
+    "invalid",
+    "modal",
+    "multiselectable",
+    "busy",
+];
+
+/// Builds a selector for each element argument: id, test id, name or aria-label
+/// when they identify it, else its nth-of-type path
+const SELECTOR_FUNCTION: &str = r#"function() {
+    const unique = (selector, el) => {
+        try { return document.querySelectorAll(selector).length === 1 && document.querySelector(selector) === el; }
+        catch (e) { return false; }
+    };
+    const selectorOf = el => {
+        if (!el || el.nodeType !== 1) return null;
+        const tag = el.tagName.toLowerCase();
+        const candidates = [];
+        if (el.id) candidates.push('#' + CSS.escape(el.id));
+        for (const attr of ['data-testid', 'data-test', 'name', 'aria-label']) {
+            const value = el.getAttribute(attr);
+            if (value) candidates.push(tag + '[' + attr + '="' + CSS.escape(value) + '"]');
+        }
+        const found = candidates.find(selector => unique(selector, el));
+        if (found) return found;
+        const parts = [];
+        for (let node = el; node && node.nodeType === 1 && node !== document.documentElement; node = node.parentElement) {
+            let part = node.tagName.toLowerCase();
+            const siblings = node.parentElement
+                ? Array.from(node.parentElement.children).filter(s => s.tagName === node.tagName)
+                : [];
+            if (siblings.length > 1) part += ':nth-of-type(' + (siblings.indexOf(node) + 1) + ')';
+            parts.unshift(part);
+        }
+        return parts.join(' > ');
+    };
+    return Array.from(arguments).map(selectorOf);
+}"#;
+
+/// Element of the page as assistive technology sees it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AxElement {
+    pub role: String,
+    /// Accessible name, computed from labels, aria-label, alt text or content
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Set states such as "focused", "disabled", "checked" or "collapsed"
+    pub states: Vec<String>,
+    /// Heading level
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<u64>,
+    /// CSS selector of the DOM element, resolved for interactive elements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    #[serde(skip)]
+    backend_node_id: Option<i64>,
+}
+
+impl AxElement {
+    /// Element for a CDP `AXNode` in its JSON form, or None for ignored and purely
+    /// structural nodes
+    pub fn from_node(node: &Value) -> Option<Self> {
+        if node["ignored"].as_bool().unwrap_or(false) {
+            return None;
+        }
+        let role = ax_text(&node["role"])?;
+        let name = ax_text(&node["name"]).unwrap_or_default();
+
+        let mut states = Vec::new();
+        let mut level = None;
+        for property in node["properties"].as_array().into_iter().flatten() {
+            let Some(property_name) = property["name"].as_str() else {
+                continue;
+            };
+            let value = &property["value"]["value"];
+            if property_name == "level" {
+                level = value.as_u64();
+                continue;
+            }
+            if !STATE_PROPERTIES.contains(&property_name) {
+                continue;
+            }
+            match (property_name, value) {
+                ("expanded", Value::Bool(false)) => states.push("collapsed".to_string()),
+                (_, Value::Bool(true)) => states.push(property_name.to_string()),
+                (_, Value::String(s)) if s == "true" => states.push(property_name.to_string()),
+                (_, Value::String(s)) if s == "mixed" => states.push("mixed".to_string()),
+                _ => {}
+            }
+        }
+
+        let element = Self {
+            description: ax_text(&node["description"]).filter(|d| !d.is_empty()),
+            value: ax_text(&node["value"]).filter(|v| !v.is_empty()),
+            backend_node_id: node["backendDOMNodeId"].as_i64(),
+            role,
+            name,
+            states,
+            level,
+            selector: None,
+        };
+        let keep = element.is_interactive()
+            || element.is_landmark()
+            || matches!(
+                element.role.as_str(),
+                "heading" | "img" | "alert" | "status"
+            )
+            || (!element.name.is_empty() && !STRUCTURAL_ROLES.contains(&element.role.as_str()));
+        keep.then_some(element)
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        INTERACTIVE_ROLES.contains(&self.role.as_str())
+    }
+
+    pub fn is_landmark(&self) -> bool {
+        LANDMARK_ROLES.contains(&self.role.as_str())
+    }
+
+    pub fn has_state(&self, state: &str) -> bool {
+        self.states.iter().any(|s| s == state)
+    }
+
+    /// How well the element fits a description such as "the sign in button",
+    /// from 0.0 to 1.0, using the accessible name and role only
+    pub fn match_score(&self, description: &str) -> f32 {
+        let description = description.to_lowercase();
+        let role_hinted = roles_mentioned(&description).contains(&self.role.as_str());
+        let words = content_words(&description);
+        if words.is_empty() {
+            return if role_hinted { 0.3 } else { 0.0 };
+        }
+
+        let name = self.name.to_lowercase();
+        let hits = words.iter().filter(|w| name.contains(*w)).count();
+        if hits == 0 {
+            return 0.0;
+        }
+        let mut score = if content_words(&name) == words {
+            0.9
+        } else {
+            0.8 * hits as f32 / words.len() as f32
+        };
+        if role_hinted {
+            score += 0.1;
+        }
+        if self.has_state("disabled") {
+            score *= 0.5;
+        }
+        score.min(1.0)
+    }
+}
+
+/// Words of a description that name the action or role rather than the element
+const FILLER_WORDS: &[&str] = &[
+    "the", "a", "an", "on", "to", "of", "in", "for", "click", "press", "tap", "open", "select",
+    "button", "link", "field", "input", "box", "textbox", "tab", "menu", "checkbox", "dropdown",
+];
+
+/// Lowercase words of a text without filler words
+fn content_words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && !FILLER_WORDS.contains(w))
+        .collect()
+}
+
+/// Roles a description refers to, e.g. "button" or "field"
+fn roles_mentioned(description: &str) -> Vec<&'static str> {
+    let mut roles = Vec::new();
+    let mentions = |word: &str| description.contains(word);
+    if mentions("button") || mentions("click") || mentions("press") {
+        roles.extend(["button", "menuitem"]);
+    }
+    if mentions("link") || mentions("go to") {
+        roles.push("link");
+    }
+    if mentions("field") || mentions("input") || mentions("box") || mentions("search") {
+        roles.extend(["textbox", "searchbox", "combobox"]);
+    }
+    if mentions("checkbox") || mentions("toggle") {
+        roles.extend(["checkbox", "switch"]);
+    }
+    if mentions("dropdown") || mentions("select") {
+        roles.extend(["combobox", "listbox"]);
+    }
+    if mentions("tab") {
+        roles.push("tab");
+    }
+    if mentions("menu") {
+        roles.push("menuitem");
+    }
+    if mentions("option") {
+        roles.push("option");
+    }
+    roles
+}
+
+/// Text of an `AXValue`, which may hold a string, number or boolean
+fn ax_text(value: &Value) -> Option<String> {
+    match &value["value"] {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+impl Browser {
+    /// Elements of the active page from the CDP accessibility tree, in document order.
+    /// With `resolve_selectors`, interactive elements also get a CSS selector.
+    pub async fn accessibility_tree(&self, resolve_selectors: bool) -> Result<Vec<AxElement>> {
+        let page = self.page.read().await;
+        let response = page.execute(GetFullAxTreeParams::default()).await?;
+
+        let mut elements: Vec<AxElement> = response
+            .result
+            .nodes
+            .iter()
+            .filter_map(|node| serde_json::to_value(node).ok())
+            .filter_map(|node| AxElement::from_node(&node))
+            .take(MAX_AX_ELEMENTS)
+            .collect();
+        debug!(
+            "Accessibility tree: {} of {} nodes kept",
+            elements.len(),
+            response.result.nodes.len()
+        );
+
+        if resolve_selectors {
+            if let Err(e) = resolve_ax_selectors(&page, &mut elements).await {
+                debug!("Could not resolve accessibility selectors: {}", e);
+            }
+            page.execute(ReleaseObjectGroupParams::new(AX_OBJECT_GROUP))
+                .await
+                .ok();
+        }
+        Ok(elements)
+    }
+}
+
+/// Fill in the selectors of interactive elements with one DOM.resolveNode per
+/// element and a single Runtime.callFunctionOn over all of them
+async fn resolve_ax_selectors(
+    page: &chromiumoxide::Page,
+    elements: &mut [AxElement],
+) -> Result<()> {
+    let mut resolved = Vec::new();
+    for (index, element) in elements.iter().enumerate() {
+        if resolved.len() >= MAX_RESOLVED_SELECTORS {
+            break;
+        }
+        let Some(backend_id) = element.backend_node_id.filter(|_| element.is_interactive()) else {
+            continue;
+        };
+        let params = ResolveNodeParams::builder()
+            .backend_node_id(BackendNodeId::new(backend_id))
+            .object_group(AX_OBJECT_GROUP)
+            .build();
+        if let Ok(response) = page.execute(params).await {
+            if let Some(object_id) = response.result.object.object_id.clone() {
+                resolved.push((index, object_id));
+            }
+        }
+    }
+    let Some((_, this)) = resolved.first() else {
+        return Ok(());
+    };
+
+    let params = CallFunctionOnParams::builder()
+        .function_declaration(SELECTOR_FUNCTION)
+        .object_id(this.clone())
+        .arguments(
+            resolved
+                .iter()
+                .map(|(_, id)| CallArgument::builder().object_id(id.clone()).build())
+                .collect::<Vec<_>>(),
+        )
+        .return_by_value(true)
+        .build()
+        .map_err(|e| anyhow!("Invalid selector params: {}", e))?;
+    let response = page.execute(params).await?;
+    let selectors = response.result.result.value.clone().unwrap_or_default();
+
+    for ((index, _), selector) in resolved
+        .iter()
+        .zip(selectors.as_array().into_iter().flatten())
+    {
+        elements[*index].selector = selector.as_str().map(str::to_string);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn node(role: &str, name: &str, properties: Value) -> Value {
+        json!({
+            "nodeId": "1",
+            "ignored": false,
+            "role": {"type": "role", "value": role},
+            "name": {"type": "computedString", "value": name},
+            "properties": properties,
+            "backendDOMNodeId": 42
+        })
+    }
+
+    #[test]
+    fn reads_role_name_and_states() {
+        let element = AxElement::from_node(&node(
+            "button",
+            "Show filters",
+            json!([
+                {"name": "focusable", "value": {"type": "booleanOrUndefined", "value": true}},
+                {"name": "expanded", "value": {"type": "booleanOrUndefined", "value": false}},
+                {"name": "disabled", "value": {"type": "boolean", "value": true}}
+            ]),
+        ))
+        .unwrap();
+        assert_eq!(element.role, "button");
+        assert_eq!(element.name, "Show filters");
+        assert_eq!(element.states, vec!["collapsed", "disabled"]);
+        assert_eq!(element.backend_node_id, Some(42));
+
+        let checkbox = AxElement::from_node(&node(
+            "checkbox",
+            "Select all",
+            json!([{"name": "checked", "value": {"type": "tristate", "value": "mixed"}}]),
+        ))
+        .unwrap();
+        assert!(checkbox.has_state("mixed"));
+    }
+
+    #[test]
+    fn skips_ignored_and_structural_nodes() {
+        let mut ignored = node("button", "Hidden", json!([]));
+        ignored["ignored"] = json!(true);
+        assert!(AxElement::from_node(&ignored).is_none());
+        assert!(AxElement::from_node(&node("generic", "", json!([]))).is_none());
+        assert!(AxElement::from_node(&node("StaticText", "Hello", json!([]))).is_none());
+
+        let heading = AxElement::from_node(&node(
+            "heading",
+            "Results",
+            json!([{"name": "level", "value": {"type": "integer", "value": 2}}]),
+        ))
+        .unwrap();
+        assert_eq!(heading.level, Some(2));
+    }
+
+    #[test]
+    fn matches_descriptions_by_name_and_role() {
+        let sign_in = AxElement::from_node(&node("button", "Sign in", json!([]))).unwrap();
+        let help = AxElement::from_node(&node("link", "Sign in help", json!([]))).unwrap();
+
+        let button_score = sign_in.match_score("click the sign in button");
+        assert!(button_score > help.match_score("click the sign in button"));
+        assert_eq!(button_score, 1.0);
+        assert_eq!(sign_in.match_score("newsletter"), 0.0);
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Kind of action performed in a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Navigation,
+    Click,
+    Type,
+    ToolExecution,
+    Perception,
+    /// A planning step of an LLM command: the instruction, the plan, the outcome
+    Thinking,
+}
+
+impl ActivityKind {
+    /// Activity reported for running the tool `name`
+    pub fn for_tool(name: &str) -> Self {
+        match name {
+            "navigate_to_url" | "go_back" | "go_forward" | "refresh_page" => {
+                ActivityKind::Navigation
+            }
+            "click" => ActivityKind::Click,
+            "type_text" => ActivityKind::Type,
+            _ => ActivityKind::ToolExecution,
+        }
+    }
+}
+
+/// Something that happened in a session, as streamed to `/api/session/:id/events`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionActivity {
+    pub session_id: String,
+    pub kind: ActivityKind,
+    pub timestamp: DateTime<Utc>,
+    /// Kind-specific details such as the URL, tool name and parameters, or duration
+    pub detail: Value,
+    /// Tenant the action was performed for
+    #[serde(skip)]
+    pub tenant: Option<String>,
+    /// Correlation id of the request the action was performed for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+impl SessionActivity {
+    pub fn new(session_id: &str, kind: ActivityKind, detail: Value) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            kind,
+            timestamp: Utc::now(),
+            detail,
+            tenant: None,
+            correlation_id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_tools() {
+        assert_eq!(
+            ActivityKind::for_tool("navigate_to_url"),
+            ActivityKind::Navigation
+        );
+        assert_eq!(ActivityKind::for_tool("click"), ActivityKind::Click);
+        assert_eq!(ActivityKind::for_tool("type_text"), ActivityKind::Type);
+        assert_eq!(
+            ActivityKind::for_tool("extract_links"),
+            ActivityKind::ToolExecution
+        );
+    }
+
+    #[test]
+    fn serializes_kind_in_snake_case() {
+        let activity = SessionActivity::new(
+            "s1",
+            ActivityKind::ToolExecution,
+            json!({"tool": "extract_text"}),
+        );
+        let value = serde_json::to_value(&activity).unwrap();
+        assert_eq!(value["kind"], json!("tool_execution"));
+        assert_eq!(value["session_id"], json!("s1"));
+    }
+}
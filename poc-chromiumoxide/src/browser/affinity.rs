@@ -0,0 +1,165 @@
+use super::core::Browser;
+use super::pool::{BrowserGuard, BrowserPool};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Client used when a request carries no client token. Its browser also backs the
+/// shared tool registry, so it is never expired or evicted.
+pub const DEFAULT_CLIENT: &str = "default";
+
+/// Whether `client` is the default client of the default partition or of a tenant
+/// (`<tenant>/default`), whose binding backs that partition's tool registry
+pub fn is_default_client(client: &str) -> bool {
+    client.rsplit('/').next() == Some(DEFAULT_CLIENT)
+}
+
+/// HTTP header carrying the client token
+pub const CLIENT_TOKEN_HEADER: &str = "x-client-token";
+
+struct Binding {
+    browser: Arc<Browser>,
+    /// Held while the browser came from the pool; None for browsers bound from elsewhere
+    guard: Option<BrowserGuard>,
+    last_used: Instant,
+}
+
+/// Sticky client-to-browser assignment for calls made without a session.
+///
+/// Each client token keeps the same browser between requests, so page state set up
+/// by one call (navigate) is still there for the next (click, type).
+pub struct BrowserAffinity {
+    pool: Arc<BrowserPool>,
+    bindings: RwLock<HashMap<String, Binding>>,
+    idle_timeout: Duration,
+}
+
+impl BrowserAffinity {
+    pub fn new(pool: Arc<BrowserPool>, idle_timeout: Duration) -> Self {
+        Self {
+            pool,
+            bindings: RwLock::new(HashMap::new()),
+            idle_timeout,
+        }
+    }
+
+    /// Browser bound to `client`, acquiring one from the pool on first use
+    pub async fn browser(&self, client: &str) -> Result<Arc<Browser>> {
+        if let Some(browser) = self.get(client).await {
+            if browser.is_connected().await {
+                return Ok(browser);
+            }
+            warn!("Browser bound to client {} disconnected, rebinding", client);
+            self.unbind(client).await;
+        }
+
+        // When every browser is held by other clients, free the least recently used one
+        if self.pool.available() == 0 && !self.evict_least_recent(client).await {
+            return Err(anyhow!("No browser available for client {}", client));
+        }
+        let guard = self.pool.acquire().await?;
+
+        let mut bindings = self.bindings.write().await;
+        // A concurrent request for the same client may have bound a browser meanwhile
+        if let Some(existing) = bindings.get_mut(client) {
+            existing.last_used = Instant::now();
+            return Ok(existing.browser.clone());
+        }
+        let browser = guard.browser_arc();
+        bindings.insert(
+            client.to_string(),
+            Binding {
+                browser: browser.clone(),
+                guard: Some(guard),
+                last_used: Instant::now(),
+            },
+        );
+        info!("Bound client {} to a pooled browser", client);
+        Ok(browser)
+    }
+
+    /// Browser currently bound to `client`, if any
+    pub async fn get(&self, client: &str) -> Option<Arc<Browser>> {
+        let mut bindings = self.bindings.write().await;
+        let binding = bindings.get_mut(client)?;
+        binding.last_used = Instant::now();
+        Some(binding.browser.clone())
+    }
+
+    /// Bind `client` to a browser obtained elsewhere, e.g. a session's browser
+    pub async fn bind(&self, client: &str, browser: Arc<Browser>) {
+        let mut bindings = self.bindings.write().await;
+        if let Some(existing) = bindings.get_mut(client) {
+            if Arc::ptr_eq(&existing.browser, &browser) {
+                existing.last_used = Instant::now();
+                return;
+            }
+        }
+        bindings.insert(
+            client.to_string(),
+            Binding {
+                browser,
+                guard: None,
+                last_used: Instant::now(),
+            },
+        );
+    }
+
+    /// Release a client's browser back to the pool
+    pub async fn unbind(&self, client: &str) -> bool {
+        self.bindings.write().await.remove(client).is_some()
+    }
+
+    /// Release bindings idle for longer than the idle timeout
+    pub async fn expire_idle(&self) -> usize {
+        let mut bindings = self.bindings.write().await;
+        let before = bindings.len();
+        bindings.retain(|client, b| {
+            is_default_client(client) || b.last_used.elapsed() < self.idle_timeout
+        });
+        let expired = before - bindings.len();
+        if expired > 0 {
+            info!("Released {} idle client browser bindings", expired);
+        }
+        expired
+    }
+
+    /// Run `expire_idle` every `interval` until the returned task is aborted
+    pub fn start_expiry(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.expire_idle().await;
+            }
+        })
+    }
+
+    /// Number of clients with a bound browser
+    pub async fn client_count(&self) -> usize {
+        self.bindings.read().await.len()
+    }
+
+    async fn evict_least_recent(&self, except: &str) -> bool {
+        let mut bindings = self.bindings.write().await;
+        let victim = bindings
+            .iter()
+            .filter(|(client, b)| {
+                client.as_str() != except && !is_default_client(client) && b.guard.is_some()
+            })
+            .min_by_key(|(_, b)| b.last_used)
+            .map(|(client, _)| client.clone());
+        match victim {
+            Some(client) => {
+                info!("Releasing browser of least recently used client {}", client);
+                bindings.remove(&client);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use super::core::Browser;
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::js_protocol::runtime::{
+    EventConsoleApiCalled, EventExceptionThrown, RemoteObject, StackTrace,
+};
+use chromiumoxide::Page;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+const CONSOLE_BUFFER_SIZE: usize = 500;
+
+/// Where a console entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleSource {
+    /// A `console.*` call
+    Console,
+    /// An uncaught exception or unhandled rejection
+    Exception,
+}
+
+/// One line of page console output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleEntry {
+    /// "log", "info", "debug", "warning" or "error"
+    pub level: String,
+    pub source: ConsoleSource,
+    pub text: String,
+    pub url: Option<String>,
+    pub line: Option<i64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ConsoleEntry {
+    pub fn is_error(&self) -> bool {
+        self.level == "error"
+    }
+}
+
+/// Ring buffer of console output for all pages of a browser
+#[derive(Debug)]
+pub struct ConsoleLog {
+    entries: RwLock<VecDeque<ConsoleEntry>>,
+}
+
+impl ConsoleLog {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::with_capacity(CONSOLE_BUFFER_SIZE)),
+        }
+    }
+
+    /// Buffered entries, oldest first, optionally filtered by level and limited to the newest `limit`
+    pub async fn entries(&self, level: Option<&str>, limit: Option<usize>) -> Vec<ConsoleEntry> {
+        let entries = self.entries.read().await;
+        let mut matching: Vec<_> = entries
+            .iter()
+            .filter(|e| level.is_none_or(|l| e.level == l))
+            .cloned()
+            .collect();
+        if let Some(limit) = limit {
+            let skip = matching.len().saturating_sub(limit);
+            matching.drain(..skip);
+        }
+        matching
+    }
+
+    /// Buffered JavaScript errors, oldest first
+    pub async fn errors(&self) -> Vec<ConsoleEntry> {
+        self.entries(Some("error"), None).await
+    }
+
+    pub async fn clear(&self) {
+        self.entries.write().await.clear();
+    }
+
+    async fn record(&self, entry: ConsoleEntry) {
+        let mut entries = self.entries.write().await;
+        if entries.len() >= CONSOLE_BUFFER_SIZE {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+impl Default for ConsoleLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Normalize a `console.*` call type to a log level
+fn console_level(call_type: &str) -> &str {
+    match call_type {
+        "error" | "assert" => "error",
+        "warning" => "warning",
+        "info" => "info",
+        "debug" => "debug",
+        _ => "log",
+    }
+}
+
+fn remote_object_text(object: &RemoteObject) -> String {
+    match &object.value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+        None => object
+            .description
+            .clone()
+            .unwrap_or_else(|| object.r#type.as_ref().to_string()),
+    }
+}
+
+fn stack_location(stack: Option<&StackTrace>) -> (Option<String>, Option<i64>) {
+    stack
+        .and_then(|s| s.call_frames.first())
+        .map(|frame| (Some(frame.url.clone()), Some(frame.line_number)))
+        .unwrap_or((None, None))
+}
+
+impl Browser {
+    /// Console output and uncaught errors captured from this browser's pages
+    pub fn console(&self) -> Arc<ConsoleLog> {
+        self.console.clone()
+    }
+
+    /// Record console calls and uncaught exceptions of `page`
+    pub(crate) async fn attach_console_listener(&self, page: &Page) -> Result<()> {
+        let mut calls = page
+            .event_listener::<EventConsoleApiCalled>()
+            .await
+            .context("Failed to subscribe to console events")?;
+        let mut exceptions = page
+            .event_listener::<EventExceptionThrown>()
+            .await
+            .context("Failed to subscribe to exception events")?;
+
+        let log = self.console.clone();
+        tokio::spawn(async move {
+            while let Some(event) = calls.next().await {
+                let text = event
+                    .args
+                    .iter()
+                    .map(remote_object_text)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let (url, line) = stack_location(event.stack_trace.as_ref());
+                log.record(ConsoleEntry {
+                    level: console_level(event.r#type.as_ref()).to_string(),
+                    source: ConsoleSource::Console,
+                    text,
+                    url,
+                    line,
+                    timestamp: Utc::now(),
+                })
+                .await;
+            }
+        });
+
+        let log = self.console.clone();
+        tokio::spawn(async move {
+            while let Some(event) = exceptions.next().await {
+                let details = &event.exception_details;
+                let text = details
+                    .exception
+                    .as_ref()
+                    .and_then(|e| e.description.clone())
+                    .unwrap_or_else(|| details.text.clone());
+                debug!("Uncaught page exception: {}", text);
+                log.record(ConsoleEntry {
+                    level: "error".to_string(),
+                    source: ConsoleSource::Exception,
+                    text,
+                    url: details.url.clone(),
+                    line: Some(details.line_number),
+                    timestamp: Utc::now(),
+                })
+                .await;
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: &str, text: &str) -> ConsoleEntry {
+        ConsoleEntry {
+            level: level.to_string(),
+            source: ConsoleSource::Console,
+            text: text.to_string(),
+            url: None,
+            line: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn maps_console_types_to_levels() {
+        assert_eq!(console_level("assert"), "error");
+        assert_eq!(console_level("warning"), "warning");
+        assert_eq!(console_level("table"), "log");
+    }
+
+    #[tokio::test]
+    async fn buffer_drops_oldest_entries() {
+        let log = ConsoleLog::new();
+        for i in 0..CONSOLE_BUFFER_SIZE + 5 {
+            log.record(entry("log", &i.to_string())).await;
+        }
+        let entries = log.entries(None, None).await;
+        assert_eq!(entries.len(), CONSOLE_BUFFER_SIZE);
+        assert_eq!(entries[0].text, "5");
+    }
+
+    #[tokio::test]
+    async fn filters_by_level_and_limit() {
+        let log = ConsoleLog::new();
+        log.record(entry("error", "first")).await;
+        log.record(entry("log", "hello")).await;
+        log.record(entry("error", "second")).await;
+
+        assert_eq!(log.errors().await.len(), 2);
+        let latest = log.entries(Some("error"), Some(1)).await;
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].text, "second");
+
+        log.clear().await;
+        assert!(log.entries(None, None).await.is_empty());
+    }
+}
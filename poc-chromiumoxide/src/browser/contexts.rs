@@ -0,0 +1,141 @@
+use super::console::ConsoleLog;
+use super::core::Browser;
+use super::dialogs::DialogManager;
+use super::mutations::DomMutationTracker;
+use super::throttling::ThrottlingState;
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::cdp::browser_protocol::browser::BrowserContextId;
+use chromiumoxide::cdp::browser_protocol::target::{
+    CreateBrowserContextParams, CreateTargetParams, DisposeBrowserContextParams,
+    GetBrowserContextsParams, GetTargetsParams,
+};
+use chromiumoxide::Page;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+impl Browser {
+    /// Create a handle to a fresh incognito context in the same Chromium process.
+    ///
+    /// Cookies, storage and cache are isolated from every other context. Downloads are
+    /// reported browser-wide, so the new handle shares this browser's download tracking.
+    pub async fn new_isolated_context(&self) -> Result<Browser> {
+        let response = self
+            .browser
+            .execute(CreateBrowserContextParams::default())
+            .await
+            .context("Failed to create browser context")?;
+        let context_id = response.result.browser_context_id.clone();
+
+        let page = self
+            .browser
+            .new_page(Self::context_target("about:blank", &context_id)?)
+            .await
+            .context("Failed to open page in browser context")?;
+
+        let browser = Browser {
+            browser: self.browser.clone(),
+            page: Arc::new(RwLock::new(page.clone())),
+            downloads: self.downloads.clone(),
+            dialogs: Arc::new(DialogManager::new()),
+            console: Arc::new(ConsoleLog::new()),
+            mutations: Arc::new(DomMutationTracker::new()),
+            // Selector stability describes the site, not the session
+            stability: self.stability.clone(),
+            context_id: Some(context_id.clone()),
+            throttling: Arc::new(RwLock::new(ThrottlingState::default())),
+        };
+        if let Err(e) = browser.attach_dialog_handler(&page).await {
+            warn!("Dialog handling unavailable in context: {}", e);
+        }
+        if let Err(e) = browser.attach_console_listener(&page).await {
+            warn!("Console capture unavailable in context: {}", e);
+        }
+        if let Err(e) = browser.attach_mutation_observer(&page).await {
+            warn!("DOM change tracking unavailable in context: {}", e);
+        }
+
+        info!("Created isolated browser context {}", context_id.inner());
+        Ok(browser)
+    }
+
+    /// Browser context this handle is bound to; None for the default context
+    pub fn context_id(&self) -> Option<&BrowserContextId> {
+        self.context_id.as_ref()
+    }
+
+    /// Close every page of this handle's context and discard its data.
+    /// Does nothing for the default context.
+    pub async fn dispose_context(&self) -> Result<()> {
+        let Some(context_id) = &self.context_id else {
+            return Ok(());
+        };
+        self.browser
+            .execute(DisposeBrowserContextParams::new(context_id.clone()))
+            .await
+            .context("Failed to dispose browser context")?;
+        info!("Disposed browser context {}", context_id.inner());
+        Ok(())
+    }
+
+    /// Open a page in this handle's context
+    pub(crate) async fn new_context_page(&self, url: &str) -> Result<Page> {
+        let page = match &self.context_id {
+            Some(context_id) => {
+                self.browser
+                    .new_page(Self::context_target(url, context_id)?)
+                    .await?
+            }
+            None => self.browser.new_page(url).await?,
+        };
+        Ok(page)
+    }
+
+    /// Pages that belong to this handle's context.
+    ///
+    /// For the default context these are the pages outside every isolated
+    /// context, so other sessions' tabs are never listed or closed through it.
+    pub(crate) async fn context_pages(&self) -> Result<Vec<Page>> {
+        let pages = self.browser.pages().await?;
+        let targets = self.browser.execute(GetTargetsParams::default()).await?;
+
+        // Only contexts created with createBrowserContext are listed, never the default one
+        let isolated: HashSet<BrowserContextId> = match &self.context_id {
+            Some(_) => HashSet::new(),
+            None => self
+                .browser
+                .execute(GetBrowserContextsParams::default())
+                .await?
+                .result
+                .browser_context_ids
+                .clone()
+                .into_iter()
+                .collect(),
+        };
+        let in_context = |id: Option<&BrowserContextId>| match &self.context_id {
+            Some(context_id) => id == Some(context_id),
+            None => id.is_none_or(|id| !isolated.contains(id)),
+        };
+        let ids: HashSet<_> = targets
+            .result
+            .target_infos
+            .iter()
+            .filter(|t| in_context(t.browser_context_id.as_ref()))
+            .map(|t| t.target_id.inner().clone())
+            .collect();
+
+        Ok(pages
+            .into_iter()
+            .filter(|p| ids.contains(p.target_id().inner()))
+            .collect())
+    }
+
+    fn context_target(url: &str, context_id: &BrowserContextId) -> Result<CreateTargetParams> {
+        CreateTargetParams::builder()
+            .url(url)
+            .browser_context_id(context_id.clone())
+            .build()
+            .map_err(|e| anyhow!("Invalid target params: {}", e))
+    }
+}
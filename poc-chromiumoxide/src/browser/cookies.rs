@@ -0,0 +1,59 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::network::{
+    ClearBrowserCookiesParams, Cookie, CookieParam, GetAllCookiesParams, SetCookiesParams,
+    TimeSinceEpoch,
+};
+use tracing::info;
+
+impl Browser {
+    /// Get every cookie in the browser, not just those for the current page
+    pub async fn get_all_cookies(&self) -> Result<Vec<Cookie>> {
+        let page = self.page.read().await;
+        let response = page.execute(GetAllCookiesParams::default()).await?;
+        Ok(response.result.cookies.clone())
+    }
+
+    /// Set several cookies at once
+    pub async fn set_cookies(&self, cookies: Vec<CookieParam>) -> Result<()> {
+        if cookies.is_empty() {
+            return Ok(());
+        }
+        let count = cookies.len();
+        let page = self.page.read().await;
+        page.execute(SetCookiesParams::new(cookies)).await?;
+        info!("Set {} cookies", count);
+        Ok(())
+    }
+
+    /// Remove all cookies from the browser
+    pub async fn clear_cookies(&self) -> Result<()> {
+        let page = self.page.read().await;
+        page.execute(ClearBrowserCookiesParams::default()).await?;
+        info!("Cleared all browser cookies");
+        Ok(())
+    }
+}
+
+/// Convert a cookie read from the browser into a parameter that can set it again
+pub fn cookie_to_param(cookie: &Cookie) -> Result<CookieParam> {
+    let mut builder = CookieParam::builder()
+        .name(cookie.name.clone())
+        .value(cookie.value.clone())
+        .domain(cookie.domain.clone())
+        .path(cookie.path.clone())
+        .secure(cookie.secure)
+        .http_only(cookie.http_only);
+
+    // Session cookies report an expiry of -1
+    if cookie.expires > 0.0 {
+        builder = builder.expires(TimeSinceEpoch::new(cookie.expires));
+    }
+    if let Some(same_site) = cookie.same_site.clone() {
+        builder = builder.same_site(same_site);
+    }
+
+    builder
+        .build()
+        .map_err(|e| anyhow!("Invalid cookie {}: {}", cookie.name, e))
+}
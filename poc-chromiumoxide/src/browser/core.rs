@@ -1,6 +1,14 @@
+use super::console::ConsoleLog;
+use super::dialogs::DialogManager;
+use super::downloads::DownloadManager;
+use super::mutations::DomMutationTracker;
+use super::stability::SelectorStabilityTracker;
+use super::throttling::ThrottlingState;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat;
+use chromiumoxide::cdp::browser_protocol::browser::{BrowserContextId, CloseParams};
+use chromiumoxide::cdp::browser_protocol::page::{CaptureScreenshotFormat, Viewport};
+use chromiumoxide::handler::Handler;
 use chromiumoxide::page::ScreenshotParams;
 use chromiumoxide::{Browser as ChromeBrowser, BrowserConfig, Element, Page};
 use futures::StreamExt;
@@ -36,6 +44,12 @@ pub struct ScreenshotOptions {
     pub viewport_width: u32,
     pub viewport_height: u32,
     pub wait_after_load: Duration,
+    /// Capture only the element matching this selector
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// Capture only this region, in CSS pixels relative to the document
+    #[serde(default)]
+    pub clip: Option<ElementRect>,
 }
 
 impl Default for ScreenshotOptions {
@@ -47,6 +61,8 @@ impl Default for ScreenshotOptions {
             viewport_width: 1920,
             viewport_height: 1080,
             wait_after_load: Duration::from_secs(2),
+            selector: None,
+            clip: None,
         }
     }
 }
@@ -67,10 +83,79 @@ pub struct ElementRect {
     pub height: f64,
 }
 
+/// Resolve a DevTools endpoint to its browser WebSocket URL
+async fn resolve_ws_url(endpoint: &str) -> Result<String> {
+    let endpoint = endpoint.trim();
+    if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+        return Ok(endpoint.to_string());
+    }
+    if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+        return Err(anyhow!(
+            "Unsupported CDP endpoint (expected ws:// or http://): {}",
+            endpoint
+        ));
+    }
+
+    let version_url = format!("{}/json/version", endpoint.trim_end_matches('/'));
+    let version: serde_json::Value = reqwest::Client::new()
+        .get(&version_url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {}", version_url))?
+        .json()
+        .await
+        .context("Invalid /json/version response")?;
+
+    version
+        .get("webSocketDebuggerUrl")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow!("No webSocketDebuggerUrl at {}", version_url))
+}
+
+/// Bounding box of an element in document coordinates, scrolled into view
+async fn element_clip(page: &Page, selector: &str) -> Result<ElementRect> {
+    let script = format!(
+        r#"
+        (function() {{
+            const el = document.querySelector({});
+            if (!el) return null;
+            el.scrollIntoView({{ block: 'center', inline: 'center' }});
+            const rect = el.getBoundingClientRect();
+            return {{
+                x: rect.x + window.scrollX,
+                y: rect.y + window.scrollY,
+                width: rect.width,
+                height: rect.height
+            }};
+        }})()
+    "#,
+        serde_json::to_string(selector)?
+    );
+
+    let value: serde_json::Value = page.evaluate(script.as_str()).await?.into_value()?;
+    if value.is_null() {
+        return Err(anyhow!("Element not found: {}", selector));
+    }
+    let rect: ElementRect = serde_json::from_value(value)?;
+    if rect.width <= 0.0 || rect.height <= 0.0 {
+        return Err(anyhow!("Element has zero dimensions: {}", selector));
+    }
+    Ok(rect)
+}
+
 /// Main browser struct using chromiumoxide
 pub struct Browser {
     pub(crate) browser: Arc<ChromeBrowser>,
     pub(crate) page: Arc<RwLock<Page>>,
+    pub(crate) downloads: Arc<DownloadManager>,
+    pub(crate) dialogs: Arc<DialogManager>,
+    pub(crate) console: Arc<ConsoleLog>,
+    pub(crate) mutations: Arc<DomMutationTracker>,
+    pub(crate) stability: Arc<SelectorStabilityTracker>,
+    pub(crate) context_id: Option<BrowserContextId>,
+    pub(crate) throttling: Arc<RwLock<ThrottlingState>>,
 }
 
 impl Browser {
@@ -102,10 +187,29 @@ impl Browser {
         // Note: BrowserConfig builder pattern should be used to add arguments
         // Arguments should be added when creating the config, not here
 
-        let (browser, mut handler) = ChromeBrowser::launch(config)
+        let (browser, handler) = ChromeBrowser::launch(config)
             .await
             .context("Failed to launch Chrome browser")?;
 
+        Self::from_connection(browser, handler).await
+    }
+
+    /// Attach to an already-running browser instead of launching one.
+    ///
+    /// Accepts a `ws://` DevTools URL or an `http://host:port` debugging endpoint,
+    /// in which case the WebSocket URL is read from `/json/version`.
+    pub async fn connect(endpoint: &str) -> Result<Self> {
+        let ws_url = resolve_ws_url(endpoint).await?;
+        info!("Connecting to remote Chrome at {}", ws_url);
+
+        let (browser, handler) = ChromeBrowser::connect(ws_url.clone())
+            .await
+            .with_context(|| format!("Failed to connect to {}", ws_url))?;
+
+        Self::from_connection(browser, handler).await
+    }
+
+    async fn from_connection(browser: ChromeBrowser, mut handler: Handler) -> Result<Self> {
         // Spawn handler in background with proper error handling
         tokio::spawn(async move {
             while let Some(h) = handler.next().await {
@@ -141,12 +245,35 @@ impl Browser {
             .await
             .context("Failed to create new page")?;
 
-        info!("Browser initialized successfully");
-
-        Ok(Self {
+        let browser = Self {
             browser: Arc::new(browser),
             page: Arc::new(RwLock::new(page)),
-        })
+            downloads: Arc::new(DownloadManager::new()),
+            dialogs: Arc::new(DialogManager::new()),
+            console: Arc::new(ConsoleLog::new()),
+            mutations: Arc::new(DomMutationTracker::new()),
+            stability: Arc::new(SelectorStabilityTracker::new()),
+            context_id: None,
+            throttling: Arc::new(RwLock::new(ThrottlingState::default())),
+        };
+
+        if let Err(e) = browser.enable_downloads().await {
+            warn!("Download tracking unavailable: {}", e);
+        }
+        let page = browser.page().await;
+        if let Err(e) = browser.attach_dialog_handler(&page).await {
+            warn!("Dialog handling unavailable: {}", e);
+        }
+        if let Err(e) = browser.attach_console_listener(&page).await {
+            warn!("Console capture unavailable: {}", e);
+        }
+        if let Err(e) = browser.attach_mutation_observer(&page).await {
+            warn!("DOM change tracking unavailable: {}", e);
+        }
+
+        info!("Browser initialized successfully");
+
+        Ok(browser)
     }
 
     /// Create browser in headless mode
@@ -335,6 +462,42 @@ impl Browser {
         page.url().await.is_ok()
     }
 
+    /// Liveness probe: a trivial script must evaluate within `timeout`.
+    /// Catches hung renderers that still answer cheap protocol calls.
+    pub async fn ping(&self, timeout: Duration) -> bool {
+        let page = self.page().await;
+        match tokio::time::timeout(timeout, page.evaluate("1 + 1")).await {
+            Ok(Ok(result)) => result.into_value::<i64>().ok() == Some(2),
+            _ => false,
+        }
+    }
+
+    /// Ask Chromium to exit through CDP. Unlike `kill`, this works while other
+    /// handles to the browser are still alive.
+    pub async fn close_process(&self) -> Result<()> {
+        self.browser.execute(CloseParams::default()).await?;
+        info!("Closed browser process");
+        Ok(())
+    }
+
+    /// Kill the Chromium process behind this handle.
+    ///
+    /// Only possible for the last handle of a launched browser; otherwise the
+    /// process is killed once the remaining handles are dropped.
+    pub async fn kill(self: Arc<Self>) {
+        let Ok(browser) = Arc::try_unwrap(self) else {
+            return;
+        };
+        if let Ok(mut chrome) = Arc::try_unwrap(browser.browser) {
+            match chrome.kill().await {
+                Some(Ok(())) => info!("Killed browser process"),
+                Some(Err(e)) => warn!("Failed to kill browser process: {}", e),
+                // Connected browsers have no local process
+                None => {}
+            }
+        }
+    }
+
     /// Set zoom level for the page
     pub async fn set_zoom_level(&self, zoom_factor: f64) -> Result<()> {
         let page = self.page().await;
@@ -572,14 +735,31 @@ impl BrowserOps for Browser {
             _ => CaptureScreenshotFormat::Png,
         };
 
-        let screenshot = page
-            .screenshot(
-                ScreenshotParams::builder()
-                    .full_page(options.full_page)
-                    .format(format)
-                    .build(),
-            )
-            .await?;
+        let clip = match &options.selector {
+            Some(selector) => Some(element_clip(&page, selector).await?),
+            None => options.clip.clone(),
+        };
+
+        let mut params = ScreenshotParams::builder().format(format);
+        params = match clip {
+            Some(rect) => {
+                if rect.width <= 0.0 || rect.height <= 0.0 {
+                    return Err(anyhow!("Screenshot region has zero size"));
+                }
+                params
+                    .clip(Viewport {
+                        x: rect.x,
+                        y: rect.y,
+                        width: rect.width,
+                        height: rect.height,
+                        scale: 1.0,
+                    })
+                    .capture_beyond_viewport(true)
+            }
+            None => params.full_page(options.full_page),
+        };
+
+        let screenshot = page.screenshot(params.build()).await?;
 
         Ok(screenshot)
     }
@@ -0,0 +1,252 @@
+use super::core::Browser;
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::cdp::browser_protocol::page::{
+    DialogType, EventJavascriptDialogOpening, HandleJavaScriptDialogParams,
+};
+use chromiumoxide::Page;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
+
+const DIALOG_HISTORY_SIZE: usize = 50;
+
+/// How to respond to a native JavaScript dialog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DialogPolicy {
+    Accept,
+    Dismiss,
+    /// Accept a prompt() with the given text
+    Respond {
+        text: String,
+    },
+}
+
+impl DialogPolicy {
+    fn accepts(&self) -> bool {
+        !matches!(self, DialogPolicy::Dismiss)
+    }
+
+    fn prompt_text(&self) -> Option<&str> {
+        match self {
+            DialogPolicy::Respond { text } => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// A dialog that was opened and how it was handled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogEvent {
+    pub dialog_type: String,
+    pub message: String,
+    pub default_prompt: Option<String>,
+    pub url: String,
+    pub policy: DialogPolicy,
+    pub handled_at: DateTime<Utc>,
+}
+
+/// Dialog handler registry: a default policy plus per-type overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogPolicies {
+    pub default: DialogPolicy,
+    #[serde(default)]
+    pub by_type: HashMap<String, DialogPolicy>,
+}
+
+impl Default for DialogPolicies {
+    fn default() -> Self {
+        // Dismissing a beforeunload dialog cancels navigation, so accept those
+        let mut by_type = HashMap::new();
+        by_type.insert("beforeunload".to_string(), DialogPolicy::Accept);
+        Self {
+            default: DialogPolicy::Dismiss,
+            by_type,
+        }
+    }
+}
+
+impl DialogPolicies {
+    pub fn policy_for(&self, dialog_type: &str) -> &DialogPolicy {
+        self.by_type.get(dialog_type).unwrap_or(&self.default)
+    }
+}
+
+/// Handles dialogs for a browser and broadcasts what happened
+#[derive(Debug)]
+pub struct DialogManager {
+    policies: RwLock<DialogPolicies>,
+    history: RwLock<VecDeque<DialogEvent>>,
+    events: broadcast::Sender<DialogEvent>,
+}
+
+impl DialogManager {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            policies: RwLock::new(DialogPolicies::default()),
+            history: RwLock::new(VecDeque::with_capacity(DIALOG_HISTORY_SIZE)),
+            events,
+        }
+    }
+
+    pub async fn policies(&self) -> DialogPolicies {
+        self.policies.read().await.clone()
+    }
+
+    /// Set the default policy, or the policy for one dialog type
+    /// ("alert", "confirm", "prompt", "beforeunload")
+    pub async fn set_policy(&self, dialog_type: Option<&str>, policy: DialogPolicy) -> Result<()> {
+        let mut policies = self.policies.write().await;
+        match dialog_type {
+            Some(t @ ("alert" | "confirm" | "prompt" | "beforeunload")) => {
+                policies.by_type.insert(t.to_string(), policy);
+            }
+            Some(other) => return Err(anyhow!("Unknown dialog type: {}", other)),
+            None => policies.default = policy,
+        }
+        Ok(())
+    }
+
+    /// Remove all per-type overrides and restore the default policy
+    pub async fn reset(&self) {
+        *self.policies.write().await = DialogPolicies::default();
+    }
+
+    /// Recently handled dialogs, oldest first
+    pub async fn history(&self) -> Vec<DialogEvent> {
+        self.history.read().await.iter().cloned().collect()
+    }
+
+    /// Receive dialog events as they are handled
+    pub fn subscribe(&self) -> broadcast::Receiver<DialogEvent> {
+        self.events.subscribe()
+    }
+
+    async fn record(&self, event: DialogEvent) {
+        {
+            let mut history = self.history.write().await;
+            if history.len() >= DIALOG_HISTORY_SIZE {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+        // No receivers is fine
+        let _ = self.events.send(event);
+    }
+}
+
+impl Default for DialogManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn dialog_type_name(dialog_type: &DialogType) -> &'static str {
+    match dialog_type {
+        DialogType::Alert => "alert",
+        DialogType::Confirm => "confirm",
+        DialogType::Prompt => "prompt",
+        DialogType::Beforeunload => "beforeunload",
+    }
+}
+
+impl Browser {
+    /// Dialog handler registry for this browser
+    pub fn dialogs(&self) -> Arc<DialogManager> {
+        self.dialogs.clone()
+    }
+
+    /// Answer dialogs opened by `page` according to the current policies
+    pub(crate) async fn attach_dialog_handler(&self, page: &Page) -> Result<()> {
+        let mut opening = page
+            .event_listener::<EventJavascriptDialogOpening>()
+            .await
+            .context("Failed to subscribe to dialog events")?;
+
+        let manager = self.dialogs.clone();
+        let page = page.clone();
+        tokio::spawn(async move {
+            while let Some(event) = opening.next().await {
+                let dialog_type = dialog_type_name(&event.r#type);
+                let policy = manager.policies().await.policy_for(dialog_type).clone();
+
+                let mut params = HandleJavaScriptDialogParams::builder().accept(policy.accepts());
+                if let Some(text) = policy.prompt_text() {
+                    params = params.prompt_text(text);
+                }
+                let result = match params.build() {
+                    Ok(params) => page
+                        .execute(params)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| anyhow!(e)),
+                    Err(e) => Err(anyhow!("Invalid dialog params: {}", e)),
+                };
+                if let Err(e) = result {
+                    warn!("Failed to handle {} dialog: {}", dialog_type, e);
+                    continue;
+                }
+
+                info!(
+                    "Handled {} dialog ({:?}): {}",
+                    dialog_type, policy, event.message
+                );
+                manager
+                    .record(DialogEvent {
+                        dialog_type: dialog_type.to_string(),
+                        message: event.message.clone(),
+                        default_prompt: event.default_prompt.clone(),
+                        url: event.url.clone(),
+                        policy,
+                        handled_at: Utc::now(),
+                    })
+                    .await;
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beforeunload_is_accepted_by_default() {
+        let policies = DialogPolicies::default();
+        assert_eq!(policies.policy_for("beforeunload"), &DialogPolicy::Accept);
+        assert_eq!(policies.policy_for("confirm"), &DialogPolicy::Dismiss);
+    }
+
+    #[test]
+    fn policy_deserializes_from_tagged_json() {
+        let policy: DialogPolicy =
+            serde_json::from_value(serde_json::json!({"action": "respond", "text": "42"})).unwrap();
+        assert_eq!(policy.prompt_text(), Some("42"));
+        assert!(policy.accepts());
+    }
+
+    #[tokio::test]
+    async fn set_policy_rejects_unknown_types() {
+        let manager = DialogManager::new();
+        assert!(manager
+            .set_policy(Some("popup"), DialogPolicy::Accept)
+            .await
+            .is_err());
+
+        manager
+            .set_policy(Some("confirm"), DialogPolicy::Accept)
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.policies().await.policy_for("confirm"),
+            &DialogPolicy::Accept
+        );
+    }
+}
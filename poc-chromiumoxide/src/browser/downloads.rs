@@ -0,0 +1,396 @@
+use super::core::Browser;
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::cdp::browser_protocol::browser::{
+    DownloadProgressState, EventDownloadProgress, EventDownloadWillBegin,
+    SetDownloadBehaviorBehavior, SetDownloadBehaviorParams,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// Lifecycle state of a tracked download
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    InProgress,
+    Completed,
+    Canceled,
+}
+
+/// Information about a single download
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadInfo {
+    pub id: String,
+    pub url: String,
+    pub suggested_filename: String,
+    pub path: Option<PathBuf>,
+    pub state: DownloadState,
+    pub received_bytes: u64,
+    pub total_bytes: u64,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl DownloadInfo {
+    /// Download progress in the range 0.0..=1.0 (0.0 when the size is unknown)
+    pub fn progress(&self) -> f64 {
+        if self.state == DownloadState::Completed {
+            return 1.0;
+        }
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        (self.received_bytes as f64 / self.total_bytes as f64).min(1.0)
+    }
+}
+
+/// Tracks CDP download events and stores files in a configurable directory
+/// inside a fixed download root
+#[derive(Debug)]
+pub struct DownloadManager {
+    root: PathBuf,
+    dir: RwLock<PathBuf>,
+    downloads: RwLock<HashMap<String, DownloadInfo>>,
+}
+
+impl DownloadManager {
+    /// Create a manager using `RAINBOW_DOWNLOAD_DIR` or a temp directory
+    pub fn new() -> Self {
+        let dir = std::env::var("RAINBOW_DOWNLOAD_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| std::env::temp_dir().join("rainbow-downloads"));
+        Self::with_dir(dir)
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            root: dir.clone(),
+            dir: RwLock::new(dir),
+            downloads: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Current download directory
+    pub async fn dir(&self) -> PathBuf {
+        self.dir.read().await.clone()
+    }
+
+    /// Directory every download directory must be inside
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve a requested download directory under the root.
+    ///
+    /// Relative paths are joined to the root; absolute paths must already be inside
+    /// it. Paths with `..` or that reach outside the root through a symlink are
+    /// rejected.
+    pub fn resolve_dir(&self, requested: &Path) -> Result<PathBuf> {
+        let relative = if requested.is_absolute() {
+            requested
+                .strip_prefix(&self.root)
+                .map_err(|_| anyhow!("Download directory must be inside {}", self.root.display()))?
+        } else {
+            requested
+        };
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(anyhow!(
+                "Download directory must not leave {}",
+                self.root.display()
+            ));
+        }
+
+        let dir = self.root.join(relative);
+        // The closest existing ancestor decides where create_dir_all would end up
+        if let (Ok(root), Some(existing)) = (
+            self.root.canonicalize(),
+            dir.ancestors().find(|p| p.exists()),
+        ) {
+            if !existing.canonicalize()?.starts_with(&root) {
+                return Err(anyhow!(
+                    "Download directory must not leave {}",
+                    self.root.display()
+                ));
+            }
+        }
+        Ok(dir)
+    }
+
+    /// All tracked downloads, most recent first
+    pub async fn list(&self) -> Vec<DownloadInfo> {
+        let mut list: Vec<DownloadInfo> = self.downloads.read().await.values().cloned().collect();
+        list.sort_by_key(|d| std::cmp::Reverse(d.started_at));
+        list
+    }
+
+    /// Get a single download by id
+    pub async fn get(&self, id: &str) -> Option<DownloadInfo> {
+        self.downloads.read().await.get(id).cloned()
+    }
+
+    /// Wait until a download finishes.
+    ///
+    /// With an id, waits for that download; otherwise waits for the first download
+    /// that completes after this call started.
+    pub async fn wait_for(&self, id: Option<&str>, timeout: Duration) -> Result<DownloadInfo> {
+        let start = Instant::now();
+        let since = Utc::now();
+
+        loop {
+            {
+                let downloads = self.downloads.read().await;
+                let found = match id {
+                    Some(id) => downloads
+                        .get(id)
+                        .filter(|d| d.state != DownloadState::InProgress),
+                    None => downloads
+                        .values()
+                        .filter(|d| d.state != DownloadState::InProgress)
+                        .filter(|d| d.completed_at.map(|t| t >= since).unwrap_or(false))
+                        .min_by_key(|d| d.completed_at),
+                };
+                if let Some(download) = found {
+                    return match download.state {
+                        DownloadState::Canceled => {
+                            Err(anyhow!("Download was canceled: {}", download.id))
+                        }
+                        _ => Ok(download.clone()),
+                    };
+                }
+            }
+
+            if start.elapsed() > timeout {
+                return Err(anyhow!("Timeout waiting for download"));
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    async fn on_will_begin(&self, event: &EventDownloadWillBegin) {
+        info!(
+            "Download started: {} ({})",
+            event.suggested_filename, event.url
+        );
+        let info = DownloadInfo {
+            id: event.guid.clone(),
+            url: event.url.clone(),
+            suggested_filename: event.suggested_filename.clone(),
+            path: None,
+            state: DownloadState::InProgress,
+            received_bytes: 0,
+            total_bytes: 0,
+            started_at: Utc::now(),
+            completed_at: None,
+        };
+        self.downloads.write().await.insert(info.id.clone(), info);
+    }
+
+    async fn on_progress(&self, event: &EventDownloadProgress) {
+        let dir = self.dir().await;
+        let mut downloads = self.downloads.write().await;
+        let Some(download) = downloads.get_mut(&event.guid) else {
+            debug!("Progress for unknown download: {}", event.guid);
+            return;
+        };
+
+        download.received_bytes = event.received_bytes as u64;
+        download.total_bytes = event.total_bytes as u64;
+
+        match event.state {
+            DownloadProgressState::Completed => {
+                // Files are written under their guid; give them their real name
+                let temp_path = dir.join(&download.id);
+                let final_path = unique_path(&dir, &download.suggested_filename);
+                let path = match tokio::fs::rename(&temp_path, &final_path).await {
+                    Ok(_) => final_path,
+                    Err(e) => {
+                        warn!("Failed to rename download {}: {}", download.id, e);
+                        temp_path
+                    }
+                };
+                info!("Download completed: {}", path.display());
+                download.path = Some(path);
+                download.state = DownloadState::Completed;
+                download.completed_at = Some(Utc::now());
+            }
+            DownloadProgressState::Canceled => {
+                warn!("Download canceled: {}", download.id);
+                download.state = DownloadState::Canceled;
+                download.completed_at = Some(Utc::now());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pick a path in `dir` for `filename` that doesn't overwrite an existing file
+fn unique_path(dir: &Path, filename: &str) -> PathBuf {
+    let filename = sanitize_filename(filename);
+    let candidate = dir.join(&filename);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let (stem, ext) = match filename.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{}", ext)),
+        _ => (filename.clone(), String::new()),
+    };
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+fn sanitize_filename(filename: &str) -> String {
+    let cleaned: String = filename
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches('.').trim().to_string();
+    if cleaned.is_empty() {
+        "download".to_string()
+    } else {
+        cleaned
+    }
+}
+
+impl Browser {
+    /// Route downloads into the manager's directory and start tracking CDP download events
+    pub(crate) async fn enable_downloads(&self) -> Result<()> {
+        self.apply_download_dir().await?;
+
+        let mut will_begin = self
+            .browser
+            .event_listener::<EventDownloadWillBegin>()
+            .await
+            .context("Failed to subscribe to download events")?;
+        let mut progress = self
+            .browser
+            .event_listener::<EventDownloadProgress>()
+            .await
+            .context("Failed to subscribe to download progress")?;
+
+        let manager = self.downloads.clone();
+        tokio::spawn(async move {
+            while let Some(event) = will_begin.next().await {
+                manager.on_will_begin(&event).await;
+            }
+        });
+        let manager = self.downloads.clone();
+        tokio::spawn(async move {
+            while let Some(event) = progress.next().await {
+                manager.on_progress(&event).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Change the directory new downloads are stored in; see
+    /// [`DownloadManager::resolve_dir`] for which directories are allowed
+    pub async fn set_download_dir(&self, dir: &Path) -> Result<PathBuf> {
+        let dir = self.downloads.resolve_dir(dir)?;
+        *self.downloads.dir.write().await = dir.clone();
+        self.apply_download_dir().await?;
+        Ok(dir)
+    }
+
+    /// Download manager for this browser
+    pub fn downloads(&self) -> Arc<DownloadManager> {
+        self.downloads.clone()
+    }
+
+    async fn apply_download_dir(&self) -> Result<()> {
+        let dir = self.downloads.dir().await;
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create download dir {}", dir.display()))?;
+
+        let params = SetDownloadBehaviorParams::builder()
+            .behavior(SetDownloadBehaviorBehavior::AllowAndName)
+            .download_path(dir.to_string_lossy().to_string())
+            .events_enabled(true)
+            .build()
+            .map_err(|e| anyhow!("Invalid download behavior: {}", e))?;
+        self.browser.execute(params).await?;
+
+        info!("Downloads will be stored in {}", dir.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_strips_path_separators() {
+        assert_eq!(sanitize_filename("../etc/passwd"), "_etc_passwd");
+        assert_eq!(sanitize_filename("report.pdf"), "report.pdf");
+        assert_eq!(sanitize_filename(".."), "download");
+    }
+
+    #[test]
+    fn unique_path_avoids_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("file.txt"), b"x").unwrap();
+
+        let path = unique_path(dir.path(), "file.txt");
+        assert_eq!(path, dir.path().join("file (1).txt"));
+    }
+
+    #[test]
+    fn resolve_dir_stays_inside_root() {
+        let root = tempfile::tempdir().unwrap();
+        let manager = DownloadManager::with_dir(root.path().to_path_buf());
+
+        let nested = manager.resolve_dir(Path::new("run-1/files")).unwrap();
+        assert_eq!(nested, root.path().join("run-1/files"));
+        let inside = root.path().join("run-2");
+        assert_eq!(manager.resolve_dir(&inside).unwrap(), inside);
+
+        assert!(manager.resolve_dir(Path::new("../escape")).is_err());
+        assert!(manager.resolve_dir(Path::new("a/../../escape")).is_err());
+        assert!(manager.resolve_dir(Path::new("/tmp/elsewhere")).is_err());
+    }
+
+    #[test]
+    fn progress_handles_unknown_size() {
+        let mut info = DownloadInfo {
+            id: "1".to_string(),
+            url: "https://example.com/a.zip".to_string(),
+            suggested_filename: "a.zip".to_string(),
+            path: None,
+            state: DownloadState::InProgress,
+            received_bytes: 50,
+            total_bytes: 0,
+            started_at: Utc::now(),
+            completed_at: None,
+        };
+        assert_eq!(info.progress(), 0.0);
+
+        info.total_bytes = 200;
+        assert_eq!(info.progress(), 0.25);
+
+        info.state = DownloadState::Completed;
+        assert_eq!(info.progress(), 1.0);
+    }
+}
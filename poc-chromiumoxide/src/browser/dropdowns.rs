@@ -0,0 +1,340 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Longest wait for a custom dropdown's options to render after opening or typing
+const LISTING_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// Attribute put on the option to click, so it can be clicked with real input events
+const OPTION_MARKER: &str = "[data-rainbow-option]";
+
+/// Attribute put on the text input of a searchable combobox
+const SEARCH_INPUT_MARKER: &str = "[data-rainbow-combobox-input]";
+
+/// Picks the option of a native `<select>`; reports `native: false` for anything else
+const NATIVE_SELECT_SCRIPT: &str = r#"
+    (function() {
+        const el = document.querySelector(__SELECTOR__);
+        if (!el) return { found: false, native: false, selected: false };
+        if (el.tagName !== 'SELECT') return { found: true, native: false, selected: false };
+        const choice = __CHOICE__;
+        const norm = s => (s || '').replace(/\s+/g, ' ').trim().toLowerCase();
+        const options = Array.from(el.options);
+        let option;
+        if (choice.index !== undefined) {
+            option = options[choice.index];
+        } else if (choice.value !== undefined) {
+            option = options.find(o => o.value === choice.value) || options.find(o => norm(o.text) === norm(choice.value));
+        } else {
+            option = options.find(o => norm(o.text) === norm(choice.label)) || options.find(o => norm(o.text).includes(norm(choice.label)));
+        }
+        if (!option) {
+            return { found: true, native: true, selected: false, available: options.slice(0, 20).map(o => o.text.trim()) };
+        }
+        el.selectedIndex = option.index;
+        el.dispatchEvent(new Event('input', { bubbles: true }));
+        el.dispatchEvent(new Event('change', { bubbles: true }));
+        return { found: true, native: true, selected: true, value: el.value, text: option.text.trim(), index: option.index };
+    })()
+"#;
+
+/// Lists the options of the open menu belonging to a custom dropdown and marks the
+/// wanted one. The menu is the one the control names through aria-controls or
+/// aria-owns, else the visible listbox or menu closest to the control, since
+/// React Select and MUI render theirs in portals away from the control.
+const LIST_OPTIONS_SCRIPT: &str = r#"
+    (function() {
+        const control = document.querySelector(__SELECTOR__);
+        if (!control) return null;
+        const choice = __CHOICE__;
+        const norm = s => (s || '').replace(/\s+/g, ' ').trim().toLowerCase();
+        const visible = el => { const r = el.getBoundingClientRect(); return r.width > 0 && r.height > 0; };
+        const text = o => (o.innerText || o.textContent || '').replace(/\s+/g, ' ').trim();
+        const value = o => o.getAttribute('data-value') || o.getAttribute('value') || o.getAttribute('data-key') || null;
+
+        document.querySelectorAll('[data-rainbow-option], [data-rainbow-combobox-input]').forEach(el => {
+            el.removeAttribute('data-rainbow-option');
+            el.removeAttribute('data-rainbow-combobox-input');
+        });
+        const input = control.tagName === 'INPUT' ? control : control.querySelector('input:not([type="hidden"])');
+        const searchable = !!input && !input.readOnly && !input.disabled;
+        if (searchable) input.setAttribute('data-rainbow-combobox-input', '1');
+
+        const owned = [control, ...control.querySelectorAll('[aria-controls], [aria-owns]')]
+            .map(el => el.getAttribute('aria-controls') || el.getAttribute('aria-owns'))
+            .filter(Boolean)
+            .flatMap(ids => ids.split(/\s+/))
+            .map(id => document.getElementById(id))
+            .filter(el => el && visible(el));
+        const anchor = control.getBoundingClientRect();
+        const distance = el => Math.abs(el.getBoundingClientRect().top - anchor.bottom);
+        const lists = owned.length ? owned : Array.from(document.querySelectorAll(
+            '[role="listbox"], [role="menu"], [class*="select__menu"], [class*="-menu"], .MuiPopover-paper, ' +
+            '.MuiAutocomplete-popper, .dropdown-menu.show, [class*="dropdown"][class*="open"]'))
+            .filter(el => visible(el) && !el.contains(control))
+            .sort((a, b) => distance(a) - distance(b));
+
+        let options = [];
+        for (const list of lists) {
+            let found = list.querySelectorAll('[role="option"], [role="menuitem"]');
+            if (!found.length) found = list.querySelectorAll('[class*="option"], li');
+            options = Array.from(found).filter(o => visible(o) && !o.matches('[aria-disabled="true"], [disabled]'));
+            if (options.length) break;
+        }
+
+        let index = -1;
+        if (choice.index !== undefined) {
+            index = choice.index < options.length ? choice.index : -1;
+        } else {
+            const wanted = norm(choice.value !== undefined ? choice.value : choice.label);
+            if (choice.value !== undefined) index = options.findIndex(o => value(o) === choice.value);
+            if (index < 0) index = options.findIndex(o => norm(text(o)) === wanted);
+            if (index < 0) index = options.findIndex(o => norm(text(o)).includes(wanted));
+        }
+        const listing = { listed: options.length, matched: index >= 0, searchable, available: options.slice(0, 20).map(text) };
+        if (index < 0) return listing;
+
+        const option = options[index];
+        option.setAttribute('data-rainbow-option', '1');
+        option.scrollIntoView({ block: 'nearest' });
+        return Object.assign(listing, { text: text(option), value: value(option), index });
+    })()
+"#;
+
+/// What a custom dropdown shows once an option was picked
+const DISPLAYED_SCRIPT: &str = r#"
+    (function() {
+        const control = document.querySelector(__SELECTOR__);
+        if (!control) return null;
+        const input = control.tagName === 'INPUT' ? control : control.querySelector('input:not([type="hidden"])');
+        return [control.innerText || '', input ? input.value : ''].join(' ').replace(/\s+/g, ' ').trim();
+    })()
+"#;
+
+/// Which option of a dropdown to pick
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionChoice {
+    /// The option's value attribute, or its label when no value matches
+    Value(String),
+    /// The visible text, matched exactly first and then as a substring
+    Label(String),
+    Index(usize),
+}
+
+impl OptionChoice {
+    /// Text to type into a searchable combobox to bring the option up
+    fn search_text(&self) -> Option<&str> {
+        match self {
+            OptionChoice::Value(text) | OptionChoice::Label(text) => Some(text),
+            OptionChoice::Index(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for OptionChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionChoice::Value(value) => write!(f, "value '{}'", value),
+            OptionChoice::Label(label) => write!(f, "label '{}'", label),
+            OptionChoice::Index(index) => write!(f, "index {}", index),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DropdownKind {
+    /// A `<select>` element
+    Native,
+    /// A div-based dropdown or combobox, e.g. React Select or MUI
+    Custom,
+}
+
+/// The option a dropdown ended up on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChosenOption {
+    pub kind: DropdownKind,
+    pub value: Option<String>,
+    pub text: Option<String>,
+    pub index: Option<usize>,
+    /// Whether the control shows the chosen option afterwards
+    pub verified: bool,
+}
+
+#[derive(Deserialize)]
+struct NativeOutcome {
+    found: bool,
+    native: bool,
+    selected: bool,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    index: Option<usize>,
+    #[serde(default)]
+    available: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Listing {
+    listed: usize,
+    matched: bool,
+    searchable: bool,
+    #[serde(default)]
+    available: Vec<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    index: Option<usize>,
+}
+
+impl Browser {
+    /// Pick an option of the dropdown at `selector`. Native `<select>` elements
+    /// are set directly; custom dropdowns are opened with a click, searched by
+    /// typing when they have a text input and the option is not listed, and the
+    /// option is clicked like a user would.
+    pub async fn choose_option(
+        &self,
+        selector: &str,
+        choice: &OptionChoice,
+    ) -> Result<ChosenOption> {
+        let selector_json = serde_json::to_string(selector)?;
+        let choice_json = serde_json::to_string(choice)?;
+
+        let script = NATIVE_SELECT_SCRIPT
+            .replace("__SELECTOR__", &selector_json)
+            .replace("__CHOICE__", &choice_json);
+        let native: NativeOutcome = serde_json::from_value(self.execute_script(&script).await?)?;
+        if !native.found {
+            return Err(anyhow!("Dropdown not found: {}", selector));
+        }
+        if native.native {
+            if !native.selected {
+                return Err(anyhow!(
+                    "No option with {} in {} (options: {})",
+                    choice,
+                    selector,
+                    native.available.join(", ")
+                ));
+            }
+            return Ok(ChosenOption {
+                kind: DropdownKind::Native,
+                value: native.value,
+                text: native.text,
+                index: native.index,
+                verified: true,
+            });
+        }
+
+        let list_script = LIST_OPTIONS_SCRIPT
+            .replace("__SELECTOR__", &selector_json)
+            .replace("__CHOICE__", &choice_json);
+        self.click(selector).await?;
+        let mut listing = self.wait_for_listing(&list_script, false).await?;
+
+        // Long or async option lists only show what matches the typed text
+        if !listing.matched && listing.searchable {
+            if let Some(query) = choice.search_text() {
+                debug!("Searching combobox {} for '{}'", selector, query);
+                self.type_text(SEARCH_INPUT_MARKER, query).await?;
+                listing = self.wait_for_listing(&list_script, true).await?;
+            }
+        }
+        if !listing.matched {
+            return Err(anyhow!(
+                "No option with {} among {} listed for {} (options: {})",
+                choice,
+                listing.listed,
+                selector,
+                listing.available.join(", ")
+            ));
+        }
+
+        self.click(OPTION_MARKER).await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let shown: Option<String> = serde_json::from_value(
+            self.execute_script(&DISPLAYED_SCRIPT.replace("__SELECTOR__", &selector_json))
+                .await?,
+        )?;
+        let verified = shows_option(shown.as_deref(), listing.text.as_deref());
+        if !verified {
+            warn!(
+                "Dropdown {} does not show '{}' after picking it",
+                selector,
+                listing.text.as_deref().unwrap_or_default()
+            );
+        }
+
+        Ok(ChosenOption {
+            kind: DropdownKind::Custom,
+            value: listing.value,
+            text: listing.text,
+            index: listing.index,
+            verified,
+        })
+    }
+
+    /// Poll the open menu until it lists options, or the wanted option when
+    /// `until_matched`, or the listing timeout passes
+    async fn wait_for_listing(&self, script: &str, until_matched: bool) -> Result<Listing> {
+        let deadline = Instant::now() + LISTING_TIMEOUT;
+        loop {
+            let listing: Option<Listing> =
+                serde_json::from_value(self.execute_script(script).await?)?;
+            let listing = listing.ok_or_else(|| anyhow!("Dropdown disappeared while open"))?;
+            let ready = listing.matched || (!until_matched && listing.listed > 0);
+            if ready || Instant::now() >= deadline {
+                return Ok(listing);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
+
+/// Whether the control's text shows the chosen option; multi-selects show it
+/// among other chips
+fn shows_option(shown: Option<&str>, chosen: Option<&str>) -> bool {
+    let norm = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    match chosen.map(norm).filter(|chosen| !chosen.is_empty()) {
+        Some(chosen) => shown.is_some_and(|shown| norm(shown).contains(&chosen)),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_reach_scripts_as_tagged_objects() {
+        let label = serde_json::to_value(OptionChoice::Label("Canada".into())).unwrap();
+        assert_eq!(label, serde_json::json!({ "label": "Canada" }));
+        let index: OptionChoice = serde_json::from_str(r#"{"index": 2}"#).unwrap();
+        assert_eq!(index, OptionChoice::Index(2));
+        assert_eq!(index.search_text(), None);
+    }
+
+    #[test]
+    fn picks_are_confirmed_by_the_control_text() {
+        assert!(shows_option(
+            Some("Country\n United  States"),
+            Some("united states")
+        ));
+        assert!(shows_option(Some("Red × Blue ×"), Some("Blue")));
+        assert!(!shows_option(Some("Select..."), Some("Canada")));
+        assert!(!shows_option(None, Some("Canada")));
+        assert!(shows_option(None, None));
+    }
+}
@@ -0,0 +1,156 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    ClearDeviceMetricsOverrideParams, SetDeviceMetricsOverrideParams,
+    SetTouchEmulationEnabledParams, SetUserAgentOverrideParams,
+};
+use serde::Serialize;
+use tracing::info;
+
+/// Viewport, pixel ratio, touch and user agent settings for an emulated device
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DevicePreset {
+    pub name: &'static str,
+    pub width: i64,
+    pub height: i64,
+    pub device_scale_factor: f64,
+    pub mobile: bool,
+    pub touch: bool,
+    pub user_agent: &'static str,
+}
+
+const IPHONE_UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 16_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.0 Mobile/15E148 Safari/604.1";
+const IPAD_UA: &str = "Mozilla/5.0 (iPad; CPU OS 16_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.0 Mobile/15E148 Safari/604.1";
+const PIXEL_UA: &str = "Mozilla/5.0 (Linux; Android 13; Pixel 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/116.0.0.0 Mobile Safari/537.36";
+
+/// Built-in device presets
+pub const DEVICE_PRESETS: &[DevicePreset] = &[
+    DevicePreset {
+        name: "iphone",
+        width: 390,
+        height: 844,
+        device_scale_factor: 3.0,
+        mobile: true,
+        touch: true,
+        user_agent: IPHONE_UA,
+    },
+    DevicePreset {
+        name: "iphone-se",
+        width: 375,
+        height: 667,
+        device_scale_factor: 2.0,
+        mobile: true,
+        touch: true,
+        user_agent: IPHONE_UA,
+    },
+    DevicePreset {
+        name: "pixel",
+        width: 412,
+        height: 915,
+        device_scale_factor: 2.625,
+        mobile: true,
+        touch: true,
+        user_agent: PIXEL_UA,
+    },
+    DevicePreset {
+        name: "ipad",
+        width: 820,
+        height: 1180,
+        device_scale_factor: 2.0,
+        mobile: true,
+        touch: true,
+        user_agent: IPAD_UA,
+    },
+];
+
+impl DevicePreset {
+    /// Look up a preset by name. Matching ignores case, spaces, dashes and model numbers,
+    /// so "iPhone 12", "Pixel 5" and "iphone" all resolve.
+    pub fn find(name: &str) -> Option<&'static DevicePreset> {
+        let key: String = name
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+
+        if let Some(preset) = DEVICE_PRESETS
+            .iter()
+            .find(|p| p.name.replace('-', "") == key)
+        {
+            return Some(preset);
+        }
+
+        let family: String = key.chars().filter(|c| c.is_ascii_alphabetic()).collect();
+        DEVICE_PRESETS.iter().find(|p| p.name == family)
+    }
+}
+
+impl Browser {
+    /// Emulate a device preset on the active page
+    pub async fn emulate_preset(&self, preset: &DevicePreset) -> Result<()> {
+        let page = self.page.read().await;
+
+        page.execute(SetDeviceMetricsOverrideParams::new(
+            preset.width,
+            preset.height,
+            preset.device_scale_factor,
+            preset.mobile,
+        ))
+        .await?;
+
+        let touch = SetTouchEmulationEnabledParams::builder()
+            .enabled(preset.touch)
+            .max_touch_points(if preset.touch { 5 } else { 1 })
+            .build()
+            .map_err(|e| anyhow!("Invalid touch emulation params: {}", e))?;
+        page.execute(touch).await?;
+
+        page.execute(SetUserAgentOverrideParams::new(preset.user_agent))
+            .await?;
+
+        info!(
+            "Emulating device {} ({}x{} @{}x)",
+            preset.name, preset.width, preset.height, preset.device_scale_factor
+        );
+        Ok(())
+    }
+
+    /// Emulate a device by preset name
+    pub async fn emulate_device_preset(&self, device: &str) -> Result<&'static DevicePreset> {
+        let preset =
+            DevicePreset::find(device).ok_or_else(|| anyhow!("Unknown device: {}", device))?;
+        self.emulate_preset(preset).await?;
+        Ok(preset)
+    }
+
+    /// Return the page to desktop metrics and input
+    pub async fn clear_device_emulation(&self) -> Result<()> {
+        let page = self.page.read().await;
+        page.execute(ClearDeviceMetricsOverrideParams::default())
+            .await?;
+        page.execute(SetTouchEmulationEnabledParams::new(false))
+            .await?;
+        info!("Cleared device emulation");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_presets_by_loose_name() {
+        assert_eq!(DevicePreset::find("iPhone").unwrap().name, "iphone");
+        assert_eq!(DevicePreset::find("iPhone 12").unwrap().name, "iphone");
+        assert_eq!(DevicePreset::find("iphone-se").unwrap().name, "iphone-se");
+        assert_eq!(DevicePreset::find("Pixel 5").unwrap().name, "pixel");
+        assert_eq!(DevicePreset::find("IPAD").unwrap().name, "ipad");
+    }
+
+    #[test]
+    fn unknown_device_is_none() {
+        assert!(DevicePreset::find("nokia").is_none());
+        assert!(DevicePreset::find("").is_none());
+    }
+}
@@ -0,0 +1,290 @@
+use super::core::Browser;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Counts DOM insertions from the moment it is installed, once per document
+const MUTATION_COUNTER_SCRIPT: &str = r#"
+    (function() {
+        if (window.__rainbowMutations === undefined) {
+            window.__rainbowMutations = 0;
+            new MutationObserver(records => {
+                for (const record of records) window.__rainbowMutations += record.addedNodes.length;
+            }).observe(document.documentElement, { childList: true, subtree: true });
+        }
+        return true;
+    })()
+"#;
+
+/// Time without DOM insertions after which a scroll's content counts as rendered
+const MUTATION_QUIET: Duration = Duration::from_millis(300);
+
+/// Limits of a scroll-until-stable run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrollOptions {
+    /// Selector of one feed or listing item; when set, stability also requires the
+    /// item count to stop growing
+    #[serde(default)]
+    pub item_selector: Option<String>,
+    #[serde(default = "default_max_scrolls")]
+    pub max_scrolls: usize,
+    /// Network idle time awaited after each scroll
+    #[serde(default = "default_idle_ms")]
+    pub idle_ms: u64,
+    /// Longest wait for content after each scroll
+    #[serde(default = "default_settle_timeout_ms")]
+    pub settle_timeout_ms: u64,
+    /// Consecutive scrolls without new content before the page counts as fully loaded
+    #[serde(default = "default_stable_rounds")]
+    pub stable_rounds: usize,
+    #[serde(default = "default_max_duration_ms")]
+    pub max_duration_ms: u64,
+    /// Scroll back to where the page was once loading stops
+    #[serde(default = "default_restore_position")]
+    pub restore_position: bool,
+}
+
+fn default_max_scrolls() -> usize {
+    20
+}
+
+fn default_idle_ms() -> u64 {
+    500
+}
+
+fn default_settle_timeout_ms() -> u64 {
+    3000
+}
+
+fn default_stable_rounds() -> usize {
+    2
+}
+
+fn default_max_duration_ms() -> u64 {
+    30000
+}
+
+fn default_restore_position() -> bool {
+    true
+}
+
+impl Default for ScrollOptions {
+    fn default() -> Self {
+        Self {
+            item_selector: None,
+            max_scrolls: default_max_scrolls(),
+            idle_ms: default_idle_ms(),
+            settle_timeout_ms: default_settle_timeout_ms(),
+            stable_rounds: default_stable_rounds(),
+            max_duration_ms: default_max_duration_ms(),
+            restore_position: default_restore_position(),
+        }
+    }
+}
+
+/// Outcome of a scroll-until-stable run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrollReport {
+    pub scrolls: usize,
+    pub initial_height: u64,
+    pub final_height: u64,
+    /// Matches of the item selector before and after loading
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_items: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_items: Option<u64>,
+    /// Whether scrolling stopped because no more content arrived
+    pub reached_end: bool,
+    /// "stable", "max_scrolls" or "max_duration"
+    pub stop_reason: String,
+    pub duration_ms: u64,
+}
+
+/// Page size measured after each scroll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ContentSize {
+    height: u64,
+    items: Option<u64>,
+}
+
+/// Counts consecutive scrolls that brought no new content
+#[derive(Debug)]
+struct StabilityTracker {
+    required: usize,
+    stable_rounds: usize,
+    last: ContentSize,
+}
+
+impl StabilityTracker {
+    fn new(initial: ContentSize, required: usize) -> Self {
+        Self {
+            required: required.max(1),
+            stable_rounds: 0,
+            last: initial,
+        }
+    }
+
+    /// Record the size after a scroll; true once it stopped growing for long enough
+    fn observe(&mut self, size: ContentSize) -> bool {
+        let grew = size.height > self.last.height || size.items > self.last.items;
+        self.stable_rounds = if grew { 0 } else { self.stable_rounds + 1 };
+        self.last = size;
+        self.stable_rounds >= self.required
+    }
+}
+
+impl Browser {
+    /// Scroll to the bottom repeatedly, waiting for the network and DOM to settle each
+    /// time, until no more content loads or a limit is hit
+    pub async fn scroll_until_stable(&self, options: &ScrollOptions) -> Result<ScrollReport> {
+        let start = Instant::now();
+        let max_duration = Duration::from_millis(options.max_duration_ms);
+        self.execute_script(MUTATION_COUNTER_SCRIPT).await?;
+
+        let origin = self
+            .execute_script("({ x: window.scrollX, y: window.scrollY })")
+            .await?;
+        let initial = self.content_size(options.item_selector.as_deref()).await?;
+        let mut tracker = StabilityTracker::new(initial, options.stable_rounds);
+        let mut size = initial;
+        let mut scrolls = 0;
+
+        let stop_reason = loop {
+            if scrolls >= options.max_scrolls {
+                break "max_scrolls";
+            }
+            if start.elapsed() >= max_duration {
+                break "max_duration";
+            }
+
+            self.execute_script(
+                "window.scrollTo(0, (document.scrollingElement || document.body).scrollHeight)",
+            )
+            .await?;
+            scrolls += 1;
+            self.settle_after_scroll(options).await;
+
+            size = self.content_size(options.item_selector.as_deref()).await?;
+            debug!(
+                "Scroll {}: height {} items {:?}",
+                scrolls, size.height, size.items
+            );
+            if tracker.observe(size) {
+                break "stable";
+            }
+        };
+
+        if options.restore_position {
+            let x = origin["x"].as_f64().unwrap_or(0.0) as i32;
+            let y = origin["y"].as_f64().unwrap_or(0.0) as i32;
+            self.scroll_to(x, y).await?;
+        }
+
+        info!(
+            "Scrolled {} times: height {} -> {} ({})",
+            scrolls, initial.height, size.height, stop_reason
+        );
+        Ok(ScrollReport {
+            scrolls,
+            initial_height: initial.height,
+            final_height: size.height,
+            initial_items: initial.items,
+            final_items: size.items,
+            reached_end: stop_reason == "stable",
+            stop_reason: stop_reason.to_string(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Wait for requests triggered by a scroll, then for the DOM to stop changing
+    async fn settle_after_scroll(&self, options: &ScrollOptions) {
+        let deadline = Instant::now() + Duration::from_millis(options.settle_timeout_ms);
+        let idle = Duration::from_millis(options.idle_ms);
+        let _ = tokio::time::timeout(
+            deadline.saturating_duration_since(Instant::now()),
+            self.wait_for_network_idle(idle),
+        )
+        .await;
+
+        // The page observer reports changes directly; the counter is the fallback
+        if self.mutations.is_active() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            self.wait_for_dom_settled(MUTATION_QUIET, remaining).await;
+            return;
+        }
+        let mut last_count = self.mutation_count().await;
+        let mut quiet_since = Instant::now();
+        while Instant::now() < deadline && quiet_since.elapsed() < MUTATION_QUIET {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let count = self.mutation_count().await;
+            if count != last_count {
+                last_count = count;
+                quiet_since = Instant::now();
+            }
+        }
+    }
+
+    async fn mutation_count(&self) -> u64 {
+        self.execute_script("window.__rainbowMutations || 0")
+            .await
+            .ok()
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+    }
+
+    async fn content_size(&self, item_selector: Option<&str>) -> Result<ContentSize> {
+        let selector = serde_json::to_string(&item_selector)?;
+        let script = format!(
+            r#"
+            (function() {{
+                const selector = {};
+                return {{
+                    height: (document.scrollingElement || document.body).scrollHeight,
+                    items: selector ? document.querySelectorAll(selector).length : null
+                }};
+            }})()"#,
+            selector
+        );
+        let result = self.execute_script(&script).await?;
+        Ok(ContentSize {
+            height: result["height"].as_u64().unwrap_or(0),
+            items: result["items"].as_u64(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(height: u64, items: Option<u64>) -> ContentSize {
+        ContentSize { height, items }
+    }
+
+    #[test]
+    fn stable_after_rounds_without_growth() {
+        let mut tracker = StabilityTracker::new(size(1000, None), 2);
+        assert!(!tracker.observe(size(2000, None)));
+        assert!(!tracker.observe(size(2000, None)));
+        assert!(tracker.observe(size(2000, None)));
+    }
+
+    #[test]
+    fn growth_resets_stability() {
+        let mut tracker = StabilityTracker::new(size(1000, Some(10)), 2);
+        assert!(!tracker.observe(size(1000, Some(10))));
+        // Items appended inside a fixed-height scroll container
+        assert!(!tracker.observe(size(1000, Some(20))));
+        assert!(!tracker.observe(size(1000, Some(20))));
+        assert!(tracker.observe(size(1000, Some(20))));
+    }
+
+    #[test]
+    fn options_default_from_empty_json() {
+        let options: ScrollOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.max_scrolls, 20);
+        assert_eq!(options.stable_rounds, 2);
+        assert!(options.restore_position);
+    }
+}
@@ -0,0 +1,375 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::input::{DispatchKeyEventParams, DispatchKeyEventType};
+use chromiumoxide::Page;
+use serde::Serialize;
+use tracing::info;
+
+/// Modifier keys with their CDP bit flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Modifier {
+    Alt,
+    Ctrl,
+    Meta,
+    Shift,
+}
+
+impl Modifier {
+    fn flag(self) -> i64 {
+        match self {
+            Modifier::Alt => 1,
+            Modifier::Ctrl => 2,
+            Modifier::Meta => 4,
+            Modifier::Shift => 8,
+        }
+    }
+
+    fn key_def(self) -> KeyDef {
+        match self {
+            Modifier::Alt => KeyDef::named("Alt", "AltLeft", 18),
+            Modifier::Ctrl => KeyDef::named("Control", "ControlLeft", 17),
+            Modifier::Meta => KeyDef::named("Meta", "MetaLeft", 91),
+            Modifier::Shift => KeyDef::named("Shift", "ShiftLeft", 16),
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "alt" | "option" => Some(Modifier::Alt),
+            "ctrl" | "control" => Some(Modifier::Ctrl),
+            "meta" | "cmd" | "command" | "super" | "win" => Some(Modifier::Meta),
+            "shift" => Some(Modifier::Shift),
+            _ => None,
+        }
+    }
+}
+
+/// A single key as understood by `Input.dispatchKeyEvent`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyDef {
+    pub key: String,
+    pub code: String,
+    pub key_code: i64,
+    pub text: Option<String>,
+}
+
+impl KeyDef {
+    fn named(key: &str, code: &str, key_code: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            code: code.to_string(),
+            key_code,
+            text: None,
+        }
+    }
+
+    fn with_text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    /// The key as typed with Shift held: "a" becomes "A" and "1" becomes "!"
+    /// (US layout). Named keys are unchanged.
+    fn shifted(mut self) -> Self {
+        let mut chars = self.key.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return self;
+        };
+        if self.text.as_deref() != Some(self.key.as_str()) {
+            return self;
+        }
+        let shifted = shifted_char(c).to_string();
+        self.key = shifted.clone();
+        self.text = Some(shifted);
+        self
+    }
+
+    /// Resolve a named key ("Enter", "ArrowDown", "F5") or a single character
+    pub fn parse(name: &str) -> Option<Self> {
+        let lower = name.to_lowercase();
+        let def = match lower.as_str() {
+            "enter" | "return" => KeyDef::named("Enter", "Enter", 13).with_text("\r"),
+            "tab" => KeyDef::named("Tab", "Tab", 9),
+            "escape" | "esc" => KeyDef::named("Escape", "Escape", 27),
+            "backspace" => KeyDef::named("Backspace", "Backspace", 8),
+            "delete" | "del" => KeyDef::named("Delete", "Delete", 46),
+            "space" => KeyDef::named(" ", "Space", 32).with_text(" "),
+            "arrowup" | "up" => KeyDef::named("ArrowUp", "ArrowUp", 38),
+            "arrowdown" | "down" => KeyDef::named("ArrowDown", "ArrowDown", 40),
+            "arrowleft" | "left" => KeyDef::named("ArrowLeft", "ArrowLeft", 37),
+            "arrowright" | "right" => KeyDef::named("ArrowRight", "ArrowRight", 39),
+            "home" => KeyDef::named("Home", "Home", 36),
+            "end" => KeyDef::named("End", "End", 35),
+            "pageup" => KeyDef::named("PageUp", "PageUp", 33),
+            "pagedown" => KeyDef::named("PageDown", "PageDown", 34),
+            "insert" => KeyDef::named("Insert", "Insert", 45),
+            _ => return Self::function_key(&lower).or_else(|| Self::character(name)),
+        };
+        Some(def)
+    }
+
+    fn function_key(lower: &str) -> Option<Self> {
+        let n: i64 = lower.strip_prefix('f')?.parse().ok()?;
+        if !(1..=12).contains(&n) {
+            return None;
+        }
+        let name = format!("F{}", n);
+        Some(KeyDef::named(&name, &name, 111 + n))
+    }
+
+    fn character(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        let c = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+
+        let (code, key_code) = if c.is_ascii_alphabetic() {
+            let upper = c.to_ascii_uppercase();
+            (format!("Key{}", upper), upper as i64)
+        } else if c.is_ascii_digit() {
+            (format!("Digit{}", c), c as i64)
+        } else {
+            (String::new(), 0)
+        };
+
+        Some(Self {
+            key: c.to_string(),
+            code,
+            key_code,
+            text: Some(c.to_string()),
+        })
+    }
+}
+
+fn shifted_char(c: char) -> char {
+    match c {
+        'a'..='z' => c.to_ascii_uppercase(),
+        '1' => '!',
+        '2' => '@',
+        '3' => '#',
+        '4' => '$',
+        '5' => '%',
+        '6' => '^',
+        '7' => '&',
+        '8' => '*',
+        '9' => '(',
+        '0' => ')',
+        '-' => '_',
+        '=' => '+',
+        '[' => '{',
+        ']' => '}',
+        '\\' => '|',
+        ';' => ':',
+        '\'' => '"',
+        ',' => '<',
+        '.' => '>',
+        '/' => '?',
+        '`' => '~',
+        c => c,
+    }
+}
+
+/// A key with optional modifiers, e.g. "Ctrl+Shift+T"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyChord {
+    pub modifiers: Vec<Modifier>,
+    pub key: KeyDef,
+}
+
+impl KeyChord {
+    /// Parse a chord such as "Enter", "Ctrl+A" or "Shift+Tab"
+    pub fn parse(chord: &str) -> Result<Self> {
+        let chord = chord.trim();
+        if chord.is_empty() {
+            return Err(anyhow!("Key chord cannot be empty"));
+        }
+
+        // A trailing "+" is the plus key itself ("Ctrl++")
+        let (prefix, key_part) = match chord.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None if chord == "+" => ("", "+"),
+            None => match chord.rsplit_once('+') {
+                Some((prefix, key)) => (prefix, key),
+                None => ("", chord),
+            },
+        };
+
+        let mut modifiers = Vec::new();
+        for part in prefix.split('+').filter(|p| !p.is_empty()) {
+            let modifier = Modifier::parse(part.trim())
+                .ok_or_else(|| anyhow!("Unknown modifier: {}", part))?;
+            if !modifiers.contains(&modifier) {
+                modifiers.push(modifier);
+            }
+        }
+
+        let mut key =
+            KeyDef::parse(key_part.trim()).ok_or_else(|| anyhow!("Unknown key: {}", key_part))?;
+        if modifiers.contains(&Modifier::Shift) {
+            key = key.shifted();
+        }
+
+        Ok(Self { modifiers, key })
+    }
+
+    fn modifier_flags(&self) -> i64 {
+        self.modifiers.iter().fold(0, |acc, m| acc | m.flag())
+    }
+
+    /// Text produced by the chord, if it types a character
+    fn text(&self) -> Option<String> {
+        // Ctrl/Alt/Meta chords are shortcuts and don't insert text
+        let is_shortcut = self
+            .modifiers
+            .iter()
+            .any(|m| matches!(m, Modifier::Ctrl | Modifier::Alt | Modifier::Meta));
+        if is_shortcut {
+            return None;
+        }
+        self.key.text.clone()
+    }
+}
+
+impl Browser {
+    /// Press a single key chord on the active page
+    pub async fn press_key(&self, chord: &str) -> Result<()> {
+        let chord = KeyChord::parse(chord)?;
+        let page = self.page.read().await;
+        dispatch_chord(&page, &chord).await
+    }
+
+    /// Send a sequence of key chords, optionally focusing an element first
+    pub async fn send_keys(&self, selector: Option<&str>, keys: &[String]) -> Result<()> {
+        // Parse everything up front so a typo doesn't leave a half-sent sequence
+        let chords = keys
+            .iter()
+            .map(|k| KeyChord::parse(k))
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(selector) = selector {
+            self.focus(selector).await?;
+        }
+
+        let page = self.page.read().await;
+        for chord in &chords {
+            dispatch_chord(&page, chord).await?;
+        }
+        info!("Sent {} key chords", chords.len());
+        Ok(())
+    }
+}
+
+async fn dispatch_chord(page: &Page, chord: &KeyChord) -> Result<()> {
+    let mut active = 0;
+    for modifier in &chord.modifiers {
+        active |= modifier.flag();
+        dispatch(
+            page,
+            DispatchKeyEventType::RawKeyDown,
+            &modifier.key_def(),
+            active,
+            None,
+        )
+        .await?;
+    }
+
+    let flags = chord.modifier_flags();
+    let text = chord.text();
+    let down_type = if text.is_some() {
+        DispatchKeyEventType::KeyDown
+    } else {
+        DispatchKeyEventType::RawKeyDown
+    };
+    dispatch(page, down_type, &chord.key, flags, text).await?;
+    dispatch(page, DispatchKeyEventType::KeyUp, &chord.key, flags, None).await?;
+
+    for modifier in chord.modifiers.iter().rev() {
+        active &= !modifier.flag();
+        dispatch(
+            page,
+            DispatchKeyEventType::KeyUp,
+            &modifier.key_def(),
+            active,
+            None,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn dispatch(
+    page: &Page,
+    event_type: DispatchKeyEventType,
+    key: &KeyDef,
+    modifiers: i64,
+    text: Option<String>,
+) -> Result<()> {
+    let mut builder = DispatchKeyEventParams::builder()
+        .r#type(event_type)
+        .key(key.key.clone())
+        .code(key.code.clone())
+        .windows_virtual_key_code(key.key_code)
+        .native_virtual_key_code(key.key_code)
+        .modifiers(modifiers);
+    if let Some(text) = text {
+        builder = builder.text(text.clone()).unmodified_text(text);
+    }
+    let params = builder
+        .build()
+        .map_err(|e| anyhow!("Invalid key event: {}", e))?;
+    page.execute(params).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_named_keys() {
+        let chord = KeyChord::parse("Enter").unwrap();
+        assert!(chord.modifiers.is_empty());
+        assert_eq!(chord.key.key, "Enter");
+        assert_eq!(chord.key.key_code, 13);
+
+        assert_eq!(KeyChord::parse("arrowdown").unwrap().key.key, "ArrowDown");
+        assert_eq!(KeyChord::parse("F5").unwrap().key.key_code, 116);
+    }
+
+    #[test]
+    fn parses_modifier_chords() {
+        let chord = KeyChord::parse("Ctrl+Shift+t").unwrap();
+        assert_eq!(chord.modifiers, vec![Modifier::Ctrl, Modifier::Shift]);
+        assert_eq!(chord.key.code, "KeyT");
+        assert_eq!(chord.modifier_flags(), 2 | 8);
+        assert_eq!(chord.text(), None);
+
+        let plus = KeyChord::parse("Ctrl++").unwrap();
+        assert_eq!(plus.key.key, "+");
+    }
+
+    #[test]
+    fn shift_uppercases_text() {
+        let chord = KeyChord::parse("Shift+a").unwrap();
+        assert_eq!(chord.key.key, "A");
+        assert_eq!(chord.key.code, "KeyA");
+        assert_eq!(chord.text(), Some("A".to_string()));
+
+        let digit = KeyChord::parse("Shift+1").unwrap();
+        assert_eq!(digit.key.key, "!");
+        assert_eq!(digit.key.code, "Digit1");
+        assert_eq!(digit.text(), Some("!".to_string()));
+
+        let tab = KeyChord::parse("Shift+Tab").unwrap();
+        assert_eq!(tab.key.key, "Tab");
+        assert_eq!(tab.text(), None);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(KeyChord::parse("Hyper+A").is_err());
+        assert!(KeyChord::parse("NotAKey").is_err());
+        assert!(KeyChord::parse("").is_err());
+    }
+}
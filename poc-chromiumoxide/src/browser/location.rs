@@ -0,0 +1,140 @@
+use super::core::Browser;
+use super::permissions::PermissionState;
+use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    ClearGeolocationOverrideParams, SetGeolocationOverrideParams, SetTimezoneOverrideParams,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Emulated position reported to `navigator.geolocation`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Geolocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default = "default_accuracy")]
+    pub accuracy: f64,
+}
+
+fn default_accuracy() -> f64 {
+    100.0
+}
+
+impl Geolocation {
+    pub fn new(latitude: f64, longitude: f64, accuracy: Option<f64>) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(anyhow!("Latitude must be between -90 and 90: {}", latitude));
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(anyhow!(
+                "Longitude must be between -180 and 180: {}",
+                longitude
+            ));
+        }
+        let accuracy = accuracy.unwrap_or_else(default_accuracy);
+        if accuracy < 0.0 {
+            return Err(anyhow!("Accuracy cannot be negative: {}", accuracy));
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+            accuracy,
+        })
+    }
+}
+
+/// Check that a timezone looks like an IANA id ("Europe/Paris", "UTC")
+pub fn is_valid_timezone_id(tz: &str) -> bool {
+    if tz.is_empty() || tz.starts_with('/') || tz.ends_with('/') || tz.contains("//") {
+        return false;
+    }
+    tz.chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'))
+}
+
+impl Browser {
+    /// Override the geolocation reported to pages and grant them access to it
+    pub async fn set_geolocation(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        accuracy: Option<f64>,
+    ) -> Result<Geolocation> {
+        let location = Geolocation::new(latitude, longitude, accuracy)?;
+
+        // Without the permission the page would show a prompt instead of reading the position
+        if let Err(e) = self
+            .set_permission("geolocation", PermissionState::Granted, None)
+            .await
+        {
+            warn!("Failed to grant geolocation permission: {}", e);
+        }
+
+        let params = SetGeolocationOverrideParams::builder()
+            .latitude(location.latitude)
+            .longitude(location.longitude)
+            .accuracy(location.accuracy)
+            .build();
+        let page = self.page.read().await;
+        page.execute(params).await?;
+
+        info!(
+            "Geolocation set to {}, {} (±{}m)",
+            location.latitude, location.longitude, location.accuracy
+        );
+        Ok(location)
+    }
+
+    /// Remove the geolocation override
+    pub async fn clear_geolocation(&self) -> Result<()> {
+        let page = self.page.read().await;
+        page.execute(ClearGeolocationOverrideParams::default())
+            .await?;
+        info!("Geolocation override cleared");
+        Ok(())
+    }
+
+    /// Override the page timezone; an empty id restores the system timezone
+    pub async fn set_timezone(&self, timezone_id: &str) -> Result<()> {
+        if !timezone_id.is_empty() && !is_valid_timezone_id(timezone_id) {
+            return Err(anyhow!("Invalid timezone id: {}", timezone_id));
+        }
+        let page = self.page.read().await;
+        page.execute(SetTimezoneOverrideParams::new(timezone_id))
+            .await?;
+        info!(
+            "Timezone set to {}",
+            if timezone_id.is_empty() {
+                "system default"
+            } else {
+                timezone_id
+            }
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geolocation_validates_ranges() {
+        assert!(Geolocation::new(48.85, 2.35, None).is_ok());
+        assert!(Geolocation::new(91.0, 0.0, None).is_err());
+        assert!(Geolocation::new(0.0, -181.0, None).is_err());
+        assert!(Geolocation::new(0.0, 0.0, Some(-1.0)).is_err());
+        assert_eq!(Geolocation::new(0.0, 0.0, None).unwrap().accuracy, 100.0);
+    }
+
+    #[test]
+    fn timezone_ids() {
+        assert!(is_valid_timezone_id("Europe/Paris"));
+        assert!(is_valid_timezone_id("America/Argentina/Buenos_Aires"));
+        assert!(is_valid_timezone_id("UTC"));
+        assert!(is_valid_timezone_id("Etc/GMT+5"));
+        assert!(!is_valid_timezone_id("Europe//Paris"));
+        assert!(!is_valid_timezone_id("Europe/Paris; drop"));
+        assert!(!is_valid_timezone_id(""));
+    }
+}
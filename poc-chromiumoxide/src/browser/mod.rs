@@ -1,8 +1,52 @@
+pub mod accessibility;
+pub mod activity;
+pub mod affinity;
+pub mod console;
+pub mod contexts;
+pub mod cookies;
 pub mod core;
+pub mod dialogs;
+pub mod downloads;
+pub mod dropdowns;
+pub mod emulation;
+pub mod infinite_scroll;
+pub mod keyboard;
+pub mod location;
+pub mod mutations;
 pub mod navigation;
+pub mod performance;
+pub mod permissions;
 pub mod pool;
+pub mod profiles;
+pub mod recording;
 pub mod session;
+pub mod stability;
+pub mod storage;
+pub mod tabs;
+pub mod throttling;
 
 // Re-export main types
+pub use accessibility::AxElement;
+pub use activity::{ActivityKind, SessionActivity};
+pub use affinity::BrowserAffinity;
+pub use console::{ConsoleEntry, ConsoleLog, ConsoleSource};
 pub use core::{Browser, BrowserOps, ElementInfo, ScreenshotOptions};
-pub use session::SessionManager;
+pub use dialogs::{DialogEvent, DialogManager, DialogPolicy};
+pub use downloads::{DownloadInfo, DownloadManager, DownloadState};
+pub use dropdowns::{ChosenOption, DropdownKind, OptionChoice};
+pub use emulation::DevicePreset;
+pub use infinite_scroll::{ScrollOptions, ScrollReport};
+pub use keyboard::KeyChord;
+pub use location::Geolocation;
+pub use mutations::{DomChange, DomMutationTracker};
+pub use performance::{VitalRating, WebVitals};
+pub use permissions::PermissionState;
+pub use profiles::{BrowserProfile, ProfileStore};
+pub use recording::{ActionScript, RecordedAction, RecordedStep, RecordedWorkflow, ReplayStep};
+pub use session::{
+    ExpiryReason, SessionExpired, SessionLimits, SessionManager, SessionOptions, SessionSnapshot,
+};
+pub use stability::{SelectorRecord, SelectorStabilityTracker};
+pub use storage::{StorageArea, StorageSnapshot};
+pub use tabs::TabInfo;
+pub use throttling::{NetworkConditions, ThrottlingState};
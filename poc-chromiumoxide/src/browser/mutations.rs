@@ -0,0 +1,308 @@
+use super::core::Browser;
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::cdp::browser_protocol::page::AddScriptToEvaluateOnNewDocumentParams;
+use chromiumoxide::cdp::js_protocol::runtime::{AddBindingParams, EventBindingCalled};
+use chromiumoxide::Page;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, warn};
+
+const MUTATION_HISTORY_SIZE: usize = 200;
+
+/// Page-side function the observer reports through
+const MUTATION_BINDING: &str = "__rainbowDomChanged";
+
+/// Observes the document and reports significant changes through the binding in
+/// batches of at most one per 50ms. Whitespace, comments, scripts and styles are
+/// ignored, and only attributes that change what a user sees or can do are watched.
+const OBSERVER_SCRIPT: &str = r#"
+    (function() {
+        const report = window.__rainbowDomChanged;
+        if (window.__rainbowDomObserver || typeof report !== 'function') return false;
+        window.__rainbowDomObserver = true;
+        const watched = ['disabled', 'hidden', 'aria-hidden', 'aria-expanded', 'aria-busy', 'aria-selected',
+            'open', 'checked', 'selected', 'value', 'href', 'src'];
+        const ignored = node => node.nodeType === 1
+            ? /^(SCRIPT|STYLE|LINK|META|NOSCRIPT|TEMPLATE)$/.test(node.tagName)
+            : node.nodeType !== 3 || !node.textContent.trim();
+        const describe = node => {
+            const el = node.nodeType === 1 ? node : node.parentElement;
+            if (!el || !el.tagName) return null;
+            const id = el.getAttribute('id');
+            const className = typeof el.className === 'string' ? el.className.trim().split(/\s+/)[0] : '';
+            return el.tagName.toLowerCase() + (id ? '#' + id : className ? '.' + className : '');
+        };
+        let pending = { document: true, added: 0, removed: 0, attributes: 0, text: 0, targets: [] };
+        let scheduled = false;
+        const flush = () => {
+            scheduled = false;
+            const batch = pending;
+            pending = null;
+            report(JSON.stringify(batch));
+        };
+        const schedule = () => {
+            if (!scheduled) {
+                scheduled = true;
+                setTimeout(flush, 50);
+            }
+        };
+        new MutationObserver(records => {
+            let added = 0, removed = 0, attributes = 0, text = 0;
+            const targets = [];
+            for (const record of records) {
+                if (record.type === 'childList') {
+                    const a = Array.from(record.addedNodes).filter(n => !ignored(n)).length;
+                    const r = Array.from(record.removedNodes).filter(n => !ignored(n)).length;
+                    if (!a && !r) continue;
+                    added += a;
+                    removed += r;
+                } else if (record.type === 'attributes') {
+                    attributes++;
+                } else if (record.type === 'characterData') {
+                    if (ignored(record.target)) continue;
+                    text++;
+                }
+                const target = describe(record.target);
+                if (target) targets.push(target);
+            }
+            if (!added && !removed && !attributes && !text) return;
+            pending = pending || { document: false, added: 0, removed: 0, attributes: 0, text: 0, targets: [] };
+            pending.added += added;
+            pending.removed += removed;
+            pending.attributes += attributes;
+            pending.text += text;
+            for (const target of targets) {
+                if (pending.targets.length < 10 && !pending.targets.includes(target)) pending.targets.push(target);
+            }
+            schedule();
+        }).observe(document, {
+            childList: true,
+            subtree: true,
+            characterData: true,
+            attributes: true,
+            attributeFilter: watched
+        });
+        // The first report announces the new document
+        schedule();
+        return true;
+    })()
+"#;
+
+/// Batch of significant DOM changes reported by a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomChange {
+    /// A new document was loaded
+    #[serde(default)]
+    pub document: bool,
+    /// Element and non-empty text nodes inserted or removed
+    #[serde(default)]
+    pub added: u32,
+    #[serde(default)]
+    pub removed: u32,
+    /// Changes of watched attributes such as `disabled`, `hidden` or `aria-expanded`
+    #[serde(default)]
+    pub attributes: u32,
+    #[serde(default)]
+    pub text: u32,
+    /// Up to ten changed elements, as "tag#id" or "tag.class"
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default = "Utc::now")]
+    pub timestamp: DateTime<Utc>,
+}
+
+impl DomChange {
+    /// Whether elements appeared or disappeared, which can invalidate selectors
+    pub fn is_structural(&self) -> bool {
+        self.document || self.added > 0 || self.removed > 0
+    }
+}
+
+/// Tracks DOM changes of a browser's pages and broadcasts them as they arrive
+#[derive(Debug)]
+pub struct DomMutationTracker {
+    active: AtomicBool,
+    version: AtomicU64,
+    structure_version: AtomicU64,
+    last_change: RwLock<Instant>,
+    history: RwLock<VecDeque<DomChange>>,
+    events: broadcast::Sender<DomChange>,
+}
+
+impl DomMutationTracker {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            active: AtomicBool::new(false),
+            version: AtomicU64::new(0),
+            structure_version: AtomicU64::new(0),
+            last_change: RwLock::new(Instant::now()),
+            history: RwLock::new(VecDeque::with_capacity(MUTATION_HISTORY_SIZE)),
+            events,
+        }
+    }
+
+    /// Whether an observer is reporting; when false, versions never change
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Incremented with every reported change
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Relaxed)
+    }
+
+    /// Incremented when elements are added or removed or a new document loads
+    pub fn structure_version(&self) -> u64 {
+        self.structure_version.load(Ordering::Relaxed)
+    }
+
+    /// Time since the last reported change
+    pub async fn quiet_for(&self) -> Duration {
+        self.last_change.read().await.elapsed()
+    }
+
+    /// Most recent changes, oldest first
+    pub async fn recent(&self, limit: Option<usize>) -> Vec<DomChange> {
+        let history = self.history.read().await;
+        let skip = limit.map_or(0, |limit| history.len().saturating_sub(limit));
+        history.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DomChange> {
+        self.events.subscribe()
+    }
+
+    async fn record(&self, change: DomChange) {
+        self.version.fetch_add(1, Ordering::Relaxed);
+        if change.is_structural() {
+            self.structure_version.fetch_add(1, Ordering::Relaxed);
+        }
+        *self.last_change.write().await = Instant::now();
+        {
+            let mut history = self.history.write().await;
+            if history.len() >= MUTATION_HISTORY_SIZE {
+                history.pop_front();
+            }
+            history.push_back(change.clone());
+        }
+        // No receivers is fine
+        let _ = self.events.send(change);
+    }
+}
+
+impl Default for DomMutationTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Browser {
+    /// DOM changes observed on this browser's pages
+    pub fn dom_mutations(&self) -> Arc<DomMutationTracker> {
+        self.mutations.clone()
+    }
+
+    /// Install the mutation observer in `page`, now and in every document it loads
+    pub(crate) async fn attach_mutation_observer(&self, page: &Page) -> Result<()> {
+        let mut calls = page
+            .event_listener::<EventBindingCalled>()
+            .await
+            .context("Failed to subscribe to binding events")?;
+        page.execute(AddBindingParams::new(MUTATION_BINDING))
+            .await
+            .map_err(|e| anyhow!("Failed to add mutation binding: {}", e))?;
+        page.execute(AddScriptToEvaluateOnNewDocumentParams::new(OBSERVER_SCRIPT))
+            .await
+            .map_err(|e| anyhow!("Failed to install mutation observer: {}", e))?;
+        page.evaluate(OBSERVER_SCRIPT)
+            .await
+            .map_err(|e| anyhow!("Failed to start mutation observer: {}", e))?;
+
+        let tracker = self.mutations.clone();
+        tracker.active.store(true, Ordering::Relaxed);
+        tokio::spawn(async move {
+            while let Some(event) = calls.next().await {
+                if event.name != MUTATION_BINDING {
+                    continue;
+                }
+                match serde_json::from_str::<DomChange>(&event.payload) {
+                    Ok(change) => {
+                        debug!(
+                            "DOM change: +{} -{} attrs {} text {}",
+                            change.added, change.removed, change.attributes, change.text
+                        );
+                        tracker.record(change).await;
+                    }
+                    Err(e) => warn!("Malformed DOM change report: {}", e),
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Wait until no significant DOM change was reported for `quiet`, giving up after
+    /// `timeout`. Returns whether the page settled; without an observer it just
+    /// waits for `quiet` and returns false.
+    pub async fn wait_for_dom_settled(&self, quiet: Duration, timeout: Duration) -> bool {
+        if !self.mutations.is_active() {
+            tokio::time::sleep(quiet).await;
+            return false;
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let quiet_for = self.mutations.quiet_for().await;
+            if quiet_for >= quiet {
+                return true;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            let wait = (quiet - quiet_for).min(deadline - now);
+            tokio::time::sleep(wait.min(Duration::from_millis(100))).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_observer_reports() {
+        let change: DomChange =
+            serde_json::from_str(r#"{"added":3,"removed":0,"attributes":1,"targets":["ul#feed"]}"#)
+                .unwrap();
+        assert!(change.is_structural());
+        assert_eq!(change.targets, vec!["ul#feed"]);
+
+        let change: DomChange = serde_json::from_str(r#"{"attributes":2}"#).unwrap();
+        assert!(!change.is_structural());
+    }
+
+    #[tokio::test]
+    async fn versions_follow_recorded_changes() {
+        let tracker = DomMutationTracker::new();
+        let mut events = tracker.subscribe();
+        tracker
+            .record(serde_json::from_str(r#"{"document":true}"#).unwrap())
+            .await;
+        tracker
+            .record(serde_json::from_str(r#"{"text":4}"#).unwrap())
+            .await;
+
+        assert_eq!(tracker.version(), 2);
+        assert_eq!(tracker.structure_version(), 1);
+        assert!(events.recv().await.unwrap().document);
+        assert_eq!(tracker.recent(Some(1)).await[0].text, 4);
+        assert!(tracker.quiet_for().await < Duration::from_secs(1));
+    }
+}
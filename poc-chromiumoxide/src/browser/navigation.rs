@@ -338,25 +338,11 @@ impl Browser {
         Ok(())
     }
 
-    /// Emulate device (simplified version)
+    /// Emulate device by preset name (see `browser::emulation`)
     pub async fn emulate_device(&self, device_name: &str) -> Result<()> {
-        let page = self.page.read().await;
-
-        // Common device user agents
-        let user_agent = match device_name {
-            "iPhone 12" => {
-                "Mozilla/5.0 (iPhone; CPU iPhone OS 14_0 like Mac OS X) AppleWebKit/605.1.15"
-            }
-            "iPad" => "Mozilla/5.0 (iPad; CPU OS 14_0 like Mac OS X) AppleWebKit/605.1.15",
-            "Pixel 5" => "Mozilla/5.0 (Linux; Android 11; Pixel 5) AppleWebKit/537.36",
-            _ => {
-                warn!("Unknown device: {}, using default", device_name);
-                return Ok(());
-            }
-        };
-
-        page.set_user_agent(user_agent).await?;
-        info!("Emulating device: {}", device_name);
+        if let Err(e) = self.emulate_device_preset(device_name).await {
+            warn!("{}, using default", e);
+        }
         Ok(())
     }
 
@@ -365,21 +351,27 @@ impl Browser {
         let page = self.page.read().await;
 
         // Get performance metrics via JavaScript
-        let timing_result = page.evaluate(r#"
-            JSON.stringify({
+        let timing_result = page
+            .evaluate(
+                r#"
+            ({
                 loadTime: performance.timing.loadEventEnd - performance.timing.navigationStart,
                 domContentLoaded: performance.timing.domContentLoadedEventEnd - performance.timing.navigationStart,
                 responseTime: performance.timing.responseEnd - performance.timing.requestStart,
                 renderTime: performance.timing.domComplete - performance.timing.domLoading
             })
-        "#).await?;
+        "#,
+            )
+            .await?;
         let timing: serde_json::Value = timing_result.into_value()?;
+        drop(page);
 
         Ok(PageMetrics {
             load_time_ms: timing["loadTime"].as_u64().unwrap_or(0),
             dom_content_loaded_ms: timing["domContentLoaded"].as_u64().unwrap_or(0),
             response_time_ms: timing["responseTime"].as_u64().unwrap_or(0),
             render_time_ms: timing["renderTime"].as_u64().unwrap_or(0),
+            throttling: self.throttling().await,
         })
     }
 }
@@ -391,4 +383,6 @@ pub struct PageMetrics {
     pub dom_content_loaded_ms: u64,
     pub response_time_ms: u64,
     pub render_time_ms: u64,
+    /// Conditions the timings were measured under
+    pub throttling: super::throttling::ThrottlingState,
 }
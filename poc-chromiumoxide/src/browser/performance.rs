@@ -0,0 +1,210 @@
+use super::core::Browser;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Collects navigation timing plus Core Web Vitals from buffered performance entries.
+/// Observers are created with `buffered: true`, so entries recorded before this
+/// script ran are delivered too; the short wait lets them arrive.
+const VITALS_SCRIPT: &str = r#"
+new Promise((resolve) => {
+    const out = { lcp: null, cls: 0, fid: null, inp: null, fcp: null };
+    const observe = (type, onEntry, extra) => {
+        try {
+            new PerformanceObserver((list) => list.getEntries().forEach(onEntry))
+                .observe(Object.assign({ type, buffered: true }, extra || {}));
+        } catch (e) {}
+    };
+    observe('largest-contentful-paint', (e) => { out.lcp = e.renderTime || e.startTime; });
+    observe('layout-shift', (e) => { if (!e.hadRecentInput) out.cls += e.value; });
+    observe('first-input', (e) => { out.fid = e.processingStart - e.startTime; });
+    observe('event', (e) => {
+        if (e.interactionId) out.inp = Math.max(out.inp || 0, e.duration);
+    }, { durationThreshold: 16 });
+    const fcp = performance.getEntriesByName('first-contentful-paint')[0];
+    if (fcp) out.fcp = fcp.startTime;
+
+    setTimeout(() => {
+        const nav = performance.getEntriesByType('navigation')[0];
+        resolve(Object.assign(out, {
+            url: location.href,
+            ttfb: nav ? nav.responseStart : null,
+            domContentLoaded: nav ? nav.domContentLoadedEventEnd : null,
+            load: nav && nav.loadEventEnd > 0 ? nav.loadEventEnd : null
+        }));
+    }, 100);
+})
+"#;
+
+/// How a metric compares to the published Web Vitals thresholds, ordered best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VitalRating {
+    Good,
+    NeedsImprovement,
+    Poor,
+}
+
+impl VitalRating {
+    fn from_thresholds(value: f64, good: f64, poor: f64) -> Self {
+        if value <= good {
+            VitalRating::Good
+        } else if value <= poor {
+            VitalRating::NeedsImprovement
+        } else {
+            VitalRating::Poor
+        }
+    }
+}
+
+/// Navigation timing and Web Vitals of one page load. Times are in milliseconds
+/// from the start of navigation; a metric is None when the page has not produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebVitals {
+    pub url: String,
+    pub ttfb_ms: Option<f64>,
+    pub dom_content_loaded_ms: Option<f64>,
+    pub load_ms: Option<f64>,
+    pub fcp_ms: Option<f64>,
+    pub lcp_ms: Option<f64>,
+    pub cls: f64,
+    pub fid_ms: Option<f64>,
+    /// Slowest interaction so far, an approximation of INP
+    pub inp_ms: Option<f64>,
+    pub collected_at: DateTime<Utc>,
+}
+
+impl WebVitals {
+    pub fn lcp_rating(&self) -> Option<VitalRating> {
+        self.lcp_ms
+            .map(|v| VitalRating::from_thresholds(v, 2500.0, 4000.0))
+    }
+
+    pub fn cls_rating(&self) -> VitalRating {
+        VitalRating::from_thresholds(self.cls, 0.1, 0.25)
+    }
+
+    pub fn inp_rating(&self) -> Option<VitalRating> {
+        self.inp_ms
+            .map(|v| VitalRating::from_thresholds(v, 200.0, 500.0))
+    }
+
+    /// Worst rating among the metrics that were measured
+    pub fn overall_rating(&self) -> VitalRating {
+        [
+            self.lcp_rating(),
+            Some(self.cls_rating()),
+            self.inp_rating(),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(VitalRating::Good)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawVitals {
+    url: String,
+    ttfb: Option<f64>,
+    dom_content_loaded: Option<f64>,
+    load: Option<f64>,
+    fcp: Option<f64>,
+    lcp: Option<f64>,
+    cls: f64,
+    fid: Option<f64>,
+    inp: Option<f64>,
+}
+
+impl From<RawVitals> for WebVitals {
+    fn from(raw: RawVitals) -> Self {
+        Self {
+            url: raw.url,
+            ttfb_ms: raw.ttfb,
+            dom_content_loaded_ms: raw.dom_content_loaded,
+            load_ms: raw.load,
+            fcp_ms: raw.fcp,
+            lcp_ms: raw.lcp,
+            cls: raw.cls,
+            fid_ms: raw.fid,
+            inp_ms: raw.inp,
+            collected_at: Utc::now(),
+        }
+    }
+}
+
+impl Browser {
+    /// Measure navigation timing and Web Vitals of the active page
+    pub async fn collect_web_vitals(&self) -> Result<WebVitals> {
+        let page = self.page.read().await;
+        let raw: RawVitals = page.evaluate(VITALS_SCRIPT).await?.into_value()?;
+        Ok(raw.into())
+    }
+
+    /// JS heap in use by the active page, in bytes; None when Chromium does not report it
+    pub async fn js_heap_used(&self) -> Result<Option<u64>> {
+        let page = self.page.read().await;
+        Ok(page
+            .evaluate("performance.memory ? performance.memory.usedJSHeapSize : null")
+            .await?
+            .into_value()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vitals(lcp: Option<f64>, cls: f64, inp: Option<f64>) -> WebVitals {
+        WebVitals {
+            url: "https://example.com".to_string(),
+            ttfb_ms: None,
+            dom_content_loaded_ms: None,
+            load_ms: None,
+            fcp_ms: None,
+            lcp_ms: lcp,
+            cls,
+            fid_ms: None,
+            inp_ms: inp,
+            collected_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn rates_against_thresholds() {
+        assert_eq!(
+            vitals(Some(2000.0), 0.0, None).lcp_rating(),
+            Some(VitalRating::Good)
+        );
+        assert_eq!(
+            vitals(None, 0.2, None).cls_rating(),
+            VitalRating::NeedsImprovement
+        );
+        assert_eq!(
+            vitals(None, 0.0, Some(600.0)).inp_rating(),
+            Some(VitalRating::Poor)
+        );
+    }
+
+    #[test]
+    fn overall_rating_is_the_worst_measured() {
+        assert_eq!(vitals(None, 0.0, None).overall_rating(), VitalRating::Good);
+        assert_eq!(
+            vitals(Some(5000.0), 0.0, Some(100.0)).overall_rating(),
+            VitalRating::Poor
+        );
+    }
+
+    #[test]
+    fn parses_script_output() {
+        let raw: RawVitals = serde_json::from_value(serde_json::json!({
+            "url": "https://example.com/", "ttfb": 120.5, "domContentLoaded": 480.0,
+            "load": null, "fcp": 300.0, "lcp": 900.0, "cls": 0.02, "fid": null, "inp": null
+        }))
+        .unwrap();
+        let vitals = WebVitals::from(raw);
+        assert_eq!(vitals.ttfb_ms, Some(120.5));
+        assert!(vitals.load_ms.is_none());
+    }
+}
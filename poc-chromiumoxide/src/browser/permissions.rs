@@ -0,0 +1,142 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::browser::{
+    PermissionDescriptor, PermissionSetting, ResetPermissionsParams, SetPermissionParams,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// Permission names accepted by `Browser.setPermission`
+const KNOWN_PERMISSIONS: &[&str] = &[
+    "geolocation",
+    "notifications",
+    "camera",
+    "microphone",
+    "clipboard-read",
+    "clipboard-write",
+    "midi",
+    "push",
+    "background-sync",
+    "persistent-storage",
+];
+
+/// Whether a permission is granted, denied or left to prompt the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    Prompt,
+}
+
+impl From<PermissionState> for PermissionSetting {
+    fn from(state: PermissionState) -> Self {
+        match state {
+            PermissionState::Granted => PermissionSetting::Granted,
+            PermissionState::Denied => PermissionSetting::Denied,
+            PermissionState::Prompt => PermissionSetting::Prompt,
+        }
+    }
+}
+
+/// Expand a permission name into CDP permission names.
+/// "clipboard" covers both clipboard-read and clipboard-write.
+pub fn resolve_permission(name: &str) -> Result<Vec<&'static str>> {
+    let name = name.trim().to_lowercase().replace('_', "-");
+    if name == "clipboard" {
+        return Ok(vec!["clipboard-read", "clipboard-write"]);
+    }
+    KNOWN_PERMISSIONS
+        .iter()
+        .find(|p| **p == name)
+        .map(|p| vec![*p])
+        .ok_or_else(|| anyhow!("Unknown permission: {}", name))
+}
+
+impl Browser {
+    /// Set one permission, for a single origin or for every origin when `origin` is None
+    pub async fn set_permission(
+        &self,
+        name: &str,
+        state: PermissionState,
+        origin: Option<&str>,
+    ) -> Result<()> {
+        for permission in resolve_permission(name)? {
+            let mut params = SetPermissionParams::builder()
+                .permission(PermissionDescriptor::new(permission))
+                .setting(PermissionSetting::from(state));
+            if let Some(origin) = origin {
+                params = params.origin(origin);
+            }
+            let params = params
+                .build()
+                .map_err(|e| anyhow!("Invalid permission params: {}", e))?;
+            self.browser.execute(params).await?;
+        }
+
+        info!(
+            "Permission {} set to {:?} for {}",
+            name,
+            state,
+            origin.unwrap_or("all origins")
+        );
+        Ok(())
+    }
+
+    /// Apply several permissions at once. Names are validated before anything is changed.
+    pub async fn set_permissions(
+        &self,
+        permissions: &BTreeMap<String, PermissionState>,
+        origin: Option<&str>,
+    ) -> Result<()> {
+        for name in permissions.keys() {
+            resolve_permission(name)?;
+        }
+        for (name, state) in permissions {
+            self.set_permission(name, *state, origin).await?;
+        }
+        Ok(())
+    }
+
+    /// Restore default permission handling for all origins
+    pub async fn reset_permissions(&self) -> Result<()> {
+        self.browser
+            .execute(ResetPermissionsParams::default())
+            .await?;
+        info!("Permissions reset");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_permission_names() {
+        assert_eq!(
+            resolve_permission("Notifications").unwrap(),
+            vec!["notifications"]
+        );
+        assert_eq!(
+            resolve_permission("clipboard").unwrap(),
+            vec!["clipboard-read", "clipboard-write"]
+        );
+        assert_eq!(
+            resolve_permission("clipboard_write").unwrap(),
+            vec!["clipboard-write"]
+        );
+        assert!(resolve_permission("telepathy").is_err());
+    }
+
+    #[test]
+    fn permission_state_deserializes_lowercase() {
+        let map: BTreeMap<String, PermissionState> = serde_json::from_value(
+            serde_json::json!({"camera": "denied", "geolocation": "granted"}),
+        )
+        .unwrap();
+        assert_eq!(map["camera"], PermissionState::Denied);
+        assert_eq!(map["geolocation"], PermissionState::Granted);
+    }
+}
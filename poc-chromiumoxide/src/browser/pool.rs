@@ -1,17 +1,184 @@
 use super::core::Browser;
+use super::profiles::ProfileStore;
 use anyhow::{anyhow, Context, Result};
+use chromiumoxide::handler::viewport::Viewport;
 use chromiumoxide::BrowserConfig;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
 use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
+/// Browsers bound to named profiles. A profile directory can only be opened by
+/// one Chrome process, so each profile has at most one browser.
+#[derive(Default)]
+pub(crate) struct ProfileSlots {
+    idle: HashMap<String, Arc<Browser>>,
+    in_use: HashSet<String>,
+}
+
+/// Browsers available for reuse
+type BrowserList = Arc<RwLock<Vec<Arc<Browser>>>>;
+
+/// Time a pooled browser gets to answer a liveness probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of one health check pass over the idle browsers
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// Idle browsers probed
+    pub checked: usize,
+    /// Browsers that failed the probe and were killed
+    pub recycled: usize,
+    /// Fresh browsers launched into the shared pool in their place
+    pub replaced: usize,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Browser window size in CSS pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Chromium launch flags that can differ between acquires
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LaunchOptions {
+    /// Override the pool's headless mode
+    #[serde(default)]
+    pub headless: Option<bool>,
+    /// Window and page viewport size
+    #[serde(default)]
+    pub viewport: Option<WindowSize>,
+    /// Proxy server, e.g. "http://proxy:8080" or "socks5://127.0.0.1:1080"
+    #[serde(default)]
+    pub proxy: Option<String>,
+}
+
+impl LaunchOptions {
+    /// Whether these options launch the same browser as the pool defaults
+    fn is_default_for(&self, pool_headless: bool) -> bool {
+        self.headless.unwrap_or(pool_headless) == pool_headless
+            && self.viewport.is_none()
+            && self.proxy.is_none()
+    }
+
+    /// Identifies browsers launched with equivalent flags
+    fn key(&self, pool_headless: bool) -> String {
+        format!(
+            "headless={};viewport={:?};proxy={}",
+            self.headless.unwrap_or(pool_headless),
+            self.viewport.map(|v| (v.width, v.height)),
+            self.proxy.as_deref().unwrap_or("")
+        )
+    }
+}
+
+/// Configuration for a single `BrowserPool::acquire_with` call
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcquireConfig {
+    #[serde(flatten)]
+    pub launch: LaunchOptions,
+    /// Named profile to acquire; the launch options apply when its browser is started
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+/// Where the pool gets new browsers from
+#[derive(Debug, Clone)]
+pub enum BrowserProvider {
+    /// Launch local Chromium processes
+    Launch,
+    /// Attach to already-running browsers (CI containers, docker), used round-robin
+    Remote { endpoints: Vec<String> },
+}
+
 /// Browser pool for managing multiple browser instances
 pub struct BrowserPool {
-    browsers: Arc<RwLock<Vec<Arc<Browser>>>>,
+    browsers: BrowserList,
+    /// Idle browsers launched with non-default options, keyed by their launch flags
+    configured: Arc<RwLock<HashMap<String, BrowserList>>>,
     semaphore: Arc<Semaphore>,
     max_browsers: usize,
     config: BrowserConfig,
     headless: bool,
+    profiles: ProfileStore,
+    profile_slots: Arc<RwLock<ProfileSlots>>,
+    provider: BrowserProvider,
+    next_endpoint: AtomicUsize,
+    last_health: RwLock<Option<HealthReport>>,
+    /// Every browser this pool started, so shutdown can close those still checked out
+    launched: Mutex<Vec<Weak<Browser>>>,
+}
+
+/// Fresh user-data-dir so concurrently launched browsers do not conflict
+fn temp_user_data_dir() -> PathBuf {
+    let unique_id = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!("rainbow-browser-{}", unique_id))
+}
+
+/// Launch configuration shared by pooled and profile browsers
+fn launch_config(
+    headless: bool,
+    user_data_dir: &Path,
+    options: &LaunchOptions,
+) -> Result<BrowserConfig> {
+    let mut builder = if headless {
+        BrowserConfig::builder()
+            .arg("--headless")
+            .arg("--disable-gpu")
+            .arg("--disable-web-security")
+            .arg("--disable-features=VizDisplayCompositor")
+            .arg("--disable-dev-shm-usage")
+            .arg("--no-sandbox")
+            .arg(format!("--user-data-dir={}", user_data_dir.display()))
+            .arg("--new-window") // Force new window
+            .arg("--no-startup-window") // Don't restore previous session
+    } else {
+        // For headed mode, don't use .with_head() as it seems to cause issues
+        // Just create a browser without --headless flag, which makes it visible by default
+        BrowserConfig::builder()
+            .no_sandbox()
+            .arg("--disable-web-security")
+            .arg("--disable-features=VizDisplayCompositor")
+            .arg("--disable-gpu")
+            .arg("--disable-dev-shm-usage")
+            .arg("--disable-background-timer-throttling")
+            .arg("--disable-backgrounding-occluded-windows")
+            .arg("--disable-renderer-backgrounding")
+            .arg("--no-first-run")
+            .arg("--no-default-browser-check")
+            .arg("--disable-blink-features=AutomationControlled")
+            .arg(format!("--user-data-dir={}", user_data_dir.display()))
+            .arg("--new-window") // Force new window
+            .arg("--no-startup-window") // Don't restore previous session
+    };
+
+    if let Some(size) = options.viewport {
+        builder = builder
+            .window_size(size.width, size.height)
+            .viewport(Viewport {
+                width: size.width,
+                height: size.height,
+                ..Viewport::default()
+            });
+    }
+    if let Some(proxy) = &options.proxy {
+        builder = builder.arg(format!("--proxy-server={}", proxy));
+    }
+
+    builder
+        .build()
+        .map_err(|e| anyhow!("Failed to build browser config: {}", e))
 }
 
 impl BrowserPool {
@@ -29,47 +196,21 @@ impl BrowserPool {
             .as_nanos();
         let base_dir = std::env::temp_dir().join(format!("rainbow-pool-{}", pool_id));
 
-        let config = if headless {
-            BrowserConfig::builder()
-                .arg("--headless")
-                .arg("--disable-gpu")
-                .arg("--disable-web-security")
-                .arg("--disable-features=VizDisplayCompositor")
-                .arg("--disable-dev-shm-usage")
-                .arg("--no-sandbox")
-                .arg(format!("--user-data-dir={}", base_dir.display()))
-                .arg("--new-window")
-                .arg("--no-startup-window")
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build headless config: {}", e))?
-        } else {
-            // For headed mode, don't use .with_head() as it seems to cause issues
-            // Just create a browser without --headless flag, which makes it visible by default
-            BrowserConfig::builder()
-                .no_sandbox()
-                .arg("--disable-web-security")
-                .arg("--disable-features=VizDisplayCompositor")
-                .arg("--disable-gpu")
-                .arg("--disable-dev-shm-usage")
-                .arg("--disable-background-timer-throttling")
-                .arg("--disable-backgrounding-occluded-windows")
-                .arg("--disable-renderer-backgrounding")
-                .arg("--no-first-run")
-                .arg("--no-default-browser-check")
-                .arg("--disable-blink-features=AutomationControlled")
-                .arg(format!("--user-data-dir={}", base_dir.display()))
-                .arg("--new-window")
-                .arg("--no-startup-window")
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build headed config: {}", e))?
-        };
+        let config = launch_config(headless, &base_dir, &LaunchOptions::default())?;
 
         Ok(Self {
             browsers: Arc::new(RwLock::new(Vec::new())),
+            configured: Arc::new(RwLock::new(HashMap::new())),
             semaphore: Arc::new(Semaphore::new(max_browsers)),
             max_browsers,
             config,
             headless,
+            profiles: ProfileStore::new(),
+            profile_slots: Arc::new(RwLock::new(ProfileSlots::default())),
+            provider: BrowserProvider::Launch,
+            next_endpoint: AtomicUsize::new(0),
+            last_health: RwLock::new(None),
+            launched: Mutex::new(Vec::new()),
         })
     }
 
@@ -77,11 +218,56 @@ impl BrowserPool {
     pub fn with_config(max_browsers: usize, config: BrowserConfig) -> Self {
         Self {
             browsers: Arc::new(RwLock::new(Vec::new())),
+            configured: Arc::new(RwLock::new(HashMap::new())),
             semaphore: Arc::new(Semaphore::new(max_browsers)),
             max_browsers,
             config,
             headless: false, // Default to headed mode for custom config
+            profiles: ProfileStore::new(),
+            profile_slots: Arc::new(RwLock::new(ProfileSlots::default())),
+            provider: BrowserProvider::Launch,
+            next_endpoint: AtomicUsize::new(0),
+            last_health: RwLock::new(None),
+            launched: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create a pool that connects to remote CDP endpoints instead of launching Chromium.
+    /// Each endpoint is a `ws://` DevTools URL or an `http://host:port` debugging address.
+    pub fn with_remote_endpoints(max_browsers: usize, endpoints: Vec<String>) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("At least one remote CDP endpoint is required"));
+        }
+        let mut pool = Self::new_with_headless(max_browsers, true)?;
+        info!("Browser pool using remote endpoints: {:?}", endpoints);
+        pool.provider = BrowserProvider::Remote { endpoints };
+        Ok(pool)
+    }
+
+    /// Provider this pool gets its browsers from
+    pub fn provider(&self) -> &BrowserProvider {
+        &self.provider
+    }
+
+    /// Launch or connect a new browser according to the provider
+    async fn create_browser(&self, config: BrowserConfig) -> Result<Browser> {
+        match &self.provider {
+            BrowserProvider::Launch => Browser::new_with_config(config).await,
+            BrowserProvider::Remote { endpoints } => {
+                let index = self.next_endpoint.fetch_add(1, Ordering::Relaxed) % endpoints.len();
+                Browser::connect(&endpoints[index]).await
+            }
+        }
+    }
+
+    /// Wrap a newly started browser, remembering it for `shutdown`
+    fn track(&self, browser: Browser) -> Arc<Browser> {
+        let browser = Arc::new(browser);
+        if let Ok(mut launched) = self.launched.lock() {
+            launched.retain(|b| b.strong_count() > 0);
+            launched.push(Arc::downgrade(&browser));
         }
+        browser
     }
 
     /// Acquire a browser from the pool
@@ -110,8 +296,8 @@ impl BrowserPool {
             {
                 let mut browsers = self.browsers.write().await;
                 while let Some(browser) = browsers.pop() {
-                    // Check if the browser is still connected
-                    if browser.is_connected().await {
+                    // Check that the browser is alive, not just connected
+                    if browser.ping(PROBE_TIMEOUT).await {
                         info!(
                             "Reusing existing browser from pool (attempt {})",
                             attempt + 1
@@ -119,11 +305,13 @@ impl BrowserPool {
                         return Ok(BrowserGuard {
                             browser,
                             pool: self.browsers.clone(),
+                            profile: None,
                             _permit: permit,
                         });
                     } else {
-                        warn!("Discarding disconnected browser from pool");
-                        // Browser is dead, discard it and try the next one
+                        warn!("Discarding unresponsive browser from pool");
+                        // Browser is dead, kill it and try the next one
+                        tokio::spawn(browser.kill());
                     }
                 }
             }
@@ -160,48 +348,20 @@ impl BrowserPool {
             let temp_dir = std::env::temp_dir().join(format!("rainbow-browser-{}", unique_id));
 
             // Rebuild config with unique user-data-dir and ensure new instance
-            let config_with_unique_dir = if self.headless {
-                BrowserConfig::builder()
-                    .arg("--headless")
-                    .arg("--disable-gpu")
-                    .arg("--disable-web-security")
-                    .arg("--disable-features=VizDisplayCompositor")
-                    .arg("--disable-dev-shm-usage")
-                    .arg("--no-sandbox")
-                    .arg(format!("--user-data-dir={}", temp_dir.display()))
-                    .arg("--new-window") // Force new window
-                    .arg("--no-startup-window") // Don't restore previous session
-                    .build()
-                    .unwrap_or(self.config.clone())
-            } else {
-                BrowserConfig::builder()
-                    .no_sandbox()
-                    .arg("--disable-web-security")
-                    .arg("--disable-features=VizDisplayCompositor")
-                    .arg("--disable-gpu")
-                    .arg("--disable-dev-shm-usage")
-                    .arg("--disable-background-timer-throttling")
-                    .arg("--disable-backgrounding-occluded-windows")
-                    .arg("--disable-renderer-backgrounding")
-                    .arg("--no-first-run")
-                    .arg("--no-default-browser-check")
-                    .arg("--disable-blink-features=AutomationControlled")
-                    .arg(format!("--user-data-dir={}", temp_dir.display()))
-                    .arg("--new-window") // Force new window
-                    .arg("--no-startup-window") // Don't restore previous session
-                    .build()
-                    .unwrap_or(self.config.clone())
-            };
+            let config_with_unique_dir =
+                launch_config(self.headless, &temp_dir, &LaunchOptions::default())
+                    .unwrap_or(self.config.clone());
 
-            match Browser::new_with_config(config_with_unique_dir).await {
+            match self.create_browser(config_with_unique_dir).await {
                 Ok(browser) => {
                     info!("Successfully created new browser for pool");
                     // Test the new browser is actually connected
-                    let browser_arc = Arc::new(browser);
+                    let browser_arc = self.track(browser);
                     if browser_arc.is_connected().await {
                         return Ok(BrowserGuard {
                             browser: browser_arc,
                             pool: self.browsers.clone(),
+                            profile: None,
                             _permit: permit,
                         });
                     } else {
@@ -236,6 +396,175 @@ impl BrowserPool {
         Err(last_error.unwrap_or_else(|| anyhow!("Failed to create browser after {} retries", 3)))
     }
 
+    /// Acquire a browser launched with specific options.
+    ///
+    /// Requests matching the pool defaults are served from the shared pool. Other
+    /// configurations get browsers of their own, which are reused only by later
+    /// acquires with the same launch options.
+    pub async fn acquire_with(&self, config: AcquireConfig) -> Result<BrowserGuard> {
+        if let Some(name) = &config.profile {
+            return self.acquire_profile_with(name, &config.launch).await;
+        }
+        if config.launch.is_default_for(self.headless) {
+            return self.acquire().await;
+        }
+        if let BrowserProvider::Remote { .. } = self.provider {
+            return Err(anyhow!(
+                "Launch options require locally launched browsers; this pool uses remote endpoints"
+            ));
+        }
+
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .context("Failed to acquire semaphore permit")?;
+
+        let key = config.launch.key(self.headless);
+        let idle = self
+            .configured
+            .write()
+            .await
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        {
+            let mut browsers = idle.write().await;
+            while let Some(browser) = browsers.pop() {
+                if browser.ping(PROBE_TIMEOUT).await {
+                    info!("Reusing configured browser ({})", key);
+                    return Ok(BrowserGuard {
+                        browser,
+                        pool: idle.clone(),
+                        profile: None,
+                        _permit: permit,
+                    });
+                }
+                warn!("Discarding unresponsive configured browser");
+                tokio::spawn(browser.kill());
+            }
+        }
+
+        let headless = config.launch.headless.unwrap_or(self.headless);
+        let launch = launch_config(headless, &temp_user_data_dir(), &config.launch)?;
+
+        info!("Launching configured browser ({})", key);
+        let browser = Browser::new_with_config(launch)
+            .await
+            .context("Failed to launch configured browser")?;
+        Ok(BrowserGuard {
+            browser: self.track(browser),
+            pool: idle,
+            profile: None,
+            _permit: permit,
+        })
+    }
+
+    /// Acquire the browser bound to a named profile, launching it if needed.
+    ///
+    /// The profile is created on first use. Its browser is kept out of the shared
+    /// pool and can only be held by one guard at a time.
+    pub async fn acquire_profile(&self, name: &str) -> Result<BrowserGuard> {
+        self.acquire_profile_with(name, &LaunchOptions::default())
+            .await
+    }
+
+    async fn acquire_profile_with(
+        &self,
+        name: &str,
+        options: &LaunchOptions,
+    ) -> Result<BrowserGuard> {
+        if let BrowserProvider::Remote { .. } = self.provider {
+            return Err(anyhow!(
+                "Profiles require locally launched browsers; this pool uses remote endpoints"
+            ));
+        }
+        let profile = self.profiles.get_or_create(name)?;
+
+        {
+            let mut slots = self.profile_slots.write().await;
+            if slots.in_use.contains(name) {
+                return Err(anyhow!("Profile '{}' is already in use", name));
+            }
+            slots.in_use.insert(name.to_string());
+        }
+        let acquired = async {
+            let permit = self
+                .semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .context("Failed to acquire semaphore permit")?;
+
+            let idle = self.profile_slots.write().await.idle.remove(name);
+            let browser = match idle {
+                Some(browser) if browser.ping(PROBE_TIMEOUT).await => {
+                    info!("Reusing browser for profile {}", name);
+                    browser
+                }
+                _ => {
+                    info!(
+                        "Launching browser for profile {} ({})",
+                        name,
+                        profile.path.display()
+                    );
+                    let headless = options.headless.unwrap_or(self.headless);
+                    let config = launch_config(headless, &profile.path, options)?;
+                    self.track(
+                        Browser::new_with_config(config)
+                            .await
+                            .with_context(|| format!("Failed to launch profile {}", name))?,
+                    )
+                }
+            };
+            Ok::<_, anyhow::Error>((browser, permit))
+        }
+        .await;
+
+        match acquired {
+            Ok((browser, permit)) => Ok(BrowserGuard {
+                browser,
+                pool: self.browsers.clone(),
+                profile: Some(ProfileLease {
+                    name: name.to_string(),
+                    slots: self.profile_slots.clone(),
+                }),
+                _permit: permit,
+            }),
+            Err(e) => {
+                self.profile_slots.write().await.in_use.remove(name);
+                Err(e)
+            }
+        }
+    }
+
+    /// Named profile storage used by this pool
+    pub fn profiles(&self) -> &ProfileStore {
+        &self.profiles
+    }
+
+    /// Whether a profile's browser is currently held by a session
+    pub async fn profile_in_use(&self, name: &str) -> bool {
+        self.profile_slots.read().await.in_use.contains(name)
+    }
+
+    /// Close a profile's idle browser and delete its data
+    pub async fn delete_profile(&self, name: &str) -> Result<()> {
+        if self.profile_in_use(name).await {
+            return Err(anyhow!("Profile '{}' is in use", name));
+        }
+        if let Some(browser) = self.profile_slots.write().await.idle.remove(name) {
+            browser.close().await.ok();
+        }
+        self.profiles.delete(name)
+    }
+
+    /// Browsers that can still be acquired without waiting for a release
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
     /// Get the current pool size
     pub async fn size(&self) -> usize {
         self.browsers.read().await.len()
@@ -245,9 +574,38 @@ impl BrowserPool {
     pub async fn clear(&self) {
         let mut browsers = self.browsers.write().await;
         browsers.clear();
+        self.configured.write().await.clear();
         info!("Browser pool cleared");
     }
 
+    /// Stop handing out browsers and close every Chromium process this pool started.
+    ///
+    /// Pending and future `acquire` calls fail afterwards. Browsers attached through
+    /// remote endpoints are only released, never closed.
+    pub async fn shutdown(&self) -> usize {
+        self.semaphore.close();
+        self.clear().await;
+        self.profile_slots.write().await.idle.clear();
+
+        if let BrowserProvider::Remote { .. } = self.provider {
+            return 0;
+        }
+        let launched: Vec<Arc<Browser>> = match self.launched.lock() {
+            Ok(mut launched) => launched.drain(..).filter_map(|b| b.upgrade()).collect(),
+            Err(_) => Vec::new(),
+        };
+        let mut closed = 0;
+        for browser in launched {
+            match tokio::time::timeout(PROBE_TIMEOUT, browser.close_process()).await {
+                Ok(Ok(())) => closed += 1,
+                Ok(Err(e)) => warn!("Failed to close browser during shutdown: {}", e),
+                Err(_) => warn!("Browser did not close in time during shutdown"),
+            }
+        }
+        info!("Browser pool shut down, closed {} browsers", closed);
+        closed
+    }
+
     /// Clean up disconnected browsers from the pool
     pub async fn cleanup_disconnected(&self) -> usize {
         let mut browsers = self.browsers.write().await;
@@ -274,6 +632,130 @@ impl BrowserPool {
         removed_count
     }
 
+    /// Probe every idle browser and recycle the ones that do not answer.
+    ///
+    /// Browsers are taken out of their lists while probed so they cannot be handed
+    /// out mid-check. Dead shared browsers are replaced to keep the pool warm.
+    pub async fn check_health(&self) -> HealthReport {
+        let mut lists = vec![self.browsers.clone()];
+        lists.extend(self.configured.read().await.values().cloned());
+
+        let mut checked = 0;
+        let mut recycled = 0;
+        let mut replaced = 0;
+        for (index, list) in lists.iter().enumerate() {
+            let idle: Vec<_> = list.write().await.drain(..).collect();
+            checked += idle.len();
+            let probes =
+                futures::future::join_all(idle.iter().map(|b| b.ping(PROBE_TIMEOUT))).await;
+
+            let mut dead = 0;
+            for (browser, alive) in idle.into_iter().zip(probes) {
+                if alive {
+                    list.write().await.push(browser);
+                } else {
+                    dead += 1;
+                    browser.kill().await;
+                }
+            }
+            recycled += dead;
+
+            // Only the shared pool is refilled; configured browsers are launched on demand
+            if index == 0 {
+                replaced += self.replace_browsers(dead).await;
+            }
+        }
+
+        let dead_profiles: Vec<_> = {
+            let idle: Vec<_> = self
+                .profile_slots
+                .read()
+                .await
+                .idle
+                .iter()
+                .map(|(name, browser)| (name.clone(), browser.clone()))
+                .collect();
+            let mut dead = Vec::new();
+            for (name, browser) in idle {
+                checked += 1;
+                if !browser.ping(PROBE_TIMEOUT).await {
+                    dead.push(name);
+                }
+            }
+            dead
+        };
+        for name in dead_profiles {
+            if let Some(browser) = self.profile_slots.write().await.idle.remove(&name) {
+                warn!("Recycling unresponsive browser for profile {}", name);
+                recycled += 1;
+                browser.kill().await;
+            }
+        }
+
+        let report = HealthReport {
+            checked,
+            recycled,
+            replaced,
+            checked_at: Utc::now(),
+        };
+        if recycled > 0 {
+            warn!(
+                "Pool health check recycled {} of {} browsers ({} replaced)",
+                recycled, checked, replaced
+            );
+        }
+        *self.last_health.write().await = Some(report.clone());
+        report
+    }
+
+    /// Result of the most recent health check
+    pub async fn last_health(&self) -> Option<HealthReport> {
+        self.last_health.read().await.clone()
+    }
+
+    /// Run `check_health` every `interval` until the returned task is aborted
+    pub fn start_health_checks(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        info!("Pool health checks every {:?}", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                self.check_health().await;
+            }
+        })
+    }
+
+    async fn replace_browsers(&self, count: usize) -> usize {
+        let mut replaced = 0;
+        for _ in 0..count {
+            let config = match launch_config(
+                self.headless,
+                &temp_user_data_dir(),
+                &LaunchOptions::default(),
+            ) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to build replacement browser config: {}", e);
+                    break;
+                }
+            };
+            match self.create_browser(config).await {
+                Ok(browser) => {
+                    let browser = self.track(browser);
+                    self.browsers.write().await.push(browser);
+                    replaced += 1;
+                }
+                Err(e) => {
+                    error!("Failed to launch replacement browser: {}", e);
+                    break;
+                }
+            }
+        }
+        replaced
+    }
+
     /// Preload browsers into the pool
     pub async fn preload(&self, count: usize) -> Result<()> {
         let count = count.min(self.max_browsers);
@@ -281,9 +763,9 @@ impl BrowserPool {
 
         let mut browsers = Vec::new();
         for i in 0..count {
-            match Browser::new_with_config(self.config.clone()).await {
+            match self.create_browser(self.config.clone()).await {
                 Ok(browser) => {
-                    browsers.push(Arc::new(browser));
+                    browsers.push(self.track(browser));
                     info!("Preloaded browser {}/{}", i + 1, count);
                 }
                 Err(e) => {
@@ -299,10 +781,17 @@ impl BrowserPool {
     }
 }
 
+/// A profile browser checked out of the pool
+pub(crate) struct ProfileLease {
+    name: String,
+    slots: Arc<RwLock<ProfileSlots>>,
+}
+
 /// Guard for automatically returning browsers to the pool
 pub struct BrowserGuard {
     browser: Arc<Browser>,
-    pool: Arc<RwLock<Vec<Arc<Browser>>>>,
+    pool: BrowserList,
+    profile: Option<ProfileLease>,
     _permit: tokio::sync::OwnedSemaphorePermit,
 }
 
@@ -316,27 +805,48 @@ impl BrowserGuard {
     pub fn browser_arc(&self) -> Arc<Browser> {
         Arc::clone(&self.browser)
     }
+
+    /// Name of the profile this browser is bound to, if any
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_ref().map(|lease| lease.name.as_str())
+    }
+}
+
+/// Put a released browser back where it came from
+async fn release_browser(browser: Arc<Browser>, pool: BrowserList, profile: Option<ProfileLease>) {
+    let connected = browser.is_connected().await;
+
+    if let Some(lease) = profile {
+        let mut slots = lease.slots.write().await;
+        if connected {
+            slots.idle.insert(lease.name.clone(), browser);
+        }
+        slots.in_use.remove(&lease.name);
+        info!("Browser for profile {} released", lease.name);
+        return;
+    }
+
+    // Check if browser is still connected before returning to pool
+    if connected {
+        // Don't navigate to about:blank - keep the current page
+        // This allows tools to maintain state between operations
+        let mut browsers = pool.write().await;
+        browsers.push(browser);
+        info!("Browser returned to pool successfully");
+    } else {
+        warn!("Not returning disconnected browser to pool");
+    }
 }
 
 impl Drop for BrowserGuard {
     fn drop(&mut self) {
         let browser = self.browser.clone();
         let pool = self.pool.clone();
+        let profile = self.profile.take();
 
         // Return browser to pool immediately with better error handling
         if let Ok(rt) = tokio::runtime::Handle::try_current() {
-            rt.spawn(async move {
-                // Check if browser is still connected before returning to pool
-                if browser.is_connected().await {
-                    // Don't navigate to about:blank - keep the current page
-                    // This allows tools to maintain state between operations
-                    let mut browsers = pool.write().await;
-                    browsers.push(browser);
-                    info!("Browser returned to pool successfully");
-                } else {
-                    warn!("Not returning disconnected browser to pool");
-                }
-            });
+            rt.spawn(release_browser(browser, pool, profile));
         } else {
             // Fallback - try to return browser using a blocking approach
             let rt = match tokio::runtime::Runtime::new() {
@@ -348,16 +858,7 @@ impl Drop for BrowserGuard {
             };
 
             let _ = std::thread::spawn(move || {
-                rt.block_on(async move {
-                    // Check if browser is still connected before returning to pool
-                    if browser.is_connected().await {
-                        let mut browsers = pool.write().await;
-                        browsers.push(browser);
-                        info!("Browser returned to pool successfully (fallback)");
-                    } else {
-                        warn!("Not returning disconnected browser to pool (fallback)");
-                    }
-                });
+                rt.block_on(release_browser(browser, pool, profile));
             });
         }
     }
@@ -370,3 +871,40 @@ impl std::ops::Deref for BrowserGuard {
         &self.browser
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launch_options_match_pool_defaults() {
+        assert!(LaunchOptions::default().is_default_for(true));
+        let headed = LaunchOptions {
+            headless: Some(false),
+            ..LaunchOptions::default()
+        };
+        assert!(headed.is_default_for(false));
+        assert!(!headed.is_default_for(true));
+        assert_ne!(headed.key(true), LaunchOptions::default().key(true));
+    }
+
+    #[test]
+    fn acquire_config_deserializes_flat() {
+        let config: AcquireConfig = serde_json::from_value(serde_json::json!({
+            "headless": false,
+            "viewport": {"width": 1280, "height": 720},
+            "proxy": "http://proxy:8080",
+            "profile": "work"
+        }))
+        .unwrap();
+        assert_eq!(config.launch.headless, Some(false));
+        assert_eq!(
+            config.launch.viewport,
+            Some(WindowSize {
+                width: 1280,
+                height: 720
+            })
+        );
+        assert_eq!(config.profile.as_deref(), Some("work"));
+    }
+}
@@ -0,0 +1,169 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::info;
+
+const PROFILE_META_FILE: &str = "rainbow-profile.json";
+
+/// A named Chrome user-data-dir that persists cookies, storage and extensions across runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserProfile {
+    pub name: String,
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Named profiles stored as directories under one root
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    root: PathBuf,
+}
+
+impl ProfileStore {
+    /// Create a store using `RAINBOW_PROFILE_DIR`, falling back to `~/.rainbow/profiles`
+    pub fn new() -> Self {
+        let root = std::env::var("RAINBOW_PROFILE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                std::env::var("HOME")
+                    .map(|home| PathBuf::from(home).join(".rainbow").join("profiles"))
+                    .unwrap_or_else(|_| std::env::temp_dir().join("rainbow-profiles"))
+            });
+        Self::with_root(root)
+    }
+
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    /// Get a profile, creating its directory on first use
+    pub fn get_or_create(&self, name: &str) -> Result<BrowserProfile> {
+        if let Some(profile) = self.get(name)? {
+            return Ok(profile);
+        }
+
+        let path = self.root.join(name);
+        std::fs::create_dir_all(&path)
+            .with_context(|| format!("Failed to create profile directory {}", path.display()))?;
+        let profile = BrowserProfile {
+            name: name.to_string(),
+            path: path.clone(),
+            created_at: Utc::now(),
+        };
+        std::fs::write(
+            path.join(PROFILE_META_FILE),
+            serde_json::to_vec_pretty(&profile)?,
+        )?;
+
+        info!("Created browser profile {} at {}", name, path.display());
+        Ok(profile)
+    }
+
+    /// Look up an existing profile
+    pub fn get(&self, name: &str) -> Result<Option<BrowserProfile>> {
+        validate_profile_name(name)?;
+        let meta = self.root.join(name).join(PROFILE_META_FILE);
+        if !meta.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read(&meta)?;
+        Ok(Some(serde_json::from_slice(&data)?))
+    }
+
+    /// All profiles, sorted by name
+    pub fn list(&self) -> Result<Vec<BrowserProfile>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut profiles = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if let Some(name) = entry.file_name().to_str() {
+                if let Ok(Some(profile)) = self.get(name) {
+                    profiles.push(profile);
+                }
+            }
+        }
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+
+    /// Delete a profile and all of its browser data
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let profile = self
+            .get(name)?
+            .ok_or_else(|| anyhow!("Profile not found: {}", name))?;
+        std::fs::remove_dir_all(&profile.path)
+            .with_context(|| format!("Failed to delete profile {}", name))?;
+        info!("Deleted browser profile {}", name);
+        Ok(())
+    }
+}
+
+impl Default for ProfileStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Profile names become directory names, so keep them to a safe character set
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Invalid profile name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> ProfileStore {
+        let root =
+            std::env::temp_dir().join(format!("rainbow-profiles-test-{}", uuid::Uuid::new_v4()));
+        ProfileStore::with_root(root)
+    }
+
+    #[test]
+    fn rejects_unsafe_names() {
+        assert!(validate_profile_name("work-account").is_ok());
+        assert!(validate_profile_name("../etc").is_err());
+        assert!(validate_profile_name("a/b").is_err());
+        assert!(validate_profile_name(".hidden").is_err());
+        assert!(validate_profile_name("").is_err());
+    }
+
+    #[test]
+    fn create_list_delete_roundtrip() {
+        let store = temp_store();
+        assert!(store.list().unwrap().is_empty());
+
+        let created = store.get_or_create("shopping").unwrap();
+        assert!(created.path.is_dir());
+        let again = store.get_or_create("shopping").unwrap();
+        assert_eq!(again.created_at, created.created_at);
+
+        let names: Vec<_> = store.list().unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["shopping"]);
+
+        store.delete("shopping").unwrap();
+        assert!(store.get("shopping").unwrap().is_none());
+        std::fs::remove_dir_all(store.root()).ok();
+    }
+}
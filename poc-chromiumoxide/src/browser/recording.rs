@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// A browser action performed in a session, stored as the tool call that repeats it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedAction {
+    pub tool: String,
+    pub parameters: Value,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl RecordedAction {
+    pub fn new(tool: &str, parameters: Value) -> Self {
+        Self {
+            tool: tool.to_string(),
+            parameters,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Navigation made outside the tool registry, replayed with `navigate_to_url`
+    pub fn navigate(url: &str) -> Self {
+        Self::new("navigate_to_url", json!({ "url": url }))
+    }
+
+    /// Tool call repeating a step the AI executed from a task plan, for steps
+    /// that have a tool equivalent
+    pub fn from_plan_step(
+        action_type: &str,
+        target: Option<&str>,
+        value: Option<&str>,
+    ) -> Option<Self> {
+        let action = match (action_type, target, value) {
+            ("navigate", Some(url), _) => Self::navigate(url),
+            ("click", Some(selector), _) => Self::new("click", json!({ "selector": selector })),
+            ("type", Some(selector), Some(text)) => {
+                Self::new("type_text", json!({ "selector": selector, "text": text }))
+            }
+            ("wait_for_element", Some(selector), _) => {
+                Self::new("wait_for_element", json!({ "selector": selector }))
+            }
+            ("extract", Some(selector), _) => {
+                Self::new("extract_text", json!({ "selector": selector }))
+            }
+            ("screenshot", _, _) => Self::new("screenshot", json!({})),
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// Replayable script of the successful actions of a session, in execution order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionScript {
+    pub session_id: String,
+    pub actions: Vec<RecordedAction>,
+}
+
+impl ActionScript {
+    /// Editable workflow of the recording: repeated navigations are dropped and
+    /// typed text becomes named inputs, blank for password fields
+    pub fn to_workflow(&self, name: &str) -> RecordedWorkflow {
+        let mut inputs = BTreeMap::new();
+        let mut steps: Vec<RecordedStep> = Vec::new();
+
+        for action in &self.actions {
+            let mut parameters = action.parameters.clone();
+            if action.tool == "navigate_to_url" {
+                let previous = steps.last().filter(|step| step.tool == "navigate_to_url");
+                if previous.is_some_and(|step| step.parameters["url"] == parameters["url"]) {
+                    continue;
+                }
+            }
+            if action.tool == "type_text" {
+                let selector = parameters["selector"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                let text = parameters["text"].as_str().unwrap_or_default().to_string();
+                let input = unique_input_name(&selector, &inputs);
+                let secret = selector.to_lowercase().contains("password");
+                inputs.insert(input.clone(), if secret { String::new() } else { text });
+                parameters["text"] = Value::String(format!("{{{{{}}}}}", input));
+            }
+
+            steps.push(RecordedStep {
+                name: describe_step(&action.tool, &parameters),
+                tool: action.tool.clone(),
+                parameters,
+            });
+        }
+
+        RecordedWorkflow {
+            name: name.to_string(),
+            description: Some(format!("Recorded from session {}", self.session_id)),
+            inputs,
+            steps,
+        }
+    }
+}
+
+/// Workflow generated from a session recording, meant to be edited and replayed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedWorkflow {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Values referenced from step parameters as `{{name}}`, with their recorded defaults
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub inputs: BTreeMap<String, String>,
+    pub steps: Vec<RecordedStep>,
+}
+
+/// One tool call of a recorded workflow
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedStep {
+    pub name: String,
+    pub tool: String,
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+impl RecordedWorkflow {
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).context("Failed to serialize workflow to YAML")
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml).context("Failed to parse workflow YAML")
+    }
+
+    /// Actions to replay, with `{{input}}` placeholders filled from `overrides`
+    /// or else the workflow's defaults
+    pub fn actions(&self, overrides: &HashMap<String, String>) -> Vec<RecordedAction> {
+        let mut values = self.inputs.clone();
+        values.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        self.steps
+            .iter()
+            .map(|step| {
+                let mut parameters = step.parameters.clone();
+                fill_placeholders(&mut parameters, &values);
+                RecordedAction::new(&step.tool, parameters)
+            })
+            .collect()
+    }
+}
+
+/// Input name derived from the last identifier of a selector, e.g. `#email` or
+/// `input[name='q']`, suffixed when already taken
+fn unique_input_name(selector: &str, taken: &BTreeMap<String, String>) -> String {
+    let base = selector
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .rfind(|word| {
+            !word.is_empty() && !matches!(*word, "input" | "textarea" | "name" | "id" | "type")
+        })
+        .map(|word| word.replace('-', "_").to_lowercase())
+        .unwrap_or_else(|| "text".to_string());
+
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while taken.contains_key(&name) {
+        name = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+fn describe_step(tool: &str, parameters: &Value) -> String {
+    let selector = parameters["selector"].as_str().unwrap_or_default();
+    match tool {
+        "navigate_to_url" => format!("Open {}", parameters["url"].as_str().unwrap_or_default()),
+        "click" => format!("Click {}", selector),
+        "type_text" => format!(
+            "Type {} into {}",
+            parameters["text"].as_str().unwrap_or_default(),
+            selector
+        ),
+        "select_option" => format!("Select option in {}", selector),
+        "wait_for_element" => format!("Wait for {}", selector),
+        "extract_text" => format!("Extract text from {}", selector),
+        _ if !selector.is_empty() => format!("{} {}", tool, selector),
+        _ => tool.to_string(),
+    }
+}
+
+fn fill_placeholders(value: &mut Value, inputs: &BTreeMap<String, String>) {
+    match value {
+        Value::String(text) => {
+            for (name, input) in inputs {
+                *text = text.replace(&format!("{{{{{}}}}}", name), input);
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| fill_placeholders(item, inputs)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|item| fill_placeholders(item, inputs)),
+        _ => {}
+    }
+}
+
+/// Outcome of one action during a replay
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    pub index: usize,
+    pub tool: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn navigation_replays_through_navigate_tool() {
+        let action = RecordedAction::navigate("https://example.com");
+        assert_eq!(action.tool, "navigate_to_url");
+        assert_eq!(action.parameters, json!({"url": "https://example.com"}));
+    }
+
+    #[test]
+    fn script_round_trips_through_json() {
+        let script = ActionScript {
+            session_id: "s1".to_string(),
+            actions: vec![
+                RecordedAction::navigate("https://example.com"),
+                RecordedAction::new("click", json!({"selector": "#buy"})),
+            ],
+        };
+        let parsed: ActionScript =
+            serde_json::from_str(&serde_json::to_string(&script).unwrap()).unwrap();
+        assert_eq!(parsed.actions, script.actions);
+    }
+
+    #[test]
+    fn recording_becomes_editable_workflow() {
+        let script = ActionScript {
+            session_id: "s1".to_string(),
+            actions: vec![
+                RecordedAction::navigate("https://example.com/login"),
+                RecordedAction::navigate("https://example.com/login"),
+                RecordedAction::new(
+                    "type_text",
+                    json!({"selector": "#email", "text": "me@example.com"}),
+                ),
+                RecordedAction::new(
+                    "type_text",
+                    json!({"selector": "input[name='password']", "text": "hunter2"}),
+                ),
+                RecordedAction::new("click", json!({"selector": "button[type=submit]"})),
+            ],
+        };
+
+        let workflow = script.to_workflow("login");
+        assert_eq!(workflow.steps.len(), 4);
+        assert_eq!(workflow.steps[1].name, "Type {{email}} into #email");
+        assert_eq!(workflow.inputs["email"], "me@example.com");
+        assert_eq!(workflow.inputs["password"], "");
+
+        let yaml = workflow.to_yaml().unwrap();
+        assert!(!yaml.contains("hunter2"));
+        let parsed = RecordedWorkflow::from_yaml(&yaml).unwrap();
+        assert_eq!(parsed, workflow);
+
+        let overrides = HashMap::from([("password".to_string(), "s3cret".to_string())]);
+        let actions = parsed.actions(&overrides);
+        assert_eq!(actions[1].parameters["text"], "me@example.com");
+        assert_eq!(actions[2].parameters["text"], "s3cret");
+        assert_eq!(actions[3].tool, "click");
+    }
+}
@@ -1,14 +1,44 @@
+use super::activity::{ActivityKind, SessionActivity};
+use super::cookies::cookie_to_param;
 use super::core::Browser;
-use super::pool::{BrowserGuard, BrowserPool};
+use super::performance::WebVitals;
+use super::pool::{AcquireConfig, BrowserGuard, BrowserPool, LaunchOptions};
+use super::recording::{ActionScript, RecordedAction};
+use super::storage::StorageSnapshot;
 use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::network::CookieParam;
 use chromiumoxide::BrowserConfig;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::info;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
 use uuid::Uuid;
 
+tokio::task_local! {
+    /// Tenant the current task acts for
+    static TENANT: Option<String>;
+}
+
+/// Run `future` on behalf of `tenant`: sessions it creates belong to the tenant and
+/// sessions of other tenants are invisible to it. Outside any tenant scope, e.g. in
+/// background cleanup, every session is visible.
+pub async fn with_tenant<F: Future>(tenant: Option<String>, future: F) -> F::Output {
+    TENANT.scope(tenant, future).await
+}
+
+/// Tenant of the current task; `None` outside a tenant scope or for the default tenant
+pub fn current_tenant() -> Option<String> {
+    TENANT.try_with(|tenant| tenant.clone()).ok().flatten()
+}
+
+/// Whether the current task may see a session, snapshot or activity of `tenant`
+pub fn visible(tenant: &Option<String>) -> bool {
+    TENANT.try_with(|current| current == tenant).unwrap_or(true)
+}
+
 /// Browser session for stateful operations
 #[derive(Clone)]
 pub struct BrowserSession {
@@ -19,30 +49,179 @@ pub struct BrowserSession {
     pub metadata: HashMap<String, String>,
     pub current_url: Option<String>,
     pub history: Vec<String>,
+    /// Web Vitals of recent navigations, oldest first
+    pub performance: Vec<WebVitals>,
+    /// User-assigned labels such as owner, purpose or workflow id
+    pub tags: HashMap<String, String>,
+    pub limits: SessionLimits,
+    /// Navigations made through `navigate`, including a refused one past the limit
+    pub navigation_count: u32,
+    /// Successful actions in execution order, replayable with the tool registry
+    pub actions: Vec<RecordedAction>,
+    /// Whether successful actions are added to `actions`; on unless recording was stopped
+    pub recording: bool,
+    /// Persona set at creation, sent as the system prompt of the session's LLM calls
+    pub system_prompt: Option<String>,
+    /// Subject of the authenticated principal that created the session
+    pub owner: Option<String>,
+    /// Tenant the session belongs to; only that tenant's requests can see it
+    pub tenant: Option<String>,
+}
+
+/// Navigations whose performance is kept per session
+const PERFORMANCE_HISTORY_SIZE: usize = 50;
+
+/// Per-session limits set at creation; unset limits fall back to the manager's defaults
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SessionLimits {
+    /// Maximum lifetime from creation, regardless of activity
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Idle timeout replacing the manager-wide session timeout
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_navigations: Option<u32>,
+    /// Maximum JS heap of the session's page
+    #[serde(default)]
+    pub max_memory_mb: Option<u64>,
+}
+
+impl SessionLimits {
+    /// Limit, other than memory, that a session with these stats has exceeded
+    pub fn exceeded(
+        &self,
+        age_secs: i64,
+        idle_secs: i64,
+        navigations: u32,
+        default_idle_secs: i64,
+    ) -> Option<ExpiryReason> {
+        if self.ttl_secs.is_some_and(|ttl| age_secs > ttl as i64) {
+            return Some(ExpiryReason::Ttl);
+        }
+        if self.max_navigations.is_some_and(|max| navigations > max) {
+            return Some(ExpiryReason::NavigationLimit);
+        }
+        let idle_timeout = self
+            .idle_timeout_secs
+            .map(|secs| secs as i64)
+            .unwrap_or(default_idle_secs);
+        if idle_secs > idle_timeout {
+            return Some(ExpiryReason::Idle);
+        }
+        None
+    }
+}
+
+/// Why a session was ended by the manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryReason {
+    Idle,
+    Ttl,
+    NavigationLimit,
+    MemoryLimit,
+}
+
+impl ExpiryReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpiryReason::Idle => "idle",
+            ExpiryReason::Ttl => "ttl",
+            ExpiryReason::NavigationLimit => "navigation_limit",
+            ExpiryReason::MemoryLimit => "memory_limit",
+        }
+    }
+}
+
+/// Published by the manager whenever it ends a session on its own
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionExpired {
+    pub session_id: String,
+    pub reason: ExpiryReason,
+    pub tags: HashMap<String, String>,
 }
 
 impl BrowserSession {
     /// Create a new browser session using browser pool
     pub async fn from_pool(browser_pool: &BrowserPool) -> Result<(Self, BrowserGuard)> {
+        Self::from_pool_with_profile(browser_pool, None).await
+    }
+
+    /// Create a new browser session, bound to a persistent profile when one is given
+    pub async fn from_pool_with_profile(
+        browser_pool: &BrowserPool,
+        profile: Option<&str>,
+    ) -> Result<(Self, BrowserGuard)> {
+        let config = AcquireConfig {
+            profile: profile.map(str::to_string),
+            ..AcquireConfig::default()
+        };
+        Self::from_pool_with(browser_pool, config).await
+    }
+
+    /// Create a new browser session from a browser acquired with explicit launch options
+    pub async fn from_pool_with(
+        browser_pool: &BrowserPool,
+        config: AcquireConfig,
+    ) -> Result<(Self, BrowserGuard)> {
         let id = Uuid::new_v4().to_string();
-        let browser_guard = browser_pool.acquire().await?;
+        let profile = config.profile.clone();
+        let browser_guard = browser_pool.acquire_with(config).await?;
         let browser = browser_guard.browser_arc();
 
-        info!("Created new browser session from pool: {}", id);
+        let mut metadata = HashMap::new();
+        if let Some(name) = &profile {
+            metadata.insert("profile".to_string(), name.to_string());
+            info!("Created new browser session {} with profile {}", id, name);
+        } else {
+            info!("Created new browser session from pool: {}", id);
+        }
 
         let session = Self {
             id: id.clone(),
             browser,
             created_at: Utc::now(),
             last_used: Utc::now(),
-            metadata: HashMap::new(),
+            metadata,
             current_url: None,
             history: Vec::new(),
+            performance: Vec::new(),
+            tags: HashMap::new(),
+            limits: SessionLimits::default(),
+            navigation_count: 0,
+            actions: Vec::new(),
+            recording: true,
+            system_prompt: None,
+            owner: None,
+            tenant: None,
         };
 
         Ok((session, browser_guard))
     }
 
+    /// Create a session around an existing browser handle
+    pub fn from_browser(browser: Arc<Browser>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            browser,
+            created_at: Utc::now(),
+            last_used: Utc::now(),
+            metadata: HashMap::new(),
+            current_url: None,
+            history: Vec::new(),
+            performance: Vec::new(),
+            tags: HashMap::new(),
+            limits: SessionLimits::default(),
+            navigation_count: 0,
+            actions: Vec::new(),
+            recording: true,
+            system_prompt: None,
+            owner: None,
+            tenant: None,
+        }
+    }
+
     /// Create a new browser session (deprecated - use from_pool instead)
     #[deprecated(note = "Use from_pool() to reuse browsers from pool")]
     pub async fn new() -> Result<Self> {
@@ -59,6 +238,15 @@ impl BrowserSession {
             metadata: HashMap::new(),
             current_url: None,
             history: Vec::new(),
+            performance: Vec::new(),
+            tags: HashMap::new(),
+            limits: SessionLimits::default(),
+            navigation_count: 0,
+            actions: Vec::new(),
+            recording: true,
+            system_prompt: None,
+            owner: None,
+            tenant: None,
         })
     }
 
@@ -77,6 +265,15 @@ impl BrowserSession {
             metadata: HashMap::new(),
             current_url: None,
             history: Vec::new(),
+            performance: Vec::new(),
+            tags: HashMap::new(),
+            limits: SessionLimits::default(),
+            navigation_count: 0,
+            actions: Vec::new(),
+            recording: true,
+            system_prompt: None,
+            owner: None,
+            tenant: None,
         })
     }
 
@@ -88,6 +285,16 @@ impl BrowserSession {
     /// Navigate and track history
     pub async fn navigate(&mut self, url: &str) -> Result<()> {
         self.touch();
+        self.navigation_count += 1;
+        if let Some(max) = self.limits.max_navigations {
+            if self.navigation_count > max {
+                return Err(anyhow::anyhow!(
+                    "Session {} reached its limit of {} navigations",
+                    self.id,
+                    max
+                ));
+            }
+        }
         self.browser.navigate_to(url).await?;
 
         // Update history
@@ -95,11 +302,29 @@ impl BrowserSession {
             self.history.push(current.clone());
         }
         self.current_url = Some(url.to_string());
+        self.record(RecordedAction::navigate(url));
 
         info!("Session {} navigated to: {}", self.id, url);
+        self.record_performance().await;
         Ok(())
     }
 
+    /// Measure the current page and add it to the session's performance history
+    pub async fn record_performance(&mut self) {
+        match self.browser.collect_web_vitals().await {
+            Ok(vitals) => {
+                if self.performance.len() >= PERFORMANCE_HISTORY_SIZE {
+                    self.performance.remove(0);
+                }
+                self.performance.push(vitals);
+            }
+            Err(e) => warn!(
+                "Failed to collect Web Vitals for session {}: {}",
+                self.id, e
+            ),
+        }
+    }
+
     /// Go back in history
     pub async fn go_back(&mut self) -> Result<()> {
         self.touch();
@@ -124,6 +349,18 @@ impl BrowserSession {
         }
     }
 
+    /// Add a successful tool call to the session's recording
+    pub fn record_action(&mut self, tool: &str, parameters: serde_json::Value) {
+        self.record(RecordedAction::new(tool, parameters));
+    }
+
+    /// Add an action to the session's recording unless recording is stopped
+    pub fn record(&mut self, action: RecordedAction) {
+        if self.recording {
+            self.actions.push(action);
+        }
+    }
+
     /// Get session age in seconds
     pub fn age_seconds(&self) -> i64 {
         (Utc::now() - self.created_at).num_seconds()
@@ -148,6 +385,58 @@ impl BrowserSession {
     pub fn get_metadata(&self, key: &str) -> Option<&String> {
         self.metadata.get(key)
     }
+
+    /// Whether the session carries every tag in `filter`; a filter without a value
+    /// only requires the key
+    pub fn matches_tags(&self, filter: &[(String, Option<String>)]) -> bool {
+        filter
+            .iter()
+            .all(|(key, value)| match (self.tags.get(key), value) {
+                (Some(tag), Some(value)) => tag == value,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+    }
+}
+
+/// How a new session gets its browser
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    /// Persistent browser profile to use. Profile sessions always get a dedicated browser.
+    pub profile: Option<String>,
+    /// Run the session in its own incognito context on a shared browser process
+    pub isolated: bool,
+    /// Launch flags for the session's browser. Non-default options always get a
+    /// dedicated browser, since contexts share their host's flags.
+    pub launch: LaunchOptions,
+    /// Initial tags of the session
+    pub tags: HashMap<String, String>,
+    pub limits: SessionLimits,
+    /// Persona for the session's LLM calls, e.g. "you are a cautious QA bot"
+    pub system_prompt: Option<String>,
+    /// Authenticated principal creating the session
+    pub owner: Option<String>,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            isolated: true,
+            launch: LaunchOptions::default(),
+            tags: HashMap::new(),
+            limits: SessionLimits::default(),
+            system_prompt: None,
+            owner: None,
+        }
+    }
+}
+
+impl SessionOptions {
+    /// Whether the session runs in an incognito context on the shared host browser
+    pub fn uses_context(&self) -> bool {
+        self.isolated && self.profile.is_none() && self.launch == LaunchOptions::default()
+    }
 }
 
 /// Session manager for managing multiple browser sessions
@@ -155,6 +444,11 @@ pub struct SessionManager {
     sessions: Arc<RwLock<HashMap<String, Arc<RwLock<BrowserSession>>>>>,
     browser_guards: Arc<RwLock<HashMap<String, BrowserGuard>>>,
     browser_pool: Arc<BrowserPool>,
+    /// Browser process hosting the isolated contexts of all isolated sessions
+    context_host: Arc<RwLock<Option<BrowserGuard>>>,
+    snapshots: Arc<RwLock<HashMap<String, SessionSnapshot>>>,
+    expiry_events: broadcast::Sender<SessionExpired>,
+    activity: broadcast::Sender<SessionActivity>,
     max_sessions: usize,
     session_timeout: i64, // seconds
 }
@@ -166,6 +460,10 @@ impl SessionManager {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             browser_guards: Arc::new(RwLock::new(HashMap::new())),
             browser_pool,
+            context_host: Arc::new(RwLock::new(None)),
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
+            expiry_events: broadcast::channel(64).0,
+            activity: broadcast::channel(256).0,
             max_sessions,
             session_timeout,
         }
@@ -173,6 +471,11 @@ impl SessionManager {
 
     /// Create a new session
     pub async fn create_session(&self) -> Result<String> {
+        self.create_session_with(SessionOptions::default()).await
+    }
+
+    /// Create a new session with explicit browser options
+    pub async fn create_session_with(&self, options: SessionOptions) -> Result<String> {
         // Clean up expired sessions first
         self.cleanup_expired().await;
 
@@ -187,15 +490,38 @@ impl SessionManager {
             }
         }
 
-        // Create new session using browser pool
-        let (session, browser_guard) = BrowserSession::from_pool(&self.browser_pool).await?;
+        let tags = options.tags.clone();
+        let limits = options.limits.clone();
+        let system_prompt = options.system_prompt.clone();
+        let owner = options.owner.clone();
+        let (mut session, browser_guard) = if options.uses_context() {
+            let browser = self.isolated_browser().await?;
+            let mut session = BrowserSession::from_browser(Arc::new(browser));
+            session.set_metadata("isolated".to_string(), "true".to_string());
+            (session, None)
+        } else {
+            let config = AcquireConfig {
+                launch: options.launch,
+                profile: options.profile,
+            };
+            let (session, guard) =
+                BrowserSession::from_pool_with(&self.browser_pool, config).await?;
+            (session, Some(guard))
+        };
+        session.tags = tags;
+        session.limits = limits;
+        session.system_prompt = system_prompt;
+        session.owner = owner;
+        session.tenant = current_tenant();
         let session_id = session.id.clone();
 
         // Store session and its browser guard
         let mut sessions = self.sessions.write().await;
         let mut browser_guards = self.browser_guards.write().await;
         sessions.insert(session_id.clone(), Arc::new(RwLock::new(session)));
-        browser_guards.insert(session_id.clone(), browser_guard);
+        if let Some(browser_guard) = browser_guard {
+            browser_guards.insert(session_id.clone(), browser_guard);
+        }
 
         info!(
             "Created session: {} (total: {})",
@@ -205,18 +531,43 @@ impl SessionManager {
         Ok(session_id)
     }
 
+    /// Create a fresh incognito context on the shared host browser, launching it if needed
+    async fn isolated_browser(&self) -> Result<Browser> {
+        let mut host = self.context_host.write().await;
+        if let Some(guard) = host.as_ref() {
+            if !guard.is_connected().await {
+                warn!("Context host browser disconnected, acquiring a new one");
+                *host = None;
+            }
+        }
+        if host.is_none() {
+            *host = Some(self.browser_pool.acquire().await?);
+        }
+
+        let guard = host
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No context host browser"))?;
+        guard.new_isolated_context().await
+    }
+
     /// Get a session by ID
     pub async fn get_session(&self, session_id: &str) -> Option<Arc<RwLock<BrowserSession>>> {
-        let sessions = self.sessions.read().await;
-        sessions.get(session_id).cloned()
+        let session = self.sessions.read().await.get(session_id).cloned()?;
+        let is_visible = visible(&session.read().await.tenant);
+        is_visible.then_some(session)
     }
 
     /// Remove a session
     pub async fn remove_session(&self, session_id: &str) -> Result<()> {
+        if self.get_session(session_id).await.is_none() {
+            return Err(anyhow::anyhow!("Session not found: {}", session_id));
+        }
         let mut sessions = self.sessions.write().await;
         let mut browser_guards = self.browser_guards.write().await;
 
-        if sessions.remove(session_id).is_some() {
+        if let Some(session) = sessions.remove(session_id) {
+            // Discard an isolated session's context along with its cookies and storage
+            Self::dispose_context(&session).await;
             // Also remove the browser guard (this returns the browser to the pool)
             browser_guards.remove(session_id);
             info!(
@@ -236,24 +587,35 @@ impl SessionManager {
         let mut browser_guards = self.browser_guards.write().await;
         let _initial_count = sessions.len();
 
-        let expired_ids: Vec<String> = {
+        let expired: Vec<(String, ExpiryReason)> = {
             let mut expired = Vec::new();
             for (id, session) in sessions.iter() {
                 let session_guard = session.read().await;
-                if session_guard.is_expired(self.session_timeout) {
-                    expired.push(id.clone());
+                if let Some(reason) =
+                    Self::expiry_reason(&session_guard, self.session_timeout).await
+                {
+                    expired.push((id.clone(), reason));
                 }
             }
             expired
         };
 
-        for id in &expired_ids {
-            sessions.remove(id);
+        for (id, reason) in &expired {
+            if let Some(session) = sessions.remove(id) {
+                Self::dispose_context(&session).await;
+                let tags = session.read().await.tags.clone();
+                // Nobody listening is fine
+                let _ = self.expiry_events.send(SessionExpired {
+                    session_id: id.clone(),
+                    reason: *reason,
+                    tags,
+                });
+            }
             browser_guards.remove(id); // Return browser to pool
-            info!("Cleaned up expired session: {}", id);
+            info!("Cleaned up expired session: {} ({})", id, reason.as_str());
         }
 
-        let removed_count = expired_ids.len();
+        let removed_count = expired.len();
         if removed_count > 0 {
             info!(
                 "Cleaned up {} expired sessions (remaining: {})",
@@ -265,13 +627,79 @@ impl SessionManager {
         removed_count
     }
 
+    /// Limit a session has exceeded, checking its page's memory only when capped
+    async fn expiry_reason(
+        session: &BrowserSession,
+        default_idle_secs: i64,
+    ) -> Option<ExpiryReason> {
+        let exceeded = session.limits.exceeded(
+            session.age_seconds(),
+            session.idle_seconds(),
+            session.navigation_count,
+            default_idle_secs,
+        );
+        if exceeded.is_some() {
+            return exceeded;
+        }
+        let max_mb = session.limits.max_memory_mb?;
+        match session.browser.js_heap_used().await {
+            Ok(Some(bytes)) if bytes > max_mb * 1024 * 1024 => Some(ExpiryReason::MemoryLimit),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Could not read memory of session {}: {}", session.id, e);
+                None
+            }
+        }
+    }
+
+    /// Notifications of sessions the manager ended because of a timeout or limit
+    pub fn subscribe_expiry(&self) -> broadcast::Receiver<SessionExpired> {
+        self.expiry_events.subscribe()
+    }
+
+    /// Publish an action performed in a session to its activity subscribers
+    pub fn record_activity(&self, session_id: &str, kind: ActivityKind, detail: serde_json::Value) {
+        let mut activity = SessionActivity::new(session_id, kind, detail);
+        activity.tenant = current_tenant();
+        activity.correlation_id = crate::coordination::correlation::current_correlation_id();
+        // Nobody listening is fine
+        let _ = self.activity.send(activity);
+    }
+
+    /// Actions performed in any session; subscribers filter by session id
+    pub fn subscribe_activity(&self) -> broadcast::Receiver<SessionActivity> {
+        self.activity.subscribe()
+    }
+
+    /// Run `cleanup_expired` every `interval` until the returned task is aborted
+    pub fn start_cleanup(
+        self: Arc<Self>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.cleanup_expired().await;
+            }
+        })
+    }
+
     /// Get all active sessions
     pub async fn list_sessions(&self) -> Vec<SessionInfo> {
+        self.find_sessions(&[]).await
+    }
+
+    /// Get the active sessions carrying every tag in `filter`
+    pub async fn find_sessions(&self, filter: &[(String, Option<String>)]) -> Vec<SessionInfo> {
         let sessions = self.sessions.read().await;
         let mut session_list = Vec::new();
 
         for (id, session) in sessions.iter() {
             let session_guard = session.read().await;
+            if !visible(&session_guard.tenant) || !session_guard.matches_tags(filter) {
+                continue;
+            }
             session_list.push(SessionInfo {
                 id: id.clone(),
                 created_at: session_guard.created_at,
@@ -279,12 +707,41 @@ impl SessionManager {
                 current_url: session_guard.current_url.clone(),
                 age_seconds: session_guard.age_seconds(),
                 idle_seconds: session_guard.idle_seconds(),
+                tags: session_guard.tags.clone(),
+                owner: session_guard.owner.clone(),
+                tenant: session_guard.tenant.clone(),
             });
         }
 
         session_list
     }
 
+    /// Add or overwrite tags of a session, returning all of its tags
+    pub async fn set_tags(
+        &self,
+        session_id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<HashMap<String, String>> {
+        let session_arc = self
+            .get_session(session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+        let mut session = session_arc.write().await;
+        session.tags.extend(tags);
+        Ok(session.tags.clone())
+    }
+
+    /// Remove one tag of a session, returning the remaining tags
+    pub async fn remove_tag(&self, session_id: &str, key: &str) -> Result<HashMap<String, String>> {
+        let session_arc = self
+            .get_session(session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+        let mut session = session_arc.write().await;
+        session.tags.remove(key);
+        Ok(session.tags.clone())
+    }
+
     /// Get session count
     pub async fn session_count(&self) -> usize {
         let sessions = self.sessions.read().await;
@@ -295,9 +752,226 @@ impl SessionManager {
     pub async fn clear_all(&self) {
         let mut sessions = self.sessions.write().await;
         let count = sessions.len();
+        for session in sessions.values() {
+            Self::dispose_context(session).await;
+        }
         sessions.clear();
         info!("Cleared all {} sessions", count);
     }
+
+    async fn dispose_context(session: &Arc<RwLock<BrowserSession>>) {
+        let browser = session.read().await.browser.clone();
+        if let Err(e) = browser.dispose_context().await {
+            warn!("Failed to dispose session context: {}", e);
+        }
+    }
+
+    /// Capture a session's URL, history and cookies so it can be restored later.
+    /// The snapshot is also kept by the manager under the session id.
+    pub async fn save_session(&self, session_id: &str) -> Result<SessionSnapshot> {
+        let session_arc = self
+            .get_session(session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+        let session = session_arc.read().await;
+
+        let mut cookies = Vec::new();
+        for cookie in session.browser.get_all_cookies().await? {
+            match cookie_to_param(&cookie) {
+                Ok(param) => cookies.push(param),
+                Err(e) => warn!("Skipping cookie while saving session: {}", e),
+            }
+        }
+
+        // Pages without an origin (about:blank) have no storage to keep
+        let storage = match session.browser.export_storage().await {
+            Ok(storage) if !storage.is_empty() => Some(storage),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Skipping web storage while saving session: {}", e);
+                None
+            }
+        };
+
+        let snapshot = SessionSnapshot {
+            session_id: session.id.clone(),
+            current_url: session.current_url.clone(),
+            history: session.history.clone(),
+            metadata: session.metadata.clone(),
+            tags: session.tags.clone(),
+            actions: session.actions.clone(),
+            system_prompt: session.system_prompt.clone(),
+            owner: session.owner.clone(),
+            tenant: session.tenant.clone(),
+            cookies,
+            storage,
+            saved_at: Utc::now(),
+        };
+
+        self.snapshots
+            .write()
+            .await
+            .insert(session_id.to_string(), snapshot.clone());
+        info!(
+            "Saved session {} ({} cookies)",
+            session_id,
+            snapshot.cookies.len()
+        );
+        Ok(snapshot)
+    }
+
+    /// Recorded actions of a session, falling back to its saved snapshot once closed
+    pub async fn recording(&self, session_id: &str) -> Option<ActionScript> {
+        let actions = match self.get_session(session_id).await {
+            Some(session) => session.read().await.actions.clone(),
+            None => self.get_snapshot(session_id).await?.actions,
+        };
+        Some(ActionScript {
+            session_id: session_id.to_string(),
+            actions,
+        })
+    }
+
+    /// Start recording afresh, dropping earlier actions, or stop recording
+    /// while keeping what was recorded so far
+    pub async fn set_recording(&self, session_id: &str, recording: bool) -> Result<()> {
+        let session = self
+            .get_session(session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+        let mut session = session.write().await;
+        if recording {
+            session.actions.clear();
+        }
+        session.recording = recording;
+        info!(
+            "Recording {} for session {}",
+            if recording { "started" } else { "stopped" },
+            session_id
+        );
+        Ok(())
+    }
+
+    /// Get the last saved snapshot of a session, if any
+    pub async fn get_snapshot(&self, session_id: &str) -> Option<SessionSnapshot> {
+        self.snapshots
+            .read()
+            .await
+            .get(session_id)
+            .filter(|snapshot| visible(&snapshot.tenant))
+            .cloned()
+    }
+
+    /// Save every open session, then write all snapshots to `path` so they survive
+    /// a restart. The file is replaced atomically.
+    pub async fn persist_snapshots(&self, path: &Path) -> Result<usize> {
+        let ids: Vec<String> = self.sessions.read().await.keys().cloned().collect();
+        for id in &ids {
+            if let Err(e) = self.save_session(id).await {
+                warn!("Could not save session {} before persisting: {}", id, e);
+            }
+        }
+
+        let snapshots = self.snapshots.read().await;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_vec_pretty(&*snapshots)?)?;
+        std::fs::rename(&partial, path)?;
+        info!(
+            "Persisted {} session snapshots to {}",
+            snapshots.len(),
+            path.display()
+        );
+        Ok(snapshots.len())
+    }
+
+    /// Load snapshots written by `persist_snapshots`; a missing file loads nothing
+    pub async fn load_snapshots(&self, path: &Path) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let data = std::fs::read(path)?;
+        let loaded: HashMap<String, SessionSnapshot> = serde_json::from_slice(&data)?;
+        let count = loaded.len();
+        self.snapshots.write().await.extend(loaded);
+        info!("Loaded {} session snapshots from {}", count, path.display());
+        Ok(count)
+    }
+
+    /// Create a new session from a snapshot, restoring cookies before navigating
+    pub async fn restore_session(&self, snapshot: &SessionSnapshot) -> Result<String> {
+        let session_id = self.create_session().await?;
+        let session_arc = self
+            .get_session(&session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Restored session disappeared: {}", session_id))?;
+
+        let mut session = session_arc.write().await;
+        session
+            .browser
+            .set_cookies(snapshot.cookies.clone())
+            .await?;
+        if let Some(url) = &snapshot.current_url {
+            session.navigate(url).await?;
+            // Storage is per origin, so it can only be seeded once the page is loaded;
+            // reload so the app starts from the restored state
+            if let Some(storage) = &snapshot.storage {
+                match session.browser.import_storage(storage).await {
+                    Ok(_) => session.browser.refresh().await?,
+                    Err(e) => warn!("Could not restore web storage: {}", e),
+                }
+            }
+        }
+        session.history = snapshot.history.clone();
+        session.metadata = snapshot.metadata.clone();
+        session.tags = snapshot.tags.clone();
+        session.actions = snapshot.actions.clone();
+        session.system_prompt = snapshot.system_prompt.clone();
+        session.owner = snapshot.owner.clone();
+
+        info!(
+            "Restored session {} from snapshot of {}",
+            session_id, snapshot.session_id
+        );
+        Ok(session_id)
+    }
+}
+
+/// File session snapshots are persisted to on shutdown: `RAINBOW_SESSION_FILE`,
+/// falling back to `~/.rainbow/sessions.json`
+pub fn snapshot_file() -> PathBuf {
+    std::env::var("RAINBOW_SESSION_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            std::env::var("HOME")
+                .map(|home| PathBuf::from(home).join(".rainbow").join("sessions.json"))
+                .unwrap_or_else(|_| std::env::temp_dir().join("rainbow-sessions.json"))
+        })
+}
+
+/// Serializable session state used to save and restore logins
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SessionSnapshot {
+    pub session_id: String,
+    pub current_url: Option<String>,
+    pub history: Vec<String>,
+    pub metadata: HashMap<String, String>,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    #[serde(default)]
+    pub actions: Vec<RecordedAction>,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    pub cookies: Vec<CookieParam>,
+    #[serde(default)]
+    pub storage: Option<StorageSnapshot>,
+    pub saved_at: DateTime<Utc>,
 }
 
 /// Session information for API responses
@@ -309,7 +983,42 @@ pub struct SessionInfo {
     pub current_url: Option<String>,
     pub age_seconds: i64,
     pub idle_seconds: i64,
+    pub tags: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
 }
 
 // Default implementation removed - SessionManager now requires a BrowserPool
 // Use SessionManager::new(browser_pool, max_sessions, timeout) directly
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_report_the_first_exceeded() {
+        let limits = SessionLimits {
+            ttl_secs: Some(60),
+            max_navigations: Some(3),
+            ..SessionLimits::default()
+        };
+        assert_eq!(limits.exceeded(10, 5, 3, 1800), None);
+        assert_eq!(limits.exceeded(61, 5, 3, 1800), Some(ExpiryReason::Ttl));
+        assert_eq!(
+            limits.exceeded(10, 5, 4, 1800),
+            Some(ExpiryReason::NavigationLimit)
+        );
+    }
+
+    #[test]
+    fn idle_timeout_overrides_the_default() {
+        let limits = SessionLimits {
+            idle_timeout_secs: Some(30),
+            ..SessionLimits::default()
+        };
+        assert_eq!(limits.exceeded(100, 31, 0, 1800), Some(ExpiryReason::Idle));
+        assert_eq!(SessionLimits::default().exceeded(100, 31, 0, 1800), None);
+    }
+}
@@ -0,0 +1,252 @@
+use super::core::Browser;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Selectors remembered per browser before the least recently seen are dropped
+const MAX_TRACKED_SELECTORS: usize = 5000;
+
+/// Resolves each selector in the current document and fingerprints its match.
+/// `performance.timeOrigin` identifies the document, so repeated lookups within
+/// one load count once.
+const RESOLVE_SCRIPT: &str = r#"
+    (function() {
+        const fingerprint = el => [
+            el.tagName.toLowerCase(),
+            el.getAttribute('id') || '',
+            el.getAttribute('name') || '',
+            el.getAttribute('type') || '',
+            el.getAttribute('role') || '',
+            el.getAttribute('aria-label') || '',
+            (el.getAttribute('href') || '').split('?')[0],
+            // Digits are left out so counters and prices do not make an element "new"
+            (el.innerText || el.value || '').replace(/\d+/g, '').replace(/\s+/g, ' ').trim().slice(0, 40)
+        ].join('|');
+        return {
+            document: location.origin + location.pathname + '@' + performance.timeOrigin,
+            page: location.origin + location.pathname,
+            results: __SELECTORS__.map(selector => {
+                try {
+                    const matches = document.querySelectorAll(selector);
+                    return { count: matches.length, fingerprint: matches.length === 1 ? fingerprint(matches[0]) : null };
+                } catch (e) {
+                    return { count: 0, fingerprint: null };
+                }
+            })
+        };
+    })()
+"#;
+
+/// What a selector resolved to in one document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Missing,
+    /// Several elements matched
+    Ambiguous,
+    Unique(String),
+}
+
+/// How a selector behaved across the loads of one page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectorRecord {
+    /// Documents the selector was looked up in
+    pub loads: u32,
+    /// Loads after the first where it matched exactly the element it matched before
+    pub stable_loads: u32,
+    #[serde(skip)]
+    fingerprint: Option<String>,
+    #[serde(skip)]
+    document: String,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl SelectorRecord {
+    fn new() -> Self {
+        Self {
+            loads: 0,
+            stable_loads: 0,
+            fingerprint: None,
+            document: String::new(),
+            last_seen: Utc::now(),
+        }
+    }
+
+    fn observe(&mut self, document: &str, resolution: Resolution) {
+        self.last_seen = Utc::now();
+        if self.document == document {
+            return;
+        }
+        let fingerprint = match resolution {
+            Resolution::Unique(fingerprint) => Some(fingerprint),
+            Resolution::Missing | Resolution::Ambiguous => None,
+        };
+        if self.loads > 0 && fingerprint.is_some() && fingerprint == self.fingerprint {
+            self.stable_loads += 1;
+        }
+        self.loads += 1;
+        self.fingerprint = fingerprint;
+        self.document = document.to_string();
+    }
+
+    /// Share of reloads that found the same single element, starting from an even
+    /// 0.5 and moving toward 0 or 1 as evidence builds up. None before the
+    /// selector was seen in a second document.
+    pub fn stability(&self) -> Option<f32> {
+        if self.loads < 2 {
+            return None;
+        }
+        let reloads = (self.loads - 1) as f32;
+        Some((self.stable_loads as f32 + 1.0) / (reloads + 2.0))
+    }
+}
+
+/// Remembers how selectors resolved on each page a browser visited
+#[derive(Debug, Default)]
+pub struct SelectorStabilityTracker {
+    records: RwLock<HashMap<(String, String), SelectorRecord>>,
+}
+
+impl SelectorStabilityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `selector` resolved to `resolution` in `document` of `page`
+    pub async fn observe(
+        &self,
+        page: &str,
+        document: &str,
+        selector: &str,
+        resolution: Resolution,
+    ) -> Option<f32> {
+        let mut records = self.records.write().await;
+        let key = (page.to_string(), selector.to_string());
+        if !records.contains_key(&key) && records.len() >= MAX_TRACKED_SELECTORS {
+            if let Some(oldest) = records
+                .iter()
+                .min_by_key(|(_, record)| record.last_seen)
+                .map(|(key, _)| key.clone())
+            {
+                records.remove(&oldest);
+            }
+        }
+        let record = records.entry(key).or_insert_with(SelectorRecord::new);
+        record.observe(document, resolution);
+        record.stability()
+    }
+
+    /// Stability of `selector` on `page`, if it was seen across reloads
+    pub async fn stability(&self, page: &str, selector: &str) -> Option<f32> {
+        self.records
+            .read()
+            .await
+            .get(&(page.to_string(), selector.to_string()))
+            .and_then(|record| record.stability())
+    }
+
+    /// Everything known about the selectors of `page`
+    pub async fn records_for(&self, page: &str) -> HashMap<String, SelectorRecord> {
+        self.records
+            .read()
+            .await
+            .iter()
+            .filter(|((p, _), _)| p == page)
+            .map(|((_, selector), record)| (selector.clone(), record.clone()))
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct ResolvedSelector {
+    count: usize,
+    fingerprint: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ResolveReport {
+    document: String,
+    page: String,
+    results: Vec<ResolvedSelector>,
+}
+
+impl Browser {
+    /// Selector stability observed on this browser's pages
+    pub fn selector_stability(&self) -> Arc<SelectorStabilityTracker> {
+        self.stability.clone()
+    }
+
+    /// Resolve `selectors` in the current document, record the outcome and return
+    /// each selector's stability so far, in order
+    pub async fn observe_selectors(&self, selectors: &[String]) -> Result<Vec<Option<f32>>> {
+        let script = RESOLVE_SCRIPT.replace("__SELECTORS__", &serde_json::to_string(selectors)?);
+        let report: ResolveReport = serde_json::from_value(self.execute_script(&script).await?)?;
+
+        let mut scores = Vec::with_capacity(selectors.len());
+        for (selector, resolved) in selectors.iter().zip(report.results) {
+            let resolution = match (resolved.count, resolved.fingerprint) {
+                (1, Some(fingerprint)) => Resolution::Unique(fingerprint),
+                (0, _) => Resolution::Missing,
+                _ => Resolution::Ambiguous,
+            };
+            scores.push(
+                self.stability
+                    .observe(&report.page, &report.document, selector, resolution)
+                    .await,
+            );
+        }
+        scores.resize(selectors.len(), None);
+        Ok(scores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_each_document_once() {
+        let mut record = SelectorRecord::new();
+        record.observe("a@1", Resolution::Unique("button|submit".into()));
+        record.observe("a@1", Resolution::Missing);
+        assert_eq!(record.loads, 1);
+        assert_eq!(record.stability(), None);
+
+        record.observe("a@2", Resolution::Unique("button|submit".into()));
+        record.observe("a@3", Resolution::Unique("button|submit".into()));
+        assert_eq!(record.stable_loads, 2);
+        assert_eq!(record.stability(), Some(0.75));
+    }
+
+    #[tokio::test]
+    async fn changed_or_ambiguous_matches_are_unstable() {
+        let tracker = SelectorStabilityTracker::new();
+        let page = "https://example.com/cart";
+        tracker
+            .observe(
+                page,
+                "1",
+                "li:nth-of-type(2)",
+                Resolution::Unique("li|a".into()),
+            )
+            .await;
+        tracker
+            .observe(
+                page,
+                "2",
+                "li:nth-of-type(2)",
+                Resolution::Unique("li|b".into()),
+            )
+            .await;
+        tracker
+            .observe(page, "3", "li:nth-of-type(2)", Resolution::Ambiguous)
+            .await;
+
+        let stability = tracker.stability(page, "li:nth-of-type(2)").await.unwrap();
+        assert!(stability < 0.5);
+        assert_eq!(tracker.stability(page, "#checkout").await, None);
+        assert_eq!(tracker.records_for(page).await.len(), 1);
+    }
+}
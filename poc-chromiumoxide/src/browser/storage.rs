@@ -0,0 +1,166 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// Web storage area of the current page's origin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageArea {
+    Local,
+    Session,
+}
+
+impl StorageArea {
+    fn js_object(self) -> &'static str {
+        match self {
+            StorageArea::Local => "window.localStorage",
+            StorageArea::Session => "window.sessionStorage",
+        }
+    }
+}
+
+/// Both storage areas of one origin, as exported from or imported into a page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StorageSnapshot {
+    pub origin: String,
+    #[serde(default)]
+    pub local: BTreeMap<String, String>,
+    #[serde(default)]
+    pub session: BTreeMap<String, String>,
+}
+
+impl StorageSnapshot {
+    pub fn is_empty(&self) -> bool {
+        self.local.is_empty() && self.session.is_empty()
+    }
+}
+
+fn read_script(area: StorageArea) -> String {
+    format!(
+        "(function() {{ const s = {}; const out = {{}}; \
+         for (let i = 0; i < s.length; i++) {{ const k = s.key(i); out[k] = s.getItem(k); }} \
+         return out; }})()",
+        area.js_object()
+    )
+}
+
+fn write_script(
+    area: StorageArea,
+    items: &BTreeMap<String, String>,
+    replace: bool,
+) -> Result<String> {
+    Ok(format!(
+        "(function() {{ const s = {}; {} const items = {}; \
+         for (const [k, v] of Object.entries(items)) s.setItem(k, v); \
+         return s.length; }})()",
+        area.js_object(),
+        if replace { "s.clear();" } else { "" },
+        serde_json::to_string(items)?
+    ))
+}
+
+impl Browser {
+    /// Origin of the active page, which scopes its web storage
+    pub async fn storage_origin(&self) -> Result<String> {
+        let page = self.page.read().await;
+        let origin: String = page
+            .evaluate("window.location.origin")
+            .await?
+            .into_value()?;
+        if origin == "null" {
+            return Err(anyhow!("The current page has no origin with web storage"));
+        }
+        Ok(origin)
+    }
+
+    /// All items of one storage area
+    pub async fn get_storage(&self, area: StorageArea) -> Result<BTreeMap<String, String>> {
+        let page = self.page.read().await;
+        let items = page.evaluate(read_script(area)).await?.into_value()?;
+        Ok(items)
+    }
+
+    /// Set items in one storage area, optionally clearing it first
+    pub async fn set_storage_items(
+        &self,
+        area: StorageArea,
+        items: &BTreeMap<String, String>,
+        replace: bool,
+    ) -> Result<()> {
+        let page = self.page.read().await;
+        page.evaluate(write_script(area, items, replace)?).await?;
+        info!("Set {} {:?} storage items", items.len(), area);
+        Ok(())
+    }
+
+    /// Remove one key, or clear the whole area when `key` is None
+    pub async fn remove_storage(&self, area: StorageArea, key: Option<&str>) -> Result<()> {
+        let script = match key {
+            Some(key) => format!(
+                "{}.removeItem({})",
+                area.js_object(),
+                serde_json::to_string(key)?
+            ),
+            None => format!("{}.clear()", area.js_object()),
+        };
+        let page = self.page.read().await;
+        page.evaluate(script).await?;
+        Ok(())
+    }
+
+    /// Export local and session storage of the current origin
+    pub async fn export_storage(&self) -> Result<StorageSnapshot> {
+        Ok(StorageSnapshot {
+            origin: self.storage_origin().await?,
+            local: self.get_storage(StorageArea::Local).await?,
+            session: self.get_storage(StorageArea::Session).await?,
+        })
+    }
+
+    /// Replace the current origin's storage with a snapshot taken from the same origin
+    pub async fn import_storage(&self, snapshot: &StorageSnapshot) -> Result<()> {
+        let origin = self.storage_origin().await?;
+        if origin != snapshot.origin {
+            return Err(anyhow!(
+                "Storage snapshot is for {} but the page is on {}",
+                snapshot.origin,
+                origin
+            ));
+        }
+        self.set_storage_items(StorageArea::Local, &snapshot.local, true)
+            .await?;
+        self.set_storage_items(StorageArea::Session, &snapshot.session, true)
+            .await?;
+        info!(
+            "Imported storage for {} ({} local, {} session items)",
+            origin,
+            snapshot.local.len(),
+            snapshot.session.len()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_script_escapes_values() {
+        let mut items = BTreeMap::new();
+        items.insert("token".to_string(), "a'b\"c</script>".to_string());
+        let script = write_script(StorageArea::Session, &items, true).unwrap();
+        assert!(script.contains("window.sessionStorage"));
+        assert!(script.contains("s.clear();"));
+        assert!(script.contains(r#""a'b\"c</script>""#));
+    }
+
+    #[test]
+    fn snapshot_areas_default_to_empty() {
+        let snapshot: StorageSnapshot =
+            serde_json::from_value(serde_json::json!({"origin": "https://example.com"})).unwrap();
+        assert!(snapshot.is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use super::core::Browser;
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::Page;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Summary of a single browser tab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabInfo {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub active: bool,
+}
+
+impl Browser {
+    /// Open a new tab, optionally navigating it and making it the active page
+    pub async fn open_tab(&self, url: Option<&str>, activate: bool) -> Result<TabInfo> {
+        let target = url.unwrap_or("about:blank");
+        info!("Opening new tab: {} (activate: {})", target, activate);
+
+        let page = self
+            .new_context_page(target)
+            .await
+            .context("Failed to open new tab")?;
+        if let Err(e) = self.attach_dialog_handler(&page).await {
+            warn!("Dialog handling unavailable for new tab: {}", e);
+        }
+        if let Err(e) = self.attach_console_listener(&page).await {
+            warn!("Console capture unavailable for new tab: {}", e);
+        }
+        if let Err(e) = self.attach_mutation_observer(&page).await {
+            warn!("DOM change tracking unavailable for new tab: {}", e);
+        }
+
+        if activate {
+            page.bring_to_front().await.ok();
+            *self.page.write().await = page.clone();
+        }
+
+        Self::tab_info(&page, activate).await
+    }
+
+    /// List all open tabs of this browser
+    pub async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        let active_id = self.active_tab_id().await;
+        let pages = self.context_pages().await?;
+
+        let mut tabs = Vec::with_capacity(pages.len());
+        for page in &pages {
+            let is_active = page.target_id().inner() == &active_id;
+            tabs.push(Self::tab_info(page, is_active).await?);
+        }
+        Ok(tabs)
+    }
+
+    /// Make the tab with the given id the active page for subsequent operations
+    pub async fn switch_tab(&self, tab_id: &str) -> Result<TabInfo> {
+        let page = self.find_tab(tab_id).await?;
+        page.bring_to_front().await.ok();
+        *self.page.write().await = page.clone();
+
+        info!("Switched to tab: {}", tab_id);
+        Self::tab_info(&page, true).await
+    }
+
+    /// Close a tab. Closing the active tab activates the most recent remaining one.
+    pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
+        let pages = self.context_pages().await?;
+        if pages.len() <= 1 {
+            return Err(anyhow!("Cannot close the last remaining tab"));
+        }
+
+        let page = pages
+            .iter()
+            .find(|p| p.target_id().inner() == tab_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Tab not found: {}", tab_id))?;
+
+        if self.active_tab_id().await == tab_id {
+            let fallback = pages
+                .iter()
+                .rev()
+                .find(|p| p.target_id().inner() != tab_id)
+                .cloned()
+                .ok_or_else(|| anyhow!("No tab left to activate"))?;
+            fallback.bring_to_front().await.ok();
+            *self.page.write().await = fallback;
+        }
+
+        page.close().await.context("Failed to close tab")?;
+        info!("Closed tab: {}", tab_id);
+        Ok(())
+    }
+
+    /// Id of the tab currently used for page operations
+    pub async fn active_tab_id(&self) -> String {
+        self.page.read().await.target_id().inner().clone()
+    }
+
+    async fn find_tab(&self, tab_id: &str) -> Result<Page> {
+        self.context_pages()
+            .await?
+            .into_iter()
+            .find(|p| p.target_id().inner() == tab_id)
+            .ok_or_else(|| anyhow!("Tab not found: {}", tab_id))
+    }
+
+    async fn tab_info(page: &Page, active: bool) -> Result<TabInfo> {
+        Ok(TabInfo {
+            id: page.target_id().inner().clone(),
+            url: page
+                .url()
+                .await?
+                .unwrap_or_else(|| "about:blank".to_string()),
+            title: page.get_title().await?.unwrap_or_default(),
+            active,
+        })
+    }
+}
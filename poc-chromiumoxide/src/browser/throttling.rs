@@ -0,0 +1,159 @@
+use super::core::Browser;
+use anyhow::{anyhow, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::SetCpuThrottlingRateParams;
+use chromiumoxide::cdp::browser_protocol::network::EmulateNetworkConditionsParams;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// Emulated network link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConditions {
+    /// Preset name, or "custom"
+    #[serde(default = "custom_name")]
+    pub name: String,
+    #[serde(default)]
+    pub offline: bool,
+    pub latency_ms: f64,
+    pub download_kbps: f64,
+    pub upload_kbps: f64,
+}
+
+fn custom_name() -> String {
+    "custom".to_string()
+}
+
+impl NetworkConditions {
+    /// DevTools network presets: "slow-3g", "fast-3g", "4g" and "offline"
+    pub fn preset(name: &str) -> Option<Self> {
+        let key: String = name
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+        let (name, offline, latency_ms, download_kbps, upload_kbps) = match key.as_str() {
+            "slow3g" => ("slow-3g", false, 2000.0, 400.0, 400.0),
+            "fast3g" => ("fast-3g", false, 562.5, 1474.56, 675.0),
+            "4g" => ("4g", false, 170.0, 9000.0, 9000.0),
+            "offline" => ("offline", true, 0.0, 0.0, 0.0),
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            offline,
+            latency_ms,
+            download_kbps,
+            upload_kbps,
+        })
+    }
+
+    /// Throughput in bytes per second as expected by CDP
+    fn bytes_per_second(kbps: f64) -> f64 {
+        kbps * 1000.0 / 8.0
+    }
+}
+
+/// Throttling currently applied to a browser
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThrottlingState {
+    pub network: Option<NetworkConditions>,
+    /// CPU slowdown factor, e.g. 4.0 for a 4x slower CPU
+    pub cpu_slowdown: Option<f64>,
+}
+
+impl Browser {
+    /// Emulate a network link on the active page; None restores full speed
+    pub async fn set_network_throttling(
+        &self,
+        conditions: Option<NetworkConditions>,
+    ) -> Result<()> {
+        let params = match &conditions {
+            Some(c) => EmulateNetworkConditionsParams::builder()
+                .offline(c.offline)
+                .latency(c.latency_ms)
+                .download_throughput(NetworkConditions::bytes_per_second(c.download_kbps))
+                .upload_throughput(NetworkConditions::bytes_per_second(c.upload_kbps))
+                .build(),
+            // -1 disables throughput throttling
+            None => EmulateNetworkConditionsParams::builder()
+                .offline(false)
+                .latency(0.0)
+                .download_throughput(-1.0)
+                .upload_throughput(-1.0)
+                .build(),
+        }
+        .map_err(|e| anyhow!("Invalid network conditions: {}", e))?;
+
+        let page = self.page.read().await;
+        page.execute(params).await?;
+        drop(page);
+
+        match &conditions {
+            Some(c) => info!(
+                "Network throttling: {} ({}ms, {}/{} kbps)",
+                c.name, c.latency_ms, c.download_kbps, c.upload_kbps
+            ),
+            None => info!("Network throttling disabled"),
+        }
+        self.throttling.write().await.network = conditions;
+        Ok(())
+    }
+
+    /// Slow down the CPU by `rate` (1.0 = no throttling)
+    pub async fn set_cpu_throttling(&self, rate: f64) -> Result<()> {
+        if !(1.0..=20.0).contains(&rate) {
+            return Err(anyhow!("CPU slowdown must be between 1 and 20: {}", rate));
+        }
+        let page = self.page.read().await;
+        page.execute(SetCpuThrottlingRateParams::new(rate)).await?;
+        drop(page);
+
+        info!("CPU throttling: {}x", rate);
+        self.throttling.write().await.cpu_slowdown = (rate > 1.0).then_some(rate);
+        Ok(())
+    }
+
+    /// Remove network and CPU throttling
+    pub async fn clear_throttling(&self) -> Result<()> {
+        self.set_network_throttling(None).await?;
+        self.set_cpu_throttling(1.0).await
+    }
+
+    /// Throttling currently applied to this browser
+    pub async fn throttling(&self) -> ThrottlingState {
+        self.throttling.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_network_presets() {
+        assert_eq!(
+            NetworkConditions::preset("Slow 3G").unwrap().name,
+            "slow-3g"
+        );
+        assert_eq!(
+            NetworkConditions::preset("fast-3g").unwrap().latency_ms,
+            562.5
+        );
+        assert!(NetworkConditions::preset("offline").unwrap().offline);
+        assert!(NetworkConditions::preset("5g").is_none());
+    }
+
+    #[test]
+    fn converts_kbps_to_bytes() {
+        assert_eq!(NetworkConditions::bytes_per_second(400.0), 50_000.0);
+    }
+
+    #[test]
+    fn custom_conditions_deserialize_without_name() {
+        let c: NetworkConditions = serde_json::from_value(serde_json::json!({
+            "latency_ms": 100.0, "download_kbps": 1000.0, "upload_kbps": 500.0
+        }))
+        .unwrap();
+        assert_eq!(c.name, "custom");
+        assert!(!c.offline);
+    }
+}
@@ -10,7 +10,7 @@ use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use super::cache::UnifiedCache;
-use super::events::{Event, EventBus};
+use super::events::{Event, EventBus, EventBusConfig};
 use super::monitoring::UnifiedMonitoring;
 use super::session::{SessionBundle, SessionContext};
 use super::state::UnifiedStateManager;
@@ -198,7 +198,7 @@ impl Default for CoordinatorConfig {
 impl RainbowCoordinator {
     /// Create a new coordinator
     pub async fn new(browser_pool: Arc<BrowserPool>) -> Result<Self> {
-        let event_bus = Arc::new(EventBus::new());
+        let event_bus = Arc::new(EventBus::with_config(EventBusConfig::from_env()));
         let state_manager = Arc::new(UnifiedStateManager::new(event_bus.clone()).await?);
         let resource_manager = Arc::new(ResourceManager::new(browser_pool));
         let module_registry = Arc::new(ModuleRegistry::new());
@@ -251,6 +251,9 @@ impl RainbowCoordinator {
             .await?,
         );
 
+        // Publish dialogs handled by this session's browser on the event bus
+        self.forward_dialog_events(&session_id, &context.browser);
+
         // Create session bundle with all modules
         let bundle = Arc::new(SessionBundle::new(context.clone()).await?);
 
@@ -277,6 +280,81 @@ impl RainbowCoordinator {
         Ok(bundle)
     }
 
+    fn forward_dialog_events(&self, session_id: &str, browser: &crate::browser::Browser) {
+        let mut dialogs = browser.dialogs().subscribe();
+        let event_bus = self.event_bus.clone();
+        let session_contexts = self.session_contexts.clone();
+        let session_id = session_id.to_string();
+
+        tokio::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                let dialog = match dialogs.recv().await {
+                    Ok(dialog) => dialog,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Dropped {} dialog events for session {}",
+                            skipped, session_id
+                        );
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                // The browser outlives the session when returned to the pool
+                if !session_contexts.read().await.contains_key(&session_id) {
+                    break;
+                }
+                let action = serde_json::to_value(&dialog.policy)
+                    .ok()
+                    .and_then(|v| v["action"].as_str().map(String::from))
+                    .unwrap_or_default();
+                let _ = event_bus
+                    .emit(Event::DialogHandled {
+                        session_id: session_id.clone(),
+                        dialog_type: dialog.dialog_type,
+                        message: dialog.message,
+                        action,
+                        timestamp: Instant::now(),
+                    })
+                    .await;
+            }
+        });
+    }
+
+    /// Publish session manager expiries on the event bus
+    pub fn forward_session_expiry(
+        &self,
+        mut expired: tokio::sync::broadcast::Receiver<crate::browser::SessionExpired>,
+    ) {
+        let event_bus = self.event_bus.clone();
+
+        tokio::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                let expiry = match expired.recv().await {
+                    Ok(expiry) => expiry,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Dropped {} session expiry events", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let _ = event_bus
+                    .emit(Event::SessionExpired {
+                        session_id: expiry.session_id,
+                        reason: expiry.reason.as_str().to_string(),
+                        timestamp: Instant::now(),
+                    })
+                    .await;
+            }
+        });
+    }
+
+    /// Bus the coordinated modules publish their events on
+    pub fn event_bus(&self) -> Arc<EventBus> {
+        self.event_bus.clone()
+    }
+
     /// Get an existing session bundle
     pub async fn get_session(&self, session_id: &str) -> Option<Arc<SessionBundle>> {
         self.session_bundles.read().await.get(session_id).cloned()
@@ -0,0 +1,67 @@
+// Correlation IDs
+// Every API request runs under an id, taken from its `X-Correlation-Id` header or
+// generated, that follows the work it causes through the engines: events emitted,
+// session activity, LLM calls and log lines all carry it. Work spawned off a
+// request keeps the id by running inside `with_correlation_id`.
+
+use std::future::Future;
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Header a request's correlation id is read from and answered with
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+/// Longest correlation id accepted from a client
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    /// Correlation id of the request the current task serves
+    static CORRELATION_ID: Option<String>;
+}
+
+/// Run `future` under the correlation id `id`, tagging its log lines with it
+pub async fn with_correlation_id<F: Future>(id: Option<String>, future: F) -> F::Output {
+    let span = match &id {
+        Some(id) => tracing::info_span!("correlation", correlation_id = %id),
+        None => tracing::Span::none(),
+    };
+    CORRELATION_ID.scope(id, future.instrument(span)).await
+}
+
+/// Correlation id of the current task; `None` outside any request
+pub fn current_correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(|id| id.clone()).ok().flatten()
+}
+
+pub fn new_correlation_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Whether a client-supplied id can be used as is: up to 128 letters, digits, `-`,
+/// `_`, `.` or `:`
+pub fn valid_correlation_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_id_follows_scope() {
+        assert_eq!(current_correlation_id(), None);
+        let seen = with_correlation_id(Some("req-1".to_string()), async {
+            current_correlation_id()
+        })
+        .await;
+        assert_eq!(seen.as_deref(), Some("req-1"));
+
+        assert!(valid_correlation_id("4f0c:checkout.retry_2"));
+        assert!(!valid_correlation_id("has space"));
+        assert!(!valid_correlation_id(&"x".repeat(MAX_LEN + 1)));
+    }
+}
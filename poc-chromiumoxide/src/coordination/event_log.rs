@@ -0,0 +1,177 @@
+// Event Log
+// Append-only JSON lines file backing a durable EventBus: every emitted event is
+// appended as it is emitted, so history survives restarts and can be replayed,
+// and the offsets consumers have committed are kept in a file beside it.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+use super::events::TimestampedEvent;
+
+/// Log file of a durable event bus: `RAINBOW_EVENT_LOG`, unset for an in-memory bus
+pub fn log_path_from_env() -> Option<PathBuf> {
+    std::env::var("RAINBOW_EVENT_LOG")
+        .ok()
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+pub struct EventLog {
+    path: PathBuf,
+    writer: Mutex<Option<File>>,
+}
+
+impl EventLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            writer: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// File the committed consumer offsets are kept in
+    fn offsets_path(&self) -> PathBuf {
+        self.path.with_extension("offsets.json")
+    }
+
+    /// Logged events, oldest first; a missing log reads as empty and lines that
+    /// don't parse, such as one cut short by a crash, are skipped
+    pub fn read(&self) -> Result<Vec<TimestampedEvent>> {
+        let data = match std::fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read event log {}", self.path.display()))
+            }
+        };
+        let mut events = Vec::new();
+        for (number, line) in data.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(event) => events.push(event),
+                Err(e) => warn!(
+                    "Skipping line {} of event log {}: {}",
+                    number + 1,
+                    self.path.display(),
+                    e
+                ),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Append `event` as one line
+    pub fn append(&self, event: &TimestampedEvent) -> Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        if writer.is_none() {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .with_context(|| format!("Failed to open event log {}", self.path.display()))?;
+            *writer = Some(file);
+        }
+        if let Some(file) = writer.as_mut() {
+            file.write_all(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Committed offsets by consumer; none when nothing was committed yet
+    pub fn read_offsets(&self) -> Result<HashMap<String, u64>> {
+        match std::fs::read(self.offsets_path()) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replace the committed offsets, only once the new ones are fully written
+    pub fn write_offsets(&self, offsets: &HashMap<String, u64>) -> Result<()> {
+        let path = self.offsets_path();
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_vec_pretty(offsets)?)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coordination::{Event, EventBus, EventBusConfig, EventType};
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    fn created(session_id: &str) -> Event {
+        Event::SessionCreated {
+            session_id: session_id.to_string(),
+            timestamp: Instant::now(),
+        }
+    }
+
+    fn durable_bus(dir: &tempfile::TempDir) -> EventBus {
+        EventBus::with_config(EventBusConfig {
+            log_path: Some(dir.path().join("events.jsonl")),
+            ..EventBusConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_consumer_catches_up_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = |seen: &Arc<Mutex<Vec<String>>>| {
+            let seen = seen.clone();
+            move |event: &Event| {
+                seen.lock()
+                    .unwrap()
+                    .push(event.session_id().unwrap().to_string());
+                Ok(())
+            }
+        };
+
+        let bus = durable_bus(&dir);
+        bus.emit(created("before")).await.unwrap();
+        bus.subscribe_durable("analytics", &[EventType::SessionCreated], record(&seen))
+            .await
+            .unwrap();
+        bus.emit(created("a")).await.unwrap();
+        drop(bus);
+
+        // Emitted while the consumer is down
+        let bus = durable_bus(&dir);
+        bus.emit(created("b")).await.unwrap();
+        assert_eq!(bus.get_history(None).await.len(), 3);
+        drop(bus);
+
+        let bus = durable_bus(&dir);
+        bus.subscribe_durable("analytics", &[EventType::SessionCreated], record(&seen))
+            .await
+            .unwrap();
+        bus.emit(created("c")).await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), ["a", "b", "c"]);
+        assert_eq!(bus.consumer_offset("analytics").await, Some(4));
+
+        let since = bus.get_history(None).await[1].occurred_at;
+        let replayed = bus.replay_since(since).await.unwrap();
+        let ids: Vec<u64> = replayed.iter().map(|e| e.sequence_id).collect();
+        assert_eq!(ids, [2, 3, 4]);
+    }
+}
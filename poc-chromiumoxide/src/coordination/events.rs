@@ -2,36 +2,55 @@
 // Provides event-driven communication between modules
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::RwLock;
-use tracing::{debug, error, info};
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use super::event_log::{self, EventLog};
+use super::sinks::{self, SinkConfig};
+
 /// Core event types in the system
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
     // Browser Events
     NavigationStarted {
         session_id: String,
         url: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     NavigationCompleted {
         session_id: String,
         url: String,
         load_time_ms: u64,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     PageContentChanged {
         session_id: String,
         change_type: ContentChangeType,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     BrowserError {
         session_id: String,
         error: String,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+    },
+    DialogHandled {
+        session_id: String,
+        dialog_type: String,
+        message: String,
+        action: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
 
@@ -39,6 +58,7 @@ pub enum Event {
     PerceptionAnalysisStarted {
         session_id: String,
         analysis_type: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     PerceptionAnalysisCompleted {
@@ -46,6 +66,7 @@ pub enum Event {
         analysis_type: String,
         duration_ms: u64,
         result_summary: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     ElementFound {
@@ -53,12 +74,14 @@ pub enum Event {
         selector: String,
         confidence: f64,
         element_type: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     PageClassified {
         session_id: String,
         page_type: String,
         confidence: f64,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     AnalysisCompleted {
@@ -66,6 +89,7 @@ pub enum Event {
         analysis_type: String,
         element_count: usize,
         duration_ms: u64,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
 
@@ -75,12 +99,14 @@ pub enum Event {
         action_id: String,
         confidence: f64,
         duration_ms: u64,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     LearningCompleted {
         session_id: String,
         success: bool,
         patterns_updated: usize,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
 
@@ -89,6 +115,7 @@ pub enum Event {
         session_id: String,
         tool_name: String,
         execution_id: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     ToolExecutionCompleted {
@@ -97,6 +124,7 @@ pub enum Event {
         execution_id: String,
         success: bool,
         duration_ms: u64,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     ToolExecutionFailed {
@@ -104,22 +132,45 @@ pub enum Event {
         tool_name: String,
         execution_id: String,
         error: String,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+    },
+
+    // Workflow Events
+    /// A simple or intelligent workflow ran to its end, successfully or not
+    WorkflowCompleted {
+        workflow: String,
+        success: bool,
+        steps_completed: usize,
+        total_steps: usize,
+        duration_ms: u64,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
 
     // Session Events
     SessionCreated {
         session_id: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     SessionClosed {
         session_id: String,
         reason: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     SessionTimeout {
         session_id: String,
         idle_duration_ms: u64,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+    },
+    /// Ended by the session manager for exceeding its TTL, idle timeout or a resource limit
+    SessionExpired {
+        session_id: String,
+        reason: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
 
@@ -128,16 +179,19 @@ pub enum Event {
         cache_type: String,
         reason: String,
         keys_affected: Vec<String>,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     CacheHit {
         cache_type: String,
         key: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     CacheMiss {
         cache_type: String,
         key: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
 
@@ -146,35 +200,50 @@ pub enum Event {
         resource_type: String,
         usage_percent: f64,
         threshold: f64,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+    },
+    /// A client's LLM spend reached its daily budget
+    BudgetExceeded {
+        client: String,
+        budget_usd: f64,
+        spent_usd: f64,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     ModuleInitialized {
         module_type: String,
         session_id: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     ModuleShutdown {
         module_type: String,
         session_id: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     ModuleError {
         module_type: String,
         error: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
     SessionContextCreated {
         session_id: String,
+        #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
     },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EventType {
     NavigationStarted,
     NavigationCompleted,
     PageContentChanged,
     BrowserError,
+    DialogHandled,
     PerceptionAnalysisStarted,
     PerceptionAnalysisCompleted,
     ElementFound,
@@ -185,26 +254,80 @@ pub enum EventType {
     ToolExecutionStarted,
     ToolExecutionCompleted,
     ToolExecutionFailed,
+    WorkflowCompleted,
     SessionCreated,
     SessionClosed,
     SessionTimeout,
+    SessionExpired,
     CacheInvalidated,
     CacheHit,
     CacheMiss,
     ResourceWarning,
+    BudgetExceeded,
     ModuleInitialized,
     ModuleShutdown,
     ModuleError,
     SessionContextCreated,
 }
 
+/// Engine an event comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSource {
+    Browser,
+    Perception,
+    Intelligence,
+    Tools,
+    Workflow,
+    Session,
+    Cache,
+    Llm,
+    /// Resources and module lifecycle
+    System,
+}
+
 impl Event {
+    pub fn source(&self) -> EventSource {
+        match self {
+            Event::NavigationStarted { .. }
+            | Event::NavigationCompleted { .. }
+            | Event::PageContentChanged { .. }
+            | Event::BrowserError { .. }
+            | Event::DialogHandled { .. } => EventSource::Browser,
+            Event::PerceptionAnalysisStarted { .. }
+            | Event::PerceptionAnalysisCompleted { .. }
+            | Event::ElementFound { .. }
+            | Event::PageClassified { .. } => EventSource::Perception,
+            Event::AnalysisCompleted { .. }
+            | Event::PlanningCompleted { .. }
+            | Event::LearningCompleted { .. } => EventSource::Intelligence,
+            Event::ToolExecutionStarted { .. }
+            | Event::ToolExecutionCompleted { .. }
+            | Event::ToolExecutionFailed { .. } => EventSource::Tools,
+            Event::WorkflowCompleted { .. } => EventSource::Workflow,
+            Event::SessionCreated { .. }
+            | Event::SessionClosed { .. }
+            | Event::SessionTimeout { .. }
+            | Event::SessionExpired { .. }
+            | Event::SessionContextCreated { .. } => EventSource::Session,
+            Event::CacheInvalidated { .. } | Event::CacheHit { .. } | Event::CacheMiss { .. } => {
+                EventSource::Cache
+            }
+            Event::BudgetExceeded { .. } => EventSource::Llm,
+            Event::ResourceWarning { .. }
+            | Event::ModuleInitialized { .. }
+            | Event::ModuleShutdown { .. }
+            | Event::ModuleError { .. } => EventSource::System,
+        }
+    }
+
     pub fn event_type(&self) -> EventType {
         match self {
             Event::NavigationStarted { .. } => EventType::NavigationStarted,
             Event::NavigationCompleted { .. } => EventType::NavigationCompleted,
             Event::PageContentChanged { .. } => EventType::PageContentChanged,
             Event::BrowserError { .. } => EventType::BrowserError,
+            Event::DialogHandled { .. } => EventType::DialogHandled,
             Event::PerceptionAnalysisStarted { .. } => EventType::PerceptionAnalysisStarted,
             Event::PerceptionAnalysisCompleted { .. } => EventType::PerceptionAnalysisCompleted,
             Event::ElementFound { .. } => EventType::ElementFound,
@@ -215,13 +338,16 @@ impl Event {
             Event::ToolExecutionStarted { .. } => EventType::ToolExecutionStarted,
             Event::ToolExecutionCompleted { .. } => EventType::ToolExecutionCompleted,
             Event::ToolExecutionFailed { .. } => EventType::ToolExecutionFailed,
+            Event::WorkflowCompleted { .. } => EventType::WorkflowCompleted,
             Event::SessionCreated { .. } => EventType::SessionCreated,
             Event::SessionClosed { .. } => EventType::SessionClosed,
             Event::SessionTimeout { .. } => EventType::SessionTimeout,
+            Event::SessionExpired { .. } => EventType::SessionExpired,
             Event::CacheInvalidated { .. } => EventType::CacheInvalidated,
             Event::CacheHit { .. } => EventType::CacheHit,
             Event::CacheMiss { .. } => EventType::CacheMiss,
             Event::ResourceWarning { .. } => EventType::ResourceWarning,
+            Event::BudgetExceeded { .. } => EventType::BudgetExceeded,
             Event::ModuleInitialized { .. } => EventType::ModuleInitialized,
             Event::ModuleShutdown { .. } => EventType::ModuleShutdown,
             Event::ModuleError { .. } => EventType::ModuleError,
@@ -235,6 +361,7 @@ impl Event {
             | Event::NavigationCompleted { session_id, .. }
             | Event::PageContentChanged { session_id, .. }
             | Event::BrowserError { session_id, .. }
+            | Event::DialogHandled { session_id, .. }
             | Event::PerceptionAnalysisStarted { session_id, .. }
             | Event::PerceptionAnalysisCompleted { session_id, .. }
             | Event::ElementFound { session_id, .. }
@@ -248,6 +375,7 @@ impl Event {
             | Event::SessionCreated { session_id, .. }
             | Event::SessionClosed { session_id, .. }
             | Event::SessionTimeout { session_id, .. }
+            | Event::SessionExpired { session_id, .. }
             | Event::ModuleInitialized { session_id, .. }
             | Event::ModuleShutdown { session_id, .. }
             | Event::SessionContextCreated { session_id, .. } => Some(session_id),
@@ -256,7 +384,8 @@ impl Event {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ContentChangeType {
     DomMutation,
     FormInput,
@@ -266,6 +395,28 @@ pub enum ContentChangeType {
     Unknown,
 }
 
+/// Which events a consumer wants; empty lists and no session match everything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventFilter {
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<EventSource>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type()))
+            && (self.sources.is_empty() || self.sources.contains(&event.source()))
+            && self
+                .session_id
+                .as_deref()
+                .is_none_or(|id| event.session_id() == Some(id))
+    }
+}
+
 /// Event handler trait
 #[async_trait::async_trait]
 pub trait EventHandler: Send + Sync {
@@ -278,14 +429,35 @@ pub struct EventSubscriber {
     handler: Box<dyn EventHandler>,
     #[allow(clippy::type_complexity)]
     filter: Option<Box<dyn Fn(&Event) -> bool + Send + Sync>>,
+    /// Durable consumer whose offset advances with each handled event
+    consumer: Option<String>,
+}
+
+/// Handler shared by the subscriptions of a durable consumer
+struct SharedHandler(Arc<dyn EventHandler>);
+
+#[async_trait::async_trait]
+impl EventHandler for SharedHandler {
+    async fn handle(&self, event: &Event) -> Result<()> {
+        self.0.handle(event).await
+    }
 }
 
 /// Timestamped event for history
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimestampedEvent {
     pub event: Event,
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
+    /// Wall-clock time of the event, kept across restarts
+    pub occurred_at: DateTime<Utc>,
     pub sequence_id: u64,
+    /// Tenant the event was emitted for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Correlation id of the request the event was emitted for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
 }
 
 /// Event bus metrics
@@ -297,6 +469,9 @@ pub struct EventMetrics {
     pub average_handling_time_ms: f64,
 }
 
+/// Events buffered for a live receiver that falls behind
+const LIVE_CAPACITY: usize = 1024;
+
 /// Central event bus for the system
 pub struct EventBus {
     subscribers: Arc<RwLock<HashMap<EventType, Vec<Arc<EventSubscriber>>>>>,
@@ -304,6 +479,12 @@ pub struct EventBus {
     metrics: Arc<RwLock<EventMetrics>>,
     sequence_counter: Arc<RwLock<u64>>,
     max_history_size: usize,
+    /// Log every event is appended to, when the bus is durable
+    log: Option<Arc<EventLog>>,
+    /// Sequence id of the last event each durable consumer handled
+    consumer_offsets: Arc<RwLock<HashMap<String, u64>>>,
+    /// Every event as it is emitted, for streaming to clients
+    live: broadcast::Sender<TimestampedEvent>,
 }
 
 impl EventBus {
@@ -311,16 +492,59 @@ impl EventBus {
         Self::with_config(EventBusConfig::default())
     }
 
+    /// A bus configured by `config`; with a `log_path` the logged history and
+    /// consumer offsets are loaded and sequence ids continue where they left off.
+    /// Configured sinks start exporting right away, so this needs a Tokio runtime
+    /// when there are any.
     pub fn with_config(config: EventBusConfig) -> Self {
-        Self {
+        let mut history = VecDeque::with_capacity(config.max_history_size);
+        let mut last_sequence_id = 0;
+        let mut offsets = HashMap::new();
+        let log = config.log_path.map(|path| Arc::new(EventLog::new(path)));
+        if let Some(log) = &log {
+            match log.read() {
+                Ok(events) => {
+                    last_sequence_id = events.last().map(|e| e.sequence_id).unwrap_or(0);
+                    let skip = events.len().saturating_sub(config.max_history_size);
+                    history.extend(events.into_iter().skip(skip));
+                }
+                Err(e) => warn!("Event history not loaded: {:#}", e),
+            }
+            match log.read_offsets() {
+                Ok(loaded) => offsets = loaded,
+                Err(e) => warn!("Consumer offsets not loaded: {:#}", e),
+            }
+            info!(
+                "Event log {} resumes after event {}",
+                log.path().display(),
+                last_sequence_id
+            );
+        }
+
+        let bus = Self {
             subscribers: Arc::new(RwLock::new(HashMap::new())),
-            event_history: Arc::new(RwLock::new(VecDeque::with_capacity(
-                config.max_history_size,
-            ))),
+            event_history: Arc::new(RwLock::new(history)),
             metrics: Arc::new(RwLock::new(EventMetrics::default())),
-            sequence_counter: Arc::new(RwLock::new(0)),
+            sequence_counter: Arc::new(RwLock::new(last_sequence_id)),
             max_history_size: config.max_history_size,
+            log,
+            consumer_offsets: Arc::new(RwLock::new(offsets)),
+            live: broadcast::channel(LIVE_CAPACITY).0,
+        };
+        if !config.sinks.is_empty() {
+            sinks::start_sinks(&bus, config.sinks);
         }
+        bus
+    }
+
+    /// Receive every event from now on, with its sequence id and tenant
+    pub fn subscribe_live(&self) -> broadcast::Receiver<TimestampedEvent> {
+        self.live.subscribe()
+    }
+
+    /// Whether events are logged and survive restarts
+    pub fn is_durable(&self) -> bool {
+        self.log.is_some()
     }
 
     /// Emit an event to all subscribers
@@ -337,15 +561,26 @@ impl EventBus {
 
         // Store in history
         {
+            let entry = TimestampedEvent {
+                event: event.clone(),
+                timestamp: Instant::now(),
+                occurred_at: Utc::now(),
+                sequence_id,
+                tenant: crate::browser::session::current_tenant(),
+                correlation_id: super::correlation::current_correlation_id(),
+            };
+            if let Some(log) = &self.log {
+                if let Err(e) = log.append(&entry) {
+                    error!("Event {} not logged: {:#}", sequence_id, e);
+                }
+            }
+            // No live receivers is fine
+            let _ = self.live.send(entry.clone());
             let mut history = self.event_history.write().await;
             if history.len() >= self.max_history_size {
                 history.pop_front();
             }
-            history.push_back(TimestampedEvent {
-                event: event.clone(),
-                timestamp: Instant::now(),
-                sequence_id,
-            });
+            history.push_back(entry);
         }
 
         // Update metrics
@@ -367,10 +602,19 @@ impl EventBus {
                 }
 
                 // Handle event
-                if let Err(e) = subscriber.handler.handle(&event).await {
-                    error!("Event handler {} failed: {}", subscriber.id, e);
-                    let mut metrics = self.metrics.write().await;
-                    metrics.failed_handlers += 1;
+                match subscriber.handler.handle(&event).await {
+                    Ok(()) => {
+                        if let Some(consumer) = &subscriber.consumer {
+                            if let Err(e) = self.commit_offset(consumer, sequence_id).await {
+                                warn!("Offset of consumer {} not committed: {:#}", consumer, e);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("Event handler {} failed: {}", subscriber.id, e);
+                        let mut metrics = self.metrics.write().await;
+                        metrics.failed_handlers += 1;
+                    }
                 }
             }
         }
@@ -415,11 +659,25 @@ impl EventBus {
         H: EventHandler + 'static,
         F: Fn(&Event) -> bool + Send + Sync + 'static,
     {
+        let filter = filter.map(|f| Box::new(f) as Box<dyn Fn(&Event) -> bool + Send + Sync>);
+        self.add_subscriber(event_type, Box::new(handler), filter, None)
+            .await
+    }
+
+    #[allow(clippy::type_complexity)]
+    async fn add_subscriber(
+        &self,
+        event_type: EventType,
+        handler: Box<dyn EventHandler>,
+        filter: Option<Box<dyn Fn(&Event) -> bool + Send + Sync>>,
+        consumer: Option<String>,
+    ) -> String {
         let subscriber_id = Uuid::new_v4().to_string();
         let subscriber = Arc::new(EventSubscriber {
             id: subscriber_id.clone(),
-            handler: Box::new(handler),
-            filter: filter.map(|f| Box::new(f) as Box<dyn Fn(&Event) -> bool + Send + Sync>),
+            handler,
+            filter,
+            consumer,
         });
 
         let mut subscribers = self.subscribers.write().await;
@@ -432,6 +690,105 @@ impl EventBus {
         subscriber_id
     }
 
+    /// Subscribe `consumer` to `event_types`, first handing it the events of those
+    /// types emitted since its committed offset, then each new one. Every handled
+    /// event commits the offset, so after a restart of a durable bus the consumer
+    /// picks up where it stopped. A consumer without an offset starts with the
+    /// next event. Events emitted while catching up may be handled twice.
+    pub async fn subscribe_durable<H>(
+        &self,
+        consumer: &str,
+        event_types: &[EventType],
+        handler: H,
+    ) -> Result<Vec<String>>
+    where
+        H: EventHandler + 'static,
+    {
+        let handler: Arc<dyn EventHandler> = Arc::new(handler);
+        let caught_up_to = *self.sequence_counter.read().await;
+        let mut subscriber_ids = Vec::with_capacity(event_types.len());
+        for event_type in event_types {
+            let shared = Box::new(SharedHandler(handler.clone()));
+            subscriber_ids.push(
+                self.add_subscriber(*event_type, shared, None, Some(consumer.to_string()))
+                    .await,
+            );
+        }
+
+        let Some(offset) = self.consumer_offset(consumer).await else {
+            self.commit_offset(consumer, caught_up_to).await?;
+            return Ok(subscriber_ids);
+        };
+        let missed: Vec<_> = self
+            .events_after(offset)
+            .await?
+            .into_iter()
+            .filter(|logged| logged.sequence_id <= caught_up_to)
+            .collect();
+        info!(
+            "Consumer {} catching up on {} events after {}",
+            consumer,
+            missed.len(),
+            offset
+        );
+        for logged in missed {
+            if event_types.contains(&logged.event.event_type()) {
+                if let Err(e) = handler.handle(&logged.event).await {
+                    error!(
+                        "Consumer {} failed replaying event {}: {}",
+                        consumer, logged.sequence_id, e
+                    );
+                    self.metrics.write().await.failed_handlers += 1;
+                    continue;
+                }
+            }
+            self.commit_offset(consumer, logged.sequence_id).await?;
+        }
+        Ok(subscriber_ids)
+    }
+
+    /// Sequence id of the last event `consumer` handled
+    pub async fn consumer_offset(&self, consumer: &str) -> Option<u64> {
+        self.consumer_offsets.read().await.get(consumer).copied()
+    }
+
+    /// Record that `consumer` handled the events up to `sequence_id`; offsets only
+    /// move forward
+    pub async fn commit_offset(&self, consumer: &str, sequence_id: u64) -> Result<()> {
+        let mut offsets = self.consumer_offsets.write().await;
+        let offset = offsets.entry(consumer.to_string()).or_insert(sequence_id);
+        *offset = (*offset).max(sequence_id);
+        if let Some(log) = &self.log {
+            log.write_offsets(&offsets)?;
+        }
+        Ok(())
+    }
+
+    /// Events emitted at or after `since`, oldest first: the whole log of a
+    /// durable bus, else what is left of the in-memory history
+    pub async fn replay_since(&self, since: DateTime<Utc>) -> Result<Vec<TimestampedEvent>> {
+        let mut events = self.all_events().await?;
+        events.retain(|logged| logged.occurred_at >= since);
+        Ok(events)
+    }
+
+    /// Events after `sequence_id`, oldest first
+    pub async fn events_after(&self, sequence_id: u64) -> Result<Vec<TimestampedEvent>> {
+        let mut events = self.all_events().await?;
+        events.retain(|logged| logged.sequence_id > sequence_id);
+        Ok(events)
+    }
+
+    async fn all_events(&self) -> Result<Vec<TimestampedEvent>> {
+        match &self.log {
+            Some(log) => {
+                let log = log.clone();
+                tokio::task::spawn_blocking(move || log.read()).await?
+            }
+            None => Ok(self.event_history.read().await.iter().cloned().collect()),
+        }
+    }
+
     /// Unsubscribe from events
     pub async fn unsubscribe(&self, subscriber_id: &str) -> Result<()> {
         let mut subscribers = self.subscribers.write().await;
@@ -472,6 +829,22 @@ impl Default for EventBus {
 pub struct EventBusConfig {
     pub max_history_size: usize,
     pub enable_metrics: bool,
+    /// Append-only log making the bus durable; in-memory only when unset
+    pub log_path: Option<PathBuf>,
+    /// Brokers every event is exported to
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl EventBusConfig {
+    /// Defaults, logging to `RAINBOW_EVENT_LOG` when it is set and exporting to
+    /// the brokers in `RAINBOW_EVENT_SINKS`
+    pub fn from_env() -> Self {
+        Self {
+            log_path: event_log::log_path_from_env(),
+            sinks: sinks::sinks_from_env(),
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for EventBusConfig {
@@ -479,6 +852,8 @@ impl Default for EventBusConfig {
         Self {
             max_history_size: 1000,
             enable_metrics: true,
+            log_path: None,
+            sinks: Vec::new(),
         }
     }
 }
@@ -4,19 +4,25 @@
 pub mod browser_context;
 pub mod cache;
 pub mod coordinator;
+pub mod correlation;
 pub mod error_handler;
+pub mod event_log;
 pub mod events;
 pub mod intelligence_impl;
 pub mod monitoring;
 pub mod perception_impl;
 pub mod session;
+pub mod sinks;
 pub mod state;
 pub mod tools_impl;
 
 // Re-export main types
 pub use cache::{CacheCoordinator, UnifiedCache};
 pub use coordinator::RainbowCoordinator;
-pub use events::{Event, EventBus, EventHandler, EventType};
+pub use events::{
+    Event, EventBus, EventBusConfig, EventFilter, EventHandler, EventSource, EventType,
+    TimestampedEvent,
+};
 pub use monitoring::{ModuleHealth, UnifiedMonitoring};
 pub use session::{SessionBundle, SessionContext};
 pub use state::{BrowserState, PerceptionState, ToolState, UnifiedStateManager};
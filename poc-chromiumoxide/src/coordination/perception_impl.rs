@@ -9,6 +9,7 @@ use tokio::sync::RwLock;
 use tracing::{debug, info};
 
 use crate::browser::Browser;
+use crate::perception::visual_diff::{self, VisualDiff};
 
 // Placeholder for PerceptionEngine - will be replaced with real implementation
 pub struct PerceptionEngine {
@@ -66,6 +67,7 @@ pub enum PageType {
     Generic,
 }
 use super::{
+    events::ContentChangeType,
     monitoring::HealthStatus,
     session::{
         ActionAnalysis, ExecutionResult, IntelligentActionRequest, PageAnalysis, SessionContext,
@@ -85,6 +87,8 @@ pub struct RealCoordinatedPerceptionEngine {
     event_bus: Arc<EventBus>,
     state_manager: Arc<UnifiedStateManager>,
     _context: Arc<RwLock<PerceptionContext>>,
+    /// Viewport screenshot actions are compared against during verification
+    baseline_screenshot: Arc<RwLock<Option<Vec<u8>>>>,
 
     // Metrics
     operations_count: Arc<RwLock<u64>>,
@@ -113,6 +117,7 @@ impl RealCoordinatedPerceptionEngine {
             event_bus: event_bus.clone(),
             state_manager,
             _context: context,
+            baseline_screenshot: Arc::new(RwLock::new(None)),
             operations_count: Arc::new(RwLock::new(0)),
             last_operation: Arc::new(RwLock::new(None)),
             error_count: Arc::new(RwLock::new(0)),
@@ -304,15 +309,51 @@ impl RealCoordinatedPerceptionEngine {
         Ok(analysis)
     }
 
-    /// Verify action result
+    /// Remember how the page looks before an action, for `verify_action_result`
+    pub async fn capture_baseline(&self) {
+        let screenshot = self.viewport_screenshot().await;
+        *self.baseline_screenshot.write().await = screenshot;
+    }
+
+    async fn viewport_screenshot(&self) -> Option<Vec<u8>> {
+        let options = crate::browser::ScreenshotOptions {
+            full_page: false,
+            wait_after_load: std::time::Duration::ZERO,
+            ..crate::browser::ScreenshotOptions::default()
+        };
+        match self.browser.screenshot(options).await {
+            Ok(screenshot) => Some(screenshot),
+            Err(e) => {
+                debug!("Screenshot for change detection failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Compare the page with the baseline and make the current look the new baseline
+    async fn visual_change(&self) -> Option<VisualDiff> {
+        let before = self.baseline_screenshot.write().await.take()?;
+        let after = self.viewport_screenshot().await?;
+        let diff = visual_diff::compare(&before, &after).ok();
+        *self.baseline_screenshot.write().await = Some(after);
+        diff
+    }
+
+    /// Verify action result, using the visible page change since `capture_baseline`
+    /// when a baseline was captured
     pub async fn verify_action_result(
         &self,
         result: &ExecutionResult,
     ) -> Result<VerificationResult> {
         debug!("Verifying action result");
 
-        // Simple verification based on execution success
-        // In a real implementation, this would check DOM changes, etc.
+        if result.success {
+            if let Some(diff) = self.visual_change().await {
+                return self.verify_visual_change(diff).await;
+            }
+        }
+
+        // Without screenshots, verification is based on execution success
         let verification = if result.success {
             VerificationResult {
                 success: true,
@@ -334,6 +375,45 @@ impl RealCoordinatedPerceptionEngine {
         Ok(verification)
     }
 
+    async fn verify_visual_change(&self, diff: VisualDiff) -> Result<VerificationResult> {
+        if !diff.changed {
+            return Ok(VerificationResult {
+                success: false,
+                confidence: 0.3,
+                error: Some("Action left the page visually unchanged".to_string()),
+                changes_detected: vec![],
+                verification_method: "visual_diff".to_string(),
+            });
+        }
+
+        self.event_bus
+            .emit(Event::PageContentChanged {
+                session_id: self.session_id.clone(),
+                change_type: ContentChangeType::DomMutation,
+                timestamp: Instant::now(),
+            })
+            .await?;
+
+        let mut changes_detected = vec![diff.describe()];
+        changes_detected.extend(diff.regions.iter().map(|r| {
+            format!(
+                "region at {:.0}%,{:.0}% ({:.0}%x{:.0}%)",
+                r.x * 100.0,
+                r.y * 100.0,
+                r.width * 100.0,
+                r.height * 100.0
+            )
+        }));
+        Ok(VerificationResult {
+            success: true,
+            // Larger changes are stronger evidence that the action took effect
+            confidence: (0.8 + diff.changed_ratio as f64).min(1.0),
+            error: None,
+            changes_detected,
+            verification_method: "visual_diff".to_string(),
+        })
+    }
+
     /// Cleanup perception engine
     pub async fn cleanup(&self) -> Result<()> {
         info!(
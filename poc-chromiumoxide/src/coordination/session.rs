@@ -283,9 +283,12 @@ impl SessionBundle {
 
         // Phase 3: Tool Execution
         debug!("Phase 3: Executing planned action");
+        self.perception.capture_baseline().await;
         let execution_result = self.tools.execute_planned_action(plan.clone()).await?;
 
         // Phase 4: Verification & Learning
+        // Verification compares the page against the baseline, so learning sees
+        // actions that left the page unchanged as failures
         debug!("Phase 4: Verifying results and applying learning");
         let verification = self
             .perception
@@ -460,6 +463,10 @@ impl CoordinatedPerceptionEngine {
         self.inner.analyze_current_page().await
     }
 
+    pub async fn capture_baseline(&self) {
+        self.inner.capture_baseline().await
+    }
+
     pub async fn verify_action_result(
         &self,
         result: &ExecutionResult,
@@ -0,0 +1,165 @@
+use anyhow::{anyhow, bail, Result};
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::client::{ClientBuilder, Credentials, SaslConfig};
+use rskafka::record::Record;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use url::Url;
+
+use super::{record, redacted, within_timeout, EventSink};
+use crate::coordination::events::TimestampedEvent;
+
+/// Produces to a Kafka topic with the rskafka client, over TLS for `kafka+ssl://`
+/// URLs. Records are keyed by session and a session's records always go to the
+/// same partition, so they stay in order.
+pub struct KafkaSink {
+    url: Url,
+    topic: String,
+    /// One client per partition of the topic, by partition index
+    partitions: Option<Vec<PartitionClient>>,
+}
+
+impl KafkaSink {
+    pub(super) fn new(url: &Url) -> Self {
+        let topic = url.path().trim_matches('/');
+        Self {
+            url: url.clone(),
+            topic: if topic.is_empty() {
+                "rainbow-events".to_string()
+            } else {
+                topic.to_string()
+            },
+            partitions: None,
+        }
+    }
+
+    /// SASL from the URL's user and password: PLAIN unless `?sasl=scram-sha-256`
+    /// or `?sasl=scram-sha-512` asks for SCRAM
+    fn sasl(&self) -> Result<Option<SaslConfig>> {
+        if self.url.username().is_empty() {
+            return Ok(None);
+        }
+        let credentials = Credentials::new(
+            self.url.username().to_string(),
+            self.url.password().unwrap_or_default().to_string(),
+        );
+        let mechanism = self
+            .url
+            .query_pairs()
+            .find(|(name, _)| name == "sasl")
+            .map(|(_, value)| value.to_ascii_lowercase());
+        Ok(Some(match mechanism.as_deref() {
+            None | Some("plain") => SaslConfig::Plain(credentials),
+            Some("scram-sha-256") => SaslConfig::ScramSha256(credentials),
+            Some("scram-sha-512") => SaslConfig::ScramSha512(credentials),
+            Some(other) => bail!("Unsupported Kafka SASL mechanism {}", other),
+        }))
+    }
+
+    async fn connect(&self) -> Result<Vec<PartitionClient>> {
+        let broker = format!(
+            "{}:{}",
+            self.url.host_str().unwrap_or_default(),
+            self.url.port().unwrap_or(9092)
+        );
+        let mut builder = ClientBuilder::new(vec![broker]).client_id("rainbow-browser-ai");
+        if self.url.scheme() == "kafka+ssl" {
+            builder = builder.tls_config(Arc::new(tls_config()?));
+        }
+        if let Some(sasl) = self.sasl()? {
+            builder = builder.sasl_config(sasl);
+        }
+        let client = builder.build().await?;
+
+        let topic = client
+            .list_topics()
+            .await?
+            .into_iter()
+            .find(|topic| topic.name == self.topic)
+            .ok_or_else(|| anyhow!("Kafka topic {} does not exist", self.topic))?;
+        let mut partitions = Vec::with_capacity(topic.partitions.len());
+        for partition in topic.partitions {
+            partitions.push(
+                client
+                    .partition_client(self.topic.clone(), partition, UnknownTopicHandling::Error)
+                    .await?,
+            );
+        }
+        if partitions.is_empty() {
+            bail!("Kafka topic {} has no partitions", self.topic);
+        }
+        Ok(partitions)
+    }
+
+    async fn try_publish(&mut self, events: &[TimestampedEvent]) -> Result<()> {
+        if self.partitions.is_none() {
+            self.partitions = Some(self.connect().await?);
+        }
+        let Some(partitions) = self.partitions.as_ref() else {
+            unreachable!("connected above");
+        };
+
+        let mut batches: HashMap<usize, Vec<Record>> = HashMap::new();
+        for logged in events {
+            let key = logged.event.session_id();
+            batches
+                .entry(partition_for(key, logged.sequence_id, partitions.len()))
+                .or_default()
+                .push(Record {
+                    key: key.map(|key| key.as_bytes().to_vec()),
+                    value: Some(serde_json::to_vec(&record(logged))?),
+                    headers: BTreeMap::new(),
+                    timestamp: logged.occurred_at,
+                });
+        }
+        for (partition, records) in batches {
+            partitions[partition]
+                .produce(records, Compression::NoCompression)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Partition of a record: the same for every record of a session, spread by
+/// sequence for events outside sessions
+pub(super) fn partition_for(session_id: Option<&str>, sequence_id: u64, count: usize) -> usize {
+    match session_id {
+        Some(session_id) => {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            session_id.hash(&mut hasher);
+            (hasher.finish() % count as u64) as usize
+        }
+        None => (sequence_id % count as u64) as usize,
+    }
+}
+
+/// TLS trusting the Mozilla root certificates
+fn tls_config() -> Result<rustls::ClientConfig> {
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    Ok(rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_root_certificates(roots)
+    .with_no_client_auth())
+}
+
+#[async_trait::async_trait]
+impl EventSink for KafkaSink {
+    fn describe(&self) -> String {
+        format!("{} (topic {})", redacted(&self.url), self.topic)
+    }
+
+    async fn publish(&mut self, events: &[TimestampedEvent]) -> Result<()> {
+        let result = within_timeout(self.try_publish(events)).await;
+        if result.is_err() {
+            // Reconnect and look up the partitions afresh next time
+            self.partitions = None;
+        }
+        result
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{bail, Result};
+use url::Url;
+
+use super::{record, EventSink, BROKER_TIMEOUT};
+use crate::coordination::events::TimestampedEvent;
+
+/// POSTs records to a topic of a Kafka REST proxy (v2 JSON API), keyed by
+/// session so a session's events stay in order on one partition
+pub struct KafkaRestSink {
+    endpoint: String,
+    credentials: Option<(String, Option<String>)>,
+    client: reqwest::Client,
+}
+
+impl KafkaRestSink {
+    pub(super) fn new(url: &Url) -> Self {
+        let scheme = url.scheme().trim_start_matches("kafka+");
+        let topic = url.path().trim_matches('/');
+        let topic = if topic.is_empty() {
+            "rainbow-events"
+        } else {
+            topic
+        };
+        let host = url.host_str().unwrap_or_default();
+        let endpoint = match url.port() {
+            Some(port) => format!("{}://{}:{}/topics/{}", scheme, host, port, topic),
+            None => format!("{}://{}/topics/{}", scheme, host, topic),
+        };
+        Self {
+            endpoint,
+            credentials: (!url.username().is_empty()).then(|| {
+                (
+                    url.username().to_string(),
+                    url.password().map(str::to_string),
+                )
+            }),
+            client: reqwest::Client::builder()
+                .timeout(BROKER_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventSink for KafkaRestSink {
+    fn describe(&self) -> String {
+        self.endpoint.clone()
+    }
+
+    async fn publish(&mut self, events: &[TimestampedEvent]) -> Result<()> {
+        let records: Vec<_> = events
+            .iter()
+            .map(|logged| {
+                serde_json::json!({
+                    "key": logged.event.session_id(),
+                    "value": record(logged),
+                })
+            })
+            .collect();
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header("Content-Type", "application/vnd.kafka.json.v2+json")
+            .header("Accept", "application/vnd.kafka.v2+json")
+            .body(serde_json::json!({ "records": records }).to_string());
+        if let Some((user, password)) = &self.credentials {
+            request = request.basic_auth(user, password.as_ref());
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            bail!("Kafka REST proxy answered {}: {}", status, body);
+        }
+        // The proxy answers 200 even when single records were rejected
+        let rejected = body["offsets"]
+            .as_array()
+            .map(|offsets| offsets.iter().filter(|o| !o["error"].is_null()).count())
+            .unwrap_or(0);
+        if rejected > 0 {
+            bail!("Kafka rejected {} of {} records", rejected, events.len());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use url::Url;
+
+use super::{record, redacted, type_name, within_timeout, EventSink};
+use crate::coordination::events::TimestampedEvent;
+
+/// Publishes to NATS subjects with the async-nats client, over TLS for
+/// `nats+tls://` URLs
+pub struct NatsSink {
+    url: Url,
+    subject: String,
+    client: Option<async_nats::Client>,
+}
+
+impl NatsSink {
+    pub(super) fn new(url: &Url) -> Self {
+        let subject = url.path().trim_matches('/').replace('/', ".");
+        Self {
+            url: url.clone(),
+            subject: if subject.is_empty() {
+                "rainbow.events".to_string()
+            } else {
+                subject
+            },
+            client: None,
+        }
+    }
+
+    async fn connect(&self) -> Result<async_nats::Client> {
+        // `?creds=` names a credentials file holding an NKey seed and user JWT
+        let mut options = match self.url.query_pairs().find(|(name, _)| name == "creds") {
+            Some((_, path)) => async_nats::ConnectOptions::with_credentials_file(path.as_ref())
+                .await
+                .with_context(|| format!("Reading NATS credentials {}", path))?,
+            None => async_nats::ConnectOptions::new(),
+        };
+        // A user without a password is a token
+        match (self.url.username(), self.url.password()) {
+            ("", _) => {}
+            (token, None) => options = options.token(token.to_string()),
+            (user, Some(pass)) => {
+                options = options.user_and_password(user.to_string(), pass.to_string())
+            }
+        }
+        let address = format!(
+            "nats://{}:{}",
+            self.url.host_str().unwrap_or_default(),
+            self.url.port().unwrap_or(4222)
+        );
+        Ok(options
+            .name("rainbow-browser-ai")
+            .require_tls(self.url.scheme() == "nats+tls")
+            .connect(address)
+            .await?)
+    }
+
+    async fn try_publish(&mut self, events: &[TimestampedEvent]) -> Result<()> {
+        if self.client.is_none() {
+            self.client = Some(self.connect().await?);
+        }
+        let Some(client) = self.client.as_ref() else {
+            unreachable!("connected above");
+        };
+        for logged in events {
+            let payload = serde_json::to_vec(&record(logged))?;
+            let subject = format!("{}.{}", self.subject, type_name(logged));
+            client.publish(subject, payload.into()).await?;
+        }
+        // Returns once everything above is written to the server; core NATS
+        // publishes are not acknowledged
+        client.flush().await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl EventSink for NatsSink {
+    fn describe(&self) -> String {
+        format!("{} ({}.*)", redacted(&self.url), self.subject)
+    }
+
+    async fn publish(&mut self, events: &[TimestampedEvent]) -> Result<()> {
+        let result = within_timeout(self.try_publish(events)).await;
+        if result.is_err() {
+            // Start over on a fresh connection next time
+            self.client = None;
+        }
+        result
+    }
+}
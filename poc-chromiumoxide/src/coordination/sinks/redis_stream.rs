@@ -0,0 +1,123 @@
+use anyhow::Result;
+use url::Url;
+
+use super::{record, redacted, type_name, within_timeout, EventSink};
+use crate::coordination::events::TimestampedEvent;
+
+/// Appends a batch to the stream, all of it or none. The first key marks the
+/// batch as written, so a retry of a batch whose reply was lost adds nothing.
+const APPEND_BATCH: &str = r"
+if not redis.call('SET', KEYS[2], 1, 'NX', 'EX', ARGV[1]) then
+    return 0
+end
+local max_len = tonumber(ARGV[2])
+for i = 3, #ARGV, 2 do
+    if max_len > 0 then
+        redis.call('XADD', KEYS[1], 'MAXLEN', '~', max_len, '*', 'type', ARGV[i], 'data', ARGV[i + 1])
+    else
+        redis.call('XADD', KEYS[1], '*', 'type', ARGV[i], 'data', ARGV[i + 1])
+    end
+end
+return (#ARGV - 2) / 2
+";
+
+/// How long a batch's marker outlives it, well beyond the last retry
+const MARKER_TTL_SECS: u64 = 600;
+
+/// Appends to a Redis stream with XADD, one entry per event with its `type` and
+/// JSON `data`, over TLS for `rediss://` URLs. A batch is appended by one Lua
+/// script, so it is written whole or not at all and never twice.
+pub struct RedisStreamSink {
+    url: Url,
+    key: String,
+    /// Approximate length the stream is trimmed to (`?maxlen=`)
+    pub(super) max_len: Option<u64>,
+    /// Tells this process's batch markers from those of earlier runs, whose
+    /// sequence ids may repeat
+    run_id: String,
+    script: redis::Script,
+    connection: Option<redis::aio::MultiplexedConnection>,
+}
+
+impl RedisStreamSink {
+    pub(super) fn new(url: &Url) -> Self {
+        let key = url.path().trim_matches('/').to_string();
+        let max_len = url
+            .query_pairs()
+            .find(|(name, _)| name == "maxlen")
+            .and_then(|(_, value)| value.parse().ok());
+        Self {
+            url: url.clone(),
+            key: if key.is_empty() {
+                "rainbow:events".to_string()
+            } else {
+                key
+            },
+            max_len,
+            run_id: uuid::Uuid::new_v4().simple().to_string(),
+            script: redis::Script::new(APPEND_BATCH),
+            connection: None,
+        }
+    }
+
+    async fn connect(&self) -> Result<redis::aio::MultiplexedConnection> {
+        // The path names the stream here, not the database as in redis URLs
+        let mut server = self.url.clone();
+        server.set_path("");
+        server.set_query(None);
+        let client = redis::Client::open(server.as_str())?;
+        Ok(client.get_multiplexed_async_connection().await?)
+    }
+
+    /// Key marking `events` as written
+    pub(super) fn marker(&self, events: &[TimestampedEvent]) -> String {
+        let sequence = |logged: Option<&TimestampedEvent>| logged.map_or(0, |l| l.sequence_id);
+        format!(
+            "{}:batch:{}:{}-{}",
+            self.key,
+            self.run_id,
+            sequence(events.first()),
+            sequence(events.last())
+        )
+    }
+
+    /// The script call appending `events`
+    pub(super) fn invocation(&self, events: &[TimestampedEvent]) -> redis::ScriptInvocation<'_> {
+        let mut invocation = self.script.prepare_invoke();
+        invocation
+            .key(&self.key)
+            .key(self.marker(events))
+            .arg(MARKER_TTL_SECS)
+            .arg(self.max_len.unwrap_or(0));
+        for logged in events {
+            invocation
+                .arg(type_name(logged))
+                .arg(record(logged).to_string());
+        }
+        invocation
+    }
+
+    async fn try_publish(&mut self, events: &[TimestampedEvent]) -> Result<()> {
+        let mut connection = match self.connection.take() {
+            Some(connection) => connection,
+            None => self.connect().await?,
+        };
+        self.invocation(events)
+            .invoke_async::<()>(&mut connection)
+            .await?;
+        // Kept only once it has worked, so a failure reconnects next time
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl EventSink for RedisStreamSink {
+    fn describe(&self) -> String {
+        format!("{} (stream {})", redacted(&self.url), self.key)
+    }
+
+    async fn publish(&mut self, events: &[TimestampedEvent]) -> Result<()> {
+        within_timeout(self.try_publish(events)).await
+    }
+}
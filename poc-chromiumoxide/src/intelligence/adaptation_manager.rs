@@ -1,9 +1,38 @@
 // Adaptation Manager
 // Manages adaptive strategies for different environments and contexts
+//
+// Per-domain profiles record what is known about a site, keyed by origin:
+// how long its pages need to settle, which element marks them ready, which
+// overlays to dismiss, and which selector strategies hold up there. They are
+// applied after every navigation to that origin.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Longest settle time a profile may ask for after navigation
+pub const MAX_SETTLE_MS: u64 = 30_000;
+
+/// Selector strategies a profile can prefer, most specific first
+pub const SELECTOR_STRATEGIES: &[&str] = &["test_id", "aria", "id", "css", "text", "xpath"];
+
+/// Clicks each visible element matching a selector in __SELECTORS__, returns the ones clicked
+const DISMISS_SCRIPT: &str = r#"(function(){
+    var selectors = __SELECTORS__;
+    var clicked = [];
+    selectors.forEach(function(sel){
+        var el;
+        try { el = document.querySelector(sel); } catch (e) { return; }
+        if (!el) return;
+        var rect = el.getBoundingClientRect();
+        if (rect.width === 0 && rect.height === 0) return;
+        el.click();
+        clicked.push(sel);
+    });
+    return clicked;
+})()"#;
 
 /// Manages adaptation strategies for different contexts
 #[allow(dead_code)]
@@ -11,6 +40,7 @@ use std::collections::HashMap;
 pub struct AdaptationManager {
     strategies: HashMap<String, AdaptationStrategy>,
     environment_contexts: HashMap<String, EnvironmentContext>,
+    domain_profiles: HashMap<String, DomainProfile>,
 }
 
 /// Strategy for adapting to specific conditions
@@ -33,20 +63,109 @@ pub struct EnvironmentContext {
     pub performance_characteristics: HashMap<String, f64>,
 }
 
+/// What is known about automating one origin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainProfile {
+    /// Origin the profile applies to, e.g. `https://shop.example.com`
+    pub origin: String,
+    /// Extra time to let pages settle after load
+    #[serde(default)]
+    pub settle_ms: Option<u64>,
+    /// Element whose presence means the page is ready
+    #[serde(default)]
+    pub ready_selector: Option<String>,
+    /// Overlays (cookie banners, popups) to click away after navigation
+    #[serde(default)]
+    pub dismiss_selectors: Vec<String>,
+    /// Selector strategies that work on this origin, preferred first
+    #[serde(default)]
+    pub selector_strategies: Vec<String>,
+    /// Free-form notes on known quirks
+    #[serde(default)]
+    pub quirks: Vec<String>,
+    #[serde(default = "chrono::Utc::now")]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// What applying a domain profile did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedProfile {
+    pub origin: String,
+    pub settled_ms: u64,
+    /// Whether `ready_selector` appeared, if the profile has one
+    pub ready: Option<bool>,
+    pub dismissed: Vec<String>,
+    pub selector_strategies: Vec<String>,
+    pub quirks: Vec<String>,
+}
+
 impl AdaptationManager {
     pub fn new() -> Self {
         Self {
             strategies: HashMap::new(),
             environment_contexts: HashMap::new(),
+            domain_profiles: HashMap::new(),
         }
     }
 
     pub async fn suggest_adaptations(
         &self,
-        _page_context: &super::organic_perception::PageContext,
+        page_context: &super::organic_perception::PageContext,
         _patterns: &[super::pattern_recognition::SuccessPattern],
     ) -> Result<Vec<AdaptationStrategy>> {
-        Ok(vec![])
+        Ok(self
+            .profile_for(&page_context.url)
+            .map(profile_strategies)
+            .unwrap_or_default())
+    }
+
+    /// Profile for the origin of `url`, if one is known
+    pub fn profile_for(&self, url: &str) -> Option<&DomainProfile> {
+        self.domain_profiles.get(&origin_of(url)?)
+    }
+
+    /// Add or replace a profile, normalizing its origin
+    pub fn set_profile(&mut self, mut profile: DomainProfile) -> Result<DomainProfile> {
+        profile.origin = origin_of(&profile.origin)
+            .ok_or_else(|| anyhow!("Not a web origin: {}", profile.origin))?;
+        if profile.settle_ms.is_some_and(|ms| ms > MAX_SETTLE_MS) {
+            return Err(anyhow!("settle_ms may be at most {}", MAX_SETTLE_MS));
+        }
+        if let Some(unknown) = profile
+            .selector_strategies
+            .iter()
+            .find(|s| !SELECTOR_STRATEGIES.contains(&s.as_str()))
+        {
+            return Err(anyhow!(
+                "Unknown selector strategy '{}', expected one of {}",
+                unknown,
+                SELECTOR_STRATEGIES.join(", ")
+            ));
+        }
+        profile.updated_at = chrono::Utc::now();
+        info!("Saved domain profile for {}", profile.origin);
+        self.domain_profiles
+            .insert(profile.origin.clone(), profile.clone());
+        Ok(profile)
+    }
+
+    pub fn remove_profile(&mut self, origin: &str) -> Option<DomainProfile> {
+        self.domain_profiles.remove(&origin_of(origin)?)
+    }
+
+    /// All profiles, sorted by origin
+    pub fn profiles(&self) -> Vec<DomainProfile> {
+        let mut profiles: Vec<DomainProfile> = self.domain_profiles.values().cloned().collect();
+        profiles.sort_by(|a, b| a.origin.cmp(&b.origin));
+        profiles
+    }
+
+    /// Replace profiles with a persisted copy
+    pub fn restore_profiles(&mut self, profiles: Vec<DomainProfile>) {
+        self.domain_profiles = profiles
+            .into_iter()
+            .map(|p| (p.origin.clone(), p))
+            .collect();
     }
 }
 
@@ -55,3 +174,151 @@ impl Default for AdaptationManager {
         Self::new()
     }
 }
+
+impl DomainProfile {
+    /// Apply the profile to a page that just navigated to its origin
+    pub async fn apply(&self, browser: &crate::browser::Browser) -> Result<AppliedProfile> {
+        let settled_ms = self.settle_ms.unwrap_or(0).min(MAX_SETTLE_MS);
+        if settled_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(settled_ms)).await;
+        }
+
+        let ready = match &self.ready_selector {
+            Some(selector) => Some(
+                browser
+                    .wait_for_selector(selector, Duration::from_secs(10))
+                    .await
+                    .is_ok(),
+            ),
+            None => None,
+        };
+
+        let mut dismissed = Vec::new();
+        if !self.dismiss_selectors.is_empty() {
+            let script = DISMISS_SCRIPT.replace(
+                "__SELECTORS__",
+                &serde_json::to_string(&self.dismiss_selectors)?,
+            );
+            let clicked = browser.execute_script(&script).await?;
+            dismissed = serde_json::from_value(clicked).unwrap_or_default();
+        }
+
+        debug!(
+            "Applied domain profile {}: settled {}ms, ready={:?}, dismissed {:?}",
+            self.origin, settled_ms, ready, dismissed
+        );
+        Ok(AppliedProfile {
+            origin: self.origin.clone(),
+            settled_ms,
+            ready,
+            dismissed,
+            selector_strategies: self.selector_strategies.clone(),
+            quirks: self.quirks.clone(),
+        })
+    }
+}
+
+/// `scheme://host[:port]` of a URL or bare host; `None` for non-web URLs
+pub fn origin_of(url: &str) -> Option<String> {
+    let url = url.trim();
+    let parsed = if url.contains("://") {
+        url::Url::parse(url).ok()?
+    } else {
+        url::Url::parse(&format!("https://{}", url)).ok()?
+    };
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return None;
+    }
+    Some(parsed.origin().ascii_serialization())
+}
+
+/// Adaptation strategies that carry a profile into intelligence decisions
+fn profile_strategies(profile: &DomainProfile) -> Vec<AdaptationStrategy> {
+    let strategy =
+        |name: &str, adaptation_type: &str, value: serde_json::Value| AdaptationStrategy {
+            name: name.to_string(),
+            condition: format!("origin == {}", profile.origin),
+            adaptation_type: adaptation_type.to_string(),
+            parameters: HashMap::from([("value".to_string(), value)]),
+            confidence: 0.9,
+            fallback_strategy: None,
+        };
+
+    let mut strategies = Vec::new();
+    if let Some(ms) = profile.settle_ms {
+        strategies.push(strategy("domain_settle_time", "wait", ms.into()));
+    }
+    if let Some(selector) = &profile.ready_selector {
+        strategies.push(strategy(
+            "domain_ready_selector",
+            "wait_for_selector",
+            selector.clone().into(),
+        ));
+    }
+    if !profile.selector_strategies.is_empty() {
+        strategies.push(strategy(
+            "domain_selector_strategies",
+            "selector_strategy",
+            profile.selector_strategies.clone().into(),
+        ));
+    }
+    for quirk in &profile.quirks {
+        strategies.push(strategy("domain_quirk", "quirk", quirk.clone().into()));
+    }
+    strategies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(origin: &str) -> DomainProfile {
+        serde_json::from_value(serde_json::json!({
+            "origin": origin,
+            "settle_ms": 800,
+            "selector_strategies": ["test_id", "aria"],
+            "quirks": ["Search results load after a second request"]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn origins_normalize_urls_and_hosts() {
+        assert_eq!(
+            origin_of("https://Shop.Example.com/cart?x=1").as_deref(),
+            Some("https://shop.example.com")
+        );
+        assert_eq!(
+            origin_of("example.com").as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            origin_of("http://localhost:8080/").as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert!(origin_of("file:///tmp/page.html").is_none());
+    }
+
+    #[test]
+    fn profiles_are_keyed_by_origin() {
+        let mut manager = AdaptationManager::new();
+        let saved = manager.set_profile(profile("shop.example.com")).unwrap();
+        assert_eq!(saved.origin, "https://shop.example.com");
+
+        let found = manager
+            .profile_for("https://shop.example.com/products/42")
+            .unwrap();
+        assert_eq!(found.settle_ms, Some(800));
+        assert!(manager.profile_for("http://shop.example.com/").is_none());
+        assert_eq!(profile_strategies(found).len(), 3);
+
+        let mut bad = profile("example.org");
+        bad.selector_strategies = vec!["guess".to_string()];
+        assert!(manager.set_profile(bad).is_err());
+
+        assert!(manager
+            .remove_profile("https://shop.example.com/")
+            .is_some());
+        assert!(manager.profiles().is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+// Anomaly Detection
+// Watches action outcomes per origin and flags behavior that no longer matches
+// what the site usually does
+//
+// Three signals are tracked: selector failure rates jumping above their
+// baseline, navigations ending up on a different origin than requested, and
+// large layout shifts while an action ran. Each anomaly carries the response it
+// calls for, so a workflow can re-perceive the page or stop for human review
+// instead of carrying on blindly.
+
+use super::adaptation_manager::origin_of;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Anomaly events kept for inspection
+const MAX_EVENTS: usize = 200;
+
+/// Sums layout-shift entries not caused by input within the last __WINDOW_MS__ ms
+const LAYOUT_SHIFT_SCRIPT: &str = r#"new Promise(function(resolve){
+    if (typeof PerformanceObserver === 'undefined') { resolve(null); return; }
+    var since = performance.now() - __WINDOW_MS__;
+    var total = 0;
+    try {
+        new PerformanceObserver(function(list){
+            list.getEntries().forEach(function(e){
+                if (!e.hadRecentInput && e.startTime >= since) total += e.value;
+            });
+        }).observe({ type: 'layout-shift', buffered: true });
+    } catch (e) { resolve(null); return; }
+    setTimeout(function(){ resolve(total); }, 50);
+})"#;
+
+/// What kind of unexpected behavior was seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// Selector-based actions started failing far more often than usual
+    SelectorFailureSpike,
+    /// A navigation landed on a different origin than requested
+    UnexpectedRedirect,
+    /// The layout moved substantially while an action ran
+    LayoutShift,
+}
+
+/// What should happen before the workflow continues
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyResponse {
+    /// Perceive the page again; element knowledge is probably stale
+    Reperceive,
+    /// Stop and let a person look at the page
+    HumanReview,
+}
+
+/// Raised when an origin behaves unexpectedly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyEvent {
+    pub id: String,
+    pub origin: String,
+    pub kind: AnomalyKind,
+    pub response: AnomalyResponse,
+    pub action_type: String,
+    pub selector: Option<String>,
+    pub detail: String,
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AnomalyEvent {
+    pub fn requires_review(&self) -> bool {
+        self.response == AnomalyResponse::HumanReview
+    }
+}
+
+/// Outcome of one action, as seen by the detector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionObservation {
+    /// Page the action ran on
+    pub url: String,
+    pub action_type: String,
+    /// Selector the action targeted; only these count towards failure rates
+    #[serde(default)]
+    pub selector: Option<String>,
+    pub success: bool,
+    /// Where a navigation was asked to go
+    #[serde(default)]
+    pub expected_url: Option<String>,
+    /// Where the page was after the action
+    #[serde(default)]
+    pub final_url: Option<String>,
+    /// Layout shift score accumulated while the action ran
+    #[serde(default)]
+    pub layout_shift: Option<f64>,
+}
+
+/// When outcomes count as anomalous
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyThresholds {
+    /// Recent selector actions compared against the origin's baseline
+    pub recent_window: usize,
+    /// Failure rate over the recent window that counts as a spike
+    pub failure_rate: f64,
+    /// How far above the baseline failure rate the spike must be
+    pub failure_margin: f64,
+    /// Layout shift score per action that counts as a large shift
+    pub layout_shift: f64,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            recent_window: 5,
+            failure_rate: 0.6,
+            failure_margin: 0.3,
+            // Web Vitals rates a cumulative layout shift above 0.25 as poor
+            layout_shift: 0.25,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct OriginHistory {
+    recent: VecDeque<bool>,
+    baseline_total: u64,
+    baseline_failures: u64,
+}
+
+impl OriginHistory {
+    fn baseline_failure_rate(&self) -> f64 {
+        if self.baseline_total == 0 {
+            0.0
+        } else {
+            self.baseline_failures as f64 / self.baseline_total as f64
+        }
+    }
+}
+
+/// Tracks action outcomes per origin and raises anomaly events
+#[derive(Debug, Default)]
+pub struct AnomalyDetector {
+    thresholds: AnomalyThresholds,
+    origins: HashMap<String, OriginHistory>,
+    events: VecDeque<AnomalyEvent>,
+}
+
+impl AnomalyDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_thresholds(thresholds: AnomalyThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Self::default()
+        }
+    }
+
+    /// Record an action outcome, returning the anomalies it revealed
+    pub fn observe(&mut self, observation: &ActionObservation) -> Vec<AnomalyEvent> {
+        let Some(origin) = origin_of(&observation.url)
+            .or_else(|| observation.expected_url.as_deref().and_then(origin_of))
+        else {
+            return Vec::new();
+        };
+
+        let mut raised = Vec::new();
+        if let Some(detail) = self.track_selector_outcome(&origin, observation) {
+            let response = if detail.total_failure {
+                AnomalyResponse::HumanReview
+            } else {
+                AnomalyResponse::Reperceive
+            };
+            raised.push(self.event(
+                &origin,
+                AnomalyKind::SelectorFailureSpike,
+                response,
+                observation,
+                detail.message,
+            ));
+        }
+
+        if let (Some(expected), Some(actual)) = (
+            observation.expected_url.as_deref().and_then(origin_of),
+            observation.final_url.as_deref().and_then(origin_of),
+        ) {
+            if expected != actual {
+                raised.push(self.event(
+                    &expected,
+                    AnomalyKind::UnexpectedRedirect,
+                    AnomalyResponse::HumanReview,
+                    observation,
+                    format!("Navigation to {} ended on {}", expected, actual),
+                ));
+            }
+        }
+
+        if let Some(shift) = observation.layout_shift {
+            if shift >= self.thresholds.layout_shift {
+                raised.push(self.event(
+                    &origin,
+                    AnomalyKind::LayoutShift,
+                    AnomalyResponse::Reperceive,
+                    observation,
+                    format!(
+                        "Layout shifted by {:.2} (threshold {:.2})",
+                        shift, self.thresholds.layout_shift
+                    ),
+                ));
+            }
+        }
+
+        for event in &raised {
+            warn!(
+                "Anomaly on {}: {:?} after {} ({})",
+                event.origin, event.kind, event.action_type, event.detail
+            );
+            self.events.push_back(event.clone());
+        }
+        while self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
+        }
+        raised
+    }
+
+    /// Most recent events first, optionally only those of one origin
+    pub fn events(&self, origin: Option<&str>, limit: usize) -> Vec<AnomalyEvent> {
+        let origin = origin.and_then(origin_of);
+        self.events
+            .iter()
+            .rev()
+            .filter(|e| match &origin {
+                Some(origin) => &e.origin == origin,
+                None => true,
+            })
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    fn track_selector_outcome(
+        &mut self,
+        origin: &str,
+        observation: &ActionObservation,
+    ) -> Option<SpikeDetail> {
+        observation.selector.as_ref()?;
+        let thresholds = &self.thresholds;
+        let history = self.origins.entry(origin.to_string()).or_default();
+
+        history.recent.push_back(observation.success);
+        if history.recent.len() > thresholds.recent_window {
+            let success = history.recent.pop_front().unwrap_or(true);
+            history.baseline_total += 1;
+            if !success {
+                history.baseline_failures += 1;
+            }
+        }
+        if history.recent.len() < thresholds.recent_window {
+            return None;
+        }
+
+        let failures = history.recent.iter().filter(|s| !**s).count();
+        let rate = failures as f64 / history.recent.len() as f64;
+        let baseline = history.baseline_failure_rate();
+        if rate < thresholds.failure_rate || rate - baseline < thresholds.failure_margin {
+            return None;
+        }
+
+        debug!(
+            "Selector failure spike on {}: {:.0}% vs baseline {:.0}%",
+            origin,
+            rate * 100.0,
+            baseline * 100.0
+        );
+        // Start a fresh window so one spike is reported once
+        history.recent.clear();
+        Some(SpikeDetail {
+            total_failure: failures == thresholds.recent_window,
+            message: format!(
+                "{} of the last {} selector actions failed (baseline failure rate {:.0}%)",
+                failures,
+                thresholds.recent_window,
+                baseline * 100.0
+            ),
+        })
+    }
+
+    fn event(
+        &self,
+        origin: &str,
+        kind: AnomalyKind,
+        response: AnomalyResponse,
+        observation: &ActionObservation,
+        detail: String,
+    ) -> AnomalyEvent {
+        AnomalyEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            origin: origin.to_string(),
+            kind,
+            response,
+            action_type: observation.action_type.clone(),
+            selector: observation.selector.clone(),
+            detail,
+            detected_at: chrono::Utc::now(),
+        }
+    }
+}
+
+struct SpikeDetail {
+    total_failure: bool,
+    message: String,
+}
+
+/// Layout shift score of the page over the last `window`, if the browser reports it
+pub async fn recent_layout_shift(
+    browser: &crate::browser::Browser,
+    window: Duration,
+) -> Option<f64> {
+    let script = LAYOUT_SHIFT_SCRIPT.replace("__WINDOW_MS__", &window.as_millis().to_string());
+    browser.execute_script(&script).await.ok()?.as_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click(success: bool) -> ActionObservation {
+        ActionObservation {
+            url: "https://shop.example.com/cart".to_string(),
+            action_type: "click".to_string(),
+            selector: Some("#checkout".to_string()),
+            success,
+            expected_url: None,
+            final_url: None,
+            layout_shift: None,
+        }
+    }
+
+    #[test]
+    fn selector_failure_spikes_are_raised_once() {
+        let mut detector = AnomalyDetector::new();
+        for _ in 0..10 {
+            assert!(detector.observe(&click(true)).is_empty());
+        }
+
+        for success in [false, true, false] {
+            assert!(detector.observe(&click(success)).is_empty());
+        }
+        let raised = detector.observe(&click(false));
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].kind, AnomalyKind::SelectorFailureSpike);
+        assert_eq!(raised[0].response, AnomalyResponse::Reperceive);
+        assert!(detector.observe(&click(false)).is_empty());
+
+        // Actions without a selector do not count
+        let mut scroll = click(false);
+        scroll.selector = None;
+        assert!(detector.observe(&scroll).is_empty());
+        assert_eq!(detector.events(Some("shop.example.com"), 10).len(), 1);
+    }
+
+    #[test]
+    fn redirects_and_layout_shifts_are_flagged() {
+        let mut detector = AnomalyDetector::new();
+        let navigate = ActionObservation {
+            url: "about:blank".to_string(),
+            action_type: "navigate".to_string(),
+            selector: None,
+            success: true,
+            expected_url: Some("https://shop.example.com/account".to_string()),
+            final_url: Some("https://login.example.net/sso".to_string()),
+            layout_shift: None,
+        };
+        let raised = detector.observe(&navigate);
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].origin, "https://shop.example.com");
+        assert!(raised[0].requires_review());
+
+        let mut shifted = click(true);
+        shifted.layout_shift = Some(0.4);
+        let raised = detector.observe(&shifted);
+        assert_eq!(raised[0].kind, AnomalyKind::LayoutShift);
+        assert_eq!(raised[0].response, AnomalyResponse::Reperceive);
+        assert_eq!(detector.events(None, 1)[0].kind, AnomalyKind::LayoutShift);
+    }
+}
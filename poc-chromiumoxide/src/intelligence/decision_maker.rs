@@ -5,6 +5,16 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Actions inferred from intent keywords, highest priority first
+const ACTION_KEYWORDS: &[(&str, &[&str])] = &[
+    ("click", &["click"]),
+    ("type", &["type", "enter"]),
+    ("navigate", &["navigate", "go to"]),
+];
+
+/// Elements listed in a trace beyond the chosen one
+const MAX_TRACED_ELEMENTS: usize = 5;
+
 /// Makes intelligent decisions for browser automation
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -23,9 +33,46 @@ pub struct Decision {
     pub reasoning: String,
     pub expected_outcome: String,
     pub alternatives: Vec<String>,
+    /// How the decision was reached
+    #[serde(default)]
+    pub trace: DecisionTrace,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// Auditable record of what a decision considered and why it chose what it did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DecisionTrace {
+    pub candidate_actions: Vec<TracedCandidate>,
+    pub candidate_elements: Vec<TracedCandidate>,
+    pub patterns_consulted: Vec<TracedCandidate>,
+    /// Adaptation strategies in effect, by name
+    pub adaptations_consulted: Vec<String>,
+    /// Alternatives dropped while building the recommendation
+    #[serde(default)]
+    pub rejected_alternatives: Vec<TracedCandidate>,
+}
+
+/// One option a decision weighed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracedCandidate {
+    pub name: String,
+    pub score: f64,
+    pub selected: bool,
+    /// Why the option won, or why it lost
+    pub reason: String,
+}
+
+impl TracedCandidate {
+    fn new(name: impl Into<String>, score: f64, selected: bool, reason: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            score,
+            selected,
+            reason: reason.into(),
+        }
+    }
+}
+
 /// Confidence level with detailed breakdown
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Confidence {
@@ -55,62 +102,236 @@ impl DecisionMaker {
         &self,
         user_intent: &str,
         perception_result: &super::organic_perception::PerceptionResult,
-        _learned_patterns: &[super::pattern_recognition::SuccessPattern],
-        _adaptations: &[super::adaptation_manager::AdaptationStrategy],
+        learned_patterns: &[super::pattern_recognition::SuccessPattern],
+        adaptations: &[super::adaptation_manager::AdaptationStrategy],
     ) -> Result<Decision> {
         // Simple decision making logic
-        let action_type = self.infer_action_type(user_intent);
-        let target_element = self.select_best_element(perception_result);
+        let (action_type, candidate_actions) = self.infer_action_type(user_intent);
+        let target = self.select_best_element(perception_result);
+        let trace = DecisionTrace {
+            candidate_actions,
+            candidate_elements: trace_elements(perception_result, target),
+            patterns_consulted: trace_patterns(learned_patterns, &action_type),
+            adaptations_consulted: adaptations.iter().map(|a| a.name.clone()).collect(),
+            rejected_alternatives: Vec::new(),
+        };
 
+        let mut uncertainty_sources = vec!["Limited pattern data".to_string()];
         let confidence = Confidence {
             value: perception_result.confidence * 0.8, // Adjust based on perception confidence
             factors: {
                 let mut factors = HashMap::new();
                 factors.insert("perception".to_string(), perception_result.confidence);
                 factors.insert("element_quality".to_string(), 0.8);
+                match target.and_then(|e| e.stability) {
+                    Some(stability) => {
+                        factors.insert("selector_stability".to_string(), stability);
+                    }
+                    None if target.is_some() => uncertainty_sources
+                        .push("Selector not yet seen across page loads".to_string()),
+                    None => {}
+                }
                 factors
             },
-            uncertainty_sources: vec!["Limited pattern data".to_string()],
+            uncertainty_sources,
         };
 
         Ok(Decision {
             action_type,
-            target_element,
+            target_element: target.map(|e| e.selector.clone()),
             parameters: HashMap::new(),
             confidence,
             reasoning: "Based on perception analysis and user intent".to_string(),
             expected_outcome: "Action should complete successfully".to_string(),
             alternatives: vec!["retry".to_string(), "fallback_selector".to_string()],
+            trace,
             timestamp: chrono::Utc::now(),
         })
     }
 
-    fn infer_action_type(&self, intent: &str) -> String {
+    /// Action for the intent, with every action considered and why it won or lost
+    fn infer_action_type(&self, intent: &str) -> (String, Vec<TracedCandidate>) {
         let intent_lower = intent.to_lowercase();
-        if intent_lower.contains("click") {
-            "click".to_string()
-        } else if intent_lower.contains("type") || intent_lower.contains("enter") {
-            "type".to_string()
-        } else if intent_lower.contains("navigate") || intent_lower.contains("go to") {
-            "navigate".to_string()
-        } else {
-            "generic_action".to_string()
+        let mut chosen: Option<&str> = None;
+        let mut candidates = Vec::new();
+        for (action, keywords) in ACTION_KEYWORDS {
+            let matched: Vec<&str> = keywords
+                .iter()
+                .copied()
+                .filter(|k| intent_lower.contains(k))
+                .collect();
+            let score = matched.len() as f64 / keywords.len() as f64;
+            let candidate = if matched.is_empty() {
+                TracedCandidate::new(*action, 0.0, false, "No keyword in intent")
+            } else if let Some(winner) = chosen {
+                TracedCandidate::new(
+                    *action,
+                    score,
+                    false,
+                    format!("Matched {:?} but '{}' takes priority", matched, winner),
+                )
+            } else {
+                chosen = Some(*action);
+                TracedCandidate::new(*action, score, true, format!("Matched {:?}", matched))
+            };
+            candidates.push(candidate);
         }
+
+        let action = chosen.unwrap_or("generic_action");
+        candidates.push(TracedCandidate::new(
+            "generic_action",
+            0.0,
+            chosen.is_none(),
+            match chosen {
+                Some(_) => "A specific action matched",
+                None => "No specific action matched the intent",
+            },
+        ));
+        (action.to_string(), candidates)
     }
 
-    fn select_best_element(
+    /// The most confident element, preferring selectors that proved stable across
+    /// page loads
+    fn select_best_element<'a>(
         &self,
-        perception_result: &super::organic_perception::PerceptionResult,
-    ) -> Option<String> {
-        perception_result
-            .elements
-            .first()
-            .map(|e| e.selector.clone())
+        perception_result: &'a super::organic_perception::PerceptionResult,
+    ) -> Option<&'a super::organic_perception::ElementInsight> {
+        // Earliest of equally ranked elements
+        perception_result.elements.iter().min_by(|a, b| {
+            b.ranking_score()
+                .partial_cmp(&a.ranking_score())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
     }
 }
 
+/// The chosen element and the best-ranked runners-up
+fn trace_elements(
+    perception_result: &super::organic_perception::PerceptionResult,
+    target: Option<&super::organic_perception::ElementInsight>,
+) -> Vec<TracedCandidate> {
+    let mut ranked: Vec<_> = perception_result.elements.iter().collect();
+    ranked.sort_by(|a, b| b.ranking_score().total_cmp(&a.ranking_score()));
+
+    let mut traced = Vec::new();
+    if let Some(target) = target {
+        traced.push(TracedCandidate::new(
+            target.selector.clone(),
+            target.ranking_score(),
+            true,
+            "Highest confidence weighted by selector stability",
+        ));
+    }
+    for element in ranked
+        .into_iter()
+        .filter(|e| !target.is_some_and(|t| std::ptr::eq(*e, t)))
+        .take(MAX_TRACED_ELEMENTS)
+    {
+        let reason = match element.stability {
+            Some(stability) if stability < 0.5 => {
+                format!("Ranked lower; selector unstable ({:.2})", stability)
+            }
+            _ => "Ranked lower".to_string(),
+        };
+        traced.push(TracedCandidate::new(
+            element.selector.clone(),
+            element.ranking_score(),
+            false,
+            reason,
+        ));
+    }
+    traced
+}
+
+/// Learned patterns and whether they back the chosen action
+fn trace_patterns(
+    patterns: &[super::pattern_recognition::SuccessPattern],
+    action_type: &str,
+) -> Vec<TracedCandidate> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let supports = pattern
+                .action_sequence
+                .first()
+                .is_some_and(|step| step.action_type == action_type);
+            let reason = if supports {
+                format!(
+                    "Starts with '{}', {} past successes",
+                    action_type, pattern.success_count
+                )
+            } else {
+                format!("Does not start with '{}'", action_type)
+            };
+            TracedCandidate::new(pattern.name.clone(), pattern.confidence, supports, reason)
+        })
+        .collect()
+}
+
 impl Default for DecisionMaker {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::organic_perception::{ElementInsight, PerceptionResult};
+
+    fn element(selector: &str, confidence: f64, stability: Option<f64>) -> ElementInsight {
+        ElementInsight {
+            selector: selector.to_string(),
+            element_type: "button".to_string(),
+            confidence,
+            context_score: 0.5,
+            interaction_likelihood: 0.5,
+            visual_prominence: 0.5,
+            semantic_meaning: None,
+            alternative_selectors: Vec::new(),
+            predicted_behavior: None,
+            risk_factors: Vec::new(),
+            stability,
+        }
+    }
+
+    #[test]
+    fn traces_why_each_action_won_or_lost() {
+        let (action, candidates) =
+            DecisionMaker::new().infer_action_type("Click the field and type your name");
+        assert_eq!(action, "click");
+        let typed = candidates.iter().find(|c| c.name == "type").unwrap();
+        assert!(!typed.selected && typed.reason.contains("takes priority"));
+        let generic = candidates.last().unwrap();
+        assert_eq!(generic.name, "generic_action");
+        assert!(!generic.selected);
+
+        let (action, candidates) = DecisionMaker::new().infer_action_type("scroll down");
+        assert_eq!(action, "generic_action");
+        assert!(candidates.last().unwrap().selected);
+    }
+
+    #[tokio::test]
+    async fn decision_carries_element_trace() {
+        let perception = PerceptionResult {
+            elements: vec![
+                element("#flaky", 0.9, Some(0.2)),
+                element("#submit", 0.8, Some(1.0)),
+            ],
+            page_complexity: 0.3,
+            dynamic_elements: 0,
+            confidence: 0.9,
+            processing_time_ms: 10,
+        };
+        let decision = DecisionMaker::new()
+            .make_decision("click submit", &perception, &[], &[])
+            .await
+            .unwrap();
+
+        assert_eq!(decision.target_element.as_deref(), Some("#submit"));
+        let elements = &decision.trace.candidate_elements;
+        assert_eq!(elements.len(), 2);
+        assert!(elements[0].selected && elements[0].name == "#submit");
+        assert!(elements[1].reason.contains("unstable"));
+    }
+}
@@ -0,0 +1,191 @@
+// Element Embedding Index
+// Matches natural-language element queries by vector similarity
+//
+// Each element of the page is described the way a user would refer to it
+// (role, accessible name, description, value), embedded once, and compared to
+// the embedded query. This finds "the log-in button" for an element named
+// "Sign in to your account" when the provider knows synonyms, and tolerates
+// word order and inflections even with the offline provider.
+
+use crate::browser::{AxElement, Browser};
+use crate::llm::embeddings::{cosine_similarity, EmbeddingProvider};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Matches below this similarity are not worth returning
+pub const MIN_SIMILARITY: f32 = 0.2;
+
+/// What a user could refer to an element by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementDescription {
+    pub selector: String,
+    pub role: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+impl ElementDescription {
+    /// Description of an accessibility-tree element that has a selector
+    pub fn from_ax(element: AxElement) -> Option<Self> {
+        Some(Self {
+            selector: element.selector?,
+            role: element.role,
+            name: element.name,
+            description: element.description,
+            value: element.value,
+        })
+    }
+
+    /// Text embedded for the element, e.g. "button: Sign in. Uses your work account"
+    pub fn text(&self) -> String {
+        let mut text = format!("{}: {}", self.role, self.name);
+        for extra in [&self.description, &self.value].into_iter().flatten() {
+            if !extra.is_empty() {
+                text.push_str(". ");
+                text.push_str(extra);
+            }
+        }
+        text
+    }
+}
+
+/// Element found by similarity to a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementMatch {
+    pub selector: String,
+    pub role: String,
+    pub name: String,
+    pub similarity: f32,
+}
+
+/// Embedded descriptions of a page's elements
+pub struct ElementEmbeddingIndex {
+    provider: Arc<dyn EmbeddingProvider>,
+    entries: Vec<(ElementDescription, Vec<f32>)>,
+}
+
+impl ElementEmbeddingIndex {
+    /// Embed `elements` with `provider`
+    pub async fn build(
+        provider: Arc<dyn EmbeddingProvider>,
+        elements: Vec<ElementDescription>,
+    ) -> Result<Self> {
+        let texts: Vec<String> = elements.iter().map(|e| e.text()).collect();
+        let vectors = if texts.is_empty() {
+            Vec::new()
+        } else {
+            provider.embed(&texts).await?
+        };
+        debug!(
+            "Indexed {} elements with {} embeddings",
+            elements.len(),
+            provider.provider_name()
+        );
+        Ok(Self {
+            provider,
+            entries: elements.into_iter().zip(vectors).collect(),
+        })
+    }
+
+    /// Index the named elements of the browser's active page
+    pub async fn from_page(
+        browser: &Browser,
+        provider: Arc<dyn EmbeddingProvider>,
+    ) -> Result<Self> {
+        let elements = browser
+            .accessibility_tree(true)
+            .await?
+            .into_iter()
+            .filter(|e| !e.name.is_empty())
+            .filter_map(ElementDescription::from_ax)
+            .collect();
+        Self::build(provider, elements).await
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Up to `limit` elements most similar to `query`, best first
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<ElementMatch>> {
+        if self.entries.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query_vector = self
+            .provider
+            .embed(&[query.to_string()])
+            .await?
+            .pop()
+            .unwrap_or_default();
+
+        let mut matches: Vec<ElementMatch> = self
+            .entries
+            .iter()
+            .map(|(element, vector)| ElementMatch {
+                selector: element.selector.clone(),
+                role: element.role.clone(),
+                name: element.name.clone(),
+                similarity: cosine_similarity(&query_vector, vector),
+            })
+            .filter(|m| m.similarity >= MIN_SIMILARITY)
+            .collect();
+        matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::embeddings::HashingEmbeddingProvider;
+
+    fn element(selector: &str, role: &str, name: &str) -> ElementDescription {
+        ElementDescription {
+            selector: selector.to_string(),
+            role: role.to_string(),
+            name: name.to_string(),
+            description: None,
+            value: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn ranks_elements_by_similarity() {
+        let index = ElementEmbeddingIndex::build(
+            Arc::new(HashingEmbeddingProvider::new(256)),
+            vec![
+                element("#newsletter", "button", "Subscribe to our newsletter"),
+                element("#login", "button", "Sign in to your account"),
+                element("#search", "searchbox", "Search products"),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(index.len(), 3);
+
+        let matches = index.search("the signin button", 2).await.unwrap();
+        assert_eq!(matches[0].selector, "#login");
+        assert!(matches.len() <= 2);
+
+        let matches = index.search("search box for products", 1).await.unwrap();
+        assert_eq!(matches[0].selector, "#search");
+    }
+
+    #[test]
+    fn describes_elements_for_embedding() {
+        let mut e = element("#q", "searchbox", "Search");
+        e.description = Some("Find articles".to_string());
+        e.value = Some(String::new());
+        assert_eq!(e.text(), "searchbox: Search. Find articles");
+    }
+}
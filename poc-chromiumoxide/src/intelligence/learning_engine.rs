@@ -532,6 +532,33 @@ impl LearningEngine {
         Ok(imported_count)
     }
 
+    /// Samples and action patterns learned so far, for persistence
+    pub fn learned_state(&self) -> (Vec<LearningData>, HashMap<String, ActionPattern>) {
+        (
+            self.learning_data.iter().cloned().collect(),
+            self.action_patterns.clone(),
+        )
+    }
+
+    /// Replace learned state with a persisted copy, keeping the newest samples
+    pub async fn restore_state(
+        &mut self,
+        samples: Vec<LearningData>,
+        patterns: HashMap<String, ActionPattern>,
+    ) {
+        let skip = samples
+            .len()
+            .saturating_sub(self.learning_config.max_learning_samples);
+        self.learning_data = samples.into_iter().skip(skip).collect();
+        self.action_patterns = patterns;
+        self.update_performance_metrics().await;
+        info!(
+            "Restored {} learning samples and {} action patterns",
+            self.learning_data.len(),
+            self.action_patterns.len()
+        );
+    }
+
     /// Clean up old learning data
     pub async fn cleanup_old_data(&mut self) -> Result<usize> {
         let cutoff_date = chrono::Utc::now()
@@ -2,19 +2,34 @@
 // Provides advanced AI-driven automation with learning and adaptation capabilities
 
 pub mod adaptation_manager;
+pub mod anomaly_detection;
 pub mod decision_maker;
+pub mod element_index;
 pub mod learning_engine;
 pub mod organic_perception;
 pub mod pattern_recognition;
+pub mod pattern_sharing;
+pub mod persistence;
 
 // Re-exports for public API
-pub use adaptation_manager::{AdaptationManager, AdaptationStrategy, EnvironmentContext};
-pub use decision_maker::{Confidence, Decision, DecisionContext, DecisionMaker};
+pub use adaptation_manager::{
+    AdaptationManager, AdaptationStrategy, AppliedProfile, DomainProfile, EnvironmentContext,
+};
+pub use anomaly_detection::{
+    ActionObservation, AnomalyDetector, AnomalyEvent, AnomalyKind, AnomalyResponse,
+    AnomalyThresholds,
+};
+pub use decision_maker::{
+    Confidence, Decision, DecisionContext, DecisionMaker, DecisionTrace, TracedCandidate,
+};
+pub use element_index::{ElementDescription, ElementEmbeddingIndex, ElementMatch};
 pub use learning_engine::{ActionPattern, LearningData, LearningEngine, PerformanceMetrics};
 pub use organic_perception::{
     ElementInsight, OrganicPerceptionEngine, PageContext, PerceptionResult, ViewportInfo,
 };
 pub use pattern_recognition::{ActionSequence, PatternMatch, PatternRecognizer, SuccessPattern};
+pub use pattern_sharing::{PatternBundle, PatternImport};
+pub use persistence::{LearningSnapshot, LearningStore, SharedLearning};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -31,6 +46,7 @@ pub struct IntelligenceService {
     adaptation_manager: Arc<RwLock<AdaptationManager>>,
     pattern_recognizer: Arc<RwLock<PatternRecognizer>>,
     decision_maker: Arc<RwLock<DecisionMaker>>,
+    learning: Arc<SharedLearning>,
     config: IntelligenceConfig,
 }
 
@@ -69,6 +85,9 @@ pub struct IntelligenceAnalysis {
     pub decision: Decision,
     pub confidence: f64,
     pub reasoning: String,
+    /// Recent JavaScript errors reported by the page
+    #[serde(default)]
+    pub page_errors: Vec<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
@@ -82,6 +101,9 @@ pub struct ActionRecommendation {
     pub expected_outcome: String,
     pub alternative_actions: Vec<AlternativeAction>,
     pub risk_assessment: RiskAssessment,
+    /// Candidates, scores and patterns behind the recommendation
+    #[serde(default)]
+    pub trace: DecisionTrace,
 }
 
 /// Alternative action if primary fails
@@ -103,8 +125,13 @@ pub struct RiskAssessment {
 }
 
 impl IntelligenceService {
-    /// Create new intelligence service
+    /// Create new intelligence service that learns only for its own lifetime
     pub fn new(config: IntelligenceConfig) -> Self {
+        Self::with_learning(config, Arc::new(SharedLearning::in_memory()))
+    }
+
+    /// Create an intelligence service that learns into shared, persisted state
+    pub fn with_learning(config: IntelligenceConfig, learning: Arc<SharedLearning>) -> Self {
         info!(
             "Initializing Intelligence Service with mode: {}",
             config.organic_perception_mode
@@ -112,10 +139,11 @@ impl IntelligenceService {
 
         Self {
             organic_perception: Arc::new(RwLock::new(OrganicPerceptionEngine::new())),
-            learning_engine: Arc::new(RwLock::new(LearningEngine::new())),
-            adaptation_manager: Arc::new(RwLock::new(AdaptationManager::new())),
-            pattern_recognizer: Arc::new(RwLock::new(PatternRecognizer::new())),
+            learning_engine: learning.learning_engine.clone(),
+            adaptation_manager: learning.adaptation_manager.clone(),
+            pattern_recognizer: learning.pattern_recognizer.clone(),
             decision_maker: Arc::new(RwLock::new(DecisionMaker::new())),
+            learning,
             config,
         }
     }
@@ -178,12 +206,24 @@ impl IntelligenceService {
                 .await?
         };
 
-        let confidence = decision.confidence.value;
+        // 5. Pages that throw JavaScript errors are less likely to respond as expected
+        let page_errors: Vec<String> = browser
+            .console()
+            .errors()
+            .await
+            .into_iter()
+            .rev()
+            .take(MAX_REPORTED_PAGE_ERRORS)
+            .map(|e| e.text)
+            .collect();
+
+        let confidence = (decision.confidence.value - js_error_penalty(page_errors.len())).max(0.0);
         let reasoning = format!(
-            "Analysis based on {} elements perceived, {} patterns matched, {} adaptations suggested",
+            "Analysis based on {} elements perceived, {} patterns matched, {} adaptations suggested, {} page errors",
             perception_result.elements.len(),
             learned_patterns.len(),
-            adaptation_suggestions.len()
+            adaptation_suggestions.len(),
+            page_errors.len()
         );
 
         debug!(
@@ -198,6 +238,7 @@ impl IntelligenceService {
             decision,
             confidence,
             reasoning,
+            page_errors,
             timestamp: chrono::Utc::now(),
         })
     }
@@ -220,6 +261,8 @@ impl IntelligenceService {
         // Generate alternatives based on patterns and adaptations
         let mut alternative_actions = Vec::new();
 
+        let mut trace = decision.trace.clone();
+
         // Add alternatives from learned patterns
         for pattern in &analysis.learned_patterns {
             if pattern.confidence > 0.6 && !pattern.action_sequence.is_empty() {
@@ -230,6 +273,18 @@ impl IntelligenceService {
                     confidence: pattern.confidence,
                     reason: format!("Based on successful pattern: {}", pattern.name),
                 });
+            } else {
+                let reason = if pattern.action_sequence.is_empty() {
+                    "Pattern has no recorded actions".to_string()
+                } else {
+                    format!("Confidence {:.2} is not above 0.6", pattern.confidence)
+                };
+                trace.rejected_alternatives.push(TracedCandidate {
+                    name: pattern.name.clone(),
+                    score: pattern.confidence,
+                    selected: false,
+                    reason,
+                });
             }
         }
 
@@ -247,7 +302,12 @@ impl IntelligenceService {
 
         // Risk assessment
         let risk_assessment = self
-            .assess_action_risk(&action_type, confidence, &analysis.perception_result)
+            .assess_action_risk(
+                &action_type,
+                confidence,
+                &analysis.perception_result,
+                &analysis.page_errors,
+            )
             .await;
 
         Ok(ActionRecommendation {
@@ -258,6 +318,7 @@ impl IntelligenceService {
             expected_outcome: decision.expected_outcome.clone(),
             alternative_actions,
             risk_assessment,
+            trace,
         })
     }
 
@@ -303,6 +364,7 @@ impl IntelligenceService {
                 .await;
         }
 
+        self.learning.mark_dirty();
         Ok(())
     }
 
@@ -312,6 +374,7 @@ impl IntelligenceService {
         action_type: &str,
         confidence: f64,
         perception_result: &PerceptionResult,
+        page_errors: &[String],
     ) -> RiskAssessment {
         let mut potential_issues = Vec::new();
         let mut mitigation_strategies = Vec::new();
@@ -352,8 +415,21 @@ impl IntelligenceService {
             mitigation_strategies.push("Use multiple fallback selectors".to_string());
         }
 
-        let success_probability =
-            (confidence * 0.7) + (0.3 * (1.0 - perception_result.page_complexity));
+        // Broken page scripts often leave handlers unattached
+        if !page_errors.is_empty() {
+            potential_issues.push(format!(
+                "Page reported {} JavaScript error(s), latest: {}",
+                page_errors.len(),
+                page_errors[0]
+            ));
+            mitigation_strategies
+                .push("Verify the action's effect; page scripts may be failing".to_string());
+        }
+
+        let success_probability = ((confidence * 0.7)
+            + (0.3 * (1.0 - perception_result.page_complexity))
+            - js_error_penalty(page_errors.len()))
+        .max(0.0);
 
         RiskAssessment {
             risk_level: risk_level.to_string(),
@@ -400,6 +476,13 @@ impl Default for IntelligenceService {
     }
 }
 
+const MAX_REPORTED_PAGE_ERRORS: usize = 10;
+
+/// Confidence lost for JavaScript errors on the page, capped at 0.3
+fn js_error_penalty(error_count: usize) -> f64 {
+    (error_count as f64 * 0.05).min(0.3)
+}
+
 /// Statistics about intelligence service performance
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntelligenceStatistics {
@@ -446,10 +529,27 @@ mod tests {
         };
 
         let risk = service
-            .assess_action_risk("click", 0.8, &perception_result)
+            .assess_action_risk("click", 0.8, &perception_result, &[])
             .await;
 
         assert_eq!(risk.risk_level, "medium");
         assert!(risk.success_probability > 0.0);
+
+        let errors = vec!["TypeError: x is undefined".to_string(); 3];
+        let failing = service
+            .assess_action_risk("click", 0.8, &perception_result, &errors)
+            .await;
+        assert!(failing.success_probability < risk.success_probability);
+        assert!(failing
+            .potential_issues
+            .iter()
+            .any(|i| i.contains("JavaScript error")));
+    }
+
+    #[test]
+    fn js_error_penalty_is_capped() {
+        assert_eq!(js_error_penalty(0), 0.0);
+        assert!((js_error_penalty(2) - 0.1).abs() < f64::EPSILON);
+        assert_eq!(js_error_penalty(100), 0.3);
     }
 }
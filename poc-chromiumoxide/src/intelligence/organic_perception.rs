@@ -123,6 +123,17 @@ pub struct ElementInsight {
     pub alternative_selectors: Vec<String>,
     pub predicted_behavior: Option<String>,
     pub risk_factors: Vec<String>,
+    /// How reliably `selector` found the same element across page loads
+    #[serde(default)]
+    pub stability: Option<f64>,
+}
+
+impl ElementInsight {
+    /// Confidence weighted by selector stability; selectors not yet seen across
+    /// loads count as middling
+    pub fn ranking_score(&self) -> f64 {
+        self.confidence * (0.5 + 0.5 * self.stability.unwrap_or(0.5))
+    }
 }
 
 /// Page context for analysis
@@ -379,9 +390,14 @@ impl OrganicPerceptionEngine {
             elements.extend(learned_elements);
         }
 
-        // 4. Consolidate and rank elements
+        // 4. Consolidate and rank elements, preferring selectors that survive reloads
         elements = self.consolidate_elements(elements);
-        elements.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+        self.apply_selector_stability(&mut elements, browser).await;
+        elements.sort_by(|a, b| {
+            b.ranking_score()
+                .partial_cmp(&a.ranking_score())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
 
         // 5. Calculate page complexity and other metrics
         let page_complexity = self.calculate_page_complexity(&page_characteristics, elements.len());
@@ -635,6 +651,7 @@ impl OrganicPerceptionEngine {
             alternative_selectors,
             predicted_behavior: self.predict_element_behavior(element, element_type),
             risk_factors: self.identify_risk_factors(element),
+            stability: None,
         })
     }
 
@@ -725,6 +742,59 @@ impl OrganicPerceptionEngine {
         risks
     }
 
+    /// Attach selector stability to each element and switch to an alternative
+    /// selector when it has proven more stable than the primary one
+    async fn apply_selector_stability(
+        &self,
+        elements: &mut [ElementInsight],
+        browser: &crate::browser::Browser,
+    ) {
+        let selectors: Vec<String> = elements
+            .iter()
+            .flat_map(|e| std::iter::once(&e.selector).chain(e.alternative_selectors.iter()))
+            .cloned()
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if selectors.is_empty() {
+            return;
+        }
+        let scores: HashMap<String, f64> = match browser.observe_selectors(&selectors).await {
+            Ok(scores) => selectors
+                .into_iter()
+                .zip(scores)
+                .filter_map(|(selector, score)| score.map(|score| (selector, score as f64)))
+                .collect(),
+            Err(e) => {
+                debug!("Selector stability unavailable: {}", e);
+                return;
+            }
+        };
+
+        for element in elements.iter_mut() {
+            element.stability = scores.get(&element.selector).copied();
+            let current = element.stability.unwrap_or(0.5);
+            let best_alternative = element
+                .alternative_selectors
+                .iter()
+                .filter_map(|alt| scores.get(alt).map(|score| (alt.clone(), *score)))
+                .filter(|(_, score)| *score > current)
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+            if let Some((alternative, score)) = best_alternative {
+                debug!(
+                    "Preferring stable selector {} ({:.2}) over {} ({:.2})",
+                    alternative, score, element.selector, current
+                );
+                element
+                    .alternative_selectors
+                    .retain(|alt| alt != &alternative);
+                let replaced = std::mem::replace(&mut element.selector, alternative);
+                element.alternative_selectors.push(replaced);
+                element.stability = Some(score);
+            }
+        }
+    }
+
     /// Consolidate duplicate or similar elements
     fn consolidate_elements(&self, mut elements: Vec<ElementInsight>) -> Vec<ElementInsight> {
         // Remove duplicates and merge similar elements
@@ -879,6 +949,29 @@ mod tests {
         assert!(ecommerce_model.is_some());
     }
 
+    #[test]
+    fn test_stable_selectors_rank_higher() {
+        let insight = |selector: &str, stability: Option<f64>| ElementInsight {
+            selector: selector.to_string(),
+            element_type: "button".to_string(),
+            confidence: 0.8,
+            context_score: 0.5,
+            interaction_likelihood: 0.9,
+            visual_prominence: 0.6,
+            semantic_meaning: None,
+            alternative_selectors: Vec::new(),
+            predicted_behavior: None,
+            risk_factors: Vec::new(),
+            stability,
+        };
+
+        let stable = insight("#checkout", Some(0.9));
+        let unseen = insight("button.checkout", None);
+        let flaky = insight("button:nth-of-type(3)", Some(0.2));
+        assert!(stable.ranking_score() > unseen.ranking_score());
+        assert!(unseen.ranking_score() > flaky.ranking_score());
+    }
+
     #[test]
     fn test_page_complexity_calculation() {
         let engine = OrganicPerceptionEngine::new();
@@ -5,6 +5,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+const INITIAL_CONFIDENCE: f64 = 0.5;
+const REINFORCEMENT_RATE: f64 = 0.1;
+
 /// Recognizes and matches successful automation patterns
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -57,21 +60,66 @@ impl PatternRecognizer {
 
     pub async fn find_relevant_patterns(
         &self,
-        _intent: &str,
+        intent: &str,
         _perception_result: &super::organic_perception::PerceptionResult,
     ) -> Vec<SuccessPattern> {
-        vec![]
+        let intent = intent.to_lowercase();
+        let mut relevant: Vec<SuccessPattern> = self
+            .patterns
+            .values()
+            .filter(|p| {
+                intent.contains(&p.name.to_lowercase())
+                    || p.contexts
+                        .iter()
+                        .any(|c| intent.contains(&c.to_lowercase()))
+            })
+            .cloned()
+            .collect();
+        relevant.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        relevant
+    }
+
+    /// Count a success for `action_type`, creating its pattern on first use
+    pub async fn reinforce_successful_pattern(&mut self, action_type: &str) {
+        let pattern = self
+            .patterns
+            .entry(action_type.to_string())
+            .or_insert_with(|| SuccessPattern {
+                name: action_type.to_string(),
+                action_sequence: vec![ActionSequence {
+                    action_type: action_type.to_string(),
+                    parameters: HashMap::new(),
+                    timing: None,
+                }],
+                confidence: INITIAL_CONFIDENCE,
+                success_count: 0,
+                contexts: Vec::new(),
+            });
+        pattern.success_count += 1;
+        // Each success closes a tenth of the remaining gap to full confidence
+        pattern.confidence += (1.0 - pattern.confidence) * REINFORCEMENT_RATE;
+    }
+
+    /// All learned patterns, for persistence
+    pub fn patterns(&self) -> Vec<SuccessPattern> {
+        self.patterns.values().cloned().collect()
     }
 
-    pub async fn reinforce_successful_pattern(&mut self, _action_type: &str) {
-        // Implementation for reinforcing patterns
+    /// Replace learned patterns with a persisted copy
+    pub fn restore_patterns(&mut self, patterns: Vec<SuccessPattern>) {
+        self.patterns = patterns.into_iter().map(|p| (p.name.clone(), p)).collect();
     }
 
     pub async fn get_statistics(&self) -> PatternStatistics {
         PatternStatistics {
             total_patterns: self.patterns.len(),
-            successful_matches: 0,
-            average_confidence: 0.0,
+            successful_matches: self.patterns.values().map(|p| p.success_count).sum(),
+            average_confidence: if self.patterns.is_empty() {
+                0.0
+            } else {
+                self.patterns.values().map(|p| p.confidence).sum::<f64>()
+                    / self.patterns.len() as f64
+            },
         }
     }
 }
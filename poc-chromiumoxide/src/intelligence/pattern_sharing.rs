@@ -0,0 +1,239 @@
+// Pattern Sharing
+// Portable bundles of learned success patterns, exchanged between deployments
+//
+// A bundle is plain JSON, so one team's knowledge of a site ("how to paginate
+// on example.com") can be exported from one instance and imported into
+// another. Imports merge into what the receiving instance already knows and
+// are idempotent: importing the same bundle twice changes nothing.
+
+use super::pattern_recognition::{PatternRecognizer, SuccessPattern};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// Current bundle format
+pub const PATTERN_BUNDLE_VERSION: u32 = 1;
+
+/// Learned patterns packaged for another instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternBundle {
+    pub bundle_version: u32,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    /// Domain the bundle was limited to, applied to imported patterns without contexts
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Free-form note on where the bundle came from
+    #[serde(default)]
+    pub description: Option<String>,
+    pub patterns: Vec<SuccessPattern>,
+}
+
+impl PatternBundle {
+    pub fn new(patterns: Vec<SuccessPattern>, domain: Option<String>) -> Self {
+        Self {
+            bundle_version: PATTERN_BUNDLE_VERSION,
+            exported_at: chrono::Utc::now(),
+            domain,
+            description: None,
+            patterns,
+        }
+    }
+
+    /// Reject bundles this instance can't read or whose patterns are malformed
+    pub fn validate(&self) -> Result<()> {
+        if self.bundle_version > PATTERN_BUNDLE_VERSION {
+            return Err(anyhow!(
+                "Pattern bundle version {} is newer than supported version {}",
+                self.bundle_version,
+                PATTERN_BUNDLE_VERSION
+            ));
+        }
+        for pattern in &self.patterns {
+            if pattern.name.trim().is_empty() {
+                return Err(anyhow!("Pattern bundle contains a pattern without a name"));
+            }
+            if !(0.0..=1.0).contains(&pattern.confidence) {
+                return Err(anyhow!(
+                    "Pattern {} has confidence {} outside 0..=1",
+                    pattern.name,
+                    pattern.confidence
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of importing a bundle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatternImport {
+    /// Patterns this instance did not know before
+    pub added: usize,
+    /// Known patterns that gained confidence, successes or contexts
+    pub merged: usize,
+    /// Known patterns the bundle had nothing new for
+    pub unchanged: usize,
+}
+
+impl PatternRecognizer {
+    /// Bundle learned patterns, limited to those seen on `domain` when given
+    pub fn export_bundle(&self, domain: Option<&str>) -> PatternBundle {
+        let mut patterns: Vec<SuccessPattern> = self
+            .patterns()
+            .into_iter()
+            .filter(|p| match domain {
+                Some(d) => applies_to_domain(p, d),
+                None => true,
+            })
+            .collect();
+        patterns.sort_by(|a, b| a.name.cmp(&b.name));
+        PatternBundle::new(patterns, domain.map(str::to_string))
+    }
+
+    /// Merge a bundle into the learned patterns
+    pub fn import_bundle(&mut self, bundle: PatternBundle) -> Result<PatternImport> {
+        bundle.validate()?;
+
+        let mut known: std::collections::HashMap<String, SuccessPattern> = self
+            .patterns()
+            .into_iter()
+            .map(|p| (p.name.clone(), p))
+            .collect();
+        let mut summary = PatternImport::default();
+        for mut incoming in bundle.patterns {
+            if incoming.contexts.is_empty() {
+                incoming.contexts.extend(bundle.domain.clone());
+            }
+            match known.get_mut(&incoming.name) {
+                None => {
+                    summary.added += 1;
+                    known.insert(incoming.name.clone(), incoming);
+                }
+                Some(existing) => {
+                    if merge_pattern(existing, incoming) {
+                        summary.merged += 1;
+                    } else {
+                        summary.unchanged += 1;
+                    }
+                }
+            }
+        }
+
+        self.restore_patterns(known.into_values().collect());
+        info!(
+            "Imported pattern bundle: {} added, {} merged, {} unchanged",
+            summary.added, summary.merged, summary.unchanged
+        );
+        Ok(summary)
+    }
+}
+
+/// Whether `pattern` was learned on `domain` or one of its subdomains
+fn applies_to_domain(pattern: &SuccessPattern, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("www.").to_lowercase();
+    pattern.contexts.iter().any(|context| {
+        let context = context.trim().trim_start_matches("www.").to_lowercase();
+        context == domain || context.ends_with(&format!(".{}", domain))
+    })
+}
+
+/// Fold `incoming` into `existing`, keeping the stronger evidence of each.
+/// Counts are not summed so that re-importing a bundle is a no-op.
+fn merge_pattern(existing: &mut SuccessPattern, incoming: SuccessPattern) -> bool {
+    let mut changed = false;
+    if incoming.success_count > existing.success_count {
+        existing.success_count = incoming.success_count;
+        changed = true;
+    }
+    if incoming.confidence > existing.confidence {
+        existing.confidence = incoming.confidence;
+        changed = true;
+    }
+    if existing.action_sequence.is_empty() && !incoming.action_sequence.is_empty() {
+        existing.action_sequence = incoming.action_sequence;
+        changed = true;
+    }
+    for context in incoming.contexts {
+        if !existing.contexts.contains(&context) {
+            existing.contexts.push(context);
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(
+        name: &str,
+        confidence: f64,
+        success_count: u32,
+        contexts: &[&str],
+    ) -> SuccessPattern {
+        SuccessPattern {
+            name: name.to_string(),
+            action_sequence: Vec::new(),
+            confidence,
+            success_count,
+            contexts: contexts.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn exports_only_patterns_for_the_domain() {
+        let mut recognizer = PatternRecognizer::new();
+        recognizer.restore_patterns(vec![
+            pattern("paginate", 0.9, 12, &["shop.example.com"]),
+            pattern("login", 0.8, 4, &["other.org"]),
+        ]);
+
+        let bundle = recognizer.export_bundle(Some("www.example.com"));
+        assert_eq!(bundle.patterns.len(), 1);
+        assert_eq!(bundle.patterns[0].name, "paginate");
+        assert_eq!(recognizer.export_bundle(None).patterns.len(), 2);
+    }
+
+    #[test]
+    fn imports_merge_and_are_idempotent() {
+        let mut source = PatternRecognizer::new();
+        source.restore_patterns(vec![
+            pattern("paginate", 0.9, 12, &[]),
+            pattern("click", 0.6, 3, &[]),
+        ]);
+        let mut bundle = source.export_bundle(None);
+        bundle.domain = Some("example.com".to_string());
+        let json = serde_json::to_string(&bundle).unwrap();
+
+        let mut target = PatternRecognizer::new();
+        target.restore_patterns(vec![pattern("click", 0.7, 5, &[])]);
+
+        let first = target
+            .import_bundle(serde_json::from_str(&json).unwrap())
+            .unwrap();
+        assert_eq!((first.added, first.merged), (1, 1));
+        let click = target
+            .patterns()
+            .into_iter()
+            .find(|p| p.name == "click")
+            .unwrap();
+        assert_eq!((click.confidence, click.success_count), (0.7, 5));
+        assert_eq!(click.contexts, vec!["example.com".to_string()]);
+
+        let second = target
+            .import_bundle(serde_json::from_str(&json).unwrap())
+            .unwrap();
+        assert_eq!((second.added, second.merged, second.unchanged), (0, 0, 2));
+    }
+
+    #[test]
+    fn rejects_unreadable_bundles() {
+        let mut bundle = PatternBundle::new(vec![pattern("click", 0.5, 1, &[])], None);
+        bundle.bundle_version = PATTERN_BUNDLE_VERSION + 1;
+        assert!(bundle.validate().is_err());
+
+        let bundle = PatternBundle::new(vec![pattern("click", 1.5, 1, &[])], None);
+        assert!(PatternRecognizer::new().import_bundle(bundle).is_err());
+    }
+}
@@ -0,0 +1,321 @@
+// Learning Persistence
+// Keeps learning samples, success patterns and domain profiles on disk so they
+// accumulate across restarts
+//
+// Everything learned is written as one JSON snapshot. The snapshot is loaded at
+// startup, flushed periodically while something new has been learned, and
+// flushed once more on shutdown. Older snapshot formats are migrated on load.
+
+use super::adaptation_manager::{AdaptationManager, DomainProfile};
+use super::learning_engine::{ActionPattern, LearningData, LearningEngine};
+use super::pattern_recognition::{PatternRecognizer, SuccessPattern};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Current snapshot format; bump it and add a step to `migrate` when it changes
+pub const LEARNING_FORMAT_VERSION: u32 = 1;
+
+/// Everything the intelligence service has learned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningSnapshot {
+    pub format_version: u32,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub samples: Vec<LearningData>,
+    #[serde(default)]
+    pub action_patterns: HashMap<String, ActionPattern>,
+    #[serde(default)]
+    pub success_patterns: Vec<SuccessPattern>,
+    #[serde(default)]
+    pub domain_profiles: Vec<DomainProfile>,
+}
+
+impl LearningSnapshot {
+    pub fn empty() -> Self {
+        Self {
+            format_version: LEARNING_FORMAT_VERSION,
+            saved_at: chrono::Utc::now(),
+            samples: Vec::new(),
+            action_patterns: HashMap::new(),
+            success_patterns: Vec::new(),
+            domain_profiles: Vec::new(),
+        }
+    }
+
+    /// Parse a snapshot written by any supported format version
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_slice(data)?;
+        Ok(serde_json::from_value(migrate(value)?)?)
+    }
+}
+
+/// Bring a stored snapshot up to `LEARNING_FORMAT_VERSION`
+fn migrate(value: serde_json::Value) -> Result<serde_json::Value> {
+    // Format 0 is the bare sample array written by `export_learning_data`
+    if value.is_array() {
+        return Ok(serde_json::json!({
+            "format_version": LEARNING_FORMAT_VERSION,
+            "saved_at": chrono::Utc::now(),
+            "samples": value,
+        }));
+    }
+
+    let version = value
+        .get("format_version")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| anyhow!("Learning snapshot has no format_version"))?;
+    if version > LEARNING_FORMAT_VERSION as u64 {
+        return Err(anyhow!(
+            "Learning snapshot format {} is newer than supported format {}",
+            version,
+            LEARNING_FORMAT_VERSION
+        ));
+    }
+    Ok(value)
+}
+
+/// JSON file holding the learning snapshot
+#[derive(Debug, Clone)]
+pub struct LearningStore {
+    path: PathBuf,
+}
+
+impl LearningStore {
+    /// Create a store under `RAINBOW_INTELLIGENCE_DIR`, falling back to
+    /// `~/.rainbow/intelligence`
+    pub fn new() -> Self {
+        let dir = std::env::var("RAINBOW_INTELLIGENCE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                std::env::var("HOME")
+                    .map(|home| PathBuf::from(home).join(".rainbow").join("intelligence"))
+                    .unwrap_or_else(|_| std::env::temp_dir().join("rainbow-intelligence"))
+            });
+        Self::with_path(dir.join("learning.json"))
+    }
+
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the stored snapshot; a missing file loads an empty one
+    pub fn load(&self) -> Result<LearningSnapshot> {
+        if !self.path.exists() {
+            return Ok(LearningSnapshot::empty());
+        }
+        let data = std::fs::read(&self.path)?;
+        LearningSnapshot::parse(&data)
+            .with_context(|| format!("Failed to read learning data {}", self.path.display()))
+    }
+
+    /// Write `snapshot`, replacing the stored one only once it is fully written
+    pub fn save(&self, snapshot: &LearningSnapshot) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = self.path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_vec_pretty(snapshot)?)?;
+        std::fs::rename(&partial, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Default for LearningStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Learning state shared by every intelligence service in the process
+#[derive(Debug)]
+pub struct SharedLearning {
+    pub learning_engine: Arc<RwLock<LearningEngine>>,
+    pub pattern_recognizer: Arc<RwLock<PatternRecognizer>>,
+    pub adaptation_manager: Arc<RwLock<AdaptationManager>>,
+    store: Option<LearningStore>,
+    dirty: AtomicBool,
+}
+
+impl SharedLearning {
+    /// Learning state that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            learning_engine: Arc::new(RwLock::new(LearningEngine::new())),
+            pattern_recognizer: Arc::new(RwLock::new(PatternRecognizer::new())),
+            adaptation_manager: Arc::new(RwLock::new(AdaptationManager::new())),
+            store: None,
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Learning state restored from `store` and flushed back to it
+    pub async fn load(store: LearningStore) -> Result<Self> {
+        let snapshot = store.load()?;
+        let learning = Self {
+            store: Some(store),
+            ..Self::in_memory()
+        };
+
+        let pattern_count = snapshot.success_patterns.len();
+        learning
+            .learning_engine
+            .write()
+            .await
+            .restore_state(snapshot.samples, snapshot.action_patterns)
+            .await;
+        learning
+            .pattern_recognizer
+            .write()
+            .await
+            .restore_patterns(snapshot.success_patterns);
+        learning
+            .adaptation_manager
+            .write()
+            .await
+            .restore_profiles(snapshot.domain_profiles);
+        info!("Loaded {} success patterns", pattern_count);
+        Ok(learning)
+    }
+
+    /// Note that something new was learned and should be flushed
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Current learned state as a snapshot
+    pub async fn snapshot(&self) -> LearningSnapshot {
+        let (samples, action_patterns) = self.learning_engine.read().await.learned_state();
+        LearningSnapshot {
+            samples,
+            action_patterns,
+            success_patterns: self.pattern_recognizer.read().await.patterns(),
+            domain_profiles: self.adaptation_manager.read().await.profiles(),
+            ..LearningSnapshot::empty()
+        }
+    }
+
+    /// Write learned state if it changed since the last flush; returns whether it wrote
+    pub async fn flush(&self) -> Result<bool> {
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(false);
+        }
+
+        let snapshot = self.snapshot().await;
+        if let Err(e) = store.save(&snapshot) {
+            // Keep the changes pending so the next flush retries them
+            self.mark_dirty();
+            return Err(e);
+        }
+        debug!(
+            "Flushed {} learning samples to {}",
+            snapshot.samples.len(),
+            store.path().display()
+        );
+        Ok(true)
+    }
+
+    /// Flush learned state every `interval`
+    pub fn start_flush(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.flush().await {
+                    warn!("Failed to persist learning data: {}", e);
+                }
+            }
+        })
+    }
+}
+
+impl Default for SharedLearning {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(action_type: &str, success: bool) -> LearningData {
+        LearningData {
+            action_type: action_type.to_string(),
+            parameters: HashMap::new(),
+            expected_outcome: "done".to_string(),
+            actual_outcome: "done".to_string(),
+            success,
+            execution_time_ms: 120,
+            confidence: 0.8,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn migrates_legacy_sample_arrays() {
+        let legacy = serde_json::to_vec(&vec![sample("click", true)]).unwrap();
+        let snapshot = LearningSnapshot::parse(&legacy).unwrap();
+        assert_eq!(snapshot.format_version, LEARNING_FORMAT_VERSION);
+        assert_eq!(snapshot.samples.len(), 1);
+        assert!(snapshot.success_patterns.is_empty());
+
+        let future = serde_json::json!({ "format_version": LEARNING_FORMAT_VERSION + 1 });
+        assert!(LearningSnapshot::parse(future.to_string().as_bytes()).is_err());
+    }
+
+    #[tokio::test]
+    async fn learned_patterns_survive_a_restart() {
+        let path = std::env::temp_dir()
+            .join(format!("rainbow-learning-{}", uuid::Uuid::new_v4()))
+            .join("learning.json");
+
+        let first = SharedLearning::load(LearningStore::with_path(path.clone()))
+            .await
+            .unwrap();
+        first
+            .learning_engine
+            .write()
+            .await
+            .record_learning_data(sample("click", true))
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            first
+                .pattern_recognizer
+                .write()
+                .await
+                .reinforce_successful_pattern("click")
+                .await;
+        }
+        assert!(!first.flush().await.unwrap(), "nothing marked dirty yet");
+        first.mark_dirty();
+        assert!(first.flush().await.unwrap());
+
+        let second = SharedLearning::load(LearningStore::with_path(path.clone()))
+            .await
+            .unwrap();
+        let patterns = second.pattern_recognizer.read().await.patterns();
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].success_count, 2);
+        assert!(patterns[0].confidence > 0.5);
+        let stats = second.learning_engine.read().await.get_statistics().await;
+        assert_eq!(stats.total_samples, 1);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -2,6 +2,7 @@
 // Provides unified interface for different LLM providers
 
 use anyhow::Result;
+use base64::Engine;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -14,6 +15,105 @@ pub struct LLMResponse {
     pub usage: TokenUsage,
     pub finish_reason: String,
     pub timestamp: DateTime<Utc>,
+    /// Tools the model asked to call, for providers that support tool use
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// A request with an optional system prompt and tools
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LLMRequest {
+    #[serde(default)]
+    pub system: Option<String>,
+    pub prompt: String,
+    /// Earlier turns of the conversation, oldest first
+    #[serde(default)]
+    pub history: Vec<ChatTurn>,
+    #[serde(default)]
+    pub tools: Vec<ToolDefinition>,
+    /// Whether answering needs a model that can see images
+    #[serde(default)]
+    pub requires_vision: bool,
+    /// Images shown to the model along with the prompt
+    #[serde(default)]
+    pub images: Vec<ImageInput>,
+}
+
+impl LLMRequest {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Attach an image, which routes the request to a vision model
+    pub fn with_image(mut self, image: ImageInput) -> Self {
+        self.images.push(image);
+        self.requires_vision = true;
+        self
+    }
+}
+
+/// An image sent to a vision model, base64 encoded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageInput {
+    /// MIME type such as "image/png"
+    pub media_type: String,
+    pub data: String,
+}
+
+impl ImageInput {
+    pub fn png(bytes: &[u8]) -> Self {
+        Self {
+            media_type: "image/png".to_string(),
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        }
+    }
+
+    /// The image as a `data:` URL, the form OpenAI-compatible APIs accept
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
+
+/// A previous message of a conversation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatTurn {
+    pub role: ChatRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    User,
+    Assistant,
+}
+
+impl ChatRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Assistant => "assistant",
+        }
+    }
+}
+
+/// Tool the model may call, described by a JSON schema of its input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub input_schema: serde_json::Value,
+}
+
+/// Tool call requested by the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
 }
 
 /// Token usage information
@@ -44,6 +144,9 @@ pub enum LLMError {
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
+
+    #[error("Token budget exceeded: {0}")]
+    BudgetExceeded(String),
 }
 
 /// Main LLM client interface
@@ -66,6 +169,11 @@ impl LLMClient {
         self.provider.query(prompt, &self.config).await
     }
 
+    /// Send a request with a system prompt and tools
+    pub async fn complete(&mut self, request: &LLMRequest) -> Result<LLMResponse, LLMError> {
+        self.provider.complete(request, &self.config).await
+    }
+
     /// Send a query with context
     pub async fn query_with_context(
         &mut self,
@@ -104,7 +212,9 @@ impl LLMClient {
     pub fn is_configured(&self) -> bool {
         match self.config.default_provider.as_str() {
             "openai" => self.config.openai_api_key.is_some(),
-            "claude" => self.config.claude_api_key.is_some(),
+            "azure" => self.config.azure.is_some(),
+            "claude" | "anthropic" => self.config.claude_api_key.is_some(),
+            "gemini" => self.config.gemini_api_key.is_some(),
             _ => false,
         }
     }
@@ -142,6 +252,31 @@ impl LLMClientBuilder {
         self
     }
 
+    pub fn azure(mut self, azure: super::AzureOpenAIConfig) -> Self {
+        self.config.azure = Some(azure);
+        self
+    }
+
+    pub fn gemini_key(mut self, key: String) -> Self {
+        self.config.gemini_api_key = Some(key);
+        self
+    }
+
+    pub fn model(mut self, model: String) -> Self {
+        self.config.model = Some(model);
+        self
+    }
+
+    pub fn model_selection(mut self, strategy: super::ModelSelectionStrategy) -> Self {
+        self.config.model_selection = strategy;
+        self
+    }
+
+    pub fn system_prompt(mut self, prompt: String) -> Self {
+        self.config.system_prompt = Some(prompt);
+        self
+    }
+
     pub fn max_tokens(mut self, tokens: u32) -> Self {
         self.config.max_tokens = tokens;
         self
@@ -166,12 +301,16 @@ impl LLMClientBuilder {
                     }
                     Box::new(crate::llm::providers::OpenAIProvider::new(&self.config)?)
                 }
-                "claude" => {
+                "azure" => Box::new(crate::llm::providers::AzureOpenAIProvider::new(
+                    &self.config,
+                )?),
+                "claude" | "anthropic" => {
                     if self.config.claude_api_key.is_none() {
                         return Err(LLMError::ConfigError("Claude API key required".to_string()));
                     }
                     Box::new(crate::llm::providers::ClaudeProvider::new(&self.config)?)
                 }
+                "gemini" => Box::new(crate::llm::providers::GeminiProvider::new(&self.config)?),
                 _ => {
                     return Err(LLMError::ConfigError(format!(
                         "Unsupported provider: {}",
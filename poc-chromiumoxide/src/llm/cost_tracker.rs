@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{info, warn};
 
+use super::providers::{canonical_provider, LOCAL_MODEL_PREFIX};
 use super::LLMResponse;
 
 /// Tracks LLM usage and costs
@@ -79,62 +80,64 @@ pub struct CostReport {
     pub projection_monthly: f64,
 }
 
-impl CostTracker {
-    /// Create new cost tracker with default pricing
-    pub fn new() -> Self {
-        let mut pricing = HashMap::new();
-
-        // OpenAI GPT-4 pricing (as of 2024)
-        pricing.insert(
-            "gpt-4".to_string(),
-            ModelPricing {
-                model_name: "gpt-4".to_string(),
-                prompt_token_cost: 0.03,     // $0.03 per 1K tokens
-                completion_token_cost: 0.06, // $0.06 per 1K tokens
-                request_cost: 0.0,
-            },
-        );
-
-        pricing.insert(
-            "gpt-4-turbo".to_string(),
-            ModelPricing {
-                model_name: "gpt-4-turbo".to_string(),
-                prompt_token_cost: 0.01,
-                completion_token_cost: 0.03,
-                request_cost: 0.0,
-            },
-        );
-
-        pricing.insert(
-            "gpt-3.5-turbo".to_string(),
-            ModelPricing {
-                model_name: "gpt-3.5-turbo".to_string(),
-                prompt_token_cost: 0.0015,    // $0.0015 per 1K tokens
-                completion_token_cost: 0.002, // $0.002 per 1K tokens
-                request_cost: 0.0,
-            },
-        );
-
+/// Providers with a pricing table
+pub const PRICED_PROVIDERS: &[&str] = &["openai", "azure", "claude", "gemini", "local"];
+
+/// Pricing of the models a provider serves, in USD per 1K tokens
+pub fn provider_pricing(provider: &str) -> Vec<ModelPricing> {
+    let table: &[(&str, f64, f64)] = match canonical_provider(provider) {
+        // OpenAI pricing (as of 2024)
+        "openai" => &[
+            ("gpt-4", 0.03, 0.06),
+            ("gpt-4-turbo", 0.01, 0.03),
+            ("gpt-3.5-turbo", 0.0015, 0.002),
+        ],
+        // Azure OpenAI bills like OpenAI but reports GPT-3.5 as gpt-35-turbo
+        "azure" => &[
+            ("gpt-4", 0.03, 0.06),
+            ("gpt-4-turbo", 0.01, 0.03),
+            ("gpt-35-turbo", 0.0015, 0.002),
+        ],
         // Claude pricing (Anthropic)
-        pricing.insert(
-            "claude-3-sonnet-20240229".to_string(),
-            ModelPricing {
-                model_name: "claude-3-sonnet-20240229".to_string(),
-                prompt_token_cost: 0.003,     // $0.003 per 1K tokens
-                completion_token_cost: 0.015, // $0.015 per 1K tokens
+        "claude" => &[
+            ("claude-3-sonnet-20240229", 0.003, 0.015),
+            ("claude-3-opus-20240229", 0.015, 0.075),
+            ("claude-3-5-haiku-20241022", 0.0008, 0.004),
+            ("claude-sonnet-4-20250514", 0.003, 0.015),
+            ("claude-opus-4-20250514", 0.015, 0.075),
+        ],
+        // Gemini pricing (Google, prompts up to 128K tokens)
+        "gemini" => &[
+            ("gemini-1.5-flash", 0.000075, 0.0003),
+            ("gemini-1.5-pro", 0.00125, 0.005),
+            ("gemini-2.5-pro", 0.00125, 0.01),
+        ],
+        // Local models run on the caller's hardware
+        "local" => &[(LOCAL_MODEL_PREFIX, 0.0, 0.0)],
+        _ => &[],
+    };
+
+    table
+        .iter()
+        .map(
+            |(model, prompt_token_cost, completion_token_cost)| ModelPricing {
+                model_name: model.to_string(),
+                prompt_token_cost: *prompt_token_cost,
+                completion_token_cost: *completion_token_cost,
                 request_cost: 0.0,
             },
-        );
+        )
+        .collect()
+}
 
-        pricing.insert(
-            "claude-3-opus-20240229".to_string(),
-            ModelPricing {
-                model_name: "claude-3-opus-20240229".to_string(),
-                prompt_token_cost: 0.015,
-                completion_token_cost: 0.075,
-                request_cost: 0.0,
-            },
-        );
+impl CostTracker {
+    /// Create new cost tracker with default pricing
+    pub fn new() -> Self {
+        let pricing = PRICED_PROVIDERS
+            .iter()
+            .flat_map(|provider| provider_pricing(provider))
+            .map(|pricing| (pricing.model_name.clone(), pricing))
+            .collect();
 
         Self {
             metrics: UsageMetrics {
@@ -226,7 +229,7 @@ impl CostTracker {
 
     /// Calculate cost for a request
     pub fn calculate_cost(&self, model: &str, prompt_tokens: u32, completion_tokens: u32) -> f64 {
-        if let Some(pricing) = self.pricing.get(model) {
+        if let Some(pricing) = self.pricing_for(model) {
             let prompt_cost = (prompt_tokens as f64 / 1000.0) * pricing.prompt_token_cost;
             let completion_cost =
                 (completion_tokens as f64 / 1000.0) * pricing.completion_token_cost;
@@ -240,6 +243,18 @@ impl CostTracker {
         }
     }
 
+    /// Pricing of `model`, or of the longest priced model name it extends
+    /// (APIs report versioned names such as `gpt-4-0613`)
+    fn pricing_for(&self, model: &str) -> Option<&ModelPricing> {
+        self.pricing.get(model).or_else(|| {
+            self.pricing
+                .iter()
+                .filter(|(name, _)| model.starts_with(name.as_str()))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, pricing)| pricing)
+        })
+    }
+
     /// Check if usage is within limits
     pub fn is_within_limits(&self) -> bool {
         // Check total limit
@@ -465,6 +480,21 @@ mod tests {
         // Test GPT-3.5 pricing
         let cost = tracker.calculate_cost("gpt-3.5-turbo", 1000, 1000);
         assert!((cost - 0.0035).abs() < 0.001); // $0.0015 + $0.002 = $0.0035
+
+        // Test Claude pricing
+        let cost = tracker.calculate_cost("claude-sonnet-4-20250514", 1000, 1000);
+        assert!((cost - 0.018).abs() < 0.0001); // $0.003 + $0.015 = $0.018
+
+        // Versioned names fall back to their base model
+        let cost = tracker.calculate_cost("gpt-4-0613", 1000, 500);
+        assert!((cost - 0.06).abs() < 0.001);
+
+        // Every provider's table feeds the tracker
+        let cost = tracker.calculate_cost("gemini-1.5-pro-002", 1000, 1000);
+        assert!((cost - 0.00625).abs() < 0.00001);
+        let cost = tracker.calculate_cost("gpt-35-turbo", 1000, 1000);
+        assert!((cost - 0.0035).abs() < 0.001);
+        assert!(provider_pricing("anthropic").len() >= 3);
     }
 
     #[test]
@@ -481,6 +511,7 @@ mod tests {
             },
             finish_reason: "stop".to_string(),
             timestamp: Utc::now(),
+            tool_calls: Vec::new(),
         };
 
         tracker.track_usage(&response);
@@ -519,6 +550,7 @@ mod tests {
                 },
                 finish_reason: "stop".to_string(),
                 timestamp: Utc::now(),
+                tool_calls: Vec::new(),
             };
             tracker.track_usage(&response);
         }
@@ -545,6 +577,7 @@ mod tests {
             },
             finish_reason: "stop".to_string(),
             timestamp: Utc::now(),
+            tool_calls: Vec::new(),
         };
 
         tracker.track_usage(&response);
@@ -0,0 +1,319 @@
+// Embedding providers
+// Turn text into vectors so it can be compared by meaning instead of by substring
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+use super::LLMError;
+
+/// Most inputs sent to a remote provider in one request
+const MAX_BATCH: usize = 256;
+
+/// Trait for embedding providers
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// One vector per input text, in input order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMError>;
+    fn provider_name(&self) -> &str;
+}
+
+/// Configuration for the embedding provider
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingConfig {
+    /// "local" or "openai" (any OpenAI-compatible `/embeddings` endpoint)
+    pub provider: String,
+    pub model: String,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    /// Vector size of the local provider
+    pub dimensions: usize,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            provider: "local".to_string(),
+            model: "text-embedding-3-small".to_string(),
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key: None,
+            dimensions: 256,
+        }
+    }
+}
+
+impl EmbeddingConfig {
+    /// Configuration from `RAINBOW_EMBEDDING_PROVIDER`, `RAINBOW_EMBEDDING_MODEL`,
+    /// `RAINBOW_EMBEDDING_URL` and `OPENAI_API_KEY`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            provider: std::env::var("RAINBOW_EMBEDDING_PROVIDER").unwrap_or(defaults.provider),
+            model: std::env::var("RAINBOW_EMBEDDING_MODEL").unwrap_or(defaults.model),
+            base_url: std::env::var("RAINBOW_EMBEDDING_URL").unwrap_or(defaults.base_url),
+            api_key: std::env::var("OPENAI_API_KEY")
+                .ok()
+                .filter(|k| !k.is_empty()),
+            dimensions: defaults.dimensions,
+        }
+    }
+}
+
+/// Provider named by `config`
+pub fn create_embedding_provider(
+    config: &EmbeddingConfig,
+) -> Result<Arc<dyn EmbeddingProvider>, LLMError> {
+    match config.provider.as_str() {
+        "local" => Ok(Arc::new(HashingEmbeddingProvider::new(config.dimensions))),
+        "openai" => Ok(Arc::new(OpenAIEmbeddingProvider::new(config)?)),
+        other => Err(LLMError::ConfigError(format!(
+            "Unknown embedding provider '{}', expected local or openai",
+            other
+        ))),
+    }
+}
+
+/// OpenAI embeddings, or any server exposing the same `/embeddings` API
+pub struct OpenAIEmbeddingProvider {
+    client: Client,
+    api_key: Option<String>,
+    base_url: String,
+    model: String,
+}
+
+impl OpenAIEmbeddingProvider {
+    pub fn new(config: &EmbeddingConfig) -> Result<Self, LLMError> {
+        // Self-hosted compatible servers often need no key; api.openai.com does
+        if config.api_key.is_none() && config.base_url.contains("api.openai.com") {
+            return Err(LLMError::ConfigError(
+                "OpenAI API key required for embeddings".to_string(),
+            ));
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .map_err(|e| LLMError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            api_key: config.api_key.clone(),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            model: config.model.clone(),
+        })
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMError> {
+        let request = EmbeddingRequest {
+            model: &self.model,
+            input: texts,
+        };
+        let mut builder = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&request);
+        if let Some(key) = &self.api_key {
+            builder = builder.header("Authorization", format!("Bearer {}", key));
+        }
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(format!("Request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!("Embedding API error {}: {}", status, error_text);
+
+            return match status.as_u16() {
+                429 => Err(LLMError::RateLimit(error_text)),
+                401 | 403 => Err(LLMError::AuthError(error_text)),
+                _ => Err(LLMError::ApiError(format!("{}: {}", status, error_text))),
+            };
+        }
+
+        let mut body: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| LLMError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+        if body.data.len() != texts.len() {
+            return Err(LLMError::InvalidResponse(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                body.data.len()
+            )));
+        }
+        body.data.sort_by_key(|d| d.index);
+        Ok(body.data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddingProvider {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMError> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(MAX_BATCH) {
+            vectors.extend(self.embed_batch(batch).await?);
+        }
+        Ok(vectors)
+    }
+
+    fn provider_name(&self) -> &str {
+        "openai"
+    }
+}
+
+/// Offline provider hashing words and character trigrams into a fixed-size vector.
+///
+/// It knows no synonyms, but tolerates word order, inflections and typos far better
+/// than substring matching, and needs no network or API key.
+pub struct HashingEmbeddingProvider {
+    dimensions: usize,
+}
+
+impl HashingEmbeddingProvider {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(16),
+        }
+    }
+
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        let lower = text.to_lowercase();
+        for word in lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            self.add_feature(&mut vector, word.as_bytes(), 1.0);
+            let padded: Vec<char> = format!(" {} ", word).chars().collect();
+            for trigram in padded.windows(3) {
+                let trigram: String = trigram.iter().collect();
+                self.add_feature(&mut vector, trigram.as_bytes(), 0.5);
+            }
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+
+    fn add_feature(&self, vector: &mut [f32], feature: &[u8], weight: f32) {
+        let hash = fnv1a(feature);
+        let slot = (hash % self.dimensions as u64) as usize;
+        // The top bit picks a sign so that collisions cancel out rather than pile up
+        vector[slot] += if hash >> 63 == 0 { weight } else { -weight };
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashingEmbeddingProvider {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMError> {
+        Ok(texts.iter().map(|t| self.embed_text(t)).collect())
+    }
+
+    fn provider_name(&self) -> &str {
+        "local"
+    }
+}
+
+/// Provider configured by the environment, falling back to the local provider
+pub fn embedding_provider_from_env() -> Arc<dyn EmbeddingProvider> {
+    let config = EmbeddingConfig::from_env();
+    match create_embedding_provider(&config) {
+        Ok(provider) => {
+            info!("Using {} embedding provider", provider.provider_name());
+            provider
+        }
+        Err(e) => {
+            error!(
+                "Embedding provider unavailable, using local embeddings: {}",
+                e
+            );
+            Arc::new(HashingEmbeddingProvider::new(config.dimensions))
+        }
+    }
+}
+
+/// Cosine similarity of two vectors, 0.0 when either is empty or zero
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// 64-bit FNV-1a, stable across runs and Rust versions unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// Embedding API request/response structures
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_texts_score_higher() {
+        let provider = HashingEmbeddingProvider::new(256);
+        let query = provider.embed_text("sign in button");
+        let sign_in = provider.embed_text("button Sign in to your account");
+        let newsletter = provider.embed_text("button Subscribe to the newsletter");
+
+        assert!(cosine_similarity(&query, &sign_in) > cosine_similarity(&query, &newsletter));
+        assert!((cosine_similarity(&sign_in, &sign_in) - 1.0).abs() < 1e-5);
+        assert_eq!(
+            provider.embed_text("Checkout"),
+            provider.embed_text("checkout")
+        );
+    }
+
+    #[test]
+    fn unknown_providers_are_rejected() {
+        let config = EmbeddingConfig {
+            provider: "carrier-pigeon".to_string(),
+            ..EmbeddingConfig::default()
+        };
+        assert!(create_embedding_provider(&config).is_err());
+
+        let config = EmbeddingConfig {
+            provider: "openai".to_string(),
+            ..EmbeddingConfig::default()
+        };
+        assert!(create_embedding_provider(&config).is_err(), "needs a key");
+        assert_eq!(cosine_similarity(&[], &[]), 0.0);
+    }
+}
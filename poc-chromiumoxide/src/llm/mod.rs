@@ -3,19 +3,32 @@
 
 pub mod client;
 pub mod cost_tracker;
+pub mod embeddings;
+pub mod model_selection;
 pub mod prompt_engine;
 pub mod providers;
 pub mod task_planner;
+pub mod token_budget;
 
-pub use client::{LLMClient, LLMError, LLMResponse, TokenUsage};
+pub use client::{
+    ChatRole, ChatTurn, ImageInput, LLMClient, LLMError, LLMRequest, LLMResponse, TokenUsage,
+    ToolCall, ToolDefinition,
+};
 pub use cost_tracker::{CostTracker, UsageMetrics};
+pub use embeddings::{EmbeddingConfig, EmbeddingProvider};
+pub use model_selection::{ModelSelectionStrategy, ModelTiers, ProviderCapabilities};
 pub use prompt_engine::{ContextAwarePrompt, PromptEngine, PromptTemplate};
-pub use providers::{ClaudeProvider, LLMProvider, OpenAIProvider};
+pub use providers::{
+    canonical_provider, AzureOpenAIProvider, ClaudeProvider, GeminiProvider, LLMProvider,
+    LocalProvider, OpenAIProvider,
+};
 pub use task_planner::{TaskPlan, TaskPlanExecutor, TaskStep};
+pub use token_budget::TokenBudget;
 
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tracing::info;
 
 /// Configuration for LLM services
 #[derive(Debug, Clone, Deserialize)]
@@ -26,6 +39,163 @@ pub struct LLMConfig {
     pub max_tokens: u32,
     pub temperature: f32,
     pub cost_limit_usd: f32,
+    /// Model to use regardless of `model_selection`
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub model_selection: ModelSelectionStrategy,
+    /// System prompt sent with every query
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Local model server used by the "local" provider
+    #[serde(default)]
+    pub local: LocalModelConfig,
+    /// Azure OpenAI resource used by the "azure" provider
+    #[serde(default)]
+    pub azure: Option<AzureOpenAIConfig>,
+    #[serde(default)]
+    pub gemini_api_key: Option<String>,
+    /// Hard token limits checked before requests are sent
+    #[serde(default)]
+    pub budget: TokenBudget,
+}
+
+/// Azure OpenAI resource, addressed by deployment rather than model name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureOpenAIConfig {
+    /// Resource endpoint, e.g. `https://my-resource.openai.azure.com`
+    pub endpoint: String,
+    pub api_version: String,
+    /// Deployment used for models without an entry in `deployments`
+    pub deployment: String,
+    /// Deployment serving each model, so model selection works across deployments
+    #[serde(default)]
+    pub deployments: HashMap<String, String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Azure AD credentials, used when no API key is set
+    #[serde(default)]
+    pub aad: Option<AzureAdCredentials>,
+}
+
+/// Azure AD (Entra ID) authentication for Azure OpenAI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AzureAdCredentials {
+    /// A bearer token obtained elsewhere, e.g. from `az account get-access-token`
+    Token(String),
+    /// A service principal exchanging its secret for tokens
+    ClientSecret {
+        tenant_id: String,
+        client_id: String,
+        client_secret: String,
+    },
+}
+
+impl AzureOpenAIConfig {
+    pub const DEFAULT_API_VERSION: &'static str = "2024-06-01";
+
+    /// Configuration from `AZURE_OPENAI_ENDPOINT`, `AZURE_OPENAI_DEPLOYMENT`,
+    /// `AZURE_OPENAI_DEPLOYMENTS` (`model=deployment,...`), `AZURE_OPENAI_API_VERSION`
+    /// and either `AZURE_OPENAI_API_KEY`, `AZURE_OPENAI_AD_TOKEN` or
+    /// `AZURE_TENANT_ID`/`AZURE_CLIENT_ID`/`AZURE_CLIENT_SECRET`; `None` without an
+    /// endpoint and deployment
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let aad = var("AZURE_OPENAI_AD_TOKEN")
+            .map(AzureAdCredentials::Token)
+            .or_else(|| {
+                Some(AzureAdCredentials::ClientSecret {
+                    tenant_id: var("AZURE_TENANT_ID")?,
+                    client_id: var("AZURE_CLIENT_ID")?,
+                    client_secret: var("AZURE_CLIENT_SECRET")?,
+                })
+            });
+        Some(Self {
+            endpoint: var("AZURE_OPENAI_ENDPOINT")?,
+            api_version: var("AZURE_OPENAI_API_VERSION")
+                .unwrap_or_else(|| Self::DEFAULT_API_VERSION.to_string()),
+            deployment: var("AZURE_OPENAI_DEPLOYMENT")?,
+            deployments: var("AZURE_OPENAI_DEPLOYMENTS")
+                .map(|v| Self::parse_deployments(&v))
+                .unwrap_or_default(),
+            api_key: var("AZURE_OPENAI_API_KEY"),
+            aad,
+        })
+    }
+
+    /// Parse `model=deployment` pairs separated by commas
+    pub fn parse_deployments(spec: &str) -> HashMap<String, String> {
+        spec.split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(model, deployment)| (model.trim().to_string(), deployment.trim().to_string()))
+            .filter(|(model, deployment)| !model.is_empty() && !deployment.is_empty())
+            .collect()
+    }
+
+    /// Deployment serving `model`
+    pub fn deployment_for(&self, model: &str) -> &str {
+        self.deployments
+            .get(model)
+            .map_or(self.deployment.as_str(), String::as_str)
+    }
+}
+
+/// API spoken by a local model server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalModelApi {
+    /// Ollama's `/api/chat`
+    #[default]
+    Ollama,
+    /// llama.cpp server's OpenAI-compatible `/v1/chat/completions`
+    LlamaCpp,
+}
+
+/// Local model server that runs without network access or API keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalModelConfig {
+    pub api: LocalModelApi,
+    pub base_url: String,
+    pub model: String,
+    /// Context window the server is configured with, in tokens
+    pub context_tokens: usize,
+}
+
+impl Default for LocalModelConfig {
+    fn default() -> Self {
+        Self {
+            api: LocalModelApi::Ollama,
+            base_url: "http://localhost:11434".to_string(),
+            model: "llama3.1:8b".to_string(),
+            context_tokens: 8_192,
+        }
+    }
+}
+
+impl LocalModelConfig {
+    /// Configuration from `RAINBOW_LOCAL_LLM_API`, `RAINBOW_LOCAL_LLM_URL`,
+    /// `RAINBOW_LOCAL_LLM_MODEL` and `RAINBOW_LOCAL_LLM_CONTEXT`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let api = match std::env::var("RAINBOW_LOCAL_LLM_API").as_deref() {
+            Ok("llama_cpp") | Ok("llamacpp") => LocalModelApi::LlamaCpp,
+            _ => LocalModelApi::Ollama,
+        };
+        let base_url = std::env::var("RAINBOW_LOCAL_LLM_URL").unwrap_or_else(|_| match api {
+            LocalModelApi::Ollama => defaults.base_url.clone(),
+            LocalModelApi::LlamaCpp => "http://localhost:8080".to_string(),
+        });
+        Self {
+            api,
+            base_url,
+            model: std::env::var("RAINBOW_LOCAL_LLM_MODEL").unwrap_or(defaults.model),
+            context_tokens: std::env::var("RAINBOW_LOCAL_LLM_CONTEXT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.context_tokens),
+        }
+    }
 }
 
 impl Default for LLMConfig {
@@ -37,16 +207,33 @@ impl Default for LLMConfig {
             max_tokens: 4000,
             temperature: 0.7,
             cost_limit_usd: 1.0,
+            model: None,
+            model_selection: ModelSelectionStrategy::default(),
+            system_prompt: None,
+            local: LocalModelConfig::default(),
+            azure: None,
+            gemini_api_key: None,
+            budget: TokenBudget::default(),
         }
     }
 }
 
-/// Main LLM service manager
+impl LLMConfig {
+    /// Model a provider with `tiers` should use for `request`
+    pub fn model_for(&self, tiers: &ModelTiers, request: &LLMRequest) -> String {
+        self.model
+            .clone()
+            .unwrap_or_else(|| self.model_selection.select(tiers, request).to_string())
+    }
+}
+
+/// Main LLM service manager; one service is one session for token budgets
 pub struct LLMService {
     config: LLMConfig,
     providers: HashMap<String, Box<dyn LLMProvider>>,
     cost_tracker: CostTracker,
     prompt_engine: PromptEngine,
+    session_tokens: u64,
 }
 
 impl LLMService {
@@ -66,12 +253,28 @@ impl LLMService {
                 Box::new(ClaudeProvider::new(&config)?),
             );
         }
+        if config.azure.is_some() {
+            providers.insert(
+                "azure".to_string(),
+                Box::new(AzureOpenAIProvider::new(&config)?),
+            );
+        }
+        if config.gemini_api_key.is_some() {
+            providers.insert(
+                "gemini".to_string(),
+                Box::new(GeminiProvider::new(&config)?),
+            );
+        }
+        if canonical_provider(&config.default_provider) == "local" {
+            providers.insert("local".to_string(), Box::new(LocalProvider::new(&config)?));
+        }
 
         Ok(Self {
             config,
             providers,
             cost_tracker: CostTracker::new(),
             prompt_engine: PromptEngine::new(),
+            session_tokens: 0,
         })
     }
 
@@ -89,17 +292,113 @@ impl LLMService {
     }
 
     pub async fn query(&mut self, prompt: &str) -> Result<LLMResponse> {
-        let provider_name = &self.config.default_provider;
-        if let Some(provider) = self.providers.get_mut(provider_name) {
-            let response = provider.query(prompt, &self.config).await?;
-            self.cost_tracker.track_usage(&response);
-            Ok(response)
-        } else {
-            Err(anyhow::anyhow!(
-                "No LLM provider available: {}",
-                provider_name
-            ))
+        let request = LLMRequest {
+            system: self.config.system_prompt.clone(),
+            ..LLMRequest::new(prompt)
+        };
+        let (provider_name, _, config) = self.prepare(&request)?;
+        let provider = self
+            .providers
+            .get_mut(&provider_name)
+            .expect("selected provider is registered");
+        let response = provider.query(prompt, &config).await?;
+        self.record_usage(&response);
+        Ok(response)
+    }
+
+    /// Send a request with a system prompt, history and tools. History that does
+    /// not fit the provider's context window is summarized or dropped, oldest first.
+    pub async fn complete(&mut self, request: &LLMRequest) -> Result<LLMResponse> {
+        let (provider_name, request, config) = self.prepare(request)?;
+        let provider = self
+            .providers
+            .get_mut(&provider_name)
+            .expect("selected provider is registered");
+        let response = provider.complete(&request, &config).await?;
+        self.record_usage(&response);
+        Ok(response)
+    }
+
+    /// Tokens used by this service so far
+    pub fn session_tokens_used(&self) -> u64 {
+        self.session_tokens
+    }
+
+    /// Provider to send `request` to, the request fitted to the default
+    /// provider's context window, and the configuration to send it with, its
+    /// `max_tokens` lowered to what the token budgets allow
+    fn prepare(&self, request: &LLMRequest) -> Result<(String, LLMRequest, LLMConfig)> {
+        let preferred = canonical_provider(&self.config.default_provider);
+        let fitted = match self.providers.get(preferred) {
+            Some(provider) => token_budget::fit_to_context(
+                request,
+                provider.capabilities().context_tokens,
+                self.config.max_tokens,
+            ),
+            None => request.clone(),
+        };
+        if fitted.history != request.history {
+            info!(
+                "Trimmed conversation history from {} to {} turns to fit the {} context window",
+                request.history.len(),
+                fitted.history.len(),
+                preferred
+            );
+        }
+        let request = fitted;
+
+        let prompt_tokens = model_selection::estimate_tokens(&request);
+        let max_tokens =
+            self.config
+                .budget
+                .admit(prompt_tokens, self.config.max_tokens, self.session_tokens)?;
+        if max_tokens < self.config.max_tokens {
+            info!(
+                "Lowering max_tokens from {} to {} to stay within the token budget",
+                self.config.max_tokens, max_tokens
+            );
         }
+
+        let provider_name = self.provider_for(&request, max_tokens)?;
+        let config = LLMConfig {
+            max_tokens,
+            ..self.config.clone()
+        };
+        Ok((provider_name, request, config))
+    }
+
+    fn record_usage(&mut self, response: &LLMResponse) {
+        self.session_tokens += u64::from(response.usage.total_tokens);
+        self.cost_tracker.track_usage(response);
+    }
+
+    /// The default provider if it can serve `request`, otherwise another
+    /// registered provider picked by the model selection strategy
+    fn provider_for(&self, request: &LLMRequest, max_tokens: u32) -> Result<String> {
+        let preferred = canonical_provider(&self.config.default_provider);
+        let candidates: Vec<(String, ProviderCapabilities)> = self
+            .providers
+            .iter()
+            .map(|(name, provider)| (name.clone(), provider.capabilities()))
+            .collect();
+        self.config
+            .model_selection
+            .select_provider(preferred, &candidates, request, max_tokens)
+            .ok_or_else(|| {
+                if self.providers.contains_key(preferred) {
+                    anyhow::anyhow!(
+                        "No LLM provider can serve this request (tools: {}, vision: {}, ~{} tokens)",
+                        !request.tools.is_empty(),
+                        request.requires_vision,
+                        model_selection::estimate_tokens(request)
+                    )
+                } else {
+                    anyhow::anyhow!(
+                        "No LLM provider available: {}",
+                        self.config.default_provider
+                    )
+                }
+            })
     }
 
     pub fn get_cost_metrics(&self) -> &UsageMetrics {
@@ -117,4 +416,67 @@ mod tests {
         let service = LLMService::new(config);
         assert!(service.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_offline_service_rejects_what_local_models_cannot_do() {
+        let config = LLMConfig {
+            default_provider: "ollama".to_string(),
+            ..LLMConfig::default()
+        };
+        let mut service = LLMService::new(config).unwrap();
+        assert!(service.providers.contains_key("local"));
+
+        let mut request = LLMRequest::new("Describe the screenshot");
+        request.requires_vision = true;
+        let error = service.complete(&request).await.unwrap_err();
+        assert!(error.to_string().contains("can serve"));
+    }
+
+    #[tokio::test]
+    async fn test_session_budget_rejects_before_reaching_the_provider() {
+        let config = LLMConfig {
+            default_provider: "ollama".to_string(),
+            budget: TokenBudget {
+                per_request: None,
+                per_session: Some(1_000),
+            },
+            ..LLMConfig::default()
+        };
+        let mut service = LLMService::new(config).unwrap();
+        service.session_tokens = 900;
+
+        let error = service.query("Plan a login").await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<LLMError>(),
+            Some(LLMError::BudgetExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn test_azure_models_route_to_their_deployments() {
+        let azure = AzureOpenAIConfig {
+            endpoint: "https://example.openai.azure.com".to_string(),
+            api_version: AzureOpenAIConfig::DEFAULT_API_VERSION.to_string(),
+            deployment: "chat".to_string(),
+            deployments: AzureOpenAIConfig::parse_deployments(
+                "gpt-4=gpt4-prod, gpt-3.5-turbo = gpt35,broken",
+            ),
+            api_key: Some("key".to_string()),
+            aad: None,
+        };
+        assert_eq!(azure.deployment_for("gpt-4"), "gpt4-prod");
+        assert_eq!(azure.deployment_for("gpt-3.5-turbo"), "gpt35");
+        assert_eq!(azure.deployment_for("gpt-4-turbo"), "chat");
+        assert_eq!(azure.deployments.len(), 2);
+
+        let config = LLMConfig {
+            default_provider: "azure".to_string(),
+            azure: Some(azure),
+            gemini_api_key: Some("key".to_string()),
+            ..LLMConfig::default()
+        };
+        let service = LLMService::new(config).unwrap();
+        assert!(service.providers.contains_key("azure"));
+        assert!(service.providers.contains_key("gemini"));
+    }
 }
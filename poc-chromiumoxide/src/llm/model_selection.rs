@@ -0,0 +1,226 @@
+// Model Selection
+// Picks the provider and model used for each request
+
+use serde::{Deserialize, Serialize};
+
+use super::LLMRequest;
+
+/// Prompts longer than this (in characters) count as large for adaptive selection
+const ADAPTIVE_LARGE_PROMPT: usize = 12_000;
+
+/// Prompts shorter than this without tools go to the fast model
+const ADAPTIVE_SMALL_PROMPT: usize = 1_500;
+
+/// Rough characters per token, for estimating whether a prompt fits a context window
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Rough tokens a vision model spends on one attached image
+const TOKENS_PER_IMAGE: usize = 1_500;
+
+/// What a provider's models can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    pub vision: bool,
+    pub tool_use: bool,
+    /// Context window, in tokens
+    pub context_tokens: usize,
+}
+
+impl Default for ProviderCapabilities {
+    fn default() -> Self {
+        Self {
+            vision: false,
+            tool_use: false,
+            context_tokens: 8_192,
+        }
+    }
+}
+
+impl ProviderCapabilities {
+    /// Whether `request` can be served, leaving room for `max_tokens` of output
+    pub fn fits(&self, request: &LLMRequest, max_tokens: u32) -> bool {
+        (self.vision || !request.requires_vision)
+            && (self.tool_use || request.tools.is_empty())
+            && estimate_tokens(request) + max_tokens as usize <= self.context_tokens
+    }
+}
+
+/// Approximate prompt tokens of a request
+pub fn estimate_tokens(request: &LLMRequest) -> usize {
+    let chars = request.prompt.len()
+        + request.system.as_ref().map_or(0, |s| s.len())
+        + request
+            .history
+            .iter()
+            .map(|turn| turn.content.len())
+            .sum::<usize>();
+    chars.div_ceil(CHARS_PER_TOKEN) + request.images.len() * TOKENS_PER_IMAGE
+}
+
+/// A provider's models, cheapest to most capable
+#[derive(Debug, Clone, Copy)]
+pub struct ModelTiers {
+    pub fast: &'static str,
+    pub balanced: &'static str,
+    pub best: &'static str,
+}
+
+pub const OPENAI_MODELS: ModelTiers = ModelTiers {
+    fast: "gpt-3.5-turbo",
+    balanced: "gpt-4",
+    best: "gpt-4-turbo",
+};
+
+pub const ANTHROPIC_MODELS: ModelTiers = ModelTiers {
+    fast: "claude-3-5-haiku-20241022",
+    balanced: "claude-sonnet-4-20250514",
+    best: "claude-opus-4-20250514",
+};
+
+pub const GEMINI_MODELS: ModelTiers = ModelTiers {
+    fast: "gemini-1.5-flash",
+    balanced: "gemini-1.5-pro",
+    best: "gemini-2.5-pro",
+};
+
+/// How a provider chooses between its models
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSelectionStrategy {
+    /// The provider's general-purpose model
+    #[default]
+    Balanced,
+    /// The cheapest model
+    CostOptimized,
+    /// The most capable model
+    QualityOptimized,
+    /// Small prompts go to the fast model, large ones to the best
+    Adaptive,
+}
+
+impl ModelSelectionStrategy {
+    /// Provider to send `request` to: `preferred` when it can serve it, otherwise
+    /// the smallest capable provider when optimizing cost and the largest else
+    pub fn select_provider(
+        self,
+        preferred: &str,
+        candidates: &[(String, ProviderCapabilities)],
+        request: &LLMRequest,
+        max_tokens: u32,
+    ) -> Option<String> {
+        let mut capable: Vec<&(String, ProviderCapabilities)> = candidates
+            .iter()
+            .filter(|(_, capabilities)| capabilities.fits(request, max_tokens))
+            .collect();
+        if capable.iter().any(|(name, _)| name == preferred) {
+            return Some(preferred.to_string());
+        }
+        capable.sort_by(|a, b| {
+            a.1.context_tokens
+                .cmp(&b.1.context_tokens)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        let chosen = match self {
+            Self::CostOptimized => capable.first(),
+            _ => capable.last(),
+        };
+        chosen.map(|(name, _)| name.clone())
+    }
+
+    pub fn select(self, tiers: &ModelTiers, request: &LLMRequest) -> &'static str {
+        match self {
+            Self::Balanced => tiers.balanced,
+            Self::CostOptimized => tiers.fast,
+            Self::QualityOptimized => tiers.best,
+            Self::Adaptive => {
+                let size = request.prompt.len() + request.system.as_ref().map_or(0, |s| s.len());
+                if size > ADAPTIVE_LARGE_PROMPT {
+                    tiers.best
+                } else if size < ADAPTIVE_SMALL_PROMPT && request.tools.is_empty() {
+                    tiers.fast
+                } else {
+                    tiers.balanced
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::ToolDefinition;
+
+    #[test]
+    fn adaptive_selection_scales_with_the_request() {
+        let mut request = LLMRequest::new("Click the login button");
+        let adaptive = ModelSelectionStrategy::Adaptive;
+        assert_eq!(
+            adaptive.select(&ANTHROPIC_MODELS, &request),
+            ANTHROPIC_MODELS.fast
+        );
+
+        request.tools.push(ToolDefinition {
+            name: "click".to_string(),
+            description: "Click an element".to_string(),
+            input_schema: serde_json::json!({ "type": "object" }),
+        });
+        assert_eq!(
+            adaptive.select(&ANTHROPIC_MODELS, &request),
+            ANTHROPIC_MODELS.balanced
+        );
+
+        request.prompt = "x".repeat(ADAPTIVE_LARGE_PROMPT + 1);
+        assert_eq!(
+            adaptive.select(&ANTHROPIC_MODELS, &request),
+            ANTHROPIC_MODELS.best
+        );
+    }
+
+    #[test]
+    fn providers_without_a_capability_are_skipped() {
+        let local = ProviderCapabilities::default();
+        let claude = ProviderCapabilities {
+            vision: true,
+            tool_use: true,
+            context_tokens: 200_000,
+        };
+        let candidates = vec![("claude".to_string(), claude), ("local".to_string(), local)];
+        let strategy = ModelSelectionStrategy::CostOptimized;
+
+        let request = LLMRequest::new("Plan a login");
+        assert_eq!(
+            strategy.select_provider("local", &candidates, &request, 2000),
+            Some("local".to_string())
+        );
+
+        let mut screenshot = request.clone();
+        screenshot.requires_vision = true;
+        assert_eq!(
+            strategy.select_provider("local", &candidates, &screenshot, 2000),
+            Some("claude".to_string())
+        );
+
+        let long = LLMRequest::new("x".repeat(40_000));
+        assert!(!local.fits(&long, 2000));
+        assert_eq!(
+            strategy.select_provider("local", &candidates[1..], &long, 2000),
+            None
+        );
+    }
+
+    #[test]
+    fn fixed_strategies_pick_their_tier() {
+        let request = LLMRequest::new("Summarize the page");
+        assert_eq!(
+            ModelSelectionStrategy::CostOptimized.select(&OPENAI_MODELS, &request),
+            "gpt-3.5-turbo"
+        );
+        assert_eq!(
+            ModelSelectionStrategy::default().select(&OPENAI_MODELS, &request),
+            "gpt-4"
+        );
+        let parsed: ModelSelectionStrategy = serde_json::from_str("\"quality_optimized\"").unwrap();
+        assert_eq!(parsed, ModelSelectionStrategy::QualityOptimized);
+    }
+}
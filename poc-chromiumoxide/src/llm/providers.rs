@@ -1,14 +1,27 @@
 // LLM Provider implementations
-// Supports OpenAI GPT and Claude API integration
+// Supports OpenAI GPT, Azure OpenAI, the Anthropic Claude messages API, Google
+// Gemini and local models served by Ollama or llama.cpp
 
 use anyhow::Result;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{error, info};
 
-use super::{LLMConfig, LLMError, LLMResponse, TokenUsage};
+use super::model_selection::{
+    ProviderCapabilities, ANTHROPIC_MODELS, GEMINI_MODELS, OPENAI_MODELS,
+};
+use super::{
+    AzureAdCredentials, AzureOpenAIConfig, ChatRole, ImageInput, LLMConfig, LLMError, LLMRequest,
+    LLMResponse, LocalModelApi, LocalModelConfig, TokenUsage, ToolCall, ToolDefinition,
+};
+
+/// Azure AD authority issuing tokens for service principals
+const AZURE_AD_AUTHORITY: &str = "https://login.microsoftonline.com";
+
+/// Scope of Azure AD tokens accepted by Azure OpenAI
+const AZURE_COGNITIVE_SERVICES_SCOPE: &str = "https://cognitiveservices.azure.com/.default";
 
 /// Trait for LLM providers
 #[async_trait]
@@ -16,6 +29,60 @@ pub trait LLMProvider: Send + Sync {
     async fn query(&mut self, prompt: &str, config: &LLMConfig) -> Result<LLMResponse, LLMError>;
     fn provider_name(&self) -> &str;
     fn is_available(&self) -> bool;
+
+    /// What the provider's models can do; used to route requests it cannot serve
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
+
+    /// Send a request with a system prompt, history, images and tools. Providers
+    /// without native support fold the system prompt and history into the query
+    /// and reject images and tools.
+    async fn complete(
+        &mut self,
+        request: &LLMRequest,
+        config: &LLMConfig,
+    ) -> Result<LLMResponse, LLMError> {
+        if !request.tools.is_empty() {
+            return Err(LLMError::ConfigError(format!(
+                "Provider {} does not support tool use",
+                self.provider_name()
+            )));
+        }
+        if !request.images.is_empty() {
+            return Err(LLMError::ConfigError(format!(
+                "Provider {} does not support image input",
+                self.provider_name()
+            )));
+        }
+        if request.system.is_none() && request.history.is_empty() {
+            return self.query(&request.prompt, config).await;
+        }
+
+        let mut prompt = String::new();
+        if let Some(system) = &request.system {
+            prompt.push_str(system);
+            prompt.push_str("\n\n");
+        }
+        for turn in &request.history {
+            prompt.push_str(&format!("{}: {}\n", turn.role.as_str(), turn.content));
+        }
+        if !request.history.is_empty() {
+            prompt.push('\n');
+        }
+        prompt.push_str(&request.prompt);
+        self.query(&prompt, config).await
+    }
+}
+
+/// Provider key a configured provider name refers to; "anthropic" is an alias of
+/// "claude" and "ollama" of "local"
+pub fn canonical_provider(name: &str) -> &str {
+    match name {
+        "anthropic" => "claude",
+        "ollama" => "local",
+        other => other,
+    }
 }
 
 /// OpenAI GPT provider
@@ -49,12 +116,28 @@ impl OpenAIProvider {
 #[async_trait]
 impl LLMProvider for OpenAIProvider {
     async fn query(&mut self, prompt: &str, config: &LLMConfig) -> Result<LLMResponse, LLMError> {
+        let request = LLMRequest {
+            system: config.system_prompt.clone(),
+            ..LLMRequest::new(prompt)
+        };
+        self.complete(&request, config).await
+    }
+
+    async fn complete(
+        &mut self,
+        request: &LLMRequest,
+        config: &LLMConfig,
+    ) -> Result<LLMResponse, LLMError> {
+        if !request.tools.is_empty() {
+            return Err(LLMError::ConfigError(format!(
+                "Provider {} does not support tool use",
+                self.provider_name()
+            )));
+        }
+
         let request = OpenAIRequest {
-            model: "gpt-4".to_string(),
-            messages: vec![OpenAIMessage {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
+            model: config.model_for(&OPENAI_MODELS, request),
+            messages: chat_messages(request),
             max_tokens: Some(config.max_tokens),
             temperature: Some(config.temperature),
         };
@@ -108,6 +191,7 @@ impl LLMProvider for OpenAIProvider {
                 .clone()
                 .unwrap_or_else(|| "unknown".to_string()),
             timestamp: chrono::Utc::now(),
+            tool_calls: Vec::new(),
         })
     }
 
@@ -118,9 +202,17 @@ impl LLMProvider for OpenAIProvider {
     fn is_available(&self) -> bool {
         !self.api_key.is_empty()
     }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: true,
+            tool_use: false,
+            context_tokens: 128_000,
+        }
+    }
 }
 
-/// Claude provider (Anthropic)
+/// Claude provider for the Anthropic messages API, with system prompts and tool use
 pub struct ClaudeProvider {
     client: Client,
     api_key: String,
@@ -151,24 +243,46 @@ impl ClaudeProvider {
 #[async_trait]
 impl LLMProvider for ClaudeProvider {
     async fn query(&mut self, prompt: &str, config: &LLMConfig) -> Result<LLMResponse, LLMError> {
-        let request = ClaudeRequest {
-            model: "claude-3-sonnet-20240229".to_string(),
+        let request = LLMRequest {
+            system: config.system_prompt.clone(),
+            ..LLMRequest::new(prompt)
+        };
+        self.complete(&request, config).await
+    }
+
+    async fn complete(
+        &mut self,
+        request: &LLMRequest,
+        config: &LLMConfig,
+    ) -> Result<LLMResponse, LLMError> {
+        let claude_request = ClaudeRequest {
+            model: config.model_for(&ANTHROPIC_MODELS, request),
             max_tokens: config.max_tokens,
             temperature: Some(config.temperature),
-            messages: vec![ClaudeMessage {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
+            system: request.system.as_deref(),
+            messages: request
+                .history
+                .iter()
+                .map(|turn| ClaudeMessage {
+                    role: turn.role.as_str().to_string(),
+                    content: serde_json::Value::String(turn.content.clone()),
+                })
+                .chain(std::iter::once(ClaudeMessage {
+                    role: "user".to_string(),
+                    content: claude_user_content(request),
+                }))
+                .collect(),
+            tools: (!request.tools.is_empty()).then_some(request.tools.as_slice()),
         };
 
-        info!("Sending request to Claude API");
+        info!("Sending request to Claude API ({})", claude_request.model);
         let response = self
             .client
             .post(format!("{}/messages", self.base_url))
             .header("x-api-key", &self.api_key)
             .header("Content-Type", "application/json")
             .header("anthropic-version", "2023-06-01")
-            .json(&request)
+            .json(&claude_request)
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(format!("Request failed: {}", e)))?;
@@ -182,7 +296,8 @@ impl LLMProvider for ClaudeProvider {
             error!("Claude API error {}: {}", status, error_text);
 
             return match status.as_u16() {
-                429 => Err(LLMError::RateLimit(error_text)),
+                // 529 is Anthropic's "overloaded", retryable like a rate limit
+                429 | 529 => Err(LLMError::RateLimit(error_text)),
                 401 | 403 => Err(LLMError::AuthError(error_text)),
                 _ => Err(LLMError::ApiError(format!("{}: {}", status, error_text))),
             };
@@ -192,37 +307,584 @@ impl LLMProvider for ClaudeProvider {
             .json()
             .await
             .map_err(|e| LLMError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+        claude_response.into_llm_response()
+    }
 
-        let content = claude_response
-            .content
-            .first()
-            .ok_or_else(|| LLMError::InvalidResponse("No content in response".to_string()))?;
+    fn provider_name(&self) -> &str {
+        "claude"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: true,
+            tool_use: true,
+            context_tokens: 200_000,
+        }
+    }
+}
+
+/// Azure OpenAI provider. Requests go to the deployment serving the selected
+/// model and authenticate with an API key or an Azure AD token.
+pub struct AzureOpenAIProvider {
+    client: Client,
+    config: AzureOpenAIConfig,
+    /// Azure AD token fetched for a service principal, and when to refresh it
+    aad_token: Option<(String, Instant)>,
+}
+
+impl AzureOpenAIProvider {
+    pub fn new(config: &LLMConfig) -> Result<Self, LLMError> {
+        let azure = config.azure.clone().ok_or_else(|| {
+            LLMError::ConfigError("Azure OpenAI endpoint and deployment required".to_string())
+        })?;
+        if azure.api_key.is_none() && azure.aad.is_none() {
+            return Err(LLMError::ConfigError(
+                "Azure OpenAI API key or Azure AD credentials required".to_string(),
+            ));
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .map_err(|e| LLMError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            config: azure,
+            aad_token: None,
+        })
+    }
+
+    fn chat_completions_url(&self, deployment: &str) -> String {
+        format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.config.endpoint.trim_end_matches('/'),
+            deployment,
+            self.config.api_version
+        )
+    }
+
+    /// Header authenticating a request, preferring the API key over Azure AD
+    async fn auth_header(&mut self) -> Result<(&'static str, String), LLMError> {
+        if let Some(api_key) = &self.config.api_key {
+            return Ok(("api-key", api_key.clone()));
+        }
+
+        let cached_token = self
+            .aad_token
+            .as_ref()
+            .filter(|(_, refresh_at)| Instant::now() < *refresh_at)
+            .map(|(token, _)| token.clone());
+        let token = match &self.config.aad {
+            Some(AzureAdCredentials::Token(token)) => token.clone(),
+            Some(AzureAdCredentials::ClientSecret {
+                tenant_id,
+                client_id,
+                client_secret,
+            }) => match cached_token {
+                Some(token) => token,
+                None => {
+                    let response = self
+                        .client
+                        .post(format!(
+                            "{}/{}/oauth2/v2.0/token",
+                            AZURE_AD_AUTHORITY, tenant_id
+                        ))
+                        .form(&[
+                            ("grant_type", "client_credentials"),
+                            ("client_id", client_id.as_str()),
+                            ("client_secret", client_secret.as_str()),
+                            ("scope", AZURE_COGNITIVE_SERVICES_SCOPE),
+                        ])
+                        .send()
+                        .await
+                        .map_err(|e| {
+                            LLMError::NetworkError(format!("Azure AD request failed: {}", e))
+                        })?;
+                    if !response.status().is_success() {
+                        let error_text = response
+                            .text()
+                            .await
+                            .unwrap_or_else(|_| "Unknown error".to_string());
+                        error!("Azure AD token request failed: {}", error_text);
+                        return Err(LLMError::AuthError(error_text));
+                    }
+                    let token: AzureAdToken = response.json().await.map_err(|e| {
+                        LLMError::AuthError(format!("Failed to parse Azure AD token: {}", e))
+                    })?;
+
+                    // Refresh a minute early so no request carries an expiring token
+                    let refresh_at =
+                        Instant::now() + Duration::from_secs(token.expires_in.saturating_sub(60));
+                    self.aad_token = Some((token.access_token.clone(), refresh_at));
+                    token.access_token
+                }
+            },
+            None => {
+                return Err(LLMError::ConfigError(
+                    "Azure OpenAI API key or Azure AD credentials required".to_string(),
+                ))
+            }
+        };
+        Ok(("Authorization", format!("Bearer {}", token)))
+    }
+}
+
+#[async_trait]
+impl LLMProvider for AzureOpenAIProvider {
+    async fn query(&mut self, prompt: &str, config: &LLMConfig) -> Result<LLMResponse, LLMError> {
+        let request = LLMRequest {
+            system: config.system_prompt.clone(),
+            ..LLMRequest::new(prompt)
+        };
+        self.complete(&request, config).await
+    }
+
+    async fn complete(
+        &mut self,
+        request: &LLMRequest,
+        config: &LLMConfig,
+    ) -> Result<LLMResponse, LLMError> {
+        if !request.tools.is_empty() {
+            return Err(LLMError::ConfigError(format!(
+                "Provider {} does not support tool use",
+                self.provider_name()
+            )));
+        }
+
+        let model = config.model_for(&OPENAI_MODELS, request);
+        let deployment = self.config.deployment_for(&model).to_string();
+        let request = OpenAIRequest {
+            model,
+            messages: chat_messages(request),
+            max_tokens: Some(config.max_tokens),
+            temperature: Some(config.temperature),
+        };
+        let (auth_name, auth_value) = self.auth_header().await?;
+
+        info!("Sending request to Azure OpenAI deployment {}", deployment);
+        let response = self
+            .client
+            .post(self.chat_completions_url(&deployment))
+            .header(auth_name, auth_value)
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(format!("Request failed: {}", e)))?;
 
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!("Azure OpenAI API error {}: {}", status, error_text);
+
+            return match status.as_u16() {
+                429 => Err(LLMError::RateLimit(error_text)),
+                401 | 403 => Err(LLMError::AuthError(error_text)),
+                _ => Err(LLMError::ApiError(format!("{}: {}", status, error_text))),
+            };
+        }
+
+        let azure_response: OpenAIResponse = response
+            .json()
+            .await
+            .map_err(|e| LLMError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+        let choice = azure_response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| LLMError::InvalidResponse("No choices in response".to_string()))?;
+
+        // Azure reports the deployed model (e.g. gpt-35-turbo), which its
+        // pricing table is keyed by
         Ok(LLMResponse {
-            content: content.text.clone(),
-            model: claude_response.model,
+            content: choice.message.content,
+            model: azure_response.model,
             usage: TokenUsage {
-                prompt_tokens: claude_response.usage.input_tokens,
-                completion_tokens: claude_response.usage.output_tokens,
-                total_tokens: claude_response.usage.input_tokens
-                    + claude_response.usage.output_tokens,
+                prompt_tokens: azure_response.usage.prompt_tokens,
+                completion_tokens: azure_response.usage.completion_tokens,
+                total_tokens: azure_response.usage.total_tokens,
             },
-            finish_reason: claude_response
-                .stop_reason
+            finish_reason: choice
+                .finish_reason
                 .unwrap_or_else(|| "unknown".to_string()),
             timestamp: chrono::Utc::now(),
+            tool_calls: Vec::new(),
         })
     }
 
     fn provider_name(&self) -> &str {
-        "claude"
+        "azure"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.config.endpoint.is_empty()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: true,
+            tool_use: false,
+            context_tokens: 128_000,
+        }
+    }
+}
+
+/// Google Gemini provider for the Generative Language API
+pub struct GeminiProvider {
+    client: Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl GeminiProvider {
+    pub fn new(config: &LLMConfig) -> Result<Self, LLMError> {
+        let api_key = config
+            .gemini_api_key
+            .as_ref()
+            .ok_or_else(|| LLMError::ConfigError("Gemini API key required".to_string()))?
+            .clone();
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .map_err(|e| LLMError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            api_key,
+            base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl LLMProvider for GeminiProvider {
+    async fn query(&mut self, prompt: &str, config: &LLMConfig) -> Result<LLMResponse, LLMError> {
+        let request = LLMRequest {
+            system: config.system_prompt.clone(),
+            ..LLMRequest::new(prompt)
+        };
+        self.complete(&request, config).await
+    }
+
+    async fn complete(
+        &mut self,
+        request: &LLMRequest,
+        config: &LLMConfig,
+    ) -> Result<LLMResponse, LLMError> {
+        if !request.tools.is_empty() {
+            return Err(LLMError::ConfigError(format!(
+                "Provider {} does not support tool use",
+                self.provider_name()
+            )));
+        }
+
+        let model = config.model_for(&GEMINI_MODELS, request);
+        let gemini_request = GeminiRequest {
+            contents: request
+                .history
+                .iter()
+                .map(|turn| GeminiContent {
+                    // Gemini calls the assistant "model"
+                    role: Some(
+                        match turn.role {
+                            ChatRole::User => "user",
+                            ChatRole::Assistant => "model",
+                        }
+                        .to_string(),
+                    ),
+                    parts: vec![GeminiPart::text(&turn.content)],
+                })
+                .chain(std::iter::once(GeminiContent {
+                    role: Some("user".to_string()),
+                    parts: request
+                        .images
+                        .iter()
+                        .map(GeminiPart::image)
+                        .chain(std::iter::once(GeminiPart::text(&request.prompt)))
+                        .collect(),
+                }))
+                .collect(),
+            system_instruction: request.system.as_ref().map(|system| GeminiContent {
+                role: None,
+                parts: vec![GeminiPart::text(system)],
+            }),
+            generation_config: GeminiGenerationConfig {
+                temperature: config.temperature,
+                max_output_tokens: config.max_tokens,
+            },
+        };
+
+        info!("Sending request to Gemini API ({})", model);
+        let response = self
+            .client
+            .post(format!(
+                "{}/models/{}:generateContent",
+                self.base_url, model
+            ))
+            .header("x-goog-api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .json(&gemini_request)
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(format!("Request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!("Gemini API error {}: {}", status, error_text);
+
+            return match status.as_u16() {
+                429 => Err(LLMError::RateLimit(error_text)),
+                401 | 403 => Err(LLMError::AuthError(error_text)),
+                _ => Err(LLMError::ApiError(format!("{}: {}", status, error_text))),
+            };
+        }
+
+        let gemini_response: GeminiResponse = response
+            .json()
+            .await
+            .map_err(|e| LLMError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+        gemini_response.into_llm_response(&model)
+    }
+
+    fn provider_name(&self) -> &str {
+        "gemini"
     }
 
     fn is_available(&self) -> bool {
         !self.api_key.is_empty()
     }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: true,
+            tool_use: false,
+            context_tokens: 1_000_000,
+        }
+    }
+}
+
+/// Chat messages for OpenAI-compatible APIs, with images as `image_url` parts of
+/// the user message
+fn chat_messages(request: &LLMRequest) -> Vec<OpenAIMessage> {
+    let mut messages = Vec::new();
+    if let Some(system) = &request.system {
+        messages.push(OpenAIMessage {
+            role: "system".to_string(),
+            content: OpenAIContent::Text(system.clone()),
+        });
+    }
+    for turn in &request.history {
+        messages.push(OpenAIMessage {
+            role: turn.role.as_str().to_string(),
+            content: OpenAIContent::Text(turn.content.clone()),
+        });
+    }
+    let content = if request.images.is_empty() {
+        OpenAIContent::Text(request.prompt.clone())
+    } else {
+        OpenAIContent::Parts(
+            std::iter::once(serde_json::json!({ "type": "text", "text": request.prompt }))
+                .chain(request.images.iter().map(|image| {
+                    serde_json::json!({
+                        "type": "image_url",
+                        "image_url": { "url": image.data_url() }
+                    })
+                }))
+                .collect(),
+        )
+    };
+    messages.push(OpenAIMessage {
+        role: "user".to_string(),
+        content,
+    });
+    messages
+}
+
+/// Content of the Claude user message; images go before the text, as Anthropic
+/// recommends
+fn claude_user_content(request: &LLMRequest) -> serde_json::Value {
+    if request.images.is_empty() {
+        return serde_json::Value::String(request.prompt.clone());
+    }
+    request
+        .images
+        .iter()
+        .map(|image| {
+            serde_json::json!({
+                "type": "image",
+                "source": {
+                    "type": "base64",
+                    "media_type": image.media_type,
+                    "data": image.data
+                }
+            })
+        })
+        .chain(std::iter::once(
+            serde_json::json!({ "type": "text", "text": request.prompt }),
+        ))
+        .collect()
+}
+
+/// Local model served by Ollama or a llama.cpp server, for running fully offline.
+///
+/// Local models see no images, call no tools and have small context windows;
+/// requests needing more are routed to another provider by the model selection
+/// strategy.
+pub struct LocalProvider {
+    client: Client,
+    config: LocalModelConfig,
+}
+
+impl LocalProvider {
+    pub fn new(config: &LLMConfig) -> Result<Self, LLMError> {
+        // Local models on CPU can take minutes for a long plan
+        let client = Client::builder()
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| LLMError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            config: config.local.clone(),
+        })
+    }
+
+    async fn post<T: Serialize + Sync>(
+        &self,
+        path: &str,
+        body: &T,
+    ) -> Result<reqwest::Response, LLMError> {
+        let base_url = self.config.base_url.trim_end_matches('/');
+        let response = self
+            .client
+            .post(format!("{}{}", base_url, path))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| {
+                LLMError::NetworkError(format!(
+                    "Local model server at {} unreachable: {}",
+                    base_url, e
+                ))
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!("Local model error {}: {}", status, error_text);
+            return Err(LLMError::ApiError(format!("{}: {}", status, error_text)));
+        }
+        Ok(response)
+    }
 }
 
+#[async_trait]
+impl LLMProvider for LocalProvider {
+    async fn query(&mut self, prompt: &str, config: &LLMConfig) -> Result<LLMResponse, LLMError> {
+        let messages = chat_messages(&LLMRequest {
+            system: config.system_prompt.clone(),
+            ..LLMRequest::new(prompt)
+        });
+        let model = config
+            .model
+            .clone()
+            .unwrap_or_else(|| self.config.model.clone());
+
+        info!("Sending request to local model {}", model);
+        match self.config.api {
+            LocalModelApi::Ollama => {
+                let request = OllamaRequest {
+                    model: &model,
+                    messages,
+                    stream: false,
+                    options: OllamaOptions {
+                        temperature: config.temperature,
+                        num_ctx: self.config.context_tokens,
+                        num_predict: config.max_tokens,
+                    },
+                };
+                let response: OllamaResponse = self
+                    .post("/api/chat", &request)
+                    .await?
+                    .json()
+                    .await
+                    .map_err(|e| {
+                        LLMError::InvalidResponse(format!("Failed to parse response: {}", e))
+                    })?;
+                Ok(response.into_llm_response())
+            }
+            LocalModelApi::LlamaCpp => {
+                let request = OpenAIRequest {
+                    model: model.clone(),
+                    messages,
+                    max_tokens: Some(config.max_tokens),
+                    temperature: Some(config.temperature),
+                };
+                let response: OpenAIResponse = self
+                    .post("/v1/chat/completions", &request)
+                    .await?
+                    .json()
+                    .await
+                    .map_err(|e| {
+                        LLMError::InvalidResponse(format!("Failed to parse response: {}", e))
+                    })?;
+                let choice = response.choices.into_iter().next().ok_or_else(|| {
+                    LLMError::InvalidResponse("No choices in response".to_string())
+                })?;
+                Ok(LLMResponse {
+                    content: choice.message.content,
+                    model: format!("{}{}", LOCAL_MODEL_PREFIX, model),
+                    usage: TokenUsage {
+                        prompt_tokens: response.usage.prompt_tokens,
+                        completion_tokens: response.usage.completion_tokens,
+                        total_tokens: response.usage.total_tokens,
+                    },
+                    finish_reason: choice
+                        .finish_reason
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    timestamp: chrono::Utc::now(),
+                    tool_calls: Vec::new(),
+                })
+            }
+        }
+    }
+
+    fn provider_name(&self) -> &str {
+        "local"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.config.base_url.is_empty()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: false,
+            tool_use: false,
+            context_tokens: self.config.context_tokens,
+        }
+    }
+}
+
+/// Prefix of the model names local responses report, priced at zero
+pub const LOCAL_MODEL_PREFIX: &str = "local/";
+
 /// Mock provider for testing
 pub struct MockProvider {
     responses: Vec<String>,
@@ -276,6 +938,7 @@ impl LLMProvider for MockProvider {
             },
             finish_reason: "stop".to_string(),
             timestamp: chrono::Utc::now(),
+            tool_calls: Vec::new(),
         })
     }
 
@@ -286,6 +949,14 @@ impl LLMProvider for MockProvider {
     fn is_available(&self) -> bool {
         true
     }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: true,
+            tool_use: false,
+            context_tokens: usize::MAX,
+        }
+    }
 }
 
 // OpenAI API request/response structures
@@ -297,9 +968,21 @@ struct OpenAIRequest {
     temperature: Option<f32>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 struct OpenAIMessage {
     role: String,
+    content: OpenAIContent,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum OpenAIContent {
+    Text(String),
+    Parts(Vec<serde_json::Value>),
+}
+
+#[derive(Deserialize)]
+struct OpenAIResponseMessage {
     content: String,
 }
 
@@ -312,7 +995,7 @@ struct OpenAIResponse {
 
 #[derive(Deserialize)]
 struct OpenAIChoice {
-    message: OpenAIMessage,
+    message: OpenAIResponseMessage,
     finish_reason: Option<String>,
 }
 
@@ -323,19 +1006,75 @@ struct OpenAIUsage {
     total_tokens: u32,
 }
 
+// Ollama API request/response structures
+#[derive(Serialize)]
+struct OllamaRequest<'a> {
+    model: &'a str,
+    messages: Vec<OpenAIMessage>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Serialize)]
+struct OllamaOptions {
+    temperature: f32,
+    num_ctx: usize,
+    num_predict: u32,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    model: String,
+    message: OpenAIResponseMessage,
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
+}
+
+impl OllamaResponse {
+    fn into_llm_response(self) -> LLMResponse {
+        LLMResponse {
+            content: self.message.content,
+            model: format!("{}{}", LOCAL_MODEL_PREFIX, self.model),
+            usage: TokenUsage {
+                prompt_tokens: self.prompt_eval_count,
+                completion_tokens: self.eval_count,
+                total_tokens: self.prompt_eval_count + self.eval_count,
+            },
+            finish_reason: self.done_reason.unwrap_or_else(|| "stop".to_string()),
+            timestamp: chrono::Utc::now(),
+            tool_calls: Vec::new(),
+        }
+    }
+}
+
+// Azure AD token response
+#[derive(Deserialize)]
+struct AzureAdToken {
+    access_token: String,
+    expires_in: u64,
+}
+
 // Claude API request/response structures
 #[derive(Serialize)]
-struct ClaudeRequest {
+struct ClaudeRequest<'a> {
     model: String,
     max_tokens: u32,
     temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
     messages: Vec<ClaudeMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [ToolDefinition]>,
 }
 
 #[derive(Serialize)]
 struct ClaudeMessage {
     role: String,
-    content: String,
+    /// Text, or content blocks when images are attached
+    content: serde_json::Value,
 }
 
 #[derive(Deserialize)]
@@ -347,8 +1086,18 @@ struct ClaudeResponse {
 }
 
 #[derive(Deserialize)]
-struct ClaudeContent {
-    text: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClaudeContent {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Deserialize)]
@@ -357,6 +1106,167 @@ struct ClaudeUsage {
     output_tokens: u32,
 }
 
+impl ClaudeResponse {
+    fn into_llm_response(self) -> Result<LLMResponse, LLMError> {
+        let mut text = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in self.content {
+            match block {
+                ClaudeContent::Text { text: t } => text.push(t),
+                ClaudeContent::ToolUse { id, name, input } => {
+                    tool_calls.push(ToolCall { id, name, input })
+                }
+                ClaudeContent::Other => {}
+            }
+        }
+        if text.is_empty() && tool_calls.is_empty() {
+            return Err(LLMError::InvalidResponse(
+                "No content in response".to_string(),
+            ));
+        }
+
+        Ok(LLMResponse {
+            content: text.join("\n"),
+            model: self.model,
+            usage: TokenUsage {
+                prompt_tokens: self.usage.input_tokens,
+                completion_tokens: self.usage.output_tokens,
+                total_tokens: self.usage.input_tokens + self.usage.output_tokens,
+            },
+            finish_reason: self.stop_reason.unwrap_or_else(|| "unknown".to_string()),
+            timestamp: chrono::Utc::now(),
+            tool_calls,
+        })
+    }
+}
+
+// Gemini API request/response structures
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    generation_config: GeminiGenerationConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GeminiContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GeminiPart {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    text: String,
+    #[serde(
+        default,
+        rename = "inlineData",
+        skip_serializing_if = "Option::is_none"
+    )]
+    inline_data: Option<GeminiInlineData>,
+}
+
+impl GeminiPart {
+    fn text(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            inline_data: None,
+        }
+    }
+
+    fn image(image: &ImageInput) -> Self {
+        Self {
+            text: String::new(),
+            inline_data: Some(GeminiInlineData {
+                mime_type: image.media_type.clone(),
+                data: image.data.clone(),
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiInlineData {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    temperature: f32,
+    max_output_tokens: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    usage_metadata: GeminiUsage,
+    model_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    /// Missing when the candidate was blocked
+    content: Option<GeminiContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
+}
+
+impl GeminiResponse {
+    fn into_llm_response(self, model: &str) -> Result<LLMResponse, LLMError> {
+        let candidate =
+            self.candidates.into_iter().next().ok_or_else(|| {
+                LLMError::InvalidResponse("No candidates in response".to_string())
+            })?;
+        let finish_reason = candidate
+            .finish_reason
+            .unwrap_or_else(|| "unknown".to_string());
+        let content: String = candidate
+            .content
+            .map(|content| content.parts.into_iter().map(|part| part.text).collect())
+            .unwrap_or_default();
+        if content.is_empty() {
+            return Err(LLMError::InvalidResponse(format!(
+                "No content in response (finish reason: {})",
+                finish_reason
+            )));
+        }
+
+        Ok(LLMResponse {
+            content,
+            model: self.model_version.unwrap_or_else(|| model.to_string()),
+            usage: TokenUsage {
+                prompt_tokens: self.usage_metadata.prompt_token_count,
+                completion_tokens: self.usage_metadata.candidates_token_count,
+                total_tokens: self.usage_metadata.total_token_count,
+            },
+            finish_reason,
+            timestamp: chrono::Utc::now(),
+            tool_calls: Vec::new(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,4 +1295,164 @@ mod tests {
         let response3 = provider.query("test prompt", &config).await.unwrap();
         assert_eq!(response3.content, "Response 1");
     }
+
+    #[test]
+    fn test_claude_tool_use_response() {
+        let response: ClaudeResponse = serde_json::from_value(serde_json::json!({
+            "model": "claude-sonnet-4-20250514",
+            "content": [
+                { "type": "text", "text": "Clicking the login button." },
+                {
+                    "type": "tool_use",
+                    "id": "toolu_01",
+                    "name": "click",
+                    "input": { "selector": "#login" }
+                }
+            ],
+            "usage": { "input_tokens": 120, "output_tokens": 40 },
+            "stop_reason": "tool_use"
+        }))
+        .unwrap();
+
+        let response = response.into_llm_response().unwrap();
+        assert_eq!(response.content, "Clicking the login button.");
+        assert_eq!(response.finish_reason, "tool_use");
+        assert_eq!(response.usage.total_tokens, 160);
+        assert_eq!(response.tool_calls[0].name, "click");
+        assert_eq!(response.tool_calls[0].input["selector"], "#login");
+    }
+
+    #[tokio::test]
+    async fn test_tools_need_native_support() {
+        let mut provider = MockProvider::new();
+        let mut request = LLMRequest::new("Log in");
+        request.tools.push(ToolDefinition {
+            name: "click".to_string(),
+            description: "Click an element".to_string(),
+            input_schema: serde_json::json!({ "type": "object" }),
+        });
+        let config = LLMConfig::default();
+        assert!(provider.complete(&request, &config).await.is_err());
+
+        request.tools.clear();
+        request.system = Some("You automate browsers.".to_string());
+        assert!(provider.complete(&request, &config).await.is_ok());
+        assert_eq!(canonical_provider("anthropic"), "claude");
+    }
+
+    #[tokio::test]
+    async fn test_images_use_each_api_format() {
+        let request = LLMRequest::new("Which button is highlighted?")
+            .with_image(ImageInput::png(&[0x89, b'P', b'N', b'G']));
+        assert!(request.requires_vision);
+
+        let messages = serde_json::to_value(chat_messages(&request)).unwrap();
+        let parts = &messages[0]["content"];
+        assert_eq!(parts[0]["text"], "Which button is highlighted?");
+        assert_eq!(
+            parts[1]["image_url"]["url"],
+            "data:image/png;base64,iVBORw=="
+        );
+
+        let claude = claude_user_content(&request);
+        assert_eq!(claude[0]["source"]["media_type"], "image/png");
+        assert_eq!(claude[1]["type"], "text");
+
+        let gemini = serde_json::to_value(GeminiPart::image(&request.images[0])).unwrap();
+        assert_eq!(
+            gemini,
+            serde_json::json!({ "inlineData": { "mimeType": "image/png", "data": "iVBORw==" } })
+        );
+
+        let mut provider = MockProvider::new();
+        let error = provider
+            .complete(&request, &LLMConfig::default())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("image input"));
+    }
+
+    #[test]
+    fn test_gemini_response_uses_the_served_model_version() {
+        let body = serde_json::json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "Click " }, { "text": "Login" }] },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": { "promptTokenCount": 12, "candidatesTokenCount": 3, "totalTokenCount": 15 },
+            "modelVersion": "gemini-1.5-pro-002"
+        });
+        let response: GeminiResponse = serde_json::from_value(body).unwrap();
+        let response = response.into_llm_response("gemini-1.5-pro").unwrap();
+        assert_eq!(response.content, "Click Login");
+        assert_eq!(response.model, "gemini-1.5-pro-002");
+        assert_eq!(response.usage.total_tokens, 15);
+
+        let blocked: GeminiResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{ "finishReason": "SAFETY" }]
+        }))
+        .unwrap();
+        let error = blocked.into_llm_response("gemini-1.5-pro").unwrap_err();
+        assert!(error.to_string().contains("SAFETY"));
+    }
+
+    #[tokio::test]
+    async fn test_azure_prefers_api_key_over_azure_ad() {
+        let mut azure = AzureOpenAIConfig {
+            endpoint: "https://example.openai.azure.com/".to_string(),
+            api_version: AzureOpenAIConfig::DEFAULT_API_VERSION.to_string(),
+            deployment: "chat".to_string(),
+            deployments: Default::default(),
+            api_key: None,
+            aad: Some(AzureAdCredentials::Token("aad-token".to_string())),
+        };
+        let config = LLMConfig {
+            azure: Some(azure.clone()),
+            ..LLMConfig::default()
+        };
+        let mut provider = AzureOpenAIProvider::new(&config).unwrap();
+        assert_eq!(
+            provider.chat_completions_url("chat"),
+            "https://example.openai.azure.com/openai/deployments/chat/chat/completions?api-version=2024-06-01"
+        );
+        let (name, value) = provider.auth_header().await.unwrap();
+        assert_eq!(
+            (name, value.as_str()),
+            ("Authorization", "Bearer aad-token")
+        );
+
+        azure.api_key = Some("key".to_string());
+        provider.config = azure.clone();
+        assert_eq!(provider.auth_header().await.unwrap().0, "api-key");
+
+        azure.api_key = None;
+        azure.aad = None;
+        let config = LLMConfig {
+            azure: Some(azure),
+            ..LLMConfig::default()
+        };
+        assert!(AzureOpenAIProvider::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_ollama_response_is_free_local_usage() {
+        let response: OllamaResponse = serde_json::from_value(serde_json::json!({
+            "model": "llama3.1:8b",
+            "message": { "role": "assistant", "content": "{\"steps\": []}" },
+            "done_reason": "stop",
+            "prompt_eval_count": 512,
+            "eval_count": 64
+        }))
+        .unwrap();
+
+        let response = response.into_llm_response();
+        assert_eq!(response.model, "local/llama3.1:8b");
+        assert_eq!(response.usage.total_tokens, 576);
+        let tracker = crate::llm::CostTracker::new();
+        assert_eq!(tracker.calculate_cost(&response.model, 512, 64), 0.0);
+
+        let provider = LocalProvider::new(&LLMConfig::default()).unwrap();
+        assert!(!provider.capabilities().vision);
+        assert_eq!(canonical_provider("ollama"), "local");
+    }
 }
@@ -0,0 +1,199 @@
+// Token Budgets
+// Enforces per-request and per-session token limits before a request reaches a
+// provider, and fits conversation history into a model's context window
+
+use serde::{Deserialize, Serialize};
+
+use super::model_selection::estimate_tokens;
+use super::{ChatRole, ChatTurn, LLMError, LLMRequest};
+
+/// Requests that could only be granted fewer completion tokens than this are
+/// rejected rather than downgraded
+pub const MIN_COMPLETION_TOKENS: u32 = 256;
+
+/// Characters of each dropped turn kept in the history summary
+const SUMMARY_SNIPPET_CHARS: usize = 120;
+
+/// Hard token limits; unset limits are not enforced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBudget {
+    /// Prompt and completion tokens a single request may use
+    #[serde(default)]
+    pub per_request: Option<u32>,
+    /// Tokens an `LLMService` may use over its lifetime
+    #[serde(default)]
+    pub per_session: Option<u64>,
+}
+
+impl TokenBudget {
+    /// Budget from `RAINBOW_LLM_REQUEST_TOKEN_BUDGET` and
+    /// `RAINBOW_LLM_SESSION_TOKEN_BUDGET`
+    pub fn from_env() -> Self {
+        Self {
+            per_request: std::env::var("RAINBOW_LLM_REQUEST_TOKEN_BUDGET")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            per_session: std::env::var("RAINBOW_LLM_SESSION_TOKEN_BUDGET")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// Completion tokens to grant a request of `prompt_tokens` asking for
+    /// `max_tokens`, after `session_used` tokens this session. Fewer than asked
+    /// when only a shorter completion fits; an error when not even
+    /// `MIN_COMPLETION_TOKENS` (or `max_tokens`, if smaller) would.
+    pub fn admit(
+        &self,
+        prompt_tokens: usize,
+        max_tokens: u32,
+        session_used: u64,
+    ) -> Result<u32, LLMError> {
+        let limits = [
+            ("request", self.per_request.map(u64::from)),
+            (
+                "session",
+                self.per_session
+                    .map(|budget| budget.saturating_sub(session_used)),
+            ),
+        ];
+        let needed = u64::from(MIN_COMPLETION_TOKENS.min(max_tokens));
+
+        let mut granted = u64::from(max_tokens);
+        for (scope, remaining) in limits {
+            let Some(remaining) = remaining else {
+                continue;
+            };
+            let available = remaining.saturating_sub(prompt_tokens as u64);
+            if available < needed {
+                return Err(LLMError::BudgetExceeded(format!(
+                    "~{} prompt tokens leave {} of the remaining {} token {} budget; at least {} needed",
+                    prompt_tokens, available, remaining, scope, needed
+                )));
+            }
+            granted = granted.min(available);
+        }
+        Ok(granted as u32)
+    }
+}
+
+/// `request` with its oldest history turns summarized, or dropped, until it fits
+/// `context_tokens` with room for `max_tokens` of completion. Requests that do
+/// not fit even without history are returned with none.
+pub fn fit_to_context(request: &LLMRequest, context_tokens: usize, max_tokens: u32) -> LLMRequest {
+    let fits =
+        |candidate: &LLMRequest| estimate_tokens(candidate) + max_tokens as usize <= context_tokens;
+    if fits(request) {
+        return request.clone();
+    }
+
+    let history = &request.history;
+    for dropped in 1..=history.len() {
+        let kept = &history[dropped..];
+        let summary = summarize(&history[..dropped]);
+        for candidate_history in [
+            std::iter::once(summary)
+                .chain(kept.iter().cloned())
+                .collect(),
+            kept.to_vec(),
+        ] {
+            let candidate = LLMRequest {
+                history: candidate_history,
+                ..request.clone()
+            };
+            if fits(&candidate) {
+                return candidate;
+            }
+        }
+    }
+
+    LLMRequest {
+        history: Vec::new(),
+        ..request.clone()
+    }
+}
+
+/// One turn standing in for `turns`, keeping the start of each
+fn summarize(turns: &[ChatTurn]) -> ChatTurn {
+    let lines: Vec<String> = turns
+        .iter()
+        .map(|turn| {
+            let snippet: String = turn.content.chars().take(SUMMARY_SNIPPET_CHARS).collect();
+            let ellipsis = if snippet.len() < turn.content.len() {
+                "…"
+            } else {
+                ""
+            };
+            format!("{}: {}{}", turn.role.as_str(), snippet, ellipsis)
+        })
+        .collect();
+
+    ChatTurn {
+        role: ChatRole::User,
+        content: format!(
+            "Summary of {} earlier messages:\n{}",
+            turns.len(),
+            lines.join("\n")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: ChatRole, content: &str) -> ChatTurn {
+        ChatTurn {
+            role,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn budgets_downgrade_then_reject() {
+        let budget = TokenBudget {
+            per_request: Some(2_000),
+            per_session: Some(10_000),
+        };
+        assert_eq!(budget.admit(500, 1_000, 0).unwrap(), 1_000);
+        // Only 1500 of the request budget remain for the completion
+        assert_eq!(budget.admit(500, 4_000, 0).unwrap(), 1_500);
+        // The session has 1000 tokens left
+        assert_eq!(budget.admit(500, 1_000, 9_000).unwrap(), 500);
+
+        let error = budget.admit(1_900, 1_000, 0).unwrap_err();
+        assert!(matches!(error, LLMError::BudgetExceeded(_)));
+        assert!(budget.admit(500, 1_000, 9_800).is_err());
+        assert_eq!(
+            TokenBudget::default()
+                .admit(1_000_000, 4_000, u64::MAX)
+                .unwrap(),
+            4_000
+        );
+    }
+
+    #[test]
+    fn oldest_history_is_summarized_to_fit_the_context_window() {
+        let mut request = LLMRequest::new("Now click checkout");
+        request.history = vec![
+            turn(ChatRole::User, &"Find a laptop under $1000. ".repeat(40)),
+            turn(ChatRole::Assistant, &"Here are three options. ".repeat(40)),
+            turn(ChatRole::User, "Add the second one to the cart"),
+        ];
+        assert_eq!(
+            fit_to_context(&request, 100_000, 1_000).history,
+            request.history
+        );
+
+        let fitted = fit_to_context(&request, 700, 400);
+        assert!(estimate_tokens(&fitted) + 400 <= 700);
+        assert_eq!(fitted.history.last(), request.history.last());
+        assert!(fitted.history[0].content.starts_with("Summary of"));
+
+        // Room for the prompt alone
+        let prompt_only = estimate_tokens(&LLMRequest::new("Now click checkout"));
+        let fitted = fit_to_context(&request, prompt_only + 400, 400);
+        assert!(fitted.history.is_empty());
+        assert_eq!(fitted.prompt, request.prompt);
+    }
+}
@@ -33,6 +33,11 @@ enum Commands {
         /// Enable headless mode
         #[arg(long)]
         headless: bool,
+
+        /// Attach to running browsers instead of launching Chromium (repeatable,
+        /// ws:// or http://host:port). Defaults to RAINBOW_CDP_ENDPOINTS.
+        #[arg(long = "cdp-endpoint")]
+        cdp_endpoints: Vec<String>,
     },
 
     /// Navigate to a URL and take a screenshot
@@ -78,8 +83,12 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Serve { port, headless } => {
-            serve_api(port, headless).await?;
+        Commands::Serve {
+            port,
+            headless,
+            cdp_endpoints,
+        } => {
+            serve_api(port, headless, cdp_endpoints).await?;
         }
         Commands::Navigate {
             url,
@@ -109,17 +118,32 @@ fn init_logging() {
         .init();
 }
 
-async fn serve_api(port: u16, headless: bool) -> Result<()> {
+async fn serve_api(port: u16, headless: bool, mut cdp_endpoints: Vec<String>) -> Result<()> {
     info!("Starting API server on port {}", port);
-    info!(
-        "Browser mode: {}",
-        if headless { "headless" } else { "headed" }
-    );
+
+    if cdp_endpoints.is_empty() {
+        if let Ok(env) = std::env::var("RAINBOW_CDP_ENDPOINTS") {
+            cdp_endpoints = env
+                .split(',')
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty())
+                .collect();
+        }
+    }
 
     // Initialize browser pool with headless mode (3 browsers max to prevent excessive windows)
     // Do not preload browsers at startup so the API can come up even if
     // Chromium/headless deps are not available yet. Browsers will be created lazily.
-    let pool = browser::pool::BrowserPool::new_with_headless(3, headless)?;
+    let pool = if cdp_endpoints.is_empty() {
+        info!(
+            "Browser mode: {}",
+            if headless { "headless" } else { "headed" }
+        );
+        browser::pool::BrowserPool::new_with_headless(3, headless)?
+    } else {
+        info!("Browser mode: remote ({} endpoints)", cdp_endpoints.len());
+        browser::pool::BrowserPool::with_remote_endpoints(3, cdp_endpoints)?
+    };
 
     // Start API server
     api::serve(port, pool).await?;
@@ -152,7 +176,10 @@ async fn navigate_and_screenshot(
         let screenshot_data = browser
             .screenshot(browser::ScreenshotOptions::default())
             .await?;
-        std::fs::write(&path, screenshot_data)?;
+        // Write then rename so an interrupted run never leaves a truncated image
+        let partial = format!("{}.partial", path);
+        std::fs::write(&partial, screenshot_data)?;
+        std::fs::rename(&partial, &path)?;
         info!("Screenshot saved to: {}", path);
     }
 
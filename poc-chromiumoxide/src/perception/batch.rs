@@ -0,0 +1,302 @@
+// Batch Perception - Perceive a list of URLs in parallel across the browser pool
+// Each URL is loaded in its own pooled browser with Quick or Standard perception,
+// and the results are consolidated into one report for comparison shopping and
+// competitive research workflows
+
+use super::layered_perception::{LayeredPerception, PerceptionMode, PerceptionResult};
+use super::page_classifier::{PageClassifierChain, PageSignals};
+use super::PageType;
+use crate::browser::pool::BrowserPool;
+use anyhow::Result;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Largest number of URLs accepted in one batch
+pub const MAX_BATCH_URLS: usize = 50;
+
+/// Characters of visible text kept per page
+const TEXT_EXCERPT_CHARS: usize = 300;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchMode {
+    #[default]
+    Quick,
+    Standard,
+}
+
+impl BatchMode {
+    fn perception_mode(self) -> PerceptionMode {
+        match self {
+            BatchMode::Quick => PerceptionMode::Quick,
+            BatchMode::Standard => PerceptionMode::Standard,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub mode: BatchMode,
+    /// Pages perceived at once; the pool's size limits this further
+    pub concurrency: usize,
+    /// Budget for loading and perceiving one page, including waiting for a browser
+    pub page_timeout: Duration,
+    /// Attach each page's full perception result to its report
+    pub include_perception: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            mode: BatchMode::Quick,
+            concurrency: 3,
+            page_timeout: Duration::from_secs(30),
+            include_perception: false,
+        }
+    }
+}
+
+/// The parts of a perception result useful for comparing pages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSummary {
+    pub title: String,
+    pub clickable_count: usize,
+    pub input_count: usize,
+    pub link_count: usize,
+    pub form_count: usize,
+    pub headings: Vec<String>,
+    pub text_excerpt: String,
+}
+
+/// Outcome for one URL of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPageReport {
+    pub url: String,
+    /// Where the page ended up after redirects
+    pub final_url: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub page_type: Option<PageType>,
+    pub page_type_confidence: Option<f32>,
+    pub summary: Option<PageSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perception: Option<serde_json::Value>,
+    pub duration_ms: u64,
+}
+
+impl BatchPageReport {
+    fn failed(url: &str, error: String, duration_ms: u64) -> Self {
+        Self {
+            url: url.to_string(),
+            final_url: None,
+            success: false,
+            error: Some(error),
+            page_type: None,
+            page_type_confidence: None,
+            summary: None,
+            perception: None,
+            duration_ms,
+        }
+    }
+}
+
+/// Consolidated result of a batch, pages in request order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub mode: BatchMode,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    /// How many pages were classified as each page type
+    pub page_types: BTreeMap<String, usize>,
+    pub pages: Vec<BatchPageReport>,
+}
+
+impl BatchReport {
+    fn from_pages(mode: BatchMode, pages: Vec<BatchPageReport>, duration: Duration) -> Self {
+        let succeeded = pages.iter().filter(|p| p.success).count();
+        let mut page_types = BTreeMap::new();
+        for page_type in pages.iter().filter_map(|p| p.page_type.as_ref()) {
+            *page_types.entry(format!("{:?}", page_type)).or_insert(0) += 1;
+        }
+        Self {
+            mode,
+            total: pages.len(),
+            succeeded,
+            failed: pages.len() - succeeded,
+            duration_ms: duration.as_millis() as u64,
+            page_types,
+            pages,
+        }
+    }
+}
+
+/// Perceive every URL with a browser of its own from `pool`, at most
+/// `options.concurrency` at a time. Failures are reported per page.
+pub async fn perceive_urls(
+    pool: &BrowserPool,
+    urls: &[String],
+    options: &BatchOptions,
+) -> BatchReport {
+    let start = Instant::now();
+    info!(
+        "Batch perception of {} URLs ({:?}, {} at a time)",
+        urls.len(),
+        options.mode,
+        options.concurrency
+    );
+
+    let mut pages: Vec<(usize, BatchPageReport)> = futures::stream::iter(urls.iter().cloned().enumerate())
+        .map(|(index, url)| async move { (index, perceive_one(pool, &url, options).await) })
+        .buffer_unordered(options.concurrency.max(1))
+        .collect()
+        .await;
+    pages.sort_by_key(|(index, _)| *index);
+
+    BatchReport::from_pages(
+        options.mode,
+        pages.into_iter().map(|(_, page)| page).collect(),
+        start.elapsed(),
+    )
+}
+
+async fn perceive_one(pool: &BrowserPool, url: &str, options: &BatchOptions) -> BatchPageReport {
+    let start = Instant::now();
+    let elapsed = || start.elapsed().as_millis() as u64;
+
+    let outcome = tokio::time::timeout(options.page_timeout, async {
+        // The guard returns the browser to the pool when this block ends
+        let guard = pool.acquire().await?;
+        let browser = guard.browser_arc();
+        browser.navigate_to(url).await?;
+
+        let perception = LayeredPerception::new(browser.clone())
+            .perceive(options.mode.perception_mode())
+            .await?;
+        let classification = match PageSignals::collect(&browser).await {
+            Ok(signals) => Some(PageClassifierChain::default().classify(&signals).await),
+            Err(e) => {
+                debug!("Page signals unavailable for {}: {}", url, e);
+                None
+            }
+        };
+        let final_url = browser.current_url().await.ok();
+        Ok::<_, anyhow::Error>((perception, classification, final_url))
+    })
+    .await;
+
+    match outcome {
+        Ok(Ok((perception, classification, final_url))) => BatchPageReport {
+            url: url.to_string(),
+            final_url,
+            success: true,
+            error: None,
+            page_type: classification.as_ref().map(|c| c.page_type.clone()),
+            page_type_confidence: classification.as_ref().map(|c| c.confidence),
+            summary: Some(summarize(&perception)),
+            perception: if options.include_perception {
+                perception_json(&perception).ok()
+            } else {
+                None
+            },
+            duration_ms: elapsed(),
+        },
+        Ok(Err(e)) => BatchPageReport::failed(url, e.to_string(), elapsed()),
+        Err(_) => BatchPageReport::failed(
+            url,
+            format!("Timed out after {}ms", options.page_timeout.as_millis()),
+            elapsed(),
+        ),
+    }
+}
+
+fn summarize(result: &PerceptionResult) -> PageSummary {
+    let (lightning, quick) = match result {
+        PerceptionResult::Lightning(l) => (l, None),
+        PerceptionResult::Quick(q) => (&q.lightning, Some(q)),
+        PerceptionResult::Standard(s) => (&s.quick.lightning, Some(&s.quick)),
+        PerceptionResult::Deep(d) => (&d.standard.quick.lightning, Some(&d.standard.quick)),
+        PerceptionResult::Accessibility(a) => (&a.lightning, None),
+    };
+    let mut headings: Vec<String> = match result {
+        PerceptionResult::Standard(s) => s.semantic_structure.headings.clone(),
+        PerceptionResult::Deep(d) => d.standard.semantic_structure.headings.clone(),
+        _ => Vec::new(),
+    };
+    let mut text_excerpt = String::new();
+    if let Some(quick) = quick {
+        if headings.is_empty() {
+            headings = quick
+                .visible_text_blocks
+                .iter()
+                .filter(|b| b.is_heading)
+                .map(|b| b.content.trim().to_string())
+                .collect();
+        }
+        text_excerpt = quick
+            .visible_text_blocks
+            .iter()
+            .filter(|b| !b.is_heading)
+            .map(|b| b.content.trim())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(TEXT_EXCERPT_CHARS)
+            .collect();
+    }
+    PageSummary {
+        title: lightning.title.clone(),
+        clickable_count: lightning.clickable_count,
+        input_count: lightning.input_count,
+        link_count: lightning.link_count,
+        form_count: lightning.form_count,
+        headings,
+        text_excerpt,
+    }
+}
+
+fn perception_json(result: &PerceptionResult) -> Result<serde_json::Value> {
+    Ok(match result {
+        PerceptionResult::Lightning(l) => serde_json::to_value(l)?,
+        PerceptionResult::Quick(q) => serde_json::to_value(q)?,
+        PerceptionResult::Standard(s) => serde_json::to_value(s)?,
+        PerceptionResult::Deep(d) => serde_json::to_value(d)?,
+        PerceptionResult::Accessibility(a) => serde_json::to_value(a)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consolidates_page_outcomes() {
+        let mut product = BatchPageReport::failed("https://a.example/item", String::new(), 900);
+        product.success = true;
+        product.error = None;
+        product.page_type = Some(PageType::ProductPage);
+        let mut other = product.clone();
+        other.url = "https://b.example/item".to_string();
+        let failed = BatchPageReport::failed("https://c.example", "Timed out".into(), 30000);
+
+        let report = BatchReport::from_pages(
+            BatchMode::Standard,
+            vec![product, other, failed],
+            Duration::from_secs(31),
+        );
+        assert_eq!((report.total, report.succeeded, report.failed), (3, 2, 1));
+        assert_eq!(report.page_types.get("ProductPage"), Some(&2));
+        assert_eq!(report.pages[2].url, "https://c.example");
+    }
+
+    #[test]
+    fn batch_modes_parse_from_requests() {
+        let mode: BatchMode = serde_json::from_str("\"standard\"").unwrap();
+        assert_eq!(mode, BatchMode::Standard);
+        assert_eq!(BatchMode::default(), BatchMode::Quick);
+    }
+}
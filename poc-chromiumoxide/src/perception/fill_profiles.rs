@@ -0,0 +1,270 @@
+// Fill Profiles - Named auto-fill profiles for smart forms
+//
+// Profiles (personal info, shipping address, test card data) are stored as one
+// JSON file each, readable only by the current user, so a request can name a
+// profile ("work", "test-buyer") instead of sending personal data every time.
+// Only well-known payment test cards are accepted; real card numbers never
+// reach the disk.
+
+use super::smart_forms::UserProfile;
+use crate::browser::profiles::validate_profile_name;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Card numbers payment providers publish for testing (Stripe, Braintree, Adyen)
+const TEST_CARD_NUMBERS: &[&str] = &[
+    "4242424242424242",
+    "4000056655665556",
+    "4111111111111111",
+    "4012888888881881",
+    "5555555555554444",
+    "2223003122003222",
+    "5200828282828210",
+    "5105105105105100",
+    "378282246310005",
+    "371449635398431",
+    "6011111111111117",
+    "3056930009020004",
+    "36227206271667",
+    "3566002020360505",
+    "6200000000000005",
+];
+
+/// What a stored profile contains, without the data itself
+#[derive(Debug, Clone, Serialize)]
+pub struct FillProfileSummary {
+    pub name: String,
+    pub has_personal_info: bool,
+    pub has_contact_info: bool,
+    pub has_address: bool,
+    /// Last digits of the test card, if the profile has one
+    pub card_last4: Option<String>,
+}
+
+impl FillProfileSummary {
+    fn of(profile: &UserProfile) -> Self {
+        Self {
+            name: profile.name.clone(),
+            has_personal_info: !profile.personal_info.first_name.is_empty()
+                || !profile.personal_info.last_name.is_empty(),
+            has_contact_info: !profile.contact_info.email.is_empty()
+                || !profile.contact_info.phone.is_empty(),
+            has_address: !profile.address_info.street_address.is_empty(),
+            card_last4: profile.payment_info.as_ref().map(|p| {
+                let digits = card_digits(&p.card_number);
+                digits[digits.len().saturating_sub(4)..].to_string()
+            }),
+        }
+    }
+}
+
+/// Named fill profiles stored as files under one root
+#[derive(Debug, Clone)]
+pub struct FillProfileStore {
+    root: PathBuf,
+}
+
+impl FillProfileStore {
+    /// Create a store using `RAINBOW_FILL_PROFILE_DIR`, falling back to
+    /// `~/.rainbow/fill-profiles`
+    pub fn new() -> Self {
+        let root = std::env::var("RAINBOW_FILL_PROFILE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                std::env::var("HOME")
+                    .map(|home| PathBuf::from(home).join(".rainbow").join("fill-profiles"))
+                    .unwrap_or_else(|_| std::env::temp_dir().join("rainbow-fill-profiles"))
+            });
+        Self::with_root(root)
+    }
+
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Store `profile` under its name, replacing any profile of that name
+    pub fn save(&self, profile: &UserProfile) -> Result<FillProfileSummary> {
+        validate_profile_name(&profile.name)?;
+        if let Some(payment) = &profile.payment_info {
+            if !is_test_card(&payment.card_number) {
+                return Err(anyhow!(
+                    "Only payment test card numbers can be stored in fill profiles"
+                ));
+            }
+        }
+
+        create_private_dir(&self.root)?;
+        let path = self.path_of(&profile.name);
+        write_private(&path, &serde_json::to_vec_pretty(profile)?)
+            .with_context(|| format!("Failed to write fill profile {}", path.display()))?;
+        info!("Saved fill profile {}", profile.name);
+        Ok(FillProfileSummary::of(profile))
+    }
+
+    /// Look up a stored profile
+    pub fn get(&self, name: &str) -> Result<Option<UserProfile>> {
+        validate_profile_name(name)?;
+        let path = self.path_of(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read(&path)?;
+        Ok(Some(serde_json::from_slice(&data)?))
+    }
+
+    /// Summaries of all stored profiles, sorted by name
+    pub fn list(&self) -> Result<Vec<FillProfileSummary>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut summaries = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            let name = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) if path.extension().is_some_and(|e| e == "json") => name.to_string(),
+                _ => continue,
+            };
+            if let Ok(Some(profile)) = self.get(&name) {
+                summaries.push(FillProfileSummary::of(&profile));
+            }
+        }
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(summaries)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        validate_profile_name(name)?;
+        let path = self.path_of(name);
+        if !path.exists() {
+            return Err(anyhow!("Fill profile not found: {}", name));
+        }
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to delete fill profile {}", name))?;
+        info!("Deleted fill profile {}", name);
+        Ok(())
+    }
+
+    fn path_of(&self, name: &str) -> PathBuf {
+        self.root.join(format!("{}.json", name))
+    }
+}
+
+impl Default for FillProfileStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn card_digits(number: &str) -> String {
+    number.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Whether `number` is one of the published payment test cards
+pub fn is_test_card(number: &str) -> bool {
+    let digits = card_digits(number);
+    TEST_CARD_NUMBERS.contains(&digits.as_str())
+}
+
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::create_dir_all(dir)?;
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // `mode` only applies to new files
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(data)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> FillProfileStore {
+        let root =
+            std::env::temp_dir().join(format!("rainbow-fill-profiles-{}", uuid::Uuid::new_v4()));
+        FillProfileStore::with_root(root)
+    }
+
+    fn profile(name: &str, card: &str) -> UserProfile {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "contact_info": { "email": "qa@example.com", "phone": "555-0100" },
+            "payment_info": {
+                "cardholder_name": "QA Buyer",
+                "card_number": card,
+                "expiry": "12/30",
+                "cvv": "123"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn stores_partial_profiles_privately() {
+        let store = temp_store();
+        let summary = store.save(&profile("work", "4242 4242 4242 4242")).unwrap();
+        assert_eq!(summary.card_last4.as_deref(), Some("4242"));
+        assert!(summary.has_contact_info && !summary.has_address);
+
+        let loaded = store.get("work").unwrap().unwrap();
+        assert_eq!(loaded.contact_info.email, "qa@example.com");
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(store.path_of("work"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        store.delete("work").unwrap();
+        assert!(store.get("work").unwrap().is_none());
+        std::fs::remove_dir_all(&store.root).ok();
+    }
+
+    #[test]
+    fn refuses_real_cards_and_unsafe_names() {
+        let store = temp_store();
+        assert!(store.save(&profile("real", "4929 1234 5678 9012")).is_err());
+        assert!(store
+            .save(&profile("../escape", "4242424242424242"))
+            .is_err());
+
+        let mut no_card = profile("home", "4242424242424242");
+        no_card.payment_info = None;
+        assert!(store.save(&no_card).is_ok());
+        std::fs::remove_dir_all(&store.root).ok();
+    }
+}
@@ -0,0 +1,368 @@
+// Form schema extraction
+//
+// Describes every form on a page (fields, labels, requirements, current values and
+// the validation the browser will apply) so forms can be filled without guessing.
+
+use crate::browser::Browser;
+use crate::perception::smart_forms::ValidationRule;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Lists forms matching `__SCOPE__` (every form when null) with their controls.
+/// Controls outside any form are reported as one extra form without a selector.
+const FORMS_SCRIPT: &str = r#"
+    (function() {
+        const scope = __SCOPE__;
+        const clean = s => (s || '').trim().replace(/\s+/g, ' ');
+        const selectorOf = el => {
+            // Attributes, since controls named "id" or "name" shadow a form's properties
+            const id = el.getAttribute('id');
+            const name = el.getAttribute('name');
+            if (id) return '#' + CSS.escape(id);
+            if (name && el.form === null && document.getElementsByName(name).length === 1) {
+                return el.tagName.toLowerCase() + '[name="' + CSS.escape(name) + '"]';
+            }
+            const parts = [];
+            for (let node = el; node && node.nodeType === 1 && node !== document.documentElement; node = node.parentElement) {
+                if (node.getAttribute('id')) { parts.unshift('#' + CSS.escape(node.getAttribute('id'))); break; }
+                let part = node.tagName.toLowerCase();
+                const siblings = node.parentElement
+                    ? Array.from(node.parentElement.children).filter(s => s.tagName === node.tagName)
+                    : [];
+                if (siblings.length > 1) part += ':nth-of-type(' + (siblings.indexOf(node) + 1) + ')';
+                parts.unshift(part);
+            }
+            return parts.join(' > ');
+        };
+        // Label text without the text of controls nested in it
+        const labelText = label => {
+            const copy = label.cloneNode(true);
+            copy.querySelectorAll('input, select, textarea').forEach(n => n.remove());
+            return clean(copy.textContent);
+        };
+        const idsText = ids => clean((ids || '').split(/\s+/)
+            .map(id => document.getElementById(id))
+            .filter(Boolean)
+            .map(n => n.textContent)
+            .join(' '));
+        const labelOf = el => {
+            const labels = el.labels ? Array.from(el.labels).map(labelText).filter(Boolean) : [];
+            if (labels.length) return labels.join(' ');
+            const labelled = idsText(el.getAttribute('aria-labelledby'));
+            if (labelled) return labelled;
+            if (el.getAttribute('aria-label')) return clean(el.getAttribute('aria-label'));
+            const prev = el.previousElementSibling;
+            if (prev && /^(LABEL|SPAN|DIV|P|STRONG|B)$/.test(prev.tagName) && clean(prev.textContent).length <= 60) {
+                return clean(prev.textContent) || null;
+            }
+            return clean(el.title) || null;
+        };
+        const visible = el => {
+            const rect = el.getBoundingClientRect();
+            const style = window.getComputedStyle(el);
+            return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+        };
+        const skipped = ['hidden', 'submit', 'button', 'reset', 'image'];
+        const typeOf = el => el.tagName === 'INPUT' ? (el.getAttribute('type') || 'text').toLowerCase() : el.tagName.toLowerCase();
+        const describe = (el, group) => {
+            const type = typeOf(el);
+            const attr = name => el.hasAttribute(name) ? el.getAttribute(name) : null;
+            const field = {
+                selector: selectorOf(el),
+                name: el.name || null,
+                id: el.id || null,
+                tag: el.tagName.toLowerCase(),
+                input_type: type,
+                label: labelOf(el),
+                placeholder: el.placeholder || null,
+                autocomplete: attr('autocomplete'),
+                required: el.required || el.getAttribute('aria-required') === 'true',
+                disabled: el.disabled,
+                readonly: !!el.readOnly,
+                visible: visible(el),
+                // Passwords are reported as filled or not, never by value
+                value: type === 'password' ? null : (el.value || null),
+                filled: !!el.value,
+                checked: null,
+                options: [],
+                validation: {
+                    pattern: attr('pattern'),
+                    min_length: el.minLength > 0 ? el.minLength : null,
+                    max_length: el.maxLength > 0 ? el.maxLength : null,
+                    min: attr('min'),
+                    max: attr('max'),
+                    step: attr('step'),
+                    message: el.validationMessage || null,
+                    description: idsText(el.getAttribute('aria-describedby')) || null
+                }
+            };
+            if (el.tagName === 'SELECT') {
+                field.options = Array.from(el.options).map(o => ({ value: o.value, label: clean(o.text), selected: o.selected }));
+                field.filled = Array.from(el.selectedOptions).some(o => o.value);
+            } else if (type === 'checkbox') {
+                field.checked = el.checked;
+                field.value = el.checked ? (el.value || 'on') : null;
+                field.filled = el.checked;
+            } else if (type === 'radio') {
+                const checked = group.find(r => r.checked);
+                const fieldset = el.closest('fieldset');
+                const legend = fieldset && fieldset.querySelector('legend');
+                field.label = (legend && clean(legend.textContent)) || field.label;
+                field.options = group.map(r => ({ value: r.value, label: labelOf(r) || r.value, selected: r.checked }));
+                field.required = group.some(r => r.required);
+                field.value = checked ? checked.value : null;
+                field.filled = !!checked;
+                field.checked = !!checked;
+            }
+            return field;
+        };
+        const fieldsOf = controls => {
+            const fields = [];
+            const radios = new Set();
+            for (const el of controls) {
+                if (!/^(INPUT|SELECT|TEXTAREA)$/.test(el.tagName) || skipped.includes(typeOf(el))) continue;
+                if (typeOf(el) === 'radio' && el.name) {
+                    if (radios.has(el.name)) continue;
+                    radios.add(el.name);
+                    fields.push(describe(el, controls.filter(r => r.type === 'radio' && r.name === el.name)));
+                } else {
+                    fields.push(describe(el, [el]));
+                }
+            }
+            return fields;
+        };
+        const submitsOf = root => Array.from(root.querySelectorAll('button[type="submit"], input[type="submit"], input[type="image"], button:not([type])'))
+            .filter(visible)
+            .map(selectorOf);
+        const roots = scope ? Array.from(document.querySelectorAll(scope)) : Array.from(document.forms);
+        const forms = roots.map(root => {
+            const isForm = root.tagName === 'FORM';
+            const action = isForm ? root.getAttribute('action') : null;
+            return {
+                selector: selectorOf(root),
+                id: root.getAttribute('id'),
+                name: root.getAttribute('name'),
+                action: action !== null ? new URL(action, document.baseURI).href : (isForm ? location.href : null),
+                method: ((isForm && root.getAttribute('method')) || 'get').toLowerCase(),
+                fields: fieldsOf(Array.from(isForm ? root.elements : root.querySelectorAll('input, select, textarea'))),
+                submit_selectors: submitsOf(root)
+            };
+        });
+        if (!scope) {
+            const orphans = Array.from(document.querySelectorAll('input, select, textarea')).filter(el => !el.form);
+            const fields = fieldsOf(orphans);
+            if (fields.length) {
+                forms.push({ selector: null, id: null, name: null, action: null, method: 'get', fields, submit_selectors: [] });
+            }
+        }
+        return forms;
+    })()
+"#;
+
+/// Forms found on a page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FormSchema {
+    pub forms: Vec<FormDescriptor>,
+}
+
+/// One form and the controls that submit with it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormDescriptor {
+    /// None for the controls placed outside any form element
+    pub selector: Option<String>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    /// Absolute submission URL
+    pub action: Option<String>,
+    pub method: String,
+    pub fields: Vec<FieldSchema>,
+    pub submit_selectors: Vec<String>,
+}
+
+/// A form control; a radio group is reported as one field with its buttons as options
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldSchema {
+    pub selector: String,
+    pub name: Option<String>,
+    pub id: Option<String>,
+    pub tag: String,
+    /// Input type, or "select" / "textarea" for those elements
+    pub input_type: String,
+    pub label: Option<String>,
+    pub placeholder: Option<String>,
+    /// Autofill token such as "email" or "postal-code"
+    pub autocomplete: Option<String>,
+    pub required: bool,
+    pub disabled: bool,
+    pub readonly: bool,
+    pub visible: bool,
+    /// Current value; never reported for password fields
+    pub value: Option<String>,
+    pub filled: bool,
+    /// Checkbox state, or whether a radio group has a selection
+    pub checked: Option<bool>,
+    pub options: Vec<FieldOption>,
+    pub validation: ValidationHints,
+}
+
+/// Choice of a select or radio group
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldOption {
+    pub value: String,
+    pub label: String,
+    pub selected: bool,
+}
+
+/// Constraints the browser checks on submit, plus hints shown to the user
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationHints {
+    pub pattern: Option<String>,
+    pub min_length: Option<u32>,
+    pub max_length: Option<u32>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub step: Option<String>,
+    /// Browser message for the current value, None while it is valid
+    pub message: Option<String>,
+    /// Help text linked with aria-describedby
+    pub description: Option<String>,
+}
+
+impl FieldSchema {
+    /// Whether a user could type into or pick a value for this field
+    pub fn is_fillable(&self) -> bool {
+        self.visible && !self.disabled && !self.readonly
+    }
+
+    /// Rule in the form smart_forms validates against, when the field has any
+    /// constraint
+    pub fn validation_rule(&self) -> Option<ValidationRule> {
+        let hints = &self.validation;
+        let mut custom_rules = Vec::new();
+        if let Some(min) = &hints.min {
+            custom_rules.push(format!("min:{}", min));
+        }
+        if let Some(max) = &hints.max {
+            custom_rules.push(format!("max:{}", max));
+        }
+        if let Some(step) = &hints.step {
+            custom_rules.push(format!("step:{}", step));
+        }
+        if matches!(self.input_type.as_str(), "email" | "url" | "number" | "tel") {
+            custom_rules.push(format!("type:{}", self.input_type));
+        }
+        if !self.required
+            && hints.pattern.is_none()
+            && hints.min_length.is_none()
+            && hints.max_length.is_none()
+            && custom_rules.is_empty()
+        {
+            return None;
+        }
+        Some(ValidationRule {
+            pattern: hints.pattern.clone(),
+            min_length: hints.min_length,
+            max_length: hints.max_length,
+            required: self.required,
+            custom_rules,
+        })
+    }
+}
+
+impl FormDescriptor {
+    /// Required fields that still have no value
+    pub fn missing_required(&self) -> Vec<&FieldSchema> {
+        self.fields
+            .iter()
+            .filter(|field| field.required && !field.filled)
+            .collect()
+    }
+
+    /// Fields whose current value fails the browser's validation
+    pub fn invalid_fields(&self) -> Vec<&FieldSchema> {
+        self.fields
+            .iter()
+            .filter(|field| field.filled && field.validation.message.is_some())
+            .collect()
+    }
+}
+
+/// Forms of the page currently open in the browser; `scope` limits them to the
+/// elements matching a selector
+pub async fn extract(browser: &Browser, scope: Option<&str>) -> Result<FormSchema> {
+    let scope = serde_json::to_string(&scope)?;
+    let result = browser
+        .execute_script(&FORMS_SCRIPT.replace("__SCOPE__", &scope))
+        .await?;
+    let forms: Vec<FormDescriptor> = serde_json::from_value(result).unwrap_or_default();
+    Ok(FormSchema { forms })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(input_type: &str, required: bool, value: Option<&str>) -> FieldSchema {
+        FieldSchema {
+            selector: format!("#{}", input_type),
+            input_type: input_type.to_string(),
+            required,
+            visible: true,
+            value: value.map(str::to_string),
+            filled: value.is_some(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_partial_field_json() {
+        let field: FieldSchema = serde_json::from_value(serde_json::json!({
+            "selector": "#email",
+            "input_type": "email",
+            "required": true,
+            "validation": { "max_length": 64, "message": null }
+        }))
+        .unwrap();
+        assert_eq!(field.validation.max_length, Some(64));
+        assert!(field.options.is_empty());
+        assert!(!field.is_fillable());
+    }
+
+    #[test]
+    fn builds_validation_rules_from_hints() {
+        assert!(field("text", false, None).validation_rule().is_none());
+
+        let mut quantity = field("number", true, None);
+        quantity.validation.min = Some("1".to_string());
+        let rule = quantity.validation_rule().unwrap();
+        assert!(rule.required);
+        assert_eq!(rule.custom_rules, vec!["min:1", "type:number"]);
+
+        let mut zip = field("text", false, None);
+        zip.validation.pattern = Some("[0-9]{5}".to_string());
+        assert_eq!(
+            zip.validation_rule().unwrap().pattern.as_deref(),
+            Some("[0-9]{5}")
+        );
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_fields() {
+        let mut email = field("email", true, Some("not-an-email"));
+        email.validation.message = Some("Please include an '@'".to_string());
+        let form = FormDescriptor {
+            fields: vec![
+                email,
+                field("password", true, None),
+                field("text", false, None),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(form.missing_required()[0].input_type, "password");
+        assert_eq!(form.invalid_fields()[0].input_type, "email");
+    }
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::info;
 
-use crate::browser::Browser;
+use crate::browser::{Browser, OptionChoice};
 use crate::perception::{ElementType, PageType, PerceivedElement, PerceptionEngine};
 
 /// Enhanced browser automation with perception capabilities
@@ -252,8 +252,16 @@ impl PerceptionAwareBrowser {
         // Find the select element
         let element = self.perception.find_element(&description).await?;
 
-        // Verify it's a select element
-        if element.element_type != ElementType::Select {
+        // Custom dropdowns are often perceived as buttons or inputs, so only rule
+        // out elements that cannot open a list of options
+        if matches!(
+            element.element_type,
+            ElementType::Link
+                | ElementType::Image
+                | ElementType::Checkbox
+                | ElementType::Radio
+                | ElementType::TextArea
+        ) {
             return Err(anyhow::anyhow!(
                 "Target element is not a dropdown: {:?}",
                 element.element_type
@@ -262,7 +270,7 @@ impl PerceptionAwareBrowser {
 
         // Select the option
         self.browser
-            .select_option(&element.selector, &value)
+            .choose_option(&element.selector, &OptionChoice::Label(value.clone()))
             .await?;
 
         // Update context
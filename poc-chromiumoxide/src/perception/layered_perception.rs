@@ -11,7 +11,7 @@ use tracing::{debug, info};
 // Note: Some CDP features may not be available in chromiumoxide 0.5
 // This is a design template - actual CDP access may need adjustment based on chromiumoxide version
 
-use crate::browser::Browser;
+use crate::browser::{AxElement, Browser, ScrollOptions};
 
 /// 四层感知架构 - Lightning/Quick/Standard/Deep
 pub struct LayeredPerception {
@@ -23,13 +23,16 @@ pub struct LayeredPerception {
 /// 感知配置
 #[derive(Debug, Clone)]
 pub struct PerceptionConfig {
-    pub lightning_timeout: Duration, // <50ms
-    pub quick_timeout: Duration,     // <200ms
-    pub standard_timeout: Duration,  // <1000ms
-    pub deep_timeout: Duration,      // <5000ms
+    pub lightning_timeout: Duration,     // <50ms
+    pub quick_timeout: Duration,         // <200ms
+    pub standard_timeout: Duration,      // <1000ms
+    pub deep_timeout: Duration,          // <5000ms
+    pub accessibility_timeout: Duration, // <2000ms
     pub enable_cache: bool,
     pub cache_ttl: Duration,
     pub max_cache_size: usize,
+    /// Deep感知前滚动加载无限滚动和懒加载内容
+    pub load_lazy_content: bool,
 }
 
 impl Default for PerceptionConfig {
@@ -39,9 +42,11 @@ impl Default for PerceptionConfig {
             quick_timeout: Duration::from_millis(200),
             standard_timeout: Duration::from_millis(1000),
             deep_timeout: Duration::from_millis(5000),
+            accessibility_timeout: Duration::from_millis(2000),
             enable_cache: true,
             cache_ttl: Duration::from_secs(30),
             max_cache_size: 1000,
+            load_lazy_content: false,
         }
     }
 }
@@ -128,6 +133,22 @@ pub struct DeepPerception {
     pub ai_insights: AiInsights,
 }
 
+/// Accessibility - 无障碍树感知层 (<2000ms)
+/// 基于CDP Accessibility树的角色、名称和状态，不依赖类名和DOM结构
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccessibilityPerception {
+    #[serde(flatten)]
+    pub lightning: LightningPerception,
+
+    /// 可交互元素（按钮、链接、输入框等），带CSS选择器
+    pub interactive_elements: Vec<AxElement>,
+    /// 页面地标区域（导航、主内容、对话框等）
+    pub landmarks: Vec<AxElement>,
+    pub headings: Vec<AxElement>,
+    /// 其他有名称的元素（图片、提示、列表项等）
+    pub named_elements: Vec<AxElement>,
+}
+
 /// 统一感知结果
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum PerceptionResult {
@@ -135,16 +156,18 @@ pub enum PerceptionResult {
     Quick(QuickPerception),
     Standard(StandardPerception),
     Deep(DeepPerception),
+    Accessibility(AccessibilityPerception),
 }
 
 /// 感知模式枚举
 #[derive(Debug, Clone, Copy)]
 pub enum PerceptionMode {
-    Lightning, // 极速模式
-    Quick,     // 快速模式
-    Standard,  // 标准模式
-    Deep,      // 深度模式
-    Adaptive,  // 自适应模式
+    Lightning,     // 极速模式
+    Quick,         // 快速模式
+    Standard,      // 标准模式
+    Deep,          // 深度模式
+    Adaptive,      // 自适应模式
+    Accessibility, // 无障碍树模式
 }
 
 impl LayeredPerception {
@@ -192,7 +215,14 @@ impl LayeredPerception {
                 .perceive_standard()
                 .await
                 .map(PerceptionResult::Standard),
-            PerceptionMode::Deep => self.perceive_deep().await.map(PerceptionResult::Deep),
+            PerceptionMode::Deep => {
+                self.load_lazy_content().await;
+                self.perceive_deep().await.map(PerceptionResult::Deep)
+            }
+            PerceptionMode::Accessibility => self
+                .perceive_accessibility()
+                .await
+                .map(PerceptionResult::Accessibility),
             PerceptionMode::Adaptive => {
                 // Inline adaptive logic to avoid recursion
                 let complexity = self.estimate_page_complexity().await?;
@@ -221,8 +251,11 @@ impl LayeredPerception {
                         .perceive_standard()
                         .await
                         .map(PerceptionResult::Standard),
-                    PerceptionMode::Deep => self.perceive_deep().await.map(PerceptionResult::Deep),
-                    PerceptionMode::Adaptive => {
+                    PerceptionMode::Deep => {
+                        self.load_lazy_content().await;
+                        self.perceive_deep().await.map(PerceptionResult::Deep)
+                    }
+                    PerceptionMode::Adaptive | PerceptionMode::Accessibility => {
                         // Fallback to Quick to prevent infinite recursion
                         self.perceive_quick().await.map(PerceptionResult::Quick)
                     }
@@ -395,6 +428,63 @@ impl LayeredPerception {
             })?
     }
 
+    /// 滚动到内容稳定，使Deep感知覆盖信息流和懒加载列表的全部内容
+    async fn load_lazy_content(&self) {
+        if !self.config.load_lazy_content {
+            return;
+        }
+        match self
+            .browser
+            .scroll_until_stable(&ScrollOptions::default())
+            .await
+        {
+            Ok(report) => debug!(
+                "Loaded lazy content before deep perception: {} scrolls, height {} -> {}",
+                report.scrolls, report.initial_height, report.final_height
+            ),
+            Err(e) => debug!("Could not load lazy content: {}", e),
+        }
+    }
+
+    /// Accessibility感知 - 基于无障碍树的元素模型 (<2000ms)
+    async fn perceive_accessibility(&self) -> Result<AccessibilityPerception> {
+        let perception_future = async {
+            let (lightning, elements) = tokio::try_join!(
+                self.perceive_lightning(),
+                self.browser.accessibility_tree(true)
+            )?;
+
+            let mut perception = AccessibilityPerception {
+                lightning,
+                interactive_elements: Vec::new(),
+                landmarks: Vec::new(),
+                headings: Vec::new(),
+                named_elements: Vec::new(),
+            };
+            for element in elements {
+                if element.is_interactive() {
+                    perception.interactive_elements.push(element);
+                } else if element.is_landmark() {
+                    perception.landmarks.push(element);
+                } else if element.role == "heading" {
+                    perception.headings.push(element);
+                } else if !element.name.is_empty() {
+                    perception.named_elements.push(element);
+                }
+            }
+            Ok(perception)
+        };
+
+        timeout(self.config.accessibility_timeout, perception_future)
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "Accessibility perception timed out after {:?}",
+                    self.config.accessibility_timeout
+                )
+            })?
+    }
+
     /// 自适应感知 - 根据场景自动选择最佳模式
     #[allow(dead_code)] // Used by adaptive mode selection
     async fn perceive_adaptive(&mut self) -> Result<PerceptionResult> {
@@ -420,6 +510,11 @@ impl LayeredPerception {
 
     async fn get_cache_key(&self) -> Result<String> {
         let url = self.browser.current_url().await?;
+        let mutations = self.browser.dom_mutations();
+        if mutations.is_active() {
+            // Any significant DOM change invalidates results for the page
+            return Ok(format!("{}:dom{}", url, mutations.version()));
+        }
         // 使用URL和时间戳创建缓存键
         Ok(format!("{}:{}", url, chrono::Utc::now().timestamp() / 30)) // 30秒缓存窗口
     }
@@ -0,0 +1,398 @@
+// Page metadata extraction
+//
+// Reads what a page says about itself in its head: title, description, canonical
+// URL, OpenGraph and Twitter cards, schema.org JSON-LD and favicons.
+
+use crate::browser::Browser;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Collects the meta tags, link tags and JSON-LD scripts of the page
+const METADATA_SOURCES_SCRIPT: &str = r#"
+    (function() {
+        const meta = Array.from(document.querySelectorAll('meta[property], meta[name], meta[itemprop]'))
+            .map(m => ({
+                key: (m.getAttribute('property') || m.getAttribute('name') || m.getAttribute('itemprop') || '').trim().toLowerCase(),
+                content: (m.getAttribute('content') || '').trim()
+            }))
+            .filter(m => m.key && m.content);
+        const links = Array.from(document.querySelectorAll('link[rel][href]')).map(l => ({
+            rel: l.getAttribute('rel').toLowerCase(),
+            href: l.href,
+            sizes: l.getAttribute('sizes'),
+            type: l.getAttribute('type'),
+            hreflang: l.getAttribute('hreflang')
+        }));
+        return {
+            url: location.href,
+            title: document.title || null,
+            language: document.documentElement.getAttribute('lang'),
+            meta: meta,
+            links: links,
+            json_ld: Array.from(document.querySelectorAll('script[type="application/ld+json"]')).map(s => s.textContent)
+        };
+    })()
+"#;
+
+/// OpenGraph object types whose properties are kept with their prefix
+const OPEN_GRAPH_NAMESPACES: &[&str] = &[
+    "article:", "product:", "profile:", "book:", "music:", "video:",
+];
+
+/// Metadata of a page, as structured JSON
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PageMetadata {
+    pub url: String,
+    /// Document title, or the OpenGraph title when the page has none
+    pub title: Option<String>,
+    /// Meta description, falling back to the OpenGraph and Twitter ones
+    pub description: Option<String>,
+    /// `link rel="canonical"`, falling back to `og:url`
+    pub canonical_url: Option<String>,
+    pub language: Option<String>,
+    pub keywords: Vec<String>,
+    pub author: Option<String>,
+    pub robots: Option<String>,
+    /// OpenGraph properties without the `og:` prefix (`title`, `image`,
+    /// `article:published_time`); repeated properties become arrays
+    pub open_graph: BTreeMap<String, Value>,
+    /// Twitter card tags without the `twitter:` prefix
+    pub twitter: BTreeMap<String, Value>,
+    /// Parsed JSON-LD blocks; blocks that aren't valid JSON are left out
+    pub json_ld: Vec<Value>,
+    /// schema.org types the JSON-LD describes, including those in `@graph`
+    pub json_ld_types: Vec<String>,
+    pub favicons: Vec<Favicon>,
+    /// Translations of the page from `link rel="alternate" hreflang`
+    pub alternates: Vec<AlternateLink>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Favicon {
+    pub href: String,
+    /// `icon`, `apple-touch-icon`, `mask-icon`, or `default` for the
+    /// `/favicon.ico` browsers fall back to
+    pub rel: String,
+    pub sizes: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlternateLink {
+    pub hreflang: String,
+    pub href: String,
+}
+
+/// Raw metadata as collected from the page
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetadataSources {
+    #[serde(default)]
+    pub url: String,
+    pub title: Option<String>,
+    pub language: Option<String>,
+    /// Meta tags in page order, keyed by lowercase property, name or itemprop
+    #[serde(default)]
+    pub meta: Vec<MetaTag>,
+    #[serde(default)]
+    pub links: Vec<LinkTag>,
+    /// Text of each `application/ld+json` script
+    #[serde(default)]
+    pub json_ld: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetaTag {
+    pub key: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LinkTag {
+    /// Lowercase, possibly several space-separated values
+    pub rel: String,
+    /// Absolute
+    pub href: String,
+    pub sizes: Option<String>,
+    #[serde(rename = "type")]
+    pub mime_type: Option<String>,
+    pub hreflang: Option<String>,
+}
+
+impl PageMetadata {
+    pub fn from_sources(sources: &MetadataSources) -> Self {
+        let meta = |key: &str| {
+            sources
+                .meta
+                .iter()
+                .find(|tag| tag.key == key)
+                .map(|tag| tag.content.clone())
+        };
+        let link = |rel: &str| {
+            sources
+                .links
+                .iter()
+                .find(|link| link.rel.split_whitespace().any(|r| r == rel))
+        };
+
+        let json_ld: Vec<Value> = sources
+            .json_ld
+            .iter()
+            .filter_map(|text| serde_json::from_str(text).ok())
+            .collect();
+        let mut json_ld_types = Vec::new();
+        for block in &json_ld {
+            collect_types(block, &mut json_ld_types);
+        }
+
+        PageMetadata {
+            url: sources.url.clone(),
+            title: non_empty(sources.title.clone()).or_else(|| meta("og:title")),
+            description: meta("description")
+                .or_else(|| meta("og:description"))
+                .or_else(|| meta("twitter:description")),
+            canonical_url: link("canonical")
+                .map(|link| link.href.clone())
+                .or_else(|| meta("og:url")),
+            language: non_empty(sources.language.clone()).or_else(|| meta("og:locale")),
+            keywords: meta("keywords")
+                .map(|keywords| {
+                    keywords
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|k| !k.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            author: meta("author"),
+            robots: meta("robots"),
+            open_graph: properties(&sources.meta, |key| {
+                key.strip_prefix("og:").or_else(|| {
+                    OPEN_GRAPH_NAMESPACES
+                        .iter()
+                        .any(|namespace| key.starts_with(namespace))
+                        .then_some(key)
+                })
+            }),
+            twitter: properties(&sources.meta, |key| key.strip_prefix("twitter:")),
+            json_ld,
+            json_ld_types,
+            favicons: favicons(sources),
+            alternates: sources
+                .links
+                .iter()
+                .filter(|link| link.rel.split_whitespace().any(|r| r == "alternate"))
+                .filter_map(|link| {
+                    Some(AlternateLink {
+                        hreflang: link.hreflang.clone()?,
+                        href: link.href.clone(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+fn non_empty(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Meta tags whose key `rename` accepts, under the name it gives; the first of
+/// a repeated key stays first in its array
+fn properties<'a>(
+    meta: &'a [MetaTag],
+    rename: impl Fn(&'a str) -> Option<&'a str>,
+) -> BTreeMap<String, Value> {
+    let mut properties: BTreeMap<String, Value> = BTreeMap::new();
+    for tag in meta {
+        let Some(name) = rename(&tag.key) else {
+            continue;
+        };
+        let content = Value::String(tag.content.clone());
+        match properties.get_mut(name) {
+            None => {
+                properties.insert(name.to_string(), content);
+            }
+            Some(Value::Array(values)) => values.push(content),
+            Some(first) => *first = Value::Array(vec![first.take(), content]),
+        }
+    }
+    properties
+}
+
+/// `@type` of `node` and everything nested in it, each once
+fn collect_types(node: &Value, types: &mut Vec<String>) {
+    match node {
+        Value::Object(object) => {
+            let own = match object.get("@type") {
+                Some(Value::String(kind)) => vec![kind.clone()],
+                Some(Value::Array(kinds)) => kinds
+                    .iter()
+                    .filter_map(|kind| kind.as_str().map(str::to_string))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for kind in own {
+                if !types.contains(&kind) {
+                    types.push(kind);
+                }
+            }
+            for value in object.values() {
+                collect_types(value, types);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_types(item, types);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Declared icons, or the `/favicon.ico` browsers request when there are none
+fn favicons(sources: &MetadataSources) -> Vec<Favicon> {
+    let mut icons: Vec<Favicon> = sources
+        .links
+        .iter()
+        .filter_map(|link| {
+            let rel = link.rel.split_whitespace().find(|rel| {
+                matches!(
+                    *rel,
+                    "icon" | "apple-touch-icon" | "apple-touch-icon-precomposed" | "mask-icon"
+                )
+            })?;
+            Some(Favicon {
+                href: link.href.clone(),
+                rel: rel.to_string(),
+                sizes: link.sizes.clone(),
+                mime_type: link.mime_type.clone(),
+            })
+        })
+        .collect();
+    if icons.is_empty() {
+        if let Ok(default) = url::Url::parse(&sources.url).and_then(|url| url.join("/favicon.ico"))
+        {
+            if matches!(default.scheme(), "http" | "https") {
+                icons.push(Favicon {
+                    href: default.to_string(),
+                    rel: "default".to_string(),
+                    sizes: None,
+                    mime_type: None,
+                });
+            }
+        }
+    }
+    icons
+}
+
+/// Metadata of the page currently open in the browser
+pub async fn extract(browser: &Browser) -> Result<PageMetadata> {
+    let result = browser.execute_script(METADATA_SOURCES_SCRIPT).await?;
+    let sources: MetadataSources = serde_json::from_value(result)?;
+    Ok(PageMetadata::from_sources(&sources))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tag(key: &str, content: &str) -> MetaTag {
+        MetaTag {
+            key: key.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn link(rel: &str, href: &str) -> LinkTag {
+        LinkTag {
+            rel: rel.to_string(),
+            href: href.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn structures_head_metadata() {
+        let json_ld = r#"{
+            "@context": "https://schema.org",
+            "@graph": [
+                {"@type": "WebSite", "name": "Example News"},
+                {"@type": ["NewsArticle", "Article"], "author": {"@type": "Person", "name": "Kim"}}
+            ]
+        }"#;
+        let sources = MetadataSources {
+            url: "https://news.example/2025/rates?utm_source=x".to_string(),
+            title: Some(" ".to_string()),
+            language: Some("en".to_string()),
+            meta: vec![
+                tag("og:title", "Rates held"),
+                tag("og:image", "https://news.example/a.jpg"),
+                tag("og:image", "https://news.example/b.jpg"),
+                tag("article:published_time", "2025-03-14T09:00:00Z"),
+                tag("twitter:card", "summary_large_image"),
+                tag("twitter:description", "The bank kept rates"),
+                tag("keywords", "rates, economy, "),
+            ],
+            links: vec![
+                link("canonical", "https://news.example/2025/rates"),
+                link("shortcut icon", "https://news.example/fav.png"),
+                LinkTag {
+                    hreflang: Some("de".to_string()),
+                    ..link("alternate", "https://news.example/de/2025/rates")
+                },
+            ],
+            json_ld: vec!["{broken".to_string(), json_ld.to_string()],
+        };
+
+        let metadata = PageMetadata::from_sources(&sources);
+        assert_eq!(metadata.title.as_deref(), Some("Rates held"));
+        assert_eq!(metadata.description.as_deref(), Some("The bank kept rates"));
+        assert_eq!(
+            metadata.canonical_url.as_deref(),
+            Some("https://news.example/2025/rates")
+        );
+        assert_eq!(metadata.keywords, ["rates", "economy"]);
+        assert_eq!(
+            metadata.open_graph["image"],
+            json!(["https://news.example/a.jpg", "https://news.example/b.jpg"])
+        );
+        assert_eq!(
+            metadata.open_graph["article:published_time"],
+            "2025-03-14T09:00:00Z"
+        );
+        assert_eq!(metadata.twitter["card"], "summary_large_image");
+        assert_eq!(metadata.json_ld.len(), 1);
+        assert_eq!(
+            metadata.json_ld_types,
+            ["WebSite", "NewsArticle", "Article", "Person"]
+        );
+        assert_eq!(metadata.favicons[0].rel, "icon");
+        assert_eq!(metadata.alternates[0].hreflang, "de");
+    }
+
+    #[test]
+    fn falls_back_to_default_favicon() {
+        let sources = MetadataSources {
+            url: "https://plain.example/docs/page".to_string(),
+            title: Some("Docs".to_string()),
+            ..Default::default()
+        };
+        let metadata = PageMetadata::from_sources(&sources);
+        assert_eq!(
+            metadata.favicons[0].href,
+            "https://plain.example/favicon.ico"
+        );
+        assert_eq!(metadata.favicons[0].rel, "default");
+        assert!(metadata.open_graph.is_empty() && metadata.canonical_url.is_none());
+
+        let blank = PageMetadata::from_sources(&MetadataSources {
+            url: "about:blank".to_string(),
+            ..Default::default()
+        });
+        assert!(blank.favicons.is_empty());
+    }
+}
@@ -18,13 +18,70 @@ pub use layered_perception::{
     PerceptionResult, QuickPerception, StandardPerception,
 };
 
-// pub mod visual; // Removed: Stub code not implemented
+pub mod batch;
 pub mod chromium_integration;
 pub mod context_aware;
+pub mod fill_profiles;
+pub mod form_schema;
 pub mod integration;
 pub mod layered_perception;
+pub mod metadata;
+pub mod ocr;
+pub mod page_classifier;
+pub mod pagination;
+pub mod product;
+pub mod schema;
+pub mod search_results;
 pub mod semantic;
 pub mod smart_forms;
+pub mod vision;
+pub mod visual;
+pub mod visual_diff;
+
+/// Rendered boxes of visible elements in document coordinates, matching the
+/// full-page screenshot taken by classify_page
+const VISUAL_BOXES_SCRIPT: &str = r#"
+    (function() {
+        const selectorOf = el => {
+            if (el.id) return '#' + CSS.escape(el.id);
+            const parts = [];
+            while (el && el.nodeType === 1 && parts.length < 5) {
+                if (el.id) { parts.unshift('#' + CSS.escape(el.id)); break; }
+                const siblings = el.parentElement
+                    ? Array.from(el.parentElement.children).filter(s => s.tagName === el.tagName)
+                    : [];
+                const tag = el.tagName.toLowerCase();
+                parts.unshift(siblings.length > 1 ? tag + ':nth-of-type(' + (siblings.indexOf(el) + 1) + ')' : tag);
+                el = el.parentElement;
+            }
+            return parts.join(' > ');
+        };
+        const query = 'a, button, input, select, textarea, img, svg, canvas, label, h1, h2, h3, [role], span, p, td, li';
+        const elements = Array.from(document.querySelectorAll(query)).slice(0, 400);
+        const boxes = [];
+        for (const el of elements) {
+            const r = el.getBoundingClientRect();
+            if (r.width < 2 || r.height < 2 || getComputedStyle(el).visibility === 'hidden') continue;
+            boxes.push({
+                selector: selectorOf(el),
+                tag: el.tagName.toLowerCase(),
+                role: el.getAttribute('role') || '',
+                text: (el.innerText || el.value || el.alt || el.getAttribute('aria-label') || '').trim().slice(0, 80),
+                x: r.x + window.scrollX,
+                y: r.y + window.scrollY,
+                width: r.width,
+                height: r.height,
+                clickable: ['a', 'button', 'input', 'select', 'textarea'].includes(el.tagName.toLowerCase()) || el.onclick !== null
+            });
+        }
+        const doc = document.documentElement;
+        return {
+            boxes,
+            width: Math.max(doc.scrollWidth, window.innerWidth),
+            height: Math.max(doc.scrollHeight, window.innerHeight)
+        };
+    })()
+"#;
 
 /// Enhanced core perception engine with layered architecture
 pub struct PerceptionEngine {
@@ -35,6 +92,11 @@ pub struct PerceptionEngine {
     // New layered perception components
     layered_perception: LayeredPerception,
     chromium_integration: Option<ChromiumIntegration>,
+    /// Tesseract, when installed and enabled, for text rendered as pixels
+    ocr: Option<ocr::OcrEngine>,
+    /// Multimodal LLM consulted when DOM heuristics find no element
+    vision: Option<vision::VisionModel>,
+    page_classifier: page_classifier::PageClassifierChain,
     config: EnhancedPerceptionConfig,
 }
 
@@ -47,6 +109,8 @@ pub struct EnhancedPerceptionConfig {
     pub cache_enabled: bool,
     pub performance_monitoring: bool,
     pub accessibility_analysis: bool,
+    /// Read text in images and canvases with OCR when tesseract is available
+    pub enable_ocr: bool,
 }
 
 /// Maintains context across interactions
@@ -54,15 +118,21 @@ pub struct EnhancedPerceptionConfig {
 pub struct PerceptionContext {
     pub current_url: String,
     pub page_type: PageType,
+    /// Confidence and runner-ups of the latest classification
+    #[serde(default)]
+    pub classification: Option<page_classifier::PageClassification>,
     pub last_action: Option<String>,
     pub last_element: Option<String>,
     pub form_state: HashMap<String, String>,
     pub named_elements: HashMap<String, String>, // "the search box" -> selector
     pub screenshot_cache: Option<Vec<u8>>,       // Latest screenshot for visual analysis
+    /// Words read from the latest screenshot by OCR
+    #[serde(default)]
+    pub ocr_words: Vec<ocr::OcrWord>,
 }
 
 /// Categorizes what type of page we're on
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PageType {
     Homepage,
     SearchResults,
@@ -72,6 +142,16 @@ pub enum PageType {
     FormPage,
     Dashboard,
     Settings,
+    Checkout,
+    Cart,
+    Pricing,
+    Documentation,
+    /// 404 and other error pages
+    ErrorPage,
+    /// Content hidden behind a subscription prompt
+    Paywall,
+    /// A cookie or privacy consent dialog covers the page
+    ConsentBanner,
     Unknown,
 }
 
@@ -97,6 +177,13 @@ pub struct PerceivedElement {
     pub attributes: HashMap<String, String>,
     pub position: Option<ElementPosition>,
     pub visual_context: Option<VisualContext>,
+    /// Part of the page layout the element sits in
+    #[serde(default)]
+    pub region: semantic::PageRegion,
+    /// How reliably the selector found this same element across page loads; None
+    /// until it was seen in more than one
+    #[serde(default)]
+    pub stability: Option<f32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -141,6 +228,8 @@ struct CachedElement {
     element_type: ElementType,
     last_seen: std::time::Instant,
     confidence: f32,
+    /// DOM structure version the selector was found in
+    structure_version: u64,
 }
 
 impl Default for EnhancedPerceptionConfig {
@@ -152,6 +241,7 @@ impl Default for EnhancedPerceptionConfig {
             cache_enabled: true,
             performance_monitoring: true,
             accessibility_analysis: true,
+            enable_ocr: true,
         }
     }
 }
@@ -176,20 +266,31 @@ impl PerceptionEngine {
             None
         };
 
+        let ocr = if config.enable_ocr {
+            ocr::OcrEngine::detect().await
+        } else {
+            None
+        };
+
         Ok(Self {
             browser,
             context: PerceptionContext {
                 current_url: String::new(),
                 page_type: PageType::Unknown,
+                classification: None,
                 last_action: None,
                 last_element: None,
                 form_state: HashMap::new(),
                 named_elements: HashMap::new(),
                 screenshot_cache: None,
+                ocr_words: Vec::new(),
             },
             element_cache: HashMap::new(),
             layered_perception,
             chromium_integration,
+            ocr,
+            vision: None,
+            page_classifier: page_classifier::PageClassifierChain::default(),
             config,
         })
     }
@@ -218,6 +319,7 @@ impl PerceptionEngine {
             context: self.context.clone(),
             analysis_timestamp: chrono::Utc::now(),
             performance_score: self.calculate_performance_score().await?,
+            web_vitals: self.browser.collect_web_vitals().await.ok(),
         };
 
         // Update context with new findings
@@ -304,6 +406,11 @@ impl PerceptionEngine {
         Ok(matches)
     }
 
+    /// "Next page" and numbered pagination controls of the current page
+    pub async fn detect_pagination(&self) -> Result<pagination::Pagination> {
+        pagination::detect(&self.browser).await
+    }
+
     /// Legacy analyze_page method for backwards compatibility
     pub async fn analyze_page(&mut self) -> Result<PageAnalysis> {
         // Get current URL
@@ -389,22 +496,88 @@ impl PerceptionEngine {
 
     /// Classify the current page type
     pub async fn classify_page(&mut self) -> Result<PageType> {
-        let url = self.browser.current_url().await?;
-        self.context.current_url = url.clone();
+        Ok(self.classify_page_detailed().await?.page_type)
+    }
+
+    /// Classify the current page, with the confidence and the classifier that decided
+    pub async fn classify_page_detailed(&mut self) -> Result<page_classifier::PageClassification> {
+        self.context.current_url = self.browser.current_url().await?;
 
         // Take a screenshot for visual analysis
         let screenshot = self
             .browser
             .screenshot(crate::browser::ScreenshotOptions::default())
             .await?;
+        self.context.ocr_words = match &self.ocr {
+            Some(engine) => {
+                let page_width = self.page_width().await?;
+                engine
+                    .recognize(&screenshot, page_width)
+                    .await
+                    .unwrap_or_else(|e| {
+                        debug!("OCR of page failed: {}", e);
+                        Vec::new()
+                    })
+            }
+            None => Vec::new(),
+        };
         self.context.screenshot_cache = Some(screenshot);
 
-        // Use URL and page content analysis
-        let page_type = self.classify_by_url_and_content(&url).await?;
-        self.context.page_type = page_type.clone();
+        let signals = page_classifier::PageSignals::collect(&self.browser).await?;
+        let classification = self.page_classifier.classify(&signals).await;
+        info!(
+            "Classified page as: {:?} ({:.2}, {})",
+            classification.page_type, classification.confidence, classification.classifier
+        );
+        self.context.page_type = classification.page_type.clone();
+        self.context.classification = Some(classification.clone());
+        Ok(classification)
+    }
+
+    /// Consult `classifier`, such as a small model or an LLM, when heuristics
+    /// cannot tell the page type with enough confidence
+    pub fn add_page_classifier(
+        &mut self,
+        classifier: std::sync::Arc<dyn page_classifier::PageClassifier>,
+    ) {
+        self.page_classifier.add_fallback(classifier);
+    }
 
-        info!("Classified page as: {:?}", page_type);
-        Ok(page_type)
+    /// Ask `llm`'s vision models about screenshots when DOM heuristics find no
+    /// element, and answer questions about how the page looks
+    pub fn set_vision_model(&mut self, llm: crate::llm::LLMService) {
+        self.vision = Some(vision::VisionModel::new(llm));
+    }
+
+    /// Answer a question about how the page looks, such as "which button is
+    /// highlighted?", from the cached screenshot (taking one if there is none)
+    pub async fn ask_about_page(&mut self, question: &str) -> Result<String> {
+        if self.vision.is_none() {
+            return Err(anyhow::anyhow!("No vision model configured"));
+        }
+        if self.context.screenshot_cache.is_none() {
+            let screenshot = self
+                .browser
+                .screenshot(crate::browser::ScreenshotOptions::default())
+                .await?;
+            self.context.screenshot_cache = Some(screenshot);
+        }
+        let page_width = self.page_width().await?;
+
+        match (&self.vision, &self.context.screenshot_cache) {
+            (Some(vision), Some(screenshot)) => vision.ask(screenshot, page_width, question).await,
+            _ => Err(anyhow::anyhow!("No vision model configured")),
+        }
+    }
+
+    /// Width of the document in CSS pixels, which full-page screenshots span
+    async fn page_width(&self) -> Result<f64> {
+        Ok(self
+            .browser
+            .execute_script("Math.max(document.documentElement.scrollWidth, window.innerWidth)")
+            .await?
+            .as_f64()
+            .unwrap_or(1920.0))
     }
 
     /// Extract structured data based on page type
@@ -451,9 +624,60 @@ impl PerceptionEngine {
             candidates.extend(self.find_by_visual_context(&desc_lower).await?);
         }
 
+        // Strategy 6: Vision model, only when nothing else matched
+        if candidates.is_empty() && self.vision.is_some() {
+            candidates.extend(self.find_by_vision(description).await?);
+        }
+
+        self.annotate_regions(&mut candidates).await;
+        self.annotate_stability(&mut candidates).await;
+
         Ok(candidates)
     }
 
+    /// Label each element with the page region it sits in
+    async fn annotate_regions(&self, elements: &mut [PerceivedElement]) {
+        if elements.is_empty() {
+            return;
+        }
+        let selectors: Vec<String> = elements.iter().map(|e| e.selector.clone()).collect();
+        match semantic::SemanticAnalyzer::new()
+            .regions_of(&self.browser, &selectors)
+            .await
+        {
+            Ok(regions) => {
+                for (element, region) in elements.iter_mut().zip(regions) {
+                    element.region = region;
+                }
+            }
+            Err(e) => debug!("Region detection failed: {}", e),
+        }
+    }
+
+    /// Record how each element's selector resolves in this load and attach its
+    /// stability across loads
+    async fn annotate_stability(&self, elements: &mut [PerceivedElement]) {
+        let selectors: Vec<String> = elements
+            .iter()
+            .map(|e| e.selector.clone())
+            .filter(|s| !s.is_empty())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if selectors.is_empty() {
+            return;
+        }
+        match self.browser.observe_selectors(&selectors).await {
+            Ok(scores) => {
+                let scores: HashMap<&String, Option<f32>> = selectors.iter().zip(scores).collect();
+                for element in elements.iter_mut() {
+                    element.stability = scores.get(&element.selector).copied().flatten();
+                }
+            }
+            Err(e) => debug!("Selector stability tracking failed: {}", e),
+        }
+    }
+
     async fn find_by_element_type(&self, description: &str) -> Result<Vec<PerceivedElement>> {
         let mut elements = Vec::new();
 
@@ -664,6 +888,20 @@ impl PerceptionEngine {
     }
 
     async fn find_by_accessibility(&self, description: &str) -> Result<Vec<PerceivedElement>> {
+        // The accessibility tree names elements the way users see them, whatever
+        // their class names or DOM structure
+        if self.config.accessibility_analysis {
+            match self.browser.accessibility_tree(true).await {
+                Ok(tree) => {
+                    let elements = ax_matches(tree, description);
+                    if !elements.is_empty() {
+                        return Ok(elements);
+                    }
+                }
+                Err(e) => debug!("Accessibility tree unavailable: {}", e),
+            }
+        }
+
         let mut elements = Vec::new();
 
         let aria_script = format!(
@@ -707,11 +945,120 @@ impl PerceptionEngine {
         Ok(elements)
     }
 
-    async fn find_by_visual_context(&self, _description: &str) -> Result<Vec<PerceivedElement>> {
-        // TODO: Implement visual analysis using the cached screenshot
-        // This would involve computer vision to identify elements visually
-        // For now, return empty - this is a future enhancement
-        Ok(vec![])
+    async fn find_by_visual_context(&self, description: &str) -> Result<Vec<PerceivedElement>> {
+        let Some(screenshot) = &self.context.screenshot_cache else {
+            return Ok(vec![]);
+        };
+
+        let (boxes, page) = self.visual_layout().await?;
+        let segmentation = match visual::Segmentation::from_screenshot(screenshot, page.0) {
+            Ok(segmentation) => segmentation,
+            Err(e) => {
+                debug!("Cached screenshot could not be analyzed: {}", e);
+                return Ok(vec![]);
+            }
+        };
+
+        Ok(visual::rank(&segmentation, &boxes, page, description, 5)
+            .into_iter()
+            .map(|m| PerceivedElement {
+                selector: m.element.selector,
+                text: m.element.text,
+                element_type: element_type_of(&m.element.tag, &m.element.role),
+                clickable: m.element.clickable,
+                visible: true,
+                confidence: m.score,
+                attributes: HashMap::new(),
+                position: Some(ElementPosition {
+                    x: m.element.x,
+                    y: m.element.y,
+                    width: m.element.width,
+                    height: m.element.height,
+                }),
+                visual_context: Some(VisualContext {
+                    nearby_elements: m.nearby,
+                    parent_context: m.anchor,
+                    visual_prominence: m.prominence,
+                }),
+                region: semantic::PageRegion::Unknown,
+                stability: None,
+            })
+            .collect())
+    }
+
+    /// Rendered boxes of visible elements, with text read by OCR added, and the
+    /// document size in CSS pixels
+    async fn visual_layout(&self) -> Result<(Vec<visual::VisualBox>, (f64, f64))> {
+        let layout = self.browser.execute_script(VISUAL_BOXES_SCRIPT).await?;
+        let mut boxes: Vec<visual::VisualBox> =
+            serde_json::from_value(layout["boxes"].clone()).unwrap_or_default();
+        // Text drawn in images, canvases or backgrounds only exists in the screenshot
+        if !self.context.ocr_words.is_empty() {
+            for element in &mut boxes {
+                // Elements with DOM text already carry what OCR would read
+                let pixels_only = matches!(element.tag.as_str(), "img" | "canvas" | "svg");
+                if !pixels_only && !element.text.is_empty() {
+                    continue;
+                }
+                let text = ocr::text_within(
+                    &self.context.ocr_words,
+                    element.x,
+                    element.y,
+                    element.width,
+                    element.height,
+                );
+                if !text.is_empty() && !element.text.contains(&text) {
+                    element.text = format!("{} {}", element.text, text).trim().to_string();
+                }
+            }
+        }
+        let page = (
+            layout["width"].as_f64().unwrap_or(1920.0),
+            layout["height"].as_f64().unwrap_or(1080.0),
+        );
+        Ok((boxes, page))
+    }
+
+    /// Let the vision model pick among the rendered elements by how the page looks
+    async fn find_by_vision(&self, description: &str) -> Result<Vec<PerceivedElement>> {
+        let (Some(vision), Some(screenshot)) = (&self.vision, &self.context.screenshot_cache)
+        else {
+            return Ok(vec![]);
+        };
+
+        let (boxes, page) = self.visual_layout().await?;
+        let found = match vision.locate(screenshot, page.0, description, &boxes).await {
+            Ok(found) => found,
+            Err(e) => {
+                debug!("Vision model could not locate '{}': {}", description, e);
+                return Ok(vec![]);
+            }
+        };
+
+        Ok(found
+            .into_iter()
+            .map(|m| PerceivedElement {
+                selector: m.element.selector,
+                text: m.element.text,
+                element_type: element_type_of(&m.element.tag, &m.element.role),
+                clickable: m.element.clickable,
+                visible: true,
+                confidence: m.confidence,
+                attributes: m
+                    .reason
+                    .map(|reason| HashMap::from([("vision_reason".to_string(), reason)]))
+                    .unwrap_or_default(),
+                position: Some(ElementPosition {
+                    x: m.element.x,
+                    y: m.element.y,
+                    width: m.element.width,
+                    height: m.element.height,
+                }),
+                visual_context: None,
+                region: semantic::PageRegion::Unknown,
+                stability: None,
+            })
+            .collect())
     }
 
     async fn select_best_candidate(
@@ -767,7 +1114,16 @@ impl PerceptionEngine {
             score += 0.2;
         }
 
-        score.min(1.0) // Cap at 1.0
+        let score = score.min(1.0); // Cap at 1.0
+
+        // "the search box in the header": elements elsewhere on the page are worse
+        // matches however well they score otherwise
+        match semantic::PageRegion::from_description(description) {
+            Some(wanted) if element.region == wanted => score,
+            Some(_) if element.region == semantic::PageRegion::Unknown => score - 0.1,
+            Some(_) => score - 0.3,
+            None => score,
+        }
     }
 
     fn calculate_text_similarity(&self, element_text: &str, description: &str) -> f32 {
@@ -847,6 +1203,8 @@ impl PerceptionEngine {
             attributes: HashMap::new(),
             position: None, // TODO: Extract position from element
             visual_context: None,
+            region: semantic::PageRegion::Unknown,
+            stability: None,
         })
     }
 
@@ -867,13 +1225,18 @@ impl PerceptionEngine {
             attributes: HashMap::new(),
             position: None,
             visual_context: None,
+            region: semantic::PageRegion::Unknown,
+            stability: None,
         })
     }
 
     fn check_cache(&self, description: &str) -> Option<&CachedElement> {
         self.element_cache.get(description).and_then(|cached| {
-            // Check if cache is still valid (within 30 seconds)
-            if cached.last_seen.elapsed().as_secs() < 30 {
+            // Check if cache is still valid (within 30 seconds, and no elements
+            // added or removed since)
+            if cached.last_seen.elapsed().as_secs() < 30
+                && cached.structure_version == self.browser.dom_mutations().structure_version()
+            {
                 Some(cached)
             } else {
                 None
@@ -901,6 +1264,8 @@ impl PerceptionEngine {
             attributes: HashMap::new(),
             position: None,
             visual_context: None,
+            region: semantic::PageRegion::Unknown,
+            stability: None,
         })
     }
 
@@ -922,6 +1287,7 @@ impl PerceptionEngine {
                 element_type: element.element_type.clone(),
                 last_seen: std::time::Instant::now(),
                 confidence: element.confidence,
+                structure_version: self.browser.dom_mutations().structure_version(),
             },
         );
     }
@@ -951,95 +1317,17 @@ impl PerceptionEngine {
         Ok(None)
     }
 
-    // Page classification methods
-
-    async fn classify_by_url_and_content(&self, url: &str) -> Result<PageType> {
-        // URL-based classification
-        let url_lower = url.to_lowercase();
-
-        if url_lower.contains("login") || url_lower.contains("signin") || url_lower.contains("auth")
-        {
-            return Ok(PageType::LoginPage);
-        }
-
-        if url_lower.contains("search") || url_lower.contains("results") {
-            return Ok(PageType::SearchResults);
-        }
-
-        if url_lower.contains("product") || url_lower.contains("item") || url_lower.contains("shop")
-        {
-            return Ok(PageType::ProductPage);
-        }
-
-        // Content-based classification using JavaScript
-        let classification_script = r#"
-            const indicators = {
-                form: document.querySelectorAll('form').length,
-                articles: document.querySelectorAll('article, .article, .post').length,
-                products: document.querySelectorAll('[class*="product"], [id*="product"]').length,
-                login: document.querySelectorAll('input[type="password"], [class*="login"], [id*="login"]').length,
-                search: document.querySelectorAll('input[type="search"], [class*="search"], [id*="search"]').length,
-                dashboard: document.querySelectorAll('[class*="dashboard"], [class*="admin"], nav').length
-            };
-            
-            return indicators;
-        "#;
-
-        if let Ok(result) = self.browser.execute_script(classification_script).await {
-            if let Some(indicators) = result.as_object() {
-                let form_count = indicators.get("form").and_then(|v| v.as_u64()).unwrap_or(0);
-                let article_count = indicators
-                    .get("articles")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let product_count = indicators
-                    .get("products")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let login_count = indicators
-                    .get("login")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let search_count = indicators
-                    .get("search")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let dashboard_count = indicators
-                    .get("dashboard")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-
-                // Determine page type based on indicators
-                if login_count > 0 {
-                    return Ok(PageType::LoginPage);
-                } else if search_count > 2 {
-                    return Ok(PageType::SearchResults);
-                } else if product_count > 3 {
-                    return Ok(PageType::ProductPage);
-                } else if article_count > 0 {
-                    return Ok(PageType::ArticlePage);
-                } else if form_count > 0 {
-                    return Ok(PageType::FormPage);
-                } else if dashboard_count > 3 {
-                    return Ok(PageType::Dashboard);
-                }
-            }
-        }
+    // Data extraction methods (simplified for now)
 
-        Ok(PageType::Unknown)
+    /// Title, price, availability, images and reviews of the current product page
+    pub async fn extract_product(&self) -> Result<product::ProductData> {
+        product::extract(&self.browser).await
     }
 
-    // Data extraction methods (simplified for now)
-
     async fn extract_product_data(&self) -> Result<serde_json::Value> {
-        // TODO: Implement product data extraction
-        Ok(serde_json::json!({
-            "type": "product",
-            "title": "",
-            "price": "",
-            "description": "",
-            "images": []
-        }))
+        let mut data = serde_json::to_value(self.extract_product().await?)?;
+        data["type"] = serde_json::json!("product");
+        Ok(data)
     }
 
     async fn extract_article_data(&self) -> Result<serde_json::Value> {
@@ -1053,20 +1341,26 @@ impl PerceptionEngine {
         }))
     }
 
+    /// Ranked result list of the current search engine or site search page
+    pub async fn extract_search(&self) -> Result<search_results::SearchResults> {
+        search_results::extract(&self.browser).await
+    }
+
     async fn extract_search_results(&self) -> Result<serde_json::Value> {
-        // TODO: Implement search results extraction
-        Ok(serde_json::json!({
-            "type": "search_results",
-            "results": []
-        }))
+        let mut data = serde_json::to_value(self.extract_search().await?)?;
+        data["type"] = serde_json::json!("search_results");
+        Ok(data)
+    }
+
+    /// Fields, labels, requirements and validation of every form on the current page
+    pub async fn extract_forms(&self) -> Result<form_schema::FormSchema> {
+        form_schema::extract(&self.browser, None).await
     }
 
     async fn extract_form_data(&self) -> Result<serde_json::Value> {
-        // TODO: Implement form data extraction
-        Ok(serde_json::json!({
-            "type": "form",
-            "fields": []
-        }))
+        let mut data = serde_json::to_value(self.extract_forms().await?)?;
+        data["type"] = serde_json::json!("form");
+        Ok(data)
     }
 
     async fn extract_generic_data(&self) -> Result<serde_json::Value> {
@@ -1131,6 +1425,9 @@ impl PerceptionEngine {
             PerceptionResult::Deep(deep) => {
                 self.context.current_url = deep.standard.quick.lightning.url.clone();
             }
+            PerceptionResult::Accessibility(accessibility) => {
+                self.context.current_url = accessibility.lightning.url.clone();
+            }
         }
         Ok(())
     }
@@ -1245,6 +1542,59 @@ impl PerceptionEngine {
     }
 }
 
+/// Best accessibility-tree matches for a description, as perceived elements
+fn ax_matches(tree: Vec<crate::browser::AxElement>, description: &str) -> Vec<PerceivedElement> {
+    let mut scored: Vec<_> = tree
+        .into_iter()
+        .filter(|element| element.selector.is_some())
+        .map(|element| (element.match_score(description), element))
+        .filter(|(score, _)| *score >= 0.4)
+        .collect();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    scored
+        .into_iter()
+        .take(5)
+        .map(|(score, element)| {
+            let mut attributes = HashMap::from([("role".to_string(), element.role.clone())]);
+            if !element.states.is_empty() {
+                attributes.insert("states".to_string(), element.states.join(" "));
+            }
+            PerceivedElement {
+                selector: element.selector.clone().unwrap_or_default(),
+                element_type: element_type_of("", &element.role),
+                clickable: element.is_interactive() && !element.has_state("disabled"),
+                // Hidden elements are ignored by the accessibility tree
+                visible: true,
+                confidence: score,
+                text: element.name,
+                attributes,
+                position: None,
+                visual_context: None,
+                region: semantic::PageRegion::Unknown,
+                stability: None,
+            }
+        })
+        .collect()
+}
+
+/// Element type of a rendered element from its tag and ARIA role
+fn element_type_of(tag: &str, role: &str) -> ElementType {
+    match (tag, role) {
+        ("button", _) | (_, "button") => ElementType::Button,
+        ("a", _) | (_, "link") => ElementType::Link,
+        ("input", _) | (_, "textbox") | (_, "searchbox") => ElementType::Input,
+        ("select", _) | (_, "combobox") | (_, "listbox") => ElementType::Select,
+        ("textarea", _) => ElementType::TextArea,
+        ("img", _) | ("svg", _) | ("canvas", _) | (_, "img") => ElementType::Image,
+        (_, "navigation") => ElementType::Navigation,
+        (_, "dialog") | (_, "alertdialog") => ElementType::Modal,
+        (_, "checkbox") | (_, "switch") => ElementType::Checkbox,
+        (_, "radio") => ElementType::Radio,
+        _ => ElementType::Text,
+    }
+}
+
 // === Enhanced data structures ===
 
 /// Enhanced page analysis result combining all perception layers
@@ -1255,6 +1605,9 @@ pub struct EnhancedPageAnalysis {
     pub context: PerceptionContext,
     pub analysis_timestamp: DateTime<Utc>,
     pub performance_score: f64,
+    /// Load timing and Web Vitals of the analyzed page, when they could be measured
+    #[serde(default)]
+    pub web_vitals: Option<crate::browser::WebVitals>,
 }
 
 /// Smart element match with confidence and type information
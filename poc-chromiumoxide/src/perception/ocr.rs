@@ -0,0 +1,178 @@
+// OCR of page screenshots through the tesseract CLI
+//
+// Reads text that only exists as pixels (images, canvases, rendered banners) so it
+// can be matched like DOM text.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{debug, info};
+
+const OCR_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Words recognized with less confidence (0-100) are dropped
+const MIN_CONFIDENCE: f32 = 60.0;
+
+/// Word recognized in a screenshot, positioned in CSS pixels of the document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrWord {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub confidence: f32,
+}
+
+impl OcrWord {
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+/// Tesseract installation used for OCR
+#[derive(Debug, Clone)]
+pub struct OcrEngine {
+    command: String,
+    language: String,
+}
+
+impl OcrEngine {
+    /// The tesseract binary at `RAINBOW_TESSERACT` (default `tesseract` on the PATH)
+    /// with the languages in `RAINBOW_OCR_LANG` (default `eng`), if it runs
+    pub async fn detect() -> Option<Self> {
+        let command =
+            std::env::var("RAINBOW_TESSERACT").unwrap_or_else(|_| "tesseract".to_string());
+        let language = std::env::var("RAINBOW_OCR_LANG").unwrap_or_else(|_| "eng".to_string());
+        let available = Command::new(&command)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if !available {
+            debug!("OCR disabled: {} not found", command);
+            return None;
+        }
+        info!("OCR enabled with {} ({})", command, language);
+        Some(Self { command, language })
+    }
+
+    /// Recognize the words of an encoded screenshot spanning `css_width` CSS pixels
+    pub async fn recognize(&self, screenshot: &[u8], css_width: f64) -> Result<Vec<OcrWord>> {
+        let (image_width, _) = image::io::Reader::new(std::io::Cursor::new(screenshot))
+            .with_guessed_format()?
+            .into_dimensions()?;
+
+        let mut child = Command::new(&self.command)
+            .args(["stdin", "stdout", "-l", &self.language, "tsv"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("tesseract stdin unavailable"))?;
+        stdin.write_all(screenshot).await?;
+        drop(stdin);
+
+        let output = tokio::time::timeout(OCR_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow!("OCR timed out after {:?}", OCR_TIMEOUT))??;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "tesseract failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let scale = if css_width > 0.0 {
+            css_width / image_width as f64
+        } else {
+            1.0
+        };
+        let words = parse_tsv(&String::from_utf8_lossy(&output.stdout), scale);
+        debug!("OCR recognized {} words", words.len());
+        Ok(words)
+    }
+}
+
+/// Words of tesseract's TSV output, with pixel positions multiplied by `scale`
+pub fn parse_tsv(tsv: &str, scale: f64) -> Vec<OcrWord> {
+    tsv.lines()
+        .skip(1) // header
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            // level 5 rows are words: level, page, block, par, line, word,
+            // left, top, width, height, conf, text
+            if fields.len() < 12 || fields[0] != "5" {
+                return None;
+            }
+            let text = fields[11].trim();
+            let confidence: f32 = fields[10].parse().ok()?;
+            if text.is_empty() || confidence < MIN_CONFIDENCE {
+                return None;
+            }
+            let number = |i: usize| fields[i].parse::<f64>().ok().map(|v| v * scale);
+            Some(OcrWord {
+                text: text.to_string(),
+                x: number(6)?,
+                y: number(7)?,
+                width: number(8)?,
+                height: number(9)?,
+                confidence,
+            })
+        })
+        .collect()
+}
+
+/// Text of the words whose centers fall inside a box, in reading order
+pub fn text_within(words: &[OcrWord], x: f64, y: f64, width: f64, height: f64) -> String {
+    words
+        .iter()
+        .filter(|word| {
+            let (cx, cy) = word.center();
+            cx >= x && cx <= x + width && cy >= y && cy <= y + height
+        })
+        .map(|word| word.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t400\t200\t-1\t
+5\t1\t1\t1\t1\t1\t20\t40\t60\t20\t96.5\tSUMMER
+5\t1\t1\t1\t1\t2\t90\t40\t40\t20\t91.0\tSALE
+5\t1\t1\t1\t2\t1\t300\t150\t30\t10\t12.0\t~~
+";
+
+    #[test]
+    fn parses_confident_words_and_scales_them() {
+        let words = parse_tsv(TSV, 0.5);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "SUMMER");
+        assert_eq!(
+            (words[0].x, words[0].y, words[0].width, words[0].height),
+            (10.0, 20.0, 30.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn collects_words_inside_a_box() {
+        let words = parse_tsv(TSV, 1.0);
+        assert_eq!(text_within(&words, 0.0, 0.0, 200.0, 100.0), "SUMMER SALE");
+        assert_eq!(text_within(&words, 0.0, 0.0, 85.0, 100.0), "SUMMER");
+        assert_eq!(text_within(&words, 200.0, 0.0, 100.0, 100.0), "");
+    }
+}
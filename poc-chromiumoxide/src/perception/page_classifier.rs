@@ -0,0 +1,648 @@
+// Page type classification
+//
+// Collects URL, metadata and DOM signals once, scores every page type with
+// heuristics, and hands inconclusive pages to pluggable fallbacks such as a small
+// model or an LLM.
+
+use super::PageType;
+use crate::browser::Browser;
+use crate::llm::LLMService;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// Reads every signal the classifiers use in one evaluation
+const SIGNALS_SCRIPT: &str = r#"
+    (function() {
+        const count = selector => { try { return document.querySelectorAll(selector).length; } catch (e) { return 0; } };
+        const visibleAny = selector => Array.from(document.querySelectorAll(selector)).some(el => {
+            const rect = el.getBoundingClientRect();
+            const style = window.getComputedStyle(el);
+            return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+        });
+        const meta = name => {
+            const m = document.querySelector('meta[property="' + name + '"], meta[name="' + name + '"]');
+            return m ? m.getAttribute('content') : null;
+        };
+        const jsonLdTypes = [];
+        const collect = node => {
+            if (!node || typeof node !== 'object') return;
+            if (Array.isArray(node)) { node.forEach(collect); return; }
+            if (node['@type']) jsonLdTypes.push(...[].concat(node['@type']).map(String));
+            if (node['@graph']) collect(node['@graph']);
+        };
+        document.querySelectorAll('script[type="application/ld+json"]').forEach(s => {
+            try { collect(JSON.parse(s.textContent)); } catch (e) {}
+        });
+        const text = (document.body && document.body.innerText) || '';
+        const h1 = document.querySelector('h1');
+        const buttons = Array.from(document.querySelectorAll('button, input[type="submit"], input[type="button"], a[role="button"], [role="button"]'))
+            .map(b => (b.innerText || b.value || '').trim().replace(/\s+/g, ' ').toLowerCase())
+            .filter(t => t && t.length <= 40);
+        return {
+            url: location.href,
+            title: document.title || '',
+            heading: h1 ? h1.innerText.trim().slice(0, 200) : '',
+            og_type: meta('og:type'),
+            json_ld_types: jsonLdTypes.slice(0, 20),
+            text_length: text.length,
+            text_sample: text.slice(0, 3000).toLowerCase(),
+            forms: count('form'),
+            inputs: count('input:not([type="hidden"]):not([type="submit"]):not([type="button"]), select, textarea'),
+            password_inputs: count('input[type="password"]'),
+            search_inputs: count('input[type="search"], input[name="q"], input[name="query"], [role="search"] input'),
+            payment_inputs: count('input[autocomplete^="cc-"], input[name*="card" i], input[name*="cvv" i], input[name*="cvc" i], iframe[src*="stripe"], iframe[name*="card" i]'),
+            address_inputs: count('input[autocomplete*="address"], input[autocomplete="postal-code"], input[name*="address" i], input[name*="zip" i], input[name*="postal" i]'),
+            quantity_inputs: count('input[name*="qty" i], input[name*="quantity" i], select[name*="quantity" i]'),
+            articles: count('article, [itemtype*="Article"], .post, .article'),
+            product_markers: count('[itemtype*="Product"], [class*="product" i], [id*="product" i]'),
+            cart_markers: count('[class*="cart" i], [id*="cart" i], [class*="basket" i]'),
+            pricing_markers: count('[class*="pricing" i], [id*="pricing" i], [class*="plan-" i], [class*="-plan" i]'),
+            code_blocks: count('pre'),
+            result_links: count('h2 a[href], h3 a[href], a[href] h2, a[href] h3'),
+            tables: count('table'),
+            nav_links: count('nav a, aside a'),
+            buttons: buttons.slice(0, 200),
+            paywall_visible: visibleAny('[class*="paywall" i], [id*="paywall" i], [class*="subscribe-wall" i], [class*="regwall" i]'),
+            consent_visible: visibleAny('#onetrust-banner-sdk, #CybotCookiebotDialog, .fc-consent-root, #didomi-notice, [class*="cookie-consent" i], [class*="cookie-banner" i], [id*="cookie-banner" i], [id*="consent-banner" i], [aria-label*="cookie" i][role="dialog"]')
+        };
+    })()
+"#;
+
+/// What a page looks like to the classifiers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageSignals {
+    pub url: String,
+    pub title: String,
+    /// Text of the first h1
+    pub heading: String,
+    pub og_type: Option<String>,
+    /// schema.org types declared in JSON-LD
+    pub json_ld_types: Vec<String>,
+    pub text_length: usize,
+    /// Start of the visible text, lowercased
+    pub text_sample: String,
+    pub forms: usize,
+    pub inputs: usize,
+    pub password_inputs: usize,
+    pub search_inputs: usize,
+    pub payment_inputs: usize,
+    pub address_inputs: usize,
+    pub quantity_inputs: usize,
+    pub articles: usize,
+    pub product_markers: usize,
+    pub cart_markers: usize,
+    pub pricing_markers: usize,
+    pub code_blocks: usize,
+    pub result_links: usize,
+    pub tables: usize,
+    pub nav_links: usize,
+    /// Lowercased labels of buttons
+    pub buttons: Vec<String>,
+    pub paywall_visible: bool,
+    pub consent_visible: bool,
+}
+
+impl PageSignals {
+    /// Signals of the page currently open in the browser
+    pub async fn collect(browser: &Browser) -> Result<Self> {
+        let result = browser.execute_script(SIGNALS_SCRIPT).await?;
+        Ok(serde_json::from_value(result).unwrap_or_default())
+    }
+
+    fn has_json_ld(&self, types: &[&str]) -> bool {
+        self.json_ld_types
+            .iter()
+            .any(|t| types.iter().any(|wanted| t.eq_ignore_ascii_case(wanted)))
+    }
+
+    fn has_button(&self, labels: &[&str]) -> bool {
+        self.buttons
+            .iter()
+            .any(|b| labels.iter().any(|label| b.contains(label)))
+    }
+
+    fn text_mentions(&self, phrases: &[&str]) -> bool {
+        phrases.iter().any(|p| self.text_sample.contains(p))
+    }
+}
+
+/// Score of one page type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageTypeScore {
+    pub page_type: PageType,
+    pub score: f32,
+}
+
+/// Page type with how sure the classifier is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageClassification {
+    pub page_type: PageType,
+    /// 0.0 to 1.0
+    pub confidence: f32,
+    /// Name of the classifier that decided
+    pub classifier: String,
+    /// Runner-up types, best first
+    #[serde(default)]
+    pub alternatives: Vec<PageTypeScore>,
+}
+
+impl PageClassification {
+    pub fn unknown(classifier: &str) -> Self {
+        Self {
+            page_type: PageType::Unknown,
+            confidence: 0.0,
+            classifier: classifier.to_string(),
+            alternatives: Vec::new(),
+        }
+    }
+}
+
+/// Something that can tell page types apart; return None to abstain
+#[async_trait]
+pub trait PageClassifier: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn classify(&self, signals: &PageSignals) -> Result<Option<PageClassification>>;
+}
+
+/// Weighted URL, metadata and DOM rules
+#[derive(Debug, Default)]
+pub struct HeuristicClassifier;
+
+/// Scores below this leave the page Unknown
+const MIN_HEURISTIC_SCORE: f32 = 0.25;
+
+impl HeuristicClassifier {
+    /// Score of every page type with any evidence, best first
+    pub fn scores(&self, s: &PageSignals) -> Vec<PageTypeScore> {
+        let url = url::Url::parse(&s.url).ok();
+        let path = url
+            .as_ref()
+            .map(|u| u.path().to_lowercase())
+            .unwrap_or_default();
+        let has_query = |names: &[&str]| {
+            url.as_ref().is_some_and(|u| {
+                u.query_pairs()
+                    .any(|(k, v)| names.contains(&k.as_ref()) && !v.is_empty())
+            })
+        };
+        let path_has = |words: &[&str]| {
+            path.split(|c: char| !c.is_alphanumeric())
+                .any(|segment| words.contains(&segment))
+        };
+        let headline = format!("{} {}", s.title, s.heading).to_lowercase();
+
+        let mut scores: Vec<(PageType, f32)> = Vec::new();
+        let mut add = |page_type: PageType, amount: f32| match scores
+            .iter_mut()
+            .find(|(t, _)| *t == page_type)
+        {
+            Some((_, score)) => *score += amount,
+            None => scores.push((page_type, amount)),
+        };
+
+        if path.is_empty() || path == "/" || path_has(&["index", "home"]) {
+            add(PageType::Homepage, 0.45);
+            if s.nav_links > 10 {
+                add(PageType::Homepage, 0.1);
+            }
+        }
+
+        if s.password_inputs > 0 {
+            add(PageType::LoginPage, 0.5);
+            if s.inputs <= 4 {
+                add(PageType::LoginPage, 0.3);
+            }
+        }
+        if path_has(&["login", "signin", "auth", "logon"]) || path.contains("sign-in") {
+            add(PageType::LoginPage, 0.3);
+        }
+
+        if has_query(&[
+            "q",
+            "query",
+            "search",
+            "wd",
+            "k",
+            "keywords",
+            "search_query",
+        ]) {
+            add(PageType::SearchResults, 0.35);
+        }
+        if path_has(&["search", "results", "s"]) {
+            add(PageType::SearchResults, 0.25);
+        }
+        if s.result_links >= 5 {
+            add(PageType::SearchResults, 0.3);
+        }
+
+        if s.has_json_ld(&["Product", "ProductGroup"]) {
+            add(PageType::ProductPage, 0.6);
+        }
+        if s.og_type.as_deref().is_some_and(|t| t.contains("product")) {
+            add(PageType::ProductPage, 0.4);
+        }
+        if s.has_button(&[
+            "add to cart",
+            "add to bag",
+            "add to basket",
+            "buy now",
+            "加入购物车",
+        ]) {
+            add(PageType::ProductPage, 0.35);
+        }
+        if s.product_markers > 3 {
+            add(PageType::ProductPage, 0.15);
+        }
+        if path_has(&["product", "products", "item", "dp", "p"]) {
+            add(PageType::ProductPage, 0.2);
+        }
+
+        if path_has(&["cart", "basket", "bag", "trolley"]) {
+            add(PageType::Cart, 0.5);
+        }
+        if s.quantity_inputs > 0 && s.cart_markers > 0 {
+            add(PageType::Cart, 0.2);
+        }
+        if s.has_button(&["checkout", "proceed to checkout", "check out"]) {
+            add(PageType::Cart, 0.3);
+        }
+
+        if path_has(&["checkout", "payment", "pay"]) {
+            add(PageType::Checkout, 0.5);
+        }
+        if s.payment_inputs > 0 {
+            add(PageType::Checkout, 0.4);
+        }
+        if s.address_inputs >= 2 {
+            add(PageType::Checkout, 0.15);
+        }
+        if s.has_button(&[
+            "place order",
+            "pay now",
+            "complete purchase",
+            "submit order",
+        ]) {
+            add(PageType::Checkout, 0.25);
+        }
+
+        if path_has(&["pricing", "plans", "prices"]) {
+            add(PageType::Pricing, 0.5);
+        }
+        if s.pricing_markers >= 3 {
+            add(PageType::Pricing, 0.2);
+        }
+        if s.text_mentions(&["per month", "/month", "/mo", "billed annually", "per user"]) {
+            add(PageType::Pricing, 0.2);
+        }
+        if s.has_button(&[
+            "start free trial",
+            "choose plan",
+            "get started",
+            "contact sales",
+        ]) {
+            add(PageType::Pricing, 0.1);
+        }
+
+        if path_has(&[
+            "docs",
+            "documentation",
+            "reference",
+            "api",
+            "guide",
+            "manual",
+        ]) || url
+            .as_ref()
+            .and_then(|u| u.host_str())
+            .is_some_and(|h| h.starts_with("docs.") || h.starts_with("developer."))
+        {
+            add(PageType::Documentation, 0.4);
+        }
+        if s.code_blocks >= 3 {
+            add(PageType::Documentation, 0.3);
+        }
+        if s.nav_links >= 30 && s.code_blocks > 0 {
+            add(PageType::Documentation, 0.1);
+        }
+        if s.has_json_ld(&["TechArticle", "APIReference"]) {
+            add(PageType::Documentation, 0.3);
+        }
+
+        if s.has_json_ld(&["Article", "NewsArticle", "BlogPosting", "Report"]) {
+            add(PageType::ArticlePage, 0.6);
+        }
+        if s.og_type.as_deref() == Some("article") {
+            add(PageType::ArticlePage, 0.4);
+        }
+        if s.articles > 0 {
+            add(PageType::ArticlePage, 0.2);
+        }
+        if s.text_length > 3000 && s.articles > 0 {
+            add(PageType::ArticlePage, 0.15);
+        }
+
+        if s.forms > 0 && s.inputs >= 3 && s.password_inputs == 0 {
+            add(PageType::FormPage, 0.35);
+        }
+
+        if path_has(&["dashboard", "admin", "console", "overview"]) {
+            add(PageType::Dashboard, 0.35);
+        }
+        if s.tables > 0 && s.nav_links > 10 {
+            add(PageType::Dashboard, 0.15);
+        }
+
+        if path_has(&["settings", "preferences", "account", "profile"]) {
+            add(PageType::Settings, 0.45);
+            if s.forms > 0 {
+                add(PageType::Settings, 0.1);
+            }
+        }
+
+        let error_words = [
+            "404",
+            "not found",
+            "page doesn't exist",
+            "page does not exist",
+            "500 internal",
+            "403 forbidden",
+            "access denied",
+            "页面不存在",
+        ];
+        if error_words.iter().any(|w| headline.contains(w)) {
+            add(PageType::ErrorPage, 0.8);
+            if s.text_length < 2000 {
+                add(PageType::ErrorPage, 0.1);
+            }
+        }
+
+        // Overlays that block the page win when they are on screen
+        if s.paywall_visible {
+            add(PageType::Paywall, 0.7);
+        }
+        if s.text_mentions(&[
+            "subscribe to continue",
+            "to continue reading",
+            "subscribers only",
+            "you've reached your limit",
+        ]) {
+            add(PageType::Paywall, 0.3);
+        }
+        if s.consent_visible {
+            add(PageType::ConsentBanner, 0.75);
+            if s.text_mentions(&["we use cookies", "accept all", "cookie settings"]) {
+                add(PageType::ConsentBanner, 0.2);
+            }
+        }
+
+        let mut scores: Vec<PageTypeScore> = scores
+            .into_iter()
+            .map(|(page_type, score)| PageTypeScore {
+                page_type,
+                score: score.min(1.0),
+            })
+            .collect();
+        scores.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        scores
+    }
+}
+
+#[async_trait]
+impl PageClassifier for HeuristicClassifier {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    async fn classify(&self, signals: &PageSignals) -> Result<Option<PageClassification>> {
+        let mut scores = self.scores(signals);
+        if scores.first().is_none_or(|s| s.score < MIN_HEURISTIC_SCORE) {
+            return Ok(Some(PageClassification::unknown(self.name())));
+        }
+        let best = scores.remove(0);
+        // A close runner-up makes the decision less certain
+        let second = scores.first().map_or(0.0, |s| s.score);
+        let confidence = best.score * (0.5 + 0.5 * (best.score - second) / best.score);
+        Ok(Some(PageClassification {
+            page_type: best.page_type,
+            confidence,
+            classifier: self.name().to_string(),
+            alternatives: scores.into_iter().take(3).collect(),
+        }))
+    }
+}
+
+/// Asks an LLM to pick the page type from a summary of the signals
+pub struct LlmPageClassifier {
+    llm: Mutex<LLMService>,
+}
+
+impl LlmPageClassifier {
+    pub fn new(llm: LLMService) -> Self {
+        Self {
+            llm: Mutex::new(llm),
+        }
+    }
+
+    fn prompt(signals: &PageSignals) -> String {
+        let mut summary = signals.clone();
+        summary.text_sample = signals.text_sample.chars().take(800).collect();
+        summary.buttons.truncate(30);
+        format!(
+            "Classify this web page. Choose page_type from: Homepage, SearchResults, LoginPage, \
+             ProductPage, ArticlePage, FormPage, Dashboard, Settings, Checkout, Cart, Pricing, \
+             Documentation, ErrorPage, Paywall, ConsentBanner, Unknown.\n\
+             Page signals:\n{}\n\n\
+             Respond with JSON only: {{\"page_type\": \"...\", \"confidence\": 0.0-1.0}}",
+            serde_json::to_string_pretty(&summary).unwrap_or_default()
+        )
+    }
+}
+
+#[async_trait]
+impl PageClassifier for LlmPageClassifier {
+    fn name(&self) -> &str {
+        "llm"
+    }
+
+    async fn classify(&self, signals: &PageSignals) -> Result<Option<PageClassification>> {
+        let response = self.llm.lock().await.query(&Self::prompt(signals)).await?;
+        let content = &response.content;
+        let json = match (content.find('{'), content.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &content[start..=end],
+            _ => return Ok(None),
+        };
+
+        #[derive(Deserialize)]
+        struct Answer {
+            page_type: PageType,
+            confidence: f32,
+        }
+        Ok(serde_json::from_str::<Answer>(json)
+            .ok()
+            .map(|answer| PageClassification {
+                page_type: answer.page_type,
+                confidence: answer.confidence.clamp(0.0, 1.0),
+                classifier: self.name().to_string(),
+                alternatives: Vec::new(),
+            }))
+    }
+}
+
+/// Runs classifiers in order until one is confident enough, keeping the most
+/// confident answer
+pub struct PageClassifierChain {
+    classifiers: Vec<Arc<dyn PageClassifier>>,
+    min_confidence: f32,
+}
+
+impl PageClassifierChain {
+    /// Heuristics only, accepting answers with at least `min_confidence`
+    pub fn new(min_confidence: f32) -> Self {
+        Self {
+            classifiers: vec![Arc::new(HeuristicClassifier)],
+            min_confidence,
+        }
+    }
+
+    /// Consult `classifier` when the classifiers before it are inconclusive
+    pub fn with_fallback(mut self, classifier: Arc<dyn PageClassifier>) -> Self {
+        self.classifiers.push(classifier);
+        self
+    }
+
+    pub fn add_fallback(&mut self, classifier: Arc<dyn PageClassifier>) {
+        self.classifiers.push(classifier);
+    }
+
+    pub async fn classify(&self, signals: &PageSignals) -> PageClassification {
+        let mut best: Option<PageClassification> = None;
+        for classifier in &self.classifiers {
+            let result = match classifier.classify(signals).await {
+                Ok(Some(result)) => result,
+                Ok(None) => continue,
+                Err(e) => {
+                    debug!("Page classifier {} failed: {}", classifier.name(), e);
+                    continue;
+                }
+            };
+            if best
+                .as_ref()
+                .is_none_or(|b| result.confidence > b.confidence)
+            {
+                best = Some(result);
+            }
+            if best.as_ref().is_some_and(|b| {
+                b.confidence >= self.min_confidence && b.page_type != PageType::Unknown
+            }) {
+                break;
+            }
+        }
+        best.unwrap_or_else(|| PageClassification::unknown("none"))
+    }
+}
+
+impl Default for PageClassifierChain {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signals(url: &str) -> PageSignals {
+        PageSignals {
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn classify(signals: &PageSignals) -> PageClassification {
+        HeuristicClassifier
+            .classify(signals)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn recognizes_product_and_cart_pages() {
+        let mut product = signals("https://shop.example/product/tv-55");
+        product.json_ld_types = vec!["Product".to_string()];
+        product.buttons = vec!["add to cart".to_string()];
+        let result = classify(&product).await;
+        assert_eq!(result.page_type, PageType::ProductPage);
+        assert!(result.confidence > 0.7);
+
+        let mut cart = signals("https://shop.example/cart");
+        cart.buttons = vec!["proceed to checkout".to_string()];
+        assert_eq!(classify(&cart).await.page_type, PageType::Cart);
+    }
+
+    #[tokio::test]
+    async fn recognizes_error_and_consent_overlays() {
+        let mut missing = signals("https://blog.example/posts/gone");
+        missing.title = "Page Not Found".to_string();
+        assert_eq!(classify(&missing).await.page_type, PageType::ErrorPage);
+
+        let mut consent = signals("https://news.example/");
+        consent.consent_visible = true;
+        consent.text_sample = "we use cookies to improve your experience".to_string();
+        let result = classify(&consent).await;
+        assert_eq!(result.page_type, PageType::ConsentBanner);
+        assert_eq!(result.alternatives[0].page_type, PageType::Homepage);
+    }
+
+    #[tokio::test]
+    async fn weak_evidence_stays_unknown() {
+        let result = classify(&signals("https://example.com/about-us")).await;
+        assert_eq!(result.page_type, PageType::Unknown);
+    }
+
+    struct Fixed(PageType, f32);
+
+    #[async_trait]
+    impl PageClassifier for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn classify(&self, _: &PageSignals) -> Result<Option<PageClassification>> {
+            Ok(Some(PageClassification {
+                page_type: self.0.clone(),
+                confidence: self.1,
+                classifier: "fixed".to_string(),
+                alternatives: Vec::new(),
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_heuristics_are_unsure() {
+        let chain =
+            PageClassifierChain::default().with_fallback(Arc::new(Fixed(PageType::Pricing, 0.9)));
+        let result = chain
+            .classify(&signals("https://example.com/about-us"))
+            .await;
+        assert_eq!(result.page_type, PageType::Pricing);
+        assert_eq!(result.classifier, "fixed");
+
+        // Confident heuristics never reach the fallback
+        let mut login = signals("https://example.com/login");
+        login.password_inputs = 1;
+        login.inputs = 2;
+        let result = chain.classify(&login).await;
+        assert_eq!(result.page_type, PageType::LoginPage);
+        assert_eq!(result.classifier, "heuristic");
+    }
+}
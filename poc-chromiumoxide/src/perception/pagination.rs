@@ -0,0 +1,310 @@
+// Pagination detection
+//
+// Finds "next page" and numbered page controls of search results and listings, so
+// extraction can follow them page by page.
+
+use crate::browser::Browser;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Lists every link or button that may be a pagination control, with the hints used
+/// to classify it
+const CANDIDATES_SCRIPT: &str = r#"
+    (function() {
+        const selectorOf = el => {
+            if (el.id) return '#' + CSS.escape(el.id);
+            const parts = [];
+            for (let node = el; node && node.nodeType === 1 && node !== document.documentElement; node = node.parentElement) {
+                let part = node.tagName.toLowerCase();
+                const siblings = node.parentElement
+                    ? Array.from(node.parentElement.children).filter(s => s.tagName === node.tagName)
+                    : [];
+                if (siblings.length > 1) part += ':nth-of-type(' + (siblings.indexOf(node) + 1) + ')';
+                parts.unshift(part);
+            }
+            return parts.join(' > ');
+        };
+        const containers = 'nav, [role="navigation"], [class*="pagination" i], [class*="pager" i], [class*="paging" i], [id*="pagination" i]';
+        const visible = el => {
+            const rect = el.getBoundingClientRect();
+            const style = window.getComputedStyle(el);
+            return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+        };
+        const elements = document.querySelectorAll('a, button, [role="link"], [role="button"], span[aria-current], li[aria-current]');
+        const results = [];
+        for (const el of elements) {
+            if (results.length >= 300) break;
+            if (!visible(el)) continue;
+            const text = (el.innerText || el.textContent || '').trim().replace(/\s+/g, ' ');
+            if (text.length > 40) continue;
+            const className = typeof el.className === 'string' ? el.className : '';
+            // Links to '#' or 'javascript:' are handled by script and must be clicked
+            const rawHref = el.getAttribute('href') || '';
+            const navigable = rawHref && !rawHref.startsWith('#') && !/^javascript:/i.test(rawHref);
+            results.push({
+                selector: selectorOf(el),
+                text: text,
+                aria_label: el.getAttribute('aria-label') || el.getAttribute('title') || '',
+                rel: el.getAttribute('rel') || '',
+                class_name: className,
+                href: navigable ? el.href : null,
+                disabled: el.disabled === true
+                    || el.getAttribute('aria-disabled') === 'true'
+                    || /\bdisabled\b/i.test(className),
+                current: el.getAttribute('aria-current') === 'page'
+                    || el.getAttribute('aria-current') === 'true'
+                    || /\b(active|current|selected)\b/i.test(className + ' ' + (el.parentElement && typeof el.parentElement.className === 'string' ? el.parentElement.className : '')),
+                in_pagination: !!el.closest(containers)
+            });
+        }
+        const relNext = document.querySelector('link[rel="next"]');
+        return { candidates: results, rel_next: relNext ? relNext.href : null };
+    })()
+"#;
+
+/// Link or button that may be a pagination control, as reported by the page
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PaginationCandidate {
+    pub selector: String,
+    pub text: String,
+    #[serde(default)]
+    pub aria_label: String,
+    #[serde(default)]
+    pub rel: String,
+    #[serde(default)]
+    pub class_name: String,
+    pub href: Option<String>,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub current: bool,
+    #[serde(default)]
+    pub in_pagination: bool,
+}
+
+/// Control that leads to another page of results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageLink {
+    pub selector: String,
+    /// Target of the link; None for buttons handled by script
+    pub href: Option<String>,
+    /// Page number, for numbered controls
+    pub number: Option<u32>,
+}
+
+impl PageLink {
+    /// URL to navigate to instead of clicking, when the control is a plain link
+    pub fn navigable_href(&self) -> Option<&str> {
+        self.href
+            .as_deref()
+            .filter(|href| href.starts_with("http://") || href.starts_with("https://"))
+    }
+}
+
+/// Pagination controls found on a page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pagination {
+    /// Control for the following page, absent on the last page
+    pub next: Option<PageLink>,
+    /// Numbered page controls, in page order
+    pub pages: Vec<PageLink>,
+    pub current_page: Option<u32>,
+}
+
+impl Pagination {
+    /// Whether the page shows any pagination control
+    pub fn is_paginated(&self) -> bool {
+        self.next.is_some() || !self.pages.is_empty()
+    }
+
+    /// Classify candidates into the next-page control and numbered page controls.
+    /// `rel_next` is the target of a `<link rel="next">` in the document head.
+    pub fn from_candidates(candidates: &[PaginationCandidate], rel_next: Option<&str>) -> Self {
+        let mut pagination = Pagination::default();
+
+        for candidate in candidates {
+            let number = candidate
+                .text
+                .parse::<u32>()
+                .ok()
+                .filter(|_| candidate.in_pagination);
+            if let Some(number) = number {
+                if candidate.current {
+                    pagination.current_page = Some(number);
+                } else if !pagination.pages.iter().any(|p| p.number == Some(number)) {
+                    pagination.pages.push(PageLink {
+                        selector: candidate.selector.clone(),
+                        href: candidate.href.clone(),
+                        number: Some(number),
+                    });
+                }
+            }
+        }
+        pagination.pages.sort_by_key(|p| p.number);
+
+        // Prefer explicit markup, then wording, then the numbered page after the current one
+        let next = candidates
+            .iter()
+            .filter(|c| !c.disabled)
+            .map(|c| (next_score(c), c))
+            .filter(|(score, _)| *score > 0)
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, c)| PageLink {
+                selector: c.selector.clone(),
+                href: c.href.clone(),
+                number: pagination.current_page.map(|n| n + 1),
+            });
+        pagination.next = next
+            .or_else(|| {
+                rel_next.map(|href| PageLink {
+                    selector: String::new(),
+                    href: Some(href.to_string()),
+                    number: pagination.current_page.map(|n| n + 1),
+                })
+            })
+            .or_else(|| {
+                let following = pagination.current_page? + 1;
+                pagination
+                    .pages
+                    .iter()
+                    .find(|p| p.number == Some(following))
+                    .cloned()
+            });
+        pagination
+    }
+}
+
+/// How strongly a candidate looks like a "next page" control, 0 when it does not
+fn next_score(candidate: &PaginationCandidate) -> u32 {
+    let mut score = 0;
+    if candidate.rel.split_whitespace().any(|r| r == "next") {
+        score += 4;
+    }
+    if is_next_label(&candidate.text) || is_next_label(&candidate.aria_label) {
+        score += 3;
+    }
+    if score > 0 && candidate.in_pagination {
+        score += 1;
+    }
+    // Arrow-only or class-only controls count only inside a pagination container
+    if score == 0 && candidate.in_pagination {
+        let class_name = candidate.class_name.to_lowercase();
+        if matches!(candidate.text.as_str(), "›" | "»" | ">" | "→" | ">>")
+            || class_name.split_whitespace().any(|c| c.contains("next"))
+        {
+            score += 2;
+        }
+    }
+    score
+}
+
+/// Whether a label reads as "next page", in the languages the apps target
+pub fn is_next_label(label: &str) -> bool {
+    let label = label
+        .trim()
+        .trim_matches(|c: char| matches!(c, '›' | '»' | '>' | '→') || c.is_whitespace())
+        .to_lowercase();
+    matches!(
+        label.as_str(),
+        "next"
+            | "next page"
+            | "go to next page"
+            | "more results"
+            | "下一页"
+            | "下页"
+            | "suivant"
+            | "page suivante"
+            | "weiter"
+            | "nächste seite"
+            | "siguiente"
+            | "次へ"
+            | "다음"
+    )
+}
+
+/// Pagination controls of the page currently open in the browser
+pub async fn detect(browser: &Browser) -> Result<Pagination> {
+    let result = browser.execute_script(CANDIDATES_SCRIPT).await?;
+    let candidates: Vec<PaginationCandidate> =
+        serde_json::from_value(result["candidates"].clone()).unwrap_or_default();
+    Ok(Pagination::from_candidates(
+        &candidates,
+        result["rel_next"].as_str(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(text: &str, in_pagination: bool) -> PaginationCandidate {
+        PaginationCandidate {
+            selector: format!("#{}", text),
+            text: text.to_string(),
+            href: Some(format!("https://shop.example/search?q=tv&p={}", text)),
+            in_pagination,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recognizes_next_labels() {
+        assert!(is_next_label("Next"));
+        assert!(is_next_label("Next »"));
+        assert!(is_next_label(" 下一页 "));
+        assert!(!is_next_label("Next steps for your order"));
+        assert!(!is_next_label(""));
+    }
+
+    #[test]
+    fn finds_numbered_pages_and_next_control() {
+        let mut current = candidate("2", true);
+        current.current = true;
+        let candidates = vec![
+            candidate("1", true),
+            current,
+            candidate("3", true),
+            candidate("4", true),
+            candidate("Next", true),
+            // Numbers outside a pagination container are prices, ratings and so on
+            candidate("42", false),
+        ];
+        let pagination = Pagination::from_candidates(&candidates, None);
+        assert_eq!(pagination.current_page, Some(2));
+        assert_eq!(
+            pagination
+                .pages
+                .iter()
+                .map(|p| p.number)
+                .collect::<Vec<_>>(),
+            vec![Some(1), Some(3), Some(4)]
+        );
+        let next = pagination.next.unwrap();
+        assert_eq!(next.selector, "#Next");
+        assert_eq!(next.number, Some(3));
+    }
+
+    #[test]
+    fn falls_back_to_the_following_number() {
+        let mut current = candidate("1", true);
+        current.current = true;
+        let candidates = vec![current, candidate("2", true)];
+        let pagination = Pagination::from_candidates(&candidates, None);
+        assert_eq!(pagination.next.unwrap().number, Some(2));
+    }
+
+    #[test]
+    fn disabled_next_means_last_page() {
+        let mut next = candidate("Next", true);
+        next.disabled = true;
+        let pagination = Pagination::from_candidates(&[next], None);
+        assert!(pagination.next.is_none());
+        assert!(!pagination.is_paginated());
+
+        let pagination = Pagination::from_candidates(&[], Some("https://blog.example/page/2"));
+        assert_eq!(
+            pagination.next.unwrap().navigable_href(),
+            Some("https://blog.example/page/2")
+        );
+    }
+}
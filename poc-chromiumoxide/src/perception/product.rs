@@ -0,0 +1,699 @@
+// Product data extraction
+//
+// Reads product pages from schema.org JSON-LD first, then OpenGraph/product meta
+// tags, then DOM heuristics, keeping the first source that provides each field.
+
+use crate::browser::Browser;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Collects every source of product data the page offers
+const PRODUCT_SOURCES_SCRIPT: &str = r#"
+    (function() {
+        const text = el => el ? (el.getAttribute('content') || el.innerText || el.textContent || '').trim().replace(/\s+/g, ' ') : null;
+        const json_ld = Array.from(document.querySelectorAll('script[type="application/ld+json"]'))
+            .map(s => s.textContent);
+        const meta = {};
+        document.querySelectorAll('meta[property], meta[name], meta[itemprop]').forEach(m => {
+            const key = (m.getAttribute('property') || m.getAttribute('name') || m.getAttribute('itemprop') || '').toLowerCase();
+            const content = m.getAttribute('content');
+            if (key && content && !(key in meta)) meta[key] = content;
+        });
+        const first = selectors => {
+            for (const selector of selectors) {
+                const el = document.querySelector(selector);
+                const value = text(el);
+                if (value) return value;
+            }
+            return null;
+        };
+        const images = Array.from(document.querySelectorAll(
+            '[itemprop="image"], [class*="gallery" i] img, [class*="product" i] img, [id*="product" i] img'
+        ))
+            .map(img => img.currentSrc || img.src || img.getAttribute('content') || img.getAttribute('href'))
+            .filter(src => src && !src.startsWith('data:'))
+            .slice(0, 20);
+        const reviews = Array.from(document.querySelectorAll('[itemprop="review"], [class*="review-item" i], [data-hook="review"]'))
+            .slice(0, 10)
+            .map(r => ({
+                author: text(r.querySelector('[itemprop="author"], [class*="author" i]')),
+                rating: text(r.querySelector('[itemprop="ratingValue"], [class*="rating" i], [class*="star" i]')),
+                body: text(r.querySelector('[itemprop="reviewBody"], [class*="body" i], [class*="content" i], p'))
+            }));
+        return {
+            url: location.href,
+            json_ld: json_ld,
+            meta: meta,
+            dom: {
+                title: first(['[itemprop="name"]', 'h1[class*="product" i]', '[class*="product-title" i]', '[id*="productTitle" i]', 'h1']),
+                price: first(['[itemprop="price"]', '[class*="sale-price" i]', '[class*="current-price" i]', '[data-price]', '[class*="price" i]:not([class*="old" i]):not([class*="was" i])', '[id*="price" i]']),
+                currency: first(['[itemprop="priceCurrency"]']),
+                availability: first(['[itemprop="availability"]', '[class*="availability" i]', '[class*="stock" i]', '[id*="availability" i]']),
+                brand: first(['[itemprop="brand"]', '[class*="brand" i]']),
+                description: first(['[itemprop="description"]', '[class*="product-description" i]', '#description']),
+                rating: first(['[itemprop="ratingValue"]', '[class*="average-rating" i]', '[class*="rating-value" i]']),
+                review_count: first(['[itemprop="reviewCount"]', '[itemprop="ratingCount"]', '[class*="review-count" i]', '[class*="reviews-count" i]']),
+                images: images,
+                reviews: reviews
+            }
+        };
+    })()
+"#;
+
+/// Whether a product can be bought now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductAvailability {
+    InStock,
+    LimitedStock,
+    OutOfStock,
+    PreOrder,
+    BackOrder,
+    Discontinued,
+}
+
+impl ProductAvailability {
+    /// Availability from a schema.org URL such as "https://schema.org/InStock" or
+    /// from on-page wording such as "Only 3 left" or "Sold out"
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let key = if value.starts_with("http") {
+            value.rsplit('/').next().unwrap_or(value)
+        } else {
+            value
+        }
+        .to_lowercase();
+        let key = key.as_str();
+        let mentions = |words: &[&str]| words.iter().any(|w| key.contains(w));
+        if mentions(&[
+            "outofstock",
+            "out of stock",
+            "sold out",
+            "soldout",
+            "unavailable",
+            "缺货",
+            "无货",
+            "售罄",
+        ]) {
+            Some(Self::OutOfStock)
+        } else if mentions(&["discontinued"]) {
+            Some(Self::Discontinued)
+        } else if mentions(&["preorder", "pre-order", "pre order", "预售", "预订"]) {
+            Some(Self::PreOrder)
+        } else if mentions(&["backorder", "back-order", "back order"]) {
+            Some(Self::BackOrder)
+        } else if mentions(&[
+            "limitedavailability",
+            "only ",
+            " left",
+            "low stock",
+            "few left",
+        ]) {
+            Some(Self::LimitedStock)
+        } else if mentions(&[
+            "instock",
+            "in stock",
+            "available",
+            "onlineonly",
+            "instoreonly",
+            "有货",
+            "现货",
+        ]) {
+            Some(Self::InStock)
+        } else {
+            None
+        }
+    }
+}
+
+/// Aggregate rating of a product
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductRating {
+    pub value: f64,
+    /// Upper bound of the scale, usually 5
+    pub best: f64,
+    pub count: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductReview {
+    pub author: Option<String>,
+    pub rating: Option<f64>,
+    pub body: Option<String>,
+}
+
+/// Structured data of a product page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProductData {
+    pub url: String,
+    pub title: Option<String>,
+    pub brand: Option<String>,
+    pub sku: Option<String>,
+    pub description: Option<String>,
+    pub price: Option<f64>,
+    /// ISO 4217 code such as "USD"
+    pub currency: Option<String>,
+    pub availability: Option<ProductAvailability>,
+    pub images: Vec<String>,
+    pub rating: Option<ProductRating>,
+    pub reviews: Vec<ProductReview>,
+    /// Sources that contributed, among "json-ld", "opengraph" and "dom"
+    pub sources: Vec<String>,
+}
+
+/// Raw product data as collected from the page
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProductSources {
+    #[serde(default)]
+    pub url: String,
+    /// Text of each `application/ld+json` script
+    #[serde(default)]
+    pub json_ld: Vec<String>,
+    /// Meta tag contents by lowercase property, name or itemprop
+    #[serde(default)]
+    pub meta: HashMap<String, String>,
+    #[serde(default)]
+    pub dom: DomProductHints,
+}
+
+/// Text of the elements that usually hold product fields
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DomProductHints {
+    pub title: Option<String>,
+    pub price: Option<String>,
+    pub currency: Option<String>,
+    pub availability: Option<String>,
+    pub brand: Option<String>,
+    pub description: Option<String>,
+    pub rating: Option<String>,
+    pub review_count: Option<String>,
+    #[serde(default)]
+    pub images: Vec<String>,
+    #[serde(default)]
+    pub reviews: Vec<DomReview>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DomReview {
+    pub author: Option<String>,
+    pub rating: Option<String>,
+    pub body: Option<String>,
+}
+
+impl ProductData {
+    /// Merge the sources, each field taken from the most reliable source providing it
+    pub fn from_sources(sources: &ProductSources) -> Self {
+        let mut product = ProductData {
+            url: sources.url.clone(),
+            ..Default::default()
+        };
+
+        if let Some(node) = sources
+            .json_ld
+            .iter()
+            .filter_map(|text| serde_json::from_str::<Value>(text).ok())
+            .find_map(|value| find_product_node(&value).cloned())
+        {
+            product.merge_json_ld(&node);
+            product.sources.push("json-ld".to_string());
+        }
+        if product.merge_meta(&sources.meta) {
+            product.sources.push("opengraph".to_string());
+        }
+        if product.merge_dom(&sources.dom) {
+            product.sources.push("dom".to_string());
+        }
+        product
+    }
+
+    fn merge_json_ld(&mut self, node: &Value) {
+        self.title = self.title.take().or_else(|| text_of(&node["name"]));
+        self.description = self
+            .description
+            .take()
+            .or_else(|| text_of(&node["description"]));
+        self.brand = self.brand.take().or_else(|| name_of(&node["brand"]));
+        self.sku = self
+            .sku
+            .take()
+            .or_else(|| text_of(&node["sku"]))
+            .or_else(|| text_of(&node["mpn"]));
+        self.images.extend(urls_of(&node["image"]));
+
+        let offer = match &node["offers"] {
+            Value::Array(offers) => offers.first().cloned().unwrap_or_default(),
+            offer => offer.clone(),
+        };
+        if self.price.is_none() {
+            let price = [
+                &offer["price"],
+                &offer["lowPrice"],
+                &offer["priceSpecification"]["price"],
+            ]
+            .into_iter()
+            .find_map(number_of);
+            self.price = price;
+        }
+        self.currency = self
+            .currency
+            .take()
+            .or_else(|| text_of(&offer["priceCurrency"]))
+            .or_else(|| text_of(&offer["priceSpecification"]["priceCurrency"]));
+        self.availability = self.availability.or_else(|| {
+            text_of(&offer["availability"]).and_then(|a| ProductAvailability::parse(&a))
+        });
+
+        let rating = &node["aggregateRating"];
+        if self.rating.is_none() {
+            self.rating = number_of(&rating["ratingValue"]).map(|value| ProductRating {
+                value,
+                best: number_of(&rating["bestRating"]).unwrap_or(5.0),
+                count: number_of(&rating["reviewCount"])
+                    .or_else(|| number_of(&rating["ratingCount"]))
+                    .map(|c| c as u64),
+            });
+        }
+
+        let reviews = match &node["review"] {
+            Value::Array(reviews) => reviews.clone(),
+            Value::Object(_) => vec![node["review"].clone()],
+            _ => vec![],
+        };
+        self.reviews
+            .extend(reviews.iter().map(|review| ProductReview {
+                author: name_of(&review["author"]),
+                rating: number_of(&review["reviewRating"]["ratingValue"]),
+                body: text_of(&review["reviewBody"]).or_else(|| text_of(&review["description"])),
+            }));
+    }
+
+    /// Fill fields from OpenGraph and product meta tags; true if any was used
+    fn merge_meta(&mut self, meta: &HashMap<String, String>) -> bool {
+        let get = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|k| meta.get(*k))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let mut used = false;
+        let mut fill = |field: &mut Option<String>, value: Option<String>| {
+            if field.is_none() && value.is_some() {
+                *field = value;
+                used = true;
+            }
+        };
+        fill(&mut self.title, get(&["og:title", "twitter:title", "name"]));
+        fill(
+            &mut self.description,
+            get(&["og:description", "description"]),
+        );
+        fill(
+            &mut self.brand,
+            get(&["product:brand", "og:brand", "brand"]),
+        );
+        fill(
+            &mut self.currency,
+            get(&[
+                "product:price:currency",
+                "og:price:currency",
+                "pricecurrency",
+            ]),
+        );
+        fill(&mut self.sku, get(&["product:retailer_item_id", "sku"]));
+
+        if self.price.is_none() {
+            if let Some(price) = get(&["product:price:amount", "og:price:amount", "price"])
+                .and_then(|p| parse_price(&p).0)
+            {
+                self.price = Some(price);
+                used = true;
+            }
+        }
+        if self.availability.is_none() {
+            if let Some(availability) =
+                get(&["product:availability", "og:availability", "availability"])
+                    .and_then(|a| ProductAvailability::parse(&a))
+            {
+                self.availability = Some(availability);
+                used = true;
+            }
+        }
+        if let Some(image) = get(&["og:image", "og:image:url", "twitter:image"]) {
+            if !self.images.contains(&image) {
+                self.images.push(image);
+                used = true;
+            }
+        }
+        used
+    }
+
+    /// Fill the remaining fields from page elements; true if any was used
+    fn merge_dom(&mut self, dom: &DomProductHints) -> bool {
+        let mut used = false;
+        let mut fill = |field: &mut Option<String>, value: &Option<String>| {
+            if field.is_none() {
+                if let Some(value) = value.as_ref().filter(|v| !v.is_empty()) {
+                    *field = Some(value.clone());
+                    used = true;
+                }
+            }
+        };
+        fill(&mut self.title, &dom.title);
+        fill(&mut self.brand, &dom.brand);
+        fill(&mut self.description, &dom.description);
+        fill(&mut self.currency, &dom.currency);
+
+        if let Some(text) = &dom.price {
+            let (price, currency) = parse_price(text);
+            if self.price.is_none() && price.is_some() {
+                self.price = price;
+                used = true;
+            }
+            if self.currency.is_none() && currency.is_some() {
+                self.currency = currency;
+                used = true;
+            }
+        }
+        if self.availability.is_none() {
+            self.availability = dom
+                .availability
+                .as_deref()
+                .and_then(ProductAvailability::parse);
+            used |= self.availability.is_some();
+        }
+        if self.rating.is_none() {
+            if let Some(value) = dom.rating.as_deref().and_then(first_number) {
+                self.rating = Some(ProductRating {
+                    value,
+                    best: 5.0,
+                    count: dom
+                        .review_count
+                        .as_deref()
+                        .and_then(|c| parse_price(c).0)
+                        .map(|c| c as u64),
+                });
+                used = true;
+            }
+        }
+        for image in &dom.images {
+            if !self.images.contains(image) {
+                self.images.push(image.clone());
+                used = true;
+            }
+        }
+        if self.reviews.is_empty() {
+            self.reviews = dom
+                .reviews
+                .iter()
+                .filter(|r| r.body.is_some())
+                .map(|r| ProductReview {
+                    author: r.author.clone(),
+                    rating: r.rating.as_deref().and_then(first_number),
+                    body: r.body.clone(),
+                })
+                .collect();
+            used |= !self.reviews.is_empty();
+        }
+        used
+    }
+}
+
+/// First schema.org Product node in a JSON-LD document, searching arrays and @graph
+fn find_product_node(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Array(items) => items.iter().find_map(find_product_node),
+        Value::Object(object) => {
+            let is_product = match object.get("@type") {
+                Some(Value::String(t)) => t == "Product" || t.ends_with("/Product"),
+                Some(Value::Array(types)) => types.iter().any(|t| t == "Product"),
+                _ => false,
+            };
+            if is_product {
+                return Some(value);
+            }
+            object
+                .get("@graph")
+                .or_else(|| object.get("mainEntity"))
+                .and_then(find_product_node)
+        }
+        _ => None,
+    }
+}
+
+fn text_of(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Name of a value that is either a plain string or an object with a name
+fn name_of(value: &Value) -> Option<String> {
+    match value {
+        Value::Array(items) => items.first().and_then(name_of),
+        Value::Object(_) => text_of(&value["name"]),
+        _ => text_of(value),
+    }
+}
+
+fn number_of(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => parse_price(s).0,
+        _ => None,
+    }
+}
+
+/// Image URLs of a string, ImageObject or list of either
+fn urls_of(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(url) => vec![url.clone()],
+        Value::Array(items) => items.iter().flat_map(urls_of).collect(),
+        Value::Object(_) => text_of(&value["url"])
+            .or_else(|| text_of(&value["contentUrl"]))
+            .into_iter()
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn first_number(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let number: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number.trim_end_matches('.').parse().ok()
+}
+
+/// Amount and ISO currency code of a price text such as "$1,299.99",
+/// "1.299,99 €" or "USD 25"
+pub fn parse_price(text: &str) -> (Option<f64>, Option<String>) {
+    const SYMBOLS: &[(&str, &str)] = &[
+        ("US$", "USD"),
+        ("C$", "CAD"),
+        ("A$", "AUD"),
+        ("$", "USD"),
+        ("€", "EUR"),
+        ("£", "GBP"),
+        ("￥", "CNY"),
+        ("元", "CNY"),
+        ("¥", "JPY"),
+        ("₹", "INR"),
+        ("₩", "KRW"),
+    ];
+    const CODES: &[&str] = &[
+        "USD", "EUR", "GBP", "CNY", "RMB", "JPY", "INR", "KRW", "CAD", "AUD", "CHF",
+    ];
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .map(|w| w.to_uppercase())
+        .collect();
+    let currency = CODES
+        .iter()
+        .find(|code| words.iter().any(|w| w == *code))
+        .map(|code| if *code == "RMB" { "CNY" } else { code }.to_string())
+        .or_else(|| {
+            SYMBOLS
+                .iter()
+                .find(|(symbol, _)| text.contains(symbol))
+                .map(|(_, code)| code.to_string())
+        });
+
+    let Some(start) = text.find(|c: char| c.is_ascii_digit()) else {
+        return (None, currency);
+    };
+    let raw: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '\'' | ' ' | '\u{a0}'))
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ','))
+        .collect();
+    let raw = raw.trim_end_matches(['.', ',']);
+
+    // The last separator is the decimal point unless only one kind of separator is
+    // used and it groups three digits, as in "1,299" or "1.299.000"
+    let normalized = match raw.rfind(['.', ',']) {
+        Some(i) => {
+            let decimals = &raw[i + 1..];
+            let both_kinds = raw.contains('.') && raw.contains(',');
+            if decimals.len() == 3 && !both_kinds {
+                raw.replace([',', '.'], "")
+            } else {
+                format!("{}.{}", raw[..i].replace([',', '.'], ""), decimals)
+            }
+        }
+        None => raw.to_string(),
+    };
+    (normalized.parse().ok(), currency)
+}
+
+/// Product data of the page currently open in the browser
+pub async fn extract(browser: &Browser) -> Result<ProductData> {
+    let result = browser.execute_script(PRODUCT_SOURCES_SCRIPT).await?;
+    let sources: ProductSources = serde_json::from_value(result)?;
+    Ok(ProductData::from_sources(&sources))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_prices_in_common_formats() {
+        assert_eq!(
+            parse_price("$1,299.99"),
+            (Some(1299.99), Some("USD".into()))
+        );
+        assert_eq!(
+            parse_price("1.299,99 €"),
+            (Some(1299.99), Some("EUR".into()))
+        );
+        assert_eq!(parse_price("£25"), (Some(25.0), Some("GBP".into())));
+        assert_eq!(parse_price("USD 1,299"), (Some(1299.0), Some("USD".into())));
+        assert_eq!(parse_price("19,99 €"), (Some(19.99), Some("EUR".into())));
+        assert_eq!(
+            parse_price("1 299,00 €"),
+            (Some(1299.0), Some("EUR".into()))
+        );
+        assert_eq!(parse_price("Price on request"), (None, None));
+    }
+
+    #[test]
+    fn reads_availability() {
+        assert_eq!(
+            ProductAvailability::parse("https://schema.org/InStock"),
+            Some(ProductAvailability::InStock)
+        );
+        assert_eq!(
+            ProductAvailability::parse("http://schema.org/OutOfStock"),
+            Some(ProductAvailability::OutOfStock)
+        );
+        assert_eq!(
+            ProductAvailability::parse("Only 3 left in stock"),
+            Some(ProductAvailability::LimitedStock)
+        );
+        assert_eq!(
+            ProductAvailability::parse("Currently unavailable."),
+            Some(ProductAvailability::OutOfStock)
+        );
+        assert_eq!(ProductAvailability::parse("Free delivery"), None);
+    }
+
+    #[test]
+    fn prefers_json_ld_and_fills_gaps_from_meta_and_dom() {
+        let json_ld = r#"{
+            "@context": "https://schema.org",
+            "@graph": [
+                {"@type": "BreadcrumbList", "itemListElement": []},
+                {
+                    "@type": "Product",
+                    "name": "Trail Runner 2",
+                    "brand": {"@type": "Brand", "name": "Acme"},
+                    "sku": "TR2-42",
+                    "image": ["https://shop.example/tr2.jpg"],
+                    "offers": {
+                        "@type": "Offer",
+                        "price": "129.95",
+                        "priceCurrency": "EUR",
+                        "availability": "https://schema.org/InStock"
+                    },
+                    "aggregateRating": {"ratingValue": "4.6", "reviewCount": 212},
+                    "review": {
+                        "author": {"@type": "Person", "name": "Sam"},
+                        "reviewRating": {"ratingValue": 5},
+                        "reviewBody": "Light and grippy."
+                    }
+                }
+            ]
+        }"#;
+        let sources = ProductSources {
+            url: "https://shop.example/tr2".to_string(),
+            json_ld: vec!["not json".to_string(), json_ld.to_string()],
+            meta: HashMap::from([
+                (
+                    "og:title".to_string(),
+                    "Trail Runner 2 | Acme Shop".to_string(),
+                ),
+                (
+                    "og:description".to_string(),
+                    "Lightweight trail shoe".to_string(),
+                ),
+                (
+                    "og:image".to_string(),
+                    "https://shop.example/og.jpg".to_string(),
+                ),
+            ]),
+            dom: DomProductHints {
+                price: Some("€ 99,00".to_string()),
+                ..Default::default()
+            },
+        };
+
+        let product = ProductData::from_sources(&sources);
+        assert_eq!(product.title.as_deref(), Some("Trail Runner 2"));
+        assert_eq!(product.brand.as_deref(), Some("Acme"));
+        assert_eq!(product.price, Some(129.95));
+        assert_eq!(product.currency.as_deref(), Some("EUR"));
+        assert_eq!(product.availability, Some(ProductAvailability::InStock));
+        assert_eq!(
+            product.description.as_deref(),
+            Some("Lightweight trail shoe")
+        );
+        assert_eq!(
+            product.images,
+            vec![
+                "https://shop.example/tr2.jpg",
+                "https://shop.example/og.jpg"
+            ]
+        );
+        let rating = product.rating.unwrap();
+        assert_eq!((rating.value, rating.count), (4.6, Some(212)));
+        assert_eq!(product.reviews[0].author.as_deref(), Some("Sam"));
+        assert_eq!(product.sources, vec!["json-ld", "opengraph"]);
+    }
+
+    #[test]
+    fn falls_back_to_dom_heuristics() {
+        let sources = ProductSources {
+            dom: DomProductHints {
+                title: Some("Espresso Machine".to_string()),
+                price: Some("Now only $349.00".to_string()),
+                availability: Some("Out of stock".to_string()),
+                rating: Some("4.2 out of 5 stars".to_string()),
+                review_count: Some("1,024 ratings".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let product = ProductData::from_sources(&sources);
+        assert_eq!(product.title.as_deref(), Some("Espresso Machine"));
+        assert_eq!(product.price, Some(349.0));
+        assert_eq!(product.currency.as_deref(), Some("USD"));
+        assert_eq!(product.availability, Some(ProductAvailability::OutOfStock));
+        assert_eq!(product.rating.unwrap().count, Some(1024));
+        assert_eq!(product.sources, vec!["dom"]);
+    }
+}
@@ -0,0 +1,456 @@
+// Perception Schemas - Versioned wire format of perception results
+//
+// `PerceptionResult`, `PerceivedElement` and `EnhancedPageAnalysis` go out over
+// the API stamped with a `schema_version`. Each version has a published JSON
+// Schema, and shims translate payloads between versions: `upgrade` brings older
+// payloads up to the current shape, `downgrade` renders current payloads for
+// consumers pinned to an older one. A struct change that alters the wire format
+// bumps SCHEMA_VERSION and adds a step to both shims.
+//
+// Version history:
+//   1 - the original shapes
+//   2 - PerceivedElement gained `region` and `stability`; EnhancedPageAnalysis
+//       gained `web_vitals` and, in its context, `classification` and
+//       `ocr_words`; PerceptionResult gained the `Accessibility` layer
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Version of the shapes this build produces
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Oldest version the shims can still read and produce
+pub const OLDEST_SCHEMA_VERSION: u32 = 1;
+
+/// Perception payloads with a published schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaKind {
+    PerceptionResult,
+    PerceivedElement,
+    EnhancedPageAnalysis,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 3] = [
+        SchemaKind::PerceptionResult,
+        SchemaKind::PerceivedElement,
+        SchemaKind::EnhancedPageAnalysis,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaKind::PerceptionResult => "perception_result",
+            SchemaKind::PerceivedElement => "perceived_element",
+            SchemaKind::EnhancedPageAnalysis => "enhanced_page_analysis",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// A payload stamped with the schema version it follows. The version sits next
+/// to the payload's own fields, so unversioned consumers keep working.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    pub fn current(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+}
+
+fn check_version(version: u32) -> Result<()> {
+    if (OLDEST_SCHEMA_VERSION..=SCHEMA_VERSION).contains(&version) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Unsupported perception schema version {} (supported: {}-{})",
+            version,
+            OLDEST_SCHEMA_VERSION,
+            SCHEMA_VERSION
+        ))
+    }
+}
+
+/// Version a payload declares; payloads from before versioning are version 1
+pub fn version_of(value: &Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map_or(OLDEST_SCHEMA_VERSION, |v| v as u32)
+}
+
+fn stamp(value: &mut Value, version: u32) {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema_version".to_string(), json!(version));
+    }
+}
+
+/// Bring a payload of any supported version up to SCHEMA_VERSION, filling in
+/// the fields added since with their defaults
+pub fn upgrade(kind: SchemaKind, mut value: Value) -> Result<Value> {
+    let version = version_of(&value);
+    check_version(version)?;
+    if version < 2 {
+        v1_to_v2(kind, &mut value);
+    }
+    stamp(&mut value, SCHEMA_VERSION);
+    Ok(value)
+}
+
+/// Render a current payload in the shape of `target`, for consumers that have
+/// not moved to the current version
+pub fn downgrade(kind: SchemaKind, mut value: Value, target: u32) -> Result<Value> {
+    check_version(target)?;
+    if target < 2 {
+        v2_to_v1(kind, &mut value);
+    }
+    stamp(&mut value, target);
+    Ok(value)
+}
+
+/// Read a payload of any supported version into the current types
+pub fn parse<T: DeserializeOwned>(kind: SchemaKind, value: Value) -> Result<T> {
+    let mut value = upgrade(kind, value)?;
+    // The stamp is not a field of the types, and breaks externally tagged enums
+    if let Some(object) = value.as_object_mut() {
+        object.remove("schema_version");
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// `value` serialized and rendered for `version`, current when None
+pub fn render<T: Serialize>(kind: SchemaKind, value: &T, version: Option<u32>) -> Result<Value> {
+    let mut value = serde_json::to_value(value)?;
+    stamp(&mut value, SCHEMA_VERSION);
+    match version {
+        Some(version) if version != SCHEMA_VERSION => downgrade(kind, value, version),
+        _ => Ok(value),
+    }
+}
+
+fn insert_default(object: &mut Map<String, Value>, key: &str, default: Value) {
+    object.entry(key.to_string()).or_insert(default);
+}
+
+fn v1_to_v2(kind: SchemaKind, value: &mut Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    match kind {
+        SchemaKind::PerceivedElement => {
+            insert_default(object, "region", json!("unknown"));
+            insert_default(object, "stability", Value::Null);
+        }
+        SchemaKind::EnhancedPageAnalysis => {
+            insert_default(object, "web_vitals", Value::Null);
+            if let Some(context) = object.get_mut("context").and_then(Value::as_object_mut) {
+                insert_default(context, "classification", Value::Null);
+                insert_default(context, "ocr_words", json!([]));
+            }
+        }
+        // Version 1 layers are all still valid
+        SchemaKind::PerceptionResult => {}
+    }
+}
+
+fn v2_to_v1(kind: SchemaKind, value: &mut Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    match kind {
+        SchemaKind::PerceivedElement => {
+            object.remove("region");
+            object.remove("stability");
+        }
+        SchemaKind::EnhancedPageAnalysis => {
+            object.remove("web_vitals");
+            if let Some(context) = object.get_mut("context").and_then(Value::as_object_mut) {
+                context.remove("classification");
+                context.remove("ocr_words");
+            }
+            if let Some(layered) = object.get_mut("layered_result") {
+                v2_to_v1(SchemaKind::PerceptionResult, layered);
+            }
+        }
+        SchemaKind::PerceptionResult => {
+            // Version 1 has no Accessibility layer; its flattened Lightning
+            // fields are the closest thing an old consumer understands
+            if let Some(Value::Object(mut accessibility)) = object.remove("Accessibility") {
+                for key in [
+                    "interactive_elements",
+                    "landmarks",
+                    "headings",
+                    "named_elements",
+                ] {
+                    accessibility.remove(key);
+                }
+                object.insert("Lightning".to_string(), Value::Object(accessibility));
+            }
+        }
+    }
+}
+
+/// Published JSON Schema of `kind` at `version`. Nested structures are described
+/// by their type only and objects allow extra properties, so adding a field is
+/// not a breaking change.
+pub fn json_schema(kind: SchemaKind, version: u32) -> Result<Value> {
+    check_version(version)?;
+    let (title, mut schema) = match kind {
+        SchemaKind::PerceivedElement => ("Perceived element", perceived_element(version)),
+        SchemaKind::PerceptionResult => ("Perception result", perception_result(version)),
+        SchemaKind::EnhancedPageAnalysis => {
+            ("Enhanced page analysis", enhanced_page_analysis(version))
+        }
+    };
+    if let Some(object) = schema.as_object_mut() {
+        object.insert(
+            "$schema".into(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        object.insert(
+            "$id".into(),
+            json!(format!(
+                "https://rainbow-browser.ai/schemas/{}/v{}.json",
+                kind.name(),
+                version
+            )),
+        );
+        object.insert("title".into(), json!(title));
+        if let Some(properties) = object.get_mut("properties").and_then(Value::as_object_mut) {
+            properties.insert("schema_version".into(), json!({ "const": version }));
+        }
+    }
+    Ok(schema)
+}
+
+fn object_schema(required: &[&str], properties: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": required,
+        "properties": properties,
+        "additionalProperties": true
+    })
+}
+
+fn perceived_element(version: u32) -> Value {
+    let mut properties = json!({
+        "selector": { "type": "string" },
+        "text": { "type": "string" },
+        "element_type": { "enum": [
+            "Button", "Link", "Input", "Select", "TextArea", "Image", "Text", "Container",
+            "Navigation", "Modal", "Dropdown", "Checkbox", "Radio", "Unknown"
+        ] },
+        "clickable": { "type": "boolean" },
+        "visible": { "type": "boolean" },
+        "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+        "attributes": { "type": "object", "additionalProperties": { "type": "string" } },
+        "position": {
+            "type": ["object", "null"],
+            "properties": {
+                "x": { "type": "number" },
+                "y": { "type": "number" },
+                "width": { "type": "number" },
+                "height": { "type": "number" }
+            }
+        },
+        "visual_context": { "type": ["object", "null"] }
+    });
+    if version >= 2 {
+        properties["region"] = json!({ "enum": [
+            "header", "navigation", "main", "sidebar", "footer", "modal", "unknown"
+        ] });
+        properties["stability"] = json!({ "type": ["number", "null"], "minimum": 0, "maximum": 1 });
+    }
+    object_schema(
+        &[
+            "selector",
+            "text",
+            "element_type",
+            "clickable",
+            "visible",
+            "confidence",
+        ],
+        properties,
+    )
+}
+
+fn perception_result(version: u32) -> Value {
+    let lightning = json!({
+        "url": { "type": "string" },
+        "title": { "type": "string" },
+        "ready_state": { "type": "string" },
+        "clickable_count": { "type": "integer", "minimum": 0 },
+        "input_count": { "type": "integer", "minimum": 0 },
+        "link_count": { "type": "integer", "minimum": 0 },
+        "form_count": { "type": "integer", "minimum": 0 },
+        "perception_time_ms": { "type": "integer", "minimum": 0 }
+    });
+    let required = ["url", "title", "clickable_count", "perception_time_ms"];
+    // Each layer flattens the one below it and adds its own sections
+    let layer = |sections: &[&str]| {
+        let mut properties = lightning.clone();
+        for section in sections {
+            properties[*section] = json!({ "type": ["object", "array"] });
+        }
+        object_schema(&required, properties)
+    };
+    let quick = vec![
+        "interactive_elements",
+        "visible_text_blocks",
+        "form_fields",
+        "layout_info",
+    ];
+    let mut standard = quick.clone();
+    standard.extend([
+        "semantic_structure",
+        "accessibility_info",
+        "computed_styles",
+        "performance_metrics",
+    ]);
+    let mut deep = standard.clone();
+    deep.extend([
+        "dom_analysis",
+        "visual_analysis",
+        "behavioral_patterns",
+        "ai_insights",
+    ]);
+
+    let mut layers = vec![
+        ("Lightning", layer(&[])),
+        ("Quick", layer(&quick)),
+        ("Standard", layer(&standard)),
+        ("Deep", layer(&deep)),
+    ];
+    if version >= 2 {
+        layers.push((
+            "Accessibility",
+            layer(&[
+                "interactive_elements",
+                "landmarks",
+                "headings",
+                "named_elements",
+            ]),
+        ));
+    }
+    json!({
+        "type": "object",
+        "description": "Exactly one key naming the perception layer that produced the result",
+        "oneOf": layers
+            .into_iter()
+            .map(|(name, schema)| {
+                let mut properties = Map::new();
+                properties.insert(name.to_string(), schema);
+                json!({ "type": "object", "required": [name], "properties": properties })
+            })
+            .collect::<Vec<_>>(),
+        "properties": {}
+    })
+}
+
+fn enhanced_page_analysis(version: u32) -> Value {
+    let mut context = json!({
+        "current_url": { "type": "string" },
+        "page_type": { "type": "string" },
+        "last_action": { "type": ["string", "null"] },
+        "last_element": { "type": ["string", "null"] },
+        "form_state": { "type": "object" },
+        "named_elements": { "type": "object" }
+    });
+    let mut properties = json!({
+        "layered_result": perception_result(version),
+        "chromium_result": { "type": ["object", "null"] },
+        "analysis_timestamp": { "type": "string", "format": "date-time" },
+        "performance_score": { "type": "number" }
+    });
+    if version >= 2 {
+        context["classification"] = json!({ "type": ["object", "null"] });
+        context["ocr_words"] = json!({ "type": "array" });
+        properties["web_vitals"] = json!({ "type": ["object", "null"] });
+    }
+    properties["context"] = object_schema(&["current_url", "page_type"], context);
+    object_schema(
+        &[
+            "layered_result",
+            "context",
+            "analysis_timestamp",
+            "performance_score",
+        ],
+        properties,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perception::PerceivedElement;
+
+    fn v1_element() -> Value {
+        json!({
+            "selector": "#buy",
+            "text": "Buy now",
+            "element_type": "Button",
+            "clickable": true,
+            "visible": true,
+            "confidence": 0.9,
+            "attributes": {},
+            "position": null,
+            "visual_context": null
+        })
+    }
+
+    #[test]
+    fn old_payloads_upgrade_to_current_types() {
+        let element: PerceivedElement = parse(SchemaKind::PerceivedElement, v1_element()).unwrap();
+        assert_eq!(element.selector, "#buy");
+        assert_eq!(element.stability, None);
+
+        let upgraded = upgrade(SchemaKind::PerceivedElement, v1_element()).unwrap();
+        assert_eq!(upgraded["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(upgraded["region"], json!("unknown"));
+
+        let future = json!({ "schema_version": SCHEMA_VERSION + 1 });
+        assert!(upgrade(SchemaKind::PerceivedElement, future).is_err());
+    }
+
+    #[test]
+    fn downgrades_drop_what_old_consumers_do_not_know() {
+        let element: PerceivedElement = parse(SchemaKind::PerceivedElement, v1_element()).unwrap();
+        let v1 = render(SchemaKind::PerceivedElement, &element, Some(1)).unwrap();
+        assert_eq!(v1["schema_version"], json!(1));
+        assert!(v1.get("region").is_none() && v1.get("stability").is_none());
+
+        let accessibility = json!({
+            "Accessibility": { "url": "https://example.com", "title": "Example", "landmarks": [] }
+        });
+        let v1 = downgrade(SchemaKind::PerceptionResult, accessibility, 1).unwrap();
+        assert_eq!(v1["Lightning"]["title"], json!("Example"));
+        assert!(v1["Lightning"].get("landmarks").is_none());
+    }
+
+    #[test]
+    fn schemas_differ_only_by_version_additions() {
+        let v1 = json_schema(SchemaKind::PerceivedElement, 1).unwrap();
+        let v2 = json_schema(SchemaKind::PerceivedElement, 2).unwrap();
+        assert!(v1["properties"].get("region").is_none());
+        assert!(v2["properties"].get("region").is_some());
+        assert_eq!(v2["properties"]["schema_version"]["const"], json!(2));
+        assert!(json_schema(SchemaKind::EnhancedPageAnalysis, 0).is_err());
+        assert_eq!(
+            SchemaKind::from_name("perception_result"),
+            Some(SchemaKind::PerceptionResult)
+        );
+    }
+}
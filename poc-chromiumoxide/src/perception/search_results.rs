@@ -0,0 +1,428 @@
+// Search results extraction
+//
+// Reads result lists of the major search engines with per-engine selectors, and of
+// any other result page by looking for repeated heading links, then ranks them
+// against what the user is looking for.
+
+use crate::browser::Browser;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use url::Url;
+
+/// Collects raw results, using `__PROFILE__` selectors when the engine is known and
+/// repeated heading links otherwise
+const RESULTS_SCRIPT: &str = r#"
+    (function() {
+        const profile = __PROFILE__;
+        const clean = s => (s || '').trim().replace(/\s+/g, ' ');
+        const excluded = el => !!el.closest('nav, header, footer, aside, [role="navigation"], [role="banner"], [role="contentinfo"]');
+        const fromProfile = () => {
+            if (!profile) return [];
+            return Array.from(document.querySelectorAll(profile.result)).map(item => {
+                const titleEl = item.querySelector(profile.title);
+                const link = item.querySelector(profile.link) || (titleEl && titleEl.closest('a'));
+                const snippetEl = item.querySelector(profile.snippet);
+                return {
+                    title: clean(titleEl ? titleEl.textContent : link ? link.textContent : ''),
+                    url: link ? link.href : '',
+                    snippet: clean(snippetEl ? snippetEl.textContent : ''),
+                    is_ad: !!(profile.ad && (item.closest(profile.ad) || item.querySelector(profile.ad)))
+                };
+            });
+        };
+        const generic = () => {
+            const links = Array.from(document.querySelectorAll('h2 a[href], h3 a[href], h4 a[href], a[href] h2, a[href] h3, a[href] h4'))
+                .map(el => el.tagName === 'A' ? el : el.closest('a'))
+                .filter(a => a && !excluded(a));
+            return links.map(a => {
+                const heading = a.closest('h2, h3, h4') || a.querySelector('h2, h3, h4');
+                const container = a.closest('li, article, [class*="result" i], [class*="card" i], [class*="item" i]') || (heading || a).parentElement;
+                const title = clean(heading ? heading.textContent : a.textContent);
+                let snippet = clean(container ? container.textContent : '');
+                if (snippet.startsWith(title)) snippet = snippet.slice(title.length).trim();
+                const className = container && typeof container.className === 'string' ? container.className : '';
+                return { title, url: a.href, snippet: snippet.slice(0, 300), is_ad: /\b(ad|ads|sponsored)\b/i.test(className) };
+            });
+        };
+        let results = fromProfile();
+        const source = results.length ? profile.name : 'generic';
+        if (!results.length) results = generic();
+        return { source, results: results.slice(0, 50) };
+    })()
+"#;
+
+/// Selectors for the result list of one search engine
+#[derive(Debug, Serialize)]
+pub struct EngineProfile {
+    pub name: &'static str,
+    #[serde(skip)]
+    hosts: &'static [&'static str],
+    result: &'static str,
+    title: &'static str,
+    link: &'static str,
+    snippet: &'static str,
+    ad: &'static str,
+}
+
+const ENGINES: &[EngineProfile] = &[
+    EngineProfile {
+        name: "google",
+        hosts: &["google."],
+        result: "#search div.g, #rso > div > div[data-hveid]:has(h3), #tads div[data-text-ad]",
+        title: "h3",
+        link: "a:has(h3), a[href]",
+        snippet: "div[data-sncf], .VwiC3b, [style*='-webkit-line-clamp']",
+        ad: "#tads, [data-text-ad]",
+    },
+    EngineProfile {
+        name: "bing",
+        hosts: &["bing.com"],
+        result: "#b_results > li.b_algo, #b_results > li.b_ad li",
+        title: "h2",
+        link: "h2 a",
+        snippet: ".b_caption p, .b_lineclamp2, p",
+        ad: ".b_ad",
+    },
+    EngineProfile {
+        name: "duckduckgo",
+        hosts: &["duckduckgo.com"],
+        result: "article[data-testid='result'], .result.results_links",
+        title: "h2, .result__title",
+        link: "a[data-testid='result-title-a'], a.result__a",
+        snippet: "[data-result='snippet'], .result__snippet",
+        ad: ".result--ad, [data-testid='ad']",
+    },
+    EngineProfile {
+        name: "baidu",
+        hosts: &["baidu.com"],
+        result: "#content_left > div.result, #content_left > div.c-container",
+        title: "h3",
+        link: "h3 a",
+        snippet: ".c-abstract, [class*='content-right'], [class*='abstract']",
+        ad: "[data-tuiguang], .ec_tuiguang_pplink",
+    },
+    EngineProfile {
+        name: "yahoo",
+        hosts: &["search.yahoo."],
+        result: "#web li div.algo",
+        title: "h3",
+        link: "h3 a",
+        snippet: ".compText p, .compText",
+        ad: ".searchCenterTopAds, .searchCenterBottomAds",
+    },
+];
+
+/// Query string parameters engines and site searches put the query in
+const QUERY_PARAMS: &[&str] = &[
+    "q",
+    "query",
+    "wd",
+    "word",
+    "p",
+    "k",
+    "keywords",
+    "search",
+    "search_query",
+    "text",
+];
+
+/// Profile of the search engine serving `url`, if it is one of the known engines
+pub fn engine_for(url: &str) -> Option<&'static EngineProfile> {
+    let host = Url::parse(url).ok()?.host_str()?.to_lowercase();
+    ENGINES
+        .iter()
+        .find(|engine| engine.hosts.iter().any(|h| host.contains(h)))
+}
+
+/// Result as collected from the page
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RawSearchResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub snippet: String,
+    #[serde(default)]
+    pub is_ad: bool,
+}
+
+/// One entry of a result list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// 1-based position in the list as shown on the page
+    pub position: usize,
+    pub title: String,
+    /// Destination URL, with engine redirects resolved
+    pub url: String,
+    pub domain: String,
+    pub snippet: String,
+    pub is_ad: bool,
+    /// Match with the page's query, 0.0 to 1.0
+    pub relevance: f32,
+}
+
+/// Result list of a search page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchResults {
+    /// Engine name, or "generic" for other result pages
+    pub engine: String,
+    pub query: Option<String>,
+    pub results: Vec<SearchResult>,
+}
+
+impl SearchResults {
+    /// Clean up raw results: resolve redirects, drop entries without a title or web
+    /// URL, remove duplicates and score them against the query
+    pub fn from_raw(engine: &str, page_url: &str, raw: Vec<RawSearchResult>) -> Self {
+        let query = query_of(page_url);
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+        for item in raw {
+            let url = resolve_redirect(&item.url);
+            let Ok(parsed) = Url::parse(&url) else {
+                continue;
+            };
+            if item.title.is_empty()
+                || !matches!(parsed.scheme(), "http" | "https")
+                || !seen.insert(url.clone())
+            {
+                continue;
+            }
+            let domain = parsed
+                .host_str()
+                .unwrap_or_default()
+                .trim_start_matches("www.")
+                .to_string();
+            let mut result = SearchResult {
+                position: results.len() + 1,
+                title: item.title,
+                url,
+                domain,
+                snippet: item.snippet,
+                is_ad: item.is_ad,
+                relevance: 0.0,
+            };
+            if let Some(query) = &query {
+                result.relevance = relevance(&result, query);
+            }
+            results.push(result);
+        }
+        Self {
+            engine: engine.to_string(),
+            query,
+            results,
+        }
+    }
+
+    /// Results ordered by how well they fit `intent`, e.g. "official python docs",
+    /// with ties going to the higher-placed result and ads last
+    pub fn ranked(&self, intent: &str) -> Vec<&SearchResult> {
+        let mut scored: Vec<(f32, &SearchResult)> = self
+            .results
+            .iter()
+            .map(|result| {
+                // Position is a weak prior: engines already order by relevance
+                let prior = 0.1 / result.position as f32;
+                let mut score = relevance(result, intent) + prior;
+                if result.is_ad {
+                    score *= 0.5;
+                }
+                (score, result)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        scored.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Organic result at a 1-based position, counting past ads
+    pub fn at(&self, position: usize) -> Option<&SearchResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.is_ad)
+            .nth(position.checked_sub(1)?)
+    }
+}
+
+/// Share of the intent's words found in a result, weighting the title highest
+fn relevance(result: &SearchResult, intent: &str) -> f32 {
+    let intent = intent.to_lowercase();
+    let terms: Vec<&str> = intent
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1 && !STOP_WORDS.contains(w))
+        .collect();
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let title = result.title.to_lowercase();
+    let rest = format!("{} {}", result.snippet, result.domain).to_lowercase();
+    let score: f32 = terms
+        .iter()
+        .map(|term| {
+            if title.contains(term) {
+                1.0
+            } else if rest.contains(term) {
+                0.5
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    score / terms.len() as f32
+}
+
+const STOP_WORDS: &[&str] = &[
+    "the", "a", "an", "of", "for", "to", "in", "on", "and", "or", "with", "open", "result", "page",
+    "site", "website", "link",
+];
+
+/// Destination of engine redirect links such as Google's "/url?q=" and
+/// DuckDuckGo's "/l/?uddg="
+pub fn resolve_redirect(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let host = parsed.host_str().unwrap_or_default();
+    let param = match parsed.path() {
+        "/url" if host.contains("google.") => &["q", "url"][..],
+        "/l/" if host.contains("duckduckgo.com") => &["uddg"][..],
+        "/link" if host.contains("baidu.com") => &["url"][..],
+        _ => return url.to_string(),
+    };
+    parsed
+        .query_pairs()
+        .find(|(key, value)| param.contains(&key.as_ref()) && value.starts_with("http"))
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Search query in a result page URL
+pub fn query_of(page_url: &str) -> Option<String> {
+    let parsed = Url::parse(page_url).ok()?;
+    QUERY_PARAMS.iter().find_map(|param| {
+        parsed
+            .query_pairs()
+            .find(|(key, value)| key == param && !value.trim().is_empty())
+            .map(|(_, value)| value.trim().to_string())
+    })
+}
+
+/// Results of the result page currently open in the browser
+pub async fn extract(browser: &Browser) -> Result<SearchResults> {
+    let page_url = browser.current_url().await?;
+    let profile = serde_json::to_string(&engine_for(&page_url))?;
+    let result = browser
+        .execute_script(&RESULTS_SCRIPT.replace("__PROFILE__", &profile))
+        .await?;
+    let raw: Vec<RawSearchResult> =
+        serde_json::from_value(result["results"].clone()).unwrap_or_default();
+    let engine = result["source"].as_str().unwrap_or("generic");
+    Ok(SearchResults::from_raw(engine, &page_url, raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(title: &str, url: &str, snippet: &str) -> RawSearchResult {
+        RawSearchResult {
+            title: title.to_string(),
+            url: url.to_string(),
+            snippet: snippet.to_string(),
+            is_ad: false,
+        }
+    }
+
+    #[test]
+    fn recognizes_engines_by_host() {
+        assert_eq!(
+            engine_for("https://www.google.co.uk/search?q=rust").map(|e| e.name),
+            Some("google")
+        );
+        assert_eq!(
+            engine_for("https://www.baidu.com/s?wd=rust").map(|e| e.name),
+            Some("baidu")
+        );
+        assert!(engine_for("https://shop.example/search?q=tv").is_none());
+        assert_eq!(
+            query_of("https://www.baidu.com/s?ie=utf-8&wd=rust%20lang").as_deref(),
+            Some("rust lang")
+        );
+    }
+
+    #[test]
+    fn resolves_redirect_links() {
+        assert_eq!(
+            resolve_redirect("https://www.google.com/url?sa=t&url=https%3A%2F%2Fdocs.rs%2F&ved=x"),
+            "https://docs.rs/"
+        );
+        assert_eq!(
+            resolve_redirect("https://duckduckgo.com/l/?uddg=https%3A%2F%2Fwww.rust-lang.org%2F"),
+            "https://www.rust-lang.org/"
+        );
+        assert_eq!(
+            resolve_redirect("https://www.rust-lang.org/learn"),
+            "https://www.rust-lang.org/learn"
+        );
+    }
+
+    #[test]
+    fn cleans_and_numbers_results() {
+        let results = SearchResults::from_raw(
+            "google",
+            "https://www.google.com/search?q=rust+book",
+            vec![
+                raw(
+                    "The Rust Book",
+                    "https://doc.rust-lang.org/book/",
+                    "The Rust book",
+                ),
+                raw("", "https://example.com/", "no title"),
+                raw("Duplicate", "https://doc.rust-lang.org/book/", ""),
+                raw("Images", "javascript:void(0)", ""),
+                raw(
+                    "Rust (video game)",
+                    "https://en.wikipedia.org/wiki/Rust_(video_game)",
+                    "Survival game",
+                ),
+            ],
+        );
+        assert_eq!(results.query.as_deref(), Some("rust book"));
+        assert_eq!(results.results.len(), 2);
+        assert_eq!(results.results[1].position, 2);
+        assert_eq!(results.results[1].domain, "en.wikipedia.org");
+        assert_eq!(results.results[0].relevance, 1.0);
+        assert_eq!(results.results[1].relevance, 0.5);
+    }
+
+    #[test]
+    fn ranks_results_for_an_intent() {
+        let mut ad = raw(
+            "Rust hosting - cheap servers",
+            "https://ads.example/rust",
+            "",
+        );
+        ad.is_ad = true;
+        let results = SearchResults::from_raw(
+            "bing",
+            "https://www.bing.com/search?q=rust",
+            vec![
+                ad,
+                raw(
+                    "Rust (video game)",
+                    "https://store.steampowered.com/app/252490",
+                    "Survival game",
+                ),
+                raw(
+                    "Rust Programming Language",
+                    "https://www.rust-lang.org/",
+                    "Reliable and efficient software",
+                ),
+            ],
+        );
+        let ranked = results.ranked("the rust programming language website");
+        assert_eq!(ranked[0].domain, "rust-lang.org");
+        assert_eq!(ranked.last().unwrap().domain, "ads.example");
+        // Position skips ads
+        assert_eq!(results.at(1).unwrap().domain, "store.steampowered.com");
+    }
+}
@@ -1,5 +1,6 @@
 // Semantic understanding of page content and structure
 
+use crate::browser::Browser;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
@@ -71,6 +72,126 @@ pub enum RelationshipType {
     DependsOn,   // Element depends on another
 }
 
+/// Part of the page layout an element belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageRegion {
+    Header,
+    Navigation,
+    Main,
+    Sidebar,
+    Footer,
+    /// Dialogs and popups over the page
+    Modal,
+    #[default]
+    Unknown,
+}
+
+impl PageRegion {
+    fn from_js(name: &str) -> Self {
+        match name {
+            "header" => PageRegion::Header,
+            "navigation" => PageRegion::Navigation,
+            "main" => PageRegion::Main,
+            "sidebar" => PageRegion::Sidebar,
+            "footer" => PageRegion::Footer,
+            "modal" => PageRegion::Modal,
+            _ => PageRegion::Unknown,
+        }
+    }
+
+    /// Site chrome repeated on every page rather than page content
+    pub fn is_boilerplate(&self) -> bool {
+        matches!(
+            self,
+            PageRegion::Header | PageRegion::Navigation | PageRegion::Sidebar | PageRegion::Footer
+        )
+    }
+
+    /// Region a description asks for, as in "the search box in the header" or
+    /// "links at the bottom of the page"
+    pub fn from_description(description: &str) -> Option<Self> {
+        let description = description.to_lowercase();
+        let after_preposition = |phrase: &str| {
+            [
+                "in the ",
+                "inside the ",
+                "within the ",
+                "at the ",
+                "on the ",
+                "from the ",
+                "in ",
+            ]
+            .iter()
+            .any(|preposition| description.contains(&format!("{}{}", preposition, phrase)))
+        };
+        const REGIONS: &[(&[&str], PageRegion)] = &[
+            (
+                &["header", "top bar", "masthead", "top of the page", "banner"],
+                PageRegion::Header,
+            ),
+            (
+                &["navigation", "nav bar", "navbar", "nav", "main menu"],
+                PageRegion::Navigation,
+            ),
+            (&["footer", "bottom of the page"], PageRegion::Footer),
+            (&["sidebar", "side bar", "side panel"], PageRegion::Sidebar),
+            (&["dialog", "modal", "popup", "pop-up"], PageRegion::Modal),
+            (
+                &["main content", "content", "article", "body of the page"],
+                PageRegion::Main,
+            ),
+        ];
+        REGIONS
+            .iter()
+            .find(|(phrases, _)| phrases.iter().any(|phrase| after_preposition(phrase)))
+            .map(|(_, region)| *region)
+    }
+}
+
+/// Defines `regionOf(el)` for page scripts: the landmark an element sits in, then
+/// class and id conventions, then its position on the page
+pub const REGION_OF_JS: &str = r#"
+    const regionOf = el => {
+        if (!el) return 'unknown';
+        // Headers and footers of articles and sections are not page chrome
+        const sectioning = 'article, aside, main, nav, section';
+        for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+            const tag = node.tagName.toLowerCase();
+            const role = node.getAttribute('role');
+            if (tag === 'dialog' || role === 'dialog' || role === 'alertdialog' || node.getAttribute('aria-modal') === 'true') return 'modal';
+            if (tag === 'nav' || role === 'navigation') return 'navigation';
+            if (tag === 'main' || role === 'main') return 'main';
+            const topLevel = !(node.parentElement && node.parentElement.closest(sectioning));
+            if (role === 'banner' || (tag === 'header' && topLevel)) return 'header';
+            if (role === 'contentinfo' || (tag === 'footer' && topLevel)) return 'footer';
+            if (tag === 'aside' || role === 'complementary') return 'sidebar';
+        }
+        const hints = [
+            ['modal', ['modal', 'dialog', 'popup', 'lightbox', 'overlay']],
+            ['header', ['header', 'site-header', 'page-header', 'masthead', 'topbar', 'top-bar']],
+            ['navigation', ['nav', 'navbar', 'navigation', 'menu', 'main-menu', 'breadcrumb', 'breadcrumbs']],
+            ['footer', ['footer', 'site-footer', 'page-footer']],
+            ['sidebar', ['sidebar', 'side-bar', 'rail']],
+            ['main', ['main', 'content', 'main-content', 'page-content']]
+        ];
+        for (let node = el; node && node.nodeType === 1 && node !== document.body; node = node.parentElement) {
+            const tokens = ((typeof node.className === 'string' ? node.className : '') + ' ' + (node.getAttribute('id') || ''))
+                .toLowerCase().split(/\s+/);
+            for (const [region, names] of hints) {
+                if (tokens.some(t => names.includes(t))) return region;
+            }
+        }
+        const rect = el.getBoundingClientRect();
+        if (rect.width === 0 && rect.height === 0) return 'unknown';
+        const top = rect.top + window.scrollY;
+        const height = Math.max(document.documentElement.scrollHeight, window.innerHeight);
+        if (top < 100) return 'header';
+        if (height > window.innerHeight * 1.5 && top > height - 250) return 'footer';
+        return 'main';
+    };
+"#;
+
 /// Semantic analyzer for understanding page meaning
 pub struct SemanticAnalyzer {
     // Future: Add NLP models for semantic understanding
@@ -199,6 +320,35 @@ impl SemanticAnalyzer {
         Ok(vec![])
     }
 
+    /// Region of each element matched by `selectors`, in order; Unknown for
+    /// selectors that match nothing
+    pub async fn regions_of(
+        &self,
+        browser: &Browser,
+        selectors: &[String],
+    ) -> Result<Vec<PageRegion>> {
+        let script = format!(
+            r#"
+            (function() {{
+                {}
+                return {}.map(selector => {{
+                    try {{
+                        return regionOf(selector ? document.querySelector(selector) : null);
+                    }} catch (e) {{
+                        return 'unknown';
+                    }}
+                }});
+            }})()"#,
+            REGION_OF_JS,
+            serde_json::to_string(selectors)?
+        );
+        let result = browser.execute_script(&script).await?;
+        let names: Vec<String> = serde_json::from_value(result).unwrap_or_default();
+        let mut regions: Vec<PageRegion> = names.iter().map(|n| PageRegion::from_js(n)).collect();
+        regions.resize(selectors.len(), PageRegion::Unknown);
+        Ok(regions)
+    }
+
     /// Extract semantic meaning from element text
     pub fn analyze_element_semantics(&self, element_text: &str, _context: &str) -> EntityType {
         let text_lower = element_text.to_lowercase();
@@ -223,3 +373,37 @@ impl SemanticAnalyzer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_regions_from_descriptions() {
+        assert_eq!(
+            PageRegion::from_description("the search box in the header"),
+            Some(PageRegion::Header)
+        );
+        assert_eq!(
+            PageRegion::from_description("Privacy link at the bottom of the page"),
+            Some(PageRegion::Footer)
+        );
+        assert_eq!(
+            PageRegion::from_description("close button in the popup"),
+            Some(PageRegion::Modal)
+        );
+        // A "menu" element is not a request for the navigation region
+        assert_eq!(PageRegion::from_description("the account menu"), None);
+    }
+
+    #[test]
+    fn boilerplate_regions() {
+        assert!(PageRegion::Footer.is_boilerplate());
+        assert!(!PageRegion::Main.is_boilerplate());
+        assert!(!PageRegion::Modal.is_boilerplate());
+        assert_eq!(
+            serde_json::to_value(PageRegion::Navigation).unwrap(),
+            serde_json::json!("navigation")
+        );
+    }
+}
@@ -1,9 +1,189 @@
 #![allow(clippy::upper_case_acronyms)]
 // Smart form handling with intelligent field detection and auto-fill capabilities
 
-use anyhow::Result;
+use super::form_schema;
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration as DateDuration, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+use tracing::debug;
+
+/// Month pages a calendar widget is paged through before giving up
+const MAX_CALENDAR_STEPS: usize = 24;
+
+/// How a date input wants its value, and which widget library drives it
+const DATE_INPUT_SCRIPT: &str = r#"
+    (function() {
+        const el = document.querySelector(__SELECTOR__);
+        if (!el) return null;
+        const cls = typeof el.className === 'string' ? el.className.toLowerCase() : '';
+        return {
+            input_type: el.tagName === 'INPUT' ? (el.getAttribute('type') || 'text').toLowerCase() : el.tagName.toLowerCase(),
+            readonly: el.tagName !== 'INPUT' || el.readOnly === true,
+            placeholder: el.getAttribute('placeholder') || el.getAttribute('data-date-format') || '',
+            flatpickr: !!el._flatpickr,
+            jquery_ui: cls.includes('hasdatepicker') && typeof window.jQuery === 'function'
+        };
+    })()
+"#;
+
+/// Set an input's value the way frameworks notice: the native setter, then events
+const SET_VALUE_SCRIPT: &str = r#"
+    (function() {
+        const el = document.querySelector(__SELECTOR__);
+        if (!el) return null;
+        const setter = Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, 'value').set;
+        el.focus();
+        setter.call(el, __VALUE__);
+        el.dispatchEvent(new Event('input', { bubbles: true }));
+        el.dispatchEvent(new Event('change', { bubbles: true }));
+        el.blur();
+        return el.value;
+    })()
+"#;
+
+/// Hand the date to the picker library itself when the page uses a known one
+const WIDGET_API_SCRIPT: &str = r#"
+    (function() {
+        const el = document.querySelector(__SELECTOR__);
+        if (!el) return null;
+        if (el._flatpickr) {
+            el._flatpickr.setDate(__ISO__, true);
+            return el.value;
+        }
+        if (window.jQuery && window.jQuery(el).datepicker) {
+            window.jQuery(el).datepicker('setDate', new Date(__YEAR__, __MONTH0__, __DAY__));
+            window.jQuery(el).trigger('change');
+            return el.value;
+        }
+        return null;
+    })()
+"#;
+
+/// The open calendar popup, whichever library rendered it
+const CALENDAR_ROOT_JS: &str = r#"
+        const calendarRoot = () => {
+            const selectors = ['.flatpickr-calendar.open', '.ui-datepicker', '.react-datepicker', '.mat-calendar',
+                '.DayPicker', '.rdp', '.pika-single', '.datepicker', '[role="dialog"]', '[role="grid"]',
+                '[class*="calendar"]', '[class*="datepicker"]'];
+            for (const selector of selectors) {
+                for (const el of document.querySelectorAll(selector)) {
+                    const rect = el.getBoundingClientRect();
+                    if (rect.width > 0 && rect.height > 0 && getComputedStyle(el).visibility !== 'hidden') return el;
+                }
+            }
+            return null;
+        };
+"#;
+
+/// Text of the month/year caption of the open calendar
+const CALENDAR_HEADING_SCRIPT: &str = r#"
+    (function() {
+        __CALENDAR_ROOT__
+        const root = calendarRoot();
+        if (!root) return null;
+        const flatpickr = root.querySelector('.flatpickr-current-month');
+        if (flatpickr) {
+            const month = flatpickr.querySelector('select, .cur-month');
+            const year = flatpickr.querySelector('input, .cur-year');
+            const monthText = month && month.tagName === 'SELECT' ? month.options[month.selectedIndex].text : (month ? month.textContent : '');
+            return monthText + ' ' + (year ? (year.value || year.textContent) : '');
+        }
+        const heading = root.querySelector('.ui-datepicker-title, .react-datepicker__current-month, .mat-calendar-period-button, ' +
+            '.rdp-caption_label, .DayPicker-Caption, .pika-label, caption, [class*="month-title"], [class*="caption"], [aria-live]');
+        return heading ? heading.textContent.replace(/\s+/g, ' ').trim() : '';
+    })()
+"#;
+
+/// Click the calendar's next or previous month control
+const CALENDAR_STEP_SCRIPT: &str = r#"
+    (function() {
+        __CALENDAR_ROOT__
+        const root = calendarRoot();
+        if (!root) return false;
+        const wanted = __DIRECTION__ === 'next' ? /next|forward|下一|›|»|→/i : /prev|back|上一|‹|«|←/i;
+        const control = Array.from(root.querySelectorAll('button, a, [role="button"], [class*="next"], [class*="prev"]'))
+            .find(c => wanted.test([c.getAttribute('aria-label'), c.getAttribute('title'),
+                typeof c.className === 'string' ? c.className : '', c.textContent.trim()].join(' ')));
+        if (!control) return false;
+        control.click();
+        return true;
+    })()
+"#;
+
+/// Click the day cell of the wanted date. Cells named by data attributes or
+/// aria-labels are trusted anywhere; bare day numbers only when `__TEXT_MATCH__`
+/// says the calendar shows the right month.
+const CALENDAR_PICK_SCRIPT: &str = r#"
+    (function() {
+        __CALENDAR_ROOT__
+        const root = calendarRoot();
+        if (!root) return false;
+        const iso = __ISO__;
+        const labels = __LABELS__;
+        const usable = el => !el.matches('[disabled], [aria-disabled="true"]') &&
+            !/disabled|outside|other-month|prevmonthday|nextmonthday|unselectable/i.test(typeof el.className === 'string' ? el.className : '');
+        let cell = Array.from(root.querySelectorAll('[data-date], [data-day], [data-iso], [data-value]'))
+            .find(el => [el.dataset.date, el.dataset.day, el.dataset.iso, el.dataset.value].some(v => v && v.slice(0, 10) === iso));
+        if (!cell) {
+            cell = Array.from(root.querySelectorAll('[aria-label]')).find(el => {
+                const label = ' ' + el.getAttribute('aria-label').toLowerCase();
+                return labels.some(l => label.includes(' ' + l));
+            });
+        }
+        if (!cell && __TEXT_MATCH__) {
+            cell = Array.from(root.querySelectorAll('td, button, a, [role="gridcell"]'))
+                .find(el => el.children.length <= 1 && el.textContent.trim() === String(__DAY__) && usable(el));
+        }
+        if (!cell || !usable(cell)) return false;
+        (cell.querySelector('button, a') || cell).click();
+        return true;
+    })()
+"#;
+
+/// Current value of the field, to confirm a calendar pick landed
+const READ_VALUE_SCRIPT: &str = r#"
+    (function() {
+        const el = document.querySelector(__SELECTOR__);
+        return el ? (el.value || el.textContent || '').trim() : null;
+    })()
+"#;
+
+/// Set many fields in one go, the way frameworks notice. Takes
+/// `[{selector, kind, value}]` and answers with null or an error for each.
+const FILL_FIELDS_SCRIPT: &str = r#"
+    (function() {
+        return __FILLS__.map(fill => {
+            const el = document.querySelector(fill.selector);
+            if (!el) return 'element not found';
+            try {
+                if (fill.kind === 'checkbox') {
+                    const wanted = fill.value === 'true';
+                    if (el.checked !== wanted) el.click();
+                    return el.checked === wanted ? null : 'checkbox did not change';
+                }
+                if (fill.kind === 'radio') {
+                    const group = el.form ? Array.from(el.form.elements) : Array.from(document.getElementsByName(el.name));
+                    const radio = group.find(r => r.type === 'radio' && r.name === el.name && r.value === fill.value);
+                    if (!radio) return 'option not found';
+                    radio.click();
+                    return radio.checked ? null : 'radio did not change';
+                }
+                const proto = el.tagName === 'SELECT' ? HTMLSelectElement.prototype
+                    : el.tagName === 'TEXTAREA' ? HTMLTextAreaElement.prototype : HTMLInputElement.prototype;
+                el.focus();
+                Object.getOwnPropertyDescriptor(proto, 'value').set.call(el, fill.value);
+                el.dispatchEvent(new Event('input', { bubbles: true }));
+                el.dispatchEvent(new Event('change', { bubbles: true }));
+                el.blur();
+                return el.value === fill.value ? null : 'value was not accepted';
+            } catch (e) {
+                return String(e);
+            }
+        });
+    })()
+"#;
 
 /// Smart form handler that can intelligently fill forms
 pub struct SmartFormHandler {
@@ -47,7 +227,7 @@ pub struct FormField {
     pub validation: Option<ValidationRule>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FieldType {
     Text,
     Email,
@@ -84,13 +264,22 @@ pub struct ValidationRule {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserProfile {
     pub name: String,
+    #[serde(default)]
     pub personal_info: PersonalInfo,
+    #[serde(default)]
     pub contact_info: ContactInfo,
+    /// Shipping address
+    #[serde(default)]
     pub address_info: AddressInfo,
+    /// Test card used for checkout forms
+    #[serde(default)]
+    pub payment_info: Option<PaymentInfo>,
+    #[serde(default)]
     pub preferences: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PersonalInfo {
     pub first_name: String,
     pub last_name: String,
@@ -100,7 +289,8 @@ pub struct PersonalInfo {
     pub gender: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ContactInfo {
     pub email: String,
     pub phone: String,
@@ -108,7 +298,8 @@ pub struct ContactInfo {
     pub alternate_phone: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AddressInfo {
     pub street_address: String,
     pub street_address_2: Option<String>,
@@ -118,6 +309,15 @@ pub struct AddressInfo {
     pub country: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentInfo {
+    pub cardholder_name: String,
+    pub card_number: String,
+    /// As checkout forms ask for it, e.g. "12/30"
+    pub expiry: String,
+    pub cvv: String,
+}
+
 /// Result of form filling operation
 #[derive(Debug, Serialize)]
 pub struct FillResult {
@@ -128,6 +328,69 @@ pub struct FillResult {
     pub next_steps: Vec<String>,
 }
 
+/// A field `fill_by_name` filled
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedFieldFill {
+    /// The key its value was given under
+    pub key: String,
+    pub selector: String,
+    pub label: Option<String>,
+    pub field_type: FieldType,
+}
+
+/// A field `fill_by_name` matched but could not fill
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedFieldFailure {
+    pub key: String,
+    pub selector: String,
+    pub error: String,
+}
+
+/// Result of filling a form from named values
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedFillResult {
+    /// Every key matched a field and was filled
+    pub success: bool,
+    /// None for the controls outside any form element
+    pub form_selector: Option<String>,
+    pub filled: Vec<NamedFieldFill>,
+    pub failed: Vec<NamedFieldFailure>,
+    /// Keys no field of the form matched
+    pub unmatched: Vec<String>,
+    pub submit_selectors: Vec<String>,
+}
+
+/// How a date ended up in its field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFillMethod {
+    /// Native `<input type="date">` and friends, set to their ISO value
+    Native,
+    /// The page's picker library (flatpickr, jQuery UI) was told the date
+    WidgetApi,
+    /// Typed into a text field in the format its placeholder asks for
+    Typed,
+    /// Picked by paging a calendar popup to the month and clicking the day
+    Calendar,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DateFill {
+    pub date: NaiveDate,
+    pub method: DateFillMethod,
+    /// The field's value afterwards
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DateInputInfo {
+    input_type: String,
+    readonly: bool,
+    placeholder: String,
+    flatpickr: bool,
+    jquery_ui: bool,
+}
+
 /// Pattern for recognizing field types
 struct FieldPattern {
     labels: Vec<String>,
@@ -158,125 +421,41 @@ impl SmartFormHandler {
     ) -> Result<SmartFormAnalysis> {
         let selector = form_selector.unwrap_or("form");
 
-        // Get form structure using JavaScript
-        let form_analysis_script = format!(
-            r#"
-            const form = document.querySelector('{}');
-            if (!form) return null;
-            
-            const fields = [];
-            const inputs = form.querySelectorAll('input, select, textarea');
-            
-            inputs.forEach(input => {{
-                const label = form.querySelector(`label[for="${{input.id}}]`) || 
-                             input.closest('label') ||
-                             input.previousElementSibling?.tagName === 'LABEL' ? input.previousElementSibling : null;
-                
-                fields.push({{
-                    selector: input.tagName.toLowerCase() + (input.id ? '#' + input.id : ''),
-                    type: input.type || input.tagName.toLowerCase(),
-                    name: input.name || '',
-                    placeholder: input.placeholder || '',
-                    label: label?.textContent?.trim() || '',
-                    required: input.required || false,
-                    value: input.value || ''
-                }});
-            }});
-            
-            const submitButtons = form.querySelectorAll('input[type="submit"], button[type="submit"], button:not([type])');
-            
-            return {{
-                fields: fields,
-                submitButtons: Array.from(submitButtons).map(btn => btn.tagName.toLowerCase() + (btn.id ? '#' + btn.id : '')),
-                action: form.action || '',
-                method: form.method || 'get'
-            }};
-        "#,
-            selector
-        );
-
-        let result = browser.execute_script(&form_analysis_script).await?;
-
-        if result.is_null() {
-            return Err(anyhow::anyhow!("No form found with selector: {}", selector));
-        }
-
-        // Parse the form analysis result
-        let form_data: serde_json::Value = result;
-        let empty_vec = vec![];
-        let fields_data = form_data
-            .get("fields")
-            .and_then(|f| f.as_array())
-            .unwrap_or(&empty_vec);
+        let schema = form_schema::extract(browser, Some(selector)).await?;
+        let form = schema
+            .forms
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No form found with selector: {}", selector))?;
 
         let mut fields = Vec::new();
         let mut required_fields = Vec::new();
+        let mut validation_rules = HashMap::new();
+
+        // Hidden, disabled and read-only controls cannot be filled
+        for field in form.fields.iter().filter(|f| f.is_fillable()) {
+            let field_type = self.field_type_of(field);
+
+            if field.required {
+                required_fields.push(field.selector.clone());
+            }
 
-        for field_data in fields_data {
-            let field_type = self.classify_field_type(
-                field_data
-                    .get("type")
-                    .and_then(|t| t.as_str())
-                    .unwrap_or(""),
-                field_data
-                    .get("name")
-                    .and_then(|n| n.as_str())
-                    .unwrap_or(""),
-                field_data
-                    .get("label")
-                    .and_then(|l| l.as_str())
-                    .unwrap_or(""),
-                field_data
-                    .get("placeholder")
-                    .and_then(|p| p.as_str())
-                    .unwrap_or(""),
-            );
-
-            let selector = field_data
-                .get("selector")
-                .and_then(|s| s.as_str())
-                .unwrap_or("")
-                .to_string();
-            let required = field_data
-                .get("required")
-                .and_then(|r| r.as_bool())
-                .unwrap_or(false);
-
-            if required {
-                required_fields.push(selector.clone());
+            let validation = field.validation_rule();
+            if let Some(rule) = &validation {
+                validation_rules.insert(field.selector.clone(), rule.clone());
             }
 
             fields.push(FormField {
-                selector,
+                selector: field.selector.clone(),
                 field_type,
-                label: field_data
-                    .get("label")
-                    .and_then(|l| l.as_str())
-                    .map(|s| s.to_string()),
-                placeholder: field_data
-                    .get("placeholder")
-                    .and_then(|p| p.as_str())
-                    .map(|s| s.to_string()),
-                required,
-                current_value: field_data
-                    .get("value")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string()),
-                validation: None, // TODO: Implement validation rule detection
+                label: field.label.clone(),
+                placeholder: field.placeholder.clone(),
+                required: field.required,
+                current_value: field.value.clone(),
+                validation,
             });
         }
 
-        let submit_elements = form_data
-            .get("submitButtons")
-            .and_then(|s| s.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .collect()
-            })
-            .unwrap_or_default();
-
         // Classify form type based on fields
         let form_type = self.classify_form_type(&fields);
 
@@ -284,8 +463,8 @@ impl SmartFormHandler {
             form_type,
             fields,
             required_fields,
-            submit_elements,
-            validation_rules: HashMap::new(),
+            submit_elements: form.submit_selectors,
+            validation_rules,
             confidence: 0.8,
         })
     }
@@ -307,7 +486,20 @@ impl SmartFormHandler {
         let mut warnings = Vec::new();
 
         for field in &form_analysis.fields {
-            match self.get_fill_value(&field.field_type, profile) {
+            // Partial profiles leave the fields they have no data for alone
+            match self
+                .get_fill_value(&field.field_type, profile)
+                .filter(|value| !value.trim().is_empty())
+            {
+                Some(value) if matches!(field.field_type, FieldType::Date) => {
+                    match self.fill_date(browser, &field.selector, &value).await {
+                        Ok(_) => filled_fields.push(field.selector.clone()),
+                        Err(e) => {
+                            failed_fields.push(field.selector.clone());
+                            warnings.push(format!("Failed to fill {}: {}", field.selector, e));
+                        }
+                    }
+                }
                 Some(value) => match self.fill_field(browser, &field.selector, &value).await {
                     Ok(_) => {
                         filled_fields.push(field.selector.clone());
@@ -341,6 +533,119 @@ impl SmartFormHandler {
         })
     }
 
+    /// Fill a form from values keyed by what the fields mean ("email",
+    /// "first_name", "Company") instead of by selector. Keys are matched to
+    /// fields by name, id, autocomplete token, label and placeholder, and
+    /// failing that by field type. Text fields, selects, checkboxes and radio
+    /// groups are set in one script, dates through `fill_date`. Without
+    /// `form_selector` the form matching the most keys is filled.
+    pub async fn fill_by_name(
+        &self,
+        browser: &crate::browser::Browser,
+        form_selector: Option<&str>,
+        values: &[(String, String)],
+    ) -> Result<NamedFillResult> {
+        let schema = form_schema::extract(browser, form_selector).await?;
+        let keys: Vec<&str> = values.iter().map(|(key, _)| key.as_str()).collect();
+        let (form, fields, matches) = schema
+            .forms
+            .iter()
+            .map(|form| {
+                let fields: Vec<_> = form
+                    .fields
+                    .iter()
+                    .filter(|f| f.is_fillable())
+                    .map(|f| (f, self.field_type_of(f)))
+                    .collect();
+                let matches = match_fields(&keys, &fields);
+                (form, fields, matches)
+            })
+            .rev()
+            .max_by_key(|(_, _, matches)| matches.iter().flatten().count())
+            .ok_or_else(|| anyhow!("No form found"))?;
+
+        let mut filled = Vec::new();
+        let mut failed = Vec::new();
+        let mut unmatched = Vec::new();
+        let mut batch = Vec::new();
+        for ((key, value), matched) in values.iter().zip(matches) {
+            let Some((field, field_type)) = matched.map(|i| &fields[i]) else {
+                unmatched.push(key.clone());
+                continue;
+            };
+            let fill = NamedFieldFill {
+                key: key.clone(),
+                selector: field.selector.clone(),
+                label: field.label.clone(),
+                field_type: field_type.clone(),
+            };
+            let failure = |error: String| NamedFieldFailure {
+                key: key.clone(),
+                selector: field.selector.clone(),
+                error,
+            };
+            if *field_type == FieldType::Date
+                || matches!(
+                    field.input_type.as_str(),
+                    "date" | "datetime-local" | "month"
+                )
+            {
+                match self.fill_date(browser, &field.selector, value).await {
+                    Ok(_) => filled.push(fill),
+                    Err(e) => failed.push(failure(e.to_string())),
+                }
+                continue;
+            }
+            match fill_instruction(field, value) {
+                Ok((kind, value)) => batch.push((
+                    fill,
+                    serde_json::json!({
+                        "selector": field.selector,
+                        "kind": kind,
+                        "value": value,
+                    }),
+                )),
+                Err(e) => failed.push(failure(e.to_string())),
+            }
+        }
+
+        if !batch.is_empty() {
+            let fills: Vec<_> = batch.iter().map(|(_, fill)| fill).collect();
+            let errors: Vec<Option<String>> = serde_json::from_value(
+                browser
+                    .execute_script(
+                        &FILL_FIELDS_SCRIPT.replace("__FILLS__", &serde_json::to_string(&fills)?),
+                    )
+                    .await?,
+            )?;
+            for ((fill, _), error) in batch.into_iter().zip(errors) {
+                match error {
+                    None => filled.push(fill),
+                    Some(error) => failed.push(NamedFieldFailure {
+                        key: fill.key,
+                        selector: fill.selector,
+                        error,
+                    }),
+                }
+            }
+        }
+        debug!(
+            "Filled {} fields, {} failed, {} keys unmatched",
+            filled.len(),
+            failed.len(),
+            unmatched.len()
+        );
+
+        Ok(NamedFillResult {
+            success: failed.is_empty() && unmatched.is_empty(),
+            form_selector: form.selector.clone(),
+            filled,
+            failed,
+            unmatched,
+            submit_selectors: form.submit_selectors.clone(),
+        })
+    }
+
     /// Add or update user profile
     pub fn add_user_profile(&mut self, profile: UserProfile) {
         self.user_profiles.insert(profile.name.clone(), profile);
@@ -419,6 +724,21 @@ impl SmartFormHandler {
         // Add more patterns as needed...
     }
 
+    fn field_type_of(&self, field: &form_schema::FieldSchema) -> FieldType {
+        field
+            .autocomplete
+            .as_deref()
+            .and_then(field_type_for_autocomplete)
+            .unwrap_or_else(|| {
+                self.classify_field_type(
+                    &field.input_type,
+                    field.name.as_deref().unwrap_or(""),
+                    field.label.as_deref().unwrap_or(""),
+                    field.placeholder.as_deref().unwrap_or(""),
+                )
+            })
+    }
+
     fn classify_field_type(
         &self,
         input_type: &str,
@@ -507,6 +827,10 @@ impl SmartFormHandler {
             FieldType::State => Some(profile.address_info.state.clone()),
             FieldType::ZipCode => Some(profile.address_info.zip_code.clone()),
             FieldType::Country => Some(profile.address_info.country.clone()),
+            FieldType::Date => profile.personal_info.date_of_birth.clone(),
+            FieldType::CreditCard => profile.payment_info.as_ref().map(|p| p.card_number.clone()),
+            FieldType::CVV => profile.payment_info.as_ref().map(|p| p.cvv.clone()),
+            FieldType::ExpiryDate => profile.payment_info.as_ref().map(|p| p.expiry.clone()),
             _ => None,
         }
     }
@@ -539,6 +863,505 @@ impl SmartFormHandler {
 
         Ok(())
     }
+
+    /// Put `date` (anything `normalize_date` understands) into the date field at
+    /// `selector`: native date inputs get their ISO value, known picker libraries
+    /// are driven through their API, plain text fields get the date typed in the
+    /// format their placeholder shows, and read-only widgets are filled by paging
+    /// their calendar popup to the month and clicking the day.
+    pub async fn fill_date(
+        &self,
+        browser: &crate::browser::Browser,
+        selector: &str,
+        date: &str,
+    ) -> Result<DateFill> {
+        let date =
+            normalize_date(date).ok_or_else(|| anyhow!("Could not understand date: {}", date))?;
+        let selector_json = serde_json::to_string(selector)?;
+        let info: Option<DateInputInfo> = serde_json::from_value(
+            browser
+                .execute_script(&DATE_INPUT_SCRIPT.replace("__SELECTOR__", &selector_json))
+                .await?,
+        )?;
+        let info = info.ok_or_else(|| anyhow!("Date field not found: {}", selector))?;
+        let filled = |method, value| {
+            Ok(DateFill {
+                date,
+                method,
+                value,
+            })
+        };
+
+        if let Some(value) = native_date_value(&info.input_type, date) {
+            let set = set_value(browser, &selector_json, &value).await?;
+            if set.as_deref() == Some(value.as_str()) {
+                return filled(DateFillMethod::Native, value);
+            }
+            debug!("Native date input {} rejected {}", selector, value);
+        }
+
+        if info.flatpickr || info.jquery_ui {
+            let script = WIDGET_API_SCRIPT
+                .replace("__SELECTOR__", &selector_json)
+                .replace("__ISO__", &serde_json::to_string(&date.to_string())?)
+                .replace("__YEAR__", &date.year().to_string())
+                .replace("__MONTH0__", &date.month0().to_string())
+                .replace("__DAY__", &date.day().to_string());
+            if let Some(value) =
+                serde_json::from_value::<Option<String>>(browser.execute_script(&script).await?)?
+                    .filter(|v| !v.is_empty())
+            {
+                return filled(DateFillMethod::WidgetApi, value);
+            }
+        }
+
+        if !info.readonly {
+            let format = date_format_for_placeholder(&info.placeholder).unwrap_or("%Y-%m-%d");
+            let value = date.format(format).to_string();
+            // Masked inputs may reformat; anything else means the widget threw it away
+            if set_value(browser, &selector_json, &value)
+                .await?
+                .is_some_and(|v| v == value)
+            {
+                return filled(DateFillMethod::Typed, value);
+            }
+            debug!("Text date input {} did not keep {}", selector, value);
+        }
+
+        self.pick_from_calendar(browser, selector, date).await?;
+        let value: Option<String> = serde_json::from_value(
+            browser
+                .execute_script(&READ_VALUE_SCRIPT.replace("__SELECTOR__", &selector_json))
+                .await?,
+        )?;
+        filled(DateFillMethod::Calendar, value.unwrap_or_default())
+    }
+
+    /// Open the calendar behind `selector` and click through to `date`
+    async fn pick_from_calendar(
+        &self,
+        browser: &crate::browser::Browser,
+        selector: &str,
+        date: NaiveDate,
+    ) -> Result<()> {
+        browser.click(selector).await?;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let pick = |text_match: bool| -> Result<String> {
+            Ok(CALENDAR_PICK_SCRIPT
+                .replace("__CALENDAR_ROOT__", CALENDAR_ROOT_JS)
+                .replace("__ISO__", &serde_json::to_string(&date.to_string())?)
+                .replace("__LABELS__", &serde_json::to_string(&date_labels(date))?)
+                .replace("__DAY__", &date.day().to_string())
+                .replace("__TEXT_MATCH__", if text_match { "true" } else { "false" }))
+        };
+        let heading_script = CALENDAR_HEADING_SCRIPT.replace("__CALENDAR_ROOT__", CALENDAR_ROOT_JS);
+
+        for _ in 0..=MAX_CALENDAR_STEPS {
+            if browser.execute_script(&pick(false)?).await?.as_bool() == Some(true) {
+                return Ok(());
+            }
+            let heading: Option<String> =
+                serde_json::from_value(browser.execute_script(&heading_script).await?)?;
+            let heading = heading.ok_or_else(|| anyhow!("No calendar opened for {}", selector))?;
+
+            match parse_month_heading(&heading) {
+                Some((year, month)) if (year, month) != (date.year(), date.month()) => {
+                    let direction = if (date.year(), date.month()) > (year, month) {
+                        "next"
+                    } else {
+                        "prev"
+                    };
+                    let script = CALENDAR_STEP_SCRIPT
+                        .replace("__CALENDAR_ROOT__", CALENDAR_ROOT_JS)
+                        .replace("__DIRECTION__", &serde_json::to_string(direction)?);
+                    if browser.execute_script(&script).await?.as_bool() != Some(true) {
+                        return Err(anyhow!(
+                            "Calendar shows {} but has no {} month control",
+                            heading,
+                            direction
+                        ));
+                    }
+                    tokio::time::sleep(Duration::from_millis(150)).await;
+                }
+                // Right month, or a caption we cannot read: go by the day number
+                _ => {
+                    if browser.execute_script(&pick(true)?).await?.as_bool() == Some(true) {
+                        return Ok(());
+                    }
+                    return Err(anyhow!(
+                        "Day {} is not selectable in the calendar ({})",
+                        date,
+                        heading
+                    ));
+                }
+            }
+        }
+        Err(anyhow!(
+            "Calendar did not reach {} within {} months",
+            date,
+            MAX_CALENDAR_STEPS
+        ))
+    }
+}
+
+async fn set_value(
+    browser: &crate::browser::Browser,
+    selector_json: &str,
+    value: &str,
+) -> Result<Option<String>> {
+    let script = SET_VALUE_SCRIPT
+        .replace("__SELECTOR__", selector_json)
+        .replace("__VALUE__", &serde_json::to_string(value)?);
+    Ok(serde_json::from_value(
+        browser.execute_script(&script).await?,
+    )?)
+}
+
+/// Parse the ways people and pages write a date: ISO, "14/03/2025",
+/// "14.03.2025", "March 14, 2025", "14 Mar 2025", and "today", "tomorrow" or
+/// "in 3 days". Numeric dates with slashes are read month first, as US forms
+/// write them, unless the first number cannot be a month.
+pub fn normalize_date(input: &str) -> Option<NaiveDate> {
+    normalize_date_from(input, Local::now().date_naive())
+}
+
+fn normalize_date_from(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = input.trim().to_lowercase();
+    let text = text.trim_end_matches('.');
+    match text {
+        "today" => return Some(today),
+        "tomorrow" => return today.succ_opt(),
+        "yesterday" => return today.pred_opt(),
+        _ => {}
+    }
+
+    // "in 3 days", "+2 weeks"
+    let relative = text
+        .strip_prefix("in ")
+        .or_else(|| text.strip_prefix('+'))
+        .map(str::trim);
+    if let Some((count, unit)) = relative.and_then(|r| r.split_once(' ')) {
+        let count: i64 = count.trim().parse().ok()?;
+        let days = match unit.trim() {
+            "day" | "days" => count,
+            "week" | "weeks" => count * 7,
+            _ => return None,
+        };
+        return today.checked_add_signed(DateDuration::days(days));
+    }
+
+    // Date-times: keep the date part
+    let text = match text.split_once('t') {
+        Some((date, _)) if date.len() == 10 && date.as_bytes()[4] == b'-' => date,
+        _ => text,
+    };
+    let text = text.replace(',', " ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    // Ordinals: "march 14th 2025"
+    let text = regex::Regex::new(r"\b(\d{1,2})(st|nd|rd|th)\b")
+        .ok()?
+        .replace_all(&text, "$1")
+        .into_owned();
+
+    const FORMATS: &[&str] = &[
+        "%Y-%m-%d",
+        "%Y/%m/%d",
+        "%m/%d/%Y",
+        "%d/%m/%Y",
+        "%d.%m.%Y",
+        "%d-%m-%Y",
+        "%B %d %Y",
+        "%b %d %Y",
+        "%d %B %Y",
+        "%d %b %Y",
+        "%A %B %d %Y",
+        "%a %b %d %Y",
+    ];
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&text, format).ok())
+}
+
+/// Value a native date-ish input accepts for `date`, if `input_type` is one
+fn native_date_value(input_type: &str, date: NaiveDate) -> Option<String> {
+    let format = match input_type {
+        "date" => "%Y-%m-%d",
+        "datetime-local" => "%Y-%m-%dT00:00",
+        "month" => "%Y-%m",
+        "week" => "%G-W%V",
+        _ => return None,
+    };
+    Some(date.format(format).to_string())
+}
+
+/// chrono format for a placeholder such as "MM/DD/YYYY" or "jj.mm.aaaa"
+fn date_format_for_placeholder(placeholder: &str) -> Option<&'static str> {
+    let compact: String = placeholder
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    // French and Spanish placeholders use j/a for day and year
+    let compact = compact.replace("jj", "dd").replace("aaaa", "yyyy");
+    let format = match compact.as_str() {
+        "mm/dd/yyyy" => "%m/%d/%Y",
+        "dd/mm/yyyy" => "%d/%m/%Y",
+        "yyyy-mm-dd" => "%Y-%m-%d",
+        "yyyy/mm/dd" => "%Y/%m/%d",
+        "dd.mm.yyyy" => "%d.%m.%Y",
+        "dd-mm-yyyy" => "%d-%m-%Y",
+        "mm-dd-yyyy" => "%m-%d-%Y",
+        "mm/dd/yy" => "%m/%d/%y",
+        "dd/mm/yy" => "%d/%m/%y",
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Year and month shown by a calendar caption such as "March 2025",
+/// "Mar 2025" or "2025年3月"
+fn parse_month_heading(heading: &str) -> Option<(i32, u32)> {
+    let text = heading.to_lowercase();
+    // Not \b: CJK characters count as word characters
+    let year: i32 = regex::Regex::new(r"(?:^|\D)((?:19|20)\d{2})(?:\D|$)")
+        .ok()?
+        .captures(&text)?[1]
+        .parse()
+        .ok()?;
+    if let Some(month) = regex::Regex::new(r"年\s*(\d{1,2})\s*月")
+        .ok()?
+        .captures(&text)
+        .and_then(|c| c[1].parse().ok())
+    {
+        return Some((year, month));
+    }
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let month = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| word.len() >= 3)
+        .find_map(|word| MONTHS.iter().position(|m| word.starts_with(m)))?;
+    Some((year, month as u32 + 1))
+}
+
+/// Lowercase ways day cells label `date` for screen readers
+fn date_labels(date: NaiveDate) -> Vec<String> {
+    let month = date.format("%B").to_string().to_lowercase();
+    let day = date.day();
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    vec![
+        format!("{} {}, {}", month, day, date.year()),
+        format!("{} {}{}, {}", month, day, suffix, date.year()),
+        format!("{} {} {}", day, month, date.year()),
+        date.format("%Y-%m-%d").to_string(),
+    ]
+}
+
+/// Field type named by an HTML autofill token, which sites set precisely for
+/// password managers and browser autofill
+pub fn field_type_for_autocomplete(autocomplete: &str) -> Option<FieldType> {
+    // Tokens may carry section and billing/shipping prefixes: "shipping postal-code"
+    let token = autocomplete.split_whitespace().last()?.to_lowercase();
+    let field_type = match token.as_str() {
+        "email" => FieldType::Email,
+        "current-password" | "new-password" => FieldType::Password,
+        "tel" | "tel-national" => FieldType::Phone,
+        "name" | "given-name" | "family-name" | "additional-name" => FieldType::Name,
+        "street-address" | "address-line1" | "address-line2" => FieldType::Address,
+        "address-level2" => FieldType::City,
+        "address-level1" => FieldType::State,
+        "postal-code" => FieldType::ZipCode,
+        "country" | "country-name" => FieldType::Country,
+        "cc-number" => FieldType::CreditCard,
+        "cc-csc" => FieldType::CVV,
+        "cc-exp" => FieldType::ExpiryDate,
+        "bday" => FieldType::Date,
+        _ => return None,
+    };
+    Some(field_type)
+}
+
+/// Lowercase words of a key or attribute: "firstName", "first_name" and
+/// "First name" all give `["first", "name"]`
+fn words(text: &str) -> Vec<String> {
+    let mut spaced = String::with_capacity(text.len() + 4);
+    let mut previous_lower = false;
+    for c in text.chars() {
+        if c.is_uppercase() && previous_lower {
+            spaced.push(' ');
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        spaced.push(if c.is_alphanumeric() { c } else { ' ' });
+    }
+    spaced
+        .to_lowercase()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+/// The kind of field a key asks for, e.g. "zip" or "postal_code" for `ZipCode`
+fn field_type_for_key(key: &[String]) -> Option<FieldType> {
+    if let Some(field_type) = field_type_for_autocomplete(&key.join("-")) {
+        return Some(field_type);
+    }
+    let has = |names: &[&str]| key.iter().any(|word| names.contains(&word.as_str()));
+    let field_type = if has(&["email", "mail"]) {
+        FieldType::Email
+    } else if has(&["password", "pass", "pwd"]) {
+        FieldType::Password
+    } else if has(&["phone", "tel", "telephone", "mobile"]) {
+        FieldType::Phone
+    } else if has(&["cvv", "cvc", "csc"]) {
+        FieldType::CVV
+    } else if has(&["expiry", "expiration", "exp"]) {
+        FieldType::ExpiryDate
+    } else if has(&["card", "cc"]) {
+        FieldType::CreditCard
+    } else if has(&["zip", "zipcode", "postal", "postcode"]) {
+        FieldType::ZipCode
+    } else if has(&["city", "town"]) {
+        FieldType::City
+    } else if has(&["state", "province", "region"]) {
+        FieldType::State
+    } else if has(&["country"]) {
+        FieldType::Country
+    } else if has(&["address", "street"]) {
+        FieldType::Address
+    } else if has(&["birthday", "dob", "birth", "date"]) {
+        FieldType::Date
+    } else if has(&["name", "fullname"]) {
+        FieldType::Name
+    } else {
+        return None;
+    };
+    Some(field_type)
+}
+
+/// How well `key` names `field`: 1 for an exact name, id, autocomplete token,
+/// label or placeholder; less the more other words the attribute holding the
+/// key has; 0.5 when only the kind of field fits
+fn match_score(
+    key: &[String],
+    key_type: Option<&FieldType>,
+    field: &form_schema::FieldSchema,
+    field_type: &FieldType,
+) -> f32 {
+    let attributes = [
+        &field.name,
+        &field.id,
+        &field.autocomplete,
+        &field.label,
+        &field.placeholder,
+    ];
+    let best = attributes
+        .into_iter()
+        .flatten()
+        .map(|text| words(text))
+        .filter(|attribute| key.iter().all(|word| attribute.contains(word)))
+        .map(|attribute| {
+            if attribute == key {
+                1.0
+            } else {
+                0.6 + 0.3 * key.len() as f32 / attribute.len() as f32
+            }
+        })
+        .fold(0.0, f32::max);
+    if best > 0.0 {
+        best
+    } else if key_type == Some(field_type) {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+/// Index into `fields` of the field each key names, best matches first; a
+/// field takes one key at most
+fn match_fields(
+    keys: &[&str],
+    fields: &[(&form_schema::FieldSchema, FieldType)],
+) -> Vec<Option<usize>> {
+    let mut candidates = Vec::new();
+    for (k, key) in keys.iter().enumerate() {
+        let key = words(key);
+        if key.is_empty() {
+            continue;
+        }
+        let key_type = field_type_for_key(&key);
+        for (f, (field, field_type)) in fields.iter().enumerate() {
+            let score = match_score(&key, key_type.as_ref(), field, field_type);
+            if score > 0.0 {
+                candidates.push((score, k, f));
+            }
+        }
+    }
+    // Stable sort keeps key and page order among equal scores
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut matches = vec![None; keys.len()];
+    let mut taken = vec![false; fields.len()];
+    for (_, k, f) in candidates {
+        if matches[k].is_none() && !taken[f] {
+            matches[k] = Some(f);
+            taken[f] = true;
+        }
+    }
+    matches
+}
+
+/// What the fill script does with `value` for `field`: the kind of control and
+/// the value to set, an option's value for selects and radio groups
+fn fill_instruction(
+    field: &form_schema::FieldSchema,
+    value: &str,
+) -> Result<(&'static str, String)> {
+    match field.input_type.as_str() {
+        "checkbox" => {
+            let checked = match value.trim().to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" | "checked" | "y" => true,
+                "false" | "no" | "off" | "0" | "unchecked" | "n" | "" => false,
+                _ => return Err(anyhow!("Expected true or false, got '{}'", value)),
+            };
+            Ok(("checkbox", checked.to_string()))
+        }
+        "radio" | "select" => {
+            let wanted = value.trim().to_lowercase();
+            let option = field
+                .options
+                .iter()
+                .find(|o| o.value == value)
+                .or_else(|| {
+                    field
+                        .options
+                        .iter()
+                        .find(|o| o.label.to_lowercase() == wanted)
+                })
+                .or_else(|| {
+                    field.options.iter().find(|o| {
+                        !o.value.is_empty()
+                            && !wanted.is_empty()
+                            && o.label.to_lowercase().contains(&wanted)
+                    })
+                })
+                .ok_or_else(|| anyhow!("No option matches '{}'", value))?;
+            let kind = if field.input_type == "radio" {
+                "radio"
+            } else {
+                "value"
+            };
+            Ok((kind, option.value.clone()))
+        }
+        _ => Ok(("value", value.to_string())),
+    }
 }
 
 impl Default for SmartFormHandler {
@@ -546,3 +1369,120 @@ impl Default for SmartFormHandler {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_autocomplete_tokens_to_field_types() {
+        assert!(matches!(
+            field_type_for_autocomplete("shipping postal-code"),
+            Some(FieldType::ZipCode)
+        ));
+        assert!(matches!(
+            field_type_for_autocomplete("section-pay cc-number"),
+            Some(FieldType::CreditCard)
+        ));
+        assert!(field_type_for_autocomplete("off").is_none());
+        assert!(field_type_for_autocomplete("").is_none());
+    }
+
+    #[test]
+    fn matches_named_values_to_fields() {
+        let field = |name: &str, label: &str, input_type: &str| form_schema::FieldSchema {
+            selector: format!("#{}", name),
+            name: Some(name.to_string()),
+            label: Some(label.to_string()),
+            input_type: input_type.to_string(),
+            ..Default::default()
+        };
+        let fields = [
+            (field("fname", "First name", "text"), FieldType::Name),
+            (field("lname", "Last name", "text"), FieldType::Name),
+            (
+                field("contact", "How can we reach you?", "email"),
+                FieldType::Email,
+            ),
+            (field("postcode", "Postcode", "text"), FieldType::ZipCode),
+        ];
+        let fields: Vec<_> = fields.iter().map(|(f, t)| (f, t.clone())).collect();
+
+        let matches = match_fields(
+            &["lastName", "first_name", "email", "zip", "company"],
+            &fields,
+        );
+        assert_eq!(matches, [Some(1), Some(0), Some(2), Some(3), None]);
+        // A field is filled once, by the key naming it best
+        assert_eq!(match_fields(&["Postcode", "postcode"], &fields)[1], None);
+
+        let mut country = field("country", "Country", "select");
+        country.options = vec![
+            form_schema::FieldOption {
+                value: "".into(),
+                label: "Choose...".into(),
+                selected: true,
+            },
+            form_schema::FieldOption {
+                value: "ca".into(),
+                label: "Canada".into(),
+                selected: false,
+            },
+        ];
+        assert_eq!(
+            fill_instruction(&country, "canada").unwrap(),
+            ("value", "ca".to_string())
+        );
+        assert!(fill_instruction(&country, "Peru").is_err());
+        let newsletter = field("news", "Newsletter", "checkbox");
+        assert_eq!(
+            fill_instruction(&newsletter, "yes").unwrap(),
+            ("checkbox", "true".to_string())
+        );
+        assert!(fill_instruction(&newsletter, "maybe").is_err());
+    }
+
+    #[test]
+    fn normalizes_written_dates() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let date = |s: &str| normalize_date_from(s, today);
+        let pi_day = Some(today);
+
+        assert_eq!(date("2025-03-14"), pi_day);
+        assert_eq!(date("2025-03-14T09:30"), pi_day);
+        assert_eq!(date("03/14/2025"), pi_day);
+        assert_eq!(date("14/03/2025"), pi_day);
+        assert_eq!(date("14.03.2025"), pi_day);
+        assert_eq!(date("March 14th, 2025"), pi_day);
+        assert_eq!(date("14 Mar 2025"), pi_day);
+        assert_eq!(date("tomorrow"), NaiveDate::from_ymd_opt(2025, 3, 15));
+        assert_eq!(date("in 2 weeks"), NaiveDate::from_ymd_opt(2025, 3, 28));
+        assert_eq!(date("next summer"), None);
+    }
+
+    #[test]
+    fn formats_dates_for_their_inputs() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 4).unwrap();
+        assert_eq!(native_date_value("date", date).unwrap(), "2025-03-04");
+        assert_eq!(native_date_value("month", date).unwrap(), "2025-03");
+        assert!(native_date_value("text", date).is_none());
+
+        let typed = |placeholder| {
+            date.format(date_format_for_placeholder(placeholder).unwrap())
+                .to_string()
+        };
+        assert_eq!(typed("MM/DD/YYYY"), "03/04/2025");
+        assert_eq!(typed("jj.mm.aaaa"), "04.03.2025");
+        assert!(date_format_for_placeholder("Check-in").is_none());
+    }
+
+    #[test]
+    fn reads_calendar_captions() {
+        assert_eq!(parse_month_heading("March 2025"), Some((2025, 3)));
+        assert_eq!(parse_month_heading(" Sept 2026 "), Some((2026, 9)));
+        assert_eq!(parse_month_heading("2025年11月"), Some((2025, 11)));
+        assert_eq!(parse_month_heading("Su Mo Tu"), None);
+        assert!(date_labels(NaiveDate::from_ymd_opt(2025, 3, 22).unwrap())
+            .contains(&"march 22nd, 2025".to_string()));
+    }
+}
@@ -0,0 +1,297 @@
+// Vision model fallback
+//
+// Sends the cached screenshot, or a crop of a single element, to a multimodal LLM
+// to answer questions about how the page looks ("which button is highlighted?")
+// and to pick the element a description means when DOM heuristics find nothing.
+
+use super::visual::VisualBox;
+use crate::llm::{ImageInput, LLMRequest, LLMService};
+use anyhow::Result;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// Screenshots are scaled down to this width before they are sent
+const MAX_IMAGE_WIDTH: u32 = 1280;
+
+/// Taller screenshots are cut off; vision models downscale long pages until
+/// nothing is legible
+const MAX_IMAGE_HEIGHT: u32 = 2560;
+
+/// CSS pixels of surroundings kept around a cropped element
+const CROP_PADDING: f64 = 16.0;
+
+/// Candidates listed in a grounding prompt
+const MAX_CANDIDATES: usize = 60;
+
+/// Element the vision model picked for a description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisionMatch {
+    pub element: VisualBox,
+    pub confidence: f32,
+    /// The model's explanation, if it gave one
+    pub reason: Option<String>,
+}
+
+/// A screenshot ready to send: scaled down, cut off, and the CSS area it shows
+struct PreparedImage {
+    image: ImageInput,
+    /// Image pixels per CSS pixel
+    scale: f64,
+    /// CSS pixels from the top of the document the image covers
+    css_height: f64,
+}
+
+/// Asks a vision-capable LLM about screenshots
+pub struct VisionModel {
+    llm: Mutex<LLMService>,
+}
+
+impl VisionModel {
+    pub fn new(llm: LLMService) -> Self {
+        Self {
+            llm: Mutex::new(llm),
+        }
+    }
+
+    /// Answer `question` about a screenshot spanning `css_width` CSS pixels
+    pub async fn ask(&self, screenshot: &[u8], css_width: f64, question: &str) -> Result<String> {
+        let prepared = prepare(screenshot, css_width)?;
+        self.send(question, prepared.image).await
+    }
+
+    /// Answer `question` about one element, showing the model only its crop
+    pub async fn ask_about_element(
+        &self,
+        screenshot: &[u8],
+        css_width: f64,
+        element: &VisualBox,
+        question: &str,
+    ) -> Result<String> {
+        let crop = crop(screenshot, css_width, element)?;
+        self.send(question, ImageInput::png(&crop)).await
+    }
+
+    /// The element among `candidates` that `description` refers to, judged by how
+    /// the page looks; `None` when the model sees no match
+    pub async fn locate(
+        &self,
+        screenshot: &[u8],
+        css_width: f64,
+        description: &str,
+        candidates: &[VisualBox],
+    ) -> Result<Option<VisionMatch>> {
+        let prepared = prepare(screenshot, css_width)?;
+        let visible: Vec<&VisualBox> = candidates
+            .iter()
+            .filter(|element| element.y < prepared.css_height)
+            .take(MAX_CANDIDATES)
+            .collect();
+        if visible.is_empty() {
+            return Ok(None);
+        }
+
+        let prompt = grounding_prompt(description, &visible, prepared.scale);
+        let answer = self.send(&prompt, prepared.image).await?;
+        let Some(choice) = parse_choice(&answer, visible.len()) else {
+            debug!("Vision model found no element for '{}'", description);
+            return Ok(None);
+        };
+        Ok(Some(VisionMatch {
+            element: visible[choice.index].clone(),
+            confidence: choice.confidence,
+            reason: choice.reason,
+        }))
+    }
+
+    async fn send(&self, prompt: &str, image: ImageInput) -> Result<String> {
+        let request = LLMRequest::new(prompt).with_image(image);
+        let response = self.llm.lock().await.complete(&request).await?;
+        Ok(response.content)
+    }
+}
+
+/// Decode, scale down and cut off a screenshot spanning `css_width` CSS pixels
+fn prepare(screenshot: &[u8], css_width: f64) -> Result<PreparedImage> {
+    let mut image = image::load_from_memory(screenshot)?;
+    if image.width() > MAX_IMAGE_WIDTH {
+        let height = (image.height() as f64 * MAX_IMAGE_WIDTH as f64 / image.width() as f64)
+            .round()
+            .max(1.0) as u32;
+        image = image.resize_exact(MAX_IMAGE_WIDTH, height, FilterType::Triangle);
+    }
+    if image.height() > MAX_IMAGE_HEIGHT {
+        image = image.crop_imm(0, 0, image.width(), MAX_IMAGE_HEIGHT);
+    }
+
+    let scale = if css_width > 0.0 {
+        image.width() as f64 / css_width
+    } else {
+        1.0
+    };
+    Ok(PreparedImage {
+        css_height: image.height() as f64 / scale,
+        image: ImageInput::png(&encode_png(&image)?),
+        scale,
+    })
+}
+
+/// PNG of `element` and a little of its surroundings, cut from a screenshot
+/// spanning `css_width` CSS pixels
+pub fn crop(screenshot: &[u8], css_width: f64, element: &VisualBox) -> Result<Vec<u8>> {
+    let image = image::load_from_memory(screenshot)?;
+    let scale = if css_width > 0.0 {
+        image.width() as f64 / css_width
+    } else {
+        1.0
+    };
+
+    let to_pixels = |css: f64, limit: u32| ((css * scale).max(0.0) as u32).min(limit);
+    let left = to_pixels(element.x - CROP_PADDING, image.width());
+    let top = to_pixels(element.y - CROP_PADDING, image.height());
+    let right = to_pixels(element.x + element.width + CROP_PADDING, image.width());
+    let bottom = to_pixels(element.y + element.height + CROP_PADDING, image.height());
+    if right <= left || bottom <= top {
+        anyhow::bail!("Element {} lies outside the screenshot", element.selector);
+    }
+
+    encode_png(&image.crop_imm(left, top, right - left, bottom - top))
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+    Ok(bytes.into_inner())
+}
+
+/// Asks the model to pick one of the numbered candidates, located by their boxes
+/// in image pixels
+fn grounding_prompt(description: &str, candidates: &[&VisualBox], scale: f64) -> String {
+    let listing: Vec<String> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            format!(
+                "{}. <{}{}> \"{}\" at x={} y={} w={} h={}",
+                index,
+                element.tag,
+                if element.role.is_empty() {
+                    String::new()
+                } else {
+                    format!(" role={}", element.role)
+                },
+                element.text.chars().take(60).collect::<String>(),
+                (element.x * scale).round(),
+                (element.y * scale).round(),
+                (element.width * scale).round(),
+                (element.height * scale).round(),
+            )
+        })
+        .collect();
+
+    format!(
+        "The screenshot shows a web page. Which of these elements is \"{}\"? \
+         Judge by what the page looks like: highlighting, color, position and icons.\n\
+         Elements (boxes in screenshot pixels):\n{}\n\n\
+         Respond with JSON only: {{\"index\": <number or null>, \"confidence\": 0.0-1.0, \
+         \"reason\": \"...\"}}",
+        description,
+        listing.join("\n")
+    )
+}
+
+struct Choice {
+    index: usize,
+    confidence: f32,
+    reason: Option<String>,
+}
+
+/// The candidate picked in a grounding answer, if any of `candidate_count`
+fn parse_choice(content: &str, candidate_count: usize) -> Option<Choice> {
+    let json = match (content.find('{'), content.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => return None,
+    };
+
+    #[derive(Deserialize)]
+    struct Answer {
+        index: Option<usize>,
+        #[serde(default)]
+        confidence: Option<f32>,
+        #[serde(default)]
+        reason: Option<String>,
+    }
+    let answer: Answer = serde_json::from_str(json).ok()?;
+    let index = answer.index.filter(|index| *index < candidate_count)?;
+    Some(Choice {
+        index,
+        confidence: answer.confidence.unwrap_or(0.5).clamp(0.0, 1.0),
+        reason: answer.reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn screenshot(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([255, 0, 0])));
+        encode_png(&image).unwrap()
+    }
+
+    fn element(x: f64, y: f64, width: f64, height: f64) -> VisualBox {
+        VisualBox {
+            selector: "#buy".to_string(),
+            tag: "button".to_string(),
+            role: String::new(),
+            text: "Buy now".to_string(),
+            x,
+            y,
+            width,
+            height,
+            clickable: true,
+        }
+    }
+
+    #[test]
+    fn screenshots_are_scaled_and_cut_off() {
+        // A 2x device pixel ratio page, 1000 CSS pixels wide and 3000 tall
+        let prepared = prepare(&screenshot(2000, 6000), 1000.0).unwrap();
+        assert!((prepared.scale - 1.28).abs() < 1e-9);
+        assert_eq!(prepared.css_height, 2000.0);
+    }
+
+    #[test]
+    fn crops_include_padding_and_stay_inside_the_screenshot() {
+        let shot = screenshot(800, 600);
+        let crop_bytes = crop(&shot, 400.0, &element(100.0, 50.0, 80.0, 20.0)).unwrap();
+        let cropped = image::load_from_memory(&crop_bytes).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (224, 104));
+
+        let edge = crop(&shot, 400.0, &element(390.0, 290.0, 40.0, 40.0)).unwrap();
+        assert!(image::load_from_memory(&edge).unwrap().width() <= 52);
+        assert!(crop(&shot, 400.0, &element(900.0, 900.0, 10.0, 10.0)).is_err());
+    }
+
+    #[test]
+    fn parses_the_chosen_candidate() {
+        let choice = parse_choice(
+            "The highlighted one: {\"index\": 2, \"confidence\": 0.9, \"reason\": \"blue border\"}",
+            3,
+        )
+        .unwrap();
+        assert_eq!(choice.index, 2);
+        assert_eq!(choice.confidence, 0.9);
+        assert_eq!(choice.reason.as_deref(), Some("blue border"));
+
+        assert!(parse_choice("{\"index\": null, \"confidence\": 0.1}", 3).is_none());
+        assert!(parse_choice("{\"index\": 7}", 3).is_none());
+        assert!(parse_choice("no idea", 3).is_none());
+
+        let listing = grounding_prompt("the buy button", &[&element(10.0, 20.0, 30.0, 40.0)], 2.0);
+        assert!(listing.contains("0. <button> \"Buy now\" at x=20 y=40 w=60 h=80"));
+    }
+}
@@ -0,0 +1,544 @@
+// Visual analysis of page screenshots
+//
+// Segments a screenshot into regions of similar luminance, measures how much each
+// rendered element stands out from its surroundings, and ranks elements against
+// descriptions with spatial relations such as "the button next to the price".
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Side of a grid cell in screenshot pixels
+const CELL_SIZE: u32 = 8;
+
+/// Largest luminance step between neighbouring cells of the same segment
+const SEGMENT_THRESHOLD: f32 = 24.0;
+
+/// Rendered box of an element, in CSS pixels relative to the document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisualBox {
+    pub selector: String,
+    pub tag: String,
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[serde(default)]
+    pub clickable: bool,
+}
+
+impl VisualBox {
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    fn vertical_overlap(&self, other: &VisualBox) -> f64 {
+        (self.bottom().min(other.bottom()) - self.y.max(other.y)).max(0.0)
+    }
+
+    fn horizontal_overlap(&self, other: &VisualBox) -> f64 {
+        (self.right().min(other.right()) - self.x.max(other.x)).max(0.0)
+    }
+
+    fn contains(&self, other: &VisualBox) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Words describing what the element is, e.g. "button" for `<a role="button">`
+    fn kind_words(&self) -> Vec<&'static str> {
+        let mut words = Vec::new();
+        match (self.tag.as_str(), self.role.as_str()) {
+            ("button", _) | (_, "button") => words.push("button"),
+            ("a", _) | (_, "link") => words.push("link"),
+            ("input", _) | ("textarea", _) | (_, "textbox") => {
+                words.extend(["input", "field", "box"])
+            }
+            ("select", _) | (_, "combobox") => words.extend(["dropdown", "select"]),
+            ("img", _) | ("svg", _) | ("canvas", _) => words.extend(["image", "icon", "logo"]),
+            ("h1", _) | ("h2", _) | ("h3", _) => words.extend(["heading", "title"]),
+            _ => {}
+        }
+        words
+    }
+}
+
+/// Where the element described is, relative to an anchor element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpatialRelation {
+    NextTo,
+    LeftOf,
+    RightOf,
+    Above,
+    Below,
+    Inside,
+}
+
+/// Description split into the element looked for and the element it is placed by
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpatialQuery {
+    pub target: String,
+    pub relation: SpatialRelation,
+    pub anchor: String,
+}
+
+impl SpatialQuery {
+    /// Parse "<target> <relation> <anchor>", e.g. "button next to the price"
+    pub fn parse(description: &str) -> Option<Self> {
+        const PHRASES: &[(&str, SpatialRelation)] = &[
+            (" to the left of ", SpatialRelation::LeftOf),
+            (" to the right of ", SpatialRelation::RightOf),
+            (" left of ", SpatialRelation::LeftOf),
+            (" right of ", SpatialRelation::RightOf),
+            (" next to ", SpatialRelation::NextTo),
+            (" beside ", SpatialRelation::NextTo),
+            (" near ", SpatialRelation::NextTo),
+            (" above ", SpatialRelation::Above),
+            (" below ", SpatialRelation::Below),
+            (" under ", SpatialRelation::Below),
+            (" beneath ", SpatialRelation::Below),
+            (" inside ", SpatialRelation::Inside),
+            (" within ", SpatialRelation::Inside),
+            (" in ", SpatialRelation::Inside),
+        ];
+        let description = format!(" {} ", description.to_lowercase().trim());
+        PHRASES.iter().find_map(|(phrase, relation)| {
+            let (target, anchor) = description.split_once(phrase)?;
+            let target = strip_articles(target);
+            let anchor = strip_articles(anchor);
+            (!target.is_empty() && !anchor.is_empty()).then_some(SpatialQuery {
+                target,
+                relation: *relation,
+                anchor,
+            })
+        })
+    }
+}
+
+fn strip_articles(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .filter(|w| !["the", "a", "an", "click", "on"].contains(w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// How well the element's kind and text match a short description, 0.0 to 1.0
+pub fn text_match(element: &VisualBox, description: &str) -> f32 {
+    let words: Vec<&str> = description.split_whitespace().collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    let text = element.text.to_lowercase();
+    let kinds = element.kind_words();
+    let matched = words
+        .iter()
+        .filter(|w| kinds.contains(w) || text.contains(*w))
+        .count();
+    matched as f32 / words.len() as f32
+}
+
+/// How well `target` stands in `relation` to `anchor`, 0.0 to 1.0
+pub fn relation_score(relation: SpatialRelation, target: &VisualBox, anchor: &VisualBox) -> f32 {
+    let (tx, ty) = target.center();
+    let (ax, ay) = anchor.center();
+    let distance = ((tx - ax).powi(2) + (ty - ay).powi(2)).sqrt();
+    // Nearby is roughly within a few element sizes of the anchor
+    let reach = (anchor.width.max(anchor.height) + target.width.max(target.height)) * 2.0 + 50.0;
+    let closeness = (1.0 - distance / (reach * 2.0)).clamp(0.0, 1.0) as f32;
+
+    let placed = match relation {
+        SpatialRelation::NextTo => target.vertical_overlap(anchor) > 0.0 || distance < reach,
+        SpatialRelation::LeftOf => {
+            target.right() <= anchor.x + 5.0 && target.vertical_overlap(anchor) > 0.0
+        }
+        SpatialRelation::RightOf => {
+            target.x >= anchor.right() - 5.0 && target.vertical_overlap(anchor) > 0.0
+        }
+        SpatialRelation::Above => {
+            target.bottom() <= anchor.y + 5.0 && target.horizontal_overlap(anchor) > 0.0
+        }
+        SpatialRelation::Below => {
+            target.y >= anchor.bottom() - 5.0 && target.horizontal_overlap(anchor) > 0.0
+        }
+        SpatialRelation::Inside => return if anchor.contains(target) { 1.0 } else { 0.0 },
+    };
+    if placed {
+        0.5 + closeness * 0.5
+    } else {
+        closeness * 0.2
+    }
+}
+
+/// Screenshot reduced to a grid of mean luminance, segmented into regions of
+/// similar brightness
+pub struct Segmentation {
+    cols: usize,
+    rows: usize,
+    luminance: Vec<f32>,
+    labels: Vec<usize>,
+    /// Screenshot pixels per CSS pixel
+    scale: f64,
+}
+
+impl Segmentation {
+    /// Segment an encoded screenshot whose width spans `css_width` CSS pixels
+    pub fn from_screenshot(bytes: &[u8], css_width: f64) -> Result<Self> {
+        let image = image::load_from_memory(bytes)?.to_luma8();
+        let (width, height) = image.dimensions();
+        let cols = width.div_ceil(CELL_SIZE).max(1) as usize;
+        let rows = height.div_ceil(CELL_SIZE).max(1) as usize;
+
+        let mut sums = vec![0f32; cols * rows];
+        let mut counts = vec![0u32; cols * rows];
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let cell = (y / CELL_SIZE) as usize * cols + (x / CELL_SIZE) as usize;
+            sums[cell] += pixel.0[0] as f32;
+            counts[cell] += 1;
+        }
+        let luminance: Vec<f32> = sums
+            .iter()
+            .zip(&counts)
+            .map(|(sum, count)| sum / (*count).max(1) as f32)
+            .collect();
+        let labels = label_segments(cols, rows, &luminance);
+        let scale = if css_width > 0.0 {
+            width as f64 / css_width
+        } else {
+            1.0
+        };
+
+        Ok(Self {
+            cols,
+            rows,
+            luminance,
+            labels,
+            scale,
+        })
+    }
+
+    /// Number of distinct regions found
+    pub fn segment_count(&self) -> usize {
+        self.labels.iter().max().map_or(0, |max| max + 1)
+    }
+
+    /// Grid cells covered by a CSS-pixel box, as (col, row) ranges
+    fn cells(&self, element: &VisualBox) -> Option<(usize, usize, usize, usize)> {
+        let cell = CELL_SIZE as f64 / self.scale;
+        let col_start = (element.x / cell).floor().max(0.0) as usize;
+        let row_start = (element.y / cell).floor().max(0.0) as usize;
+        let col_end = ((element.right() / cell).ceil() as usize).min(self.cols);
+        let row_end = ((element.bottom() / cell).ceil() as usize).min(self.rows);
+        (col_start < col_end && row_start < row_end)
+            .then_some((col_start, col_end, row_start, row_end))
+    }
+
+    /// Luminance difference between an element and the ring of cells around it, 0.0 to 1.0
+    pub fn contrast(&self, element: &VisualBox) -> f32 {
+        let Some((c0, c1, r0, r1)) = self.cells(element) else {
+            return 0.0;
+        };
+        let (mut inside, mut inside_n, mut ring, mut ring_n) = (0f32, 0usize, 0f32, 0usize);
+        let ring_c0 = c0.saturating_sub(1);
+        let ring_r0 = r0.saturating_sub(1);
+        let ring_c1 = (c1 + 1).min(self.cols);
+        let ring_r1 = (r1 + 1).min(self.rows);
+        for row in ring_r0..ring_r1 {
+            for col in ring_c0..ring_c1 {
+                let value = self.luminance[row * self.cols + col];
+                if (c0..c1).contains(&col) && (r0..r1).contains(&row) {
+                    inside += value;
+                    inside_n += 1;
+                } else {
+                    ring += value;
+                    ring_n += 1;
+                }
+            }
+        }
+        if inside_n == 0 || ring_n == 0 {
+            return 0.0;
+        }
+        ((inside / inside_n as f32 - ring / ring_n as f32).abs() / 255.0).min(1.0)
+    }
+
+    /// Whether the element covers segments other than the one surrounding it, i.e. it
+    /// is drawn as a distinct shape rather than text on the page background
+    pub fn is_distinct(&self, element: &VisualBox) -> bool {
+        let Some((c0, c1, r0, r1)) = self.cells(element) else {
+            return false;
+        };
+        let outside = if r0 > 0 {
+            self.labels[(r0 - 1) * self.cols + c0]
+        } else if c0 > 0 {
+            self.labels[r0 * self.cols + c0 - 1]
+        } else {
+            return false;
+        };
+        (r0..r1).any(|row| (c0..c1).any(|col| self.labels[row * self.cols + col] != outside))
+    }
+
+    /// How much an element draws the eye, from its contrast, size and position
+    pub fn prominence(&self, element: &VisualBox, page: (f64, f64)) -> f32 {
+        let (page_width, page_height) = page;
+        let area = (element.width * element.height) / (page_width * page_height).max(1.0);
+        // An element covering a twentieth of the page is as large as it gets
+        let size = (area * 20.0).min(1.0) as f32;
+        // Elements near the top are seen first
+        let position = (1.0 - element.y / page_height.max(1.0)).clamp(0.0, 1.0) as f32;
+        let shape = if self.is_distinct(element) { 1.0 } else { 0.0 };
+        (self.contrast(element) * 0.35 + size * 0.25 + position * 0.2 + shape * 0.2).min(1.0)
+    }
+}
+
+/// Label connected cells whose luminance differs by less than `SEGMENT_THRESHOLD`
+fn label_segments(cols: usize, rows: usize, luminance: &[f32]) -> Vec<usize> {
+    let mut labels = vec![usize::MAX; cols * rows];
+    let mut next = 0;
+    let mut stack = Vec::new();
+    for start in 0..labels.len() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+        labels[start] = next;
+        stack.push(start);
+        while let Some(cell) = stack.pop() {
+            let (col, row) = (cell % cols, cell / cols);
+            let neighbours = [
+                (col > 0).then(|| cell - 1),
+                (col + 1 < cols).then(|| cell + 1),
+                (row > 0).then(|| cell - cols),
+                (row + 1 < rows).then(|| cell + cols),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                if labels[neighbour] == usize::MAX
+                    && (luminance[neighbour] - luminance[cell]).abs() < SEGMENT_THRESHOLD
+                {
+                    labels[neighbour] = next;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        next += 1;
+    }
+    labels
+}
+
+/// Element ranked against a description
+#[derive(Debug, Clone)]
+pub struct VisualMatch {
+    pub element: VisualBox,
+    pub score: f32,
+    pub prominence: f32,
+    /// Text of the anchor element for spatial descriptions
+    pub anchor: Option<String>,
+    /// Text of the closest other elements
+    pub nearby: Vec<String>,
+}
+
+/// Rank rendered elements against a description, best first. Spatial descriptions
+/// are scored on the relation to the best matching anchor, others on text and
+/// prominence alone.
+pub fn rank(
+    segmentation: &Segmentation,
+    elements: &[VisualBox],
+    page: (f64, f64),
+    description: &str,
+    limit: usize,
+) -> Vec<VisualMatch> {
+    let query = SpatialQuery::parse(description);
+    let target = match &query {
+        Some(query) => query.target.clone(),
+        None => strip_articles(&description.to_lowercase()),
+    };
+
+    // Anchors: the elements matching the anchor phrase best
+    let anchors: Vec<&VisualBox> = match &query {
+        Some(query) => {
+            let best = elements
+                .iter()
+                .map(|e| text_match(e, &query.anchor))
+                .fold(0.0f32, f32::max);
+            if best == 0.0 {
+                return Vec::new();
+            }
+            elements
+                .iter()
+                .filter(|e| text_match(e, &query.anchor) >= best)
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    let mut matches: Vec<VisualMatch> = elements
+        .iter()
+        .filter_map(|element| {
+            let text_score = text_match(element, &target);
+            if text_score == 0.0 {
+                return None;
+            }
+            let prominence = segmentation.prominence(element, page);
+            let (score, anchor) = match &query {
+                Some(query) => {
+                    let (relation, anchor) = anchors
+                        .iter()
+                        .filter(|anchor| !std::ptr::eq(**anchor, element))
+                        .map(|anchor| (relation_score(query.relation, element, anchor), anchor))
+                        .max_by(|a, b| a.0.total_cmp(&b.0))?;
+                    (
+                        relation * 0.5 + text_score * 0.3 + prominence * 0.2,
+                        Some(anchor.text.clone()),
+                    )
+                }
+                None => (text_score * 0.6 + prominence * 0.4, None),
+            };
+            Some(VisualMatch {
+                element: element.clone(),
+                score,
+                prominence,
+                anchor,
+                nearby: nearest_texts(element, elements, 3),
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit);
+    matches
+}
+
+fn nearest_texts(element: &VisualBox, elements: &[VisualBox], count: usize) -> Vec<String> {
+    let (x, y) = element.center();
+    let mut others: Vec<(f64, &VisualBox)> = elements
+        .iter()
+        .filter(|other| !std::ptr::eq(*other, element) && !other.text.is_empty())
+        .map(|other| {
+            let (ox, oy) = other.center();
+            (((ox - x).powi(2) + (oy - y).powi(2)).sqrt(), other)
+        })
+        .collect();
+    others.sort_by(|a, b| a.0.total_cmp(&b.0));
+    others
+        .into_iter()
+        .take(count)
+        .map(|(_, other)| other.text.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    fn element(tag: &str, text: &str, x: f64, y: f64, width: f64, height: f64) -> VisualBox {
+        VisualBox {
+            selector: format!("{}.{}", tag, text.replace(' ', "-")),
+            tag: tag.to_string(),
+            role: String::new(),
+            text: text.to_string(),
+            x,
+            y,
+            width,
+            height,
+            clickable: tag == "button",
+        }
+    }
+
+    /// White 200x100 page with a black 40x24 block at (80, 40)
+    fn screenshot() -> Vec<u8> {
+        let image = GrayImage::from_fn(200, 100, |x, y| {
+            if (80..120).contains(&x) && (40..64).contains(&y) {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        });
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn parses_spatial_descriptions() {
+        assert_eq!(
+            SpatialQuery::parse("the button next to the price"),
+            Some(SpatialQuery {
+                target: "button".to_string(),
+                relation: SpatialRelation::NextTo,
+                anchor: "price".to_string(),
+            })
+        );
+        assert_eq!(
+            SpatialQuery::parse("link to the left of cart").map(|q| q.relation),
+            Some(SpatialRelation::LeftOf)
+        );
+        assert_eq!(SpatialQuery::parse("submit button"), None);
+    }
+
+    #[test]
+    fn scores_relations_from_geometry() {
+        let price = element("span", "$19.99", 100.0, 100.0, 60.0, 20.0);
+        let beside = element("button", "Buy", 170.0, 98.0, 50.0, 24.0);
+        let far_below = element("button", "Help", 100.0, 900.0, 50.0, 24.0);
+
+        assert!(
+            relation_score(SpatialRelation::NextTo, &beside, &price)
+                > relation_score(SpatialRelation::NextTo, &far_below, &price)
+        );
+        assert!(relation_score(SpatialRelation::RightOf, &beside, &price) >= 0.5);
+        assert!(relation_score(SpatialRelation::LeftOf, &beside, &price) < 0.5);
+        assert!(relation_score(SpatialRelation::Below, &far_below, &price) >= 0.5);
+    }
+
+    #[test]
+    fn segments_and_measures_contrast() {
+        let segmentation = Segmentation::from_screenshot(&screenshot(), 200.0).unwrap();
+        assert_eq!(segmentation.segment_count(), 2);
+
+        let block = element("button", "Go", 80.0, 40.0, 40.0, 24.0);
+        let plain = element("span", "text", 10.0, 10.0, 40.0, 16.0);
+        assert!(segmentation.contrast(&block) > 0.5);
+        assert_eq!(segmentation.contrast(&plain), 0.0);
+        assert!(segmentation.is_distinct(&block));
+        assert!(!segmentation.is_distinct(&plain));
+        assert!(
+            segmentation.prominence(&block, (200.0, 100.0))
+                > segmentation.prominence(&plain, (200.0, 100.0))
+        );
+    }
+
+    #[test]
+    fn ranks_target_beside_anchor_first() {
+        let segmentation = Segmentation::from_screenshot(&screenshot(), 200.0).unwrap();
+        let elements = vec![
+            element("span", "Price $5", 10.0, 44.0, 60.0, 16.0),
+            element("button", "Add", 80.0, 40.0, 40.0, 24.0),
+            element("button", "Close", 150.0, 2.0, 40.0, 16.0),
+        ];
+        let ranked = rank(
+            &segmentation,
+            &elements,
+            (200.0, 100.0),
+            "button next to the price",
+            5,
+        );
+        assert_eq!(ranked[0].element.text, "Add");
+        assert_eq!(ranked[0].anchor.as_deref(), Some("Price $5"));
+        assert!(ranked.iter().all(|m| m.element.tag == "button"));
+    }
+}
@@ -0,0 +1,206 @@
+// Screenshot diffing for page-change detection
+//
+// Compares two screenshots with a perceptual hash (overall look) and a grid diff
+// (where it changed) to tell whether an action visibly changed the page.
+
+use anyhow::Result;
+use image::imageops::FilterType;
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+/// Width both screenshots are scaled to before the grid diff
+const DIFF_WIDTH: u32 = 128;
+
+/// Side of a grid cell in scaled pixels
+const CELL_SIZE: u32 = 8;
+
+/// Mean luminance difference (0-255) above which a cell counts as changed
+const CELL_THRESHOLD: f32 = 12.0;
+
+/// Hash bits that may differ between screenshots of an unchanged page
+const HASH_THRESHOLD: u32 = 4;
+
+/// Share of cells that must change for a meaningful change
+const MIN_CHANGED_RATIO: f32 = 0.005;
+
+/// Changed area, as fractions (0.0 to 1.0) of the screenshot's width and height
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangedRegion {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Difference between a screenshot taken before an action and one taken after
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisualDiff {
+    /// Bits differing between the perceptual hashes, 0 to 64
+    pub hash_distance: u32,
+    /// Share of the page that changed, 0.0 to 1.0
+    pub changed_ratio: f32,
+    pub regions: Vec<ChangedRegion>,
+    /// Whether the page meaningfully changed
+    pub changed: bool,
+}
+
+impl VisualDiff {
+    /// Short human-readable summary, e.g. "2 regions changed (14% of page)"
+    pub fn describe(&self) -> String {
+        if !self.changed {
+            return "no visible change".to_string();
+        }
+        format!(
+            "{} region{} changed ({:.0}% of page)",
+            self.regions.len(),
+            if self.regions.len() == 1 { "" } else { "s" },
+            self.changed_ratio * 100.0
+        )
+    }
+}
+
+/// Compare two encoded screenshots
+pub fn compare(before: &[u8], after: &[u8]) -> Result<VisualDiff> {
+    let before = image::load_from_memory(before)?.to_luma8();
+    let after = image::load_from_memory(after)?.to_luma8();
+    Ok(compare_images(&before, &after))
+}
+
+fn compare_images(before: &GrayImage, after: &GrayImage) -> VisualDiff {
+    let hash_distance = (perceptual_hash(before) ^ perceptual_hash(after)).count_ones();
+
+    // Scale both to the same size, keeping the aspect ratio of the first
+    let (width, height) = before.dimensions();
+    let scaled_height = ((DIFF_WIDTH as f64 * height as f64 / width.max(1) as f64).round() as u32)
+        .clamp(CELL_SIZE, DIFF_WIDTH * 8);
+    let before = image::imageops::resize(before, DIFF_WIDTH, scaled_height, FilterType::Triangle);
+    let after = image::imageops::resize(after, DIFF_WIDTH, scaled_height, FilterType::Triangle);
+
+    let cols = DIFF_WIDTH.div_ceil(CELL_SIZE) as usize;
+    let rows = scaled_height.div_ceil(CELL_SIZE) as usize;
+    let mut sums = vec![0f32; cols * rows];
+    let mut counts = vec![0u32; cols * rows];
+    for (x, y, pixel) in before.enumerate_pixels() {
+        let cell = (y / CELL_SIZE) as usize * cols + (x / CELL_SIZE) as usize;
+        sums[cell] += (pixel.0[0] as f32 - after.get_pixel(x, y).0[0] as f32).abs();
+        counts[cell] += 1;
+    }
+    let changed: Vec<bool> = sums
+        .iter()
+        .zip(&counts)
+        .map(|(sum, count)| sum / (*count).max(1) as f32 > CELL_THRESHOLD)
+        .collect();
+
+    let changed_ratio = changed.iter().filter(|c| **c).count() as f32 / changed.len() as f32;
+    VisualDiff {
+        hash_distance,
+        changed_ratio,
+        regions: changed_regions(&changed, cols, rows),
+        changed: hash_distance > HASH_THRESHOLD || changed_ratio >= MIN_CHANGED_RATIO,
+    }
+}
+
+/// 64-bit difference hash: whether each pixel of a 9x8 thumbnail is brighter than
+/// its right neighbour
+pub fn perceptual_hash(image: &GrayImage) -> u64 {
+    let thumbnail = image::imageops::resize(image, 9, 8, FilterType::Triangle);
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if thumbnail.get_pixel(x, y).0[0] > thumbnail.get_pixel(x + 1, y).0[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+/// Bounding boxes of connected groups of changed cells
+fn changed_regions(changed: &[bool], cols: usize, rows: usize) -> Vec<ChangedRegion> {
+    let mut seen = vec![false; changed.len()];
+    let mut regions = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..changed.len() {
+        if !changed[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let (mut min_col, mut max_col) = (start % cols, start % cols);
+        let (mut min_row, mut max_row) = (start / cols, start / cols);
+        while let Some(cell) = stack.pop() {
+            let (col, row) = (cell % cols, cell / cols);
+            min_col = min_col.min(col);
+            max_col = max_col.max(col);
+            min_row = min_row.min(row);
+            max_row = max_row.max(row);
+            let neighbours = [
+                (col > 0).then(|| cell - 1),
+                (col + 1 < cols).then(|| cell + 1),
+                (row > 0).then(|| cell - cols),
+                (row + 1 < rows).then(|| cell + cols),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                if changed[neighbour] && !seen[neighbour] {
+                    seen[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        regions.push(ChangedRegion {
+            x: min_col as f32 / cols as f32,
+            y: min_row as f32 / rows as f32,
+            width: (max_col - min_col + 1) as f32 / cols as f32,
+            height: (max_row - min_row + 1) as f32 / rows as f32,
+        });
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    /// Light gradient page, optionally with a dark 64x48 dialog at (96, 64)
+    fn page(with_dialog: bool) -> GrayImage {
+        GrayImage::from_fn(256, 192, |x, y| {
+            if with_dialog && (96..160).contains(&x) && (64..112).contains(&y) {
+                Luma([20])
+            } else {
+                Luma([180 + (x / 4) as u8 % 60])
+            }
+        })
+    }
+
+    #[test]
+    fn identical_screenshots_are_unchanged() {
+        let diff = compare_images(&page(false), &page(false));
+        assert_eq!(diff.hash_distance, 0);
+        assert_eq!(diff.changed_ratio, 0.0);
+        assert!(diff.regions.is_empty());
+        assert!(!diff.changed);
+        assert_eq!(diff.describe(), "no visible change");
+    }
+
+    #[test]
+    fn locates_the_changed_region() {
+        let diff = compare_images(&page(false), &page(true));
+        assert!(diff.changed);
+        assert_eq!(diff.regions.len(), 1);
+        let region = &diff.regions[0];
+        // The dialog spans x 0.375-0.625 and y 0.33-0.58 of the page
+        assert!(region.x >= 0.3 && region.x <= 0.4, "{:?}", region);
+        assert!(region.y >= 0.25 && region.y <= 0.35, "{:?}", region);
+        assert!(region.width >= 0.2 && region.width <= 0.35, "{:?}", region);
+        assert!(diff.describe().starts_with("1 region changed"));
+    }
+
+    #[test]
+    fn hash_ignores_scaling() {
+        let small = image::imageops::resize(&page(true), 128, 96, FilterType::Triangle);
+        let distance = (perceptual_hash(&page(true)) ^ perceptual_hash(&small)).count_ones();
+        assert!(distance <= HASH_THRESHOLD);
+    }
+}
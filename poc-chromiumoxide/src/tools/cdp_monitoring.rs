@@ -227,6 +227,8 @@ pub struct PerformanceMetricsOutput {
     pub dom_content_loaded_ms: u64,
     pub performance_score: Option<f64>,
     pub core_web_vitals: CoreWebVitals,
+    /// Network/CPU throttling active while the metrics were collected
+    pub throttling: crate::browser::ThrottlingState,
 }
 
 #[derive(Debug, Serialize)]
@@ -829,6 +831,7 @@ impl Tool for PerformanceMetricsTool {
             dom_content_loaded_ms: dom_content_loaded,
             performance_score,
             core_web_vitals,
+            throttling: self.browser.throttling().await,
         })
     }
 }
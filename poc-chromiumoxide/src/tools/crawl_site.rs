@@ -0,0 +1,342 @@
+use super::traits::{Tool, ToolCategory};
+use crate::browser::Browser;
+use crate::perception::page_classifier::{PageClassifierChain, PageSignals};
+use crate::perception::{LayeredPerception, PageType, PerceptionMode, PerceptionResult};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// Upper bound on `max_pages`, whatever the caller asks for
+const MAX_CRAWL_PAGES: usize = 200;
+
+/// Links to files rather than pages
+const SKIPPED_EXTENSIONS: &[&str] = &[
+    "pdf", "zip", "gz", "tar", "dmg", "exe", "jpg", "jpeg", "png", "gif", "svg", "webp", "mp3",
+    "mp4", "avi", "mov", "css", "js", "xml", "json", "ics",
+];
+
+const LINKS_SCRIPT: &str = r#"
+    (function() {
+        return Array.from(document.querySelectorAll('a[href]'))
+            .filter(a => a.getAttribute('rel') !== 'nofollow' && !a.hasAttribute('download'))
+            .map(a => a.href);
+    })()
+"#;
+
+// ============================================================================
+// Crawl Site Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrawlSiteInput {
+    /// Where the crawl starts; only pages of the same origin are visited
+    pub start_url: String,
+    /// Link hops followed from the start page
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+    /// Skip URLs containing any of these, e.g. "/logout" or "?sort="
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    /// Pause between pages so the site is not hammered
+    #[serde(default = "default_delay")]
+    pub delay_ms: u64,
+}
+
+fn default_max_depth() -> usize {
+    2
+}
+
+fn default_max_pages() -> usize {
+    30
+}
+
+fn default_delay() -> u64 {
+    250
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawledPage {
+    pub url: String,
+    /// Where the page ended up after redirects
+    pub final_url: Option<String>,
+    pub depth: usize,
+    pub title: String,
+    pub page_type: PageType,
+    pub error: Option<String>,
+    /// Distinct same-origin pages this page links to
+    pub internal_links: usize,
+    pub clickable_count: usize,
+    pub input_count: usize,
+    pub form_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LinkEdge {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrawlSiteOutput {
+    pub success: bool,
+    pub start_url: String,
+    /// Pages in the order they were visited
+    pub pages: Vec<CrawledPage>,
+    /// Same-origin links between pages, including links to pages left unvisited
+    pub links: Vec<LinkEdge>,
+    /// How many visited pages are of each type
+    pub page_types: BTreeMap<String, usize>,
+    /// Whether pages were left unvisited because of `max_pages`
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+pub struct CrawlSiteTool {
+    browser: Arc<Browser>,
+}
+
+impl CrawlSiteTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+
+    /// Load `url`, run Lightning perception and classify it; returns the page and
+    /// the same-origin URLs it links to
+    async fn visit(
+        &self,
+        url: &str,
+        depth: usize,
+        origin: &url::Origin,
+        exclude: &[String],
+    ) -> Result<(CrawledPage, Vec<String>)> {
+        self.browser.navigate_to(url).await?;
+        let final_url = self.browser.current_url().await.ok();
+
+        let perception = LayeredPerception::new(self.browser.clone())
+            .perceive(PerceptionMode::Lightning)
+            .await?;
+        let lightning = match &perception {
+            PerceptionResult::Lightning(l) => l.clone(),
+            _ => return Err(anyhow!("Unexpected perception result")),
+        };
+        let page_type = match PageSignals::collect(&self.browser).await {
+            Ok(signals) => {
+                PageClassifierChain::default()
+                    .classify(&signals)
+                    .await
+                    .page_type
+            }
+            Err(e) => {
+                debug!("Page signals unavailable for {}: {}", url, e);
+                PageType::Unknown
+            }
+        };
+
+        // Redirected off the site: record the page but do not follow its links
+        let stayed = final_url
+            .as_deref()
+            .and_then(|u| Url::parse(u).ok())
+            .is_none_or(|u| &u.origin() == origin);
+        let links = if stayed {
+            let hrefs: Vec<String> =
+                serde_json::from_value(self.browser.execute_script(LINKS_SCRIPT).await?)
+                    .unwrap_or_default();
+            let mut seen = HashSet::new();
+            hrefs
+                .iter()
+                .filter_map(|href| Url::parse(href).ok())
+                .filter(|link| is_crawlable(link, origin, exclude))
+                .map(|link| normalize(&link))
+                .filter(|link| seen.insert(link.clone()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok((
+            CrawledPage {
+                url: url.to_string(),
+                final_url,
+                depth,
+                title: lightning.title,
+                page_type,
+                error: None,
+                internal_links: links.len(),
+                clickable_count: lightning.clickable_count,
+                input_count: lightning.input_count,
+                form_count: lightning.form_count,
+            },
+            links,
+        ))
+    }
+}
+
+#[async_trait]
+impl Tool for CrawlSiteTool {
+    type Input = CrawlSiteInput;
+    type Output = CrawlSiteOutput;
+
+    fn name(&self) -> &str {
+        "crawl_site"
+    }
+
+    fn description(&self) -> &str {
+        "Follow same-origin links from a start page to a bounded depth and map the site's pages and page types"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Navigation
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let start = Instant::now();
+        let start_url = Url::parse(&input.start_url)?;
+        let origin = start_url.origin();
+        let max_pages = input.max_pages.clamp(1, MAX_CRAWL_PAGES);
+        info!(
+            "Crawling {} (depth {}, at most {} pages)",
+            input.start_url, input.max_depth, max_pages
+        );
+        let return_to = self.browser.current_url().await.ok();
+
+        let mut queue = VecDeque::from([(normalize(&start_url), 0usize)]);
+        let mut queued: HashSet<String> = queue.iter().map(|(url, _)| url.clone()).collect();
+        let mut pages = Vec::new();
+        let mut links = Vec::new();
+
+        while let Some((url, depth)) = queue.pop_front() {
+            if pages.len() >= max_pages {
+                queue.push_front((url, depth));
+                break;
+            }
+            if !pages.is_empty() && input.delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(input.delay_ms)).await;
+            }
+
+            match self
+                .visit(&url, depth, &origin, &input.exclude_patterns)
+                .await
+            {
+                Ok((page, targets)) => {
+                    for target in targets {
+                        links.push(LinkEdge {
+                            from: url.clone(),
+                            to: target.clone(),
+                        });
+                        if depth < input.max_depth && queued.insert(target.clone()) {
+                            queue.push_back((target, depth + 1));
+                        }
+                    }
+                    pages.push(page);
+                }
+                Err(e) => {
+                    warn!("Failed to crawl {}: {}", url, e);
+                    pages.push(CrawledPage {
+                        url,
+                        final_url: None,
+                        depth,
+                        title: String::new(),
+                        page_type: PageType::Unknown,
+                        error: Some(e.to_string()),
+                        internal_links: 0,
+                        clickable_count: 0,
+                        input_count: 0,
+                        form_count: 0,
+                    });
+                }
+            }
+        }
+
+        // Leave the browser where the caller had it
+        if let Some(url) = return_to.filter(|u| u.starts_with("http")) {
+            if let Err(e) = self.browser.navigate_to(&url).await {
+                debug!("Could not return to {} after crawl: {}", url, e);
+            }
+        }
+
+        let mut page_types = BTreeMap::new();
+        for page in pages.iter().filter(|p| p.error.is_none()) {
+            *page_types
+                .entry(format!("{:?}", page.page_type))
+                .or_insert(0) += 1;
+        }
+
+        Ok(CrawlSiteOutput {
+            success: true,
+            start_url: input.start_url,
+            truncated: !queue.is_empty(),
+            pages,
+            links,
+            page_types,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        let url = Url::parse(&input.start_url)
+            .map_err(|e| anyhow!("Invalid start URL {}: {}", input.start_url, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(anyhow!("Start URL must be http or https"));
+        }
+        if input.max_pages == 0 {
+            return Err(anyhow!("max_pages must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// URL without its fragment, so anchors within a page are one page
+fn normalize(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.to_string()
+}
+
+/// Same-origin page links that are not excluded and do not point at files
+fn is_crawlable(url: &Url, origin: &url::Origin, exclude: &[String]) -> bool {
+    if &url.origin() != origin {
+        return false;
+    }
+    let path = url.path().to_lowercase();
+    let is_file = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .is_some_and(|(_, ext)| SKIPPED_EXTENSIONS.contains(&ext));
+    if is_file {
+        return false;
+    }
+    let url = url.as_str();
+    !exclude.iter().any(|pattern| url.contains(pattern.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragments_do_not_make_new_pages() {
+        let url = Url::parse("https://example.com/docs/intro#install").unwrap();
+        assert_eq!(normalize(&url), "https://example.com/docs/intro");
+    }
+
+    #[test]
+    fn only_same_origin_pages_are_crawled() {
+        let origin = Url::parse("https://example.com/").unwrap().origin();
+        let exclude = vec!["/logout".to_string()];
+        let crawlable = |u: &str| is_crawlable(&Url::parse(u).unwrap(), &origin, &exclude);
+
+        assert!(crawlable("https://example.com/pricing?plan=pro"));
+        assert!(!crawlable("https://blog.example.com/"));
+        assert!(!crawlable("http://example.com/pricing"));
+        assert!(!crawlable("https://example.com/files/report.PDF"));
+        assert!(!crawlable("https://example.com/account/logout"));
+    }
+}
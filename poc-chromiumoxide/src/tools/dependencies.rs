@@ -201,6 +201,11 @@ impl DependencyManager {
             .filter(|tool| categories.get(*tool) == Some(&ToolCategory::DataExtraction))
             .collect();
 
+        let verification_tools: HashSet<_> = all_tools
+            .iter()
+            .filter(|tool| categories.get(*tool) == Some(&ToolCategory::Verification))
+            .collect();
+
         let memory_tools: HashSet<_> = all_tools
             .iter()
             .filter(|tool| categories.get(*tool) == Some(&ToolCategory::Memory))
@@ -238,6 +243,18 @@ impl DependencyManager {
             }
         }
 
+        // Assertions check the outcome of navigation and interaction tools
+        for verification_tool in &verification_tools {
+            for prior_tool in navigation_tools.iter().chain(&interaction_tools) {
+                if !graph.get(*verification_tool).unwrap().contains(*prior_tool) {
+                    graph
+                        .get_mut(*verification_tool)
+                        .unwrap()
+                        .push((*prior_tool).clone());
+                }
+            }
+        }
+
         // Memory tools (like screenshots) can run after any stage
         // No automatic dependencies added for memory tools
 
@@ -1,11 +1,16 @@
 use super::traits::{Tool, ToolCategory};
 use crate::browser::Browser;
+use crate::perception::metadata::{self, PageMetadata};
+use crate::perception::pagination::{self, PageLink};
+use crate::perception::semantic::{PageRegion, REGION_OF_JS};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
-use tracing::info;
+use std::time::Duration;
+use tracing::{debug, info};
 
 // ============================================================================
 // Extract Text Tool
@@ -109,14 +114,39 @@ impl Tool for ExtractTextTool {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExtractLinksInput {
+    /// Links to consider, `a[href]` by default
     #[serde(default)]
     pub selector: Option<String>,
-    #[serde(default)]
+    #[serde(default = "default_true")]
     pub include_external: bool,
-    #[serde(default)]
+    #[serde(default = "default_true")]
     pub include_internal: bool,
-    #[serde(default)]
+    #[serde(default = "default_true")]
     pub absolute_urls: bool,
+    /// Keep only links whose absolute URL matches this regular expression
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Drop links whose absolute URL matches this regular expression
+    #[serde(default)]
+    pub exclude_pattern: Option<String>,
+    /// Keep only links to these domains or their subdomains
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Drop links to these domains or their subdomains
+    #[serde(default)]
+    pub exclude_domains: Vec<String>,
+    /// Keep only links in these parts of the page, e.g. `["main"]`
+    #[serde(default)]
+    pub regions: Vec<PageRegion>,
+    /// Keep only the first link to each URL
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize)]
@@ -124,16 +154,150 @@ pub struct ExtractLinksOutput {
     pub success: bool,
     pub links: Vec<LinkInfo>,
     pub total_count: usize,
+    /// Links found before the filters were applied
+    pub scanned_count: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinkInfo {
     pub href: String,
     pub text: String,
     pub title: Option<String>,
+    pub rel: Option<String>,
+    pub region: PageRegion,
     pub is_external: bool,
 }
 
+/// A link as read from the page, before filtering
+#[derive(Debug, Deserialize)]
+struct RawLink {
+    /// Resolved against the page URL
+    href: String,
+    /// As written in the markup
+    raw_href: String,
+    text: String,
+    title: Option<String>,
+    rel: Option<String>,
+    region: PageRegion,
+}
+
+/// The filters of an `extract_links` call
+struct LinkFilter {
+    include_external: bool,
+    include_internal: bool,
+    pattern: Option<Regex>,
+    exclude_pattern: Option<Regex>,
+    domains: Vec<String>,
+    exclude_domains: Vec<String>,
+    regions: Vec<PageRegion>,
+}
+
+impl LinkFilter {
+    fn new(input: &ExtractLinksInput) -> Result<Self> {
+        let compile = |pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .map(|pattern| {
+                    Regex::new(pattern).map_err(|e| anyhow!("Invalid pattern '{}': {}", pattern, e))
+                })
+                .transpose()
+        };
+        let normalize = |domains: &[String]| {
+            domains
+                .iter()
+                .map(|domain| domain.trim().trim_start_matches("*.").to_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .collect()
+        };
+        Ok(Self {
+            include_external: input.include_external,
+            include_internal: input.include_internal,
+            pattern: compile(&input.pattern)?,
+            exclude_pattern: compile(&input.exclude_pattern)?,
+            domains: normalize(&input.domains),
+            exclude_domains: normalize(&input.exclude_domains),
+            regions: input.regions.clone(),
+        })
+    }
+
+    fn keeps(&self, href: &str, host: Option<&str>, is_external: bool, region: PageRegion) -> bool {
+        let on_domain = |domains: &[String]| {
+            host.is_some_and(|host| {
+                domains.iter().any(|domain| {
+                    host == domain
+                        || host
+                            .strip_suffix(domain.as_str())
+                            .is_some_and(|rest| rest.ends_with('.'))
+                })
+            })
+        };
+        let wanted = if is_external {
+            self.include_external
+        } else {
+            self.include_internal
+        };
+        wanted
+            && self.pattern.as_ref().is_none_or(|re| re.is_match(href))
+            && !self
+                .exclude_pattern
+                .as_ref()
+                .is_some_and(|re| re.is_match(href))
+            && (self.domains.is_empty() || on_domain(&self.domains))
+            && !on_domain(&self.exclude_domains)
+            && (self.regions.is_empty() || self.regions.contains(&region))
+    }
+}
+
+/// Filter the links read from the page at `page_url`, in page order
+fn filter_links(
+    raw: Vec<RawLink>,
+    page_url: &str,
+    input: &ExtractLinksInput,
+) -> Result<Vec<LinkInfo>> {
+    let filter = LinkFilter::new(input)?;
+    let page_host = url::Url::parse(page_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase));
+    let mut seen = HashSet::new();
+    let mut links = Vec::new();
+    for link in raw {
+        if input.limit.is_some_and(|limit| links.len() >= limit) {
+            break;
+        }
+        let parsed = url::Url::parse(&link.href).ok();
+        // Script links go nowhere a crawler could follow
+        if parsed
+            .as_ref()
+            .is_none_or(|url| url.scheme() == "javascript")
+        {
+            continue;
+        }
+        let host = parsed
+            .as_ref()
+            .and_then(|url| url.host_str().map(str::to_lowercase));
+        let is_external = host.is_some() && host != page_host;
+        if !filter.keeps(&link.href, host.as_deref(), is_external, link.region) {
+            continue;
+        }
+        if input.unique && !seen.insert(link.href.clone()) {
+            continue;
+        }
+        links.push(LinkInfo {
+            href: if input.absolute_urls {
+                link.href
+            } else {
+                link.raw_href
+            },
+            text: link.text,
+            title: link.title,
+            rel: link.rel,
+            region: link.region,
+            is_external,
+        });
+    }
+    Ok(links)
+}
+
 pub struct ExtractLinksTool {
     browser: Arc<Browser>,
 }
@@ -151,6 +315,13 @@ impl Default for ExtractLinksInput {
             include_external: true,
             include_internal: true,
             absolute_urls: true,
+            pattern: None,
+            exclude_pattern: None,
+            domains: Vec::new(),
+            exclude_domains: Vec::new(),
+            regions: Vec::new(),
+            unique: false,
+            limit: None,
         }
     }
 }
@@ -165,7 +336,7 @@ impl Tool for ExtractLinksTool {
     }
 
     fn description(&self) -> &str {
-        "Extract all links from the page or specific elements"
+        "Extract links with their text, rel and page region, filtered by URL pattern, domain and region"
     }
 
     fn category(&self) -> ToolCategory {
@@ -173,55 +344,68 @@ impl Tool for ExtractLinksTool {
     }
 
     async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
-        let selector = input.selector.as_deref().unwrap_or("a");
+        let selector = input.selector.as_deref().unwrap_or("a[href]");
         info!("Extracting links from: {}", selector);
 
         let script = format!(
             r#"
             (function() {{
-                const links = document.querySelectorAll('{}');
-                const currentHost = window.location.host;
+                {}
+                const links = document.querySelectorAll({});
                 const results = [];
                 
                 links.forEach(link => {{
-                    const href = link.href || link.getAttribute('href') || '';
-                    if (!href) return;
+                    const rawHref = link.getAttribute('href');
+                    if (!rawHref) return;
                     
-                    let absoluteHref = href;
-                    if ({} && !href.startsWith('http')) {{
-                        const url = new URL(href, window.location.origin);
-                        absoluteHref = url.href;
-                    }}
+                    let href = rawHref;
+                    try {{
+                        href = new URL(rawHref, document.baseURI).href;
+                    }} catch (e) {{}}
                     
-                    const isExternal = absoluteHref.startsWith('http') && 
-                                       !absoluteHref.includes(currentHost);
+                    const text = (link.textContent || '').replace(/\s+/g, ' ').trim() ||
+                                 link.getAttribute('aria-label') ||
+                                 link.querySelector('img[alt]')?.getAttribute('alt') || '';
                     
-                    if (({} && isExternal) || ({} && !isExternal)) {{
-                        results.push({{
-                            href: absoluteHref,
-                            text: link.textContent?.trim() || '',
-                            title: link.title || null,
-                            is_external: isExternal
-                        }});
-                    }}
+                    results.push({{
+                        href: href,
+                        raw_href: rawHref,
+                        text: text,
+                        title: link.getAttribute('title') || null,
+                        rel: link.getAttribute('rel') || null,
+                        region: regionOf(link)
+                    }});
                 }});
                 
-                return results;
+                return {{ url: window.location.href, links: results }};
             }})()"#,
-            selector, input.absolute_urls, input.include_external, input.include_internal
+            REGION_OF_JS,
+            serde_json::to_string(selector)?
         );
 
         let result = self.browser.execute_script(&script).await?;
 
-        let links: Vec<LinkInfo> = serde_json::from_value(result)?;
+        let page_url = result["url"].as_str().unwrap_or_default().to_string();
+        let raw: Vec<RawLink> = serde_json::from_value(result["links"].clone())?;
+        let scanned_count = raw.len();
+        let links = filter_links(raw, &page_url, &input)?;
         let total_count = links.len();
+        debug!("Kept {} of {} links", total_count, scanned_count);
 
         Ok(ExtractLinksOutput {
             success: true,
             links,
             total_count,
+            scanned_count,
         })
     }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.selector.as_deref().is_some_and(str::is_empty) {
+            return Err(anyhow!("Selector cannot be empty"));
+        }
+        LinkFilter::new(input).map(|_| ())
+    }
 }
 
 // ============================================================================
@@ -236,6 +420,9 @@ pub struct ExtractDataInput {
     pub include_text: bool,
     #[serde(default)]
     pub include_html: bool,
+    /// Leave out matches in the header, navigation, sidebar and footer
+    #[serde(default)]
+    pub skip_boilerplate: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -245,7 +432,7 @@ pub struct ExtractDataOutput {
     pub total_count: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElementData {
     pub text: Option<String>,
     pub html: Option<String>,
@@ -282,18 +469,57 @@ impl Tool for ExtractDataTool {
     async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
         info!("Extracting data from: {}", input.selector);
 
-        let attributes_json = serde_json::to_string(&input.attributes)?;
+        let data = extract_elements(
+            &self.browser,
+            &input.selector,
+            &input.attributes,
+            input.include_text,
+            input.include_html,
+            input.skip_boilerplate,
+        )
+        .await?;
+        let total_count = data.len();
 
-        let script = format!(
-            r#"
+        Ok(ExtractDataOutput {
+            success: true,
+            data,
+            total_count,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.selector.is_empty() {
+            return Err(anyhow!("Selector cannot be empty"));
+        }
+        Ok(())
+    }
+}
+
+/// Data of every element matching `selector`, as collected by `extract_data`
+async fn extract_elements(
+    browser: &Browser,
+    selector: &str,
+    attributes: &[String],
+    include_text: bool,
+    include_html: bool,
+    skip_boilerplate: bool,
+) -> Result<Vec<ElementData>> {
+    let attributes_json = serde_json::to_string(attributes)?;
+
+    let script = format!(
+        r#"
             (function() {{
+                {}
                 const elements = document.querySelectorAll('{}');
                 const attributes = {};
                 const includeText = {};
                 const includeHtml = {};
+                const skipBoilerplate = {};
+                const boilerplate = ['header', 'navigation', 'sidebar', 'footer'];
                 const results = [];
                 
                 elements.forEach(el => {{
+                    if (skipBoilerplate && boilerplate.includes(regionOf(el))) return;
                     const data = {{
                         text: includeText ? (el.textContent?.trim() || null) : null,
                         html: includeHtml ? el.innerHTML : null,
@@ -309,17 +535,200 @@ impl Tool for ExtractDataTool {
                 
                 return results;
             }})()"#,
-            input.selector, attributes_json, input.include_text, input.include_html
-        );
+        REGION_OF_JS, selector, attributes_json, include_text, include_html, skip_boilerplate
+    );
+
+    let result = browser.execute_script(&script).await?;
+    Ok(serde_json::from_value(result)?)
+}
 
+// ============================================================================
+// Extract Paginated Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExtractPaginatedInput {
+    /// Selector of one result item, e.g. ".search-result"
+    pub selector: String,
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    #[serde(default = "default_include_text")]
+    pub include_text: bool,
+    /// Leave out matches in the header, navigation, sidebar and footer
+    #[serde(default)]
+    pub skip_boilerplate: bool,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+    /// How long to wait for each following page to show new items
+    #[serde(default = "default_page_timeout")]
+    pub page_timeout_ms: u64,
+}
+
+fn default_include_text() -> bool {
+    true
+}
+
+fn default_max_pages() -> usize {
+    5
+}
+
+fn default_page_timeout() -> u64 {
+    10000
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractPaginatedOutput {
+    pub success: bool,
+    /// Items of every visited page, in page order, without duplicates
+    pub data: Vec<ElementData>,
+    pub total_count: usize,
+    pub pages_visited: usize,
+    pub page_urls: Vec<String>,
+    /// "max_pages", "last_page", "no_new_items" or "next_page_failed"
+    pub stop_reason: String,
+}
+
+pub struct ExtractPaginatedTool {
+    browser: Arc<Browser>,
+}
+
+impl ExtractPaginatedTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+
+    /// Current URL with the count and first item of the result list, which changes
+    /// once the following page has rendered
+    async fn fingerprint(&self, selector: &str) -> Result<String> {
+        let script = format!(
+            r#"
+            (function() {{
+                const items = document.querySelectorAll({});
+                const first = items[0] ? (items[0].textContent || '').trim().slice(0, 200) : '';
+                return location.href + '|' + items.length + '|' + first;
+            }})()"#,
+            serde_json::to_string(selector)?
+        );
         let result = self.browser.execute_script(&script).await?;
-        let data: Vec<ElementData> = serde_json::from_value(result)?;
-        let total_count = data.len();
+        Ok(result.as_str().unwrap_or_default().to_string())
+    }
 
-        Ok(ExtractDataOutput {
+    /// Open the page behind `next`: navigate when it is a link, else click it and
+    /// wait for the result list to change
+    async fn follow(&self, next: &PageLink, selector: &str, timeout: Duration) -> Result<()> {
+        if let Some(href) = next.navigable_href() {
+            self.browser.navigate_to(href).await?;
+            self.browser.wait_for_selector(selector, timeout).await.ok();
+            return Ok(());
+        }
+
+        let before = self.fingerprint(selector).await?;
+        self.browser.click(&next.selector).await?;
+        let deadline = tokio::time::Instant::now() + timeout;
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+            if self.fingerprint(selector).await? != before {
+                return Ok(());
+            }
+        }
+        Err(anyhow!(
+            "Page did not change after clicking {}",
+            next.selector
+        ))
+    }
+}
+
+/// Identity of an item for de-duplication across pages
+fn item_key(item: &ElementData) -> String {
+    let attributes: BTreeMap<_, _> = item.attributes.iter().collect();
+    format!("{:?}|{:?}|{:?}", item.text, item.html, attributes)
+}
+
+#[async_trait]
+impl Tool for ExtractPaginatedTool {
+    type Input = ExtractPaginatedInput;
+    type Output = ExtractPaginatedOutput;
+
+    fn name(&self) -> &str {
+        "extract_paginated"
+    }
+
+    fn description(&self) -> &str {
+        "Extract items across result pages by following the page's pagination controls"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::DataExtraction
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        info!(
+            "Extracting {} across up to {} pages",
+            input.selector, input.max_pages
+        );
+        let timeout = Duration::from_millis(input.page_timeout_ms);
+
+        let mut data = Vec::new();
+        let mut seen = HashSet::new();
+        let mut page_urls = Vec::new();
+        let stop_reason = loop {
+            page_urls.push(self.browser.current_url().await.unwrap_or_default());
+            let items = extract_elements(
+                &self.browser,
+                &input.selector,
+                &input.attributes,
+                input.include_text,
+                false,
+                input.skip_boilerplate,
+            )
+            .await?;
+            let before = data.len();
+            for item in items {
+                if seen.insert(item_key(&item)) {
+                    data.push(item);
+                }
+            }
+            debug!(
+                "Page {}: {} new items",
+                page_urls.len(),
+                data.len() - before
+            );
+
+            if page_urls.len() > 1 && data.len() == before {
+                break "no_new_items";
+            }
+            if page_urls.len() >= input.max_pages {
+                break "max_pages";
+            }
+            let Some(next) = pagination::detect(&self.browser).await?.next else {
+                break "last_page";
+            };
+            if next
+                .navigable_href()
+                .is_some_and(|href| page_urls.iter().any(|url| url == href))
+            {
+                break "last_page";
+            }
+            if let Err(e) = self.follow(&next, &input.selector, timeout).await {
+                debug!("Could not open the next page: {}", e);
+                break "next_page_failed";
+            }
+        };
+
+        info!(
+            "Extracted {} items from {} pages ({})",
+            data.len(),
+            page_urls.len(),
+            stop_reason
+        );
+        let total_count = data.len();
+        Ok(ExtractPaginatedOutput {
             success: true,
             data,
             total_count,
+            pages_visited: page_urls.len(),
+            page_urls,
+            stop_reason: stop_reason.to_string(),
         })
     }
 
@@ -327,6 +736,9 @@ impl Tool for ExtractDataTool {
         if input.selector.is_empty() {
             return Err(anyhow!("Selector cannot be empty"));
         }
+        if input.max_pages == 0 {
+            return Err(anyhow!("max_pages must be at least 1"));
+        }
         Ok(())
     }
 }
@@ -609,3 +1021,155 @@ impl Tool for ExtractFormTool {
         Ok(())
     }
 }
+
+// ============================================================================
+// Extract Page Metadata Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExtractPageMetadataInput {
+    /// JSON-LD blocks can be large; their types are listed either way
+    #[serde(default = "default_true")]
+    pub include_json_ld: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractPageMetadataOutput {
+    pub success: bool,
+    #[serde(flatten)]
+    pub metadata: PageMetadata,
+}
+
+pub struct ExtractPageMetadataTool {
+    browser: Arc<Browser>,
+}
+
+impl ExtractPageMetadataTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for ExtractPageMetadataTool {
+    type Input = ExtractPageMetadataInput;
+    type Output = ExtractPageMetadataOutput;
+
+    fn name(&self) -> &str {
+        "extract_page_metadata"
+    }
+
+    fn description(&self) -> &str {
+        "Extract title, meta description, canonical URL, OpenGraph and Twitter cards, JSON-LD and favicons as structured JSON"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::DataExtraction
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        info!("Extracting page metadata");
+
+        let mut metadata = metadata::extract(&self.browser).await?;
+        if !input.include_json_ld {
+            metadata.json_ld.clear();
+        }
+        debug!(
+            "Metadata of {}: {} OpenGraph, {} Twitter, {} JSON-LD types",
+            metadata.url,
+            metadata.open_graph.len(),
+            metadata.twitter.len(),
+            metadata.json_ld_types.len()
+        );
+
+        Ok(ExtractPageMetadataOutput {
+            success: true,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(href: &str, region: PageRegion) -> RawLink {
+        RawLink {
+            href: href.to_string(),
+            raw_href: href.trim_start_matches("https://shop.example").to_string(),
+            text: String::new(),
+            title: None,
+            rel: None,
+            region,
+        }
+    }
+
+    fn page() -> Vec<RawLink> {
+        vec![
+            raw("https://shop.example/", PageRegion::Navigation),
+            raw("https://shop.example/products/1", PageRegion::Main),
+            raw("https://shop.example/products/1", PageRegion::Main),
+            raw("https://shop.example/products/2?ref=nav", PageRegion::Main),
+            raw("https://docs.example.org/guide", PageRegion::Main),
+            raw("https://cdn.notexample.org/x", PageRegion::Footer),
+            raw("javascript:void(0)", PageRegion::Main),
+        ]
+    }
+
+    #[test]
+    fn test_links_filtered_by_pattern_domain_and_region() {
+        let hrefs = |input: ExtractLinksInput| {
+            filter_links(page(), "https://shop.example/products", &input)
+                .unwrap()
+                .into_iter()
+                .map(|link| link.href)
+                .collect::<Vec<_>>()
+        };
+
+        let all = filter_links(
+            page(),
+            "https://shop.example/",
+            &ExtractLinksInput::default(),
+        )
+        .unwrap();
+        assert_eq!(all.len(), 6);
+        assert!(!all[1].is_external && all[4].is_external);
+
+        let products = hrefs(ExtractLinksInput {
+            pattern: Some("/products/".into()),
+            exclude_pattern: Some(r"\?ref=".into()),
+            unique: true,
+            ..Default::default()
+        });
+        assert_eq!(products, ["https://shop.example/products/1"]);
+
+        let docs = hrefs(ExtractLinksInput {
+            domains: vec!["example.org".into()],
+            ..Default::default()
+        });
+        assert_eq!(docs, ["https://docs.example.org/guide"]);
+
+        let internal_main = hrefs(ExtractLinksInput {
+            include_external: false,
+            regions: vec![PageRegion::Main],
+            absolute_urls: false,
+            limit: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(internal_main, ["/products/1", "/products/1"]);
+    }
+
+    #[test]
+    fn test_input_defaults_and_invalid_pattern() {
+        let input: ExtractLinksInput =
+            serde_json::from_value(serde_json::json!({"regions": ["main", "footer"]})).unwrap();
+        assert!(input.include_external && input.include_internal && input.absolute_urls);
+        assert_eq!(input.regions, [PageRegion::Main, PageRegion::Footer]);
+
+        let invalid = ExtractLinksInput {
+            pattern: Some("([".into()),
+            ..Default::default()
+        };
+        assert!(LinkFilter::new(&invalid).is_err());
+    }
+}
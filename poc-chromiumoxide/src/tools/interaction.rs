@@ -1,5 +1,6 @@
 use super::traits::{Tool, ToolCategory};
-use crate::browser::Browser;
+use crate::browser::{Browser, DropdownKind, OptionChoice};
+use crate::perception::smart_forms::{NamedFillResult, SmartFormHandler};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -230,16 +231,31 @@ pub struct SelectOptionInput {
 #[serde(rename_all = "lowercase")]
 pub enum SelectOption {
     Value(String),
+    #[serde(alias = "label")]
     Text(String),
     Index(usize),
 }
 
+impl From<&SelectOption> for OptionChoice {
+    fn from(option: &SelectOption) -> Self {
+        match option {
+            SelectOption::Value(value) => OptionChoice::Value(value.clone()),
+            SelectOption::Text(text) => OptionChoice::Label(text.clone()),
+            SelectOption::Index(index) => OptionChoice::Index(*index),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct SelectOptionOutput {
     pub success: bool,
     pub selected_value: Option<String>,
     pub selected_text: Option<String>,
     pub selected_index: Option<usize>,
+    /// Native `<select>` or a custom dropdown/combobox
+    pub dropdown_kind: DropdownKind,
+    /// Whether the dropdown shows the chosen option afterwards
+    pub verified: bool,
 }
 
 pub struct SelectOptionTool {
@@ -262,7 +278,7 @@ impl Tool for SelectOptionTool {
     }
 
     fn description(&self) -> &str {
-        "Select an option from a dropdown/select element"
+        "Select an option by label, value or index from a native select or a custom dropdown/combobox"
     }
 
     fn category(&self) -> ToolCategory {
@@ -280,68 +296,18 @@ impl Tool for SelectOptionTool {
                 .await?;
         }
 
-        // Select based on option type
-        let script = match &input.option {
-            SelectOption::Value(value) => {
-                format!(
-                    r#"
-                    (function() {{
-                        const select = document.querySelector('{}');
-                        select.value = '{}';
-                        select.dispatchEvent(new Event('change'));
-                        return {{
-                            value: select.value,
-                            text: select.options[select.selectedIndex]?.text,
-                            index: select.selectedIndex
-                        }};
-                    }})()"#,
-                    input.selector, value
-                )
-            }
-            SelectOption::Text(text) => {
-                format!(
-                    r#"
-                    (function() {{
-                        const select = document.querySelector('{}');
-                        const option = Array.from(select.options).find(o => o.text === '{}');
-                        if (option) {{
-                            select.selectedIndex = option.index;
-                            select.dispatchEvent(new Event('change'));
-                        }}
-                        return {{
-                            value: select.value,
-                            text: select.options[select.selectedIndex]?.text,
-                            index: select.selectedIndex
-                        }};
-                    }})()"#,
-                    input.selector, text
-                )
-            }
-            SelectOption::Index(index) => {
-                format!(
-                    r#"
-                    (function() {{
-                        const select = document.querySelector('{}');
-                        select.selectedIndex = {};
-                        select.dispatchEvent(new Event('change'));
-                        return {{
-                            value: select.value,
-                            text: select.options[select.selectedIndex]?.text,
-                            index: select.selectedIndex
-                        }};
-                    }})()"#,
-                    input.selector, index
-                )
-            }
-        };
-
-        let result = self.browser.execute_script(&script).await?;
+        let chosen = self
+            .browser
+            .choose_option(&input.selector, &OptionChoice::from(&input.option))
+            .await?;
 
         Ok(SelectOptionOutput {
             success: true,
-            selected_value: result["value"].as_str().map(|s| s.to_string()),
-            selected_text: result["text"].as_str().map(|s| s.to_string()),
-            selected_index: result["index"].as_u64().map(|i| i as usize),
+            selected_value: chosen.value,
+            selected_text: chosen.text,
+            selected_index: chosen.index,
+            dropdown_kind: chosen.kind,
+            verified: chosen.verified,
         })
     }
 
@@ -612,3 +578,199 @@ impl Tool for FocusTool {
         Ok(())
     }
 }
+
+// ============================================================================
+// Press Key Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PressKeyInput {
+    /// Key chords to press in order, e.g. ["Ctrl+A", "Backspace", "Enter"]
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// Convenience for a single chord
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Element to focus before sending keys
+    #[serde(default)]
+    pub selector: Option<String>,
+}
+
+impl PressKeyInput {
+    fn chords(&self) -> Vec<String> {
+        self.key.iter().cloned().chain(self.keys.clone()).collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PressKeyOutput {
+    pub success: bool,
+    pub keys_pressed: Vec<String>,
+}
+
+pub struct PressKeyTool {
+    browser: Arc<Browser>,
+}
+
+impl PressKeyTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for PressKeyTool {
+    type Input = PressKeyInput;
+    type Output = PressKeyOutput;
+
+    fn name(&self) -> &str {
+        "press_key"
+    }
+
+    fn description(&self) -> &str {
+        "Press named keys or modifier chords (Enter, Tab, Ctrl+A, ArrowDown)"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Interaction
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let keys = input.chords();
+        info!("Pressing keys: {:?}", keys);
+
+        self.browser
+            .send_keys(input.selector.as_deref(), &keys)
+            .await?;
+
+        Ok(PressKeyOutput {
+            success: true,
+            keys_pressed: keys,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        let keys = input.chords();
+        if keys.is_empty() {
+            return Err(anyhow!("At least one key is required"));
+        }
+        for key in &keys {
+            crate::browser::keyboard::KeyChord::parse(key)?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Fill Form Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FillFormInput {
+    /// Values by what the field means rather than by selector, e.g.
+    /// `{"email": "ada@example.com", "first_name": "Ada", "country": "Canada", "newsletter": false}`
+    pub values: serde_json::Map<String, serde_json::Value>,
+    /// The form to fill; by default the one matching the most values
+    #[serde(default)]
+    pub form_selector: Option<String>,
+    /// Click the form's submit button once every value is filled
+    #[serde(default)]
+    pub submit: bool,
+}
+
+impl FillFormInput {
+    /// The values as text: strings as given, numbers and booleans written out
+    fn value_texts(&self) -> Result<Vec<(String, String)>> {
+        self.values
+            .iter()
+            .map(|(key, value)| {
+                let text = match value {
+                    serde_json::Value::String(text) => text.clone(),
+                    serde_json::Value::Number(_) | serde_json::Value::Bool(_) => value.to_string(),
+                    _ => {
+                        return Err(anyhow!(
+                            "Value of '{}' must be a string, number or boolean",
+                            key
+                        ))
+                    }
+                };
+                Ok((key.clone(), text))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FillFormOutput {
+    #[serde(flatten)]
+    pub result: NamedFillResult,
+    pub submitted: bool,
+}
+
+pub struct FillFormTool {
+    browser: Arc<Browser>,
+}
+
+impl FillFormTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for FillFormTool {
+    type Input = FillFormInput;
+    type Output = FillFormOutput;
+
+    fn name(&self) -> &str {
+        "fill_form"
+    }
+
+    fn description(&self) -> &str {
+        "Fill a whole form in one call from values keyed by field meaning (email, first_name, country), reporting the keys no field matched"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Interaction
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        info!("Filling {} form values", input.values.len());
+
+        let result = SmartFormHandler::new()
+            .fill_by_name(
+                &self.browser,
+                input.form_selector.as_deref(),
+                &input.value_texts()?,
+            )
+            .await?;
+        if !result.unmatched.is_empty() {
+            warn!("No field matched: {}", result.unmatched.join(", "));
+        }
+
+        // A partly filled form is left for the caller to fix rather than submitted
+        let submit = result
+            .submit_selectors
+            .first()
+            .filter(|_| input.submit && result.success);
+        let submitted = match submit {
+            Some(selector) => {
+                self.browser.click(selector).await?;
+                true
+            }
+            None => false,
+        };
+
+        Ok(FillFormOutput { result, submitted })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.values.is_empty() {
+            return Err(anyhow!("At least one value is required"));
+        }
+        if input.form_selector.as_deref().is_some_and(str::is_empty) {
+            return Err(anyhow!("Form selector cannot be empty"));
+        }
+        input.value_texts().map(|_| ())
+    }
+}
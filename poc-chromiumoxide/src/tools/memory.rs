@@ -68,6 +68,8 @@ impl ScreenshotTool {
             viewport_width: 1920,
             viewport_height: 1080,
             wait_after_load: std::time::Duration::from_millis(500),
+            selector: None,
+            clip: None,
         };
         self.browser.screenshot(options).await
     }
@@ -99,6 +101,8 @@ impl ScreenshotTool {
             viewport_width: width.max(1920),
             viewport_height: height.max(1080),
             wait_after_load: std::time::Duration::from_secs(1),
+            selector: None,
+            clip: None,
         };
         self.browser.screenshot(options).await
     }
@@ -110,47 +114,8 @@ impl ScreenshotTool {
             .await
             .map_err(|_| anyhow!("Element not found: {}", selector))?;
 
-        // Scroll element into view
-        let scroll_script = format!(
-            "document.querySelector('{}').scrollIntoView({{ behavior: 'auto', block: 'center', inline: 'center' }})",
-            selector
-        );
-        self.browser.execute_script(&scroll_script).await?;
-
-        // Wait a bit for scrolling to complete
-        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-
-        // Get element bounding box
-        let rect_script = format!(
-            r#"
-            const element = document.querySelector('{}');
-            const rect = element.getBoundingClientRect();
-            JSON.stringify({{
-                x: rect.x,
-                y: rect.y,
-                width: rect.width,
-                height: rect.height,
-                left: rect.left,
-                top: rect.top,
-                right: rect.right,
-                bottom: rect.bottom
-            }})
-            "#,
-            selector
-        );
-
-        let rect_result = self.browser.execute_script(&rect_script).await?;
-        let x = rect_result["x"].as_f64().unwrap_or(0.0) as i32;
-        let y = rect_result["y"].as_f64().unwrap_or(0.0) as i32;
-        let width = rect_result["width"].as_f64().unwrap_or(100.0) as u32;
-        let height = rect_result["height"].as_f64().unwrap_or(100.0) as u32;
-
-        if width == 0 || height == 0 {
-            return Err(anyhow!("Element has zero dimensions: {}", selector));
-        }
-
-        // Take full viewport screenshot first
-        let viewport_options = crate::browser::ScreenshotOptions {
+        // The browser scrolls the element into view and clips the capture to its bounds
+        let options = crate::browser::ScreenshotOptions {
             full_page: false,
             format: match input.format {
                 ScreenshotFormat::Png => "png".to_string(),
@@ -164,19 +129,10 @@ impl ScreenshotTool {
             viewport_width: 1920,
             viewport_height: 1080,
             wait_after_load: std::time::Duration::from_millis(200),
+            selector: Some(selector.to_string()),
+            clip: None,
         };
-
-        let viewport_screenshot = self.browser.screenshot(viewport_options).await?;
-
-        // For now, return the full screenshot with element info in debug
-        // TODO: Implement actual image cropping when image processing crate is available
-        debug!(
-            "Element bounds: x={}, y={}, width={}, height={}",
-            x, y, width, height
-        );
-        debug!("Note: Element cropping not yet implemented, returning full viewport");
-
-        Ok(viewport_screenshot)
+        self.browser.screenshot(options).await
     }
 }
 
@@ -4,6 +4,7 @@
 pub mod cache;
 pub mod cdp_monitoring;
 pub mod config;
+pub mod crawl_site;
 pub mod dependencies;
 pub mod extraction;
 pub mod intelligent_action;
@@ -13,6 +14,8 @@ pub mod navigation;
 pub mod registry;
 pub mod synchronization;
 pub mod synthetic_fixtures;
+pub mod tabs;
 pub mod traits;
+pub mod verification;
 
 // Re-exports enabled for tool system
@@ -1,5 +1,5 @@
 use super::traits::{Tool, ToolCategory};
-use crate::browser::Browser;
+use crate::browser::{Browser, ScrollOptions, ScrollReport};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -178,6 +178,69 @@ impl Tool for ScrollTool {
     }
 }
 
+// ============================================================================
+// Scroll Until Stable Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScrollUntilStableInput {
+    #[serde(flatten)]
+    pub options: ScrollOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScrollUntilStableOutput {
+    pub success: bool,
+    #[serde(flatten)]
+    pub report: ScrollReport,
+}
+
+pub struct ScrollUntilStableTool {
+    browser: Arc<Browser>,
+}
+
+impl ScrollUntilStableTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for ScrollUntilStableTool {
+    type Input = ScrollUntilStableInput;
+    type Output = ScrollUntilStableOutput;
+
+    fn name(&self) -> &str {
+        "scroll_until_stable"
+    }
+
+    fn description(&self) -> &str {
+        "Scroll an infinite feed or lazily loaded listing until no more content loads"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Navigation
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let report = self.browser.scroll_until_stable(&input.options).await?;
+        Ok(ScrollUntilStableOutput {
+            success: true,
+            report,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.options.max_scrolls == 0 {
+            return Err(anyhow::anyhow!("max_scrolls must be at least 1"));
+        }
+        if input.options.item_selector.as_deref() == Some("") {
+            return Err(anyhow::anyhow!("item_selector cannot be empty"));
+        }
+        Ok(())
+    }
+}
+
 // ============================================================================
 // Refresh Tool
 // ============================================================================
@@ -9,22 +9,32 @@ use tracing::{debug, error, info, warn};
 
 use super::cache::ToolCache;
 use super::cdp_monitoring::{CDPNetworkIdleTool, NetworkMonitorTool, PerformanceMetricsTool};
+use super::crawl_site::CrawlSiteTool;
 use super::dependencies::{DependencyManager, ExecutionContext, ExecutionPlan, ExecutionStats};
 use super::extraction::{
-    ExtractDataTool, ExtractFormTool, ExtractLinksTool, ExtractTableTool, ExtractTextTool,
+    ExtractDataTool, ExtractFormTool, ExtractLinksTool, ExtractPageMetadataTool,
+    ExtractPaginatedTool, ExtractTableTool, ExtractTextTool,
 };
 use super::intelligent_action::IntelligentActionTool;
-use super::interaction::{ClickTool, FocusTool, HoverTool, SelectOptionTool, TypeTextTool};
+use super::interaction::{
+    ClickTool, FillFormTool, FocusTool, HoverTool, PressKeyTool, SelectOptionTool, TypeTextTool,
+};
 use super::memory::{
     GetElementInfoTool, HistoryTrackerTool, PersistentCacheTool, ScreenshotTool, SessionMemoryTool,
 };
-use super::navigation::{GoBackTool, GoForwardTool, NavigateTool, RefreshTool, ScrollTool};
+use super::navigation::{
+    GoBackTool, GoForwardTool, NavigateTool, RefreshTool, ScrollTool, ScrollUntilStableTool,
+};
 use super::synchronization::{
     WaitForConditionTool, WaitForElementTool, WaitForNavigationTool, WaitForNetworkIdleTool,
+    WaitForTextTool, WaitForUrlTool,
 };
 use super::synthetic_fixtures::CreateTestFixtureTool;
+use super::tabs::{CloseTabTool, ListTabsTool, OpenTabTool, SwitchTabTool};
 use super::traits::{DynamicTool, DynamicToolWrapper, ToolCategory, ToolMetadata};
+use super::verification::{AssertElementVisibleTool, AssertTextPresentTool, AssertUrlMatchesTool};
 use crate::browser::Browser;
+use crate::coordination::correlation::{current_correlation_id, with_correlation_id};
 
 /// Performance metrics for tool execution
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -80,10 +90,20 @@ impl ToolRegistry {
 
         match name {
             "navigate_to_url"
+            | "open_tab"
             | "wait_for_element"
             | "wait_for_navigation"
             | "wait_for_condition"
-            | "wait_for_network_idle" => nav_timeout,
+            | "wait_for_text"
+            | "wait_for_url"
+            | "assert_text_present"
+            | "assert_element_visible"
+            | "assert_url_matches"
+            | "wait_for_network_idle"
+            | "extract_paginated"
+            | "scroll_until_stable" => nav_timeout,
+            // Loads many pages in one call
+            "crawl_site" => nav_timeout * 10,
             _ => Self::execution_timeout(),
         }
     }
@@ -108,16 +128,26 @@ impl ToolRegistry {
         // Navigation Tools
         self.register_tool(NavigateTool::new(browser.clone()));
         self.register_tool(ScrollTool::new(browser.clone()));
+        self.register_tool(ScrollUntilStableTool::new(browser.clone()));
         self.register_tool(RefreshTool::new(browser.clone()));
         self.register_tool(GoBackTool::new(browser.clone()));
         self.register_tool(GoForwardTool::new(browser.clone()));
+        self.register_tool(CrawlSiteTool::new(browser.clone()));
+
+        // Tab Tools
+        self.register_tool(ListTabsTool::new(browser.clone()));
+        self.register_tool(OpenTabTool::new(browser.clone()));
+        self.register_tool(SwitchTabTool::new(browser.clone()));
+        self.register_tool(CloseTabTool::new(browser.clone()));
 
         // Interaction Tools
         self.register_tool(ClickTool::new(browser.clone()));
         self.register_tool(TypeTextTool::new(browser.clone()));
         self.register_tool(SelectOptionTool::new(browser.clone()));
+        self.register_tool(FillFormTool::new(browser.clone()));
         self.register_tool(HoverTool::new(browser.clone()));
         self.register_tool(FocusTool::new(browser.clone()));
+        self.register_tool(PressKeyTool::new(browser.clone()));
 
         // Intelligent Action Engine
         self.register_tool(IntelligentActionTool::new(browser.clone()));
@@ -128,13 +158,22 @@ impl ToolRegistry {
         self.register_tool(ExtractDataTool::new(browser.clone()));
         self.register_tool(ExtractTableTool::new(browser.clone()));
         self.register_tool(ExtractFormTool::new(browser.clone()));
+        self.register_tool(ExtractPaginatedTool::new(browser.clone()));
+        self.register_tool(ExtractPageMetadataTool::new(browser.clone()));
 
         // Synchronization Tools
         self.register_tool(WaitForElementTool::new(browser.clone()));
         self.register_tool(WaitForConditionTool::new(browser.clone()));
+        self.register_tool(WaitForTextTool::new(browser.clone()));
+        self.register_tool(WaitForUrlTool::new(browser.clone()));
         self.register_tool(WaitForNavigationTool::new(browser.clone()));
         self.register_tool(WaitForNetworkIdleTool::new(browser.clone()));
 
+        // Verification Tools
+        self.register_tool(AssertTextPresentTool::new(browser.clone()));
+        self.register_tool(AssertElementVisibleTool::new(browser.clone()));
+        self.register_tool(AssertUrlMatchesTool::new(browser.clone()));
+
         // Memory Tools
         self.register_tool(ScreenshotTool::new(browser.clone()));
         self.register_tool(SessionMemoryTool::new(browser.clone()));
@@ -493,12 +532,14 @@ impl ToolRegistry {
                     let tool_name_clone = tool_name.clone();
                     let input = serde_json::json!({}); // Default empty input - could be parameterized
 
-                    let handle = tokio::spawn(async move {
+                    // Parallel tools stay part of the request running the workflow
+                    let correlation_id = current_correlation_id();
+                    let handle = tokio::spawn(with_correlation_id(correlation_id, async move {
                         let start = Instant::now();
                         let result = registry_clone.execute_tool(&tool_name_clone, input).await;
                         let duration = start.elapsed().as_millis() as u64;
                         (tool_name_clone, result, duration)
-                    });
+                    }));
                     handles.push(handle);
                 }
 
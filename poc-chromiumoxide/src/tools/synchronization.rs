@@ -2,9 +2,10 @@ use super::traits::{Tool, ToolCategory};
 use crate::browser::{core::BrowserOps, Browser};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::info;
 
 // ============================================================================
@@ -24,6 +25,45 @@ fn default_timeout() -> u64 {
     10000
 }
 
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Defines `isVisible(el)`: displayed, not hidden and taking up space
+pub(super) const IS_VISIBLE_JS: &str = r#"
+    function isVisible(el) {
+        const style = window.getComputedStyle(el);
+        const rect = el.getBoundingClientRect();
+        return style.display !== 'none' && style.visibility !== 'hidden' &&
+               rect.width > 0 && rect.height > 0;
+    }
+"#;
+
+/// Evaluate `script` every `interval` until it returns something other than
+/// `false` or `null`, and return that; `None` once `timeout` has passed.
+/// Script errors, e.g. during a navigation, count as not yet.
+async fn poll_script(
+    browser: &Browser,
+    script: &str,
+    timeout: Duration,
+    interval: Duration,
+) -> Option<serde_json::Value> {
+    let start = Instant::now();
+    loop {
+        if let Ok(value) = browser.execute_script(script).await {
+            if !matches!(
+                value,
+                serde_json::Value::Null | serde_json::Value::Bool(false)
+            ) {
+                return Some(value);
+            }
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            return None;
+        }
+        tokio::time::sleep(interval.min(timeout - elapsed)).await;
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct WaitForElementOutput {
     pub success: bool,
@@ -64,25 +104,36 @@ impl Tool for WaitForElementTool {
 
         let timeout = Duration::from_millis(input.timeout_ms);
 
-        match self
+        let mut found = self
             .browser
             .wait_for_selector(&input.selector, timeout)
             .await
-        {
-            Ok(_) => {
-                let wait_time = start.elapsed().as_millis() as u64;
-                Ok(WaitForElementOutput {
-                    success: true,
-                    element_found: true,
-                    wait_time_ms: wait_time,
-                })
-            }
-            Err(_) => Ok(WaitForElementOutput {
-                success: false,
-                element_found: false,
-                wait_time_ms: input.timeout_ms,
-            }),
+            .is_ok();
+        if found && input.visible {
+            let script = format!(
+                r#"
+                (function() {{
+                    {}
+                    return Array.from(document.querySelectorAll({})).some(isVisible);
+                }})()"#,
+                IS_VISIBLE_JS,
+                serde_json::to_string(&input.selector)?
+            );
+            let remaining = timeout.saturating_sub(start.elapsed());
+            found = poll_script(&self.browser, &script, remaining, POLL_INTERVAL)
+                .await
+                .is_some();
         }
+
+        Ok(WaitForElementOutput {
+            success: found,
+            element_found: found,
+            wait_time_ms: if found {
+                start.elapsed().as_millis() as u64
+            } else {
+                input.timeout_ms
+            },
+        })
     }
 
     async fn validate_input(&self, input: &Self::Input) -> Result<()> {
@@ -191,6 +242,235 @@ impl Tool for WaitForConditionTool {
     }
 }
 
+// ============================================================================
+// Wait For Text Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WaitForTextInput {
+    pub text: String,
+    /// Only look inside the first element matching this; the whole page by default
+    #[serde(default)]
+    pub selector: Option<String>,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Wait for the text to go away instead, e.g. a "Loading..." message
+    #[serde(default)]
+    pub absent: bool,
+    #[serde(default = "default_timeout")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_check_interval")]
+    pub check_interval_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WaitForTextOutput {
+    pub success: bool,
+    /// Whether the text was on the page when the wait ended
+    pub text_present: bool,
+    pub wait_time_ms: u64,
+}
+
+pub struct WaitForTextTool {
+    browser: Arc<Browser>,
+}
+
+impl WaitForTextTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for WaitForTextTool {
+    type Input = WaitForTextInput;
+    type Output = WaitForTextOutput;
+
+    fn name(&self) -> &str {
+        "wait_for_text"
+    }
+
+    fn description(&self) -> &str {
+        "Wait for text to appear on the page or in an element, or to disappear"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Synchronization
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        info!(
+            "Waiting for text to {}: {}",
+            if input.absent { "disappear" } else { "appear" },
+            input.text
+        );
+        let start = Instant::now();
+
+        // Whitespace is collapsed on both sides so line breaks in the markup don't matter
+        let script = format!(
+            r#"
+            (function() {{
+                const selector = {};
+                const root = selector ? document.querySelector(selector) : document.body;
+                const normalize = s => {{
+                    s = (s || '').replace(/\s+/g, ' ').trim();
+                    return {} ? s : s.toLowerCase();
+                }};
+                const present = !!root &&
+                    normalize(root.innerText || root.textContent).includes(normalize({}));
+                return present !== {};
+            }})()"#,
+            serde_json::to_string(&input.selector)?,
+            input.case_sensitive,
+            serde_json::to_string(&input.text)?,
+            input.absent
+        );
+
+        let met = poll_script(
+            &self.browser,
+            &script,
+            Duration::from_millis(input.timeout_ms),
+            Duration::from_millis(input.check_interval_ms),
+        )
+        .await
+        .is_some();
+
+        Ok(WaitForTextOutput {
+            success: met,
+            text_present: met != input.absent,
+            wait_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.text.trim().is_empty() {
+            return Err(anyhow!("Text cannot be empty"));
+        }
+        if input.selector.as_deref().is_some_and(str::is_empty) {
+            return Err(anyhow!("Selector cannot be empty"));
+        }
+        if input.check_interval_ms == 0 {
+            return Err(anyhow!("Check interval must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Wait For URL Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WaitForUrlInput {
+    /// Wait until the page URL contains this
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Wait until the page URL matches this regular expression
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default = "default_timeout")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_check_interval")]
+    pub check_interval_ms: u64,
+}
+
+impl WaitForUrlInput {
+    fn regex(&self) -> Result<Option<Regex>> {
+        compile_pattern(self.pattern.as_deref())
+    }
+}
+
+pub(super) fn compile_pattern(pattern: Option<&str>) -> Result<Option<Regex>> {
+    pattern
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| anyhow!("Invalid pattern '{}': {}", pattern, e))
+        })
+        .transpose()
+}
+
+/// Whether `url` contains `expected` and matches `pattern`, where given
+pub(super) fn url_matches(url: &str, expected: Option<&str>, pattern: Option<&Regex>) -> bool {
+    expected.is_none_or(|expected| url.contains(expected))
+        && pattern.is_none_or(|pattern| pattern.is_match(url))
+}
+
+#[derive(Debug, Serialize)]
+pub struct WaitForUrlOutput {
+    pub success: bool,
+    pub url_matched: bool,
+    pub final_url: String,
+    pub wait_time_ms: u64,
+}
+
+pub struct WaitForUrlTool {
+    browser: Arc<Browser>,
+}
+
+impl WaitForUrlTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for WaitForUrlTool {
+    type Input = WaitForUrlInput;
+    type Output = WaitForUrlOutput;
+
+    fn name(&self) -> &str {
+        "wait_for_url"
+    }
+
+    fn description(&self) -> &str {
+        "Wait for the page URL to contain a string or match a regular expression"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Synchronization
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let pattern = input.regex()?;
+        info!(
+            "Waiting for URL: {}",
+            input
+                .url
+                .as_deref()
+                .or(input.pattern.as_deref())
+                .unwrap_or_default()
+        );
+        let start = Instant::now();
+        let timeout = Duration::from_millis(input.timeout_ms);
+        let check_interval = Duration::from_millis(input.check_interval_ms);
+
+        loop {
+            // The URL can't be read mid-navigation; that counts as not yet
+            let final_url = self.browser.current_url().await.unwrap_or_default();
+            let matched = url_matches(&final_url, input.url.as_deref(), pattern.as_ref());
+            let elapsed = start.elapsed();
+            if matched || elapsed >= timeout {
+                return Ok(WaitForUrlOutput {
+                    success: matched,
+                    url_matched: matched,
+                    final_url,
+                    wait_time_ms: elapsed.as_millis() as u64,
+                });
+            }
+            tokio::time::sleep(check_interval.min(timeout - elapsed)).await;
+        }
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.url.as_deref().is_none_or(str::is_empty) && input.pattern.is_none() {
+            return Err(anyhow!("Either url or pattern is required"));
+        }
+        if input.check_interval_ms == 0 {
+            return Err(anyhow!("Check interval must be greater than 0"));
+        }
+        input.regex().map(|_| ())
+    }
+}
+
 // ============================================================================
 // Wait For Navigation Tool
 // ============================================================================
@@ -449,3 +729,29 @@ impl Tool for WaitForNetworkIdleTool {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_matches_substring_and_pattern() {
+        let url = "https://shop.example/checkout/confirm?order=42";
+        let confirm = Regex::new(r"/checkout/confirm\?order=\d+$").unwrap();
+
+        assert!(url_matches(url, Some("/checkout"), None));
+        assert!(url_matches(url, None, Some(&confirm)));
+        assert!(url_matches(url, Some("shop.example"), Some(&confirm)));
+        assert!(!url_matches(url, Some("/cart"), Some(&confirm)));
+        assert!(!url_matches(
+            "https://shop.example/checkout",
+            None,
+            Some(&confirm)
+        ));
+
+        let input: WaitForUrlInput =
+            serde_json::from_value(serde_json::json!({"pattern": "(["})).unwrap();
+        assert_eq!(input.timeout_ms, 10000);
+        assert!(input.regex().is_err());
+    }
+}
@@ -0,0 +1,365 @@
+use super::traits::{Tool, ToolCategory};
+use crate::browser::{Browser, TabInfo};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// Which tab a tool acts on: by id, by position, or by text in its URL or title.
+/// The first of these that is given is used.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TabRef {
+    /// Id from `list_tabs` or `open_tab`
+    #[serde(default)]
+    pub tab_id: Option<String>,
+    /// Position in `list_tabs`, from 0
+    #[serde(default)]
+    pub index: Option<usize>,
+    /// Text the tab's URL or title contains, ignoring case; the first such tab is used
+    #[serde(default)]
+    pub matching: Option<String>,
+}
+
+impl TabRef {
+    fn is_empty(&self) -> bool {
+        self.tab_id.is_none() && self.index.is_none() && self.matching.is_none()
+    }
+
+    /// The tab among `tabs` this refers to
+    fn resolve<'a>(&self, tabs: &'a [TabInfo]) -> Result<&'a TabInfo> {
+        if let Some(id) = &self.tab_id {
+            return tabs
+                .iter()
+                .find(|tab| &tab.id == id)
+                .ok_or_else(|| anyhow!("Tab not found: {}", id));
+        }
+        if let Some(index) = self.index {
+            return tabs.get(index).ok_or_else(|| {
+                anyhow!("No tab at index {}, there are {} tabs", index, tabs.len())
+            });
+        }
+        if let Some(text) = &self.matching {
+            let text = text.to_lowercase();
+            return tabs
+                .iter()
+                .find(|tab| {
+                    tab.url.to_lowercase().contains(&text)
+                        || tab.title.to_lowercase().contains(&text)
+                })
+                .ok_or_else(|| anyhow!("No tab's URL or title contains '{}'", text));
+        }
+        Err(anyhow!("Give tab_id, index or matching"))
+    }
+}
+
+fn default_activate() -> bool {
+    true
+}
+
+// ============================================================================
+// List Tabs Tool
+// ============================================================================
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ListTabsInput {}
+
+#[derive(Debug, Serialize)]
+pub struct ListTabsOutput {
+    pub success: bool,
+    pub tabs: Vec<TabInfo>,
+    pub active_index: Option<usize>,
+    pub count: usize,
+}
+
+pub struct ListTabsTool {
+    browser: Arc<Browser>,
+}
+
+impl ListTabsTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for ListTabsTool {
+    type Input = ListTabsInput;
+    type Output = ListTabsOutput;
+
+    fn name(&self) -> &str {
+        "list_tabs"
+    }
+
+    fn description(&self) -> &str {
+        "List the open tabs with their id, URL, title and which one is active"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Navigation
+    }
+
+    async fn execute(&self, _input: Self::Input) -> Result<Self::Output> {
+        let tabs = self.browser.list_tabs().await?;
+        Ok(ListTabsOutput {
+            success: true,
+            active_index: tabs.iter().position(|tab| tab.active),
+            count: tabs.len(),
+            tabs,
+        })
+    }
+}
+
+// ============================================================================
+// Open Tab Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenTabInput {
+    /// Page to load; a blank tab by default
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Make the new tab the one later tools act on; leave it in the background
+    /// to open several pages before visiting them
+    #[serde(default = "default_activate")]
+    pub activate: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpenTabOutput {
+    pub success: bool,
+    pub tab: TabInfo,
+    pub tab_count: usize,
+}
+
+pub struct OpenTabTool {
+    browser: Arc<Browser>,
+}
+
+impl OpenTabTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for OpenTabTool {
+    type Input = OpenTabInput;
+    type Output = OpenTabOutput;
+
+    fn name(&self) -> &str {
+        "open_tab"
+    }
+
+    fn description(&self) -> &str {
+        "Open a new tab, optionally at a URL, in the foreground or background"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Navigation
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let tab = self
+            .browser
+            .open_tab(input.url.as_deref(), input.activate)
+            .await?;
+        let tab_count = self.browser.list_tabs().await?.len();
+        Ok(OpenTabOutput {
+            success: true,
+            tab,
+            tab_count,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if let Some(url) = &input.url {
+            url::Url::parse(url).map_err(|e| anyhow!("Invalid URL '{}': {}", url, e))?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Switch Tab Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwitchTabInput {
+    #[serde(flatten)]
+    pub tab: TabRef,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SwitchTabOutput {
+    pub success: bool,
+    pub tab: TabInfo,
+    pub previous_tab_id: String,
+}
+
+pub struct SwitchTabTool {
+    browser: Arc<Browser>,
+}
+
+impl SwitchTabTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for SwitchTabTool {
+    type Input = SwitchTabInput;
+    type Output = SwitchTabOutput;
+
+    fn name(&self) -> &str {
+        "switch_tab"
+    }
+
+    fn description(&self) -> &str {
+        "Make a tab, chosen by id, index or text in its URL or title, the one later tools act on"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Navigation
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let previous_tab_id = self.browser.active_tab_id().await;
+        let tabs = self.browser.list_tabs().await?;
+        let tab_id = input.tab.resolve(&tabs)?.id.clone();
+        info!("Switching from tab {} to {}", previous_tab_id, tab_id);
+
+        let tab = self.browser.switch_tab(&tab_id).await?;
+        Ok(SwitchTabOutput {
+            success: true,
+            tab,
+            previous_tab_id,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.tab.is_empty() {
+            return Err(anyhow!("Give tab_id, index or matching"));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Close Tab Tool
+// ============================================================================
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CloseTabInput {
+    /// The tab to close; the active tab when none is given
+    #[serde(flatten)]
+    pub tab: TabRef,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CloseTabOutput {
+    pub success: bool,
+    pub closed_tab_id: String,
+    /// The tab later tools act on now
+    pub active_tab: Option<TabInfo>,
+    pub remaining: usize,
+}
+
+pub struct CloseTabTool {
+    browser: Arc<Browser>,
+}
+
+impl CloseTabTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for CloseTabTool {
+    type Input = CloseTabInput;
+    type Output = CloseTabOutput;
+
+    fn name(&self) -> &str {
+        "close_tab"
+    }
+
+    fn description(&self) -> &str {
+        "Close a tab, chosen by id, index or text in its URL or title, or the active one"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Navigation
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let closed_tab_id = if input.tab.is_empty() {
+            self.browser.active_tab_id().await
+        } else {
+            let tabs = self.browser.list_tabs().await?;
+            input.tab.resolve(&tabs)?.id.clone()
+        };
+
+        self.browser.close_tab(&closed_tab_id).await?;
+        let tabs = self.browser.list_tabs().await?;
+        Ok(CloseTabOutput {
+            success: true,
+            closed_tab_id,
+            remaining: tabs.len(),
+            active_tab: tabs.into_iter().find(|tab| tab.active),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tab(id: &str, url: &str, title: &str) -> TabInfo {
+        TabInfo {
+            id: id.to_string(),
+            url: url.to_string(),
+            title: title.to_string(),
+            active: false,
+        }
+    }
+
+    #[test]
+    fn test_tab_ref_resolution() {
+        let tabs = [
+            tab("A1", "https://search.example/?q=rust", "rust - Search"),
+            tab("B2", "https://docs.example/book", "The Rust Book"),
+            tab("C3", "https://blog.example/post", "Release notes"),
+        ];
+        let resolve = |tab: TabRef| tab.resolve(&tabs).map(|tab| tab.id.as_str());
+
+        let by_id = TabRef {
+            tab_id: Some("C3".into()),
+            index: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(resolve(by_id).unwrap(), "C3");
+        let by_index = TabRef {
+            index: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(resolve(by_index).unwrap(), "B2");
+        let by_text = TabRef {
+            matching: Some("RELEASE".into()),
+            ..Default::default()
+        };
+        assert_eq!(resolve(by_text).unwrap(), "C3");
+
+        let out_of_range = TabRef {
+            index: Some(3),
+            ..Default::default()
+        };
+        assert!(resolve(out_of_range).is_err());
+        assert!(resolve(TabRef::default()).is_err());
+
+        // Flattened into tool input
+        let input: SwitchTabInput = serde_json::from_str(r#"{"matching": "docs"}"#).unwrap();
+        assert_eq!(input.tab.matching.as_deref(), Some("docs"));
+    }
+}
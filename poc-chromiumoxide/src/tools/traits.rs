@@ -13,6 +13,7 @@ pub enum ToolCategory {
     Interaction,
     DataExtraction,
     Synchronization,
+    Verification,
     Memory,
     Intelligence,
     MetaCognitive,
@@ -0,0 +1,462 @@
+use super::synchronization::{compile_pattern, url_matches, IS_VISIBLE_JS, POLL_INTERVAL};
+use super::traits::{Tool, ToolCategory};
+use crate::browser::Browser;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Structured pass/fail result of the assertion tools
+#[derive(Debug, Serialize)]
+pub struct AssertionOutput {
+    pub passed: bool,
+    /// The assertion in words, e.g. `text "Thank you" is present`
+    pub assertion: String,
+    pub expected: Value,
+    /// What the page showed at the last check
+    pub actual: Value,
+    /// Why the assertion failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub wait_time_ms: u64,
+}
+
+/// Run `check` until it passes or `timeout` is over, pausing `POLL_INTERVAL`
+/// in between; a zero timeout checks once. Gives the last verdict, what was
+/// seen and the time taken.
+async fn check_until<F, Fut>(timeout: Duration, mut check: F) -> Result<(bool, Value, u64)>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(bool, Value)>>,
+{
+    let start = Instant::now();
+    loop {
+        let outcome = check().await;
+        let elapsed = start.elapsed();
+        match outcome {
+            Ok((true, actual)) => return Ok((true, actual, elapsed.as_millis() as u64)),
+            // Errors while the page changes count as not yet, the last one is reported
+            Ok((false, actual)) if elapsed >= timeout => {
+                return Ok((false, actual, elapsed.as_millis() as u64))
+            }
+            Err(e) if elapsed >= timeout => return Err(e),
+            _ => tokio::time::sleep(POLL_INTERVAL.min(timeout - elapsed)).await,
+        }
+    }
+}
+
+fn validate_timeout(timeout_ms: u64) -> Result<()> {
+    if timeout_ms > 300000 {
+        // 5 minutes max
+        return Err(anyhow!("Timeout cannot exceed 300 seconds"));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Assert Text Present Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssertTextPresentInput {
+    pub text: String,
+    /// Only look inside the first element matching this; the whole page by default
+    #[serde(default)]
+    pub selector: Option<String>,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Assert that the text is not there instead
+    #[serde(default)]
+    pub absent: bool,
+    /// Keep checking for this long before failing; checked once by default
+    #[serde(default)]
+    pub timeout_ms: u64,
+}
+
+/// Verdict on what the text search script saw
+fn text_verdict(input: &AssertTextPresentInput, actual: &Value) -> (bool, Option<String>) {
+    if actual["scope_found"] == false {
+        let selector = input.selector.as_deref().unwrap_or("body");
+        // Text can't be present in an element that isn't there
+        return (
+            input.absent,
+            Some(format!("No element matches {}", selector)),
+        );
+    }
+    let found = actual["found"] == true;
+    let passed = found != input.absent;
+    let message = match (passed, found) {
+        (true, _) => None,
+        (false, true) => Some(format!("Text \"{}\" is on the page", input.text)),
+        (false, false) => Some(format!("Text \"{}\" is not on the page", input.text)),
+    };
+    (passed, message)
+}
+
+pub struct AssertTextPresentTool {
+    browser: Arc<Browser>,
+}
+
+impl AssertTextPresentTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for AssertTextPresentTool {
+    type Input = AssertTextPresentInput;
+    type Output = AssertionOutput;
+
+    fn name(&self) -> &str {
+        "assert_text_present"
+    }
+
+    fn description(&self) -> &str {
+        "Assert that text is present on the page or in an element, or absent, with a pass/fail result"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Verification
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let assertion = format!(
+            "text \"{}\" is {}{}",
+            input.text,
+            if input.absent { "absent" } else { "present" },
+            input
+                .selector
+                .as_deref()
+                .map(|selector| format!(" in {}", selector))
+                .unwrap_or_default()
+        );
+        info!("Asserting {}", assertion);
+
+        // The excerpt shows the match in context, or the start of the text when missing
+        let script = format!(
+            r#"
+            (function() {{
+                const selector = {};
+                const root = selector ? document.querySelector(selector) : document.body;
+                if (!root) return {{ scope_found: false, found: false, excerpt: null }};
+                const text = (root.innerText || root.textContent || '').replace(/\s+/g, ' ').trim();
+                const caseSensitive = {};
+                const needle = {}.replace(/\s+/g, ' ').trim();
+                const index = (caseSensitive ? text : text.toLowerCase())
+                    .indexOf(caseSensitive ? needle : needle.toLowerCase());
+                return {{
+                    scope_found: true,
+                    found: index >= 0,
+                    excerpt: index >= 0
+                        ? text.slice(Math.max(0, index - 40), index + needle.length + 40)
+                        : text.slice(0, 200)
+                }};
+            }})()"#,
+            serde_json::to_string(&input.selector)?,
+            input.case_sensitive,
+            serde_json::to_string(&input.text)?
+        );
+
+        let (passed, actual, wait_time_ms) =
+            check_until(Duration::from_millis(input.timeout_ms), || async {
+                let actual = self.browser.execute_script(&script).await?;
+                Ok((text_verdict(&input, &actual).0, actual))
+            })
+            .await?;
+
+        Ok(AssertionOutput {
+            passed,
+            assertion,
+            expected: json!({
+                "text": input.text,
+                "present": !input.absent,
+            }),
+            message: text_verdict(&input, &actual).1,
+            actual,
+            wait_time_ms,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.text.trim().is_empty() {
+            return Err(anyhow!("Text cannot be empty"));
+        }
+        if input.selector.as_deref().is_some_and(str::is_empty) {
+            return Err(anyhow!("Selector cannot be empty"));
+        }
+        validate_timeout(input.timeout_ms)
+    }
+}
+
+// ============================================================================
+// Assert Element Visible Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssertElementVisibleInput {
+    pub selector: String,
+    /// Assert that no matching element is visible instead
+    #[serde(default)]
+    pub hidden: bool,
+    /// Keep checking for this long before failing; checked once by default
+    #[serde(default)]
+    pub timeout_ms: u64,
+}
+
+/// Verdict on the counts of matching and visible elements
+fn visibility_verdict(input: &AssertElementVisibleInput, actual: &Value) -> (bool, Option<String>) {
+    let matched = actual["matched"].as_u64().unwrap_or(0);
+    let visible = actual["visible"].as_u64().unwrap_or(0);
+    let passed = if input.hidden {
+        visible == 0
+    } else {
+        visible > 0
+    };
+    let message = if passed {
+        None
+    } else if input.hidden {
+        Some(format!(
+            "{} of {} elements matching {} are visible",
+            visible, matched, input.selector
+        ))
+    } else if matched == 0 {
+        Some(format!("No element matches {}", input.selector))
+    } else {
+        Some(format!(
+            "{} elements match {} but none is visible",
+            matched, input.selector
+        ))
+    };
+    (passed, message)
+}
+
+pub struct AssertElementVisibleTool {
+    browser: Arc<Browser>,
+}
+
+impl AssertElementVisibleTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for AssertElementVisibleTool {
+    type Input = AssertElementVisibleInput;
+    type Output = AssertionOutput;
+
+    fn name(&self) -> &str {
+        "assert_element_visible"
+    }
+
+    fn description(&self) -> &str {
+        "Assert that an element is visible, or hidden, with a pass/fail result"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Verification
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let assertion = format!(
+            "{} is {}",
+            input.selector,
+            if input.hidden { "hidden" } else { "visible" }
+        );
+        info!("Asserting {}", assertion);
+
+        let script = format!(
+            r#"
+            (function() {{
+                {}
+                const elements = Array.from(document.querySelectorAll({}));
+                return {{
+                    matched: elements.length,
+                    visible: elements.filter(isVisible).length
+                }};
+            }})()"#,
+            IS_VISIBLE_JS,
+            serde_json::to_string(&input.selector)?
+        );
+
+        let (passed, actual, wait_time_ms) =
+            check_until(Duration::from_millis(input.timeout_ms), || async {
+                let actual = self.browser.execute_script(&script).await?;
+                Ok((visibility_verdict(&input, &actual).0, actual))
+            })
+            .await?;
+
+        Ok(AssertionOutput {
+            passed,
+            assertion,
+            expected: json!({ "visible": !input.hidden }),
+            message: visibility_verdict(&input, &actual).1,
+            actual,
+            wait_time_ms,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.selector.is_empty() {
+            return Err(anyhow!("Selector cannot be empty"));
+        }
+        validate_timeout(input.timeout_ms)
+    }
+}
+
+// ============================================================================
+// Assert URL Matches Tool
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssertUrlMatchesInput {
+    /// The page URL must contain this
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The page URL must match this regular expression
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Keep checking for this long before failing; checked once by default
+    #[serde(default)]
+    pub timeout_ms: u64,
+}
+
+pub struct AssertUrlMatchesTool {
+    browser: Arc<Browser>,
+}
+
+impl AssertUrlMatchesTool {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for AssertUrlMatchesTool {
+    type Input = AssertUrlMatchesInput;
+    type Output = AssertionOutput;
+
+    fn name(&self) -> &str {
+        "assert_url_matches"
+    }
+
+    fn description(&self) -> &str {
+        "Assert that the page URL contains a string or matches a regular expression, with a pass/fail result"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Verification
+    }
+
+    async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+        let pattern = compile_pattern(input.pattern.as_deref())?;
+        let assertion = match (&input.url, &input.pattern) {
+            (Some(url), Some(pattern)) => {
+                format!("URL contains \"{}\" and matches /{}/", url, pattern)
+            }
+            (Some(url), None) => format!("URL contains \"{}\"", url),
+            (None, pattern) => format!("URL matches /{}/", pattern.as_deref().unwrap_or_default()),
+        };
+        info!("Asserting {}", assertion);
+
+        let (passed, actual, wait_time_ms) =
+            check_until(Duration::from_millis(input.timeout_ms), || async {
+                let url = self.browser.current_url().await?;
+                let passed = url_matches(&url, input.url.as_deref(), pattern.as_ref());
+                Ok((passed, Value::String(url)))
+            })
+            .await?;
+
+        Ok(AssertionOutput {
+            passed,
+            assertion,
+            expected: json!({
+                "contains": input.url,
+                "pattern": input.pattern,
+            }),
+            message: (!passed).then(|| format!("URL is {}", actual.as_str().unwrap_or_default())),
+            actual,
+            wait_time_ms,
+        })
+    }
+
+    async fn validate_input(&self, input: &Self::Input) -> Result<()> {
+        if input.url.as_deref().is_none_or(str::is_empty) && input.pattern.is_none() {
+            return Err(anyhow!("Either url or pattern is required"));
+        }
+        compile_pattern(input.pattern.as_deref())?;
+        validate_timeout(input.timeout_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_check_until_retries_within_timeout() {
+        let attempts = AtomicU32::new(0);
+        let (passed, actual, _) = check_until(Duration::from_secs(2), || async {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt == 1 {
+                return Err(anyhow!("page is navigating"));
+            }
+            Ok((attempt == 3, json!(attempt)))
+        })
+        .await
+        .unwrap();
+        assert!(passed);
+        assert_eq!(actual, 3);
+
+        // Without a timeout there is a single check
+        attempts.store(0, Ordering::SeqCst);
+        let (passed, _, _) = check_until(Duration::ZERO, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Ok((false, Value::Null))
+        })
+        .await
+        .unwrap();
+        assert!(!passed);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_verdicts_explain_failures() {
+        let text = AssertTextPresentInput {
+            text: "Order placed".into(),
+            selector: Some("#status".into()),
+            case_sensitive: false,
+            absent: false,
+            timeout_ms: 0,
+        };
+        let missing_scope = json!({"scope_found": false, "found": false});
+        assert_eq!(
+            text_verdict(&text, &missing_scope),
+            (false, Some("No element matches #status".to_string()))
+        );
+        assert_eq!(
+            text_verdict(&text, &json!({"scope_found": true, "found": true})),
+            (true, None)
+        );
+
+        let hidden = AssertElementVisibleInput {
+            selector: ".spinner".into(),
+            hidden: true,
+            timeout_ms: 0,
+        };
+        assert_eq!(
+            visibility_verdict(&hidden, &json!({"matched": 2, "visible": 1})),
+            (
+                false,
+                Some("1 of 2 elements matching .spinner are visible".to_string())
+            )
+        );
+        assert!(visibility_verdict(&hidden, &json!({"matched": 0, "visible": 0})).0);
+    }
+}
@@ -27,8 +27,37 @@ document.addEventListener('DOMContentLoaded', function() {
 
     // Ensure session + binding for visual tests launched via start.sh
     ensureSessionBinding();
+    connectLiveChannel();
+    loadCurrentUser();
 });
 
+// Show who is signed in, or a sign-in link when the server requires a login
+async function loadCurrentUser() {
+    try {
+        const res = await fetch(`${API_BASE}/api/auth/me`);
+        const data = await res.json();
+        const me = data && data.data;
+        if (!me || !me.auth_required) return;
+        const badge = document.getElementById('user-badge');
+        const link = document.createElement('a');
+        if (me.authenticated) {
+            badge.textContent = `${me.principal.name || me.principal.email || me.principal.subject} `;
+            link.href = '/auth/logout';
+            link.textContent = 'Sign out';
+            badge.appendChild(link);
+        } else if (me.login_url) {
+            link.href = me.login_url;
+            link.textContent = 'Sign in';
+            badge.appendChild(link);
+        } else {
+            badge.textContent = 'Not signed in';
+        }
+        document.getElementById('user-info').style.display = '';
+    } catch (e) {
+        console.warn('Failed to load current user', e);
+    }
+}
+
 // Helper: pick the best lightning-like view from various result shapes
 function pickLightningView(result) {
     const r = result || {};
@@ -54,6 +83,57 @@ function setCurrentSession(sessionId) {
         el.textContent = window.currentSessionId || 'None';
     }
     updateServerBanner();
+    subscribeLiveChannel();
+}
+
+// Live channel (/ws): streams the current session's activity, including LLM thinking steps
+let liveSocket = null;
+function connectLiveChannel() {
+    const url = API_BASE.replace(/^http/, 'ws') + '/ws';
+    try {
+        liveSocket = new WebSocket(url);
+    } catch (e) {
+        console.warn('Live channel unavailable:', e);
+        return;
+    }
+    liveSocket.onopen = () => subscribeLiveChannel();
+    liveSocket.onmessage = (message) => {
+        let data;
+        try {
+            data = JSON.parse(message.data);
+        } catch {
+            return;
+        }
+        if (data.type === 'event') {
+            appendLiveEvent(data);
+        } else if (data.type === 'error') {
+            console.warn('Live channel:', data.message);
+        }
+    };
+    // Reconnect after server restarts
+    liveSocket.onclose = () => setTimeout(connectLiveChannel, 5000);
+}
+
+function subscribeLiveChannel() {
+    if (!liveSocket || liveSocket.readyState !== WebSocket.OPEN) return;
+    if (window.currentSessionId) {
+        liveSocket.send(JSON.stringify({ type: 'subscribe', session_id: window.currentSessionId }));
+    } else {
+        liveSocket.send(JSON.stringify({ type: 'unsubscribe' }));
+    }
+}
+
+function appendLiveEvent(event) {
+    const container = document.getElementById('live-events');
+    if (!container) return;
+    const line = document.createElement('div');
+    const time = new Date(event.timestamp).toLocaleTimeString();
+    const detail = event.kind === 'thinking' ? event.detail.stage : (event.detail.tool || event.detail.url || event.detail.mode || '');
+    line.textContent = `${time} ${event.kind} ${detail}`;
+    container.prepend(line);
+    while (container.childElementCount > 200) {
+        container.lastElementChild.remove();
+    }
 }
 
 // Ensure we have a session and binding enabled for visual tests
@@ -108,6 +188,11 @@ function updateStatus(status, isError = false) {
     }
 }
 
+// Message of an API error; failed responses carry {code, message, details, retryable}
+function errorMessage(error) {
+    return error && typeof error === 'object' ? error.message : error;
+}
+
 // Show notification
 function showNotification(message, type = 'info') {
     const notificationsContainer = document.querySelector('.notifications') || createNotificationsContainer();
@@ -221,7 +306,7 @@ async function executeTool(toolName, parameters) {
             let errorData;
             try {
                 errorData = JSON.parse(errorText);
-                throw new Error(errorData.error || `HTTP ${response.status} error`);
+                throw new Error(errorMessage(errorData.error) || `HTTP ${response.status} error`);
             } catch (parseError) {
                 throw new Error(`HTTP ${response.status}: ${errorText || response.statusText}`);
             }
@@ -249,7 +334,7 @@ async function executeTool(toolName, parameters) {
                 }
             } catch (e) { /* noop */ }
         } else {
-            const errorMsg = result?.error || 'Unknown error occurred';
+            const errorMsg = errorMessage(result?.error) || 'Unknown error occurred';
             let helpMessage = '';
             
             // Provide helpful suggestions based on common errors
@@ -336,7 +421,7 @@ async function navigateAndPerceive() {
                 showNotification('Navigate + Perceive (fallback) completed', 'success');
                 return;
             } else {
-                showNotification(`Fallback analyze failed: ${fbData?.error || 'Unknown error'}`, 'error');
+                showNotification(`Fallback analyze failed: ${errorMessage(fbData?.error) || 'Unknown error'}`, 'error');
                 return;
             }
         }
@@ -347,7 +432,7 @@ async function navigateAndPerceive() {
             renderPerceptionResult('lightning', { data: data.data.perception || data.data });
             showNotification('Navigate + Perceive completed', 'success');
         } else {
-            showNotification(`Navigate + Perceive failed: ${data?.error || 'Unknown error'}`, 'error');
+            showNotification(`Navigate + Perceive failed: ${errorMessage(data?.error) || 'Unknown error'}`, 'error');
         }
     } catch (e) {
         showNotification(`Network error: ${e.message}`, 'error');
@@ -598,7 +683,7 @@ async function createSession() {
             }
             displayResult({ current_session: sid, created: true });
         } else {
-            showNotification(`Failed to create session: ${result.error}`, 'error');
+            showNotification(`Failed to create session: ${errorMessage(result.error)}`, 'error');
         }
     } catch (error) {
         console.error('Failed to create session:', error);
@@ -619,7 +704,7 @@ async function listSessions() {
                 countEl.textContent = Array.isArray(result.data) ? result.data.length : 0;
             }
         } else {
-            showNotification(`Failed to list sessions: ${result.error}`, 'error');
+            showNotification(`Failed to list sessions: ${errorMessage(result.error)}`, 'error');
         }
     } catch (error) {
         console.error('Failed to list sessions:', error);
@@ -737,7 +822,7 @@ async function testAllTools() {
                 console.log(`✅ ${tool.category}/${tool.name} passed`);
             } else {
                 failedTests++;
-                console.log(`❌ ${tool.category}/${tool.name} failed:`, result?.error || 'Unknown error');
+                console.log(`❌ ${tool.category}/${tool.name} failed:`, errorMessage(result?.error) || 'Unknown error');
             }
         } catch (error) {
             failedTests++;
@@ -900,7 +985,7 @@ async function analyzePage() {
             `;
             showNotification('Page analysis completed successfully', 'success');
         } else {
-            resultDiv.innerHTML = `<div class="error-result">Error: ${data.error || 'Analysis failed'}</div>`;
+            resultDiv.innerHTML = `<div class="error-result">Error: ${errorMessage(data.error) || 'Analysis failed'}</div>`;
             showNotification('Page analysis failed', 'error');
         }
     } catch (error) {
@@ -965,7 +1050,7 @@ async function findElement() {
             `;
             showNotification('Element found successfully', 'success');
         } else {
-            resultDiv.innerHTML = `<div class="error-result">Element not found: ${data.error || 'No matching element'}</div>`;
+            resultDiv.innerHTML = `<div class="error-result">Element not found: ${errorMessage(data.error) || 'No matching element'}</div>`;
             showNotification('Element not found', 'warning');
         }
     } catch (error) {
@@ -1032,7 +1117,7 @@ async function executeIntelligentCommand() {
             `;
             showNotification('Command executed successfully', 'success');
         } else {
-            resultDiv.innerHTML = `<div class="error-result">Command failed: ${data.error || 'Execution error'}</div>`;
+            resultDiv.innerHTML = `<div class="error-result">Command failed: ${errorMessage(data.error) || 'Execution error'}</div>`;
             showNotification('Command execution failed', 'error');
         }
     } catch (error) {
@@ -1181,7 +1266,7 @@ async function perceiveWithMode(mode) {
             resultDiv.innerHTML = html;
             showNotification(`${mode} perception completed`, 'success');
         } else {
-            resultDiv.innerHTML = `<div class="error-result">Perception failed: ${data.error || 'Unknown error'}</div>`;
+            resultDiv.innerHTML = `<div class="error-result">Perception failed: ${errorMessage(data.error) || 'Unknown error'}</div>`;
             showNotification('Perception failed', 'error');
         }
     } catch (error) {
@@ -1274,7 +1359,7 @@ async function quickScan() {
             `;
             showNotification('Quick scan completed', 'success');
         } else {
-            resultDiv.innerHTML = `<div class="error-result">Scan failed: ${data.error || 'Unknown error'}</div>`;
+            resultDiv.innerHTML = `<div class="error-result">Scan failed: ${errorMessage(data.error) || 'Unknown error'}</div>`;
             showNotification('Quick scan failed', 'error');
         }
     } catch (error) {
@@ -1374,7 +1459,7 @@ async function smartElementSearch() {
                 showNotification('No elements found', 'info');
             }
         } else {
-            resultDiv.innerHTML = `<div class="error-result">Search failed: ${data.error || 'Unknown error'}</div>`;
+            resultDiv.innerHTML = `<div class="error-result">Search failed: ${errorMessage(data.error) || 'Unknown error'}</div>`;
             showNotification('Smart search failed', 'error');
         }
     } catch (error) {
@@ -1415,7 +1500,7 @@ async function verifyPerceptionSeesTools() {
             })
         });
         const navJson = await navRes.json();
-        if (!navJson.success) throw new Error('Navigation tool failed: ' + (navJson.error || 'unknown'));
+        if (!navJson.success) throw new Error('Navigation tool failed: ' + (errorMessage(navJson.error) || 'unknown'));
 
         // 1.1) Wait for document readyState complete (use tool to ensure same session)
         try {
@@ -1442,7 +1527,7 @@ async function verifyPerceptionSeesTools() {
             body: JSON.stringify({ mode: 'quick', session_id: sid })
         });
         const perJson = await perRes.json();
-        if (!perJson.success) throw new Error('Perception failed: ' + (perJson.error || 'unknown'));
+        if (!perJson.success) throw new Error('Perception failed: ' + (errorMessage(perJson.error) || 'unknown'));
 
         // 3) Evaluate
         const data = perJson.data || {};
@@ -1523,7 +1608,7 @@ async function analyzeForm() {
             `;
             showNotification('Form analysis completed successfully', 'success');
         } else {
-            resultDiv.innerHTML = `<div class="error-result">Form analysis failed: ${data.error || 'No form found'}</div>`;
+            resultDiv.innerHTML = `<div class="error-result">Form analysis failed: ${errorMessage(data.error) || 'No form found'}</div>`;
             showNotification('Form analysis failed', 'error');
         }
     } catch (error) {
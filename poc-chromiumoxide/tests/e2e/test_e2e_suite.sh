@@ -36,7 +36,7 @@ execute_workflow_step() {
             echo "    ✓ $step_name"
             return 0
         else
-            echo "    ✗ $step_name - $(echo "$response" | jq -r '.error // "Unknown error"')"
+            echo "    ✗ $step_name - $(echo "$response" | jq -r '.error.message // .error // "Unknown error"')"
             return 1
         fi
     else
@@ -59,7 +59,7 @@ execute_and_verify() {
                 return 0
             fi
         else
-            test_failed "$test_name - $(echo "$response" | jq -r '.error // "Unknown error"')"
+            test_failed "$test_name - $(echo "$response" | jq -r '.error.message // .error // "Unknown error"')"
             return 1
         fi
     else
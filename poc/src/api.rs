@@ -1,8 +1,8 @@
 use axum::{
-    extract::{State, Json},
-    http::StatusCode,
+    extract::{State, Json, Path, Query},
+    http::{header, StatusCode},
     response::{IntoResponse, Response, Sse, sse::Event},
-    routing::{get, post},
+    routing::{get, post, delete},
     Router,
 };
 use serde::{Deserialize, Serialize};
@@ -24,6 +24,10 @@ use crate::{
     SimpleBrowser, BrowserPool, LLMService, WorkflowEngine, Workflow,
     MetricsCollector, SecurityMiddleware, Config, CostTracker,
     ParsedCommand, ScreenshotOptions, PluginManager,
+    WorkflowScheduler, SchedulerConfig, Trigger, Schedule, WorkflowRun, CheckpointStore, ArtifactStore, Artifact, ReportStore,
+    WorkflowLibrary, WorkflowSummary, WorkflowDiff, SemVer, VersionBump, import_script,
+    ErrorRecoveryManager, create_error_recovery_manager, Dataset, BatchOptions, run_batch,
+    WebhookDelivery, notify_webhooks,
     llm_service::legacy_service::CommandParams,
     // api_v2::{ApiV2State, create_v2_routes, health_check_v2},
     // Import perception modules - temporarily disabled for core action testing
@@ -42,6 +46,9 @@ pub struct ApiState {
     pub cost_tracker: Arc<RwLock<CostTracker>>,
     pub sessions: Arc<RwLock<HashMap<String, BrowserSession>>>,
     pub plugin_manager: Arc<RwLock<PluginManager>>,
+    pub scheduler: Arc<WorkflowScheduler>,
+    pub workflow_library: Arc<WorkflowLibrary>,
+    pub error_recovery: Arc<ErrorRecoveryManager>,
 }
 
 /// Browser session for stateful operations
@@ -180,6 +187,20 @@ pub struct WorkflowRequest {
     pub inputs: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Run a workflow once per dataset row; the workflow is given inline or as the id of a
+/// stored one, the dataset as CSV text or as JSON rows
+#[derive(Debug, Deserialize)]
+pub struct BatchWorkflowRequest {
+    pub workflow: Option<serde_json::Value>,
+    pub workflow_id: Option<String>,
+    pub csv: Option<String>,
+    pub rows: Option<Vec<HashMap<String, serde_json::Value>>>,
+    #[serde(flatten)]
+    pub options: BatchOptions,
+    /// "json" (default) or "csv" for the consolidated report
+    pub format: Option<String>,
+}
+
 // New flexible instruction format that accepts natural language
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
@@ -198,10 +219,17 @@ pub enum FlexibleInstruction {
 
 #[derive(Debug, Serialize)]
 pub struct WorkflowResponse {
+    /// Id for `/workflow/:run_id/resume` when the run stopped part way
+    pub run_id: Option<Uuid>,
     pub success: bool,
     pub result: Option<serde_json::Value>,
     pub steps_executed: usize,
     pub duration_ms: u64,
+    /// Files collected by the run, also listed at `/workflow/:run_id/artifacts`
+    pub artifacts: Vec<Artifact>,
+    /// Outcome of notifying the workflow's webhooks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookDelivery>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -721,24 +749,512 @@ pub async fn workflow_handler(
             code: 400,
         })?;
     
-    // Execute workflow
+    // Execute workflow, checkpointing so a run that stops part way can be resumed
     let start = std::time::Instant::now();
-    let mut engine = WorkflowEngine::new_simple();
+    let mut engine = WorkflowEngine::new_simple()
+        .with_checkpoints(CheckpointStore::default())
+        .with_artifacts(ArtifactStore::default())
+        .with_reports(ReportStore::default())
+        .with_error_recovery(state.error_recovery.clone());
+    let result = engine.execute_workflow(&workflow, req.inputs).await?;
+    let duration = start.elapsed();
+    let webhooks = notify_webhooks(&workflow, &result).await;
+    
+    // Record metrics
+    state.metrics.record_operation(duration, true, 0.01).await;
+    
+    Ok(Json(WorkflowResponse {
+        run_id: result.run_id,
+        success: result.success,
+        result: if result.success {
+            Some(serde_json::json!(result.variables))
+        } else {
+            None
+        },
+        steps_executed: result.steps_executed,
+        duration_ms: duration.as_millis() as u64,
+        artifacts: result.artifacts,
+        webhooks,
+    }))
+}
+
+/// Store a workflow definition, given as a JSON `workflow` object or a `yaml` document
+#[derive(Debug, Deserialize)]
+pub struct SaveWorkflowRequest {
+    /// Id of a new workflow; derived from its name when left out
+    pub id: Option<String>,
+    pub workflow: Option<serde_json::Value>,
+    pub yaml: Option<String>,
+    /// Explicit version; otherwise 1.0.0 for new workflows, or the latest version bumped
+    pub version: Option<String>,
+    #[serde(default)]
+    pub bump: VersionBump,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkflowVersionQuery {
+    pub version: Option<String>,
+}
+
+/// Versions to diff; default to the latest version and the one before it
+#[derive(Debug, Deserialize)]
+pub struct WorkflowDiffQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+fn parse_version(version: &str) -> Result<SemVer, ApiError> {
+    SemVer::parse(version).map_err(|e| ApiError {
+        error: "Invalid version".to_string(),
+        details: Some(e.to_string()),
+        code: 400,
+    })
+}
+
+/// Parse and validate the definition of a save request
+fn workflow_definition(state: &ApiState, req: &SaveWorkflowRequest) -> Result<Workflow, ApiError> {
+    let invalid = |e: String| ApiError {
+        error: "Invalid workflow format".to_string(),
+        details: Some(e),
+        code: 400,
+    };
+    let workflow = match (&req.workflow, &req.yaml) {
+        (Some(workflow), _) => serde_json::from_value(workflow.clone()).map_err(|e| invalid(e.to_string()))?,
+        (None, Some(yaml)) => Workflow::from_yaml(yaml).map_err(|e| invalid(format!("{:#}", e)))?,
+        (None, None) => return Err(invalid("Provide 'workflow' or 'yaml'".to_string())),
+    };
     
-    // Set input variables
-    if let Some(inputs) = req.inputs {
-        for (key, value) in inputs {
-            engine.set_variable(&key, value).await;
+    let workflow_yaml = serde_yaml::to_string(&workflow)?;
+    state.security.validate_workflow(&workflow_yaml)
+        .map_err(|e| ApiError {
+            error: "Workflow validation failed".to_string(),
+            details: Some(e.to_string()),
+            code: 400,
+        })?;
+    Ok(workflow)
+}
+
+fn save_to_library(state: &ApiState, id: &str, workflow: Workflow, req: SaveWorkflowRequest) -> Result<Json<serde_json::Value>, ApiError> {
+    let version = req.version.as_deref().map(parse_version).transpose()?;
+    let saved = state.workflow_library.save(id, workflow, version, req.bump, req.notes)
+        .map_err(|e| ApiError {
+            error: "Failed to save workflow".to_string(),
+            details: Some(e.to_string()),
+            code: 400,
+        })?;
+    
+    Ok(Json(serde_json::json!({
+        "id": id,
+        "version": saved.version,
+        "created_at": saved.created_at,
+        "notes": saved.notes,
+    })))
+}
+
+/// Stored workflows, most recently updated first
+pub async fn list_workflows_handler(State(state): State<ApiState>) -> Json<Vec<WorkflowSummary>> {
+    Json(state.workflow_library.list())
+}
+
+/// Store a new workflow at version 1.0.0 (or the given version)
+pub async fn create_workflow_handler(
+    State(state): State<ApiState>,
+    Json(req): Json<SaveWorkflowRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let workflow = workflow_definition(&state, &req)?;
+    let id = req.id.clone().unwrap_or_else(|| WorkflowLibrary::id_from_name(&workflow.name));
+    if state.workflow_library.contains(&id) {
+        return Err(ApiError {
+            error: format!("Workflow '{}' already exists", id),
+            details: Some(format!("PUT /workflows/{} stores a new version", id)),
+            code: 409,
+        });
+    }
+    save_to_library(&state, &id, workflow, req)
+}
+
+/// Store a new version of a workflow
+pub async fn update_workflow_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    Json(req): Json<SaveWorkflowRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if !state.workflow_library.contains(&id) {
+        return Err(workflow_not_found(&id));
+    }
+    let workflow = workflow_definition(&state, &req)?;
+    save_to_library(&state, &id, workflow, req)
+}
+
+/// A stored workflow's latest version, or the version given as `?version=`
+pub async fn get_workflow_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    Query(query): Query<WorkflowVersionQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let entry = state.workflow_library.get(&id).ok_or_else(|| workflow_not_found(&id))?;
+    let stored = match query.version.as_deref() {
+        Some(version) => {
+            let version = parse_version(version)?;
+            entry.version(version).ok_or_else(|| ApiError {
+                error: format!("Workflow '{}' has no version {}", id, version),
+                details: None,
+                code: 404,
+            })?
+        }
+        None => entry.latest(),
+    };
+    
+    Ok(Json(serde_json::json!({
+        "id": entry.id,
+        "version": stored.version,
+        "created_at": stored.created_at,
+        "notes": stored.notes,
+        "definition": stored.definition,
+        "yaml": stored.definition.to_yaml()?,
+        "versions": entry.versions.iter().map(|version| version.version).collect::<Vec<_>>(),
+    })))
+}
+
+/// Versions of a stored workflow, oldest first
+pub async fn workflow_versions_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<serde_json::Value>>, ApiError> {
+    let entry = state.workflow_library.get(&id).ok_or_else(|| workflow_not_found(&id))?;
+    Ok(Json(entry.versions.iter()
+        .map(|version| serde_json::json!({
+            "version": version.version,
+            "created_at": version.created_at,
+            "notes": version.notes,
+        }))
+        .collect()))
+}
+
+/// Line diff between two versions of a stored workflow
+pub async fn workflow_diff_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    Query(query): Query<WorkflowDiffQuery>,
+) -> Result<Json<WorkflowDiff>, ApiError> {
+    let entry = state.workflow_library.get(&id).ok_or_else(|| workflow_not_found(&id))?;
+    let mut versions: Vec<SemVer> = entry.versions.iter().map(|version| version.version).collect();
+    versions.sort();
+    
+    let to = match query.to.as_deref() {
+        Some(to) => parse_version(to)?,
+        None => entry.latest().version,
+    };
+    let from = match query.from.as_deref() {
+        Some(from) => parse_version(from)?,
+        None => versions.iter().rev().find(|version| **version < to).copied().unwrap_or(to),
+    };
+    
+    let diff = state.workflow_library.diff(&id, from, to)
+        .map_err(|e| ApiError {
+            error: "Failed to diff workflow versions".to_string(),
+            details: Some(e.to_string()),
+            code: 404,
+        })?;
+    Ok(Json(diff))
+}
+
+/// Delete a stored workflow and all its versions
+pub async fn delete_workflow_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    match state.workflow_library.delete(&id) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(workflow_not_found(&id)),
+        Err(e) => Err(ApiError {
+            error: "Failed to delete workflow".to_string(),
+            details: Some(e.to_string()),
+            code: 500,
+        }),
+    }
+}
+
+/// Convert a Selenium IDE project or Playwright test script into workflows
+#[derive(Debug, Deserialize)]
+pub struct ImportWorkflowRequest {
+    pub content: String,
+    /// Original file name, used to tell `.side` projects from Playwright scripts
+    pub file_name: Option<String>,
+    /// Store the converted workflows in the library instead of only returning them
+    #[serde(default)]
+    pub save: bool,
+}
+
+/// Convert a script into one workflow per test, with warnings for statements that were skipped
+pub async fn import_workflow_handler(
+    State(state): State<ApiState>,
+    Json(req): Json<ImportWorkflowRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let imported = import_script(&req.content, req.file_name.as_deref())
+        .map_err(|e| ApiError {
+            error: "Failed to import script".to_string(),
+            details: Some(format!("{:#}", e)),
+            code: 400,
+        })?;
+    
+    // Refuse the whole import rather than storing only some of the tests
+    if req.save {
+        if let Some(existing) = imported.iter()
+            .map(|imported| WorkflowLibrary::id_from_name(&imported.workflow.name))
+            .find(|id| state.workflow_library.contains(id))
+        {
+            return Err(ApiError {
+                error: format!("Workflow '{}' already exists", existing),
+                details: Some(format!("PUT /workflows/{} stores a new version", existing)),
+                code: 409,
+            });
         }
     }
     
-    let result = engine.execute(&workflow).await?;
+    let mut workflows = Vec::new();
+    for imported in imported {
+        let id = WorkflowLibrary::id_from_name(&imported.workflow.name);
+        let yaml = imported.workflow.to_yaml()?;
+        let saved = if req.save {
+            let notes = Some("Imported".to_string());
+            let saved = state.workflow_library.save(&id, imported.workflow.clone(), None, VersionBump::default(), notes)
+                .map_err(|e| ApiError {
+                    error: "Failed to save workflow".to_string(),
+                    details: Some(e.to_string()),
+                    code: 400,
+                })?;
+            Some(saved.version)
+        } else {
+            None
+        };
+        info!("📥 Imported workflow '{}' ({} steps, {} warnings)", imported.workflow.name, imported.workflow.steps.len(), imported.warnings.len());
+        
+        workflows.push(serde_json::json!({
+            "id": id,
+            "name": imported.workflow.name,
+            "yaml": yaml,
+            "warnings": imported.warnings,
+            "version": saved,
+        }));
+    }
+    
+    Ok(Json(serde_json::json!({ "workflows": workflows })))
+}
+
+/// Query parameters of the run history endpoint
+#[derive(Debug, Deserialize)]
+pub struct RunHistoryQuery {
+    pub limit: Option<usize>,
+}
+
+/// Run a stored workflow now
+#[derive(Debug, Default, Deserialize)]
+pub struct RunWorkflowRequest {
+    #[serde(default)]
+    pub inputs: HashMap<String, serde_json::Value>,
+}
+
+/// Run a stored workflow on a cron expression or interval
+#[derive(Debug, Deserialize)]
+pub struct ScheduleRequest {
+    pub trigger: Trigger,
+    #[serde(default)]
+    pub inputs: HashMap<String, serde_json::Value>,
+}
+
+fn workflow_not_found(workflow_id: &str) -> ApiError {
+    ApiError {
+        error: format!("Workflow '{}' not found", workflow_id),
+        details: None,
+        code: 404,
+    }
+}
+
+/// Run history of a stored workflow, newest first
+pub async fn workflow_runs_handler(
+    State(state): State<ApiState>,
+    Path(workflow_id): Path<String>,
+    Query(query): Query<RunHistoryQuery>,
+) -> Result<Json<Vec<WorkflowRun>>, ApiError> {
+    let runs = state.scheduler.runs(&workflow_id, query.limit.unwrap_or(50)).await;
+    // Runs of workflows deleted since stay queryable
+    if runs.is_empty() && !state.scheduler.workflow_exists(&workflow_id) {
+        return Err(workflow_not_found(&workflow_id));
+    }
+    Ok(Json(runs))
+}
+
+/// Run a stored workflow immediately and record the run
+pub async fn run_workflow_handler(
+    State(state): State<ApiState>,
+    Path(workflow_id): Path<String>,
+    req: Option<Json<RunWorkflowRequest>>,
+) -> Result<Json<WorkflowRun>, ApiError> {
+    state.security.check_request("api").await
+        .map_err(|_| ApiError {
+            error: "Rate limit exceeded".to_string(),
+            details: None,
+            code: 429,
+        })?;
+    
+    if !state.scheduler.workflow_exists(&workflow_id) {
+        return Err(workflow_not_found(&workflow_id));
+    }
+    
+    let inputs = req.map(|Json(req)| req.inputs).unwrap_or_default();
+    let run = state.scheduler.run_workflow(&workflow_id, inputs, None).await;
+    state.metrics.record_operation(std::time::Duration::from_millis(run.duration_ms), run.success, 0.01).await;
+    
+    Ok(Json(run))
+}
+
+/// Schedules of a stored workflow
+pub async fn list_schedules_handler(
+    State(state): State<ApiState>,
+    Path(workflow_id): Path<String>,
+) -> Json<Vec<Schedule>> {
+    Json(state.scheduler.list_schedules(Some(&workflow_id)).await)
+}
+
+/// Schedule a stored workflow
+pub async fn create_schedule_handler(
+    State(state): State<ApiState>,
+    Path(workflow_id): Path<String>,
+    Json(req): Json<ScheduleRequest>,
+) -> Result<Json<Schedule>, ApiError> {
+    if !state.scheduler.workflow_exists(&workflow_id) {
+        return Err(workflow_not_found(&workflow_id));
+    }
+    
+    let schedule = state.scheduler.add_schedule(&workflow_id, req.trigger, req.inputs).await
+        .map_err(|e| ApiError {
+            error: "Invalid schedule".to_string(),
+            details: Some(e.to_string()),
+            code: 400,
+        })?;
+    
+    Ok(Json(schedule))
+}
+
+/// Delete a schedule
+pub async fn delete_schedule_handler(
+    State(state): State<ApiState>,
+    Path(schedule_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match state.scheduler.remove_schedule(schedule_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError {
+            error: format!("Schedule '{}' not found", schedule_id),
+            details: None,
+            code: 404,
+        }),
+        Err(e) => Err(ApiError {
+            error: "Failed to delete schedule".to_string(),
+            details: Some(e.to_string()),
+            code: 500,
+        }),
+    }
+}
+
+/// Run a workflow once per row of a dataset and return the consolidated results
+pub async fn batch_workflow_handler(
+    State(state): State<ApiState>,
+    Json(req): Json<BatchWorkflowRequest>,
+) -> Result<Response, ApiError> {
+    state.security.check_request("api").await
+        .map_err(|_| ApiError {
+            error: "Rate limit exceeded".to_string(),
+            details: None,
+            code: 429,
+        })?;
+    
+    let invalid = |error: &str, details: String| ApiError {
+        error: error.to_string(),
+        details: Some(details),
+        code: 400,
+    };
+    let workflow: Workflow = match (req.workflow, &req.workflow_id) {
+        (Some(workflow), _) => serde_json::from_value(workflow)
+            .map_err(|e| invalid("Invalid workflow format", e.to_string()))?,
+        (None, Some(id)) => state.scheduler.load_workflow(id)
+            .map_err(|e| ApiError {
+                error: format!("Workflow '{}' not found", id),
+                details: Some(e.to_string()),
+                code: 404,
+            })?,
+        (None, None) => return Err(invalid("Invalid workflow format", "Provide 'workflow' or 'workflow_id'".to_string())),
+    };
+    let workflow_yaml = serde_yaml::to_string(&workflow)?;
+    state.security.validate_workflow(&workflow_yaml)
+        .map_err(|e| invalid("Workflow validation failed", e.to_string()))?;
+    
+    let dataset = match (req.csv, req.rows) {
+        (Some(csv), _) => Dataset::from_csv(&csv).map_err(|e| invalid("Invalid dataset", e.to_string()))?,
+        (None, Some(rows)) => Dataset::from_rows(rows),
+        (None, None) => return Err(invalid("Invalid dataset", "Provide 'csv' or 'rows'".to_string())),
+    };
+    let csv_report = match req.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => return Err(invalid("Invalid format", format!("Unknown report format '{}', expected 'json' or 'csv'", other))),
+    };
+    
+    let start = std::time::Instant::now();
+    let mut engine = WorkflowEngine::new_simple()
+        .with_checkpoints(CheckpointStore::default())
+        .with_artifacts(ArtifactStore::default())
+        .with_reports(ReportStore::default())
+        .with_error_recovery(state.error_recovery.clone());
+    let report = run_batch(&mut engine, &workflow, &dataset, &req.options).await;
+    let _ = engine.cleanup().await;
+    state.metrics.record_operation(start.elapsed(), report.failed == 0, 0.01 * report.rows.len() as f64).await;
+    
+    if csv_report {
+        Ok(([(header::CONTENT_TYPE, "text/csv")], report.to_csv()).into_response())
+    } else {
+        Ok(Json(report).into_response())
+    }
+}
+
+/// Resume a checkpointed workflow run from the step after its last successful one
+pub async fn resume_workflow_handler(
+    State(state): State<ApiState>,
+    Path(run_id): Path<Uuid>,
+) -> Result<Json<WorkflowResponse>, ApiError> {
+    state.security.check_request("api").await
+        .map_err(|_| ApiError {
+            error: "Rate limit exceeded".to_string(),
+            details: None,
+            code: 429,
+        })?;
+    
+    let start = std::time::Instant::now();
+    let checkpoints = CheckpointStore::default();
+    // The checkpointed definition carries the webhooks to notify
+    let workflow = checkpoints.load(run_id).ok().map(|checkpoint| checkpoint.workflow);
+    let mut engine = WorkflowEngine::new_simple()
+        .with_checkpoints(checkpoints)
+        .with_artifacts(ArtifactStore::default())
+        .with_reports(ReportStore::default())
+        .with_error_recovery(state.error_recovery.clone());
+    let result = engine.resume(run_id).await
+        .map_err(|e| ApiError {
+            error: "Failed to resume workflow".to_string(),
+            details: Some(e.to_string()),
+            code: 404,
+        })?;
     let duration = start.elapsed();
+    let webhooks = match &workflow {
+        Some(workflow) => notify_webhooks(workflow, &result).await,
+        None => Vec::new(),
+    };
     
-    // Record metrics
-    state.metrics.record_operation(duration, true, 0.01).await;
+    state.metrics.record_operation(duration, result.success, 0.01).await;
     
     Ok(Json(WorkflowResponse {
+        run_id: result.run_id,
         success: result.success,
         result: if result.success {
             Some(serde_json::json!(result.variables))
@@ -747,9 +1263,66 @@ pub async fn workflow_handler(
         },
         steps_executed: result.steps_executed,
         duration_ms: duration.as_millis() as u64,
+        artifacts: result.artifacts,
+        webhooks,
+    }))
+}
+
+/// Query parameters of the workflow error history
+#[derive(Debug, Deserialize)]
+pub struct WorkflowErrorsQuery {
+    pub limit: Option<usize>,
+}
+
+/// Step failures of workflow runs and how they were recovered, most recent first
+pub async fn workflow_errors_handler(
+    State(state): State<ApiState>,
+    Query(query): Query<WorkflowErrorsQuery>,
+) -> Json<serde_json::Value> {
+    let metrics = state.error_recovery.get_metrics().await;
+    let errors = state.error_recovery.get_error_history(query.limit).await;
+    Json(serde_json::json!({
+        "metrics": metrics,
+        "errors": errors,
     }))
 }
 
+/// Artifacts collected by a workflow run
+pub async fn workflow_artifacts_handler(
+    Path(run_id): Path<Uuid>,
+) -> Result<Json<Vec<Artifact>>, ApiError> {
+    let artifacts = ArtifactStore::default().list(run_id)?;
+    Ok(Json(artifacts))
+}
+
+/// Self-contained HTML report of a workflow run
+pub async fn workflow_report_handler(
+    Path(run_id): Path<Uuid>,
+) -> Result<axum::response::Html<String>, ApiError> {
+    let html = ReportStore::default().load(run_id)
+        .map_err(|e| ApiError {
+            error: "Report not found".to_string(),
+            details: Some(e.to_string()),
+            code: 404,
+        })?;
+    Ok(axum::response::Html(html))
+}
+
+/// Contents of one artifact of a workflow run
+pub async fn workflow_artifact_handler(
+    Path((run_id, name)): Path<(Uuid, String)>,
+) -> Result<Response, ApiError> {
+    let (artifact, data) = ArtifactStore::default().read(run_id, &name)
+        .map_err(|e| ApiError {
+            error: "Artifact not found".to_string(),
+            details: Some(e.to_string()),
+            code: 404,
+        })?;
+    
+    let disposition = format!("inline; filename=\"{}\"", artifact.name);
+    Ok(([(header::CONTENT_TYPE, artifact.content_type), (header::CONTENT_DISPOSITION, disposition)], data).into_response())
+}
+
 /// Handle flexible instructions (plain strings or structured)
 pub async fn flexible_instruction_handler(
     State(state): State<ApiState>,
@@ -2148,8 +2721,24 @@ pub fn create_router(state: ApiState) -> Router {
         // AI operations
         .route("/command", post(natural_language_handler))
         .route("/workflow", post(workflow_handler))
+        .route("/workflow/batch", post(batch_workflow_handler))
+        .route("/workflow/:run_id/resume", post(resume_workflow_handler))
+        .route("/workflow/:run_id/artifacts", get(workflow_artifacts_handler))
+        .route("/workflow/errors", get(workflow_errors_handler))
+        .route("/workflow/:run_id/artifacts/:name", get(workflow_artifact_handler))
+        .route("/workflow/:run_id/report", get(workflow_report_handler))
         .route("/instruction", post(flexible_instruction_handler))
         
+        // Stored workflows and schedules
+        .route("/workflows", get(list_workflows_handler).post(create_workflow_handler))
+        .route("/workflows/import", post(import_workflow_handler))
+        .route("/workflows/:id", get(get_workflow_handler).put(update_workflow_handler).delete(delete_workflow_handler))
+        .route("/workflows/:id/versions", get(workflow_versions_handler))
+        .route("/workflows/:id/diff", get(workflow_diff_handler))
+        .route("/workflows/:id/runs", get(workflow_runs_handler).post(run_workflow_handler))
+        .route("/workflows/:id/schedules", get(list_schedules_handler).post(create_schedule_handler))
+        .route("/schedules/:id", delete(delete_schedule_handler))
+        
         // Plugin operations
         .route("/plugins", post(plugin_handler))
         .route("/plugins/metrics", get(plugin_metrics_handler))
@@ -2334,7 +2923,8 @@ pub async fn start_server(config: Config) -> Result<()> {
         tracing::info!("LLM API configured with provider: {}", provider);
     }
     
-    let llm_service = Arc::new(LLMService::new(api_key));
+    let llm_cache = Arc::new(crate::LLMCache::from_config(&config.cache).await);
+    let llm_service = Arc::new(LLMService::new(api_key).with_cache(llm_cache));
     let metrics = Arc::new(MetricsCollector::new());
     let security = Arc::new(SecurityMiddleware::new(Default::default()));
     let cost_tracker = Arc::new(RwLock::new(CostTracker::new(config.budget.daily_limit)));
@@ -2347,6 +2937,15 @@ pub async fn start_server(config: Config) -> Result<()> {
         })?;
     let plugin_manager = Arc::new(RwLock::new(plugin_manager));
     
+    // Start the workflow scheduler, running library workflows before templates
+    let workflow_library = Arc::new(WorkflowLibrary::new("data/workflows")?);
+    let error_recovery = Arc::new(create_error_recovery_manager().await?);
+    let scheduler = Arc::new(WorkflowScheduler::new(SchedulerConfig {
+        workflows_dir: config.workflow.templates_dir.clone().into(),
+        ..Default::default()
+    })?.with_library(workflow_library.clone()).with_error_recovery(error_recovery.clone()));
+    scheduler.clone().start();
+    
     let state = ApiState {
         browser_pool,
         llm_service,
@@ -2356,6 +2955,9 @@ pub async fn start_server(config: Config) -> Result<()> {
         cost_tracker,
         sessions: Arc::new(RwLock::new(HashMap::new())),
         plugin_manager,
+        scheduler,
+        workflow_library,
+        error_recovery,
     };
     
     let app = create_router(state);
@@ -2414,4 +3016,4 @@ fn try_parse_simple_command(command: &str) -> Option<ParsedCommand> {
         element_selector: None,
         input_text: None,
     })
-}
\ No newline at end of file
+}
\ No newline at end of file
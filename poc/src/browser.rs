@@ -377,6 +377,22 @@ impl SimpleBrowser {
         info!("Taking screenshot: {} (full_page: {}, viewport: {}x{})", 
                filename, options.full_page, options.viewport_width, options.viewport_height);
         
+        let screenshot = self.capture_screenshot(options).await?;
+        
+        // Ensure screenshots directory exists
+        std::fs::create_dir_all("screenshots")
+            .context("Failed to create screenshots directory")?;
+        
+        let filepath = format!("screenshots/{}", filename);
+        std::fs::write(&filepath, &screenshot)
+            .context(format!("Failed to save screenshot to {}", filepath))?;
+        
+        info!("Screenshot saved: {} ({} bytes)", filepath, screenshot.len());
+        Ok(())
+    }
+
+    /// Take a screenshot and return the PNG data instead of saving it
+    pub async fn capture_screenshot(&self, options: &ScreenshotOptions) -> Result<Vec<u8>> {
         // Set viewport size if different from current
         if let Err(e) = self.set_viewport_size(options.viewport_width, options.viewport_height).await {
             warn!("Failed to set viewport size: {}", e);
@@ -400,16 +416,7 @@ impl SimpleBrowser {
                 .context("Viewport screenshot failed")?
         };
         
-        // Ensure screenshots directory exists
-        std::fs::create_dir_all("screenshots")
-            .context("Failed to create screenshots directory")?;
-        
-        let filepath = format!("screenshots/{}", filename);
-        std::fs::write(&filepath, &screenshot)
-            .context(format!("Failed to save screenshot to {}", filepath))?;
-        
-        info!("Screenshot saved: {} ({} bytes)", filepath, screenshot.len());
-        Ok(())
+        Ok(screenshot)
     }
 
     
@@ -609,6 +616,26 @@ impl SimpleBrowser {
         Ok(url.to_string())
     }
 
+    /// Cookies visible to the current page, as WebDriver cookie objects
+    pub async fn get_cookies(&self) -> Result<Vec<serde_json::Value>> {
+        let cookies = self.driver.get_all_cookies().await
+            .context("Failed to read cookies")?;
+        cookies.iter()
+            .map(|cookie| serde_json::to_value(cookie).context("Failed to serialize cookie"))
+            .collect()
+    }
+
+    /// Add cookies read with `get_cookies`; they must belong to the current page's domain
+    pub async fn add_cookies(&self, cookies: &[serde_json::Value]) -> Result<()> {
+        for cookie in cookies {
+            let cookie: thirtyfour::Cookie = serde_json::from_value(cookie.clone())
+                .context("Invalid cookie")?;
+            self.driver.add_cookie(cookie).await
+                .context("Failed to add cookie")?;
+        }
+        Ok(())
+    }
+
     // === Workflow-specific methods ===
 
     /// Fill a form field with text
@@ -1,10 +1,14 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+use crate::config::CacheConfig;
 
 /// A cached value with metadata
 #[derive(Debug, Clone)]
@@ -182,6 +186,41 @@ where
         removed
     }
 
+    /// Remove every entry whose key matches `predicate`, returning how many were removed
+    pub async fn remove_where<P>(&self, predicate: P) -> usize
+    where
+        P: Fn(&K) -> bool,
+    {
+        let mut store = self.store.write().await;
+        let before_size = store.len();
+        
+        store.retain(|key, _| !predicate(key));
+        
+        let removed = before_size - store.len();
+        if removed > 0 {
+            let mut stats = self.stats.write().await;
+            stats.current_size = store.len();
+            
+            debug!("Removed {} cache entries", removed);
+        }
+        
+        removed
+    }
+
+    /// Unexpired entries with their remaining time to live
+    pub async fn entries(&self) -> Vec<(K, V, Duration)> {
+        let store = self.store.read().await;
+        
+        store
+            .iter()
+            .filter(|(_, cached)| !cached.is_expired())
+            .map(|(key, cached)| {
+                let remaining = cached.ttl.saturating_sub(cached.created_at.elapsed());
+                (key.clone(), cached.value.clone(), remaining)
+            })
+            .collect()
+    }
+
     /// Clear all entries from the cache
     pub async fn clear(&self) {
         let mut store = self.store.write().await;
@@ -237,47 +276,222 @@ where
     }
 }
 
-/// Specialized cache for LLM responses
+/// Specialized cache for LLM responses, keyed by normalized prompt, model and
+/// temperature and optionally persisted so repeated requests survive restarts
 pub struct LLMCache {
     cache: Cache<String, serde_json::Value>,
+    enabled: bool,
+    /// File the entries are saved to after every change
+    path: Option<PathBuf>,
+}
+
+/// An LLM cache entry as saved on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedEntry {
+    key: String,
+    value: serde_json::Value,
+    /// Unix time in seconds after which the entry is stale
+    expires_at: u64,
+}
+
+impl std::fmt::Debug for LLMCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LLMCache")
+            .field("enabled", &self.enabled)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
 }
 
 impl LLMCache {
     pub fn new() -> Self {
         // LLM responses cached for 1 hour with max 500 entries
+        Self::with_config(Duration::from_secs(3600), 500)
+    }
+
+    /// Create an in-memory LLM cache with custom configuration
+    pub fn with_config(ttl: Duration, max_size: usize) -> Self {
         Self {
-            cache: Cache::with_config(Duration::from_secs(3600), 500),
+            cache: Cache::with_config(ttl, max_size),
+            enabled: true,
+            path: None,
+        }
+    }
+
+    /// Create the LLM cache described by the cache settings, persisted to
+    /// `<cache_dir>/llm_cache.json` when `persistent` is set
+    pub async fn from_config(config: &CacheConfig) -> Self {
+        let cache = Self::with_config(Duration::from_secs(config.llm_ttl), config.llm_max_size);
+        
+        if !config.enabled {
+            info!("LLM response cache disabled");
+            return Self { enabled: false, ..cache };
+        }
+        if !config.persistent {
+            return cache;
+        }
+        
+        let path = PathBuf::from(&config.cache_dir).join("llm_cache.json");
+        match cache.with_persistence(&path).await {
+            Ok(cache) => cache,
+            Err(e) => {
+                warn!("Failed to load LLM cache from {}: {}", path.display(), e);
+                Self::with_config(Duration::from_secs(config.llm_ttl), config.llm_max_size)
+            }
         }
     }
 
-    /// Generate a cache key from the prompt
-    pub fn generate_key(prompt: &str, model: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+    /// Persist entries to `path`, loading the unexpired entries already saved there
+    pub async fn with_persistence(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
         
-        let mut hasher = DefaultHasher::new();
-        prompt.hash(&mut hasher);
-        model.hash(&mut hasher);
+        if path.exists() {
+            let content = tokio::fs::read_to_string(&path).await?;
+            let entries: Vec<PersistedEntry> = serde_json::from_str(&content)?;
+            let now = unix_now();
+            let mut loaded = 0;
+            
+            for entry in entries.into_iter().filter(|entry| entry.expires_at > now) {
+                let ttl = Duration::from_secs(entry.expires_at - now);
+                self.cache.insert_with_ttl(entry.key, entry.value, ttl).await;
+                loaded += 1;
+            }
+            
+            info!("Loaded {} cached LLM responses from {}", loaded, path.display());
+        }
         
-        format!("llm_{}_{}", model, hasher.finish())
+        self.path = Some(path);
+        Ok(self)
+    }
+
+    /// Whether lookups and inserts are performed at all
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Collapse whitespace so prompts differing only in formatting share an entry
+    pub fn normalize_prompt(prompt: &str) -> String {
+        prompt.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// Generate a cache key from the normalized prompt, model and temperature.
+    /// The hash is FNV-1a so keys stay valid across restarts and toolchains.
+    pub fn generate_key(prompt: &str, model: &str, temperature: f32) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in Self::normalize_prompt(prompt).bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        
+        format!("llm_{}_t{}_{:016x}", model, temperature, hash)
     }
 
     /// Get a cached LLM response
-    pub async fn get(&self, prompt: &str, model: &str) -> Option<serde_json::Value> {
-        let key = Self::generate_key(prompt, model);
+    pub async fn get(&self, prompt: &str, model: &str, temperature: f32) -> Option<serde_json::Value> {
+        if !self.enabled {
+            return None;
+        }
+        
+        let key = Self::generate_key(prompt, model, temperature);
         self.cache.get(&key).await
     }
 
     /// Cache an LLM response
-    pub async fn insert(&self, prompt: &str, model: &str, response: serde_json::Value) {
-        let key = Self::generate_key(prompt, model);
-        self.cache.insert(key, response).await;
+    pub async fn insert(&self, prompt: &str, model: &str, temperature: f32, response: serde_json::Value) {
+        self.insert_with_ttl(prompt, model, temperature, response, self.cache.default_ttl).await;
+    }
+
+    /// Cache an LLM response with a custom TTL
+    pub async fn insert_with_ttl(
+        &self,
+        prompt: &str,
+        model: &str,
+        temperature: f32,
+        response: serde_json::Value,
+        ttl: Duration,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        
+        let key = Self::generate_key(prompt, model, temperature);
+        self.cache.insert_with_ttl(key, response, ttl).await;
+        self.persist().await;
+    }
+
+    /// Drop the cached response for one prompt
+    pub async fn invalidate(&self, prompt: &str, model: &str, temperature: f32) -> bool {
+        let key = Self::generate_key(prompt, model, temperature);
+        let removed = self.cache.remove(&key).await.is_some();
+        
+        if removed {
+            self.persist().await;
+        }
+        removed
+    }
+
+    /// Drop every cached response of a model, e.g. after changing its prompts
+    pub async fn invalidate_model(&self, model: &str) -> usize {
+        let prefix = format!("llm_{}_t", model);
+        let removed = self.cache.remove_where(|key| key.starts_with(&prefix)).await;
+        
+        if removed > 0 {
+            info!("Invalidated {} cached responses of {}", removed, model);
+            self.persist().await;
+        }
+        removed
+    }
+
+    /// Drop every cached response
+    pub async fn clear(&self) {
+        self.cache.clear().await;
+        self.persist().await;
     }
 
     /// Get cache statistics
     pub async fn stats(&self) -> CacheStats {
         self.cache.stats().await
     }
+
+    /// Save the unexpired entries; failures only cost future cache hits, so they are logged
+    async fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        
+        let now = unix_now();
+        let entries: Vec<PersistedEntry> = self
+            .cache
+            .entries()
+            .await
+            .into_iter()
+            .map(|(key, value, remaining)| PersistedEntry {
+                key,
+                value,
+                expires_at: now + remaining.as_secs(),
+            })
+            .collect();
+        
+        let result = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, serde_json::to_vec(&entries)?).await?;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+        
+        if let Err(e) = result {
+            warn!("Failed to persist LLM cache to {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
 /// Specialized cache for workflow templates
@@ -369,4 +583,41 @@ mod tests {
         let stats = cache.stats().await;
         assert_eq!(stats.evictions, 1);
     }
+
+    #[tokio::test]
+    async fn test_llm_cache_keys_normalize_prompts() {
+        let cache = LLMCache::new();
+        let plan = serde_json::json!({"action": "navigate", "url": "google.com"});
+
+        cache.insert("navigate to   google\n", "gpt-4", 0.1, plan.clone()).await;
+        assert_eq!(cache.get(" navigate to google", "gpt-4", 0.1).await, Some(plan));
+
+        // Model and temperature are part of the key
+        assert_eq!(cache.get("navigate to google", "gpt-3.5-turbo", 0.1).await, None);
+        assert_eq!(cache.get("navigate to google", "gpt-4", 0.7).await, None);
+
+        assert_eq!(cache.invalidate_model("gpt-4").await, 1);
+        assert_eq!(cache.get("navigate to google", "gpt-4", 0.1).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_llm_cache_persists_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("llm_cache.json");
+        let plan = serde_json::json!({"action": "report"});
+
+        let cache = LLMCache::new().with_persistence(&path).await.unwrap();
+        cache.insert("show cost report", "gpt-4", 0.1, plan.clone()).await;
+        cache
+            .insert_with_ttl("old report", "gpt-4", 0.1, plan.clone(), Duration::from_secs(0))
+            .await;
+
+        let restarted = LLMCache::new().with_persistence(&path).await.unwrap();
+        assert_eq!(restarted.get("show cost report", "gpt-4", 0.1).await, Some(plan));
+        assert_eq!(restarted.get("old report", "gpt-4", 0.1).await, None);
+
+        assert!(restarted.invalidate("show cost report", "gpt-4", 0.1).await);
+        let reloaded = LLMCache::new().with_persistence(&path).await.unwrap();
+        assert_eq!(reloaded.stats().await.current_size, 0);
+    }
 }
\ No newline at end of file
@@ -447,6 +447,25 @@ impl ErrorRecoveryManager {
         Ok(final_result)
     }
 
+    /// Classify an error without attempting recovery
+    pub async fn classify(&self, error: &anyhow::Error, context: &ErrorContext) -> (ErrorCategory, ErrorSeverity) {
+        self.classify_error(error, context).await
+    }
+
+    /// Record an error the caller recovered from (or gave up on) by itself, such as a
+    /// workflow step retried under its own policy, so it shows up in history and metrics
+    pub async fn record_handled_error(&self, error: &anyhow::Error, context: ErrorContext, attempts: Vec<RecoveryAttempt>, resolution_status: ResolutionStatus, recovery_time_ms: Option<u64>) -> ErrorCategory {
+        let (category, severity) = self.classify_error(error, &context).await;
+        let result = match resolution_status {
+            ResolutionStatus::Recovered | ResolutionStatus::WorkedAround => RecoveryResult::Success { details: "Recovered by caller".to_string() },
+            ResolutionStatus::PartiallyRecovered => RecoveryResult::Partial { success_details: "Worked around by caller".to_string(), remaining_issues: error.to_string() },
+            _ => RecoveryResult::Failed { reason: error.to_string() },
+        };
+        self.record_error(Uuid::new_v4(), category, severity, error.to_string(), context, attempts, resolution_status, recovery_time_ms).await;
+        self.update_metrics(category, &result).await;
+        category
+    }
+
     /// Classify error into category and severity
     async fn classify_error(&self, error: &anyhow::Error, context: &ErrorContext) -> (ErrorCategory, ErrorSeverity) {
         let classifier = self.error_classifier.read().await;
@@ -829,10 +848,47 @@ impl ErrorClassifier {
             priority: 80,
         });
 
+        // Validation error patterns, which retrying cannot fix
+        self.classification_rules.push(ClassificationRule {
+            name: "ValidationError".to_string(),
+            message_patterns: vec![
+                "invalid selector".to_string(),
+                "syntaxerror".to_string(),
+                "not a valid".to_string(),
+                "failed to parse".to_string(),
+            ],
+            context_patterns: vec![],
+            category: ErrorCategory::ValidationError,
+            confidence: 0.9,
+            priority: 95,
+        });
+
+        // Authentication error patterns
+        self.classification_rules.push(ClassificationRule {
+            name: "AuthenticationError".to_string(),
+            message_patterns: vec![
+                "unauthorized".to_string(),
+                "forbidden".to_string(),
+                "invalid credentials".to_string(),
+            ],
+            context_patterns: vec![],
+            category: ErrorCategory::AuthenticationError,
+            confidence: 0.9,
+            priority: 95,
+        });
+
         info!("🔍 Initialized {} classification rules", self.classification_rules.len());
     }
 }
 
+impl ErrorCategory {
+    /// Whether repeating the failed operation can succeed; invalid input, bad
+    /// configuration and rejected credentials fail the same way every time
+    pub fn is_transient(&self) -> bool {
+        !matches!(self, ErrorCategory::ValidationError | ErrorCategory::ConfigurationError | ErrorCategory::AuthenticationError)
+    }
+}
+
 /// Create error recovery manager with default configuration
 pub async fn create_error_recovery_manager() -> Result<ErrorRecoveryManager> {
     let config = ErrorRecoveryConfig::default();
@@ -870,6 +926,32 @@ mod tests {
         assert_eq!(category, ErrorCategory::NetworkError);
     }
 
+    #[tokio::test]
+    async fn test_handled_errors_are_recorded() {
+        let manager = create_error_recovery_manager().await.unwrap();
+        let context = || ErrorContext {
+            task_type: None,
+            context_snapshot: None,
+            stack_trace: None,
+            metadata: HashMap::new(),
+            related_errors: Vec::new(),
+        };
+
+        let error = anyhow::anyhow!("invalid selector: An invalid or illegal selector was specified");
+        let category = manager.record_handled_error(&error, context(), vec![], ResolutionStatus::Unrecovered, None).await;
+        assert_eq!(category, ErrorCategory::ValidationError);
+        assert!(!category.is_transient());
+
+        let error = anyhow::anyhow!("Element not found: .price");
+        let category = manager.record_handled_error(&error, context(), vec![], ResolutionStatus::Recovered, Some(1200)).await;
+        assert!(category.is_transient());
+
+        let metrics = manager.get_metrics().await;
+        assert_eq!(metrics.total_errors, 2);
+        assert_eq!(metrics.successful_recoveries, 1);
+        assert_eq!(manager.get_error_history(None).await.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_recovery_strategy_execution() {
         let manager = create_error_recovery_manager().await.unwrap();
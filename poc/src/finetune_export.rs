@@ -0,0 +1,290 @@
+//! Fine-Tuning Dataset Export
+//!
+//! Converts successful command history (instruction -> parsed plan -> outcome) into
+//! JSONL datasets for training custom parsing models, scrubbing personal data such
+//! as emails, phone numbers, card numbers and credentials along the way.
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use tracing::info;
+
+use crate::context::HistoryEntry;
+
+/// System prompt of chat examples unless one is configured
+const DEFAULT_SYSTEM_PROMPT: &str =
+    "Parse the browser automation command into structured JSON. Respond ONLY with valid JSON.";
+
+/// Layout of each JSONL line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FineTuneFormat {
+    /// `{"messages": [system, user, assistant]}` as used by chat fine-tuning APIs
+    Chat,
+    /// `{"instruction", "plan", "outcome", "timestamp"}` for custom training pipelines
+    Raw,
+}
+
+/// Export options
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: FineTuneFormat,
+    /// System prompt of chat examples
+    pub system_prompt: Option<String>,
+    /// Also export commands whose execution failed
+    pub include_failed: bool,
+    pub scrub_pii: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: FineTuneFormat::Chat,
+            system_prompt: None,
+            include_failed: false,
+            scrub_pii: true,
+        }
+    }
+}
+
+/// What an export wrote
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub examples: usize,
+    /// Entries left out because their execution failed
+    pub skipped_failed: usize,
+    /// Personal data values replaced by placeholders
+    pub redactions: usize,
+}
+
+/// Replaces personal data and credentials in text with placeholders
+pub struct PiiScrubber {
+    rules: Vec<(Regex, &'static str)>,
+}
+
+impl PiiScrubber {
+    pub fn new() -> Self {
+        let rules = [
+            // Credentials first, so their values are not half-matched as numbers
+            (r"(?i)\b(password|passwd|pwd|token|access_token|api[_-]?key|secret|session|sid|auth)=[^&\s]+", "$1=[SECRET]"),
+            (r"(?i)\bbearer\s+[a-z0-9._~+/-]+=*", "Bearer [SECRET]"),
+            (r"\b(?:sk|pk|rk)-[A-Za-z0-9_-]{16,}", "[SECRET]"),
+            (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "[EMAIL]"),
+            (r"\b\d{3}-\d{2}-\d{4}\b", "[SSN]"),
+            (r"\b(?:\d[ -]?){12,15}\d\b", "[CARD]"),
+            (r"(?:\+\d{1,3}[\s.-]?)?\(?\b\d{3}\)?[\s.-]?\d{3}[\s.-]?\d{4}\b", "[PHONE]"),
+            (r"\b(?:\d{1,3}\.){3}\d{1,3}\b", "[IP]"),
+        ];
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|(pattern, replacement)| (Regex::new(pattern).expect("valid PII pattern"), replacement))
+                .collect(),
+        }
+    }
+
+    /// `text` with personal data replaced, and how many values were replaced
+    pub fn scrub(&self, text: &str) -> (String, usize) {
+        let mut scrubbed = text.to_string();
+        let mut redactions = 0;
+        for (pattern, replacement) in &self.rules {
+            let matches = pattern.find_iter(&scrubbed).count();
+            if matches > 0 {
+                redactions += matches;
+                scrubbed = pattern.replace_all(&scrubbed, *replacement).into_owned();
+            }
+        }
+        (scrubbed, redactions)
+    }
+
+    /// Scrub every string in a JSON value, returning the number of values replaced
+    pub fn scrub_value(&self, value: &mut Value) -> usize {
+        match value {
+            Value::String(text) => {
+                let (scrubbed, redactions) = self.scrub(text);
+                *text = scrubbed;
+                redactions
+            }
+            Value::Array(items) => items.iter_mut().map(|item| self.scrub_value(item)).sum(),
+            Value::Object(object) => object.values_mut().map(|item| self.scrub_value(item)).sum(),
+            _ => 0,
+        }
+    }
+}
+
+impl Default for PiiScrubber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds fine-tuning datasets from command history
+pub struct FineTuneExporter {
+    options: ExportOptions,
+    scrubber: PiiScrubber,
+}
+
+impl FineTuneExporter {
+    pub fn new(options: ExportOptions) -> Self {
+        Self {
+            options,
+            scrubber: PiiScrubber::new(),
+        }
+    }
+
+    /// JSONL dataset of `history`, one example per line
+    pub fn export(&self, history: &[HistoryEntry]) -> (String, ExportSummary) {
+        let mut summary = ExportSummary::default();
+        let mut lines = Vec::new();
+
+        for entry in history {
+            if !entry.execution_result.success && !self.options.include_failed {
+                summary.skipped_failed += 1;
+                continue;
+            }
+
+            let mut example = self.example(entry);
+            if self.options.scrub_pii {
+                summary.redactions += self.scrubber.scrub_value(&mut example);
+            }
+            lines.push(example.to_string());
+            summary.examples += 1;
+        }
+
+        let mut dataset = lines.join("\n");
+        if !dataset.is_empty() {
+            dataset.push('\n');
+        }
+        (dataset, summary)
+    }
+
+    /// Write the dataset of `history` to `path`
+    pub fn export_to_file(&self, history: &[HistoryEntry], path: &Path) -> Result<ExportSummary> {
+        let (dataset, summary) = self.export(history);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, dataset)?;
+
+        info!("📦 Exported {} fine-tuning examples to {} ({} failed skipped, {} values redacted)",
+              summary.examples, path.display(), summary.skipped_failed, summary.redactions);
+        Ok(summary)
+    }
+
+    fn example(&self, entry: &HistoryEntry) -> Value {
+        let plan = plan_json(entry);
+        match self.options.format {
+            FineTuneFormat::Chat => json!({
+                "messages": [
+                    {
+                        "role": "system",
+                        "content": self.options.system_prompt.as_deref().unwrap_or(DEFAULT_SYSTEM_PROMPT),
+                    },
+                    { "role": "user", "content": entry.user_input },
+                    { "role": "assistant", "content": plan.to_string() },
+                ]
+            }),
+            FineTuneFormat::Raw => json!({
+                "instruction": entry.user_input,
+                "plan": plan,
+                "outcome": {
+                    "success": entry.execution_result.success,
+                    "duration_ms": entry.execution_result.duration_ms,
+                    "output_summary": entry.execution_result.output_summary,
+                    "error_message": entry.execution_result.error_message,
+                },
+                "timestamp": entry.timestamp,
+            }),
+        }
+    }
+}
+
+/// The parsed command in the compact shape the parsing prompt asks for: unset
+/// fields and the derived `parameters` are left out
+fn plan_json(entry: &HistoryEntry) -> Value {
+    let mut plan = serde_json::to_value(&entry.parsed_command).unwrap_or(Value::Null);
+    let password_field = entry
+        .parsed_command
+        .element_selector
+        .as_deref()
+        .is_some_and(|selector| selector.to_lowercase().contains("password"));
+
+    if let Value::Object(fields) = &mut plan {
+        fields.remove("parameters");
+        fields.retain(|_, value| !value.is_null() && value.as_array().is_none_or(|items| !items.is_empty()));
+        // Text typed into password fields never belongs in a dataset
+        if password_field && fields.contains_key("input_text") {
+            fields.insert("input_text".to_string(), Value::String("[SECRET]".to_string()));
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ExecutionResult;
+    use crate::llm_service::structured_output;
+
+    fn entry(user_input: &str, command: &str, success: bool) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: chrono::Utc::now(),
+            user_input: user_input.to_string(),
+            parsed_command: structured_output::parse(command).unwrap(),
+            execution_result: ExecutionResult {
+                success,
+                duration_ms: 1500,
+                error_message: None,
+                output_summary: "done".to_string(),
+            },
+            cost: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_scrubber_replaces_personal_data() {
+        let scrubber = PiiScrubber::new();
+        let (text, redactions) = scrubber.scrub(
+            "email jane.doe@example.com, call +1 415-555-0132, card 4111 1111 1111 1111, \
+             open https://app.example.com/login?user=jane&token=abc123 from 10.0.0.12",
+        );
+        assert_eq!(
+            text,
+            "email [EMAIL], call [PHONE], card [CARD], \
+             open https://app.example.com/login?user=jane&token=[SECRET] from [IP]"
+        );
+        assert_eq!(redactions, 5);
+    }
+
+    #[test]
+    fn test_export_writes_successful_examples() {
+        let history = vec![
+            entry("go to github", r#"{"action": "navigate", "url": "github.com", "confidence": 0.5}"#, true),
+            entry("open the broken site", r#"{"action": "navigate", "url": "broken.test", "confidence": 0.4}"#, false),
+            entry(
+                "type my password into the login form",
+                r##"{"action": "type", "element_selector": "#password", "input_text": "hunter2", "confidence": 0.8}"##,
+                true,
+            ),
+        ];
+
+        let (dataset, summary) = FineTuneExporter::new(ExportOptions::default()).export(&history);
+        assert_eq!(summary.examples, 2);
+        assert_eq!(summary.skipped_failed, 1);
+
+        let lines: Vec<Value> = dataset.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines[0]["messages"][1]["content"], "go to github");
+        let plan: Value = serde_json::from_str(lines[0]["messages"][2]["content"].as_str().unwrap()).unwrap();
+        assert_eq!(plan, json!({"action": "navigate", "url": "github.com", "screenshot": false,
+                                "viewport_only": false, "confidence": 0.5}));
+        assert!(!dataset.contains("hunter2"));
+
+        let raw = FineTuneExporter::new(ExportOptions { format: FineTuneFormat::Raw, ..Default::default() });
+        let (dataset, _) = raw.export(&history[..1]);
+        let example: Value = serde_json::from_str(dataset.trim()).unwrap();
+        assert_eq!(example["plan"]["url"], "github.com");
+        assert_eq!(example["outcome"]["success"], true);
+    }
+}
@@ -9,6 +9,12 @@ pub mod mock_llm_provider;
 // Supporting modules used by core
 pub mod context;
 pub mod workflow;
+pub mod scheduler;
+pub mod workflow_library;
+pub mod workflow_import;
+pub mod workflow_batch;
+pub mod workflow_webhooks;
+pub mod report;
 pub mod browser_pool;
 pub mod metrics;
 pub mod security;
@@ -27,6 +33,7 @@ pub mod error_recovery;
 pub mod llm_integration;
 pub mod contextual_awareness;
 pub mod simple_memory;
+pub mod finetune_export;
 
 // New enhanced modules for instruction parsing and extraction
 pub mod instruction_parser;
@@ -79,7 +86,13 @@ pub use mock_llm_provider::{MockLLMProvider, create_mock_provider, create_custom
 
 // Supporting exports
 pub use context::{ConversationContext, HistoryEntry, ExecutionResult};
-pub use workflow::{Workflow, WorkflowEngine, WorkflowResult, WorkflowStep, ActionType};
+pub use workflow::{Workflow, WorkflowEngine, WorkflowResult, WorkflowStep, ActionType, CheckpointStore, WorkflowCheckpoint, ArtifactStore, Artifact, ArtifactSpec, ArtifactKind, ErrorStrategy, RetryPolicy, Backoff};
+pub use scheduler::{WorkflowScheduler, SchedulerConfig, Schedule, Trigger, WorkflowRun, CronSchedule};
+pub use workflow_library::{WorkflowLibrary, WorkflowEntry, WorkflowVersion, WorkflowSummary, WorkflowDiff, DiffLine, SemVer, VersionBump};
+pub use workflow_import::{import_script, import_selenium_side, import_playwright, ImportedWorkflow, ImportSource};
+pub use workflow_batch::{Dataset, BatchOptions, BatchRow, BatchReport, run_batch};
+pub use workflow_webhooks::{WebhookConfig, WebhookEvent, WebhookPayload, WebhookDelivery, notify_webhooks};
+pub use report::ReportStore;
 pub use browser_pool::{BrowserPool, PooledBrowserHandle};
 pub use metrics::{MetricsCollector, Metrics, MetricsSummary};
 pub use security::{SecurityConfig, SecurityMiddleware, RateLimiter, InputValidator};
@@ -88,10 +101,11 @@ pub use plugins::{PluginManager, init_plugin_system};
 pub use cache::{Cache, LLMCache, WorkflowCache};
 pub use task_executor::{TaskExecutor, TaskExecutionResult, ExecutionProgress, AggregatedResults};
 pub use health_monitor::{HealthMonitor, HealthMonitorConfig, HealthStatus, SystemHealthMetrics, HealthReport, create_health_monitor, create_custom_health_monitor};
-pub use error_recovery::{ErrorRecoveryManager, ErrorRecoveryConfig, ErrorCategory, ErrorSeverity, RecoveryResult, create_error_recovery_manager, create_custom_error_recovery_manager};
-pub use llm_integration::{LLMIntegrationManager, LLMConfig, LLMProvider, ModelSelectionStrategy, LLMMetrics, LLMRequest, LLMResponse, IntentUnderstanding, Entity, CreativeSolution as LLMCreativeSolution, ProviderHealth, create_llm_integration_manager, create_custom_llm_integration_manager};
+pub use error_recovery::{ErrorRecoveryManager, ErrorRecoveryConfig, ErrorCategory, ErrorSeverity, RecoveryResult, ErrorContext, ErrorRecord, RecoveryAttempt, ResolutionStatus, create_error_recovery_manager, create_custom_error_recovery_manager};
+pub use llm_integration::{LLMIntegrationManager, LLMConfig, LLMProvider, ModelSelectionStrategy, LLMMetrics, FailoverEvent, LLMRequest, LLMResponse, IntentUnderstanding, Entity, CreativeSolution as LLMCreativeSolution, ProviderHealth, create_llm_integration_manager, create_custom_llm_integration_manager};
 pub use contextual_awareness::{ContextualAwareness, ContextSnapshot, ContextualRecommendations, TemporalContext, EnvironmentalContext, UserContext, SystemContext, create_contextual_awareness, create_contextual_awareness_with_memory};
-pub use simple_memory::{SimpleMemory, SimpleMemoryConfig, InteractionRecord, LearnedPattern, SimpleMemoryStats, create_simple_memory};
+pub use simple_memory::{SimpleMemory, SimpleMemoryConfig, InteractionRecord, LearnedPattern, SimpleMemoryStats, RecalledInteraction, Embedder, HashingEmbedder, create_simple_memory};
+pub use finetune_export::{FineTuneExporter, FineTuneFormat, ExportOptions, ExportSummary, PiiScrubber};
 
 // New enhanced instruction parsing and extraction exports
 pub use instruction_parser::{InstructionParser, UserInstruction, ContextHints, Feedback, PageType};
@@ -136,4 +150,4 @@ pub use continuous_improvement_pipeline::{
 // pub use advanced_learning::{AdvancedLearningEngine, AdvancedLearningConfig, LearningAlgorithm, LearningObjective, LearnedPattern as AdvancedLearnedPattern, LearningMetrics, OptimizationRecommendation, create_advanced_learning_engine, create_custom_learning_engine};
 // pub use multi_model_orchestration::{MultiModelOrchestrator, OrchestrationConfig, OrchestrationStrategy, OrchestrationRequest, OrchestrationResponse, TaskSpecialty, create_multi_model_orchestrator, create_custom_orchestrator};
 // pub use self_healing::{SelfHealingSystem, SelfHealingConfig, HealingStrategy, OptimizationArea, DetectedIssue, SelfHealingMetrics, create_self_healing_system, create_custom_self_healing_system};
-// pub use advanced_analytics::{AdvancedAnalyticsEngine, AnalyticsConfig, AnalyticsInsight, AnalyticsReport, TimePeriod, InsightType, create_analytics_engine, create_custom_analytics_engine};
\ No newline at end of file
+// pub use advanced_analytics::{AdvancedAnalyticsEngine, AnalyticsConfig, AnalyticsInsight, AnalyticsReport, TimePeriod, InsightType, create_analytics_engine, create_custom_analytics_engine};
\ No newline at end of file
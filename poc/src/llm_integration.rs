@@ -9,17 +9,20 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{RwLock, Semaphore};
 use tracing::{info, warn, debug};
 use uuid::Uuid;
 
 use crate::llm_service::llm_service_enhanced::{TaskType, ActionStep};
+use crate::llm_service::structured_output::{self, StructuredOutput, MAX_REPAIR_ATTEMPTS};
 use crate::contextual_awareness::ContextSnapshot;
 use crate::cost_tracker::CostTracker;
 
 /// Task plan for LLM integration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskPlan {
+    #[serde(default = "Uuid::new_v4")]
     pub task_id: Uuid,
     pub task_type: TaskType,
     pub steps: Vec<ActionStep>,
@@ -82,13 +85,54 @@ pub struct LLMConfig {
     pub daily_cost_budget: f64,
     /// Enable automatic model switching
     pub enable_auto_model_switching: bool,
+    /// Retries per provider before failing over to the next one
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every further retry (milliseconds)
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// Upper bound for the retry delay (milliseconds)
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub retry_max_delay_ms: u64,
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    8000
+}
+
+impl LLMConfig {
+    /// Providers to try in order: the selected one, then the configured fallbacks
+    pub fn failover_chain(&self, selected: LLMProvider) -> Vec<LLMProvider> {
+        let mut chain = vec![selected];
+        for provider in &self.fallback_providers {
+            if !chain.contains(provider) {
+                chain.push(*provider);
+            }
+        }
+        chain
+    }
+
+    /// Exponential backoff before retry number `attempt` (starting at 1)
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let delay_ms = self.retry_base_delay_ms.saturating_mul(factor).min(self.retry_max_delay_ms);
+        Duration::from_millis(delay_ms)
+    }
 }
 
 impl Default for LLMConfig {
     fn default() -> Self {
         Self {
             primary_provider: LLMProvider::Mock,
-            fallback_providers: vec![LLMProvider::OpenAI, LLMProvider::Anthropic],
+            fallback_providers: vec![LLMProvider::OpenAI, LLMProvider::Anthropic, LLMProvider::Local],
             model_selection_strategy: ModelSelectionStrategy::CostOptimized,
             enable_cost_optimization: true,
             enable_intelligent_caching: true,
@@ -97,6 +141,9 @@ impl Default for LLMConfig {
             enable_performance_monitoring: true,
             daily_cost_budget: 50.0,
             enable_auto_model_switching: true,
+            max_retries: default_max_retries(),
+            retry_base_delay_ms: default_retry_base_delay_ms(),
+            retry_max_delay_ms: default_retry_max_delay_ms(),
         }
     }
 }
@@ -124,6 +171,32 @@ pub struct LLMMetrics {
     pub cache_misses: u64,
     pub provider_performance: HashMap<LLMProvider, ProviderMetrics>,
     pub model_performance: HashMap<String, ModelMetrics>,
+    /// Retries made after provider errors or timeouts
+    #[serde(default)]
+    pub retries: u64,
+    /// Most recent provider failovers, oldest first
+    #[serde(default)]
+    pub failover_events: Vec<FailoverEvent>,
+    /// Structured responses sent back to the model because they did not match their schema
+    #[serde(default)]
+    pub structured_output_repairs: u64,
+}
+
+/// Failover events kept in `LLMMetrics`
+const MAX_FAILOVER_EVENTS: usize = 100;
+
+/// A request moving on from a provider that kept failing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverEvent {
+    pub request_id: Uuid,
+    pub task_type: TaskType,
+    pub from_provider: LLMProvider,
+    /// Next provider tried; `None` when the chain was exhausted
+    pub to_provider: Option<LLMProvider>,
+    /// Attempts made against `from_provider`
+    pub attempts: u32,
+    pub error: String,
+    pub timestamp: DateTime<Utc>,
 }
 
 /// Provider-specific metrics
@@ -424,17 +497,19 @@ impl LLMIntegrationManager {
             timeout_ms: Some(self.config.read().await.request_timeout_ms),
         };
 
-        // Execute request with selected provider
-        let result = if let Some(provider_impl) = self.providers.get(&provider) {
-            provider_impl.understand_intent(input, context).await
-        } else {
-            self.generate_mock_response(TaskType::Analysis).await
-        };
+        // Execute request with selected provider, failing over when it keeps erroring
+        let result = self.execute_with_fallback(
+            |provider_impl| async move { provider_impl.understand_intent(input, context).await },
+            provider,
+            &model,
+            request_id,
+            TaskType::Analysis,
+        ).await;
 
         let duration = start_time.elapsed().as_millis() as u64;
 
         match result {
-            Ok(understanding) => {
+            Ok((understanding, provider, model)) => {
                 info!("✅ Intent understanding completed: confidence={:.2}, duration={}ms", 
                      understanding.confidence, duration);
 
@@ -467,16 +542,18 @@ impl LLMIntegrationManager {
         // Select model optimized for planning tasks
         let (provider, model) = self.select_optimal_model(TaskType::Planning, intent, context).await?;
 
-        let result = if let Some(provider_impl) = self.providers.get(&provider) {
-            provider_impl.create_task_plan(intent, context).await
-        } else {
-            self.generate_mock_response(TaskType::Planning).await
-        };
+        let result = self.execute_with_fallback(
+            |provider_impl| async move { provider_impl.create_task_plan(intent, context).await },
+            provider,
+            &model,
+            request_id,
+            TaskType::Planning,
+        ).await;
 
         let duration = start_time.elapsed().as_millis() as u64;
 
         match result {
-            Ok(plan) => {
+            Ok((plan, provider, model)) => {
                 info!("✅ Task plan created: {} steps, duration={}ms", plan.steps.len(), duration);
                 
                 // Calculate quality score based on plan characteristics
@@ -507,16 +584,18 @@ impl LLMIntegrationManager {
         // Use creativity-optimized model
         let (provider, model) = self.select_creative_model(problem).await?;
 
-        let result = if let Some(provider_impl) = self.providers.get(&provider) {
-            provider_impl.generate_creative_solution(problem, constraints).await
-        } else {
-            self.generate_mock_response(TaskType::Analysis).await
-        };
+        let result = self.execute_with_fallback(
+            |provider_impl| async move { provider_impl.generate_creative_solution(problem, constraints).await },
+            provider,
+            &model,
+            request_id,
+            TaskType::Analysis,
+        ).await;
 
         let duration = start_time.elapsed().as_millis() as u64;
 
         match result {
-            Ok(solution) => {
+            Ok((solution, provider, model)) => {
                 info!("✅ Creative solution generated: confidence={:.2}, creativity={:.2}, duration={}ms", 
                      solution.confidence, solution.creativity_score, duration);
                 
@@ -533,6 +612,91 @@ impl LLMIntegrationManager {
         }
     }
 
+    /// Request JSON matching `T`'s schema. Responses that fail validation are sent
+    /// back to the model with the errors, up to `MAX_REPAIR_ATTEMPTS` times per provider.
+    pub async fn complete_structured<T: StructuredOutput>(&self, prompt: &str, task_type: TaskType, context: &ContextSnapshot) -> Result<T> {
+        let request_id = Uuid::new_v4();
+        let start_time = std::time::Instant::now();
+
+        info!("🧩 Requesting structured {} (id: {})", T::schema_name(), request_id);
+
+        let _permit = self.rate_limiter.acquire().await?;
+
+        let (provider, model) = self.select_optimal_model(task_type, prompt, context).await?;
+
+        // The mock fallback of execute_with_fallback only knows the built-in result types
+        let config = self.config.read().await.clone();
+        if !config.failover_chain(provider).iter().any(|p| self.providers.contains_key(p)) {
+            return Err(anyhow::anyhow!("No LLM provider available for structured {}", T::schema_name()));
+        }
+
+        let llm_context = LLMContext {
+            task_type,
+            context_snapshot: Some(context.clone()),
+            previous_attempts: vec![],
+            constraints: vec![],
+            quality_requirements: QualityRequirements {
+                require_structured_output: true,
+                ..Default::default()
+            },
+            cost_constraints: None,
+            timeout_ms: Some(config.request_timeout_ms),
+        };
+        let full_prompt = format!("{}\n\n{}", prompt, structured_output::schema_instructions::<T>());
+        let (full_prompt, model_name, llm_context) = (full_prompt.as_str(), model.as_str(), &llm_context);
+
+        let result = self.execute_with_fallback(
+            |provider_impl| async move {
+                self.complete_and_repair::<T>(provider_impl, full_prompt, model_name, llm_context).await
+            },
+            provider,
+            &model,
+            request_id,
+            task_type,
+        ).await;
+
+        let duration = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(((value, quality_score, cost), provider, model)) => {
+                info!("✅ Structured {} completed: duration={}ms", T::schema_name(), duration);
+                self.record_successful_request(request_id, provider, &model, task_type,
+                    quality_score, duration, cost).await;
+                Ok(value)
+            },
+            Err(e) => {
+                warn!("❌ Structured {} failed: {} (duration: {}ms)", T::schema_name(), e, duration);
+                self.record_failed_request(request_id, provider, &model, task_type, duration).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Complete `prompt` with one provider, repairing invalid responses. Returns the
+    /// value with the last response's quality score and the cost of all attempts.
+    async fn complete_and_repair<T: StructuredOutput>(&self, provider_impl: Arc<dyn LLMProviderTrait>,
+        prompt: &str, model: &str, context: &LLMContext) -> Result<(T, f32, f64)> {
+
+        let mut request_prompt = prompt.to_string();
+        let mut cost = 0.0;
+        let mut repairs = 0;
+        loop {
+            let response = provider_impl.complete_text(&request_prompt, model, context).await?;
+            cost += response.cost_usd;
+
+            match structured_output::parse::<T>(&response.content) {
+                Ok(value) => return Ok((value, response.quality_score, cost)),
+                Err(e) if repairs < MAX_REPAIR_ATTEMPTS => {
+                    repairs += 1;
+                    debug!("Repairing structured {} (attempt {}): {}", T::schema_name(), repairs, e);
+                    self.metrics.write().await.structured_output_repairs += 1;
+                    request_prompt = structured_output::repair_prompt::<T>(prompt, &response.content, &e);
+                },
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
     /// Select optimal model for task
     async fn select_optimal_model(&self, task_type: TaskType, _input: &str, _context: &ContextSnapshot) -> Result<(LLMProvider, String)> {
         let config = self.config.read().await;
@@ -574,53 +738,87 @@ impl LLMIntegrationManager {
         Ok((config.primary_provider, self.get_default_model_for_provider(config.primary_provider, TaskType::Analysis)))
     }
 
-    /// Execute request with automatic fallback
+    /// Execute request with retries and exponential backoff, failing over along the
+    /// configured provider chain. Returns the result with the provider and model that
+    /// produced it.
     async fn execute_with_fallback<F, T>(
         &self,
         operation: impl Fn(Arc<dyn LLMProviderTrait>) -> F,
         primary_provider: LLMProvider,
-        _model: &str,
+        model: &str,
         request_id: Uuid,
         task_type: TaskType,
-    ) -> Result<T>
+    ) -> Result<(T, LLMProvider, String)>
     where
         F: std::future::Future<Output = Result<T>>,
     {
-        // Try primary provider first
-        if let Some(provider) = self.providers.get(&primary_provider) {
-            match operation(provider.clone()).await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    warn!("Primary provider {} failed for request {}: {}", 
-                         primary_provider.to_string(), request_id, e);
-                }
-            }
+        let config = self.config.read().await.clone();
+        let timeout = Duration::from_millis(config.request_timeout_ms);
+
+        let chain: Vec<(LLMProvider, Arc<dyn LLMProviderTrait>)> = config
+            .failover_chain(primary_provider)
+            .into_iter()
+            .filter_map(|provider| self.providers.get(&provider).map(|p| (provider, p.clone())))
+            .collect();
+
+        if chain.is_empty() {
+            // No provider registered at all
+            warn!("No LLM provider available for request {}, using mock response", request_id);
+            let result = self.generate_mock_response(task_type).await?;
+            return Ok((result, LLMProvider::Mock, self.get_default_model_for_provider(LLMProvider::Mock, task_type)));
         }
 
-        // Try fallback providers
-        let config = self.config.read().await;
-        for fallback_provider in &config.fallback_providers {
-            if *fallback_provider == primary_provider {
-                continue; // Skip already tried provider
-            }
+        let mut last_error = anyhow::anyhow!("No provider attempted for request {}", request_id);
+        for (index, (provider, provider_impl)) in chain.iter().enumerate() {
+            let mut attempts = 0;
+
+            while attempts <= config.max_retries {
+                if attempts > 0 {
+                    let delay = config.retry_delay(attempts);
+                    debug!("Retrying {} for request {} in {:?} (attempt {})",
+                         provider.to_string(), request_id, delay, attempts + 1);
+                    tokio::time::sleep(delay).await;
+                    self.metrics.write().await.retries += 1;
+                }
+                attempts += 1;
 
-            if let Some(provider) = self.providers.get(fallback_provider) {
-                match operation(provider.clone()).await {
+                let outcome = match tokio::time::timeout(timeout, operation(provider_impl.clone())).await {
+                    Ok(outcome) => outcome,
+                    Err(_) => Err(anyhow::anyhow!("{} timed out after {}ms", provider.to_string(), config.request_timeout_ms)),
+                };
+
+                match outcome {
                     Ok(result) => {
-                        info!("Fallback provider {} succeeded for request {}", 
-                             fallback_provider.to_string(), request_id);
-                        return Ok(result);
+                        let model = if *provider == primary_provider {
+                            model.to_string()
+                        } else {
+                            info!("Fallback provider {} succeeded for request {}",
+                                 provider.to_string(), request_id);
+                            self.get_default_model_for_provider(*provider, task_type)
+                        };
+                        return Ok((result, *provider, model));
                     },
                     Err(e) => {
-                        debug!("Fallback provider {} failed: {}", fallback_provider.to_string(), e);
+                        warn!("Provider {} failed for request {} (attempt {}): {}",
+                             provider.to_string(), request_id, attempts, e);
+                        last_error = e;
                     }
                 }
             }
+
+            let next_provider = chain.get(index + 1).map(|(next, _)| *next);
+            self.record_failover(FailoverEvent {
+                request_id,
+                task_type,
+                from_provider: *provider,
+                to_provider: next_provider,
+                attempts,
+                error: last_error.to_string(),
+                timestamp: Utc::now(),
+            }).await;
         }
 
-        // If all providers failed, use mock response
-        warn!("All providers failed for request {}, using mock response", request_id);
-        self.generate_mock_response(task_type).await
+        Err(last_error.context(format!("All LLM providers failed for request {}", request_id)))
     }
 
     /// Generate mock response for fallback
@@ -789,6 +987,21 @@ impl LLMIntegrationManager {
         debug!("📊 Recorded failed LLM request: {}", request_id);
     }
 
+    async fn record_failover(&self, event: FailoverEvent) {
+        match event.to_provider {
+            Some(next) => warn!("🔀 Failing over from {} to {} for request {} after {} attempts",
+                 event.from_provider.to_string(), next.to_string(), event.request_id, event.attempts),
+            None => warn!("🔀 Provider chain exhausted at {} for request {}",
+                 event.from_provider.to_string(), event.request_id),
+        }
+
+        let mut metrics = self.metrics.write().await;
+        metrics.failover_events.push(event);
+        if metrics.failover_events.len() > MAX_FAILOVER_EVENTS {
+            metrics.failover_events.remove(0);
+        }
+    }
+
     /// Initialize with mock provider for development
     async fn initialize_mock_provider(mut self) -> Result<Self> {
         use crate::mock_llm_provider::create_mock_provider;
@@ -800,6 +1013,12 @@ impl LLMIntegrationManager {
         Ok(self)
     }
 
+    /// Register a provider, making it available for selection and failover
+    pub fn register_provider(&mut self, provider: LLMProvider, implementation: Arc<dyn LLMProviderTrait>) {
+        self.providers.insert(provider, implementation);
+        info!("Registered LLM provider: {}", provider.to_string());
+    }
+
     /// Get current LLM metrics
     pub async fn get_metrics(&self) -> LLMMetrics {
         self.metrics.read().await.clone()
@@ -902,4 +1121,113 @@ pub async fn create_llm_integration_manager(cost_tracker: Arc<CostTracker>) -> R
 /// Create LLM integration manager with custom config
 pub async fn create_custom_llm_integration_manager(config: LLMConfig, cost_tracker: Arc<CostTracker>) -> Result<LLMIntegrationManager> {
     LLMIntegrationManager::new(config, cost_tracker).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_llm_provider::{create_custom_mock_provider, MockLLMProvider};
+
+    /// Returns scripted completions in order, delegating everything else to the mock
+    struct ScriptedProvider {
+        completions: std::sync::Mutex<Vec<&'static str>>,
+        mock: MockLLMProvider,
+    }
+
+    #[async_trait::async_trait]
+    impl LLMProviderTrait for ScriptedProvider {
+        async fn complete_text(&self, prompt: &str, model: &str, context: &LLMContext) -> Result<LLMResponse> {
+            let mut response = self.mock.complete_text(prompt, model, context).await?;
+            response.content = self.completions.lock().unwrap().remove(0).to_string();
+            Ok(response)
+        }
+        async fn understand_intent(&self, input: &str, context: &ContextSnapshot) -> Result<IntentUnderstanding> {
+            self.mock.understand_intent(input, context).await
+        }
+        async fn create_task_plan(&self, intent: &str, context: &ContextSnapshot) -> Result<TaskPlan> {
+            self.mock.create_task_plan(intent, context).await
+        }
+        async fn extract_entities(&self, input: &str) -> Result<Vec<Entity>> {
+            self.mock.extract_entities(input).await
+        }
+        async fn generate_creative_solution(&self, problem: &str, constraints: &[String]) -> Result<CreativeSolution> {
+            self.mock.generate_creative_solution(problem, constraints).await
+        }
+        fn get_available_models(&self) -> Vec<ModelInfo> {
+            self.mock.get_available_models()
+        }
+        fn get_capabilities(&self) -> Vec<ModelCapability> {
+            self.mock.get_capabilities()
+        }
+        async fn health_check(&self) -> Result<ProviderHealth> {
+            self.mock.health_check().await
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially() {
+        let config = LLMConfig::default();
+        assert_eq!(config.retry_delay(1), Duration::from_millis(500));
+        assert_eq!(config.retry_delay(2), Duration::from_millis(1000));
+        assert_eq!(config.retry_delay(3), Duration::from_millis(2000));
+        assert_eq!(config.retry_delay(10), Duration::from_millis(8000));
+
+        assert_eq!(
+            config.failover_chain(LLMProvider::Anthropic),
+            vec![LLMProvider::Anthropic, LLMProvider::OpenAI, LLMProvider::Local]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_provider_fails_over() {
+        let config = LLMConfig {
+            primary_provider: LLMProvider::OpenAI,
+            request_timeout_ms: 50,
+            max_retries: 1,
+            retry_base_delay_ms: 1,
+            ..Default::default()
+        };
+        let mut manager = LLMIntegrationManager::new(config, Arc::new(CostTracker::new(10.0))).await.unwrap();
+        manager.register_provider(LLMProvider::OpenAI, Arc::new(create_custom_mock_provider(1000, 1.0)));
+        manager.register_provider(LLMProvider::Anthropic, Arc::new(create_custom_mock_provider(0, 1.0)));
+
+        let plan = manager.create_task_plan("search for flights", &ContextSnapshot::default()).await;
+        assert!(plan.is_ok());
+
+        let metrics = manager.get_metrics().await;
+        assert_eq!(metrics.retries, 1);
+        assert_eq!(metrics.failover_events.len(), 1);
+        assert_eq!(metrics.failover_events[0].from_provider, LLMProvider::OpenAI);
+        assert_eq!(metrics.failover_events[0].to_provider, Some(LLMProvider::Anthropic));
+        assert_eq!(metrics.failover_events[0].attempts, 2);
+        assert!(metrics.provider_performance.contains_key(&LLMProvider::Anthropic));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_structured_output_is_repaired() {
+        let config = LLMConfig {
+            primary_provider: LLMProvider::OpenAI,
+            ..Default::default()
+        };
+        let mut manager = LLMIntegrationManager::new(config, Arc::new(CostTracker::new(10.0))).await.unwrap();
+        manager.register_provider(LLMProvider::OpenAI, Arc::new(ScriptedProvider {
+            completions: std::sync::Mutex::new(vec![
+                "Sure! Here is your plan: search, then book.",
+                r#"{"task_type": "Travel", "steps": [], "estimated_total_time_minutes": 5, "confidence": 0.9}"#,
+                r#"{"task_type": "Planning", "steps": [], "estimated_total_time_minutes": 5, "confidence": 0.9}"#,
+            ]),
+            mock: MockLLMProvider::new("scripted".to_string(), 0, 1.0),
+        }));
+
+        let plan: TaskPlan = manager
+            .complete_structured("Plan a trip to Tokyo", TaskType::Planning, &ContextSnapshot::default())
+            .await
+            .unwrap();
+        assert_eq!(plan.task_type, TaskType::Planning);
+        assert_eq!(plan.estimated_total_time_minutes, 5);
+
+        let metrics = manager.get_metrics().await;
+        assert_eq!(metrics.structured_output_repairs, 2);
+        assert_eq!(metrics.successful_requests, 1);
+    }
 }
\ No newline at end of file
@@ -2,11 +2,14 @@ use anyhow::{Result, Context};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
+use std::sync::Arc;
 use std::time::Instant;
-use crate::CostTracker;
+use crate::{CostTracker, LLMCache};
+use crate::simple_memory::{InteractionRecord, SimpleMemory};
 
 // Import enhanced task understanding module
 use crate::llm_service::llm_service_enhanced::{TaskUnderstanding, MockTaskUnderstanding};
+use crate::llm_service::structured_output::{self, MAX_REPAIR_ATTEMPTS};
 
 #[derive(Debug, Clone)]
 pub struct LLMService {
@@ -14,14 +17,30 @@ pub struct LLMService {
     pub api_key: String,
     model: String,
     base_url: String,
+    /// Parsed commands of earlier identical prompts
+    cache: Option<Arc<LLMCache>>,
+    /// Past interactions recalled as few-shot examples for parsing
+    memory: Option<Arc<SimpleMemory>>,
 }
 
+/// Low temperature for consistent parsing
+const PARSE_TEMPERATURE: f32 = 0.1;
+
+/// Similar past commands shown to the model as examples
+const FEW_SHOT_EXAMPLES: usize = 3;
+
+/// Similarity a past command needs to be shown as an example
+const MIN_FEW_SHOT_SIMILARITY: f32 = 0.35;
+
 #[derive(Debug, Serialize)]
 struct OpenAIRequest {
     model: String,
     messages: Vec<Message>,
     max_tokens: u32,
     temperature: f32,
+    /// JSON mode, so the reply is always a JSON object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -30,6 +49,10 @@ struct AnthropicRequest {
     max_tokens: u32,
     messages: Vec<Message>,
     temperature: f32,
+    /// A single tool whose input schema is the command schema
+    tools: Vec<serde_json::Value>,
+    /// Forces the tool, so the command arrives as schema-shaped tool input
+    tool_choice: serde_json::Value,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -64,7 +87,11 @@ struct AnthropicResponse {
 
 #[derive(Debug, Deserialize)]
 struct AnthropicContent {
+    #[serde(default)]
     text: String,
+    /// Input of a `tool_use` block
+    #[serde(default)]
+    input: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -137,6 +164,8 @@ impl LLMService {
             api_key,
             model,
             base_url,
+            cache: None,
+            memory: None,
         }
     }
 
@@ -145,6 +174,18 @@ impl LLMService {
         self
     }
 
+    /// Reuse parsed commands for prompts already sent to the same model
+    pub fn with_cache(mut self, cache: Arc<LLMCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Ground parsing in similar commands that succeeded before
+    pub fn with_memory(mut self, memory: Arc<SimpleMemory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
     pub async fn parse_natural_command(
         &self,
         user_input: &str,
@@ -162,25 +203,57 @@ impl LLMService {
             return self.parse_command_mock(user_input, cost_tracker);
         }
         
-        let prompt = self.create_parsing_prompt(user_input);
+        let examples = self.recall_examples(user_input).await;
+        let prompt = self.create_parsing_prompt(user_input, &examples);
         let start_time = Instant::now();
         
+        if let Some(cache) = &self.cache {
+            if let Some(cached) = cache.get(&prompt, &self.model, PARSE_TEMPERATURE).await {
+                match serde_json::from_value::<ParsedCommand>(cached) {
+                    Ok(parsed_command) => {
+                        info!("LLM cache hit for command: {}", user_input);
+                        return Ok(parsed_command);
+                    }
+                    Err(e) => warn!("Ignoring unreadable cached LLM response: {}", e),
+                }
+            }
+        }
+        
         // Estimate cost before making the call
         let estimated_cost = cost_tracker.estimate_llm_operation_cost(prompt.len());
         if !cost_tracker.can_afford(estimated_cost) {
             return Err(anyhow::anyhow!("Cannot afford LLM operation: ${:.4}", estimated_cost));
         }
 
-        // Make the API call
-        let (response_text, usage_info) = self.call_llm_api(&prompt).await
-            .context("Failed to call LLM API")?;
+        // Make the API call, sending invalid responses back for repair
+        let mut request_prompt = prompt.clone();
+        let mut actual_cost = 0.0;
+        let mut repairs = 0;
+        let parsed_command = loop {
+            let (response_text, usage_info) = self.call_llm_api(&request_prompt).await
+                .context("Failed to call LLM API")?;
+            actual_cost += self.calculate_cost_from_usage(&usage_info);
 
-        // Parse the response
-        let parsed_command = self.parse_llm_response(&response_text)
-            .context("Failed to parse LLM response")?;
+            match self.parse_llm_response(&response_text) {
+                Ok(parsed_command) => break parsed_command,
+                Err(e) if repairs < MAX_REPAIR_ATTEMPTS => {
+                    repairs += 1;
+                    warn!("Invalid LLM response ({}), requesting repair {}/{}", e, repairs, MAX_REPAIR_ATTEMPTS);
+                    request_prompt = structured_output::repair_prompt::<ParsedCommand>(&prompt, &response_text, &e);
+                }
+                Err(e) => {
+                    cost_tracker.record_operation(
+                        "llm_parse".to_string(),
+                        format!("Parse command: {}", user_input),
+                        actual_cost,
+                        false,
+                    )?;
+                    return Err(e.context("Failed to parse LLM response"));
+                }
+            }
+        };
 
-        // Calculate actual cost and record operation
-        let actual_cost = self.calculate_cost_from_usage(&usage_info);
+        // Record operation
         let duration = start_time.elapsed();
         
         cost_tracker.record_operation(
@@ -197,10 +270,41 @@ impl LLMService {
             parsed_command.confidence
         );
 
+        if let Some(cache) = &self.cache {
+            if let Ok(value) = serde_json::to_value(&parsed_command) {
+                cache.insert(&prompt, &self.model, PARSE_TEMPERATURE, value).await;
+            }
+        }
+
         Ok(parsed_command)
     }
 
-    fn create_parsing_prompt(&self, user_input: &str) -> String {
+    /// Successful past commands most similar to `user_input`
+    async fn recall_examples(&self, user_input: &str) -> Vec<InteractionRecord> {
+        let Some(memory) = &self.memory else {
+            return Vec::new();
+        };
+        memory
+            .recall(user_input, FEW_SHOT_EXAMPLES * 3)
+            .await
+            .into_iter()
+            .filter(|recalled| recalled.interaction.execution_success && recalled.similarity >= MIN_FEW_SHOT_SIMILARITY)
+            .take(FEW_SHOT_EXAMPLES)
+            .map(|recalled| recalled.interaction)
+            .collect()
+    }
+
+    fn create_parsing_prompt(&self, user_input: &str, examples: &[InteractionRecord]) -> String {
+        let history = if examples.is_empty() {
+            String::new()
+        } else {
+            let lines: Vec<String> = examples
+                .iter()
+                .map(|example| format!("\"{}\" -> task type {:?}", example.user_input, example.classified_task))
+                .collect();
+            format!("\nSimilar commands this user ran successfully before:\n{}\n", lines.join("\n"))
+        };
+
         format!(
             r#"Parse this browser automation command into structured JSON. Respond ONLY with valid JSON.
 
@@ -224,9 +328,10 @@ Examples:
 "go to github and take a screenshot" -> {{"action":"navigate","url":"github.com","screenshot":true,"confidence":0.95}}
 "test google, github, stackoverflow with screenshots" -> {{"action":"test","urls":["google.com","github.com","stackoverflow.com"],"screenshot":true,"confidence":0.9}}
 "show cost report" -> {{"action":"report","confidence":0.95}}
-
+{}
 JSON:"#,
-            user_input
+            user_input,
+            history
         )
     }
 
@@ -240,6 +345,7 @@ JSON:"#,
     }
 
     async fn call_openai_api(&self, prompt: &str) -> Result<(String, UnifiedUsage)> {
+        let provider = std::env::var("LLM_PROVIDER").unwrap_or_default();
         let request = OpenAIRequest {
             model: self.model.clone(),
             messages: vec![Message {
@@ -247,11 +353,12 @@ JSON:"#,
                 content: prompt.to_string(),
             }],
             max_tokens: 500,
-            temperature: 0.1, // Low temperature for consistent parsing
+            temperature: PARSE_TEMPERATURE,
+            // Azure's 2023 API versions reject response_format
+            response_format: (provider != "azure").then(|| serde_json::json!({ "type": "json_object" })),
         };
 
         // Build request with appropriate headers for different providers
-        let provider = std::env::var("LLM_PROVIDER").unwrap_or_default();
         let mut request_builder = self
             .client
             .post(&self.base_url)
@@ -316,7 +423,12 @@ JSON:"#,
                 role: "user".to_string(),
                 content: prompt.to_string(),
             }],
-            temperature: 0.1,
+            temperature: PARSE_TEMPERATURE,
+            tools: vec![structured_output::anthropic_tool::<ParsedCommand>()],
+            tool_choice: serde_json::json!({
+                "type": "tool",
+                "name": <ParsedCommand as structured_output::StructuredOutput>::schema_name(),
+            }),
         };
 
         let response = self
@@ -350,27 +462,18 @@ JSON:"#,
             output_tokens: api_response.usage.output_tokens,
         };
 
-        let response_text = api_response.content.get(0)
-            .map(|c| c.text.clone())
+        // Prefer the forced tool's input, falling back to plain text
+        let response_text = api_response.content.iter()
+            .find_map(|c| c.input.as_ref().map(|input| input.to_string()))
+            .or_else(|| api_response.content.first().map(|c| c.text.clone()))
             .unwrap_or_default();
 
         Ok((response_text, unified_usage))
     }
 
     fn parse_llm_response(&self, response_text: &str) -> Result<ParsedCommand> {
-        // Try to extract JSON from the response
-        let json_str = if let Some(start) = response_text.find('{') {
-            if let Some(end) = response_text.rfind('}') {
-                &response_text[start..=end]
-            } else {
-                response_text
-            }
-        } else {
-            response_text
-        };
-
-        let mut parsed: ParsedCommand = serde_json::from_str(json_str)
-            .context("Failed to parse JSON response from LLM")?;
+        // Validate against the command schema, filling in omitted optional fields
+        let mut parsed: ParsedCommand = structured_output::parse(response_text)?;
 
         // Validate and clean up the parsed command
         self.validate_and_clean_command(&mut parsed)?;
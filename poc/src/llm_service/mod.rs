@@ -5,7 +5,9 @@
 
 pub mod legacy_service;
 pub mod llm_service_enhanced;
+pub mod structured_output;
 pub use legacy_service::{LLMService, ParsedCommand, CommandParams};
 pub use llm_service_enhanced::{
     TaskType, TaskUnderstanding, MockTaskUnderstanding, TaskPlan, ActionStep, Entity, IntelligentCommand
-};
\ No newline at end of file
+};
+pub use structured_output::{StructuredOutput, StructuredOutputError};
\ No newline at end of file
@@ -0,0 +1,437 @@
+// Structured LLM Output - Schema-Constrained Generation and Validation
+//
+// Describes the JSON each structured response must follow, validates what the model
+// returned against that schema before deserializing it, and builds repair prompts
+// that show the model its mistakes so a retry can fix them.
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::llm_integration::{IntentUnderstanding, TaskPlan as IntegrationTaskPlan};
+use crate::llm_service::legacy_service::ParsedCommand;
+use crate::llm_service::llm_service_enhanced::TaskPlan;
+
+/// Times an invalid response is sent back to the model for repair before giving up
+pub const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// A type the LLM produces as JSON, described by a JSON schema
+pub trait StructuredOutput: DeserializeOwned {
+    /// Name of the schema, used for tool and response format names
+    fn schema_name() -> &'static str;
+
+    /// JSON schema of the object the model must return. Properties with a
+    /// `default` may be omitted by the model.
+    fn schema() -> Value;
+}
+
+/// A response that does not match its schema
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("LLM output does not match the {schema} schema: {}", .errors.join("; "))]
+pub struct StructuredOutputError {
+    pub schema: &'static str,
+    pub errors: Vec<String>,
+}
+
+/// Parse and validate a model response as `T`
+pub fn parse<T: StructuredOutput>(response: &str) -> Result<T, StructuredOutputError> {
+    let fail = |errors: Vec<String>| StructuredOutputError {
+        schema: T::schema_name(),
+        errors,
+    };
+
+    let json = extract_json(response).ok_or_else(|| fail(vec!["no JSON object found".to_string()]))?;
+    let mut value: Value = serde_json::from_str(json)
+        .map_err(|e| fail(vec![format!("not valid JSON: {}", e)]))?;
+
+    let schema = T::schema();
+    apply_defaults(&mut value, &schema);
+    let errors = validate(&value, &schema);
+    if !errors.is_empty() {
+        return Err(fail(errors));
+    }
+
+    serde_json::from_value(value).map_err(|e| fail(vec![e.to_string()]))
+}
+
+/// The JSON object in a response, without surrounding prose or code fences
+pub fn extract_json(response: &str) -> Option<&str> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    (start < end).then(|| &response[start..=end])
+}
+
+/// Instructions appended to a prompt so the model answers with `T`'s schema
+pub fn schema_instructions<T: StructuredOutput>() -> String {
+    format!(
+        "Respond ONLY with a JSON object matching this JSON schema:\n{}",
+        serde_json::to_string_pretty(&T::schema()).unwrap_or_default()
+    )
+}
+
+/// Prompt asking the model to fix `response`, which failed with `error`
+pub fn repair_prompt<T: StructuredOutput>(
+    original_prompt: &str,
+    response: &str,
+    error: &dyn std::fmt::Display,
+) -> String {
+    format!(
+        "{}\n\nYour previous answer was:\n{}\n\nIt was rejected: {}\n\n\
+         Answer again. {}",
+        original_prompt,
+        response,
+        error,
+        schema_instructions::<T>()
+    )
+}
+
+/// Anthropic tool whose input is `T`; forcing this tool makes Claude answer with
+/// schema-shaped JSON
+pub fn anthropic_tool<T: StructuredOutput>() -> Value {
+    json!({
+        "name": T::schema_name(),
+        "description": format!("Return the {} as structured data", T::schema_name()),
+        "input_schema": T::schema(),
+    })
+}
+
+/// Fill in missing object properties that have a `default` in the schema
+pub fn apply_defaults(value: &mut Value, schema: &Value) {
+    match value {
+        Value::Object(object) => {
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                return;
+            };
+            for (name, property) in properties {
+                match object.get_mut(name) {
+                    Some(child) => apply_defaults(child, property),
+                    None => {
+                        if let Some(default) = property.get("default") {
+                            object.insert(name.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for item in items {
+                    apply_defaults(item, item_schema);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Violations of the supported schema keywords (`type`, `required`, `properties`,
+/// `items`, `enum`, `minimum`, `maximum`), each prefixed with its JSON path
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at("$", value, schema, &mut errors);
+    errors
+}
+
+fn validate_at(path: &str, value: &Value, schema: &Value, errors: &mut Vec<String>) {
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+            errors.push(format!("{}: expected {}, got {}", path, allowed.join(" or "), type_name(value)));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            errors.push(format!("{}: must be one of {}", path, Value::Array(options.clone())));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                errors.push(format!("{}: {} is below the minimum {}", path, number, minimum));
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                errors.push(format!("{}: {} is above the maximum {}", path, number, maximum));
+            }
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        errors.push(format!("{}: missing required property \"{}\"", path, name));
+                    }
+                }
+            }
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, property) in properties {
+                    if let Some(child) = object.get(name) {
+                        validate_at(&format!("{}.{}", path, name), child, property, errors);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(&format!("{}[{}]", path, index), item, item_schema, errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "number" => value.is_number(),
+        "integer" => value.is_u64() || value.is_i64(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::Null => "null",
+    }
+}
+
+/// Schema of a confidence score
+fn confidence_schema() -> Value {
+    json!({ "type": "number", "minimum": 0.0, "maximum": 1.0 })
+}
+
+fn action_step_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["step_number", "description", "action_type"],
+        "properties": {
+            "step_number": { "type": "integer", "minimum": 1 },
+            "description": { "type": "string" },
+            "action_type": { "type": "string" },
+            "parameters": { "type": "object", "default": {} },
+            "depends_on": { "type": ["integer", "null"], "default": null },
+            "optional": { "type": "boolean", "default": false }
+        }
+    })
+}
+
+impl StructuredOutput for ParsedCommand {
+    fn schema_name() -> &'static str {
+        "parsed_command"
+    }
+
+    fn schema() -> Value {
+        let optional_string = json!({ "type": ["string", "null"], "default": null });
+        let optional_count = json!({ "type": ["integer", "null"], "minimum": 0, "default": null });
+        json!({
+            "type": "object",
+            "required": ["action", "confidence"],
+            "properties": {
+                "action": { "type": "string" },
+                "url": optional_string,
+                "urls": { "type": "array", "items": { "type": "string" }, "default": [] },
+                "screenshot": { "type": "boolean", "default": false },
+                "filename": optional_string,
+                "viewport_width": optional_count,
+                "viewport_height": optional_count,
+                "viewport_only": { "type": "boolean", "default": false },
+                "retries": optional_count,
+                "timeout": optional_count,
+                // Clamped into 0..1 after parsing
+                "confidence": { "type": "number" },
+                // Filled in from the fields above after parsing
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "take_screenshot": { "type": "boolean", "default": false },
+                        "show_report": { "type": "boolean", "default": false }
+                    },
+                    "default": { "take_screenshot": false, "show_report": false }
+                },
+                "scroll_direction": optional_string,
+                "element_selector": optional_string,
+                "input_text": optional_string
+            }
+        })
+    }
+}
+
+impl StructuredOutput for TaskPlan {
+    fn schema_name() -> &'static str {
+        "task_plan"
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["title", "description", "steps", "estimated_duration"],
+            "properties": {
+                "title": { "type": "string" },
+                "description": { "type": "string" },
+                "steps": { "type": "array", "items": action_step_schema() },
+                "estimated_duration": { "type": "integer", "minimum": 0 },
+                "required_capabilities": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "default": []
+                }
+            }
+        })
+    }
+}
+
+impl StructuredOutput for IntegrationTaskPlan {
+    fn schema_name() -> &'static str {
+        "task_plan"
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["task_type", "steps", "estimated_total_time_minutes", "confidence"],
+            "properties": {
+                "task_type": task_type_schema(),
+                "steps": { "type": "array", "items": action_step_schema() },
+                "estimated_total_time_minutes": { "type": "integer", "minimum": 0 },
+                "confidence": confidence_schema(),
+                "complexity_score": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.5 },
+                "required_capabilities": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "default": []
+                }
+            }
+        })
+    }
+}
+
+impl StructuredOutput for IntentUnderstanding {
+    fn schema_name() -> &'static str {
+        "intent_understanding"
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["task_type", "confidence", "intent_description"],
+            "properties": {
+                "task_type": task_type_schema(),
+                "confidence": confidence_schema(),
+                "entities": {
+                    "type": "array",
+                    "default": [],
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "entity_type", "value"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "entity_type": { "type": "string" },
+                            "value": { "type": "string" },
+                            "confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.5 },
+                            "context": { "type": ["string", "null"], "default": null }
+                        }
+                    }
+                },
+                "intent_description": { "type": "string" },
+                "complexity_score": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.5 },
+                "reasoning": { "type": ["string", "null"], "default": null }
+            }
+        })
+    }
+}
+
+fn task_type_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": [
+            "Navigation", "Screenshot", "Search", "Planning", "Analysis", "Execution",
+            "Extraction", "Monitoring", "Testing", "Reporting", "Unknown"
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm_service::llm_service_enhanced::TaskType;
+
+    #[test]
+    fn test_prompt_examples_parse_with_defaults() {
+        // The parsing prompt's own example omits most fields
+        let command: ParsedCommand = parse(
+            "```json\n{\"action\":\"navigate\",\"url\":\"google.com\",\"screenshot\":false,\"confidence\":0.9}\n```",
+        )
+        .unwrap();
+        assert_eq!(command.action, "navigate");
+        assert_eq!(command.url.as_deref(), Some("google.com"));
+        assert!(command.urls.is_empty());
+        assert!(!command.parameters.show_report);
+    }
+
+    #[test]
+    fn test_violations_are_reported_with_paths() {
+        let error = parse::<ParsedCommand>(r#"{"action": "test", "urls": ["a.com", 3], "screenshot": "yes"}"#)
+            .unwrap_err();
+        assert_eq!(error.errors, vec![
+            "$: missing required property \"confidence\"".to_string(),
+            "$.urls[1]: expected string, got number".to_string(),
+            "$.screenshot: expected boolean, got string".to_string(),
+        ]);
+
+        let error = parse::<IntentUnderstanding>(
+            r#"{"task_type": "Search", "confidence": 1.5, "intent_description": "Find flights"}"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.errors, vec!["$.confidence: 1.5 is above the maximum 1".to_string()]);
+
+        let error = parse::<IntentUnderstanding>(r#"{"task_type": "Shopping", "confidence": 0.8}"#).unwrap_err();
+        assert_eq!(error.errors.len(), 2);
+        assert!(error.errors[0].starts_with("$: missing required property \"intent_description\""));
+        assert!(error.errors[1].starts_with("$.task_type: must be one of"));
+
+        assert!(parse::<TaskPlan>("I could not make a plan").is_err());
+    }
+
+    #[test]
+    fn test_integration_types_parse_and_repair_prompts_show_errors() {
+        let understanding: IntentUnderstanding = parse(
+            r#"{"task_type": "Search", "confidence": 0.8, "intent_description": "Find flights",
+                "entities": [{"name": "destination", "entity_type": "location", "value": "Tokyo"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(understanding.task_type, TaskType::Search);
+        assert_eq!(understanding.entities[0].confidence, 0.5);
+
+        let plan: IntegrationTaskPlan = parse(
+            r#"{"task_type": "Planning", "estimated_total_time_minutes": 10, "confidence": 0.7,
+                "steps": [{"step_number": 1, "description": "Search", "action_type": "search"}]}"#,
+        )
+        .unwrap();
+        assert!(!plan.steps[0].optional);
+
+        let error = parse::<IntegrationTaskPlan>(r#"{"steps": []}"#).unwrap_err();
+        let prompt = repair_prompt::<IntegrationTaskPlan>("Plan a trip", r#"{"steps": []}"#, &error);
+        assert!(prompt.contains("missing required property \"task_type\""));
+        assert!(prompt.contains("\"estimated_total_time_minutes\""));
+    }
+}
@@ -10,6 +10,7 @@ use rainbow_poc::{
     ConversationContext, ExecutionResult,
 };
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::info;
@@ -48,6 +49,54 @@ enum Commands {
         #[arg(short, long)]
         screenshot: bool,
     },
+
+    /// Export successful command history as a JSONL fine-tuning dataset
+    ExportFinetune {
+        /// File to write the dataset to
+        #[arg(short, long, default_value = "finetune.jsonl")]
+        output: PathBuf,
+
+        /// "chat" for chat fine-tuning APIs, "raw" for instruction/plan/outcome records
+        #[arg(short, long, default_value = "chat")]
+        format: String,
+
+        /// Also export commands whose execution failed
+        #[arg(long)]
+        include_failed: bool,
+
+        /// Keep emails, phone numbers, card numbers and credentials
+        #[arg(long)]
+        no_scrub: bool,
+    },
+    /// Convert a Selenium IDE project (.side) or Playwright test script into workflow YAML files
+    ImportWorkflow {
+        /// Script to convert
+        input: PathBuf,
+
+        /// Directory to write one workflow YAML per test to
+        #[arg(short, long, default_value = "workflows/imported")]
+        output: PathBuf,
+    },
+    /// Run a workflow once per row of a CSV or JSON dataset
+    RunBatch {
+        /// Workflow YAML file
+        workflow: PathBuf,
+
+        /// Dataset whose rows are passed to the workflow as inputs (.csv or .json)
+        data: PathBuf,
+
+        /// Report to write; .csv for one line per row, otherwise JSON
+        #[arg(short, long, default_value = "batch_results.csv")]
+        output: PathBuf,
+
+        /// Skip the remaining rows once one fails
+        #[arg(long)]
+        stop_on_failure: bool,
+
+        /// Run at most this many rows
+        #[arg(long)]
+        limit: Option<usize>,
+    },
 }
 
 #[derive(Clone)]
@@ -81,6 +130,15 @@ async fn main() -> anyhow::Result<()> {
         Some(Commands::Navigate { url, screenshot }) => {
             navigate_to_url(&url, screenshot).await?;
         }
+        Some(Commands::ExportFinetune { output, format, include_failed, no_scrub }) => {
+            export_finetune(&output, &format, include_failed, no_scrub)?;
+        }
+        Some(Commands::ImportWorkflow { input, output }) => {
+            import_workflow(&input, &output)?;
+        }
+        Some(Commands::RunBatch { workflow, data, output, stop_on_failure, limit }) => {
+            run_workflow_batch(&workflow, &data, &output, stop_on_failure, limit).await?;
+        }
         None => {
             // Default to serving if no command provided
             serve_api(3000, config).await?;
@@ -184,6 +242,77 @@ async fn navigate_endpoint(
     }
 }
 
+fn export_finetune(output: &std::path::Path, format: &str, include_failed: bool, no_scrub: bool) -> anyhow::Result<()> {
+    use rainbow_poc::{ExportOptions, FineTuneExporter, FineTuneFormat};
+
+    let format = match format {
+        "chat" => FineTuneFormat::Chat,
+        "raw" => FineTuneFormat::Raw,
+        other => anyhow::bail!("Unknown dataset format '{}', expected 'chat' or 'raw'", other),
+    };
+    let exporter = FineTuneExporter::new(ExportOptions {
+        format,
+        include_failed,
+        scrub_pii: !no_scrub,
+        ..Default::default()
+    });
+
+    let context = ConversationContext::load_from_file()?;
+    let summary = exporter.export_to_file(&context.command_history, output)?;
+    println!(
+        "Wrote {} examples to {} ({} failed commands skipped, {} values redacted)",
+        summary.examples,
+        output.display(),
+        summary.skipped_failed,
+        summary.redactions
+    );
+    Ok(())
+}
+
+fn import_workflow(input: &std::path::Path, output: &std::path::Path) -> anyhow::Result<()> {
+    use rainbow_poc::{import_script, ImportedWorkflow, WorkflowLibrary};
+
+    let content = std::fs::read_to_string(input)?;
+    let imported = import_script(&content, input.file_name().and_then(|name| name.to_str()))?;
+    std::fs::create_dir_all(output)?;
+
+    for ImportedWorkflow { workflow, warnings } in &imported {
+        let path = output.join(format!("{}.yaml", WorkflowLibrary::id_from_name(&workflow.name)));
+        std::fs::write(&path, workflow.to_yaml()?)?;
+        println!("Wrote '{}' ({} steps) to {}", workflow.name, workflow.steps.len(), path.display());
+        for warning in warnings {
+            println!("  warning: {}", warning);
+        }
+    }
+    Ok(())
+}
+
+async fn run_workflow_batch(workflow: &std::path::Path, data: &std::path::Path, output: &std::path::Path, stop_on_failure: bool, limit: Option<usize>) -> anyhow::Result<()> {
+    use rainbow_poc::{run_batch, BatchOptions, Dataset, Workflow, WorkflowEngine};
+
+    let workflow = Workflow::from_yaml(&std::fs::read_to_string(workflow)?)?;
+    let dataset = Dataset::load(data)?;
+    let mut engine = WorkflowEngine::new_simple();
+    let report = run_batch(&mut engine, &workflow, &dataset, &BatchOptions { stop_on_failure, limit }).await;
+    engine.cleanup().await?;
+
+    if output.extension().is_some_and(|extension| extension == "csv") {
+        std::fs::write(output, report.to_csv())?;
+    } else {
+        std::fs::write(output, serde_json::to_string_pretty(&report)?)?;
+    }
+    println!(
+        "Ran '{}' for {} rows: {} succeeded, {} failed, {} skipped; report written to {}",
+        report.workflow,
+        report.total,
+        report.succeeded,
+        report.failed,
+        report.skipped,
+        output.display()
+    );
+    Ok(())
+}
+
 async fn test_browser() -> anyhow::Result<()> {
     info!("Testing browser connectivity...");
     
@@ -8,6 +8,7 @@ use std::time::{Instant, Duration};
 use thirtyfour::{WebDriver, WebElement, By};
 use crate::perception_mvp::{PerceivedElement, ElementType, PageType};
 use crate::perception_mvp::semantic::{SemanticAnalyzer, SemanticElement, SemanticForm};
+use crate::perception_mvp::selector_generator::SelectorGenerator;
 
 /// Context-aware element selector that maintains interaction history and state
 pub struct ContextAwareSelector {
@@ -742,12 +743,7 @@ impl ContextAwareSelector {
         let is_enabled = element.is_enabled().await.unwrap_or(false);
         
         // Generate selector
-        let id = element.attr("id").await?.unwrap_or_default();
-        let selector = if !id.is_empty() {
-            format!("#{}", id)
-        } else {
-            format!("{}:nth-of-type(1)", element.tag_name().await?)
-        };
+        let generated = SelectorGenerator::new(self.driver.clone()).generate(&element).await?;
 
         // Collect attributes
         let mut attributes = HashMap::new();
@@ -756,9 +752,10 @@ impl ContextAwareSelector {
                 attributes.insert(attr.to_string(), value);
             }
         }
+        attributes.insert("xpath".to_string(), generated.xpath);
 
         Ok(PerceivedElement {
-            selector,
+            selector: generated.css,
             text,
             element_type,
             clickable: is_enabled && is_displayed,
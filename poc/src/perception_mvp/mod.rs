@@ -13,6 +13,7 @@ pub mod semantic;
 pub mod context_aware;
 pub mod smart_forms;
 pub mod dynamic_handler;
+pub mod selector_generator;
 // pub mod testing_framework; // Module file doesn't exist
 
 /// Core perception engine that understands web pages
@@ -450,15 +451,8 @@ impl PerceptionEngineMVP {
         let is_displayed = element.is_displayed().await.unwrap_or(false);
         let is_enabled = element.is_enabled().await.unwrap_or(false);
         
-        // Get CSS selector (simplified - you might want a better selector generator)
-        let id = element.attr("id").await?.unwrap_or_default();
-        let selector = if !id.is_empty() {
-            format!("#{}", id)
-        } else {
-            // Generate a unique selector
-            // This is simplified - in production, you'd want a robust selector generator
-            format!("{}:nth-of-type(1)", element.tag_name().await?)
-        };
+        // Minimal unique CSS anchored on stable attributes
+        let generated = SelectorGenerator::new(self.driver.clone()).generate(&element).await?;
 
         // Collect attributes
         let mut attributes = HashMap::new();
@@ -467,9 +461,10 @@ impl PerceptionEngineMVP {
                 attributes.insert(attr.to_string(), value);
             }
         }
+        attributes.insert("xpath".to_string(), generated.xpath);
 
         Ok(PerceivedElement {
-            selector,
+            selector: generated.css,
             text,
             element_type: elem_type,
             clickable: is_enabled && is_displayed,
@@ -533,4 +528,5 @@ mod tests {
 // Re-export types from sub-modules for easier access
 pub use smart_forms::{SmartFormHandler, SmartFormAnalysis, FormType, FillResult, FormProfile, PersonalInfo, ContactInfo, AddressInfo};
 pub use dynamic_handler::{DynamicContentResult, ModalAction, WaitCondition};
-pub use integration::{EnhancedPerceptionEngine};
\ No newline at end of file
+pub use integration::{EnhancedPerceptionEngine};
+pub use selector_generator::{GeneratedSelector, SelectorGenerator};
\ No newline at end of file
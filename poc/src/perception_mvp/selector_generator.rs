@@ -0,0 +1,357 @@
+// Selector Generator - Builds durable selectors for perceived elements
+// Prefers short CSS anchored on attributes that survive redesigns and reloads
+// (test ids, ids, names, labels), verifies each candidate matches exactly the
+// element in the live page, and keeps an XPath as a fallback
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use thirtyfour::{WebDriver, WebElement};
+
+/// How many ancestors are considered when anchoring a selector
+const MAX_DEPTH: usize = 6;
+
+/// Attributes that identify an element on purpose, most stable first
+const ANCHOR_ATTRIBUTES: &[&str] = &[
+    "data-testid",
+    "data-test-id",
+    "data-test",
+    "data-qa",
+    "data-cy",
+    "name",
+    "aria-label",
+    "placeholder",
+    "title",
+    "alt",
+    "for",
+    "type",
+    "role",
+    "href",
+];
+
+/// Describes the element and its ancestors, element first
+const DESCRIBE_SCRIPT: &str = r#"
+    const chain = [];
+    for (let node = arguments[0]; node && node.nodeType === 1 && chain.length < arguments[1]; node = node.parentElement) {
+        const attributes = {};
+        for (const name of arguments[2]) {
+            const value = node.getAttribute(name);
+            if (value !== null && value.length <= 80) attributes[name] = value;
+        }
+        const siblings = node.parentElement
+            ? Array.from(node.parentElement.children).filter(s => s.tagName === node.tagName)
+            : [node];
+        chain.push({
+            tag: node.tagName.toLowerCase(),
+            id: node.getAttribute('id') || '',
+            classes: typeof node.className === 'string' ? node.className.split(/\s+/).filter(c => c) : [],
+            attributes,
+            index: siblings.indexOf(node) + 1,
+            of_type: siblings.length
+        });
+        if (node === document.body) break;
+    }
+    return chain;
+"#;
+
+/// Whether each candidate matches the element and nothing else
+const VERIFY_SCRIPT: &str = r#"
+    const el = arguments[0];
+    return arguments[1].map(selector => {
+        try {
+            const matches = document.querySelectorAll(selector);
+            return matches.length === 1 && matches[0] === el;
+        } catch (e) {
+            return false;
+        }
+    });
+"#;
+
+/// One element in the chain from the target up to `body`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeDescription {
+    pub tag: String,
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub classes: Vec<String>,
+    #[serde(default)]
+    pub attributes: std::collections::BTreeMap<String, String>,
+    /// 1-based position among siblings with the same tag
+    #[serde(default = "default_index")]
+    pub index: usize,
+    #[serde(default = "default_index")]
+    pub of_type: usize,
+}
+
+fn default_index() -> usize {
+    1
+}
+
+/// Selector chosen for an element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedSelector {
+    /// Shortest verified CSS selector, or the positional path when none verified
+    pub css: String,
+    pub xpath: String,
+    /// What the CSS is anchored on: "id", an attribute name, "class", "ancestor" or "path"
+    pub strategy: String,
+    /// Whether `css` was verified to match only this element
+    pub unique: bool,
+}
+
+impl GeneratedSelector {
+    /// Stability of the anchor, from 1.0 for test ids down to 0.3 for positions
+    pub fn durability(&self) -> f32 {
+        match self.strategy.as_str() {
+            "data-testid" | "data-test-id" | "data-test" | "data-qa" | "data-cy" => 1.0,
+            "id" => 0.9,
+            "name" | "aria-label" | "for" => 0.8,
+            "placeholder" | "title" | "alt" | "role" | "type" | "href" => 0.7,
+            "class" => 0.6,
+            "ancestor" => 0.5,
+            _ => 0.3,
+        }
+    }
+}
+
+/// Generates durable selectors for elements of the driver's current page
+pub struct SelectorGenerator {
+    driver: WebDriver,
+}
+
+impl SelectorGenerator {
+    pub fn new(driver: WebDriver) -> Self {
+        Self { driver }
+    }
+
+    /// Minimal unique selector for `element`
+    pub async fn generate(&self, element: &WebElement) -> Result<GeneratedSelector> {
+        let attributes = serde_json::to_value(ANCHOR_ATTRIBUTES)?;
+        let ret = self.driver
+            .execute(DESCRIBE_SCRIPT, vec![element.to_json()?, serde_json::json!(MAX_DEPTH), attributes])
+            .await
+            .context("Failed to describe element")?;
+        let chain: Vec<NodeDescription> = serde_json::from_value(ret.json().clone())
+            .context("Unexpected element description")?;
+        let xpath = xpath_for(&chain);
+
+        // Verify every candidate in one round trip and keep the first unique one
+        let candidates = candidates_for(&chain);
+        let selectors: Vec<&str> = candidates.iter().map(|(selector, _)| selector.as_str()).collect();
+        let ret = self.driver
+            .execute(VERIFY_SCRIPT, vec![element.to_json()?, serde_json::to_value(&selectors)?])
+            .await
+            .context("Failed to verify selectors")?;
+        let unique: Vec<bool> = serde_json::from_value(ret.json().clone()).unwrap_or_default();
+
+        if let Some((css, strategy)) = candidates
+            .iter()
+            .zip(unique.iter())
+            .find(|(_, unique)| **unique)
+            .map(|(candidate, _)| candidate.clone())
+        {
+            return Ok(GeneratedSelector { css, xpath, strategy, unique: true });
+        }
+
+        Ok(GeneratedSelector {
+            css: positional_path(&chain),
+            xpath,
+            strategy: "path".to_string(),
+            unique: false,
+        })
+    }
+}
+
+/// CSS candidates for the first element of `chain`, most durable and shortest first
+pub fn candidates_for(chain: &[NodeDescription]) -> Vec<(String, String)> {
+    let Some(target) = chain.first() else {
+        return Vec::new();
+    };
+    let mut candidates = Vec::new();
+    let own = own_selectors(target);
+    candidates.extend(own.iter().cloned());
+
+    // Anchor on the nearest ancestor that identifies itself
+    let anchor = chain
+        .iter()
+        .skip(1)
+        .find_map(|ancestor| own_selectors(ancestor).into_iter().next());
+    if let Some((anchor, _)) = anchor {
+        let tails = own.iter().map(|(selector, _)| selector.clone()).chain(std::iter::once(nth_of_type(target)));
+        for tail in tails {
+            candidates.push((format!("{} {}", anchor, tail), "ancestor".to_string()));
+        }
+    }
+
+    candidates.push((positional_path(chain), "path".to_string()));
+    candidates
+}
+
+/// Selectors built from the element's own id, attributes and classes
+fn own_selectors(node: &NodeDescription) -> Vec<(String, String)> {
+    let mut selectors = Vec::new();
+    for name in ANCHOR_ATTRIBUTES.iter().take(5) {
+        if let Some(value) = node.attributes.get(*name) {
+            selectors.push((format!("[{}=\"{}\"]", name, css_string(value)), name.to_string()));
+        }
+    }
+    if is_stable_token(&node.id) {
+        selectors.push((format!("#{}", css_ident(&node.id)), "id".to_string()));
+    }
+    for name in ANCHOR_ATTRIBUTES.iter().skip(5) {
+        if let Some(value) = node.attributes.get(*name) {
+            if value.is_empty() || (*name == "href" && (value.starts_with("javascript:") || value == "#")) {
+                continue;
+            }
+            selectors.push((
+                format!("{}[{}=\"{}\"]", node.tag, name, css_string(value)),
+                name.to_string(),
+            ));
+        }
+    }
+    let classes: Vec<String> = node
+        .classes
+        .iter()
+        .filter(|c| is_stable_token(c))
+        .take(2)
+        .map(|c| format!(".{}", css_ident(c)))
+        .collect();
+    if !classes.is_empty() {
+        selectors.push((format!("{}{}", node.tag, classes.join("")), "class".to_string()));
+    }
+    selectors
+}
+
+fn nth_of_type(node: &NodeDescription) -> String {
+    if node.of_type > 1 {
+        format!("{}:nth-of-type({})", node.tag, node.index)
+    } else {
+        node.tag.clone()
+    }
+}
+
+/// Child path from the nearest stable id, or from `body`
+pub fn positional_path(chain: &[NodeDescription]) -> String {
+    let mut parts = Vec::new();
+    for (depth, node) in chain.iter().enumerate() {
+        if depth > 0 && is_stable_token(&node.id) {
+            parts.push(format!("#{}", css_ident(&node.id)));
+            break;
+        }
+        parts.push(nth_of_type(node));
+    }
+    parts.reverse();
+    parts.join(" > ")
+}
+
+/// XPath of the element, anchored on the nearest stable id
+pub fn xpath_for(chain: &[NodeDescription]) -> String {
+    let mut steps = Vec::new();
+    for (depth, node) in chain.iter().enumerate() {
+        if depth > 0 && is_stable_token(&node.id) && !node.id.contains('\'') {
+            steps.reverse();
+            return format!("//*[@id='{}']/{}", node.id, steps.join("/"));
+        }
+        steps.push(format!("{}[{}]", node.tag, node.index));
+        if node.tag == "body" {
+            break;
+        }
+    }
+    steps.reverse();
+    if steps.first().is_some_and(|s| s.starts_with("body")) {
+        format!("/html/{}", steps.join("/"))
+    } else {
+        format!("//{}", steps.join("/"))
+    }
+}
+
+/// Whether an id or class looks hand-written rather than generated by a framework
+/// or build step ("ember123", "css-1x2y3z", "sc-AxjAm", GUIDs)
+pub fn is_stable_token(token: &str) -> bool {
+    if token.is_empty() || token.len() > 40 {
+        return false;
+    }
+    let digits = token.chars().filter(|c| c.is_ascii_digit()).count();
+    if digits >= 3 || digits * 3 > token.len() {
+        return false;
+    }
+    let generated_prefixes = ["css-", "sc-", "jsx-", "ember", "react-", "ng-", "svelte-", "emotion-", "_"];
+    if generated_prefixes.iter().any(|p| token.starts_with(p)) {
+        return false;
+    }
+    // Hashed names such as "a1b2c3" or "xYz9Qw" mix cases and digits without separators
+    let mixed = token.chars().any(|c| c.is_ascii_uppercase())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && digits > 0
+        && !token.contains(['-', '_']);
+    !mixed
+}
+
+/// Escape an identifier for use after `#` or `.`
+fn css_ident(ident: &str) -> String {
+    let mut escaped = String::new();
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_ascii_alphanumeric() && !(i == 0 && c.is_ascii_digit()) || c == '-' || c == '_' || !c.is_ascii() {
+            escaped.push(c);
+        } else if c.is_ascii_digit() {
+            escaped.push_str(&format!("\\{:x} ", c as u32));
+        } else {
+            escaped.push('\\');
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Escape a value for a double-quoted attribute selector
+fn css_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(tag: &str, id: &str, index: usize, of_type: usize) -> NodeDescription {
+        NodeDescription {
+            tag: tag.to_string(),
+            id: id.to_string(),
+            index,
+            of_type,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stable_tokens() {
+        assert!(is_stable_token("search-input"));
+        assert!(is_stable_token("btn_primary"));
+        assert!(!is_stable_token("ember1234"));
+        assert!(!is_stable_token("css-1x2y3z"));
+        assert!(!is_stable_token("xYz9Qw"));
+        assert!(!is_stable_token(""));
+    }
+
+    #[test]
+    fn test_candidates_prefer_test_ids_then_ids() {
+        let mut button = node("button", "submit", 2, 3);
+        button.attributes.insert("data-testid".to_string(), "checkout".to_string());
+        button.classes = vec!["btn".to_string(), "css-1k2j3h".to_string()];
+        let candidates = candidates_for(&[button, node("form", "", 1, 1), node("body", "", 1, 1)]);
+
+        assert_eq!(candidates[0], ("[data-testid=\"checkout\"]".to_string(), "data-testid".to_string()));
+        assert_eq!(candidates[1], ("#submit".to_string(), "id".to_string()));
+        assert!(candidates.iter().any(|(css, _)| css == "button.btn"));
+        assert_eq!(candidates.last().unwrap().0, "body > form > button:nth-of-type(2)");
+    }
+
+    #[test]
+    fn test_paths_anchor_on_stable_ancestor_ids() {
+        let chain = [node("li", "", 3, 5), node("ul", "", 1, 1), node("nav", "main-nav", 1, 1), node("body", "", 1, 1)];
+        assert_eq!(positional_path(&chain), "#main-nav > ul > li:nth-of-type(3)");
+        assert_eq!(xpath_for(&chain), "//*[@id='main-nav']/ul[1]/li[3]");
+
+        let chain = [node("div", "", 2, 2), node("body", "", 1, 1)];
+        assert_eq!(xpath_for(&chain), "/html/body[1]/div[2]");
+    }
+}
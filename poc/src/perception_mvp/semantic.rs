@@ -6,6 +6,7 @@ use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use thirtyfour::{WebDriver, WebElement, By};
 use regex::Regex;
+use super::selector_generator::SelectorGenerator;
 
 /// Semantic analyzer that understands web page structure and element purposes
 pub struct SemanticAnalyzer {
@@ -438,29 +439,8 @@ impl SemanticAnalyzer {
 
     /// Generate a unique CSS selector for an element
     async fn generate_selector(&self, element: &WebElement) -> Result<String> {
-        // Try ID first
-        if let Ok(Some(id)) = element.attr("id").await {
-            if !id.is_empty() {
-                return Ok(format!("#{}", id));
-            }
-        }
-
-        // Try unique class combination
-        if let Ok(Some(classes)) = element.attr("class").await {
-            let class_selector = classes
-                .split_whitespace()
-                .map(|c| format!(".{}", c))
-                .collect::<Vec<_>>()
-                .join("");
-            
-            if !class_selector.is_empty() {
-                return Ok(class_selector);
-            }
-        }
-
-        // Fall back to tag with position
-        let tag_name = element.tag_name().await.unwrap_or_default();
-        Ok(format!("{}:nth-of-type(1)", tag_name))
+        let generated = SelectorGenerator::new(self.driver.clone()).generate(element).await?;
+        Ok(generated.css)
     }
 
     /// Determine element's purpose based on its role and context
@@ -6,6 +6,7 @@ use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use thirtyfour::{WebDriver, WebElement, By};
 use regex::Regex;
+use super::selector_generator::SelectorGenerator;
 
 /// User form profile for auto-filling
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -547,13 +548,7 @@ impl SmartFormHandler {
         let current_value = input.attr("value").await?.unwrap_or_default();
 
         // Generate selector
-        let selector = if !id.is_empty() {
-            format!("#{}", id)
-        } else if !name.is_empty() {
-            format!("input[name='{}']", name)
-        } else {
-            format!("{}:nth-of-type(1)", tag_name)
-        };
+        let selector = SelectorGenerator::new(self.driver.clone()).generate(input).await?.css;
 
         // Extract label
         let label = self.extract_field_label(input).await.unwrap_or_default();
@@ -0,0 +1,351 @@
+//! Workflow Run Reports
+//!
+//! Renders one self-contained HTML page per workflow run: the step timeline with
+//! timings and errors, screenshots inlined as data URIs and tables of the data the
+//! run extracted, so a run can be reviewed or shared without the server.
+
+use anyhow::{Context as AnyhowContext, Result};
+use base64::Engine;
+use chrono::Utc;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::workflow::{Artifact, ArtifactKind, ArtifactStore, Workflow, WorkflowResult};
+
+/// Screenshots above this size are linked instead of inlined, to keep reports small
+const MAX_INLINE_SCREENSHOT_BYTES: usize = 5 * 1024 * 1024;
+
+const STYLE: &str = "\
+body{font-family:-apple-system,'Segoe UI',Roboto,sans-serif;margin:0;padding:24px;background:#f5f6f8;color:#1f2328}\
+h1{margin:0 0 4px}h2{margin:32px 0 12px;font-size:1.2em}\
+.meta{color:#656d76;margin-bottom:16px}\
+.badge{display:inline-block;padding:2px 10px;border-radius:12px;font-size:.85em;font-weight:600;color:#fff}\
+.ok{background:#1a7f37}.failed{background:#cf222e}\
+.cards{display:flex;gap:12px;flex-wrap:wrap}\
+.card{background:#fff;border:1px solid #d0d7de;border-radius:8px;padding:12px 16px;min-width:120px}\
+.card b{display:block;font-size:1.4em}\
+table{border-collapse:collapse;width:100%;background:#fff;margin-bottom:16px}\
+th,td{border:1px solid #d0d7de;padding:6px 10px;text-align:left;vertical-align:top;font-size:.9em}\
+th{background:#f0f2f4}tr.failed td{background:#ffebe9}\
+.error{color:#cf222e;white-space:pre-wrap}\
+.bar{background:#54aeff;height:8px;border-radius:4px;min-width:2px}\
+figure{display:inline-block;margin:0 16px 16px 0;background:#fff;border:1px solid #d0d7de;border-radius:8px;padding:8px;max-width:480px}\
+figure img{max-width:100%}figcaption{font-size:.85em;color:#656d76}\
+pre{margin:0;white-space:pre-wrap}";
+
+/// Render the HTML report of a run. Screenshots are read from `artifacts` and
+/// inlined; without a store, or for runs without an id, they are only listed.
+pub fn render_html(workflow: &Workflow, result: &WorkflowResult, artifacts: Option<&ArtifactStore>) -> String {
+    let mut html = String::new();
+    let status = if result.success { ("ok", "Succeeded") } else { ("failed", "Failed") };
+
+    let _ = write!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{} run report</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape(&workflow.name), STYLE);
+    let _ = writeln!(html, "<h1>{} <span class=\"badge {}\">{}</span></h1>", escape(&workflow.name), status.0, status.1);
+    let mut meta = Vec::new();
+    if let Some(run_id) = result.run_id {
+        meta.push(format!("Run {}", run_id));
+    }
+    if let Some(version) = &workflow.version {
+        meta.push(format!("version {}", escape(version)));
+    }
+    meta.push(format!("generated {}", Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
+    let _ = writeln!(html, "<div class=\"meta\">{}</div>", meta.join(" · "));
+    if let Some(description) = &workflow.description {
+        let _ = writeln!(html, "<p>{}</p>", escape(description));
+    }
+
+    let _ = writeln!(html, "<div class=\"cards\">\
+        <div class=\"card\">Duration<b>{}</b></div>\
+        <div class=\"card\">Steps run<b>{}</b></div>\
+        <div class=\"card\">Steps failed<b>{}</b></div>\
+        <div class=\"card\">Artifacts<b>{}</b></div>\
+        <div class=\"card\">Cost<b>${:.4}</b></div></div>",
+        format_duration(result.duration_ms), result.steps_executed, result.steps_failed, result.artifacts.len(), result.cost);
+
+    render_steps(&mut html, result);
+    render_errors(&mut html, result);
+    render_data(&mut html, workflow, result);
+    render_artifacts(&mut html, result, artifacts);
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_steps(html: &mut String, result: &WorkflowResult) {
+    html.push_str("<h2>Steps</h2>\n");
+    if result.execution_log.is_empty() {
+        html.push_str("<p>No steps ran.</p>\n");
+        return;
+    }
+
+    let longest = result.execution_log.iter().map(|entry| entry.duration_ms).max().unwrap_or(0).max(1);
+    html.push_str("<table>\n<tr><th>#</th><th>Step</th><th>Action</th><th>Status</th><th>Started</th><th>Duration</th><th></th></tr>\n");
+    for (i, entry) in result.execution_log.iter().enumerate() {
+        let _ = writeln!(html, "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td style=\"width:20%\"><div class=\"bar\" style=\"width:{}%\"></div></td></tr>",
+            if entry.success { "" } else { " class=\"failed\"" },
+            i + 1,
+            escape(&entry.step_name),
+            escape(&entry.action),
+            if entry.success { "✓ ok" } else { "✗ failed" },
+            entry.timestamp.format("%H:%M:%S%.3f"),
+            format_duration(entry.duration_ms),
+            entry.duration_ms * 100 / longest);
+    }
+    html.push_str("</table>\n");
+}
+
+fn render_errors(html: &mut String, result: &WorkflowResult) {
+    let failed: Vec<_> = result.execution_log.iter().filter(|entry| entry.error.is_some()).collect();
+    if failed.is_empty() {
+        return;
+    }
+
+    html.push_str("<h2>Errors</h2>\n<table>\n<tr><th>Step</th><th>Error</th></tr>\n");
+    for entry in failed {
+        let _ = writeln!(html, "<tr><td>{}</td><td class=\"error\">{}</td></tr>",
+            escape(&entry.step_name), escape(entry.error.as_deref().unwrap_or_default()));
+    }
+    html.push_str("</table>\n");
+}
+
+fn render_data(html: &mut String, workflow: &Workflow, result: &WorkflowResult) {
+    let data = result.extracted_data(workflow);
+    if data.is_empty() {
+        return;
+    }
+
+    html.push_str("<h2>Extracted data</h2>\n");
+    let mut names: Vec<&String> = data.keys().collect();
+    names.sort();
+    let (tables, values): (Vec<&String>, Vec<&String>) = names.into_iter()
+        .partition(|name| matches!(data[*name], serde_json::Value::Array(_) | serde_json::Value::Object(_)));
+
+    if !values.is_empty() {
+        html.push_str("<table>\n<tr><th>Variable</th><th>Value</th></tr>\n");
+        for name in values {
+            let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(name), cell(&data[name]));
+        }
+        html.push_str("</table>\n");
+    }
+    for name in tables {
+        let _ = writeln!(html, "<h3>{}</h3>", escape(name));
+        render_table(html, &data[name]);
+    }
+}
+
+/// Lists of objects become one row per item and a column per field; other lists
+/// one row per item, and objects one row per field
+fn render_table(html: &mut String, value: &serde_json::Value) {
+    match value {
+        serde_json::Value::Array(items) if !items.is_empty() && items.iter().all(|item| item.is_object()) => {
+            let mut columns = Vec::new();
+            let mut seen = BTreeSet::new();
+            for item in items.iter().filter_map(|item| item.as_object()) {
+                for key in item.keys() {
+                    if seen.insert(key.as_str()) {
+                        columns.push(key.as_str());
+                    }
+                }
+            }
+            html.push_str("<table>\n<tr><th>#</th>");
+            for column in &columns {
+                let _ = write!(html, "<th>{}</th>", escape(column));
+            }
+            html.push_str("</tr>\n");
+            for (i, item) in items.iter().enumerate() {
+                let _ = write!(html, "<tr><td>{}</td>", i + 1);
+                for column in &columns {
+                    let _ = write!(html, "<td>{}</td>", item.get(*column).map(cell).unwrap_or_default());
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n");
+        }
+        serde_json::Value::Array(items) => {
+            html.push_str("<table>\n<tr><th>#</th><th>Value</th></tr>\n");
+            for (i, item) in items.iter().enumerate() {
+                let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", i + 1, cell(item));
+            }
+            html.push_str("</table>\n");
+        }
+        serde_json::Value::Object(fields) => {
+            html.push_str("<table>\n<tr><th>Field</th><th>Value</th></tr>\n");
+            for (key, field) in fields {
+                let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(key), cell(field));
+            }
+            html.push_str("</table>\n");
+        }
+        other => {
+            let _ = writeln!(html, "<p>{}</p>", cell(other));
+        }
+    }
+}
+
+fn render_artifacts(html: &mut String, result: &WorkflowResult, store: Option<&ArtifactStore>) {
+    if result.artifacts.is_empty() {
+        return;
+    }
+
+    let (screenshots, files): (Vec<&Artifact>, Vec<&Artifact>) = result.artifacts.iter()
+        .partition(|artifact| artifact.kind == ArtifactKind::Screenshot);
+    if !screenshots.is_empty() {
+        html.push_str("<h2>Screenshots</h2>\n");
+        for artifact in screenshots {
+            let image = match (store, result.run_id) {
+                (Some(store), Some(run_id)) if artifact.size <= MAX_INLINE_SCREENSHOT_BYTES => store.read(run_id, &artifact.name).ok(),
+                _ => None,
+            };
+            let caption = format!("{} · {}", escape(&artifact.step), escape(&artifact.name));
+            match image {
+                Some((_, data)) => {
+                    let _ = writeln!(html, "<figure><img src=\"data:{};base64,{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>",
+                        escape(&artifact.content_type), base64::engine::general_purpose::STANDARD.encode(data), escape(&artifact.name), caption);
+                }
+                None => {
+                    let _ = writeln!(html, "<figure><figcaption>{} (not embedded, {})</figcaption></figure>", caption, format_size(artifact.size));
+                }
+            }
+        }
+    }
+
+    if !files.is_empty() {
+        html.push_str("<h2>Files</h2>\n<table>\n<tr><th>Name</th><th>Kind</th><th>Step</th><th>Type</th><th>Size</th></tr>\n");
+        for artifact in files {
+            let name = match result.run_id {
+                Some(run_id) => format!("<a href=\"/api/workflow/{}/artifacts/{}\">{}</a>", run_id, escape(&artifact.name), escape(&artifact.name)),
+                None => escape(&artifact.name),
+            };
+            let _ = writeln!(html, "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                name, artifact.kind, escape(&artifact.step), escape(&artifact.content_type), format_size(artifact.size));
+        }
+        html.push_str("</table>\n");
+    }
+}
+
+fn cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => escape(s),
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            format!("<pre>{}</pre>", escape(&serde_json::to_string_pretty(value).unwrap_or_default()))
+        }
+        other => other.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn format_duration(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{} ms", ms)
+    } else {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Directory of run reports, one HTML file per run
+#[derive(Debug, Clone)]
+pub struct ReportStore {
+    dir: std::path::PathBuf,
+}
+
+impl ReportStore {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, run_id: uuid::Uuid) -> std::path::PathBuf {
+        self.dir.join(format!("{}.html", run_id))
+    }
+
+    pub fn save(&self, run_id: uuid::Uuid, html: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(run_id), html)?;
+        Ok(())
+    }
+
+    pub fn load(&self, run_id: uuid::Uuid) -> Result<String> {
+        std::fs::read_to_string(self.path(run_id))
+            .with_context(|| format!("No report for workflow run {}", run_id))
+    }
+}
+
+impl Default for ReportStore {
+    fn default() -> Self {
+        Self::new("data/workflow_reports")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_renders_steps_errors_data_and_screenshots() {
+        let dir = TempDir::new().unwrap();
+        let artifacts = ArtifactStore::new(dir.path());
+        let run_id = uuid::Uuid::new_v4();
+        let screenshot = artifacts.save(run_id, "Open shop", "shop.png", ArtifactKind::Screenshot, "image/png", b"\x89PNG").unwrap();
+        let export = artifacts.save(run_id, "Export", "prices.json", ArtifactKind::Json, "application/json", b"[]").unwrap();
+
+        let workflow = Workflow::from_yaml("name: \"<Price> watch\"\ninputs:\n  - {name: query, input_type: string}\nsteps: []\n").unwrap();
+        let result: WorkflowResult = serde_json::from_value(serde_json::json!({
+            "run_id": run_id,
+            "success": false,
+            "duration_ms": 2500,
+            "steps_executed": 2,
+            "steps_failed": 1,
+            "variables": {
+                "query": "rust",
+                "title": "Shop & Co",
+                "products": [{"name": "Book", "price": "$12"}, {"name": "Pen", "stock": 4}],
+            },
+            "execution_log": [
+                {"timestamp": Utc::now(), "step_name": "Open shop", "action": "navigate", "success": true, "duration_ms": 800, "error": null, "data": null},
+                {"timestamp": Utc::now(), "step_name": "Click buy", "action": "click", "success": false, "duration_ms": 200, "error": "Element <#buy> not found", "data": null},
+            ],
+            "cost": 0.02,
+            "artifacts": [screenshot, export],
+        })).unwrap();
+
+        let html = render_html(&workflow, &result, Some(&artifacts));
+        assert!(html.contains("<title>&lt;Price&gt; watch run report</title>"));
+        assert!(html.contains("Failed"));
+        assert!(html.contains("Element &lt;#buy&gt; not found"));
+        assert!(html.contains("<td>Shop &amp; Co</td>"));
+        assert!(html.contains("<th>name</th><th>price</th><th>stock</th>"));
+        assert!(!html.contains("<td>rust</td>"), "inputs are not extracted data");
+        assert!(html.contains("src=\"data:image/png;base64,iVBORw==\""));
+        assert!(html.contains(&format!("/api/workflow/{}/artifacts/prices.json", run_id)));
+
+        let reports = ReportStore::new(dir.path().join("reports"));
+        assert!(reports.load(run_id).is_err());
+        reports.save(run_id, &html).unwrap();
+        assert_eq!(reports.load(run_id).unwrap(), html);
+    }
+}
@@ -0,0 +1,546 @@
+//! Workflow Scheduler
+//!
+//! Runs stored workflows (the latest version in the workflow library, or else the
+//! YAML/JSON file of that name in the templates directory) on cron expressions or
+//! fixed intervals, persists the schedules and keeps a per-workflow history of runs.
+
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::workflow::{ArtifactStore, Workflow, WorkflowEngine};
+use crate::workflow_library::WorkflowLibrary;
+use crate::workflow_webhooks::{notify_webhooks, WebhookDelivery};
+use crate::report::ReportStore;
+use crate::error_recovery::ErrorRecoveryManager;
+
+/// Standard five-field cron expression (`minute hour day-of-month month day-of-week`),
+/// evaluated in UTC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    days_of_week: Vec<u32>,
+    /// Day-of-month and day-of-week were both restricted, so either may match
+    either_day: bool,
+}
+
+const MONTH_NAMES: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            anyhow::bail!("Cron expression '{}' must have 5 fields (minute hour day month weekday)", expression);
+        }
+
+        // 7 is an alias of Sunday
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7, WEEKDAY_NAMES, 0)?;
+        for day in days_of_week.iter_mut() {
+            if *day == 7 {
+                *day = 0;
+            }
+        }
+        days_of_week.sort_unstable();
+        days_of_week.dedup();
+
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59, &[], 0)?,
+            hours: parse_cron_field(fields[1], 0, 23, &[], 0)?,
+            days_of_month: parse_cron_field(fields[2], 1, 31, &[], 1)?,
+            months: parse_cron_field(fields[3], 1, 12, MONTH_NAMES, 1)?,
+            days_of_week,
+            either_day: !fields[2].starts_with('*') && !fields[4].starts_with('*'),
+        })
+    }
+
+    /// First matching minute strictly after `after`, searching up to five years ahead
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        let limit = after + ChronoDuration::days(5 * 366);
+
+        while time <= limit {
+            if !self.months.contains(&time.month()) {
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                time = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                continue;
+            }
+            if !self.day_matches(&time) {
+                time = Utc.from_utc_datetime(&time.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?);
+                continue;
+            }
+            if !self.hours.contains(&time.hour()) {
+                time = time.with_minute(0)? + ChronoDuration::hours(1);
+                continue;
+            }
+            if !self.minutes.contains(&time.minute()) {
+                time += ChronoDuration::minutes(1);
+                continue;
+            }
+            return Some(time);
+        }
+        None
+    }
+
+    fn day_matches(&self, time: &DateTime<Utc>) -> bool {
+        let day_of_month = self.days_of_month.contains(&time.day());
+        let day_of_week = self.days_of_week.contains(&time.weekday().num_days_from_sunday());
+        if self.either_day {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+/// Values of one cron field: `*`, `5`, `1-5`, `*/15`, `10-40/10`, `mon,wed` and lists of these
+fn parse_cron_field(field: &str, min: u32, max: u32, names: &[&str], first_name_value: u32) -> Result<Vec<u32>> {
+    let value = |text: &str| -> Result<u32> {
+        let lower = text.to_lowercase();
+        if let Some(index) = names.iter().position(|name| *name == lower) {
+            return Ok(index as u32 + first_name_value);
+        }
+        let number: u32 = text.parse().with_context(|| format!("Invalid cron value '{}'", text))?;
+        if number < min || number > max {
+            anyhow::bail!("Cron value {} is outside {}-{}", number, min, max);
+        }
+        Ok(number)
+    };
+
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().with_context(|| format!("Invalid cron step '{}'", step))?;
+                if step == 0 {
+                    anyhow::bail!("Cron step in '{}' must be positive", part);
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let single = value(range)?;
+            // `5/15` means from 5 to the end of the range
+            (single, if part.contains('/') { max } else { single })
+        };
+        if start > end {
+            anyhow::bail!("Cron range '{}' is reversed", range);
+        }
+
+        values.extend((start..=end).step_by(step as usize));
+    }
+
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+/// When a schedule fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Trigger {
+    Cron { expression: String },
+    Interval { seconds: u64 },
+}
+
+impl Trigger {
+    /// Check the trigger can fire, e.g. that a cron expression parses
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Trigger::Cron { expression } => CronSchedule::parse(expression).map(|_| ()),
+            Trigger::Interval { seconds: 0 } => anyhow::bail!("Interval must be at least one second"),
+            Trigger::Interval { .. } => Ok(()),
+        }
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Trigger::Cron { expression } => CronSchedule::parse(expression).ok()?.next_after(after),
+            Trigger::Interval { seconds } => Some(after + ChronoDuration::seconds(*seconds as i64)),
+        }
+    }
+}
+
+/// A stored workflow run on a trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: Uuid,
+    pub workflow_id: String,
+    pub trigger: Trigger,
+    #[serde(default)]
+    pub inputs: HashMap<String, serde_json::Value>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+/// One execution of a stored workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRun {
+    pub id: Uuid,
+    pub workflow_id: String,
+    /// Schedule that started the run, `None` for runs started by hand
+    pub schedule_id: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub success: bool,
+    pub steps_executed: usize,
+    pub steps_failed: usize,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// Outcome of notifying the workflow's webhooks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookDelivery>,
+}
+
+/// Scheduler configuration
+#[derive(Debug, Clone)]
+pub struct SchedulerConfig {
+    /// Where schedules and run history are persisted
+    pub data_dir: PathBuf,
+    /// Directory of stored workflows
+    pub workflows_dir: PathBuf,
+    /// How often due schedules are checked
+    pub tick_interval: Duration,
+    /// Runs kept per workflow, oldest dropped first
+    pub max_runs_per_workflow: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::from("data/scheduler"),
+            workflows_dir: PathBuf::from("workflows/templates"),
+            tick_interval: Duration::from_secs(30),
+            max_runs_per_workflow: 100,
+        }
+    }
+}
+
+/// Runs stored workflows on their schedules
+pub struct WorkflowScheduler {
+    config: SchedulerConfig,
+    library: Option<Arc<WorkflowLibrary>>,
+    error_recovery: Option<Arc<ErrorRecoveryManager>>,
+    schedules: RwLock<HashMap<Uuid, Schedule>>,
+    runs: RwLock<HashMap<String, VecDeque<WorkflowRun>>>,
+}
+
+impl WorkflowScheduler {
+    /// Create a scheduler, loading persisted schedules and run history
+    pub fn new(config: SchedulerConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.data_dir)?;
+
+        let schedules: Vec<Schedule> = load_json(&config.data_dir.join("schedules.json"))?.unwrap_or_default();
+        let runs: HashMap<String, VecDeque<WorkflowRun>> = load_json(&config.data_dir.join("runs.json"))?.unwrap_or_default();
+        info!("⏰ Loaded {} workflow schedules", schedules.len());
+
+        Ok(Self {
+            config,
+            library: None,
+            error_recovery: None,
+            schedules: RwLock::new(schedules.into_iter().map(|schedule| (schedule.id, schedule)).collect()),
+            runs: RwLock::new(runs),
+        })
+    }
+
+    /// Look workflows up in `library` before the templates directory
+    pub fn with_library(mut self, library: Arc<WorkflowLibrary>) -> Self {
+        self.library = Some(library);
+        self
+    }
+
+    /// Report step failures of scheduled runs to `manager`
+    pub fn with_error_recovery(mut self, manager: Arc<ErrorRecoveryManager>) -> Self {
+        self.error_recovery = Some(manager);
+        self
+    }
+
+    /// Check for due schedules every tick until the task is aborted
+    pub fn start(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.tick_interval);
+            loop {
+                ticker.tick().await;
+                self.run_due(Utc::now()).await;
+            }
+        })
+    }
+
+    fn workflow_path(&self, workflow_id: &str) -> Option<PathBuf> {
+        // Ids are plain file names, never paths
+        if workflow_id.is_empty() || !workflow_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return None;
+        }
+        ["yaml", "yml", "json"]
+            .iter()
+            .map(|extension| self.config.workflows_dir.join(format!("{}.{}", workflow_id, extension)))
+            .find(|path| path.is_file())
+    }
+
+    pub fn workflow_exists(&self, workflow_id: &str) -> bool {
+        self.library.as_ref().is_some_and(|library| library.contains(workflow_id))
+            || self.workflow_path(workflow_id).is_some()
+    }
+
+    /// Load the stored workflow `workflow_id`
+    pub fn load_workflow(&self, workflow_id: &str) -> Result<Workflow> {
+        if let Some(workflow) = self.library.as_ref().and_then(|library| library.latest(workflow_id)) {
+            return Ok(workflow);
+        }
+        let path = self.workflow_path(workflow_id)
+            .ok_or_else(|| anyhow::anyhow!("Workflow '{}' not found", workflow_id))?;
+        let content = std::fs::read_to_string(&path)?;
+        if path.extension().is_some_and(|extension| extension == "json") {
+            Workflow::from_json(&content)
+        } else {
+            Workflow::from_yaml(&content)
+        }
+    }
+
+    pub async fn add_schedule(&self, workflow_id: &str, trigger: Trigger, inputs: HashMap<String, serde_json::Value>) -> Result<Schedule> {
+        if !self.workflow_exists(workflow_id) {
+            anyhow::bail!("Workflow '{}' not found", workflow_id);
+        }
+        trigger.validate()?;
+
+        let now = Utc::now();
+        let schedule = Schedule {
+            id: Uuid::new_v4(),
+            workflow_id: workflow_id.to_string(),
+            next_run: trigger.next_after(now),
+            trigger,
+            inputs,
+            enabled: true,
+            created_at: now,
+            last_run: None,
+        };
+        info!("⏰ Scheduled workflow '{}' ({:?}), next run {:?}", workflow_id, schedule.trigger, schedule.next_run);
+
+        self.schedules.write().await.insert(schedule.id, schedule.clone());
+        self.save_schedules().await?;
+        Ok(schedule)
+    }
+
+    /// Remove a schedule, returning whether it existed
+    pub async fn remove_schedule(&self, id: Uuid) -> Result<bool> {
+        let removed = self.schedules.write().await.remove(&id).is_some();
+        if removed {
+            self.save_schedules().await?;
+        }
+        Ok(removed)
+    }
+
+    /// Schedules, optionally only those of one workflow, soonest first
+    pub async fn list_schedules(&self, workflow_id: Option<&str>) -> Vec<Schedule> {
+        let mut schedules: Vec<Schedule> = self.schedules.read().await
+            .values()
+            .filter(|schedule| workflow_id.is_none_or(|id| schedule.workflow_id == id))
+            .cloned()
+            .collect();
+        schedules.sort_by_key(|schedule| schedule.next_run);
+        schedules
+    }
+
+    /// Recorded runs of a workflow, newest first
+    pub async fn runs(&self, workflow_id: &str, limit: usize) -> Vec<WorkflowRun> {
+        self.runs.read().await
+            .get(workflow_id)
+            .map(|runs| runs.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Run every enabled schedule whose next run is not after `now`. A schedule
+    /// that was due while the scheduler was down runs once, not once per missed slot.
+    pub async fn run_due(&self, now: DateTime<Utc>) -> Vec<WorkflowRun> {
+        let due: Vec<Schedule> = {
+            let mut schedules = self.schedules.write().await;
+            schedules.values_mut()
+                .filter(|schedule| schedule.enabled && schedule.next_run.is_some_and(|next| next <= now))
+                .map(|schedule| {
+                    schedule.last_run = Some(now);
+                    schedule.next_run = schedule.trigger.next_after(now);
+                    schedule.clone()
+                })
+                .collect()
+        };
+        if due.is_empty() {
+            return Vec::new();
+        }
+        if let Err(e) = self.save_schedules().await {
+            error!("Failed to persist schedules: {}", e);
+        }
+
+        let mut runs = Vec::new();
+        for schedule in due {
+            info!("⏰ Running scheduled workflow '{}'", schedule.workflow_id);
+            runs.push(self.run_workflow(&schedule.workflow_id, schedule.inputs.clone(), Some(schedule.id)).await);
+        }
+        runs
+    }
+
+    /// Execute a stored workflow and record the run; failures are recorded, not returned
+    pub async fn run_workflow(&self, workflow_id: &str, inputs: HashMap<String, serde_json::Value>, schedule_id: Option<Uuid>) -> WorkflowRun {
+        let started_at = Utc::now();
+        let start = std::time::Instant::now();
+
+        let outcome = match self.load_workflow(workflow_id) {
+            Ok(workflow) => {
+                let mut engine = WorkflowEngine::new_simple()
+                    .with_artifacts(ArtifactStore::default())
+                    .with_reports(ReportStore::default());
+                if let Some(manager) = &self.error_recovery {
+                    engine = engine.with_error_recovery(manager.clone());
+                }
+                let result = engine.execute_workflow(&workflow, Some(inputs)).await;
+                let _ = engine.cleanup().await;
+                let webhooks = match &result {
+                    Ok(result) => notify_webhooks(&workflow, result).await,
+                    Err(_) => Vec::new(),
+                };
+                result.map(|result| (result, webhooks))
+            }
+            Err(e) => Err(e),
+        };
+
+        let run = WorkflowRun {
+            // The engine's run id, under which the run's artifacts are stored
+            id: outcome.as_ref().ok().and_then(|(result, _)| result.run_id).unwrap_or_else(Uuid::new_v4),
+            workflow_id: workflow_id.to_string(),
+            schedule_id,
+            started_at,
+            finished_at: Utc::now(),
+            success: outcome.as_ref().is_ok_and(|(result, _)| result.success),
+            steps_executed: outcome.as_ref().map_or(0, |(result, _)| result.steps_executed),
+            steps_failed: outcome.as_ref().map_or(0, |(result, _)| result.steps_failed),
+            duration_ms: start.elapsed().as_millis() as u64,
+            error: outcome.as_ref().err().map(|e| e.to_string()),
+            webhooks: outcome.map(|(_, webhooks)| webhooks).unwrap_or_default(),
+        };
+        if !run.success {
+            warn!("Workflow '{}' run failed: {:?}", workflow_id, run.error);
+        }
+
+        self.record_run(run.clone()).await;
+        run
+    }
+
+    async fn record_run(&self, run: WorkflowRun) {
+        {
+            let mut runs = self.runs.write().await;
+            let history = runs.entry(run.workflow_id.clone()).or_default();
+            history.push_back(run);
+            while history.len() > self.config.max_runs_per_workflow {
+                history.pop_front();
+            }
+        }
+        if let Err(e) = self.save_runs().await {
+            error!("Failed to persist workflow runs: {}", e);
+        }
+    }
+
+    async fn save_schedules(&self) -> Result<()> {
+        let schedules: Vec<Schedule> = self.schedules.read().await.values().cloned().collect();
+        std::fs::write(self.config.data_dir.join("schedules.json"), serde_json::to_string_pretty(&schedules)?)?;
+        Ok(())
+    }
+
+    async fn save_runs(&self) -> Result<()> {
+        let runs = self.runs.read().await;
+        std::fs::write(self.config.data_dir.join("runs.json"), serde_json::to_string_pretty(&*runs)?)?;
+        Ok(())
+    }
+}
+
+fn load_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_cron_next_run() {
+        let nightly = CronSchedule::parse("30 2 * * *").unwrap();
+        assert_eq!(nightly.next_after(at("2026-03-10T01:00:00Z")), Some(at("2026-03-10T02:30:00Z")));
+        assert_eq!(nightly.next_after(at("2026-03-10T02:30:00Z")), Some(at("2026-03-11T02:30:00Z")));
+
+        let weekdays = CronSchedule::parse("*/15 9-17 * * mon-fri").unwrap();
+        // 2026-03-14 is a Saturday
+        assert_eq!(weekdays.next_after(at("2026-03-13T17:50:00Z")), Some(at("2026-03-16T09:00:00Z")));
+
+        let monthly = CronSchedule::parse("@monthly").unwrap();
+        assert_eq!(monthly.next_after(at("2026-12-15T00:00:00Z")), Some(at("2027-01-01T00:00:00Z")));
+
+        assert!(CronSchedule::parse("61 * * * *").is_err());
+        assert!(CronSchedule::parse("* * *").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_schedules_and_runs_are_persisted() {
+        let dir = TempDir::new().unwrap();
+        let workflows_dir = dir.path().join("workflows");
+        std::fs::create_dir_all(&workflows_dir).unwrap();
+        std::fs::write(workflows_dir.join("price_check.yaml"), "name: price check\nvariables: {}\nsteps: []\n").unwrap();
+        let config = SchedulerConfig {
+            data_dir: dir.path().join("data"),
+            workflows_dir,
+            ..Default::default()
+        };
+
+        let scheduler = WorkflowScheduler::new(config.clone()).unwrap();
+        assert!(scheduler.add_schedule("missing", Trigger::Interval { seconds: 60 }, HashMap::new()).await.is_err());
+        assert!(scheduler.add_schedule("../price_check", Trigger::Interval { seconds: 60 }, HashMap::new()).await.is_err());
+        let schedule = scheduler
+            .add_schedule("price_check", Trigger::Cron { expression: "0 3 * * *".to_string() }, HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(schedule.next_run.unwrap().hour(), 3);
+
+        let run = scheduler.run_workflow("deleted_workflow", HashMap::new(), Some(schedule.id)).await;
+        assert!(!run.success);
+
+        let reloaded = WorkflowScheduler::new(config).unwrap();
+        assert_eq!(reloaded.list_schedules(Some("price_check")).await.len(), 1);
+        let runs = reloaded.runs("deleted_workflow", 10).await;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].schedule_id, Some(schedule.id));
+        assert!(runs[0].error.as_deref().unwrap().contains("not found"));
+    }
+}
@@ -7,6 +7,7 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
@@ -51,6 +52,74 @@ pub struct InteractionRecord {
     pub context_markers: Vec<String>,
 }
 
+/// A past interaction recalled for a query, with its cosine similarity to the query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecalledInteraction {
+    pub interaction: InteractionRecord,
+    pub similarity: f32,
+}
+
+/// Dimensions of the vectors produced by `HashingEmbedder`
+const EMBEDDING_DIMENSIONS: usize = 256;
+
+/// Words ignored when extracting patterns and embedding text
+const STOP_WORDS: [&str; 17] = ["the", "a", "an", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by", "me", "my", "i"];
+
+/// Turns text into a vector whose cosine similarity to another text's vector
+/// reflects how alike their meanings are
+pub trait Embedder: Send + Sync {
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Local embedder that hashes words and their character trigrams into a fixed
+/// number of buckets, so inputs sharing words or word stems land close together
+/// without calling a model
+#[derive(Debug, Clone, Default)]
+pub struct HashingEmbedder;
+
+impl Embedder for HashingEmbedder {
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0; EMBEDDING_DIMENSIONS];
+        let lower = text.to_lowercase();
+        let words = lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty() && !STOP_WORDS.contains(word));
+
+        for word in words {
+            vector[embedding_bucket(word)] += 1.0;
+            let padded: Vec<char> = format!(" {} ", word).chars().collect();
+            for trigram in padded.windows(3) {
+                vector[embedding_bucket(&trigram.iter().collect::<String>())] += 0.5;
+            }
+        }
+
+        let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|value| *value /= norm);
+        }
+        vector
+    }
+}
+
+/// FNV-1a bucket of a token; stable across runs, unlike the std hasher
+fn embedding_bucket(token: &str) -> usize {
+    let hash = token
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    (hash % EMBEDDING_DIMENSIONS as u64) as usize
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
 /// Learned pattern with adaptive weights
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LearnedPattern {
@@ -70,10 +139,22 @@ pub struct SimpleMemory {
     config: SimpleMemoryConfig,
     patterns_cache: Arc<RwLock<LruCache<String, LearnedPattern>>>,
     interactions_cache: Arc<RwLock<LruCache<Uuid, InteractionRecord>>>,
+    /// Embeddings of the cached interactions' user input, for semantic recall
+    embeddings: Arc<RwLock<HashMap<Uuid, Vec<f32>>>>,
+    embedder: Arc<dyn Embedder>,
     patterns_file: PathBuf,
     interactions_file: PathBuf,
 }
 
+impl std::fmt::Debug for SimpleMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimpleMemory")
+            .field("config", &self.config)
+            .field("interactions", &self.interactions_cache.read().unwrap().len())
+            .finish()
+    }
+}
+
 impl SimpleMemory {
     /// Initialize the simple memory system
     pub async fn new(config: SimpleMemoryConfig) -> Result<Self> {
@@ -92,6 +173,8 @@ impl SimpleMemory {
             config,
             patterns_cache,
             interactions_cache,
+            embeddings: Arc::new(RwLock::new(HashMap::new())),
+            embedder: Arc::new(HashingEmbedder),
             patterns_file,
             interactions_file,
         };
@@ -104,13 +187,27 @@ impl SimpleMemory {
         Ok(memory)
     }
 
+    /// Use `embedder` for recall instead of the local hashing embedder, re-embedding
+    /// the interactions already in memory
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = embedder;
+        self.embeddings.write().unwrap().clear();
+        self.index_cached_interactions();
+        self
+    }
+
     /// Record a new interaction for learning
     pub async fn record_interaction(&self, interaction: InteractionRecord) -> Result<()> {
-        // Update cache
+        // Update cache, dropping the embedding of any interaction it evicts
         {
             let mut cache = self.interactions_cache.write().unwrap();
-            cache.put(interaction.id, interaction.clone());
+            if let Some((evicted, _)) = cache.push(interaction.id, interaction.clone()) {
+                if evicted != interaction.id {
+                    self.embeddings.write().unwrap().remove(&evicted);
+                }
+            }
         }
+        self.index_interaction(&interaction);
 
         // Learn from this interaction
         self.learn_from_interaction(&interaction).await?;
@@ -126,6 +223,45 @@ impl SimpleMemory {
         Ok(())
     }
 
+    /// The `k` remembered interactions whose input is most similar in meaning to
+    /// `query`, most similar first
+    pub async fn recall(&self, query: &str, k: usize) -> Vec<RecalledInteraction> {
+        let query_embedding = self.embedder.embed(query);
+        let mut recalled: Vec<RecalledInteraction> = {
+            let cache = self.interactions_cache.read().unwrap();
+            let embeddings = self.embeddings.read().unwrap();
+            cache
+                .iter()
+                .filter_map(|(id, interaction)| {
+                    let similarity = cosine_similarity(&query_embedding, embeddings.get(id)?);
+                    (similarity > 0.0).then(|| RecalledInteraction {
+                        interaction: interaction.clone(),
+                        similarity,
+                    })
+                })
+                .collect()
+        };
+
+        recalled.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+        recalled.truncate(k);
+        recalled
+    }
+
+    fn index_interaction(&self, interaction: &InteractionRecord) {
+        let embedding = self.embedder.embed(&interaction.user_input);
+        self.embeddings.write().unwrap().insert(interaction.id, embedding);
+    }
+
+    fn index_cached_interactions(&self) {
+        let interactions: Vec<InteractionRecord> = {
+            let cache = self.interactions_cache.read().unwrap();
+            cache.iter().map(|(_, interaction)| interaction.clone()).collect()
+        };
+        for interaction in &interactions {
+            self.index_interaction(interaction);
+        }
+    }
+
     /// Learn and adapt from interaction outcome
     async fn learn_from_interaction(&self, interaction: &InteractionRecord) -> Result<()> {
         // Extract patterns from the user input
@@ -197,9 +333,8 @@ impl SimpleMemory {
         }
 
         // Extract significant single words (not stop words)
-        let stop_words = ["the", "a", "an", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by", "me", "my", "i"];
         for word in &words {
-            if word.len() > 3 && !stop_words.contains(word) {
+            if word.len() > 3 && !STOP_WORDS.contains(word) {
                 patterns.push(word.to_string());
             }
         }
@@ -353,6 +488,7 @@ impl SimpleMemory {
                 cache.put(interaction.id, interaction);
             }
         }
+        self.index_cached_interactions();
 
         info!("📚 Loaded {} interactions from file", 
               self.interactions_cache.read().unwrap().len());
@@ -399,8 +535,10 @@ impl SimpleMemory {
                 .map(|(key, _)| *key)
                 .collect();
 
+            let mut embeddings = self.embeddings.write().unwrap();
             for key in keys_to_remove {
                 cache.pop(&key);
+                embeddings.remove(&key);
             }
         }
 
@@ -447,4 +585,49 @@ pub struct SimpleMemoryStats {
 pub async fn create_simple_memory() -> Result<SimpleMemory> {
     let config = SimpleMemoryConfig::default();
     SimpleMemory::new(config).await
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interaction(user_input: &str, task: TaskType, success: bool) -> InteractionRecord {
+        InteractionRecord {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            user_input: user_input.to_string(),
+            classified_task: task,
+            confidence: 0.9,
+            execution_success: success,
+            execution_time_ms: 1200,
+            context_markers: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recall_ranks_interactions_by_similarity() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SimpleMemoryConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let memory = SimpleMemory::new(config.clone()).await.unwrap();
+        memory.record_interaction(interaction("go to github and take a screenshot", TaskType::Screenshot, true)).await.unwrap();
+        memory.record_interaction(interaction("plan a trip to Tokyo", TaskType::Planning, true)).await.unwrap();
+        memory.record_interaction(interaction("check the weather forecast", TaskType::Search, false)).await.unwrap();
+
+        let recalled = memory.recall("screenshot of github.com", 2).await;
+        assert!(recalled.len() <= 2);
+        assert_eq!(recalled[0].interaction.classified_task, TaskType::Screenshot);
+        assert!(recalled[0].similarity > 0.5);
+
+        let recalled = memory.recall("planning a trip to Osaka", 3).await;
+        assert_eq!(recalled[0].interaction.user_input, "plan a trip to Tokyo");
+        assert!(memory.recall("xyzzy", 3).await.iter().all(|recalled| recalled.similarity < 0.2));
+
+        // The index is rebuilt from the saved interactions
+        let reloaded = SimpleMemory::new(config).await.unwrap();
+        let recalled = reloaded.recall("github screenshot", 1).await;
+        assert_eq!(recalled[0].interaction.user_input, "go to github and take a screenshot");
+    }
+}
@@ -159,6 +159,7 @@ impl TaskExecutor {
             parallel: Some(false), // Execute steps sequentially by default
             on_error: Some(crate::workflow::ErrorStrategy::Continue), // Continue on errors
             timeout: Some(task_plan.estimated_duration as u64),
+            webhooks: Vec::new(),
         })
     }
 
@@ -186,6 +187,7 @@ impl TaskExecutor {
                 WorkflowActionType::Extract {
                     selector,
                     attribute: None,
+                    all: false,
                 }
             }
             "report" | "summary" => {
@@ -215,6 +217,7 @@ impl TaskExecutor {
             }),
             store_as: Some(format!("step_{}_result", action_step.step_number)),
             timeout: Some(30), // 30 seconds per step
+            artifacts: Vec::new(),
         })
     }
 
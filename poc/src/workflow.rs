@@ -1,727 +1,1899 @@
-use anyhow::{Result, Context as AnyhowContext};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::Duration;
-use tracing::{info, warn, error};
-use crate::{SimpleBrowser, CostTracker};
-use tokio::time::sleep;
-use chrono::{DateTime, Utc};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Workflow {
-    pub name: String,
-    pub description: Option<String>,
-    pub version: Option<String>,
-    pub inputs: Option<Vec<InputDefinition>>,
-    pub variables: HashMap<String, serde_json::Value>,
-    pub steps: Vec<WorkflowStep>,
-    pub parallel: Option<bool>,
-    pub on_error: Option<ErrorStrategy>,
-    pub timeout: Option<u64>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct InputDefinition {
-    pub name: String,
-    pub input_type: String,
-    pub required: Option<bool>,
-    pub default: Option<serde_json::Value>,
-    pub description: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkflowStep {
-    pub name: String,
-    pub action: ActionType,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub condition: Option<Condition>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub on_error: Option<ErrorStrategy>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub retry: Option<RetryConfig>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub store_as: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub timeout: Option<u64>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum ActionType {
-    Navigate {
-        url: String,
-        #[serde(default)]
-        screenshot: bool,
-    },
-    Click {
-        selector: String,
-        #[serde(default)]
-        wait_after: u64,
-    },
-    Fill {
-        selector: String,
-        value: String,
-    },
-    Extract {
-        selector: String,
-        attribute: Option<String>,
-    },
-    Wait {
-        #[serde(flatten)]
-        wait_type: WaitType,
-    },
-    Assert {
-        #[serde(flatten)]
-        assertion: AssertionType,
-    },
-    Loop {
-        over: String,
-        #[serde(rename = "do")]
-        body: Vec<WorkflowStep>,
-    },
-    Conditional {
-        #[serde(rename = "if")]
-        condition: Condition,
-        #[serde(rename = "then")]
-        then_branch: Vec<WorkflowStep>,
-        #[serde(rename = "else")]
-        else_branch: Option<Vec<WorkflowStep>>,
-    },
-    Script {
-        code: String,
-    },
-    Parallel {
-        steps: Vec<WorkflowStep>,
-    },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "wait_for", rename_all = "snake_case")]
-pub enum WaitType {
-    Element { selector: String },
-    Text { text: String },
-    Url { pattern: String },
-    Time { seconds: u64 },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "assert", rename_all = "snake_case")]
-pub enum AssertionType {
-    ElementExists { selector: String },
-    TextContains { text: String },
-    UrlMatches { pattern: String },
-    ElementCount { selector: String, count: usize },
-    Title { expected: String },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "check", rename_all = "snake_case")]
-pub enum Condition {
-    ElementExists { selector: String },
-    TextContains { text: String },
-    VariableEquals { var: String, value: serde_json::Value },
-    VariableGreaterThan { var: String, value: f64 },
-    VariableLessThan { var: String, value: f64 },
-    Not { condition: Box<Condition> },
-    And { conditions: Vec<Condition> },
-    Or { conditions: Vec<Condition> },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ErrorStrategy {
-    Fail,
-    Continue,
-    Retry,
-    Fallback { steps: Vec<WorkflowStep> },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RetryConfig {
-    pub max_attempts: u32,
-    pub delay_seconds: u64,
-    pub exponential_backoff: Option<bool>,
-}
-
-pub struct WorkflowEngine {
-    browser: Option<SimpleBrowser>,
-    pub cost_tracker: CostTracker,
-    variables: HashMap<String, serde_json::Value>,
-    execution_log: Vec<ExecutionEntry>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExecutionEntry {
-    pub timestamp: DateTime<Utc>,
-    pub step_name: String,
-    pub action: String,
-    pub success: bool,
-    pub duration_ms: u64,
-    pub error: Option<String>,
-    pub data: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkflowResult {
-    pub success: bool,
-    pub duration_ms: u64,
-    pub steps_executed: usize,
-    pub steps_failed: usize,
-    pub variables: HashMap<String, serde_json::Value>,
-    pub execution_log: Vec<ExecutionEntry>,
-    pub cost: f64,
-}
-
-impl WorkflowEngine {
-    pub fn new(cost_tracker: CostTracker) -> Self {
-        Self {
-            browser: None,
-            cost_tracker,
-            variables: HashMap::new(),
-            execution_log: Vec::new(),
-        }
-    }
-    
-    /// API-friendly constructor without CostTracker
-    pub fn new_simple() -> Self {
-        Self {
-            browser: None,
-            cost_tracker: CostTracker::new(100.0), // Default budget
-            variables: HashMap::new(),
-            execution_log: Vec::new(),
-        }
-    }
-    
-    /// Set a variable for use in templates
-    pub async fn set_variable(&mut self, name: &str, value: serde_json::Value) {
-        self.variables.insert(name.to_string(), value);
-    }
-    
-    /// Simple execute method for API
-    pub async fn execute(&mut self, workflow: &Workflow) -> Result<WorkflowResult> {
-        self.execute_workflow(workflow, None).await
-    }
-
-    pub async fn execute_workflow(&mut self, workflow: &Workflow, inputs: Option<HashMap<String, serde_json::Value>>) -> Result<WorkflowResult> {
-        let start_time = std::time::Instant::now();
-        info!("🎭 Starting workflow: {}", workflow.name);
-
-        // Initialize variables with inputs
-        self.variables = workflow.variables.clone();
-        if let Some(inputs) = inputs {
-            for (key, value) in inputs {
-                self.variables.insert(key, value);
-            }
-        }
-
-        // Validate required inputs
-        if let Some(input_defs) = &workflow.inputs {
-            for input_def in input_defs {
-                if input_def.required.unwrap_or(false) && !self.variables.contains_key(&input_def.name) {
-                    if let Some(default) = &input_def.default {
-                        self.variables.insert(input_def.name.clone(), default.clone());
-                    } else {
-                        return Err(anyhow::anyhow!("Required input '{}' not provided", input_def.name));
-                    }
-                }
-            }
-        }
-
-        // Initialize browser if needed
-        if self.browser.is_none() {
-            info!("🌐 Initializing browser for workflow");
-            self.browser = Some(SimpleBrowser::new().await?);
-        }
-
-        // Execute steps
-        let mut steps_executed = 0;
-        let mut steps_failed = 0;
-
-        if workflow.parallel.unwrap_or(false) {
-            // Execute steps in parallel
-            info!("⚡ Executing {} steps in parallel", workflow.steps.len());
-            let results = self.execute_parallel_steps(&workflow.steps).await?;
-            for result in results {
-                steps_executed += 1;
-                if !result.success {
-                    steps_failed += 1;
-                }
-                self.execution_log.push(result);
-            }
-        } else {
-            // Execute steps sequentially
-            for step in &workflow.steps {
-                let step_result = self.execute_step(step).await;
-                steps_executed += 1;
-
-                let success = step_result.is_ok();
-                let error_msg = step_result.as_ref().err().map(|e| e.to_string());
-
-                self.execution_log.push(ExecutionEntry {
-                    timestamp: Utc::now(),
-                    step_name: step.name.clone(),
-                    action: format!("{:?}", step.action),
-                    success,
-                    duration_ms: 0, // TODO: Track individual step duration
-                    error: error_msg.clone(),
-                    data: step_result.as_ref().ok().cloned(),
-                });
-
-                if !success {
-                    steps_failed += 1;
-                    
-                    // Handle error strategy
-                    match step.on_error.as_ref().or(workflow.on_error.as_ref()) {
-                        Some(ErrorStrategy::Continue) => {
-                            warn!("Step '{}' failed, continuing workflow", step.name);
-                            continue;
-                        }
-                        Some(ErrorStrategy::Retry) => {
-                            // Retry logic handled in execute_step
-                            continue;
-                        }
-                        Some(ErrorStrategy::Fallback { steps }) => {
-                            info!("Executing fallback steps for '{}'", step.name);
-                            for fallback_step in steps {
-                                let _ = self.execute_step(fallback_step).await;
-                            }
-                        }
-                        _ => {
-                            error!("Step '{}' failed, stopping workflow", step.name);
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-
-        // Calculate cost
-        let workflow_cost = self.cost_tracker.estimate_browser_operation_cost() * steps_executed as f64;
-        self.cost_tracker.record_operation(
-            "workflow".to_string(),
-            format!("Workflow: {}", workflow.name),
-            workflow_cost,
-            steps_failed == 0,
-        )?;
-
-        let duration = start_time.elapsed();
-        
-        Ok(WorkflowResult {
-            success: steps_failed == 0,
-            duration_ms: duration.as_millis() as u64,
-            steps_executed,
-            steps_failed,
-            variables: self.variables.clone(),
-            execution_log: self.execution_log.clone(),
-            cost: workflow_cost,
-        })
-    }
-
-    fn execute_step<'a>(&'a mut self, step: &'a WorkflowStep) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<serde_json::Value>> + Send + 'a>> {
-        Box::pin(async move {
-        info!("📍 Executing step: {}", step.name);
-
-        // Check condition if present
-        if let Some(condition) = &step.condition {
-            if !self.evaluate_condition(condition).await? {
-                info!("Skipping step '{}' - condition not met", step.name);
-                return Ok(serde_json::json!({"skipped": true}));
-            }
-        }
-
-        // Execute with retry if configured
-        let max_attempts = step.retry.as_ref().map(|r| r.max_attempts).unwrap_or(1);
-        let mut last_error = None;
-
-        for attempt in 1..=max_attempts {
-            if attempt > 1 {
-                let delay = step.retry.as_ref().map(|r| r.delay_seconds).unwrap_or(2);
-                info!("Retry attempt {} after {}s delay", attempt, delay);
-                sleep(Duration::from_secs(delay)).await;
-            }
-
-            match self.execute_action(&step.action).await {
-                Ok(result) => {
-                    // Store result if requested
-                    if let Some(var_name) = &step.store_as {
-                        self.variables.insert(var_name.clone(), result.clone());
-                        info!("Stored result in variable '{}'", var_name);
-                    }
-                    return Ok(result);
-                }
-                Err(e) => {
-                    warn!("Step '{}' attempt {} failed: {}", step.name, attempt, e);
-                    last_error = Some(e);
-                }
-            }
-        }
-
-        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Step failed after {} attempts", max_attempts)))
-        })
-    }
-
-    async fn execute_action(&mut self, action: &ActionType) -> Result<serde_json::Value> {
-        let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
-
-        match action {
-            ActionType::Navigate { url, screenshot } => {
-                info!("🌐 Navigating to: {}", url);
-                let expanded_url = self.expand_template(url)?;
-                browser.navigate_to(&expanded_url).await?;
-                
-                if *screenshot {
-                    let filename = format!("workflow_{}.png", Utc::now().format("%Y%m%d_%H%M%S"));
-                    browser.take_screenshot(&filename).await?;
-                    return Ok(serde_json::json!({"screenshot": filename}));
-                }
-                
-                Ok(serde_json::json!({"navigated": expanded_url}))
-            }
-            
-            ActionType::Click { selector, wait_after } => {
-                info!("🖱️ Clicking: {}", selector);
-                let expanded_selector = self.expand_template(selector)?;
-                browser.click_element(&expanded_selector).await?;
-                
-                if *wait_after > 0 {
-                    sleep(Duration::from_secs(*wait_after)).await;
-                }
-                
-                Ok(serde_json::json!({"clicked": expanded_selector}))
-            }
-            
-            ActionType::Fill { selector, value } => {
-                info!("✏️ Filling field: {}", selector);
-                let expanded_selector = self.expand_template(selector)?;
-                let expanded_value = self.expand_template(value)?;
-                browser.fill_field(&expanded_selector, &expanded_value).await?;
-                
-                Ok(serde_json::json!({"filled": expanded_selector, "value": expanded_value}))
-            }
-            
-            ActionType::Extract { selector, attribute } => {
-                info!("📋 Extracting from: {}", selector);
-                let expanded_selector = self.expand_template(selector)?;
-                
-                let extracted = if let Some(attr) = attribute {
-                    browser.get_attribute(&expanded_selector, attr).await?
-                } else {
-                    browser.get_text(&expanded_selector).await?
-                };
-                
-                Ok(serde_json::json!(extracted))
-            }
-            
-            ActionType::Wait { wait_type } => {
-                match wait_type {
-                    WaitType::Time { seconds } => {
-                        info!("⏳ Waiting {} seconds", seconds);
-                        sleep(Duration::from_secs(*seconds)).await;
-                    }
-                    WaitType::Element { selector } => {
-                        info!("⏳ Waiting for element: {}", selector);
-                        let expanded_selector = self.expand_template(selector)?;
-                        browser.wait_for_element(&expanded_selector, Duration::from_secs(30)).await?;
-                    }
-                    WaitType::Text { text } => {
-                        info!("⏳ Waiting for text: {}", text);
-                        let expanded_text = self.expand_template(text)?;
-                        browser.wait_for_text(&expanded_text, Duration::from_secs(30)).await?;
-                    }
-                    WaitType::Url { pattern } => {
-                        info!("⏳ Waiting for URL pattern: {}", pattern);
-                        let expanded_pattern = self.expand_template(pattern)?;
-                        browser.wait_for_url(&expanded_pattern, Duration::from_secs(30)).await?;
-                    }
-                }
-                Ok(serde_json::json!({"waited": true}))
-            }
-            
-            ActionType::Assert { assertion } => {
-                self.execute_assertion(assertion).await
-            }
-            
-            ActionType::Loop { over, body } => {
-                self.execute_loop(over, body).await
-            }
-            
-            ActionType::Conditional { condition, then_branch, else_branch } => {
-                self.execute_conditional(condition, then_branch, else_branch.as_ref()).await
-            }
-            
-            ActionType::Script { code } => {
-                self.execute_script(code).await
-            }
-            
-            ActionType::Parallel { steps } => {
-                let results = self.execute_parallel_steps(steps).await?;
-                Ok(serde_json::json!(results))
-            }
-        }
-    }
-
-    async fn execute_assertion(&self, assertion: &AssertionType) -> Result<serde_json::Value> {
-        let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
-
-        match assertion {
-            AssertionType::ElementExists { selector } => {
-                let expanded_selector = self.expand_template(selector)?;
-                let exists = browser.element_exists(&expanded_selector).await?;
-                if !exists {
-                    return Err(anyhow::anyhow!("Assertion failed: Element '{}' does not exist", expanded_selector));
-                }
-                Ok(serde_json::json!({"assertion": "element_exists", "passed": true}))
-            }
-            
-            AssertionType::TextContains { text } => {
-                let expanded_text = self.expand_template(text)?;
-                let page_text = browser.get_page_text().await?;
-                if !page_text.contains(&expanded_text) {
-                    return Err(anyhow::anyhow!("Assertion failed: Page does not contain text '{}'", expanded_text));
-                }
-                Ok(serde_json::json!({"assertion": "text_contains", "passed": true}))
-            }
-            
-            AssertionType::UrlMatches { pattern } => {
-                let expanded_pattern = self.expand_template(pattern)?;
-                let current_url = browser.current_url().await?;
-                if !current_url.contains(&expanded_pattern) {
-                    return Err(anyhow::anyhow!("Assertion failed: URL '{}' does not match pattern '{}'", current_url, expanded_pattern));
-                }
-                Ok(serde_json::json!({"assertion": "url_matches", "passed": true}))
-            }
-            
-            AssertionType::ElementCount { selector, count } => {
-                let expanded_selector = self.expand_template(selector)?;
-                let actual_count = browser.count_elements(&expanded_selector).await?;
-                if actual_count != *count {
-                    return Err(anyhow::anyhow!("Assertion failed: Expected {} elements, found {}", count, actual_count));
-                }
-                Ok(serde_json::json!({"assertion": "element_count", "passed": true, "count": count}))
-            }
-            
-            AssertionType::Title { expected } => {
-                let expanded_expected = self.expand_template(expected)?;
-                let actual_title = browser.get_title().await?;
-                if actual_title != expanded_expected {
-                    return Err(anyhow::anyhow!("Assertion failed: Expected title '{}', got '{}'", expanded_expected, actual_title));
-                }
-                Ok(serde_json::json!({"assertion": "title", "passed": true}))
-            }
-        }
-    }
-
-    async fn execute_loop(&mut self, over: &str, body: &[WorkflowStep]) -> Result<serde_json::Value> {
-        info!("🔄 Executing loop over: {}", over);
-        
-        // Get the collection to iterate over
-        let collection = self.variables.get(over)
-            .ok_or_else(|| anyhow::anyhow!("Variable '{}' not found for loop", over))?
-            .clone();
-        
-        let items = collection.as_array()
-            .ok_or_else(|| anyhow::anyhow!("Variable '{}' is not an array", over))?;
-        
-        let mut results = Vec::new();
-        let items_len = items.len();
-        
-        for (index, item) in items.iter().enumerate() {
-            info!("Loop iteration {}/{}", index + 1, items_len);
-            
-            // Set loop variables
-            self.variables.insert("_loop_index".to_string(), serde_json::json!(index));
-            self.variables.insert("_loop_item".to_string(), item.clone());
-            
-            // Execute loop body
-            for step in body {
-                let result = self.execute_step(step).await?;
-                results.push(result);
-            }
-        }
-        
-        Ok(serde_json::json!({"loop_results": results}))
-    }
-
-    async fn execute_conditional(&mut self, condition: &Condition, then_branch: &[WorkflowStep], else_branch: Option<&Vec<WorkflowStep>>) -> Result<serde_json::Value> {
-        info!("❓ Evaluating conditional");
-        
-        if self.evaluate_condition(condition).await? {
-            info!("✅ Condition met, executing then branch");
-            let mut results = Vec::new();
-            for step in then_branch {
-                let result = self.execute_step(step).await?;
-                results.push(result);
-            }
-            Ok(serde_json::json!({"branch": "then", "results": results}))
-        } else if let Some(else_steps) = else_branch {
-            info!("❌ Condition not met, executing else branch");
-            let mut results = Vec::new();
-            for step in else_steps {
-                let result = self.execute_step(step).await?;
-                results.push(result);
-            }
-            Ok(serde_json::json!({"branch": "else", "results": results}))
-        } else {
-            info!("❌ Condition not met, no else branch");
-            Ok(serde_json::json!({"branch": "none"}))
-        }
-    }
-
-    async fn execute_script(&self, code: &str) -> Result<serde_json::Value> {
-        let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
-        let expanded_code = self.expand_template(code)?;
-        
-        info!("📜 Executing script");
-        let result = browser.execute_script(&expanded_code, vec![]).await?;
-        
-        Ok(result.json().clone())
-    }
-
-    async fn execute_parallel_steps(&mut self, steps: &[WorkflowStep]) -> Result<Vec<ExecutionEntry>> {
-        use futures::future::join_all;
-        
-        let mut handles = Vec::new();
-        
-        for step in steps {
-            let step_clone = step.clone();
-            let handle = tokio::spawn(async move {
-                // Create a temporary engine for parallel execution
-                // In a real implementation, this would share state properly
-                ExecutionEntry {
-                    timestamp: Utc::now(),
-                    step_name: step_clone.name,
-                    action: format!("{:?}", step_clone.action),
-                    success: true, // Simplified for PoC
-                    duration_ms: 0,
-                    error: None,
-                    data: Some(serde_json::json!({"parallel": true})),
-                }
-            });
-            handles.push(handle);
-        }
-        
-        let results = join_all(handles).await;
-        let mut entries = Vec::new();
-        
-        for result in results {
-            match result {
-                Ok(entry) => entries.push(entry),
-                Err(e) => {
-                    error!("Parallel step failed: {}", e);
-                }
-            }
-        }
-        
-        Ok(entries)
-    }
-
-    fn evaluate_condition<'a>(&'a self, condition: &'a Condition) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<bool>> + Send + 'a>> {
-        Box::pin(async move {
-        match condition {
-            Condition::ElementExists { selector } => {
-                let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
-                let expanded_selector = self.expand_template(selector)?;
-                browser.element_exists(&expanded_selector).await
-            }
-            
-            Condition::TextContains { text } => {
-                let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
-                let expanded_text = self.expand_template(text)?;
-                let page_text = browser.get_page_text().await?;
-                Ok(page_text.contains(&expanded_text))
-            }
-            
-            Condition::VariableEquals { var, value } => {
-                let actual = self.variables.get(var);
-                Ok(actual == Some(value))
-            }
-            
-            Condition::VariableGreaterThan { var, value } => {
-                if let Some(actual) = self.variables.get(var) {
-                    if let Some(num) = actual.as_f64() {
-                        return Ok(num > *value);
-                    }
-                }
-                Ok(false)
-            }
-            
-            Condition::VariableLessThan { var, value } => {
-                if let Some(actual) = self.variables.get(var) {
-                    if let Some(num) = actual.as_f64() {
-                        return Ok(num < *value);
-                    }
-                }
-                Ok(false)
-            }
-            
-            Condition::Not { condition } => {
-                Ok(!self.evaluate_condition(condition).await?)
-            }
-            
-            Condition::And { conditions } => {
-                for cond in conditions {
-                    if !self.evaluate_condition(cond).await? {
-                        return Ok(false);
-                    }
-                }
-                Ok(true)
-            }
-            
-            Condition::Or { conditions } => {
-                for cond in conditions {
-                    if self.evaluate_condition(cond).await? {
-                        return Ok(true);
-                    }
-                }
-                Ok(false)
-            }
-        }
-        })
-    }
-
-    fn expand_template(&self, template: &str) -> Result<String> {
-        let mut result = template.to_string();
-        
-        // Simple template expansion using {{variable}} syntax
-        for (key, value) in &self.variables {
-            let placeholder = format!("{{{{{}}}}}", key);
-            let replacement = match value {
-                serde_json::Value::String(s) => s.clone(),
-                serde_json::Value::Number(n) => n.to_string(),
-                serde_json::Value::Bool(b) => b.to_string(),
-                _ => value.to_string(),
-            };
-            result = result.replace(&placeholder, &replacement);
-        }
-        
-        Ok(result)
-    }
-
-    pub async fn cleanup(&mut self) -> Result<()> {
-        // Browser cleanup is handled separately - cannot take ownership here
-        self.browser = None;
-        Ok(())
-    }
-}
-
-impl Workflow {
-    pub fn from_yaml(yaml_str: &str) -> Result<Self> {
-        serde_yaml::from_str(yaml_str)
-            .context("Failed to parse workflow YAML")
-    }
-
-    pub fn from_json(json_str: &str) -> Result<Self> {
-        serde_json::from_str(json_str)
-            .context("Failed to parse workflow JSON")
-    }
-
-    pub fn to_yaml(&self) -> Result<String> {
-        serde_yaml::to_string(self)
-            .context("Failed to serialize workflow to YAML")
-    }
-
-    pub fn to_json(&self) -> Result<String> {
-        serde_json::to_string_pretty(self)
-            .context("Failed to serialize workflow to JSON")
-    }
-}
\ No newline at end of file
+use anyhow::{Result, Context as AnyhowContext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn, error};
+use crate::{SimpleBrowser, CostTracker, ScreenshotOptions};
+use crate::workflow_webhooks::WebhookConfig;
+use crate::report::ReportStore;
+use crate::error_recovery::{ErrorContext, ErrorRecoveryManager, RecoveryAction, RecoveryAttempt, RecoveryResult, ResolutionStatus};
+use std::sync::Arc;
+use tokio::time::sleep;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use lazy_static::lazy_static;
+
+/// Iteration cap of loops that do not set their own `limit`
+const DEFAULT_MAX_LOOP_ITERATIONS: usize = 100;
+
+lazy_static! {
+    /// `{{name}}` or `{{name.field.0}}` placeholders in templates
+    static ref TEMPLATE_PLACEHOLDER: Regex = Regex::new(r"\{\{\s*([A-Za-z0-9_.-]+)\s*\}\}").unwrap();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workflow {
+    pub name: String,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub inputs: Option<Vec<InputDefinition>>,
+    #[serde(default)]
+    pub variables: HashMap<String, serde_json::Value>,
+    pub steps: Vec<WorkflowStep>,
+    pub parallel: Option<bool>,
+    pub on_error: Option<ErrorStrategy>,
+    pub timeout: Option<u64>,
+    /// Endpoints notified when a run completes or fails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputDefinition {
+    pub name: String,
+    pub input_type: String,
+    pub required: Option<bool>,
+    pub default: Option<serde_json::Value>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    pub name: String,
+    pub action: ActionType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_error: Option<ErrorStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_as: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Files collected into the run's artifact store once the step succeeds
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<ArtifactSpec>,
+}
+
+/// Artifact a step declares; names may use `{{variables}}` and default to the step name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArtifactSpec {
+    /// Screenshot of the page after the step
+    Screenshot {
+        name: Option<String>,
+        #[serde(default)]
+        full_page: bool,
+    },
+    /// The step's result, or the variable `from`, as a JSON file
+    Json {
+        name: Option<String>,
+        from: Option<String>,
+    },
+    /// File fetched from `url` with the browser's cookies
+    Download {
+        url: String,
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Screenshot,
+    Json,
+    Download,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActionType {
+    Navigate {
+        url: String,
+        #[serde(default)]
+        screenshot: bool,
+    },
+    Click {
+        selector: String,
+        #[serde(default)]
+        wait_after: u64,
+    },
+    Fill {
+        selector: String,
+        value: String,
+    },
+    Extract {
+        selector: String,
+        attribute: Option<String>,
+        /// Extract from every matching element into a list instead of the first match only
+        #[serde(default)]
+        all: bool,
+    },
+    Wait {
+        #[serde(flatten)]
+        wait_type: WaitType,
+    },
+    Assert {
+        #[serde(flatten)]
+        assertion: AssertionType,
+    },
+    /// Repeat `do` for every item of a list variable (`over`, dotted paths allowed) or of
+    /// inline `items`; `type: for_each` is accepted as well as `type: loop`
+    #[serde(alias = "for_each")]
+    Loop {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        over: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        items: Option<Vec<serde_json::Value>>,
+        /// Variable holding the current item, in addition to `_loop_item`
+        #[serde(rename = "as", default, skip_serializing_if = "Option::is_none")]
+        item_var: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
+        #[serde(rename = "do")]
+        body: Vec<WorkflowStep>,
+    },
+    /// `if`/`else` branching; `type: if` is accepted as well as `type: conditional`
+    #[serde(alias = "if")]
+    Conditional {
+        #[serde(rename = "if")]
+        condition: Condition,
+        #[serde(rename = "then")]
+        then_branch: Vec<WorkflowStep>,
+        #[serde(rename = "else")]
+        else_branch: Option<Vec<WorkflowStep>>,
+    },
+    Script {
+        code: String,
+    },
+    Parallel {
+        steps: Vec<WorkflowStep>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "wait_for", rename_all = "snake_case")]
+pub enum WaitType {
+    Element { selector: String },
+    Text { text: String },
+    Url { pattern: String },
+    Time { seconds: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "assert", rename_all = "snake_case")]
+pub enum AssertionType {
+    ElementExists { selector: String },
+    TextContains { text: String },
+    UrlMatches { pattern: String },
+    ElementCount { selector: String, count: usize },
+    Title { expected: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum Condition {
+    ElementExists { selector: String },
+    TextContains { text: String },
+    VariableEquals { var: String, value: serde_json::Value },
+    VariableGreaterThan { var: String, value: f64 },
+    VariableLessThan { var: String, value: f64 },
+    /// The variable is set and not null, empty or blank (e.g. an extraction found something)
+    VariableExists { var: String },
+    VariableContains { var: String, value: String },
+    VariableMatches { var: String, pattern: String },
+    /// Outcome of an earlier step, referenced by its name
+    StepStatus { step: String, status: StepStatus },
+    Not { condition: Box<Condition> },
+    And { conditions: Vec<Condition> },
+    Or { conditions: Vec<Condition> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// What happens when a step fails, set per step or as the workflow default. Written as
+/// `abort` (or `fail`), `continue`, `retry`, `retry(3, linear, 2s)`, `fallback_step(Name)`,
+/// or in map form: `{retry: {attempts: 3}}`, `{fallback: {steps: [...]}}`, `{fallback_step: Name}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "ErrorStrategyRepr")]
+pub enum ErrorStrategy {
+    /// Stop the workflow
+    Abort,
+    /// Record the failure and go on with the next step
+    Continue,
+    /// Run the step again, then go on with the next step if it still fails
+    Retry(RetryPolicy),
+    /// Run these steps after the failed one, which still counts as failed, and go on
+    Fallback { steps: Vec<WorkflowStep> },
+    /// Run the named top-level step after the failed one, which still counts as failed, and go on
+    FallbackStep(String),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ErrorStrategyRepr {
+    Short(String),
+    Full(ErrorStrategyFull),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ErrorStrategyFull {
+    Retry(RetryPolicy),
+    Fallback { steps: Vec<WorkflowStep> },
+    FallbackStep(String),
+}
+
+impl TryFrom<ErrorStrategyRepr> for ErrorStrategy {
+    type Error = String;
+
+    fn try_from(repr: ErrorStrategyRepr) -> std::result::Result<Self, String> {
+        match repr {
+            ErrorStrategyRepr::Short(short) => short.parse(),
+            ErrorStrategyRepr::Full(ErrorStrategyFull::Retry(policy)) => Ok(ErrorStrategy::Retry(policy)),
+            ErrorStrategyRepr::Full(ErrorStrategyFull::Fallback { steps }) => Ok(ErrorStrategy::Fallback { steps }),
+            ErrorStrategyRepr::Full(ErrorStrategyFull::FallbackStep(step)) => Ok(ErrorStrategy::FallbackStep(step)),
+        }
+    }
+}
+
+/// Written in the forms it is read from; serde_yaml would otherwise tag variants (`!retry`),
+/// which the untagged representation cannot read back
+impl Serialize for ErrorStrategy {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        #[derive(Serialize)]
+        struct FallbackSteps<'a> {
+            steps: &'a [WorkflowStep],
+        }
+
+        let mut map = match self {
+            ErrorStrategy::Abort => return serializer.serialize_str("abort"),
+            ErrorStrategy::Continue => return serializer.serialize_str("continue"),
+            _ => serializer.serialize_map(Some(1))?,
+        };
+        match self {
+            ErrorStrategy::Retry(policy) => map.serialize_entry("retry", policy)?,
+            ErrorStrategy::Fallback { steps } => map.serialize_entry("fallback", &FallbackSteps { steps })?,
+            ErrorStrategy::FallbackStep(step) => map.serialize_entry("fallback_step", step)?,
+            ErrorStrategy::Abort | ErrorStrategy::Continue => unreachable!(),
+        }
+        map.end()
+    }
+}
+
+impl std::str::FromStr for ErrorStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        let s = s.trim();
+        let (name, args) = match s.split_once('(') {
+            Some((name, rest)) => {
+                let args = rest.strip_suffix(')').ok_or_else(|| format!("Missing ')' in on_error '{}'", s))?;
+                (name.trim(), Some(args))
+            }
+            None => (s, None),
+        };
+
+        match (name, args) {
+            ("abort" | "fail", None) => Ok(ErrorStrategy::Abort),
+            ("continue", None) => Ok(ErrorStrategy::Continue),
+            ("retry", None) => Ok(ErrorStrategy::Retry(RetryPolicy::default())),
+            ("retry", Some(args)) => {
+                let mut policy = RetryPolicy::default();
+                let mut args = args.split(',').map(str::trim).filter(|arg| !arg.is_empty());
+                if let Some(attempts) = args.next() {
+                    policy.attempts = attempts.parse().map_err(|_| format!("Invalid retry count '{}'", attempts))?;
+                }
+                for arg in args {
+                    match arg {
+                        "fixed" => policy.backoff = Backoff::Fixed,
+                        "linear" => policy.backoff = Backoff::Linear,
+                        "exponential" => policy.backoff = Backoff::Exponential,
+                        delay => policy.delay_ms = parse_delay_ms(delay)
+                            .ok_or_else(|| format!("Invalid retry backoff '{}': use fixed, linear, exponential or a delay like 2s", delay))?,
+                    }
+                }
+                Ok(ErrorStrategy::Retry(policy))
+            }
+            ("fallback_step", Some(step)) if !step.trim().is_empty() => Ok(ErrorStrategy::FallbackStep(step.trim().to_string())),
+            _ => Err(format!("Unknown on_error '{}': expected abort, continue, retry(n, backoff), fallback_step(name) or a fallback map", s)),
+        }
+    }
+}
+
+/// `500ms`, `2s` or a plain number of seconds
+fn parse_delay_ms(delay: &str) -> Option<u64> {
+    if let Some(ms) = delay.strip_suffix("ms") {
+        return ms.trim().parse().ok();
+    }
+    let seconds: f64 = delay.strip_suffix('s').unwrap_or(delay).trim().parse().ok()?;
+    (seconds >= 0.0).then_some((seconds * 1000.0) as u64)
+}
+
+/// Longest wait between two retries, whatever the backoff
+const MAX_RETRY_DELAY_MS: u64 = 30_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    #[serde(default = "default_retry_attempts")]
+    pub attempts: u32,
+    #[serde(default)]
+    pub backoff: Backoff,
+    /// Wait before the first retry
+    #[serde(default = "default_retry_delay_ms")]
+    pub delay_ms: u64,
+}
+
+fn default_retry_attempts() -> u32 {
+    3
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1000
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: default_retry_attempts(),
+            backoff: Backoff::default(),
+            delay_ms: default_retry_delay_ms(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the `retry`th retry, counting from 1
+    pub fn delay_before(&self, retry: u32) -> Duration {
+        let delay = match self.backoff {
+            Backoff::Fixed => self.delay_ms,
+            Backoff::Linear => self.delay_ms.saturating_mul(retry as u64),
+            Backoff::Exponential => self.delay_ms.saturating_mul(1u64 << retry.saturating_sub(1).min(16)),
+        };
+        Duration::from_millis(delay.min(MAX_RETRY_DELAY_MS))
+    }
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        Self {
+            attempts: config.max_attempts.saturating_sub(1),
+            backoff: if config.exponential_backoff.unwrap_or(false) { Backoff::Exponential } else { Backoff::Fixed },
+            delay_ms: config.delay_seconds * 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backoff {
+    Fixed,
+    Linear,
+    #[default]
+    Exponential,
+}
+
+/// Retry settings of the `retry` step field; `on_error: retry(...)` is the newer form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub delay_seconds: u64,
+    pub exponential_backoff: Option<bool>,
+}
+
+/// How a failed step was dealt with under its `on_error` policy
+enum StepRecovery {
+    Continue,
+    /// Fallback steps ran, with these results
+    Fallback(serde_json::Value),
+    Abort,
+}
+
+pub struct WorkflowEngine {
+    browser: Option<SimpleBrowser>,
+    pub cost_tracker: CostTracker,
+    variables: HashMap<String, serde_json::Value>,
+    execution_log: Vec<ExecutionEntry>,
+    /// Latest outcome of every step run so far, including steps nested in branches and loops
+    step_status: HashMap<String, StepStatus>,
+    checkpoints: Option<CheckpointStore>,
+    artifacts: Option<ArtifactStore>,
+    reports: Option<ReportStore>,
+    run_id: Option<uuid::Uuid>,
+    /// Workflow-level `on_error`, for steps without their own
+    default_on_error: Option<ErrorStrategy>,
+    /// Top-level steps of the running workflow, the targets of `fallback_step`
+    named_steps: HashMap<String, WorkflowStep>,
+    error_recovery: Option<Arc<ErrorRecoveryManager>>,
+}
+
+/// Page the browser was on when a checkpoint was taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSnapshot {
+    pub url: String,
+    pub cookies: Vec<serde_json::Value>,
+}
+
+/// Progress of a sequential workflow run, saved after every top-level step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowCheckpoint {
+    pub run_id: uuid::Uuid,
+    pub workflow: Workflow,
+    /// Index of the first step still to run
+    pub next_step: usize,
+    pub completed: bool,
+    pub steps_executed: usize,
+    pub steps_failed: usize,
+    pub variables: HashMap<String, serde_json::Value>,
+    pub step_status: HashMap<String, StepStatus>,
+    pub execution_log: Vec<ExecutionEntry>,
+    pub page: Option<PageSnapshot>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Directory of workflow checkpoints, one JSON file per run. Checkpoints hold the
+/// run's variables and cookies, so the directory should be private to the server.
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    dir: std::path::PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, run_id: uuid::Uuid) -> std::path::PathBuf {
+        self.dir.join(format!("{}.json", run_id))
+    }
+
+    pub fn save(&self, checkpoint: &WorkflowCheckpoint) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(checkpoint.run_id), serde_json::to_string(checkpoint)?)?;
+        Ok(())
+    }
+
+    pub fn load(&self, run_id: uuid::Uuid) -> Result<WorkflowCheckpoint> {
+        let content = std::fs::read_to_string(self.path(run_id))
+            .with_context(|| format!("No checkpoint for workflow run {}", run_id))?;
+        serde_json::from_str(&content).context("Failed to parse workflow checkpoint")
+    }
+}
+
+impl Default for CheckpointStore {
+    fn default() -> Self {
+        Self::new("data/workflow_runs")
+    }
+}
+
+/// File collected during a workflow run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    /// File name, unique within the run
+    pub name: String,
+    pub kind: ArtifactKind,
+    /// Step that produced the artifact
+    pub step: String,
+    pub content_type: String,
+    pub size: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Per-run artifact directories, each with a `manifest.json` listing its files
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    dir: std::path::PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn run_dir(&self, run_id: uuid::Uuid) -> std::path::PathBuf {
+        self.dir.join(run_id.to_string())
+    }
+
+    /// Store a file for a run; the name is reduced to a plain file name and
+    /// suffixed when the run already has an artifact of that name
+    pub fn save(&self, run_id: uuid::Uuid, step: &str, name: &str, kind: ArtifactKind, content_type: &str, data: &[u8]) -> Result<Artifact> {
+        let run_dir = self.run_dir(run_id);
+        std::fs::create_dir_all(&run_dir)?;
+        let mut artifacts = self.list(run_id)?;
+
+        let base: String = name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+            .collect();
+        let base = base.trim_start_matches('.');
+        let base = if base.is_empty() { "artifact" } else { base };
+        let (stem, extension) = match base.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (base, String::new()),
+        };
+        let mut name = base.to_string();
+        let mut n = 2;
+        while name == "manifest.json" || artifacts.iter().any(|a| a.name == name) {
+            name = format!("{}-{}{}", stem, n, extension);
+            n += 1;
+        }
+
+        std::fs::write(run_dir.join(&name), data)?;
+        let artifact = Artifact {
+            name,
+            kind,
+            step: step.to_string(),
+            content_type: content_type.to_string(),
+            size: data.len(),
+            created_at: Utc::now(),
+        };
+        artifacts.push(artifact.clone());
+        std::fs::write(run_dir.join("manifest.json"), serde_json::to_string_pretty(&artifacts)?)?;
+        Ok(artifact)
+    }
+
+    /// Artifacts of a run, oldest first; empty when it has none
+    pub fn list(&self, run_id: uuid::Uuid) -> Result<Vec<Artifact>> {
+        let manifest = self.run_dir(run_id).join("manifest.json");
+        if !manifest.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&manifest)?;
+        serde_json::from_str(&content).context("Failed to parse artifact manifest")
+    }
+
+    /// An artifact listed in the run's manifest and its contents
+    pub fn read(&self, run_id: uuid::Uuid, name: &str) -> Result<(Artifact, Vec<u8>)> {
+        let artifact = self.list(run_id)?
+            .into_iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Run {} has no artifact '{}'", run_id, name))?;
+        let data = std::fs::read(self.run_dir(run_id).join(&artifact.name))?;
+        Ok((artifact, data))
+    }
+}
+
+impl Default for ArtifactStore {
+    fn default() -> Self {
+        Self::new("data/workflow_artifacts")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionEntry {
+    pub timestamp: DateTime<Utc>,
+    pub step_name: String,
+    pub action: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowResult {
+    /// Id to resume the run with, when the engine checkpoints
+    #[serde(default)]
+    pub run_id: Option<uuid::Uuid>,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub steps_executed: usize,
+    pub steps_failed: usize,
+    pub variables: HashMap<String, serde_json::Value>,
+    pub execution_log: Vec<ExecutionEntry>,
+    pub cost: f64,
+    /// Files collected by the run, when the engine has an artifact store
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+}
+
+impl WorkflowResult {
+    /// Variables the run set, such as `store_as` results; inputs, defaults and
+    /// internal `_` variables are left out
+    pub fn extracted_data(&self, workflow: &Workflow) -> HashMap<String, serde_json::Value> {
+        let inputs: Vec<&str> = workflow.inputs.iter().flatten().map(|input| input.name.as_str()).collect();
+        self.variables.iter()
+            .filter(|(name, value)| {
+                !name.starts_with('_')
+                    && !inputs.contains(&name.as_str())
+                    && workflow.variables.get(*name) != Some(*value)
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
+impl WorkflowEngine {
+    pub fn new(cost_tracker: CostTracker) -> Self {
+        Self {
+            browser: None,
+            cost_tracker,
+            variables: HashMap::new(),
+            execution_log: Vec::new(),
+            step_status: HashMap::new(),
+            checkpoints: None,
+            artifacts: None,
+            reports: None,
+            run_id: None,
+            default_on_error: None,
+            named_steps: HashMap::new(),
+            error_recovery: None,
+        }
+    }
+    
+    /// API-friendly constructor without CostTracker
+    pub fn new_simple() -> Self {
+        Self {
+            browser: None,
+            cost_tracker: CostTracker::new(100.0), // Default budget
+            variables: HashMap::new(),
+            execution_log: Vec::new(),
+            step_status: HashMap::new(),
+            checkpoints: None,
+            artifacts: None,
+            reports: None,
+            run_id: None,
+            default_on_error: None,
+            named_steps: HashMap::new(),
+            error_recovery: None,
+        }
+    }
+    
+    /// Save a checkpoint after every step of sequential runs, so they can be resumed
+    pub fn with_checkpoints(mut self, store: CheckpointStore) -> Self {
+        self.checkpoints = Some(store);
+        self
+    }
+    
+    /// Collect step artifacts and navigation screenshots into the store, under the run id
+    pub fn with_artifacts(mut self, store: ArtifactStore) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+    
+    /// Write an HTML report of every finished run to the store, under the run id
+    pub fn with_reports(mut self, store: ReportStore) -> Self {
+        self.reports = Some(store);
+        self
+    }
+    
+    /// Classify step failures with the error recovery manager, to stop retrying errors
+    /// that cannot go away, and record them in its history and metrics
+    pub fn with_error_recovery(mut self, manager: Arc<ErrorRecoveryManager>) -> Self {
+        self.error_recovery = Some(manager);
+        self
+    }
+    
+    /// Id of the current or last run
+    pub fn run_id(&self) -> Option<uuid::Uuid> {
+        self.run_id
+    }
+    
+    /// Set a variable for use in templates
+    pub async fn set_variable(&mut self, name: &str, value: serde_json::Value) {
+        self.variables.insert(name.to_string(), value);
+    }
+    
+    /// Simple execute method for API
+    pub async fn execute(&mut self, workflow: &Workflow) -> Result<WorkflowResult> {
+        self.execute_workflow(workflow, None).await
+    }
+
+    pub async fn execute_workflow(&mut self, workflow: &Workflow, inputs: Option<HashMap<String, serde_json::Value>>) -> Result<WorkflowResult> {
+        let start_time = std::time::Instant::now();
+        info!("🎭 Starting workflow: {}", workflow.name);
+
+        // Initialize variables with inputs
+        self.variables = workflow.variables.clone();
+        self.step_status.clear();
+        self.execution_log.clear();
+        self.load_error_policies(workflow);
+        if let Some(inputs) = inputs {
+            for (key, value) in inputs {
+                self.variables.insert(key, value);
+            }
+        }
+
+        // Validate required inputs
+        if let Some(input_defs) = &workflow.inputs {
+            for input_def in input_defs {
+                if input_def.required.unwrap_or(false) && !self.variables.contains_key(&input_def.name) {
+                    if let Some(default) = &input_def.default {
+                        self.variables.insert(input_def.name.clone(), default.clone());
+                    } else {
+                        return Err(anyhow::anyhow!("Required input '{}' not provided", input_def.name));
+                    }
+                }
+            }
+        }
+
+        // Initialize browser if needed
+        if self.browser.is_none() {
+            info!("🌐 Initializing browser for workflow");
+            self.browser = Some(SimpleBrowser::new().await?);
+        }
+
+        self.run_id = Some(uuid::Uuid::new_v4());
+
+        // Execute steps
+        let mut steps_executed = 0;
+        let mut steps_failed = 0;
+
+        if workflow.parallel.unwrap_or(false) {
+            // Execute steps in parallel
+            info!("⚡ Executing {} steps in parallel", workflow.steps.len());
+            let results = self.execute_parallel_steps(&workflow.steps).await?;
+            for result in results {
+                steps_executed += 1;
+                if !result.success {
+                    steps_failed += 1;
+                }
+                self.execution_log.push(result);
+            }
+        } else {
+            // Execute steps sequentially
+            (steps_executed, steps_failed) = self.execute_sequential(workflow, 0, steps_executed, steps_failed).await;
+        }
+
+        self.finish_workflow(workflow, start_time, steps_executed, steps_failed)
+    }
+
+    /// Continue a checkpointed run from the step after its last successful one,
+    /// restoring its variables and the page and cookies the browser was on
+    pub async fn resume(&mut self, run_id: uuid::Uuid) -> Result<WorkflowResult> {
+        let store = self.checkpoints.clone()
+            .ok_or_else(|| anyhow::anyhow!("Checkpointing is not enabled for this engine"))?;
+        let checkpoint = store.load(run_id)?;
+        if checkpoint.completed {
+            return Err(anyhow::anyhow!("Workflow run {} already completed", run_id));
+        }
+
+        let start_time = std::time::Instant::now();
+        let workflow = checkpoint.workflow;
+        info!("⏯️ Resuming workflow '{}' (run {}) at step {}/{}", workflow.name, run_id, checkpoint.next_step + 1, workflow.steps.len());
+
+        self.run_id = Some(run_id);
+        self.load_error_policies(&workflow);
+        self.variables = checkpoint.variables;
+        self.step_status = checkpoint.step_status;
+        self.execution_log = checkpoint.execution_log;
+
+        if self.browser.is_none() {
+            info!("🌐 Initializing browser for workflow");
+            self.browser = Some(SimpleBrowser::new().await?);
+        }
+        if let Some(page) = &checkpoint.page {
+            self.restore_page(page).await;
+        }
+
+        let (steps_executed, steps_failed) = self.execute_sequential(&workflow, checkpoint.next_step, checkpoint.steps_executed, checkpoint.steps_failed).await;
+        self.finish_workflow(&workflow, start_time, steps_executed, steps_failed)
+    }
+
+    /// Run the steps from index `start` on, checkpointing after each one, and
+    /// return the running totals of executed and failed steps
+    async fn execute_sequential(&mut self, workflow: &Workflow, start: usize, mut steps_executed: usize, mut steps_failed: usize) -> (usize, usize) {
+        for (index, step) in workflow.steps.iter().enumerate().skip(start) {
+            let step_start = std::time::Instant::now();
+            let step_result = self.execute_step(step).await;
+            steps_executed += 1;
+
+            let (success, error_msg, data, stop) = match step_result {
+                Ok(result) => (true, None, Some(result), false),
+                Err(e) => match self.recover_step(step, &e).await {
+                    StepRecovery::Fallback(result) => (false, Some(e.to_string()), Some(result), false),
+                    StepRecovery::Continue => (false, Some(e.to_string()), None, false),
+                    StepRecovery::Abort => {
+                        error!("Step '{}' failed, stopping workflow", step.name);
+                        (false, Some(e.to_string()), None, true)
+                    }
+                },
+            };
+            if !success {
+                steps_failed += 1;
+            }
+
+            self.execution_log.push(ExecutionEntry {
+                timestamp: Utc::now(),
+                step_name: step.name.clone(),
+                action: format!("{:?}", step.action),
+                success,
+                duration_ms: step_start.elapsed().as_millis() as u64,
+                error: error_msg,
+                data,
+            });
+
+            if stop {
+                // A resumed run starts again at the failed step, which then counts afresh
+                self.save_checkpoint(workflow, index, steps_executed - 1, steps_failed - 1).await;
+                break;
+            }
+            self.save_checkpoint(workflow, index + 1, steps_executed, steps_failed).await;
+        }
+
+        (steps_executed, steps_failed)
+    }
+
+    fn finish_workflow(&mut self, workflow: &Workflow, start_time: std::time::Instant, steps_executed: usize, steps_failed: usize) -> Result<WorkflowResult> {
+        // Calculate cost
+        let workflow_cost = self.cost_tracker.estimate_browser_operation_cost() * steps_executed as f64;
+        self.cost_tracker.record_operation(
+            "workflow".to_string(),
+            format!("Workflow: {}", workflow.name),
+            workflow_cost,
+            steps_failed == 0,
+        )?;
+
+        let duration = start_time.elapsed();
+        
+        let artifacts = match (&self.artifacts, self.run_id) {
+            (Some(store), Some(run_id)) => store.list(run_id).unwrap_or_else(|e| {
+                warn!("Failed to list artifacts of run {}: {}", run_id, e);
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        
+        let result = WorkflowResult {
+            run_id: self.run_id,
+            success: steps_failed == 0,
+            duration_ms: duration.as_millis() as u64,
+            steps_executed,
+            steps_failed,
+            variables: self.variables.clone(),
+            execution_log: self.execution_log.clone(),
+            cost: workflow_cost,
+            artifacts,
+        };
+        
+        if let (Some(store), Some(run_id)) = (&self.reports, self.run_id) {
+            let html = crate::report::render_html(workflow, &result, self.artifacts.as_ref());
+            if let Err(e) = store.save(run_id, &html) {
+                warn!("Failed to save report of run {}: {}", run_id, e);
+            }
+        }
+        
+        Ok(result)
+    }
+
+    async fn save_checkpoint(&self, workflow: &Workflow, next_step: usize, steps_executed: usize, steps_failed: usize) {
+        let (Some(store), Some(run_id)) = (&self.checkpoints, self.run_id) else {
+            return;
+        };
+
+        let page = match &self.browser {
+            Some(browser) => match (browser.current_url().await, browser.get_cookies().await) {
+                (Ok(url), Ok(cookies)) => Some(PageSnapshot { url, cookies }),
+                _ => None,
+            },
+            None => None,
+        };
+
+        let checkpoint = WorkflowCheckpoint {
+            run_id,
+            workflow: workflow.clone(),
+            next_step,
+            completed: next_step >= workflow.steps.len(),
+            steps_executed,
+            steps_failed,
+            variables: self.variables.clone(),
+            step_status: self.step_status.clone(),
+            execution_log: self.execution_log.clone(),
+            page,
+            updated_at: Utc::now(),
+        };
+        if let Err(e) = store.save(&checkpoint) {
+            warn!("Failed to save checkpoint of run {}: {}", run_id, e);
+        }
+    }
+
+    async fn restore_page(&self, page: &PageSnapshot) {
+        let Some(browser) = &self.browser else {
+            return;
+        };
+        // Cookies can only be set for the domain the browser is on
+        if let Err(e) = browser.navigate_to(&page.url).await {
+            warn!("Failed to reopen {} for resumed run: {}", page.url, e);
+            return;
+        }
+        if let Err(e) = browser.add_cookies(&page.cookies).await {
+            warn!("Failed to restore cookies for resumed run: {}", e);
+        }
+        let _ = browser.refresh().await;
+    }
+
+    fn execute_step<'a>(&'a mut self, step: &'a WorkflowStep) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<serde_json::Value>> + Send + 'a>> {
+        Box::pin(async move {
+        info!("📍 Executing step: {}", step.name);
+
+        // Check condition if present
+        if let Some(condition) = &step.condition {
+            if !self.evaluate_condition(condition).await? {
+                info!("Skipping step '{}' - condition not met", step.name);
+                self.step_status.insert(step.name.clone(), StepStatus::Skipped);
+                return Ok(serde_json::json!({"skipped": true}));
+            }
+        }
+
+        // Execute with retry if configured
+        let policy = match (&step.retry, step.on_error.as_ref().or(self.default_on_error.as_ref())) {
+            (Some(retry), _) => Some(RetryPolicy::from(retry)),
+            (None, Some(ErrorStrategy::Retry(policy))) => Some(*policy),
+            _ => None,
+        };
+        let max_attempts = policy.map_or(1, |p| p.attempts + 1);
+        let started = std::time::Instant::now();
+        let mut last_error = None;
+        let mut retries = Vec::new();
+
+        for attempt in 1..=max_attempts {
+            let attempt_start = std::time::Instant::now();
+            let mut delay = Duration::ZERO;
+            if attempt > 1 {
+                delay = policy.map(|p| p.delay_before(attempt - 1)).unwrap_or_default();
+                info!("Retry attempt {}/{} after {}ms delay", attempt, max_attempts, delay.as_millis());
+                sleep(delay).await;
+            }
+
+            let outcome = self.execute_action(&step.action).await;
+            if attempt > 1 {
+                retries.push(RecoveryAttempt {
+                    attempt_number: attempt - 1,
+                    strategy_name: "workflow_step_retry".to_string(),
+                    actions_taken: vec![RecoveryAction::Retry { delay_ms: delay.as_millis() as u64 }],
+                    timestamp: Utc::now(),
+                    result: match &outcome {
+                        Ok(_) => RecoveryResult::Success { details: format!("Step '{}' succeeded", step.name) },
+                        Err(e) => RecoveryResult::Failed { reason: e.to_string() },
+                    },
+                    duration_ms: attempt_start.elapsed().as_millis() as u64,
+                });
+            }
+
+            match outcome {
+                Ok(result) => {
+                    // Store result if requested
+                    if let Some(var_name) = &step.store_as {
+                        let var_name = self.expand_template(var_name)?;
+                        self.variables.insert(var_name.clone(), result.clone());
+                        info!("Stored result in variable '{}'", var_name);
+                    }
+                    for spec in &step.artifacts {
+                        // A missing artifact is reported but does not fail the step
+                        if let Err(e) = self.collect_artifact(step, spec, &result).await {
+                            warn!("Failed to collect artifact of step '{}': {}", step.name, e);
+                        }
+                    }
+                    self.step_status.insert(step.name.clone(), StepStatus::Succeeded);
+                    if let (Some(error), false) = (&last_error, retries.is_empty()) {
+                        let recovered_in = started.elapsed().as_millis() as u64;
+                        self.report_step_error(step, error, retries, ResolutionStatus::Recovered, Some(recovered_in)).await;
+                    }
+                    return Ok(result);
+                }
+                Err(e) => {
+                    warn!("Step '{}' attempt {} failed: {}", step.name, attempt, e);
+                    let transient = match &self.error_recovery {
+                        Some(manager) if attempt < max_attempts => manager.classify(&e, &self.error_context(step)).await.0.is_transient(),
+                        _ => true,
+                    };
+                    last_error = Some(e);
+                    if !transient {
+                        warn!("Step '{}' failed with an error retrying cannot fix, giving up", step.name);
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.step_status.insert(step.name.clone(), StepStatus::Failed);
+        let error = last_error.unwrap_or_else(|| anyhow::anyhow!("Step failed after {} attempts", max_attempts));
+        self.report_step_error(step, &error, retries, ResolutionStatus::Unrecovered, None).await;
+        Err(error)
+        })
+    }
+
+    fn load_error_policies(&mut self, workflow: &Workflow) {
+        self.default_on_error = workflow.on_error.clone();
+        self.named_steps = workflow.steps.iter()
+            .map(|step| (step.name.clone(), step.clone()))
+            .collect();
+    }
+
+    /// Apply the `on_error` policy of a step that failed after any retries
+    async fn recover_step(&mut self, step: &WorkflowStep, error: &anyhow::Error) -> StepRecovery {
+        let fallback = match step.on_error.as_ref().or(self.default_on_error.as_ref()) {
+            Some(ErrorStrategy::Continue) | Some(ErrorStrategy::Retry(_)) => {
+                warn!("Step '{}' failed, continuing workflow: {}", step.name, error);
+                return StepRecovery::Continue;
+            }
+            Some(ErrorStrategy::Fallback { steps }) => steps.clone(),
+            Some(ErrorStrategy::FallbackStep(name)) => match self.named_steps.get(name) {
+                Some(fallback) => vec![fallback.clone()],
+                None => {
+                    error!("Fallback step '{}' of '{}' does not exist, continuing workflow", name, step.name);
+                    return StepRecovery::Continue;
+                }
+            },
+            Some(ErrorStrategy::Abort) | None => return StepRecovery::Abort,
+        };
+
+        info!("Executing fallback steps for '{}'", step.name);
+        let mut results = Vec::new();
+        for fallback_step in &fallback {
+            match self.execute_step(fallback_step).await {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    warn!("Fallback step '{}' for '{}' failed: {}", fallback_step.name, step.name, e);
+                    results.push(serde_json::json!({"error": e.to_string()}));
+                }
+            }
+        }
+        StepRecovery::Fallback(serde_json::json!({"error": error.to_string(), "fallback": results}))
+    }
+
+    /// Run nested steps, such as a branch, applying their `on_error` policies
+    async fn execute_nested_steps(&mut self, steps: &[WorkflowStep]) -> Result<Vec<serde_json::Value>> {
+        let mut results = Vec::new();
+        for step in steps {
+            match self.execute_step(step).await {
+                Ok(result) => results.push(result),
+                Err(e) => match self.recover_step(step, &e).await {
+                    StepRecovery::Fallback(result) => results.push(result),
+                    StepRecovery::Continue => results.push(serde_json::json!({"error": e.to_string()})),
+                    StepRecovery::Abort => return Err(e),
+                },
+            }
+        }
+        Ok(results)
+    }
+
+    fn error_context(&self, step: &WorkflowStep) -> ErrorContext {
+        let mut metadata = HashMap::new();
+        metadata.insert("workflow_step".to_string(), serde_json::json!(step.name));
+        if let Some(run_id) = self.run_id {
+            metadata.insert("workflow_run_id".to_string(), serde_json::json!(run_id));
+        }
+        ErrorContext {
+            task_type: None,
+            context_snapshot: None,
+            stack_trace: None,
+            metadata,
+            related_errors: Vec::new(),
+        }
+    }
+
+    async fn report_step_error(&self, step: &WorkflowStep, error: &anyhow::Error, retries: Vec<RecoveryAttempt>, resolution: ResolutionStatus, recovery_time_ms: Option<u64>) {
+        if let Some(manager) = &self.error_recovery {
+            manager.record_handled_error(error, self.error_context(step), retries, resolution, recovery_time_ms).await;
+        }
+    }
+
+    /// Save one declared artifact of a succeeded step; a no-op without an artifact store
+    async fn collect_artifact(&self, step: &WorkflowStep, spec: &ArtifactSpec, result: &serde_json::Value) -> Result<()> {
+        let (Some(store), Some(run_id)) = (&self.artifacts, self.run_id) else {
+            return Ok(());
+        };
+        let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
+        let name = |name: &Option<String>, default: String| -> Result<String> {
+            match name {
+                Some(name) => self.expand_template(name),
+                None => Ok(default),
+            }
+        };
+
+        let artifact = match spec {
+            ArtifactSpec::Screenshot { name: file_name, full_page } => {
+                let options = ScreenshotOptions { full_page: *full_page, ..Default::default() };
+                let data = browser.capture_screenshot(&options).await?;
+                let file_name = name(file_name, format!("{}.png", step.name))?;
+                store.save(run_id, &step.name, &file_name, ArtifactKind::Screenshot, "image/png", &data)?
+            }
+            ArtifactSpec::Json { name: file_name, from } => {
+                let value = match from {
+                    Some(var) => self.lookup_variable(var).cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Variable '{}' not found", var))?,
+                    None => result.clone(),
+                };
+                let file_name = name(file_name, format!("{}.json", step.name))?;
+                store.save(run_id, &step.name, &file_name, ArtifactKind::Json, "application/json", &serde_json::to_vec_pretty(&value)?)?
+            }
+            ArtifactSpec::Download { url, name: file_name } => {
+                let url = self.expand_template(url)?;
+                let cookies = browser.get_cookies().await?
+                    .iter()
+                    .filter_map(|c| Some(format!("{}={}", c.get("name")?.as_str()?, c.get("value")?.as_str()?)))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                let response = reqwest::Client::new()
+                    .get(&url)
+                    .header(reqwest::header::COOKIE, cookies)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                let content_type = response.headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = response.bytes().await?;
+                let default = url.split(['?', '#']).next().unwrap_or_default()
+                    .rsplit('/').next()
+                    .filter(|segment| !segment.is_empty())
+                    .unwrap_or("download")
+                    .to_string();
+                let file_name = name(file_name, default)?;
+                store.save(run_id, &step.name, &file_name, ArtifactKind::Download, &content_type, &data)?
+            }
+        };
+        info!("📦 Collected artifact '{}' ({} bytes)", artifact.name, artifact.size);
+        Ok(())
+    }
+
+    async fn execute_action(&mut self, action: &ActionType) -> Result<serde_json::Value> {
+        let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
+
+        match action {
+            ActionType::Navigate { url, screenshot } => {
+                info!("🌐 Navigating to: {}", url);
+                let expanded_url = self.expand_template(url)?;
+                browser.navigate_to(&expanded_url).await?;
+                
+                if *screenshot {
+                    let filename = format!("workflow_{}.png", Utc::now().format("%Y%m%d_%H%M%S"));
+                    if let (Some(store), Some(run_id)) = (&self.artifacts, self.run_id) {
+                        let data = browser.capture_screenshot(&ScreenshotOptions::default()).await?;
+                        let artifact = store.save(run_id, "navigate", &filename, ArtifactKind::Screenshot, "image/png", &data)?;
+                        return Ok(serde_json::json!({"screenshot": artifact.name, "artifact": true}));
+                    }
+                    browser.take_screenshot(&filename).await?;
+                    return Ok(serde_json::json!({"screenshot": filename}));
+                }
+                
+                Ok(serde_json::json!({"navigated": expanded_url}))
+            }
+            
+            ActionType::Click { selector, wait_after } => {
+                info!("🖱️ Clicking: {}", selector);
+                let expanded_selector = self.expand_template(selector)?;
+                browser.click_element(&expanded_selector).await?;
+                
+                if *wait_after > 0 {
+                    sleep(Duration::from_secs(*wait_after)).await;
+                }
+                
+                Ok(serde_json::json!({"clicked": expanded_selector}))
+            }
+            
+            ActionType::Fill { selector, value } => {
+                info!("✏️ Filling field: {}", selector);
+                let expanded_selector = self.expand_template(selector)?;
+                let expanded_value = self.expand_template(value)?;
+                browser.fill_field(&expanded_selector, &expanded_value).await?;
+                
+                Ok(serde_json::json!({"filled": expanded_selector, "value": expanded_value}))
+            }
+            
+            ActionType::Extract { selector, attribute, all } => {
+                info!("📋 Extracting from: {}", selector);
+                let expanded_selector = self.expand_template(selector)?;
+                
+                if *all {
+                    let mut values = Vec::new();
+                    for element in browser.find_elements(&expanded_selector).await? {
+                        let value = if let Some(attr) = attribute {
+                            element.attr(attr).await?
+                        } else {
+                            Some(element.text().await?)
+                        };
+                        if let Some(value) = value {
+                            values.push(serde_json::json!(value));
+                        }
+                    }
+                    info!("Extracted {} values from: {}", values.len(), expanded_selector);
+                    return Ok(serde_json::Value::Array(values));
+                }
+                
+                let extracted = if let Some(attr) = attribute {
+                    browser.get_attribute(&expanded_selector, attr).await?
+                } else {
+                    browser.get_text(&expanded_selector).await?
+                };
+                
+                Ok(serde_json::json!(extracted))
+            }
+            
+            ActionType::Wait { wait_type } => {
+                match wait_type {
+                    WaitType::Time { seconds } => {
+                        info!("⏳ Waiting {} seconds", seconds);
+                        sleep(Duration::from_secs(*seconds)).await;
+                    }
+                    WaitType::Element { selector } => {
+                        info!("⏳ Waiting for element: {}", selector);
+                        let expanded_selector = self.expand_template(selector)?;
+                        browser.wait_for_element(&expanded_selector, Duration::from_secs(30)).await?;
+                    }
+                    WaitType::Text { text } => {
+                        info!("⏳ Waiting for text: {}", text);
+                        let expanded_text = self.expand_template(text)?;
+                        browser.wait_for_text(&expanded_text, Duration::from_secs(30)).await?;
+                    }
+                    WaitType::Url { pattern } => {
+                        info!("⏳ Waiting for URL pattern: {}", pattern);
+                        let expanded_pattern = self.expand_template(pattern)?;
+                        browser.wait_for_url(&expanded_pattern, Duration::from_secs(30)).await?;
+                    }
+                }
+                Ok(serde_json::json!({"waited": true}))
+            }
+            
+            ActionType::Assert { assertion } => {
+                self.execute_assertion(assertion).await
+            }
+            
+            ActionType::Loop { over, items, item_var, limit, body } => {
+                self.execute_loop(over.as_deref(), items.as_ref(), item_var.as_deref(), *limit, body).await
+            }
+            
+            ActionType::Conditional { condition, then_branch, else_branch } => {
+                self.execute_conditional(condition, then_branch, else_branch.as_ref()).await
+            }
+            
+            ActionType::Script { code } => {
+                self.execute_script(code).await
+            }
+            
+            ActionType::Parallel { steps } => {
+                let results = self.execute_parallel_steps(steps).await?;
+                Ok(serde_json::json!(results))
+            }
+        }
+    }
+
+    async fn execute_assertion(&self, assertion: &AssertionType) -> Result<serde_json::Value> {
+        let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
+
+        match assertion {
+            AssertionType::ElementExists { selector } => {
+                let expanded_selector = self.expand_template(selector)?;
+                let exists = browser.element_exists(&expanded_selector).await?;
+                if !exists {
+                    return Err(anyhow::anyhow!("Assertion failed: Element '{}' does not exist", expanded_selector));
+                }
+                Ok(serde_json::json!({"assertion": "element_exists", "passed": true}))
+            }
+            
+            AssertionType::TextContains { text } => {
+                let expanded_text = self.expand_template(text)?;
+                let page_text = browser.get_page_text().await?;
+                if !page_text.contains(&expanded_text) {
+                    return Err(anyhow::anyhow!("Assertion failed: Page does not contain text '{}'", expanded_text));
+                }
+                Ok(serde_json::json!({"assertion": "text_contains", "passed": true}))
+            }
+            
+            AssertionType::UrlMatches { pattern } => {
+                let expanded_pattern = self.expand_template(pattern)?;
+                let current_url = browser.current_url().await?;
+                if !current_url.contains(&expanded_pattern) {
+                    return Err(anyhow::anyhow!("Assertion failed: URL '{}' does not match pattern '{}'", current_url, expanded_pattern));
+                }
+                Ok(serde_json::json!({"assertion": "url_matches", "passed": true}))
+            }
+            
+            AssertionType::ElementCount { selector, count } => {
+                let expanded_selector = self.expand_template(selector)?;
+                let actual_count = browser.count_elements(&expanded_selector).await?;
+                if actual_count != *count {
+                    return Err(anyhow::anyhow!("Assertion failed: Expected {} elements, found {}", count, actual_count));
+                }
+                Ok(serde_json::json!({"assertion": "element_count", "passed": true, "count": count}))
+            }
+            
+            AssertionType::Title { expected } => {
+                let expanded_expected = self.expand_template(expected)?;
+                let actual_title = browser.get_title().await?;
+                if actual_title != expanded_expected {
+                    return Err(anyhow::anyhow!("Assertion failed: Expected title '{}', got '{}'", expanded_expected, actual_title));
+                }
+                Ok(serde_json::json!({"assertion": "title", "passed": true}))
+            }
+        }
+    }
+
+    async fn execute_loop(&mut self, over: Option<&str>, inline_items: Option<&Vec<serde_json::Value>>, item_var: Option<&str>, limit: Option<usize>, body: &[WorkflowStep]) -> Result<serde_json::Value> {
+        // Get the collection to iterate over
+        let items = match (inline_items, over) {
+            (Some(items), _) => items.clone(),
+            (None, Some(over)) => {
+                info!("🔄 Executing loop over: {}", over);
+                let collection = self.lookup_variable(over)
+                    .ok_or_else(|| anyhow::anyhow!("Variable '{}' not found for loop", over))?;
+                collection.as_array()
+                    .ok_or_else(|| anyhow::anyhow!("Variable '{}' is not an array", over))?
+                    .clone()
+            }
+            (None, None) => return Err(anyhow::anyhow!("Loop needs either 'over' or 'items'")),
+        };
+        
+        let max_iterations = limit.unwrap_or(DEFAULT_MAX_LOOP_ITERATIONS);
+        if items.len() > max_iterations {
+            warn!("Loop has {} items, only the first {} will be processed", items.len(), max_iterations);
+        }
+        let items_len = items.len().min(max_iterations);
+        
+        let mut results = Vec::new();
+        let mut collected = Vec::new();
+        
+        for (index, item) in items.into_iter().take(max_iterations).enumerate() {
+            info!("Loop iteration {}/{}", index + 1, items_len);
+            
+            // Set loop variables
+            self.variables.insert("_loop_index".to_string(), serde_json::json!(index));
+            self.variables.insert("_loop_count".to_string(), serde_json::json!(items_len));
+            if let Some(name) = item_var {
+                self.variables.insert(name.to_string(), item.clone());
+            }
+            self.variables.insert("_loop_item".to_string(), item);
+            
+            // Execute loop body, keeping what each iteration stored so it stays
+            // available after later iterations overwrite the variables
+            let mut stored = serde_json::Map::new();
+            for step in body {
+                match self.execute_step(step).await {
+                    Ok(result) => {
+                        if let Some(var_name) = &step.store_as {
+                            stored.insert(self.expand_template(var_name)?, result.clone());
+                        }
+                        results.push(result);
+                    }
+                    Err(e) => match self.recover_step(step, &e).await {
+                        StepRecovery::Fallback(result) => results.push(result),
+                        StepRecovery::Continue => {
+                            warn!("Step '{}' failed in loop iteration {}, continuing", step.name, index + 1);
+                            results.push(serde_json::json!({"error": e.to_string()}));
+                        }
+                        StepRecovery::Abort => return Err(e),
+                    },
+                }
+            }
+            collected.push(serde_json::Value::Object(stored));
+        }
+        
+        Ok(serde_json::json!({"loop_results": results, "iterations": items_len, "collected": collected}))
+    }
+
+    async fn execute_conditional(&mut self, condition: &Condition, then_branch: &[WorkflowStep], else_branch: Option<&Vec<WorkflowStep>>) -> Result<serde_json::Value> {
+        info!("❓ Evaluating conditional");
+        
+        if self.evaluate_condition(condition).await? {
+            info!("✅ Condition met, executing then branch");
+            let results = self.execute_nested_steps(then_branch).await?;
+            Ok(serde_json::json!({"branch": "then", "results": results}))
+        } else if let Some(else_steps) = else_branch {
+            info!("❌ Condition not met, executing else branch");
+            let results = self.execute_nested_steps(else_steps).await?;
+            Ok(serde_json::json!({"branch": "else", "results": results}))
+        } else {
+            info!("❌ Condition not met, no else branch");
+            Ok(serde_json::json!({"branch": "none"}))
+        }
+    }
+
+    async fn execute_script(&self, code: &str) -> Result<serde_json::Value> {
+        let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
+        let expanded_code = self.expand_template(code)?;
+        
+        info!("📜 Executing script");
+        let result = browser.execute_script(&expanded_code, vec![]).await?;
+        
+        Ok(result.json().clone())
+    }
+
+    async fn execute_parallel_steps(&mut self, steps: &[WorkflowStep]) -> Result<Vec<ExecutionEntry>> {
+        use futures::future::join_all;
+        
+        let mut handles = Vec::new();
+        
+        for step in steps {
+            let step_clone = step.clone();
+            let handle = tokio::spawn(async move {
+                // Create a temporary engine for parallel execution
+                // In a real implementation, this would share state properly
+                ExecutionEntry {
+                    timestamp: Utc::now(),
+                    step_name: step_clone.name,
+                    action: format!("{:?}", step_clone.action),
+                    success: true, // Simplified for PoC
+                    duration_ms: 0,
+                    error: None,
+                    data: Some(serde_json::json!({"parallel": true})),
+                }
+            });
+            handles.push(handle);
+        }
+        
+        let results = join_all(handles).await;
+        let mut entries = Vec::new();
+        
+        for result in results {
+            match result {
+                Ok(entry) => entries.push(entry),
+                Err(e) => {
+                    error!("Parallel step failed: {}", e);
+                }
+            }
+        }
+        
+        Ok(entries)
+    }
+
+    fn evaluate_condition<'a>(&'a self, condition: &'a Condition) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<bool>> + Send + 'a>> {
+        Box::pin(async move {
+        match condition {
+            Condition::ElementExists { selector } => {
+                let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
+                let expanded_selector = self.expand_template(selector)?;
+                browser.element_exists(&expanded_selector).await
+            }
+            
+            Condition::TextContains { text } => {
+                let browser = self.browser.as_ref().ok_or_else(|| anyhow::anyhow!("Browser not initialized"))?;
+                let expanded_text = self.expand_template(text)?;
+                let page_text = browser.get_page_text().await?;
+                Ok(page_text.contains(&expanded_text))
+            }
+            
+            Condition::VariableEquals { var, value } => {
+                let actual = self.lookup_variable(var);
+                Ok(actual == Some(value))
+            }
+            
+            Condition::VariableGreaterThan { var, value } => {
+                Ok(self.lookup_variable(var).and_then(as_number).is_some_and(|num| num > *value))
+            }
+            
+            Condition::VariableLessThan { var, value } => {
+                Ok(self.lookup_variable(var).and_then(as_number).is_some_and(|num| num < *value))
+            }
+            
+            Condition::VariableExists { var } => {
+                Ok(match self.lookup_variable(var) {
+                    None | Some(serde_json::Value::Null) => false,
+                    Some(serde_json::Value::String(s)) => !s.trim().is_empty(),
+                    Some(serde_json::Value::Array(items)) => !items.is_empty(),
+                    Some(serde_json::Value::Object(fields)) => !fields.is_empty(),
+                    Some(_) => true,
+                })
+            }
+            
+            Condition::VariableContains { var, value } => {
+                let expanded_value = self.expand_template(value)?;
+                Ok(match self.lookup_variable(var) {
+                    Some(serde_json::Value::String(s)) => s.contains(&expanded_value),
+                    Some(serde_json::Value::Array(items)) => items.iter().any(|item| {
+                        match item.as_str() {
+                            Some(s) => s == expanded_value,
+                            None => item.to_string().as_str() == expanded_value,
+                        }
+                    }),
+                    Some(other) => other.to_string().contains(&expanded_value),
+                    None => false,
+                })
+            }
+            
+            Condition::VariableMatches { var, pattern } => {
+                let regex = regex::Regex::new(pattern)
+                    .with_context(|| format!("Invalid pattern '{}' in condition on '{}'", pattern, var))?;
+                Ok(match self.lookup_variable(var) {
+                    Some(serde_json::Value::String(s)) => regex.is_match(s),
+                    Some(other) => regex.is_match(&other.to_string()),
+                    None => false,
+                })
+            }
+            
+            Condition::StepStatus { step, status } => {
+                let actual = self.step_status.get(step);
+                if actual.is_none() {
+                    warn!("Condition references step '{}' which has not run", step);
+                }
+                Ok(actual == Some(status))
+            }
+            
+            Condition::Not { condition } => {
+                Ok(!self.evaluate_condition(condition).await?)
+            }
+            
+            Condition::And { conditions } => {
+                for cond in conditions {
+                    if !self.evaluate_condition(cond).await? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            
+            Condition::Or { conditions } => {
+                for cond in conditions {
+                    if self.evaluate_condition(cond).await? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+        }
+        })
+    }
+
+    /// Value of a variable by name, or of a field inside one by dotted path
+    /// (`_loop_item.url`, `results.0.price`)
+    fn lookup_variable(&self, path: &str) -> Option<&serde_json::Value> {
+        if let Some(value) = self.variables.get(path) {
+            return Some(value);
+        }
+        
+        let mut segments = path.split('.');
+        let mut value = self.variables.get(segments.next()?)?;
+        for segment in segments {
+            value = match value {
+                serde_json::Value::Object(fields) => fields.get(segment)?,
+                serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    fn expand_template(&self, template: &str) -> Result<String> {
+        // Template expansion using {{variable}} or {{variable.field}} syntax;
+        // unknown placeholders are left as they are
+        let result = TEMPLATE_PLACEHOLDER.replace_all(template, |caps: &regex::Captures| {
+            match self.lookup_variable(&caps[1]) {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(serde_json::Value::Number(n)) => n.to_string(),
+                Some(serde_json::Value::Bool(b)) => b.to_string(),
+                Some(value) => value.to_string(),
+                None => caps[0].to_string(),
+            }
+        });
+        
+        Ok(result.into_owned())
+    }
+
+    /// Outcome of the step named `step` in the current run, if it ran
+    pub fn step_status(&self, step: &str) -> Option<StepStatus> {
+        self.step_status.get(step).copied()
+    }
+
+    pub async fn cleanup(&mut self) -> Result<()> {
+        // Browser cleanup is handled separately - cannot take ownership here
+        self.browser = None;
+        Ok(())
+    }
+}
+
+/// Numeric value of a variable; extracted text such as "42" or " 3.5 " counts as a number
+fn as_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        other => other.as_f64(),
+    }
+}
+
+impl Workflow {
+    pub fn from_yaml(yaml_str: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml_str)
+            .context("Failed to parse workflow YAML")
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Self> {
+        serde_json::from_str(json_str)
+            .context("Failed to parse workflow JSON")
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self)
+            .context("Failed to serialize workflow to YAML")
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .context("Failed to serialize workflow to JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_step_parses_from_yaml() {
+        let workflow = Workflow::from_yaml(r##"
+name: "Conditional login"
+variables: {}
+steps:
+  - name: "Read greeting"
+    action:
+      type: extract
+      selector: ".greeting"
+    store_as: greeting
+  - name: "Log in if needed"
+    action:
+      type: if
+      if:
+        check: and
+        conditions:
+          - check: element_exists
+            selector: "form#login"
+          - check: step_status
+            step: "Read greeting"
+            status: failed
+      then:
+        - name: "Fill username"
+          action:
+            type: fill
+            selector: "#username"
+            value: "{{username}}"
+"##).unwrap();
+
+        match &workflow.steps[1].action {
+            ActionType::Conditional { condition: Condition::And { conditions }, then_branch, else_branch } => {
+                assert!(matches!(&conditions[1], Condition::StepStatus { status: StepStatus::Failed, .. }));
+                assert_eq!(then_branch.len(), 1);
+                assert!(else_branch.is_none());
+            }
+            other => panic!("unexpected action: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conditions_on_extracted_values_and_step_status() {
+        let mut engine = WorkflowEngine::new_simple();
+        engine.set_variable("price", serde_json::json!(" 42.50 ")).await;
+        engine.set_variable("status", serde_json::json!("Order #1234 confirmed")).await;
+        engine.set_variable("errors", serde_json::json!("")).await;
+        engine.step_status.insert("Submit order".to_string(), StepStatus::Succeeded);
+
+        let condition = |yaml: &str| serde_yaml::from_str::<Condition>(yaml).unwrap();
+        assert!(engine.evaluate_condition(&condition("{check: variable_greater_than, var: price, value: 40}")).await.unwrap());
+        assert!(engine.evaluate_condition(&condition("{check: variable_contains, var: status, value: confirmed}")).await.unwrap());
+        assert!(engine.evaluate_condition(&condition("{check: variable_matches, var: status, pattern: '#\\d+'}")).await.unwrap());
+        assert!(!engine.evaluate_condition(&condition("{check: variable_exists, var: errors}")).await.unwrap());
+        assert!(engine.evaluate_condition(&condition("{check: step_status, step: Submit order, status: succeeded}")).await.unwrap());
+        assert!(!engine.evaluate_condition(&condition("{check: step_status, step: Never ran, status: failed}")).await.unwrap());
+    }
+
+    #[test]
+    fn test_for_each_step_parses_from_yaml() {
+        let step: WorkflowStep = serde_yaml::from_str(r#"
+name: "Visit every result"
+action:
+  type: for_each
+  over: search.links
+  as: link
+  limit: 5
+  do:
+    - name: "Open result"
+      action:
+        type: navigate
+        url: "{{link}}"
+    - name: "Read price"
+      action:
+        type: extract
+        selector: ".price"
+      store_as: price
+"#).unwrap();
+
+        match step.action {
+            ActionType::Loop { over, items, item_var, limit, body } => {
+                assert_eq!(over.as_deref(), Some("search.links"));
+                assert!(items.is_none());
+                assert_eq!(item_var.as_deref(), Some("link"));
+                assert_eq!(limit, Some(5));
+                assert_eq!(body.len(), 2);
+            }
+            other => panic!("unexpected action: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_round_trip_and_gate_resume() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = CheckpointStore::new(dir.path());
+        let workflow = Workflow::from_yaml("name: checkout\nvariables: {}\nsteps: []\n").unwrap();
+        let run_id = uuid::Uuid::new_v4();
+        let checkpoint = WorkflowCheckpoint {
+            run_id,
+            workflow,
+            next_step: 0,
+            completed: true,
+            steps_executed: 2,
+            steps_failed: 0,
+            variables: HashMap::from([("order".to_string(), serde_json::json!("A-17"))]),
+            step_status: HashMap::from([("Pay".to_string(), StepStatus::Succeeded)]),
+            execution_log: Vec::new(),
+            page: Some(PageSnapshot { url: "https://shop.test/cart".to_string(), cookies: vec![] }),
+            updated_at: Utc::now(),
+        };
+        store.save(&checkpoint).unwrap();
+
+        let loaded = store.load(run_id).unwrap();
+        assert_eq!(loaded.variables["order"], "A-17");
+        assert_eq!(loaded.step_status["Pay"], StepStatus::Succeeded);
+
+        let mut engine = WorkflowEngine::new_simple().with_checkpoints(store.clone());
+        let error = engine.resume(run_id).await.unwrap_err();
+        assert!(error.to_string().contains("already completed"));
+        assert!(engine.resume(uuid::Uuid::new_v4()).await.is_err());
+        assert!(WorkflowEngine::new_simple().resume(run_id).await.is_err(), "resume needs checkpointing enabled");
+    }
+
+    #[test]
+    fn test_artifact_store_names_and_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ArtifactStore::new(dir.path());
+        let run_id = uuid::Uuid::new_v4();
+        assert!(store.list(run_id).unwrap().is_empty());
+
+        let first = store.save(run_id, "Read prices", "prices.json", ArtifactKind::Json, "application/json", b"[1]").unwrap();
+        let second = store.save(run_id, "Read prices", "prices.json", ArtifactKind::Json, "application/json", b"[2]").unwrap();
+        let escaped = store.save(run_id, "Save report", "../../etc/passwd", ArtifactKind::Download, "text/plain", b"x").unwrap();
+        assert_eq!(first.name, "prices.json");
+        assert_eq!(second.name, "prices-2.json");
+        assert_eq!(escaped.name, "_.._etc_passwd");
+
+        let listed = store.list(run_id).unwrap();
+        assert_eq!(listed.len(), 3);
+        let (artifact, data) = store.read(run_id, "prices-2.json").unwrap();
+        assert_eq!(artifact.step, "Read prices");
+        assert_eq!(data, b"[2]");
+        assert!(store.read(run_id, "manifest.json").is_err());
+
+        let step: WorkflowStep = serde_yaml::from_str(r#"
+name: "Export results"
+action: {type: extract, selector: ".result", all: true}
+artifacts:
+  - {type: json, name: "results-{{page}}.json"}
+  - {type: screenshot, full_page: true}
+  - {type: download, url: "https://shop.test/invoice.pdf"}
+"#).unwrap();
+        assert_eq!(step.artifacts.len(), 3);
+        assert!(matches!(&step.artifacts[1], ArtifactSpec::Screenshot { name: None, full_page: true }));
+    }
+
+    #[test]
+    fn test_error_policies_parse() {
+        let policy = |yaml: &str| serde_yaml::from_str::<ErrorStrategy>(yaml).unwrap();
+        assert!(matches!(policy("abort"), ErrorStrategy::Abort));
+        assert!(matches!(policy("fail"), ErrorStrategy::Abort));
+        assert!(matches!(policy("continue"), ErrorStrategy::Continue));
+        assert!(matches!(policy("fallback_step(Search instead)"), ErrorStrategy::FallbackStep(name) if name == "Search instead"));
+        assert!(matches!(policy("{fallback_step: Search instead}"), ErrorStrategy::FallbackStep(name) if name == "Search instead"));
+        assert!(matches!(policy("{fallback: {steps: []}}"), ErrorStrategy::Fallback { steps } if steps.is_empty()));
+
+        let ErrorStrategy::Retry(retry) = policy("retry(2, linear, 500ms)") else { panic!("expected retry") };
+        assert_eq!((retry.attempts, retry.backoff, retry.delay_ms), (2, Backoff::Linear, 500));
+        assert_eq!(retry.delay_before(2), Duration::from_millis(1000));
+
+        let ErrorStrategy::Retry(retry) = policy("{retry: {attempts: 5}}") else { panic!("expected retry") };
+        assert_eq!((retry.attempts, retry.backoff), (5, Backoff::Exponential));
+        assert_eq!(retry.delay_before(3), Duration::from_millis(4000));
+        assert_eq!(retry.delay_before(10), Duration::from_millis(MAX_RETRY_DELAY_MS));
+
+        assert!(serde_yaml::from_str::<ErrorStrategy>("retry(many)").is_err());
+        assert!(serde_yaml::from_str::<ErrorStrategy>("skip").is_err());
+
+        // Policies round-trip through YAML
+        let yaml = serde_yaml::to_string(&policy("retry(3, fixed, 2s)")).unwrap();
+        assert!(matches!(policy(&yaml), ErrorStrategy::Retry(RetryPolicy { attempts: 3, backoff: Backoff::Fixed, delay_ms: 2000 })));
+
+        // Every bundled template still parses
+        let templates = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("workflows/templates");
+        for entry in std::fs::read_dir(templates).unwrap() {
+            let path = entry.unwrap().path();
+            let yaml = std::fs::read_to_string(&path).unwrap();
+            Workflow::from_yaml(&yaml).unwrap_or_else(|e| panic!("{}: {:#}", path.display(), e));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_steps_follow_their_policy() {
+        let workflow = Workflow::from_yaml(r#"
+name: policies
+variables: {}
+on_error: continue
+steps:
+  - name: "Open cart"
+    action: {type: navigate, url: "https://shop.test/cart"}
+    on_error: fallback_step(Missing)
+  - name: "Open basket"
+    action: {type: navigate, url: "https://shop.test/basket"}
+    on_error:
+      fallback:
+        steps:
+          - name: "Open home"
+            action: {type: navigate, url: "https://shop.test"}
+  - name: "Read total"
+    action: {type: extract, selector: ".total"}
+"#).unwrap();
+        let mut engine = WorkflowEngine::new_simple();
+        engine.load_error_policies(&workflow);
+        let error = anyhow::anyhow!("Element not found");
+
+        assert!(matches!(engine.recover_step(&workflow.steps[0], &error).await, StepRecovery::Continue));
+        // Without a browser the fallback fails as well, which does not stop the workflow
+        let StepRecovery::Fallback(result) = engine.recover_step(&workflow.steps[1], &error).await else {
+            panic!("fallback steps should have run");
+        };
+        assert!(result["fallback"][0]["error"].is_string());
+        assert_eq!(engine.step_status["Open home"], StepStatus::Failed);
+        // Steps without a policy of their own use the workflow's
+        assert!(matches!(engine.recover_step(&workflow.steps[2], &error).await, StepRecovery::Continue));
+    }
+
+    #[tokio::test]
+    async fn test_failed_steps_count_as_failed_until_an_abort() {
+        let workflow = Workflow::from_yaml(r#"
+name: policies
+variables: {}
+steps:
+  - name: "Open cart"
+    action: {type: navigate, url: "https://shop.test/cart"}
+    on_error: retry(1, fixed, 0ms)
+  - name: "Open basket"
+    action: {type: navigate, url: "https://shop.test/basket"}
+    on_error:
+      fallback:
+        steps:
+          - name: "Open home"
+            action: {type: navigate, url: "https://shop.test"}
+  - name: "Open checkout"
+    action: {type: navigate, url: "https://shop.test/checkout"}
+    on_error: abort
+  - name: "Read total"
+    action: {type: extract, selector: ".total"}
+"#).unwrap();
+        let mut engine = WorkflowEngine::new_simple();
+        engine.load_error_policies(&workflow);
+        let (executed, failed) = engine.execute_sequential(&workflow, 0, 0, 0).await;
+
+        // Exhausted retries and fallbacks go on with the next step, but the steps stay failed
+        assert_eq!((executed, failed), (3, 3));
+        assert!(engine.execution_log.iter().all(|entry| !entry.success));
+        assert!(engine.execution_log[1].data.as_ref().is_some_and(|data| data["fallback"].is_array()));
+    }
+
+    #[tokio::test]
+    async fn test_templates_resolve_dotted_paths() {
+        let mut engine = WorkflowEngine::new_simple();
+        engine.set_variable("_loop_item", serde_json::json!({"name": "GitHub", "url": "https://github.com"})).await;
+        engine.set_variable("prices", serde_json::json!(["$10", "$12"])).await;
+
+        assert_eq!(
+            engine.expand_template("{{_loop_item.url}}/pricing ({{_loop_item.name}}) {{prices.1}} {{missing.field}}").unwrap(),
+            "https://github.com/pricing (GitHub) $12 {{missing.field}}"
+        );
+        assert_eq!(engine.lookup_variable("prices.0"), Some(&serde_json::json!("$10")));
+        assert!(engine.lookup_variable("prices.2").is_none());
+    }
+}
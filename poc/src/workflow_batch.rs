@@ -0,0 +1,366 @@
+//! Data-driven Workflow Runs
+//!
+//! Runs a workflow once per row of a CSV or JSON dataset, with the row's fields as
+//! inputs (bulk form submission, bulk lookups), and consolidates the outcome and
+//! extracted values of every row into a single report that exports to JSON or CSV.
+
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::workflow::{Workflow, WorkflowEngine};
+
+/// Rows of named values, each bound to one workflow run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dataset {
+    /// Field names in file order, used for the columns of CSV reports
+    pub columns: Vec<String>,
+    pub rows: Vec<HashMap<String, serde_json::Value>>,
+}
+
+impl Dataset {
+    /// Parse CSV with a header row; quoted fields may contain commas, quotes and newlines
+    pub fn from_csv(text: &str) -> Result<Self> {
+        let mut records = parse_csv(text)?.into_iter();
+        let columns: Vec<String> = records.next()
+            .ok_or_else(|| anyhow::anyhow!("CSV dataset has no header row"))?
+            .into_iter()
+            .map(|column| column.trim().to_string())
+            .collect();
+        if columns.iter().any(|column| column.is_empty()) {
+            anyhow::bail!("CSV header has an empty column name");
+        }
+
+        let mut rows = Vec::new();
+        for (index, record) in records.enumerate() {
+            if record.len() == 1 && record[0].is_empty() {
+                continue;
+            }
+            if record.len() != columns.len() {
+                anyhow::bail!("CSV row {} has {} fields, expected {}", index + 1, record.len(), columns.len());
+            }
+            rows.push(columns.iter().cloned().zip(record.into_iter().map(serde_json::Value::String)).collect());
+        }
+        Ok(Self { columns, rows })
+    }
+
+    /// Parse a JSON array of objects, or an object with such an array under `rows`
+    pub fn from_json(text: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(text).context("Invalid JSON dataset")?;
+        let rows = match value {
+            serde_json::Value::Array(rows) => rows,
+            serde_json::Value::Object(mut object) => match object.remove("rows") {
+                Some(serde_json::Value::Array(rows)) => rows,
+                _ => anyhow::bail!("JSON dataset must be an array of objects or have a 'rows' array"),
+            },
+            _ => anyhow::bail!("JSON dataset must be an array of objects or have a 'rows' array"),
+        };
+
+        let rows = rows.into_iter()
+            .enumerate()
+            .map(|(index, row)| match row {
+                serde_json::Value::Object(fields) => Ok(fields.into_iter().collect()),
+                _ => Err(anyhow::anyhow!("Dataset row {} is not an object", index + 1)),
+            })
+            .collect::<Result<Vec<HashMap<_, _>>>>()?;
+        Ok(Self::from_rows(rows))
+    }
+
+    /// Dataset of rows built in code; columns are the union of the rows' fields
+    pub fn from_rows(rows: Vec<HashMap<String, serde_json::Value>>) -> Self {
+        let columns = rows.iter()
+            .flat_map(|row| row.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        Self { columns, rows }
+    }
+
+    /// Load a `.csv` or `.json` file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read dataset {}", path.display()))?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => Self::from_csv(&text),
+            Some("json") => Self::from_json(&text),
+            _ => anyhow::bail!("Unsupported dataset {}: use a .csv or .json file", path.display()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+/// Options of a batch run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchOptions {
+    /// Skip the remaining rows once one fails
+    #[serde(default)]
+    pub stop_on_failure: bool,
+    /// Run at most this many rows
+    pub limit: Option<usize>,
+}
+
+/// Outcome of the run of one dataset row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRow {
+    /// Position of the row in the dataset, from 0
+    pub index: usize,
+    pub inputs: HashMap<String, serde_json::Value>,
+    pub success: bool,
+    pub run_id: Option<uuid::Uuid>,
+    pub steps_executed: usize,
+    pub steps_failed: usize,
+    /// Variables the run set, such as `store_as` results
+    pub outputs: HashMap<String, serde_json::Value>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Consolidated results of a batch run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub workflow: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Rows not run because of `stop_on_failure` or `limit`
+    pub skipped: usize,
+    #[serde(skip)]
+    columns: Vec<String>,
+    pub rows: Vec<BatchRow>,
+}
+
+impl BatchReport {
+    /// One line per row: its status, then its inputs, then its outputs
+    pub fn to_csv(&self) -> String {
+        let outputs: BTreeSet<&String> = self.rows.iter().flat_map(|row| row.outputs.keys()).collect();
+
+        let mut header = vec!["row".to_string(), "success".to_string(), "error".to_string(), "run_id".to_string()];
+        header.extend(self.columns.iter().cloned());
+        header.extend(outputs.iter().map(|name| name.to_string()));
+
+        let mut csv = csv_line(&header);
+        for row in &self.rows {
+            let mut fields = vec![
+                row.index.to_string(),
+                row.success.to_string(),
+                row.error.clone().unwrap_or_default(),
+                row.run_id.map(|id| id.to_string()).unwrap_or_default(),
+            ];
+            fields.extend(self.columns.iter().map(|column| cell(row.inputs.get(column))));
+            fields.extend(outputs.iter().map(|name| cell(row.outputs.get(*name))));
+            csv.push_str(&csv_line(&fields));
+        }
+        csv
+    }
+}
+
+/// Run `workflow` once per dataset row, reusing the engine's browser between rows
+pub async fn run_batch(engine: &mut WorkflowEngine, workflow: &Workflow, dataset: &Dataset, options: &BatchOptions) -> BatchReport {
+    let started_at = Utc::now();
+    let start = std::time::Instant::now();
+    let to_run = options.limit.map_or(dataset.len(), |limit| limit.min(dataset.len()));
+    info!("📋 Running workflow '{}' for {} dataset rows", workflow.name, to_run);
+
+    let mut rows = Vec::new();
+    for (index, inputs) in dataset.rows.iter().take(to_run).enumerate() {
+        let row_start = std::time::Instant::now();
+        let row = match engine.execute_workflow(workflow, Some(inputs.clone())).await {
+            Ok(result) => {
+                let outputs = result.variables.into_iter()
+                    .filter(|(name, value)| {
+                        !name.starts_with('_')
+                            && workflow.variables.get(name) != Some(value)
+                            && inputs.get(name) != Some(value)
+                    })
+                    .collect();
+                let error = result.execution_log.iter().rev().find_map(|entry| entry.error.clone());
+                BatchRow {
+                    index,
+                    inputs: inputs.clone(),
+                    success: result.success,
+                    run_id: result.run_id,
+                    steps_executed: result.steps_executed,
+                    steps_failed: result.steps_failed,
+                    outputs,
+                    error: if result.success { None } else { error },
+                    duration_ms: row_start.elapsed().as_millis() as u64,
+                }
+            }
+            Err(e) => BatchRow {
+                index,
+                inputs: inputs.clone(),
+                success: false,
+                run_id: None,
+                steps_executed: 0,
+                steps_failed: 0,
+                outputs: HashMap::new(),
+                error: Some(e.to_string()),
+                duration_ms: row_start.elapsed().as_millis() as u64,
+            },
+        };
+
+        let failed = !row.success;
+        if failed {
+            warn!("Dataset row {} failed: {:?}", index + 1, row.error);
+        }
+        rows.push(row);
+        if failed && options.stop_on_failure {
+            warn!("Stopping batch after failed row {}", index + 1);
+            break;
+        }
+    }
+
+    let succeeded = rows.iter().filter(|row| row.success).count();
+    let report = BatchReport {
+        workflow: workflow.name.clone(),
+        started_at,
+        duration_ms: start.elapsed().as_millis() as u64,
+        total: dataset.len(),
+        succeeded,
+        failed: rows.len() - succeeded,
+        skipped: dataset.len() - rows.len(),
+        columns: dataset.columns.clone(),
+        rows,
+    };
+    info!("📋 Batch of '{}' finished: {} succeeded, {} failed, {} skipped", report.workflow, report.succeeded, report.failed, report.skipped);
+    report
+}
+
+fn cell(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn csv_line(fields: &[String]) -> String {
+    let mut line = fields.iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if in_quotes {
+        anyhow::bail!("Unterminated quoted field in CSV dataset");
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_dataset_parsing() {
+        let dataset = Dataset::from_csv("\u{feff}email,name,notes\r\na@x.test,Ann,\"likes \"\"tea\"\", cake\"\r\n\r\nb@x.test,Bob,\"two\nlines\"\n").unwrap();
+        assert_eq!(dataset.columns, vec!["email", "name", "notes"]);
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset.rows[0]["notes"], "likes \"tea\", cake");
+        assert_eq!(dataset.rows[1]["notes"], "two\nlines");
+
+        assert!(Dataset::from_csv("a,b\n1\n").is_err());
+        assert!(Dataset::from_csv("a\n\"open\n").is_err());
+    }
+
+    #[test]
+    fn test_json_dataset_parsing() {
+        let dataset = Dataset::from_json(r#"{"rows": [{"sku": "A1", "qty": 2}, {"sku": "B2", "gift": true}]}"#).unwrap();
+        assert_eq!(dataset.columns, vec!["gift", "qty", "sku"]);
+        assert_eq!(dataset.rows[0]["qty"], 2);
+        assert!(Dataset::from_json("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_report_csv_export() {
+        let dataset = Dataset::from_csv("sku,qty\nA1,2\n").unwrap();
+        let report = BatchReport {
+            workflow: "Lookup".to_string(),
+            started_at: Utc::now(),
+            duration_ms: 10,
+            total: 1,
+            succeeded: 1,
+            failed: 0,
+            skipped: 0,
+            columns: dataset.columns.clone(),
+            rows: vec![BatchRow {
+                index: 0,
+                inputs: dataset.rows[0].clone(),
+                success: true,
+                run_id: None,
+                steps_executed: 2,
+                steps_failed: 0,
+                outputs: HashMap::from([("price".to_string(), serde_json::json!("$4, incl. tax"))]),
+                error: None,
+                duration_ms: 10,
+            }],
+        };
+
+        assert_eq!(report.to_csv(), "row,success,error,run_id,sku,qty,price\r\n0,true,,,A1,2,\"$4, incl. tax\"\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_rows_failing_before_any_step_are_reported() {
+        let workflow = Workflow::from_yaml(r#"
+name: needs-input
+inputs:
+  - {name: sku, input_type: string, required: true}
+steps: []
+"#).unwrap();
+        let dataset = Dataset::from_json(r#"[{"qty": 1}, {"qty": 2}]"#).unwrap();
+        let mut engine = WorkflowEngine::new_simple();
+
+        let options = BatchOptions { stop_on_failure: true, limit: None };
+        let report = run_batch(&mut engine, &workflow, &dataset, &options).await;
+        assert_eq!((report.failed, report.skipped), (1, 1));
+        assert!(report.rows[0].error.as_deref().unwrap().contains("sku"));
+    }
+}
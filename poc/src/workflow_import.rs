@@ -0,0 +1,467 @@
+//! Workflow Import
+//!
+//! Converts existing automation suites into workflows: Selenium IDE projects
+//! (`.side` files) and Playwright test scripts. Statements without a workflow
+//! equivalent are skipped and reported as warnings so they can be ported by hand.
+
+use anyhow::{Context as AnyhowContext, Result};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::workflow::{ActionType, AssertionType, WaitType, Workflow, WorkflowStep};
+
+/// Kind of script being imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    SeleniumIde,
+    Playwright,
+}
+
+impl ImportSource {
+    /// Guess the source from the file name, falling back to the content
+    pub fn detect(file_name: Option<&str>, content: &str) -> Option<Self> {
+        match file_name.and_then(|name| name.rsplit('.').next()) {
+            Some("side") => return Some(ImportSource::SeleniumIde),
+            Some("js" | "ts" | "mjs" | "cjs") => return Some(ImportSource::Playwright),
+            _ => {}
+        }
+        let trimmed = content.trim_start();
+        if trimmed.starts_with('{') && content.contains("\"commands\"") {
+            Some(ImportSource::SeleniumIde)
+        } else if content.contains("page.") {
+            Some(ImportSource::Playwright)
+        } else {
+            None
+        }
+    }
+}
+
+/// A converted test, with what could not be converted
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedWorkflow {
+    pub workflow: Workflow,
+    pub warnings: Vec<String>,
+}
+
+/// Convert a script, one workflow per test it contains
+pub fn import_script(content: &str, file_name: Option<&str>) -> Result<Vec<ImportedWorkflow>> {
+    match ImportSource::detect(file_name, content) {
+        Some(ImportSource::SeleniumIde) => import_selenium_side(content),
+        Some(ImportSource::Playwright) => import_playwright(content),
+        None => anyhow::bail!("Unrecognized script: expected a Selenium IDE project or a Playwright test"),
+    }
+}
+
+fn step(name: String, action: ActionType) -> WorkflowStep {
+    WorkflowStep {
+        name,
+        action,
+        condition: None,
+        on_error: None,
+        retry: None,
+        store_as: None,
+        timeout: None,
+        artifacts: Vec::new(),
+    }
+}
+
+fn workflow(name: &str, description: String, variables: HashMap<String, serde_json::Value>, steps: Vec<WorkflowStep>) -> Workflow {
+    Workflow {
+        name: name.to_string(),
+        description: Some(description),
+        version: Some("1.0".to_string()),
+        inputs: None,
+        variables,
+        steps,
+        parallel: None,
+        on_error: None,
+        timeout: None,
+        webhooks: Vec::new(),
+    }
+}
+
+lazy_static::lazy_static! {
+    /// `${name}` variable references of Selenium IDE and JavaScript template literals
+    static ref SCRIPT_VARIABLE: Regex = Regex::new(r"\$\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}").unwrap();
+}
+
+/// Rewrite `${name}` references as workflow `{{name}}` placeholders
+fn convert_variables(text: &str) -> String {
+    SCRIPT_VARIABLE.replace_all(text, "{{$1}}").into_owned()
+}
+
+// ============================================================================
+// Selenium IDE
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct SideProject {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    url: String,
+    tests: Vec<SideTest>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SideTest {
+    name: String,
+    commands: Vec<SideCommand>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SideCommand {
+    command: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    value: String,
+}
+
+/// Convert a Selenium IDE project, one workflow per test. The project URL becomes
+/// the `base_url` variable that relative `open` targets are resolved against.
+pub fn import_selenium_side(json: &str) -> Result<Vec<ImportedWorkflow>> {
+    let project: SideProject = serde_json::from_str(json).context("Invalid Selenium IDE project")?;
+    let mut variables = HashMap::new();
+    if !project.url.is_empty() {
+        variables.insert("base_url".to_string(), serde_json::json!(project.url.trim_end_matches('/')));
+    }
+
+    Ok(project.tests.iter()
+        .map(|test| {
+            let mut warnings = Vec::new();
+            let steps = test.commands.iter()
+                .enumerate()
+                // Commented-out commands start with `//`
+                .filter(|(_, command)| !command.command.is_empty() && !command.command.starts_with("//"))
+                .filter_map(|(index, command)| match side_step(command, !project.url.is_empty()) {
+                    Ok(step) => Some(step),
+                    Err(warning) => {
+                        warnings.push(format!("Command {} ({}): {}", index + 1, command.command, warning));
+                        None
+                    }
+                })
+                .collect();
+            let description = format!("Imported from Selenium IDE project '{}'", project.name);
+            ImportedWorkflow { workflow: workflow(&test.name, description, variables.clone(), steps), warnings }
+        })
+        .collect())
+}
+
+/// CSS selector of a Selenium locator
+fn side_locator(target: &str) -> std::result::Result<String, String> {
+    let (strategy, value) = target.split_once('=').unwrap_or(("css", target));
+    match strategy {
+        "css" => Ok(value.to_string()),
+        "id" if value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') => Ok(format!("#{}", value)),
+        "id" | "name" => Ok(format!("[{}='{}']", strategy, value.replace('\'', "\\'"))),
+        _ if target.starts_with("//") || strategy == "xpath" => Err(format!("XPath locator '{}' is not supported, use a CSS selector", target)),
+        _ if !target.contains('=') || target.starts_with('[') => Ok(target.to_string()),
+        _ => Err(format!("{} locators are not supported, use a CSS selector", strategy)),
+    }
+}
+
+fn side_step(command: &SideCommand, has_base_url: bool) -> std::result::Result<WorkflowStep, String> {
+    let target = convert_variables(&command.target);
+    let value = convert_variables(&command.value);
+
+    let (name, action) = match command.command.as_str() {
+        "open" => {
+            let url = if target.starts_with("http") || !has_base_url {
+                target
+            } else {
+                format!("{{{{base_url}}}}/{}", target.trim_start_matches('/'))
+            };
+            (format!("Open {}", url), ActionType::Navigate { url, screenshot: false })
+        }
+        "click" | "clickAt" | "check" | "uncheck" => {
+            let selector = side_locator(&target)?;
+            (format!("Click {}", selector), ActionType::Click { selector, wait_after: 0 })
+        }
+        "type" | "sendKeys" | "editContent" => {
+            if value.contains("${KEY_") || value.contains("{{KEY_") {
+                return Err("special keys cannot be sent".to_string());
+            }
+            let selector = side_locator(&target)?;
+            (format!("Fill {}", selector), ActionType::Fill { selector, value })
+        }
+        "select" => {
+            // `label=Option` or `value=option`; typing the text into a select picks the option
+            let option = value.split_once('=').map_or(value.as_str(), |(_, option)| option).to_string();
+            let selector = side_locator(&target)?;
+            (format!("Select {} in {}", option, selector), ActionType::Fill { selector, value: option })
+        }
+        "waitForElementPresent" | "waitForElementVisible" => {
+            let selector = side_locator(&target)?;
+            (format!("Wait for {}", selector), ActionType::Wait { wait_type: WaitType::Element { selector } })
+        }
+        "pause" => {
+            let millis: u64 = target.parse().or_else(|_| value.parse()).unwrap_or(1000);
+            let seconds = millis.div_ceil(1000).max(1);
+            (format!("Pause {}s", seconds), ActionType::Wait { wait_type: WaitType::Time { seconds } })
+        }
+        "assertText" | "verifyText" => {
+            // The workflow assertion checks the page text, not the element's
+            (format!("Check text '{}'", value), ActionType::Assert { assertion: AssertionType::TextContains { text: value } })
+        }
+        "assertTitle" | "verifyTitle" => {
+            (format!("Check title '{}'", target), ActionType::Assert { assertion: AssertionType::Title { expected: target } })
+        }
+        "assertElementPresent" | "verifyElementPresent" => {
+            let selector = side_locator(&target)?;
+            (format!("Check {} exists", selector), ActionType::Assert { assertion: AssertionType::ElementExists { selector } })
+        }
+        "storeText" | "storeValue" => {
+            let selector = side_locator(&target)?;
+            let mut step = step(format!("Store {}", value), ActionType::Extract { selector, attribute: None, all: false });
+            step.store_as = Some(value);
+            return Ok(step);
+        }
+        "storeAttribute" => {
+            let (locator, attribute) = target.rsplit_once('@').ok_or("expected 'locator@attribute'")?;
+            let selector = side_locator(locator)?;
+            let mut step = step(
+                format!("Store {}", value),
+                ActionType::Extract { selector, attribute: Some(attribute.to_string()), all: false },
+            );
+            step.store_as = Some(value);
+            return Ok(step);
+        }
+        "runScript" | "executeScript" => ("Run script".to_string(), ActionType::Script { code: target }),
+        "setWindowSize" | "mouseOver" | "mouseOut" => return Err("not needed for replay, skipped".to_string()),
+        other => return Err(format!("unsupported command '{}'", other)),
+    };
+    Ok(step(name, action))
+}
+
+// ============================================================================
+// Playwright
+// ============================================================================
+
+/// A quoted JavaScript string; one of the three groups holds its content
+const JS_STRING: &str = r#"(?:'([^']*)'|"([^"]*)"|`([^`]*)`)"#;
+
+lazy_static::lazy_static! {
+    static ref TEST_BLOCK: Regex = Regex::new(&format!(r"\btest(?:\.only)?\(\s*{}\s*,", JS_STRING)).unwrap();
+    static ref PLAYWRIGHT_STATEMENTS: Vec<(PlaywrightStatement, Regex)> = [
+        (PlaywrightStatement::Goto, format!(r"page\.goto\(\s*{}", JS_STRING)),
+        (PlaywrightStatement::Click, format!(r"page\.(?:click|dblclick|check|tap)\(\s*{}", JS_STRING)),
+        (PlaywrightStatement::Fill, format!(r"page\.(?:fill|type|selectOption)\(\s*{}\s*,\s*{}", JS_STRING, JS_STRING)),
+        (PlaywrightStatement::Click, format!(r"page\.locator\(\s*{}\s*\)(?:\.first\(\))?\.(?:click|dblclick|check|tap)\(", JS_STRING)),
+        (PlaywrightStatement::Fill, format!(r"page\.locator\(\s*{}\s*\)(?:\.first\(\))?\.(?:fill|type|pressSequentially|selectOption)\(\s*{}", JS_STRING, JS_STRING)),
+        (PlaywrightStatement::WaitForSelector, format!(r"page\.waitForSelector\(\s*{}", JS_STRING)),
+        (PlaywrightStatement::WaitForSelector, format!(r"page\.locator\(\s*{}\s*\)(?:\.first\(\))?\.waitFor\(", JS_STRING)),
+        (PlaywrightStatement::WaitForTimeout, r"page\.waitForTimeout\(\s*(\d+)".to_string()),
+        (PlaywrightStatement::WaitForUrl, format!(r"page\.waitForURL\(\s*{}", JS_STRING)),
+        (PlaywrightStatement::ExpectTitle, format!(r"expect\(page\)\.toHaveTitle\(\s*{}", JS_STRING)),
+        (PlaywrightStatement::ExpectUrl, format!(r"expect\(page\)\.toHaveURL\(\s*{}", JS_STRING)),
+        (PlaywrightStatement::ExpectVisible, format!(r"expect\(page\.locator\(\s*{}\s*\)\)\.(?:toBeVisible|toBeAttached)\(", JS_STRING)),
+        (PlaywrightStatement::ExpectText, format!(r"expect\(page\.locator\(\s*{}\s*\)\)\.to(?:Have|Contain)Text\(\s*{}", JS_STRING, JS_STRING)),
+    ]
+    .into_iter()
+    .map(|(statement, pattern)| (statement, Regex::new(&pattern).expect("valid Playwright pattern")))
+    .collect();
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PlaywrightStatement {
+    Goto,
+    Click,
+    Fill,
+    WaitForSelector,
+    WaitForTimeout,
+    WaitForUrl,
+    ExpectTitle,
+    ExpectUrl,
+    ExpectVisible,
+    ExpectText,
+}
+
+/// Content of the `n`th JavaScript string of a match (each takes three groups)
+fn js_string(captures: &Captures, n: usize) -> String {
+    (1..=3)
+        .find_map(|group| captures.get(n * 3 + group))
+        .map(|m| convert_variables(m.as_str()))
+        .unwrap_or_default()
+}
+
+/// CSS selector of a Playwright selector
+fn playwright_selector(selector: &str) -> std::result::Result<String, String> {
+    if let Some(css) = selector.strip_prefix("css=") {
+        return Ok(css.to_string());
+    }
+    if selector.contains(">>") || selector.starts_with("text=") || selector.starts_with("role=")
+        || selector.starts_with("xpath=") || selector.starts_with("//") || selector.contains(":has-text(")
+    {
+        return Err(format!("selector '{}' is not CSS", selector));
+    }
+    Ok(selector.to_string())
+}
+
+/// Convert a Playwright test file, one workflow per `test(...)` block, or a single
+/// workflow when the script has none
+pub fn import_playwright(script: &str) -> Result<Vec<ImportedWorkflow>> {
+    let mut blocks: Vec<(String, &str)> = Vec::new();
+    let starts: Vec<(usize, String)> = TEST_BLOCK.captures_iter(script)
+        .map(|captures| (captures.get(0).unwrap().start(), js_string(&captures, 0)))
+        .collect();
+    if starts.is_empty() {
+        blocks.push(("Imported Playwright script".to_string(), script));
+    }
+    for (index, (start, name)) in starts.iter().enumerate() {
+        let end = starts.get(index + 1).map_or(script.len(), |(next, _)| *next);
+        blocks.push((name.clone(), &script[*start..end]));
+    }
+
+    Ok(blocks.into_iter()
+        .map(|(name, body)| {
+            let mut warnings = Vec::new();
+            let mut steps = Vec::new();
+            for (line_number, line) in body.lines().enumerate() {
+                let line = line.trim();
+                if line.starts_with("//") || !(line.contains("page.") || line.contains("expect(")) {
+                    continue;
+                }
+                match playwright_step(line) {
+                    Some(Ok(step)) => steps.push(step),
+                    Some(Err(warning)) => warnings.push(format!("Line {} of '{}': {}", line_number + 1, name, warning)),
+                    None if line.starts_with("test(") || line.contains("async ({") => {}
+                    None => warnings.push(format!("Line {} of '{}': unsupported statement `{}`", line_number + 1, name, line)),
+                }
+            }
+            ImportedWorkflow {
+                workflow: workflow(&name, "Imported from a Playwright test".to_string(), HashMap::new(), steps),
+                warnings,
+            }
+        })
+        .collect())
+}
+
+fn playwright_step(line: &str) -> Option<std::result::Result<WorkflowStep, String>> {
+    let (statement, captures) = PLAYWRIGHT_STATEMENTS.iter()
+        .find_map(|(statement, pattern)| pattern.captures(line).map(|captures| (*statement, captures)))?;
+
+    let selector = || playwright_selector(&js_string(&captures, 0));
+    let converted = (|| -> std::result::Result<WorkflowStep, String> {
+        let (name, action) = match statement {
+            PlaywrightStatement::Goto => {
+                let url = js_string(&captures, 0);
+                (format!("Open {}", url), ActionType::Navigate { url, screenshot: false })
+            }
+            PlaywrightStatement::Click => {
+                let selector = selector()?;
+                (format!("Click {}", selector), ActionType::Click { selector, wait_after: 0 })
+            }
+            PlaywrightStatement::Fill => {
+                let selector = selector()?;
+                (format!("Fill {}", selector), ActionType::Fill { selector, value: js_string(&captures, 1) })
+            }
+            PlaywrightStatement::WaitForSelector => {
+                let selector = selector()?;
+                (format!("Wait for {}", selector), ActionType::Wait { wait_type: WaitType::Element { selector } })
+            }
+            PlaywrightStatement::WaitForTimeout => {
+                let millis: u64 = captures[1].parse().unwrap_or(1000);
+                let seconds = millis.div_ceil(1000).max(1);
+                (format!("Pause {}s", seconds), ActionType::Wait { wait_type: WaitType::Time { seconds } })
+            }
+            PlaywrightStatement::WaitForUrl => {
+                let pattern = js_string(&captures, 0).replace("**", "");
+                (format!("Wait for URL {}", pattern), ActionType::Wait { wait_type: WaitType::Url { pattern } })
+            }
+            PlaywrightStatement::ExpectTitle => {
+                let expected = js_string(&captures, 0);
+                (format!("Check title '{}'", expected), ActionType::Assert { assertion: AssertionType::Title { expected } })
+            }
+            PlaywrightStatement::ExpectUrl => {
+                let pattern = js_string(&captures, 0);
+                (format!("Check URL {}", pattern), ActionType::Assert { assertion: AssertionType::UrlMatches { pattern } })
+            }
+            PlaywrightStatement::ExpectVisible => {
+                let selector = selector()?;
+                (format!("Check {} exists", selector), ActionType::Assert { assertion: AssertionType::ElementExists { selector } })
+            }
+            PlaywrightStatement::ExpectText => {
+                // The workflow assertion checks the page text, not the element's
+                let text = js_string(&captures, 1);
+                (format!("Check text '{}'", text), ActionType::Assert { assertion: AssertionType::TextContains { text } })
+            }
+        };
+        Ok(step(name, action))
+    })();
+    Some(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selenium_ide_project_import() {
+        let side = r#"{
+            "name": "Shop", "url": "https://shop.test/",
+            "tests": [{"name": "Search", "commands": [
+                {"command": "open", "target": "/search", "value": ""},
+                {"command": "type", "target": "name=q", "value": "${product}"},
+                {"command": "click", "target": "css=button.submit", "value": ""},
+                {"command": "storeText", "target": "id=price", "value": "price"},
+                {"command": "click", "target": "xpath=//a[1]", "value": ""},
+                {"command": "//click", "target": "id=skipped", "value": ""}
+            ]}]
+        }"#;
+
+        let imported = import_script(side, Some("shop.side")).unwrap();
+        assert_eq!(imported.len(), 1);
+        let workflow = &imported[0].workflow;
+        assert_eq!(workflow.name, "Search");
+        assert_eq!(workflow.variables["base_url"], "https://shop.test");
+        assert_eq!(workflow.steps.len(), 4);
+        assert!(matches!(&workflow.steps[0].action, ActionType::Navigate { url, .. } if url == "{{base_url}}/search"));
+        assert!(matches!(&workflow.steps[1].action, ActionType::Fill { selector, value } if selector == "[name='q']" && value == "{{product}}"));
+        assert_eq!(workflow.steps[3].store_as.as_deref(), Some("price"));
+        assert_eq!(imported[0].warnings.len(), 1);
+        assert!(imported[0].warnings[0].contains("XPath"));
+
+        // The result is valid workflow YAML
+        Workflow::from_yaml(&workflow.to_yaml().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_playwright_script_import() {
+        let script = r#"
+import { test, expect } from '@playwright/test';
+
+test('login works', async ({ page }) => {
+  await page.goto('https://app.test/login');
+  await page.fill('#email', 'me@app.test');
+  await page.locator("input[type=password]").fill(`${process}`);
+  await page.click('text=Sign in');
+  await page.locator('button[type=submit]').click();
+  await expect(page).toHaveTitle('Dashboard');
+  await page.mouse.wheel(0, 100);
+});
+
+test('home', async ({ page }) => {
+  await page.goto("https://app.test/");
+  await expect(page.locator('h1')).toHaveText('Welcome');
+});
+"#;
+
+        let imported = import_script(script, Some("login.spec.ts")).unwrap();
+        assert_eq!(imported.len(), 2);
+
+        let login = &imported[0];
+        assert_eq!(login.workflow.name, "login works");
+        assert_eq!(login.workflow.steps.len(), 5);
+        assert!(matches!(&login.workflow.steps[2].action, ActionType::Fill { value, .. } if value == "{{process}}"));
+        assert!(matches!(&login.workflow.steps[3].action, ActionType::Click { selector, .. } if selector == "button[type=submit]"));
+        assert_eq!(login.warnings.len(), 2, "{:?}", login.warnings);
+
+        let home = &imported[1];
+        assert!(matches!(&home.workflow.steps[1].action,
+                         ActionType::Assert { assertion: AssertionType::TextContains { text } } if text == "Welcome"));
+        assert!(home.warnings.is_empty());
+    }
+}
@@ -0,0 +1,414 @@
+//! Workflow Library
+//!
+//! Server-side storage of workflow definitions. Every save adds a new semantic
+//! version, earlier versions stay retrievable, and any two versions can be diffed.
+
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tracing::info;
+
+use crate::workflow::Workflow;
+
+/// `major.minor.patch` version of a stored workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl SemVer {
+    pub const INITIAL: SemVer = SemVer { major: 1, minor: 0, patch: 0 };
+
+    /// Parse `1.2.3`, also accepting a leading `v` and a missing minor or patch (`v2`, `1.4`)
+    pub fn parse(text: &str) -> Result<Self> {
+        let trimmed = text.trim().trim_start_matches('v');
+        let mut parts = trimmed.split('.');
+        let mut next = |name: &str| -> Result<u64> {
+            match parts.next() {
+                Some(part) => part.parse().with_context(|| format!("Invalid {} version in '{}'", name, text)),
+                None => Ok(0),
+            }
+        };
+        let version = SemVer { major: next("major")?, minor: next("minor")?, patch: next("patch")? };
+        if parts.next().is_some() {
+            anyhow::bail!("Version '{}' has more than three parts", text);
+        }
+        Ok(version)
+    }
+
+    pub fn bump(self, bump: VersionBump) -> Self {
+        match bump {
+            VersionBump::Major => SemVer { major: self.major + 1, minor: 0, patch: 0 },
+            VersionBump::Minor => SemVer { minor: self.minor + 1, patch: 0, ..self },
+            VersionBump::Patch => SemVer { patch: self.patch + 1, ..self },
+        }
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for SemVer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SemVer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        SemVer::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Which part of the version a save without an explicit version increments
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionBump {
+    Major,
+    Minor,
+    #[default]
+    Patch,
+}
+
+/// One saved revision of a workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowVersion {
+    pub version: SemVer,
+    pub created_at: DateTime<Utc>,
+    pub notes: Option<String>,
+    pub definition: Workflow,
+}
+
+/// A stored workflow with all of its versions, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowEntry {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub versions: Vec<WorkflowVersion>,
+}
+
+impl WorkflowEntry {
+    pub fn latest(&self) -> &WorkflowVersion {
+        self.versions.iter().max_by_key(|version| version.version).expect("stored workflows have a version")
+    }
+
+    pub fn version(&self, version: SemVer) -> Option<&WorkflowVersion> {
+        self.versions.iter().find(|stored| stored.version == version)
+    }
+}
+
+/// Listing entry of a stored workflow
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowSummary {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub latest_version: SemVer,
+    pub versions: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One line of a diff between two versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", content = "line", rename_all = "snake_case")]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Line diff of the YAML of two versions of a workflow
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowDiff {
+    pub id: String,
+    pub from: SemVer,
+    pub to: SemVer,
+    pub added: usize,
+    pub removed: usize,
+    pub lines: Vec<DiffLine>,
+    /// The diff in unified format (`-`/`+`/space prefixed lines)
+    pub unified: String,
+}
+
+/// Versioned workflow storage, one JSON file per workflow
+pub struct WorkflowLibrary {
+    dir: PathBuf,
+    entries: RwLock<HashMap<String, WorkflowEntry>>,
+}
+
+impl WorkflowLibrary {
+    /// Open the library in `dir`, loading every stored workflow
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut entries = HashMap::new();
+        for file in std::fs::read_dir(&dir)? {
+            let path = file?.path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                let content = std::fs::read_to_string(&path)?;
+                let entry: WorkflowEntry = serde_json::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                entries.insert(entry.id.clone(), entry);
+            }
+        }
+        info!("📚 Loaded {} workflows from the library", entries.len());
+
+        Ok(Self { dir, entries: RwLock::new(entries) })
+    }
+
+    /// Whether `id` can name a workflow: letters, digits, `-` and `_`
+    pub fn is_valid_id(id: &str) -> bool {
+        !id.is_empty() && id.len() <= 100 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
+    /// Id derived from a workflow name, e.g. "Nightly Price Check" -> "nightly-price-check"
+    pub fn id_from_name(name: &str) -> String {
+        let slug: String = name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+        if slug.is_empty() { "workflow".to_string() } else { slug }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.read().unwrap().contains_key(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<WorkflowEntry> {
+        self.entries.read().unwrap().get(id).cloned()
+    }
+
+    /// Latest version of a workflow's definition
+    pub fn latest(&self, id: &str) -> Option<Workflow> {
+        self.entries.read().unwrap().get(id).map(|entry| entry.latest().definition.clone())
+    }
+
+    /// Stored workflows, most recently updated first
+    pub fn list(&self) -> Vec<WorkflowSummary> {
+        let mut summaries: Vec<WorkflowSummary> = self.entries.read().unwrap()
+            .values()
+            .map(|entry| {
+                let latest = entry.latest();
+                WorkflowSummary {
+                    id: entry.id.clone(),
+                    name: latest.definition.name.clone(),
+                    description: latest.definition.description.clone(),
+                    latest_version: latest.version,
+                    versions: entry.versions.len(),
+                    updated_at: entry.updated_at,
+                }
+            })
+            .collect();
+        summaries.sort_by_key(|summary| std::cmp::Reverse(summary.updated_at));
+        summaries
+    }
+
+    /// Store `definition` as a new workflow (at `version`, or 1.0.0) or as a new version of
+    /// an existing one (at `version`, or the latest version bumped by `bump`). Explicit
+    /// versions must be higher than every stored one.
+    pub fn save(&self, id: &str, definition: Workflow, version: Option<SemVer>, bump: VersionBump, notes: Option<String>) -> Result<WorkflowVersion> {
+        if !Self::is_valid_id(id) {
+            anyhow::bail!("Invalid workflow id '{}': use letters, digits, '-' and '_'", id);
+        }
+
+        let mut entries = self.entries.write().unwrap();
+        let now = Utc::now();
+        // Work on a copy so a failed write leaves the stored entry untouched
+        let mut entry = entries.get(id).cloned().unwrap_or_else(|| WorkflowEntry {
+            id: id.to_string(),
+            created_at: now,
+            updated_at: now,
+            versions: Vec::new(),
+        });
+
+        let latest = entry.versions.iter().map(|stored| stored.version).max();
+        let version = match (version, latest) {
+            (Some(version), Some(latest)) if version <= latest => {
+                anyhow::bail!("Version {} of '{}' must be higher than the latest version {}", version, id, latest);
+            }
+            (Some(version), _) => version,
+            (None, Some(latest)) => latest.bump(bump),
+            (None, None) => SemVer::INITIAL,
+        };
+
+        let saved = WorkflowVersion { version, created_at: now, notes, definition };
+        entry.versions.push(saved.clone());
+        entry.updated_at = now;
+        self.persist(&entry)?;
+        entries.insert(id.to_string(), entry);
+
+        info!("📚 Saved workflow '{}' version {}", id, version);
+        Ok(saved)
+    }
+
+    /// Delete a workflow and all its versions, returning whether it existed
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        let removed = self.entries.write().unwrap().remove(id).is_some();
+        if removed {
+            std::fs::remove_file(self.dir.join(format!("{}.json", id)))?;
+        }
+        Ok(removed)
+    }
+
+    /// Diff the YAML of two versions of a workflow
+    pub fn diff(&self, id: &str, from: SemVer, to: SemVer) -> Result<WorkflowDiff> {
+        let entry = self.get(id).ok_or_else(|| anyhow::anyhow!("Workflow '{}' not found", id))?;
+        let yaml = |version: SemVer| -> Result<String> {
+            entry.version(version)
+                .ok_or_else(|| anyhow::anyhow!("Workflow '{}' has no version {}", id, version))?
+                .definition
+                .to_yaml()
+        };
+        let (old, new) = (yaml(from)?, yaml(to)?);
+
+        let lines = diff_lines(&old, &new);
+        let unified = lines.iter()
+            .map(|line| match line {
+                DiffLine::Same(text) => format!(" {}", text),
+                DiffLine::Removed(text) => format!("-{}", text),
+                DiffLine::Added(text) => format!("+{}", text),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(WorkflowDiff {
+            id: id.to_string(),
+            from,
+            to,
+            added: lines.iter().filter(|line| matches!(line, DiffLine::Added(_))).count(),
+            removed: lines.iter().filter(|line| matches!(line, DiffLine::Removed(_))).count(),
+            lines,
+            unified,
+        })
+    }
+
+    fn persist(&self, entry: &WorkflowEntry) -> Result<()> {
+        std::fs::write(self.dir.join(format!("{}.json", entry.id)), serde_json::to_string_pretty(entry)?)?;
+        Ok(())
+    }
+}
+
+/// Line diff through the longest common subsequence of the two texts' lines
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line.to_string())));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line.to_string())));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workflow(url: &str) -> Workflow {
+        Workflow::from_yaml(&format!(
+            "name: Price check\nvariables: {{}}\nsteps:\n  - name: Open\n    action:\n      type: navigate\n      url: \"{}\"\n",
+            url
+        )).unwrap()
+    }
+
+    #[test]
+    fn test_semver_parse_and_bump() {
+        assert_eq!(SemVer::parse("v1.4").unwrap(), SemVer { major: 1, minor: 4, patch: 0 });
+        assert_eq!(SemVer::parse("2.0.3").unwrap().bump(VersionBump::Minor).to_string(), "2.1.0");
+        assert_eq!(SemVer::INITIAL.bump(VersionBump::Major).to_string(), "2.0.0");
+        assert!(SemVer::parse("1.x").is_err());
+        assert!(SemVer::parse("1.2.3.4").is_err());
+    }
+
+    #[test]
+    fn test_versions_are_stored_and_diffed() {
+        let dir = TempDir::new().unwrap();
+        let library = WorkflowLibrary::new(dir.path()).unwrap();
+
+        let first = library.save("price-check", workflow("https://shop.test/a"), None, VersionBump::Patch, None).unwrap();
+        assert_eq!(first.version, SemVer::INITIAL);
+        let second = library.save("price-check", workflow("https://shop.test/b"), None, VersionBump::Minor, Some("new shop".to_string())).unwrap();
+        assert_eq!(second.version.to_string(), "1.1.0");
+        assert!(library.save("price-check", workflow("https://shop.test/c"), Some(SemVer::INITIAL), VersionBump::Patch, None).is_err());
+        assert!(library.save("../escape", workflow("https://shop.test/c"), None, VersionBump::Patch, None).is_err());
+
+        let reopened = WorkflowLibrary::new(dir.path()).unwrap();
+        let summaries = reopened.list();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].versions, 2);
+        assert_eq!(summaries[0].latest_version.to_string(), "1.1.0");
+
+        let diff = reopened.diff("price-check", first.version, second.version).unwrap();
+        assert_eq!((diff.added, diff.removed), (1, 1));
+        assert!(diff.lines.iter().any(|line| matches!(line, DiffLine::Removed(text) if text.contains("shop.test/a"))));
+        assert!(diff.unified.lines().any(|line| line.starts_with('+') && line.contains("shop.test/b")));
+
+        assert!(reopened.delete("price-check").unwrap());
+        assert!(!reopened.contains("price-check"));
+    }
+
+    #[test]
+    fn test_failed_save_leaves_entry_unchanged() {
+        let dir = TempDir::new().unwrap();
+        let library = WorkflowLibrary::new(dir.path()).unwrap();
+        library.save("price-check", workflow("https://shop.test/a"), None, VersionBump::Patch, None).unwrap();
+
+        // A directory in place of the entry's file makes the write fail
+        std::fs::remove_file(dir.path().join("price-check.json")).unwrap();
+        std::fs::create_dir(dir.path().join("price-check.json")).unwrap();
+        assert!(library.save("price-check", workflow("https://shop.test/b"), None, VersionBump::Minor, None).is_err());
+        std::fs::create_dir(dir.path().join("new-check.json")).unwrap();
+        assert!(library.save("new-check", workflow("https://shop.test/c"), None, VersionBump::Patch, None).is_err());
+
+        let entry = library.get("price-check").unwrap();
+        assert_eq!(entry.versions.len(), 1);
+        assert_eq!(entry.latest().version, SemVer::INITIAL);
+        assert!(!library.contains("new-check"));
+    }
+
+    #[test]
+    fn test_id_from_name() {
+        assert_eq!(WorkflowLibrary::id_from_name("Nightly Price Check!"), "nightly-price-check");
+        assert_eq!(WorkflowLibrary::id_from_name("***"), "workflow");
+    }
+}
@@ -0,0 +1,279 @@
+//! Workflow Webhooks
+//!
+//! Workflows list webhooks that are called when a run completes or fails, with the run
+//! status, its artifacts index and the data it extracted, so CI systems and automation
+//! pipelines can react to runs without polling.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::workflow::{Artifact, ArtifactKind, Workflow, WorkflowResult};
+
+/// Delivery attempts per webhook before giving up
+const MAX_DELIVERY_ATTEMPTS: u32 = 3;
+
+/// Endpoint notified when a run finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Outcomes to notify about, both by default
+    #[serde(default = "all_webhook_events")]
+    pub on: Vec<WebhookEvent>,
+    /// Extra request headers, e.g. an `Authorization` token
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Completed,
+    Failed,
+}
+
+impl WebhookEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::Completed => "workflow.completed",
+            WebhookEvent::Failed => "workflow.failed",
+        }
+    }
+}
+
+fn all_webhook_events() -> Vec<WebhookEvent> {
+    vec![WebhookEvent::Completed, WebhookEvent::Failed]
+}
+
+/// Body posted to webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// `workflow.completed` or `workflow.failed`
+    pub event: String,
+    pub workflow: String,
+    pub run_id: Option<uuid::Uuid>,
+    pub success: bool,
+    pub steps_executed: usize,
+    pub steps_failed: usize,
+    pub duration_ms: u64,
+    /// Last step error of a failed run
+    pub error: Option<String>,
+    pub artifacts: Vec<WebhookArtifact>,
+    /// Variables the run set, such as `store_as` results; inputs are left out
+    pub data: HashMap<String, serde_json::Value>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Artifact entry of a webhook payload, with the API path serving it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookArtifact {
+    pub name: String,
+    pub kind: ArtifactKind,
+    pub content_type: String,
+    pub size: usize,
+    pub path: String,
+}
+
+impl WebhookPayload {
+    pub fn new(workflow: &Workflow, result: &WorkflowResult) -> Self {
+        let event = if result.success { WebhookEvent::Completed } else { WebhookEvent::Failed };
+        Self {
+            event: event.name().to_string(),
+            workflow: workflow.name.clone(),
+            run_id: result.run_id,
+            success: result.success,
+            steps_executed: result.steps_executed,
+            steps_failed: result.steps_failed,
+            duration_ms: result.duration_ms,
+            error: if result.success { None } else { result.execution_log.iter().rev().find_map(|entry| entry.error.clone()) },
+            artifacts: result.artifacts.iter().map(|artifact| WebhookArtifact::new(result.run_id, artifact)).collect(),
+            data: result.extracted_data(workflow),
+            finished_at: Utc::now(),
+        }
+    }
+}
+
+impl WebhookArtifact {
+    fn new(run_id: Option<uuid::Uuid>, artifact: &Artifact) -> Self {
+        Self {
+            name: artifact.name.clone(),
+            kind: artifact.kind,
+            content_type: artifact.content_type.clone(),
+            size: artifact.size,
+            path: run_id.map(|id| format!("/api/workflow/{}/artifacts/{}", id, artifact.name)).unwrap_or_default(),
+        }
+    }
+}
+
+/// Outcome of calling one webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub url: String,
+    pub event: String,
+    pub delivered: bool,
+    /// HTTP status of the last attempt, if the webhook answered
+    pub status: Option<u16>,
+    pub attempts: u32,
+    pub error: Option<String>,
+}
+
+/// Call the webhooks of `workflow` subscribed to the run's outcome. Failed deliveries
+/// are retried, then reported in the returned list; they never fail the run.
+pub async fn notify_webhooks(workflow: &Workflow, result: &WorkflowResult) -> Vec<WebhookDelivery> {
+    let payload = WebhookPayload::new(workflow, result);
+    let event = if result.success { WebhookEvent::Completed } else { WebhookEvent::Failed };
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Failed to create webhook client: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut deliveries = Vec::new();
+    for webhook in workflow.webhooks.iter().filter(|webhook| webhook.on.contains(&event)) {
+        let delivery = deliver(&client, webhook, &payload).await;
+        if delivery.delivered {
+            info!("🔔 Notified webhook {} of {}", webhook.url, payload.event);
+        } else {
+            warn!("Webhook {} not notified of {}: {:?}", webhook.url, payload.event, delivery.error);
+        }
+        deliveries.push(delivery);
+    }
+    deliveries
+}
+
+async fn deliver(client: &reqwest::Client, webhook: &WebhookConfig, payload: &WebhookPayload) -> WebhookDelivery {
+    let mut delivery = WebhookDelivery {
+        url: webhook.url.clone(),
+        event: payload.event.clone(),
+        delivered: false,
+        status: None,
+        attempts: 0,
+        error: None,
+    };
+    if !(webhook.url.starts_with("http://") || webhook.url.starts_with("https://")) {
+        delivery.error = Some("Webhook URLs must use http or https".to_string());
+        return delivery;
+    }
+
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        if attempt > 1 {
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 2))).await;
+        }
+        delivery.attempts = attempt;
+
+        match send(client, webhook, payload).await {
+            Ok(status) if status.is_success() => {
+                delivery.status = Some(status.as_u16());
+                delivery.delivered = true;
+                delivery.error = None;
+                break;
+            }
+            Ok(status) => {
+                delivery.status = Some(status.as_u16());
+                delivery.error = Some(format!("Webhook answered {}", status));
+                // Client errors other than throttling will not change on retry
+                if status.is_client_error() && status.as_u16() != 429 {
+                    break;
+                }
+            }
+            Err(e) => {
+                delivery.status = None;
+                delivery.error = Some(e.to_string());
+            }
+        }
+    }
+    delivery
+}
+
+async fn send(client: &reqwest::Client, webhook: &WebhookConfig, payload: &WebhookPayload) -> Result<reqwest::StatusCode> {
+    let mut request = client.post(&webhook.url)
+        .header("X-Rainbow-Event", &payload.event)
+        .json(payload);
+    for (name, value) in &webhook.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    Ok(request.send().await?.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, serde_json::Value)>>>;
+
+    async fn receiver() -> (String, Received) {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route("/hook", post(|State(received): State<Received>, headers: HeaderMap, Json(body): Json<serde_json::Value>| async move {
+                received.lock().unwrap().push((headers, body));
+                "ok"
+            }))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    fn result(success: bool) -> WorkflowResult {
+        serde_json::from_value(serde_json::json!({
+            "run_id": uuid::Uuid::new_v4(),
+            "success": success,
+            "duration_ms": 1200,
+            "steps_executed": 3,
+            "steps_failed": if success { 0 } else { 1 },
+            "variables": {"query": "rust", "price": "$12", "_loop_index": 2, "site": "https://shop.test"},
+            "execution_log": [],
+            "cost": 0.0,
+            "artifacts": [{"name": "prices.json", "kind": "json", "step": "Read prices", "content_type": "application/json", "size": 12, "created_at": Utc::now()}],
+        })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_receive_run_outcome() {
+        let (url, received) = receiver().await;
+        let workflow = Workflow::from_yaml(&format!(r#"
+name: price-watch
+inputs:
+  - {{name: query, input_type: string, required: true}}
+variables:
+  site: "https://shop.test"
+steps: []
+webhooks:
+  - url: "{url}"
+    headers: {{Authorization: "Bearer ci-token"}}
+  - url: "{url}"
+    on: [failed]
+"#)).unwrap();
+
+        let deliveries = notify_webhooks(&workflow, &result(true)).await;
+        assert_eq!(deliveries.len(), 1, "only the first webhook wants completions");
+        assert!(deliveries[0].delivered);
+        assert_eq!(deliveries[0].status, Some(200));
+
+        let received = received.lock().unwrap();
+        let (headers, body) = &received[0];
+        assert_eq!(headers["authorization"], "Bearer ci-token");
+        assert_eq!(headers["x-rainbow-event"], "workflow.completed");
+        assert_eq!(body["workflow"], "price-watch");
+        assert_eq!(body["data"], serde_json::json!({"price": "$12"}));
+        assert!(body["artifacts"][0]["path"].as_str().unwrap().ends_with("/artifacts/prices.json"));
+    }
+
+    #[tokio::test]
+    async fn test_undeliverable_webhooks_are_reported() {
+        let workflow = Workflow::from_yaml("name: broken\nsteps: []\nwebhooks:\n  - url: \"ftp://example.test/hook\"\n").unwrap();
+        let deliveries = notify_webhooks(&workflow, &result(false)).await;
+        assert_eq!(deliveries.len(), 1);
+        assert!(!deliveries[0].delivered);
+        assert_eq!(deliveries[0].attempts, 0);
+        assert_eq!(deliveries[0].event, "workflow.failed");
+    }
+}
@@ -622,6 +622,13 @@ class RainbowDashboard {
             });
 
             resultContainer.textContent = JSON.stringify(response, null, 2);
+            if (response.run_id) {
+                const reportLink = document.createElement('a');
+                reportLink.href = `${this.apiUrl}/api/workflow/${response.run_id}/report`;
+                reportLink.target = '_blank';
+                reportLink.textContent = 'Open run report';
+                resultContainer.prepend(reportLink, document.createElement('br'));
+            }
             this.showNotification('Workflow executed successfully', 'success');
         } catch (error) {
             resultContainer.textContent = `Error: ${error.message}`;
@@ -316,10 +316,8 @@ impl ShoppingAssistant {
 
     /// 搜索商品
     async fn search_products(&mut self, user_input: &str) -> Result<ShoppingResponse> {
-        let search_prompt = format!(
-            "用户想要搜索商品：{}。请分析商品需求并提供搜索建议。",
-            user_input
-        );
+        let search_prompt = self.executor.prompts()
+            .render("shopping.search_products", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&search_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -390,10 +388,8 @@ impl ShoppingAssistant {
 
     /// 比较商品
     async fn compare_products(&mut self, user_input: &str) -> Result<ShoppingResponse> {
-        let compare_prompt = format!(
-            "用户想要比较商品：{}。请提供商品比较分析。",
-            user_input
-        );
+        let compare_prompt = self.executor.prompts()
+            .render("shopping.compare_products", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&compare_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -429,10 +425,8 @@ impl ShoppingAssistant {
 
     /// 添加到购物车
     async fn add_to_cart(&mut self, user_input: &str) -> Result<ShoppingResponse> {
-        let add_prompt = format!(
-            "用户想要添加商品到购物车：{}。请确认操作。",
-            user_input
-        );
+        let add_prompt = self.executor.prompts()
+            .render("shopping.add_to_cart", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&add_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -484,10 +478,8 @@ impl ShoppingAssistant {
 
     /// 检查价格
     async fn check_prices(&mut self, user_input: &str) -> Result<ShoppingResponse> {
-        let price_prompt = format!(
-            "用户想要检查商品价格：{}。请提供价格信息和建议。",
-            user_input
-        );
+        let price_prompt = self.executor.prompts()
+            .render("shopping.check_prices", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&price_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -507,10 +499,8 @@ impl ShoppingAssistant {
 
     /// 设置价格提醒
     async fn set_price_alert(&mut self, user_input: &str) -> Result<ShoppingResponse> {
-        let alert_prompt = format!(
-            "用户想要设置价格提醒：{}。请确认设置。",
-            user_input
-        );
+        let alert_prompt = self.executor.prompts()
+            .render("shopping.set_price_alert", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&alert_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -306,10 +306,8 @@ impl TravelAssistant {
     /// 规划旅行
     async fn plan_trip(&mut self, user_input: &str) -> Result<TravelResponse> {
         // 使用LLM分析用户需求
-        let plan_prompt = format!(
-            "用户想要规划旅行：{}。请提供旅行建议，包括目的地、行程、预算等。",
-            user_input
-        );
+        let plan_prompt = self.executor.prompts()
+            .render("travel.plan_trip", &[("user_input", user_input)])?;
         
         let llm_response = self.executor.execute_smart_request(&plan_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -347,10 +345,8 @@ impl TravelAssistant {
 
     /// 搜索目的地
     async fn search_destinations(&mut self, user_input: &str) -> Result<TravelResponse> {
-        let search_prompt = format!(
-            "用户想要搜索旅行目的地：{}。请推荐合适的目的地。",
-            user_input
-        );
+        let search_prompt = self.executor.prompts()
+            .render("travel.search_destinations", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&search_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -399,10 +395,8 @@ impl TravelAssistant {
 
     /// 预订住宿
     async fn book_accommodation(&mut self, user_input: &str) -> Result<TravelResponse> {
-        let booking_prompt = format!(
-            "用户想要预订住宿：{}。请提供住宿建议和预订信息。",
-            user_input
-        );
+        let booking_prompt = self.executor.prompts()
+            .render("travel.book_accommodation", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&booking_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -422,10 +416,8 @@ impl TravelAssistant {
 
     /// 预订交通
     async fn book_transportation(&mut self, user_input: &str) -> Result<TravelResponse> {
-        let transport_prompt = format!(
-            "用户想要预订交通：{}。请提供交通建议和预订信息。",
-            user_input
-        );
+        let transport_prompt = self.executor.prompts()
+            .render("travel.book_transportation", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&transport_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -445,10 +437,8 @@ impl TravelAssistant {
 
     /// 查找活动
     async fn find_activities(&mut self, user_input: &str) -> Result<TravelResponse> {
-        let activity_prompt = format!(
-            "用户想要查找旅行活动：{}。请推荐合适的活动。",
-            user_input
-        );
+        let activity_prompt = self.executor.prompts()
+            .render("travel.find_activities", &[("user_input", user_input)])?;
 
         let llm_response = self.executor.execute_smart_request(&activity_prompt).await
             .map_err(|e| BrowserError::ExecutionError(e.to_string()))?;
@@ -6,12 +6,14 @@ use std::collections::HashMap;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use super::llm::{SmartIntentAnalyzer, SmartIntent, TaskStep, LLMConfig};
+use super::prompts::PromptRegistry;
 
 /// 智能执行器
 pub struct SmartExecutor {
     intent_analyzer: SmartIntentAnalyzer,
     browser_manager: BrowserManager,
     execution_history: Vec<ExecutionRecord>,
+    prompts: PromptRegistry,
 }
 
 /// 浏览器管理器 (简化版本)
@@ -238,9 +240,15 @@ impl SmartExecutor {
             intent_analyzer: SmartIntentAnalyzer::new(llm_config),
             browser_manager: BrowserManager::new(),
             execution_history: Vec::new(),
+            prompts: PromptRegistry::from_env(),
         }
     }
     
+    /// 提示词模板
+    pub fn prompts(&self) -> &PromptRegistry {
+        &self.prompts
+    }
+    
     /// 智能执行用户请求
     pub async fn execute_smart_request(&mut self, user_input: &str) -> Result<SmartExecutionResult, Box<dyn std::error::Error>> {
         let start_time = std::time::Instant::now();
@@ -285,7 +293,7 @@ impl SmartExecutor {
         
         // 4. 收集执行结果
         let extracted_data = self.collect_extracted_data(&session_id);
-        let execution_summary = self.create_execution_summary(&execution_steps);
+        let execution_summary = self.create_execution_summary(&intent, &execution_steps)?;
         
         // 5. 生成智能回复
         let llm_response = self.intent_analyzer
@@ -333,23 +341,23 @@ impl SmartExecutor {
         result
     }
     
-    fn create_execution_summary(&self, steps: &[ExecutionStep]) -> String {
+    fn create_execution_summary(&self, intent: &SmartIntent, steps: &[ExecutionStep]) -> crate::error::Result<String> {
         let total_steps = steps.len();
         let successful_steps = steps.iter().filter(|s| s.success).count();
         let total_duration: u64 = steps.iter().map(|s| s.duration_ms).sum();
+        let step_details = steps.iter()
+            .enumerate()
+            .map(|(i, s)| format!("{}. {} - {}", i + 1, s.step_name, 
+                if s.success { "✅" } else { "❌" }))
+            .collect::<Vec<_>>()
+            .join("\n");
         
-        format!(
-            "执行了 {} 个步骤，成功 {} 个，总耗时 {}ms。\n步骤详情:\n{}",
-            total_steps,
-            successful_steps, 
-            total_duration,
-            steps.iter()
-                .enumerate()
-                .map(|(i, s)| format!("{}. {} - {}", i + 1, s.step_name, 
-                    if s.success { "✅" } else { "❌" }))
-                .collect::<Vec<_>>()
-                .join("\n")
-        )
+        self.prompts.render_for_task("executor.summary", &intent.intent_type, &[
+            ("total_steps", &total_steps.to_string()),
+            ("successful_steps", &successful_steps.to_string()),
+            ("total_duration_ms", &total_duration.to_string()),
+            ("step_details", &step_details),
+        ])
     }
     
     fn generate_recommendations(&self, intent: &SmartIntent, data: &HashMap<String, Vec<String>>) -> Vec<String> {
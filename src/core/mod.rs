@@ -4,4 +4,5 @@
 
 pub mod llm;
 pub mod executor;
+pub mod prompts;
 pub mod session;
\ No newline at end of file
@@ -0,0 +1,261 @@
+//! # 提示词模板 (Prompt Templates)
+//!
+//! 命名模板、变量插值、文件覆盖和版本管理，调整提示词无需重新编译
+//!
+//! 模板使用 `{{变量名}}` 插值。设置 `RAINBOW_PROMPTS_DIR` 后，目录下的每个
+//! `.json` 文件覆盖一个模板:
+//!
+//! ```json
+//! { "name": "shopping.search_products", "version": 2, "template": "..." }
+//! ```
+//!
+//! 带 `task` 字段的模板只对该任务类型生效 (例如 `"task": "travel_search"`)
+
+use std::collections::HashMap;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{BrowserError, Result};
+
+/// 覆盖目录的环境变量
+pub const PROMPTS_DIR_ENV: &str = "RAINBOW_PROMPTS_DIR";
+
+/// 内置模板 (名称, 模板)
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("intent.analyze_request", r#"
+分析用户请求并生成浏览器操作计划。
+
+用户请求: {{user_request}}
+
+请生成一个包含以下字段的JSON对象:
+- intent: 用户意图类型 (search/navigate/extract/interact)
+- actions: 操作步骤数组，每个操作包含 type 和 parameters
+- expected_outcome: 预期结果描述
+
+示例操作类型:
+- Navigate: {"type": "navigate", "url": "https://example.com"}
+- Input: {"type": "input", "selector": "input[name='q']", "text": "search text"}
+- Click: {"type": "click", "selector": "button[type='submit']"}
+- Extract: {"type": "extract", "selector": ".result"}
+        "#),
+    ("intent.continue_after_error", "执行操作时遇到错误: {{error}}\n是否应该继续执行后续步骤？(yes/no)"),
+    ("executor.summary", "执行了 {{total_steps}} 个步骤，成功 {{successful_steps}} 个，总耗时 {{total_duration_ms}}ms。\n步骤详情:\n{{step_details}}"),
+    ("shopping.search_products", "用户想要搜索商品：{{user_input}}。请分析商品需求并提供搜索建议。"),
+    ("shopping.compare_products", "用户想要比较商品：{{user_input}}。请提供商品比较分析。"),
+    ("shopping.add_to_cart", "用户想要添加商品到购物车：{{user_input}}。请确认操作。"),
+    ("shopping.check_prices", "用户想要检查商品价格：{{user_input}}。请提供价格信息和建议。"),
+    ("shopping.set_price_alert", "用户想要设置价格提醒：{{user_input}}。请确认设置。"),
+    ("travel.plan_trip", "用户想要规划旅行：{{user_input}}。请提供旅行建议，包括目的地、行程、预算等。"),
+    ("travel.search_destinations", "用户想要搜索旅行目的地：{{user_input}}。请推荐合适的目的地。"),
+    ("travel.book_accommodation", "用户想要预订住宿：{{user_input}}。请提供住宿建议和预订信息。"),
+    ("travel.book_transportation", "用户想要预订交通：{{user_input}}。请提供交通建议和预订信息。"),
+    ("travel.find_activities", "用户想要查找旅行活动：{{user_input}}。请推荐合适的活动。"),
+];
+
+/// 提示词模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    /// 版本号，同名模板使用最高版本
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// 只对该任务类型生效的覆盖
+    #[serde(default)]
+    pub task: Option<String>,
+    pub template: String,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl PromptTemplate {
+    pub fn new(name: &str, template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: default_version(),
+            task: None,
+            template: template.to_string(),
+        }
+    }
+
+    /// 插值 `{{变量名}}`，缺少变量时报错
+    pub fn render(&self, vars: &[(&str, &str)]) -> Result<String> {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| BrowserError::ConfigError(
+                format!("提示词模板 {} 有未闭合的变量", self.name)))?;
+            let var = after[..end].trim();
+            let value = vars.iter()
+                .find(|(name, _)| *name == var)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| BrowserError::ConfigError(
+                    format!("提示词模板 {} 缺少变量: {}", self.name, var)))?;
+            rendered.push_str(value);
+            rest = &after[end + 2..];
+        }
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+}
+
+/// 提示词注册表
+#[derive(Debug, Clone)]
+pub struct PromptRegistry {
+    /// (名称, 任务类型) -> 按版本排序的模板
+    templates: HashMap<(String, Option<String>), Vec<PromptTemplate>>,
+}
+
+impl Default for PromptRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PromptRegistry {
+    /// 只含内置模板的注册表
+    pub fn new() -> Self {
+        let mut registry = Self { templates: HashMap::new() };
+        for (name, template) in BUILTIN_TEMPLATES {
+            registry.register(PromptTemplate::new(name, template));
+        }
+        registry
+    }
+
+    /// 内置模板加上 `RAINBOW_PROMPTS_DIR` 中的覆盖，加载失败时只用内置模板
+    pub fn from_env() -> Self {
+        let mut registry = Self::new();
+        if let Ok(dir) = std::env::var(PROMPTS_DIR_ENV) {
+            match registry.load_overrides(&dir) {
+                Ok(count) => log::info!("加载了 {} 个提示词覆盖: {}", count, dir),
+                Err(e) => log::warn!("提示词覆盖加载失败: {}", e),
+            }
+        }
+        registry
+    }
+
+    /// 注册模板；同版本时后注册的生效
+    pub fn register(&mut self, template: PromptTemplate) {
+        let versions = self.templates
+            .entry((template.name.clone(), template.task.clone()))
+            .or_default();
+        versions.retain(|existing| existing.version != template.version);
+        versions.push(template);
+        versions.sort_by_key(|t| t.version);
+    }
+
+    /// 加载目录中的 `.json` 覆盖文件，返回加载的模板数
+    pub fn load_overrides(&mut self, dir: impl AsRef<Path>) -> Result<usize> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir).map_err(|e| BrowserError::ConfigError(
+            format!("无法读取提示词目录 {}: {}", dir.display(), e)))?;
+
+        let mut count = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| BrowserError::ConfigError(
+                format!("无法读取提示词文件 {}: {}", path.display(), e)))?;
+            let template: PromptTemplate = serde_json::from_str(&content).map_err(|e| BrowserError::ConfigError(
+                format!("提示词文件格式错误 {}: {}", path.display(), e)))?;
+            self.register(template);
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// 当前生效的模板
+    pub fn get(&self, name: &str) -> Option<&PromptTemplate> {
+        self.templates.get(&(name.to_string(), None))?.last()
+    }
+
+    /// 指定版本的模板
+    pub fn get_version(&self, name: &str, version: u32) -> Option<&PromptTemplate> {
+        self.templates.get(&(name.to_string(), None))?
+            .iter()
+            .find(|t| t.version == version)
+    }
+
+    /// 任务类型的覆盖，没有时用通用模板
+    pub fn get_for_task(&self, name: &str, task: &str) -> Option<&PromptTemplate> {
+        self.templates.get(&(name.to_string(), Some(task.to_string())))
+            .and_then(|versions| versions.last())
+            .or_else(|| self.get(name))
+    }
+
+    /// 渲染当前生效的模板
+    pub fn render(&self, name: &str, vars: &[(&str, &str)]) -> Result<String> {
+        self.get(name)
+            .ok_or_else(|| BrowserError::ConfigError(format!("未知的提示词模板: {}", name)))?
+            .render(vars)
+    }
+
+    /// 按任务类型渲染模板
+    pub fn render_for_task(&self, name: &str, task: &str, vars: &[(&str, &str)]) -> Result<String> {
+        self.get_for_task(name, task)
+            .ok_or_else(|| BrowserError::ConfigError(format!("未知的提示词模板: {}", name)))?
+            .render(vars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_interpolates_and_rejects_missing_variables() {
+        let registry = PromptRegistry::new();
+        let prompt = registry.render("shopping.search_products", &[("user_input", "机械键盘")]).unwrap();
+        assert_eq!(prompt, "用户想要搜索商品：机械键盘。请分析商品需求并提供搜索建议。");
+
+        // JSON示例中的单花括号保持原样
+        let prompt = registry.render("intent.analyze_request", &[("user_request", "搜索天气")]).unwrap();
+        assert!(prompt.contains("用户请求: 搜索天气"));
+        assert!(prompt.contains(r#"{"type": "navigate", "url": "https://example.com"}"#));
+
+        assert!(registry.render("shopping.search_products", &[]).is_err());
+        assert!(registry.render("unknown.prompt", &[]).is_err());
+    }
+
+    #[test]
+    fn test_higher_versions_and_task_overrides_win() {
+        let mut registry = PromptRegistry::new();
+        registry.register(PromptTemplate {
+            version: 2,
+            ..PromptTemplate::new("travel.plan_trip", "规划: {{user_input}}")
+        });
+        registry.register(PromptTemplate {
+            task: Some("travel_search".to_string()),
+            ..PromptTemplate::new("executor.summary", "{{successful_steps}}/{{total_steps}}")
+        });
+
+        assert_eq!(registry.get("travel.plan_trip").unwrap().version, 2);
+        assert!(registry.get_version("travel.plan_trip", 1).unwrap().template.contains("旅行建议"));
+
+        let vars = [("total_steps", "3"), ("successful_steps", "2"), ("total_duration_ms", "10"), ("step_details", "")];
+        assert_eq!(registry.render_for_task("executor.summary", "travel_search", &vars).unwrap(), "2/3");
+        assert!(registry.render_for_task("executor.summary", "shopping", &vars).unwrap().starts_with("执行了 3 个步骤"));
+    }
+
+    #[test]
+    fn test_load_overrides_from_directory() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        std::fs::write(dir.child("search.json"), r#"{"name": "shopping.search_products", "template": "找: {{user_input}}"}"#).unwrap();
+        std::fs::write(dir.child("notes.txt"), "ignored").unwrap();
+
+        let mut registry = PromptRegistry::new();
+        assert_eq!(registry.load_overrides(dir.path()).unwrap(), 1);
+        assert_eq!(registry.render("shopping.search_products", &[("user_input", "耳机")]).unwrap(), "找: 耳机");
+
+        std::fs::write(dir.child("broken.json"), "{").unwrap();
+        assert!(registry.load_overrides(dir.path()).is_err());
+    }
+}
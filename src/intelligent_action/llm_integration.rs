@@ -3,10 +3,12 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use super::{Action, ActionResult, SmartTask};
+use crate::core::prompts::PromptRegistry;
 
 /// LLM集成模块
 pub struct LLMIntegration {
     client: LLMClient,
+    prompts: PromptRegistry,
 }
 
 /// LLM提供商
@@ -31,6 +33,7 @@ impl LLMIntegration {
         
         Ok(Self {
             client: LLMClient::new(provider),
+            prompts: PromptRegistry::from_env(),
         })
     }
     
@@ -69,22 +72,7 @@ impl LLMIntegration {
     
     /// 分析用户请求
     pub async fn analyze_request(&self, user_request: &str) -> Result<SmartTask> {
-        let prompt = format!(r#"
-分析用户请求并生成浏览器操作计划。
-
-用户请求: {}
-
-请生成一个包含以下字段的JSON对象:
-- intent: 用户意图类型 (search/navigate/extract/interact)
-- actions: 操作步骤数组，每个操作包含 type 和 parameters
-- expected_outcome: 预期结果描述
-
-示例操作类型:
-- Navigate: {{"type": "navigate", "url": "https://example.com"}}
-- Input: {{"type": "input", "selector": "input[name='q']", "text": "search text"}}
-- Click: {{"type": "click", "selector": "button[type='submit']"}}
-- Extract: {{"type": "extract", "selector": ".result"}}
-        "#, user_request);
+        let prompt = self.prompts.render("intent.analyze_request", &[("user_request", user_request)])?;
         
         let response = self.client.query(&prompt).await?;
         
@@ -169,10 +157,7 @@ impl LLMIntegration {
     /// 判断错误后是否继续
     pub async fn should_continue_after_error(&self, result: &ActionResult) -> Result<bool> {
         if let Some(error) = &result.error {
-            let prompt = format!(
-                "执行操作时遇到错误: {}\n是否应该继续执行后续步骤？(yes/no)",
-                error
-            );
+            let prompt = self.prompts.render("intent.continue_after_error", &[("error", error)])?;
             
             let response = self.client.query(&prompt).await?;
             Ok(response.to_lowercase().contains("yes"))